@@ -53,6 +53,27 @@ pub(crate) fn define() -> SettingGroup {
         true,
     );
 
+    settings.add_bool(
+        "enable_strength_reduction",
+        "Replace multiplications of induction variables in loops with additions.",
+        r#"
+            This adds an induction variable for each multiplication of a loop's induction
+            variable by a constant, which is advanced by an addition on each iteration.
+            Only effective when `opt_level` is `speed` or `speed_and_size`.
+        "#,
+        false,
+    );
+
+    settings.add_bool(
+        "enable_loop_unrolling",
+        "Unroll small innermost loops.",
+        r#"
+            This makes small loops execute several iterations before jumping back to their
+            header, at the cost of code size. Only effective when `opt_level` is `speed`.
+        "#,
+        false,
+    );
+
     settings.add_bool(
         "enable_verifier",
         "Run the Cranelift IR verifier at strategic times during compilation.",
@@ -13,15 +13,18 @@ use crate::alias_analysis::AliasAnalysis;
 use crate::dominator_tree::DominatorTree;
 use crate::egraph::EgraphPass;
 use crate::flowgraph::ControlFlowGraph;
+use crate::inline::{do_inlining, Inline};
 use crate::ir::Function;
 use crate::isa::TargetIsa;
 use crate::legalizer::simple_legalize;
 use crate::loop_analysis::LoopAnalysis;
+use crate::loop_unrolling::do_loop_unrolling;
 use crate::machinst::{CompiledCode, CompiledCodeStencil};
 use crate::nan_canonicalization::do_nan_canonicalization;
 use crate::remove_constant_phis::do_remove_constant_phis;
 use crate::result::{CodegenResult, CompileResult};
 use crate::settings::{FlagsOrIsa, OptLevel};
+use crate::strength_reduction::do_strength_reduction;
 use crate::trace;
 use crate::unreachable_code::eliminate_unreachable_code;
 use crate::verifier::{verify_context, VerifierErrors, VerifierResult};
@@ -175,6 +178,13 @@ impl Context {
         self.eliminate_unreachable_code(isa)?;
         self.remove_constant_phis(isa)?;
 
+        if opt_level != OptLevel::None && isa.flags().enable_strength_reduction() {
+            self.strength_reduce(isa)?;
+        }
+        if opt_level == OptLevel::Speed && isa.flags().enable_loop_unrolling() {
+            self.unroll_loops(isa)?;
+        }
+
         self.func.dfg.resolve_all_aliases();
 
         if opt_level != OptLevel::None {
@@ -345,6 +355,54 @@ impl Context {
         Ok(())
     }
 
+    /// Perform function inlining, asking `inliner` which calls to inline.
+    ///
+    /// Returns whether any call was inlined. This is not part of `optimize`
+    /// or `compile`, since only the embedder knows the bodies of other
+    /// functions; it is meant to be run before either of them.
+    pub fn inline(&mut self, isa: &dyn TargetIsa, inliner: impl Inline) -> CodegenResult<bool> {
+        let _tt = timing::inline();
+        let inlined = do_inlining(&mut self.func, isa, inliner);
+        if inlined {
+            trace!("After inlining:\n{}", self.func.display());
+            self.verify_if(isa)?;
+        }
+        Ok(inlined)
+    }
+
+    /// Replace multiplications of induction variables in loops with additions.
+    pub fn strength_reduce<'a, FOI>(&mut self, fisa: FOI) -> CodegenResult<()>
+    where
+        FOI: Into<FlagsOrIsa<'a>>,
+    {
+        let _tt = timing::strength_reduction();
+        self.compute_loop_analysis();
+        if do_strength_reduction(&mut self.func, &self.cfg, &self.loop_analysis) {
+            trace!("After strength reduction:\n{}", self.func.display());
+            self.verify_if(fisa)?;
+        }
+        Ok(())
+    }
+
+    /// Unroll small innermost loops.
+    pub fn unroll_loops<'a, FOI>(&mut self, fisa: FOI) -> CodegenResult<()>
+    where
+        FOI: Into<FlagsOrIsa<'a>>,
+    {
+        let _tt = timing::loop_unrolling();
+        self.compute_loop_analysis();
+        if do_loop_unrolling(
+            &mut self.func,
+            &mut self.cfg,
+            &mut self.domtree,
+            &mut self.loop_analysis,
+        ) {
+            trace!("After loop unrolling:\n{}", self.func.display());
+            self.verify_if(fisa)?;
+        }
+        Ok(())
+    }
+
     /// Run optimizations via the egraph infrastructure.
     pub fn egraph_pass<'a, FOI>(
         &mut self,
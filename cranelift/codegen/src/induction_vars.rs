@@ -0,0 +1,172 @@
+//! Induction variable analysis.
+//!
+//! A basic induction variable is a parameter of a loop header which every back
+//! edge of the loop sets to the parameter plus a constant, such as `v1` in:
+//!
+//! ```text
+//! block1(v1: i32):
+//!     v2 = iconst.i32 4
+//!     v3 = iadd v1, v2
+//!     ...
+//!     brif v4, block1(v3), block2
+//! ```
+//!
+//! The value the variable starts with may differ between the edges entering
+//! the loop; only its step has to be known. All arithmetic wraps, so
+//! the step is stored truncated to the width of the variable's type.
+
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::{Block, Function, InstructionData, Opcode, Type, Value};
+use crate::loop_analysis::{Loop, LoopAnalysis};
+use alloc::vec::Vec;
+
+/// A basic induction variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InductionVar {
+    /// The loop whose iterations this variable counts.
+    pub lp: Loop,
+    /// The loop header's parameter holding the variable's value in the current
+    /// iteration.
+    pub param: Value,
+    /// The index of `param` in the header's parameters.
+    pub index: usize,
+    /// The amount added to the variable on each back edge.
+    pub step: u64,
+}
+
+/// The basic induction variables of a function's loops.
+pub struct InductionVars {
+    vars: Vec<InductionVar>,
+}
+
+impl InductionVars {
+    /// Find the basic induction variables of all loops in `func`.
+    pub fn compute(func: &Function, cfg: &ControlFlowGraph, loop_analysis: &LoopAnalysis) -> Self {
+        let mut vars = Vec::new();
+        for lp in loop_analysis.loops() {
+            let header = loop_analysis.loop_header(lp);
+            for (index, &param) in func.dfg.block_params(header).iter().enumerate() {
+                let ty = func.dfg.value_type(param);
+                if !is_scalar_int(ty) {
+                    continue;
+                }
+                let step = back_edge_arg(func, cfg, loop_analysis, lp, index)
+                    .and_then(|update| step_of(func, param, update))
+                    .map(|step| truncate(ty, step));
+                if let Some(step) = step.filter(|&step| step != 0) {
+                    vars.push(InductionVar {
+                        lp,
+                        param,
+                        index,
+                        step,
+                    });
+                }
+            }
+        }
+        Self { vars }
+    }
+
+    /// The induction variable whose value in the current iteration is
+    /// `value`, if any.
+    pub fn get(&self, func: &Function, value: Value) -> Option<&InductionVar> {
+        let value = func.dfg.resolve_aliases(value);
+        self.vars.iter().find(|var| var.param == value)
+    }
+
+    /// All induction variables found.
+    pub fn iter(&self) -> impl Iterator<Item = &InductionVar> {
+        self.vars.iter()
+    }
+}
+
+/// The value passed for the `index`th parameter of `lp`'s header on all of
+/// its back edges, if they all pass the same one.
+fn back_edge_arg(
+    func: &Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    index: usize,
+) -> Option<Value> {
+    let header = loop_analysis.loop_header(lp);
+    let pool = &func.dfg.value_lists;
+    let mut arg = None;
+    for pred in cfg.pred_iter(header) {
+        if !loop_analysis.is_in_loop(pred.block, lp) {
+            continue;
+        }
+        for dest in func.dfg.insts[pred.inst].branch_destination(&func.dfg.jump_tables) {
+            if dest.block(pool) != header {
+                continue;
+            }
+            let value = func.dfg.resolve_aliases(dest.args_slice(pool)[index]);
+            if *arg.get_or_insert(value) != value {
+                return None;
+            }
+        }
+    }
+    arg
+}
+
+/// The constant `update` adds to `param`, if it is defined that way.
+fn step_of(func: &Function, param: Value, update: Value) -> Option<u64> {
+    let inst = func.dfg.value_def(update).inst()?;
+    let is_param = |value| func.dfg.resolve_aliases(value) == param;
+    match func.dfg.insts[inst] {
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args: [a, b],
+        } => {
+            if is_param(a) {
+                iconst_value(func, b)
+            } else if is_param(b) {
+                iconst_value(func, a)
+            } else {
+                None
+            }
+        }
+        InstructionData::Binary {
+            opcode: Opcode::Isub,
+            args: [a, b],
+        } if is_param(a) => iconst_value(func, b).map(u64::wrapping_neg),
+        InstructionData::BinaryImm64 {
+            opcode: Opcode::IaddImm,
+            arg,
+            imm,
+        } if is_param(arg) => Some(imm.bits() as u64),
+        _ => None,
+    }
+}
+
+/// The constant `value` is defined as, if it's an `iconst`.
+pub(crate) fn iconst_value(func: &Function, value: Value) -> Option<u64> {
+    let inst = func.dfg.value_def(func.dfg.resolve_aliases(value)).inst()?;
+    match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => Some(imm.bits() as u64),
+        _ => None,
+    }
+}
+
+/// Whether induction variables of type `ty` are supported.
+pub(crate) fn is_scalar_int(ty: Type) -> bool {
+    ty.is_int() && ty.bits() <= 64
+}
+
+/// Truncate `value` to the width of `ty`, which must satisfy `is_scalar_int`.
+pub(crate) fn truncate(ty: Type, value: u64) -> u64 {
+    match ty.bits() {
+        64 => value,
+        bits => value & ((1 << bits) - 1),
+    }
+}
+
+/// The blocks of `lp` in layout order.
+pub(crate) fn loop_blocks(func: &Function, loop_analysis: &LoopAnalysis, lp: Loop) -> Vec<Block> {
+    func.layout
+        .blocks()
+        .filter(|&block| loop_analysis.is_in_loop(block, lp))
+        .collect()
+}
@@ -0,0 +1,661 @@
+//! Function inlining.
+//!
+//! This pass replaces direct `call` instructions with a copy of the callee's
+//! body. Cranelift compiles one function at a time and knows nothing about
+//! other functions' bodies, so the embedder drives the pass through the
+//! [`Inline`] trait: for every call it is asked whether to inline it and, if
+//! so, to supply the callee's [`Function`].
+//!
+//! Inlining a call splits the calling block in two at the call. The call is
+//! replaced with a jump to a copy of the callee's entry block, and every
+//! `return` in the copied body becomes a jump to the second half of the
+//! calling block, whose parameters take the place of the call's results. Calls
+//! in the inlined body are offered to the [`Inline`] implementation in turn,
+//! so it is responsible for bounding recursive inlining, for example with an
+//! [`InlineBudget`].
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::dominator_tree::DominatorTree;
+use crate::entity::{packed_option::PackedOption, SecondaryMap};
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::{
+    self, AbiParam, ArgumentPurpose, Block, BlockCall, ExtFuncData, ExternalName, FuncRef,
+    Function, GlobalValue, GlobalValueData, Inst, InstBuilder, InstructionData, JumpTableData,
+    Opcode, SigRef, SourceLoc, StackSlot, Value, ValueList,
+};
+use crate::isa::TargetIsa;
+use crate::trace;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use smallvec::SmallVec;
+
+/// A callback deciding which calls get inlined.
+pub trait Inline {
+    /// Decide whether to inline the call `call_inst` in `caller`.
+    ///
+    /// `callee` is the called function's reference in `caller` and
+    /// `call_args` are the arguments passed to it. To inline the call, return
+    /// [`InlineCommand::Inline`] with the callee's body, whose signature must
+    /// match the call's.
+    fn inline(
+        &mut self,
+        caller: &Function,
+        call_inst: Inst,
+        call_opcode: Opcode,
+        callee: FuncRef,
+        call_args: &[Value],
+    ) -> InlineCommand<'_>;
+}
+
+impl<T: Inline + ?Sized> Inline for &mut T {
+    fn inline(
+        &mut self,
+        caller: &Function,
+        call_inst: Inst,
+        call_opcode: Opcode,
+        callee: FuncRef,
+        call_args: &[Value],
+    ) -> InlineCommand<'_> {
+        (**self).inline(caller, call_inst, call_opcode, callee, call_args)
+    }
+}
+
+/// What to do with a call, as decided by [`Inline::inline`].
+pub enum InlineCommand<'a> {
+    /// Leave the call as it is.
+    KeepCall,
+    /// Replace the call with the given body of the callee.
+    Inline(Cow<'a, Function>),
+}
+
+/// A size budget for inlining.
+///
+/// Inlining trades code size for call overhead, so [`Inline`]
+/// implementations usually only inline small callees, and limit how much the
+/// caller may grow in total. The latter also bounds inlining of recursive
+/// calls.
+#[derive(Clone, Debug)]
+pub struct InlineBudget {
+    max_callee_size: usize,
+    remaining: usize,
+}
+
+impl InlineBudget {
+    /// Create a budget allowing callees of at most `max_callee_size`
+    /// instructions to be inlined, until the caller has grown by
+    /// `max_growth` instructions in total.
+    pub fn new(max_callee_size: usize, max_growth: usize) -> Self {
+        Self {
+            max_callee_size,
+            remaining: max_growth,
+        }
+    }
+
+    /// Check whether `callee` fits in the budget and if so, deduct its size
+    /// from it.
+    pub fn try_spend(&mut self, callee: &Function) -> bool {
+        let size = function_size(callee);
+        if size > self.max_callee_size || size > self.remaining {
+            return false;
+        }
+        self.remaining -= size;
+        true
+    }
+
+    /// The number of instructions the caller may still grow by.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+/// The number of instructions in `func`'s layout.
+fn function_size(func: &Function) -> usize {
+    func.layout
+        .blocks()
+        .map(|block| func.layout.block_insts(block).count())
+        .sum()
+}
+
+/// Perform inlining on `func`, returning whether any call was inlined.
+pub(crate) fn do_inlining(
+    func: &mut Function,
+    isa: &dyn TargetIsa,
+    mut inliner: impl Inline,
+) -> bool {
+    let mut inlined_any = false;
+
+    let mut next_block = func.layout.entry_block();
+    while let Some(block) = next_block {
+        let mut next_inst = func.layout.first_inst(block);
+        while let Some(inst) = next_inst {
+            next_inst = func.layout.next_inst(inst);
+
+            let (callee, call_args) = match func.dfg.insts[inst] {
+                InstructionData::Call {
+                    opcode: Opcode::Call,
+                    args,
+                    func_ref,
+                } => {
+                    let args: SmallVec<[Value; 8]> = args
+                        .as_slice(&func.dfg.value_lists)
+                        .iter()
+                        .copied()
+                        .collect();
+                    (func_ref, args)
+                }
+                _ => continue,
+            };
+
+            let callee_func = match inliner.inline(func, inst, Opcode::Call, callee, &call_args) {
+                InlineCommand::KeepCall => continue,
+                InlineCommand::Inline(callee_func) => callee_func,
+            };
+            if !can_inline(func, callee, &callee_func) {
+                continue;
+            }
+
+            trace!(
+                "inlining {} into {}",
+                func.dfg.display_inst(inst),
+                func.name
+            );
+            inline_call(func, isa, inst, &call_args, &callee_func);
+            inlined_any = true;
+
+            // The call has been replaced by a jump terminating `block`, and
+            // the instructions following it moved to the continuation block.
+            // The inlined blocks come next in the layout, so calls in them
+            // are visited too.
+            next_inst = None;
+        }
+        next_block = func.layout.next_block(block);
+    }
+
+    inlined_any
+}
+
+/// Check that `callee` can be inlined in place of a call to `callee_ref` in
+/// `caller`.
+fn can_inline(caller: &Function, callee_ref: FuncRef, callee: &Function) -> bool {
+    let sig = &caller.dfg.signatures[caller.dfg.ext_funcs[callee_ref].signature];
+    let types_match = |a: &[AbiParam], b: &[AbiParam]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.value_type == b.value_type)
+    };
+    if !types_match(&sig.params, &callee.signature.params)
+        || !types_match(&sig.returns, &callee.signature.returns)
+    {
+        trace!("not inlining {}: signature mismatch", callee.name);
+        return false;
+    }
+
+    if callee.layout.entry_block().is_none() {
+        trace!("not inlining {}: no body", callee.name);
+        return false;
+    }
+
+    // Dynamic vector types are tied to the function they are declared in,
+    // and proof-carrying code facts can't be carried across functions yet.
+    if !callee.dfg.dynamic_types.is_empty()
+        || !callee.dynamic_stack_slots.is_empty()
+        || !callee.memory_types.is_empty()
+    {
+        trace!("not inlining {}: unsupported entities", callee.name);
+        return false;
+    }
+
+    for gv in callee.global_values.values() {
+        match gv {
+            GlobalValueData::VMContext
+                if callee
+                    .signature
+                    .special_param_index(ArgumentPurpose::VMContext)
+                    .is_none() =>
+            {
+                return false;
+            }
+            GlobalValueData::DynScaleTargetConst { .. } => return false,
+            _ => {}
+        }
+    }
+
+    // Instructions inspecting the current frame would observe the caller's
+    // frame once inlined.
+    let inspects_frame = callee.layout.blocks().any(|block| {
+        callee.layout.block_insts(block).any(|inst| {
+            matches!(
+                callee.dfg.insts[inst].opcode(),
+                Opcode::GetFramePointer | Opcode::GetStackPointer | Opcode::GetReturnAddress
+            )
+        })
+    });
+    if inspects_frame {
+        trace!("not inlining {}: inspects its frame", callee.name);
+        return false;
+    }
+
+    true
+}
+
+/// Replace `call_inst` in `func` with the body of `callee`.
+fn inline_call(
+    func: &mut Function,
+    isa: &dyn TargetIsa,
+    call_inst: Inst,
+    call_args: &[Value],
+    callee: &Function,
+) {
+    // Move everything following the call to a continuation block, whose
+    // parameters take the place of the call's results.
+    let cont = func.dfg.make_block();
+    let after_call = func
+        .layout
+        .next_inst(call_inst)
+        .expect("calls are not terminators");
+    func.layout.split_block(cont, after_call);
+
+    let results: SmallVec<[Value; 4]> = func.dfg.inst_results(call_inst).iter().copied().collect();
+    func.dfg.clear_results(call_inst);
+    for result in results {
+        let ty = func.dfg.value_type(result);
+        let param = func.dfg.append_block_param(cont, ty);
+        func.dfg.change_to_alias(result, param);
+    }
+
+    let mut inliner = Inliner {
+        callee,
+        isa,
+        call_args,
+        cont,
+        srcloc: func.srcloc(call_inst),
+        blocks: SecondaryMap::new(),
+        values: SecondaryMap::new(),
+        func_refs: SecondaryMap::new(),
+        sig_refs: SecondaryMap::new(),
+        stack_slots: SecondaryMap::new(),
+        symbols: SecondaryMap::new(),
+    };
+
+    // The callee's reachable blocks are laid out in their original order,
+    // and created upfront along with their parameters since branches may
+    // refer to later blocks.
+    let cfg = ControlFlowGraph::with_function(callee);
+    let domtree = DominatorTree::with_function(callee, &cfg);
+    for block in callee.layout.blocks() {
+        if !domtree.is_reachable(block) {
+            continue;
+        }
+        let new_block = func.dfg.make_block();
+        func.layout.insert_block(new_block, cont);
+        if callee.layout.is_cold(block) {
+            func.layout.set_cold(new_block);
+        }
+        for &param in callee.dfg.block_params(block) {
+            let ty = callee.dfg.value_type(param);
+            inliner.values[param] = func.dfg.append_block_param(new_block, ty).into();
+        }
+        inliner.blocks[block] = new_block.into();
+    }
+
+    let entry = inliner.blocks[callee.layout.entry_block().unwrap()].unwrap();
+    func.dfg.replace(call_inst).jump(entry, call_args);
+
+    // Visiting blocks in reverse postorder guarantees that instruction
+    // results are copied before they are used.
+    for &block in domtree.cfg_postorder().iter().rev() {
+        let new_block = inliner.blocks[block].unwrap();
+        for inst in callee.layout.block_insts(block) {
+            inliner.copy_inst(func, inst, new_block);
+        }
+    }
+}
+
+/// State for copying a callee's body into a caller.
+///
+/// The maps translate the callee's entities to the caller's.
+struct Inliner<'a> {
+    callee: &'a Function,
+    isa: &'a dyn TargetIsa,
+    call_args: &'a [Value],
+    /// The block the callee returns to.
+    cont: Block,
+    /// The call's source location, given to instructions which have none.
+    srcloc: SourceLoc,
+    blocks: SecondaryMap<Block, PackedOption<Block>>,
+    values: SecondaryMap<Value, PackedOption<Value>>,
+    func_refs: SecondaryMap<FuncRef, PackedOption<FuncRef>>,
+    sig_refs: SecondaryMap<SigRef, PackedOption<SigRef>>,
+    stack_slots: SecondaryMap<StackSlot, PackedOption<StackSlot>>,
+    symbols: SecondaryMap<GlobalValue, PackedOption<GlobalValue>>,
+}
+
+impl<'a> Inliner<'a> {
+    /// Copy the callee's `inst` to the end of `block`.
+    fn copy_inst(&mut self, func: &mut Function, inst: Inst, block: Block) {
+        let callee = self.callee;
+        let data = callee.dfg.insts[inst];
+        match data {
+            InstructionData::MultiAry {
+                opcode: Opcode::Return,
+                args,
+            } => {
+                let args: SmallVec<[Value; 8]> = args
+                    .as_slice(&callee.dfg.value_lists)
+                    .iter()
+                    .map(|&arg| self.value(arg))
+                    .collect();
+                self.cursor(func, block).ins().jump(self.cont, &args);
+                return;
+            }
+            InstructionData::UnaryGlobalValue {
+                opcode: Opcode::GlobalValue,
+                global_value,
+            } => {
+                let ty = callee.dfg.ctrl_typevar(inst);
+                let value = self.global_value(func, block, global_value, ty);
+                let result = callee.dfg.first_result(inst);
+                self.values[result] = value.into();
+                return;
+            }
+            _ => {}
+        }
+
+        let mut new_data = self.map_inst_data(func, data);
+
+        // Tail calls return to the caller of the function containing them,
+        // so once inlined they become regular calls.
+        let is_tail_call = match &mut new_data {
+            InstructionData::Call { opcode, .. } if *opcode == Opcode::ReturnCall => {
+                *opcode = Opcode::Call;
+                true
+            }
+            InstructionData::CallIndirect { opcode, .. }
+                if *opcode == Opcode::ReturnCallIndirect =>
+            {
+                *opcode = Opcode::CallIndirect;
+                true
+            }
+            _ => false,
+        };
+
+        let new_inst = func.dfg.make_inst(new_data);
+        func.layout.append_inst(new_inst, block);
+        let srcloc = match callee.srcloc(inst) {
+            srcloc if srcloc.is_default() => self.srcloc,
+            srcloc => srcloc,
+        };
+        if !srcloc.is_default() {
+            func.set_srcloc(new_inst, srcloc);
+        }
+
+        func.dfg
+            .make_inst_results(new_inst, callee.dfg.ctrl_typevar(inst));
+        if is_tail_call {
+            let results: SmallVec<[Value; 4]> =
+                func.dfg.inst_results(new_inst).iter().copied().collect();
+            self.cursor(func, block).ins().jump(self.cont, &results);
+            return;
+        }
+        for (&old, &new) in callee
+            .dfg
+            .inst_results(inst)
+            .iter()
+            .zip(func.dfg.inst_results(new_inst))
+        {
+            self.values[old] = new.into();
+        }
+
+        if let Some(entries) = callee.dfg.user_stack_map_entries(inst) {
+            for entry in entries {
+                let mut entry = entry.clone();
+                entry.slot = self.stack_slot(func, entry.slot);
+                func.dfg.append_user_stack_map_entry(new_inst, entry);
+            }
+        }
+    }
+
+    /// Translate the operands of an instruction from the callee to the
+    /// caller.
+    fn map_inst_data(&mut self, func: &mut Function, data: InstructionData) -> InstructionData {
+        let callee = self.callee;
+        match data {
+            InstructionData::MultiAry { opcode, args } => InstructionData::MultiAry {
+                opcode,
+                args: self.value_list(func, args),
+            },
+            InstructionData::Call {
+                opcode,
+                args,
+                func_ref,
+            } => InstructionData::Call {
+                opcode,
+                args: self.value_list(func, args),
+                func_ref: self.func_ref(func, func_ref),
+            },
+            InstructionData::CallIndirect {
+                opcode,
+                args,
+                sig_ref,
+            } => InstructionData::CallIndirect {
+                opcode,
+                args: self.value_list(func, args),
+                sig_ref: self.sig_ref(func, sig_ref),
+            },
+            InstructionData::FuncAddr { opcode, func_ref } => InstructionData::FuncAddr {
+                opcode,
+                func_ref: self.func_ref(func, func_ref),
+            },
+            InstructionData::Jump {
+                opcode,
+                destination,
+            } => InstructionData::Jump {
+                opcode,
+                destination: self.block_call(func, destination),
+            },
+            InstructionData::Brif {
+                opcode,
+                arg,
+                blocks: [then_block, else_block],
+            } => InstructionData::Brif {
+                opcode,
+                arg: self.value(arg),
+                blocks: [
+                    self.block_call(func, then_block),
+                    self.block_call(func, else_block),
+                ],
+            },
+            InstructionData::BranchTable { opcode, arg, table } => {
+                let table_data = &callee.dfg.jump_tables[table];
+                let default = self.block_call(func, table_data.default_block());
+                let branches: Vec<BlockCall> = table_data
+                    .as_slice()
+                    .iter()
+                    .map(|&branch| self.block_call(func, branch))
+                    .collect();
+                InstructionData::BranchTable {
+                    opcode,
+                    arg: self.value(arg),
+                    table: func.create_jump_table(JumpTableData::new(default, &branches)),
+                }
+            }
+            mut data => {
+                // The remaining formats keep their value operands inline, so
+                // the callee's value lists aren't involved here.
+                let dfg = &mut func.dfg;
+                data.map_values(&mut dfg.value_lists, &mut dfg.jump_tables, |value| {
+                    self.value(value)
+                });
+                match &mut data {
+                    InstructionData::StackLoad { stack_slot, .. }
+                    | InstructionData::StackStore { stack_slot, .. } => {
+                        *stack_slot = self.stack_slot(func, *stack_slot);
+                    }
+                    InstructionData::UnaryConst {
+                        constant_handle, ..
+                    } => {
+                        let constant = callee.dfg.constants.get(*constant_handle).clone();
+                        *constant_handle = func.dfg.constants.insert(constant);
+                    }
+                    InstructionData::Shuffle { imm, .. } => {
+                        let mask = callee.dfg.immediates[*imm].clone();
+                        *imm = func.dfg.immediates.push(mask);
+                    }
+                    InstructionData::UnaryGlobalValue { global_value, .. } => {
+                        // `symbol_value` and `tls_value`.
+                        *global_value = self.symbol(func, *global_value);
+                    }
+                    _ => {}
+                }
+                data
+            }
+        }
+    }
+
+    /// A cursor appending instructions to `block` at the call's source
+    /// location.
+    fn cursor<'f>(&self, func: &'f mut Function, block: Block) -> FuncCursor<'f> {
+        FuncCursor::new(func)
+            .at_bottom(block)
+            .with_srcloc(self.srcloc)
+    }
+
+    fn value(&self, value: Value) -> Value {
+        let value = self.callee.dfg.resolve_aliases(value);
+        self.values[value].expect("values are copied before their uses")
+    }
+
+    fn value_list(&self, func: &mut Function, list: ValueList) -> ValueList {
+        let values: SmallVec<[Value; 8]> = list
+            .as_slice(&self.callee.dfg.value_lists)
+            .iter()
+            .map(|&value| self.value(value))
+            .collect();
+        ValueList::from_slice(&values, &mut func.dfg.value_lists)
+    }
+
+    fn block_call(&self, func: &mut Function, call: BlockCall) -> BlockCall {
+        let pool = &self.callee.dfg.value_lists;
+        let block = self.blocks[call.block(pool)].unwrap();
+        let args: SmallVec<[Value; 8]> = call
+            .args_slice(pool)
+            .iter()
+            .map(|&arg| self.value(arg))
+            .collect();
+        BlockCall::new(block, &args, &mut func.dfg.value_lists)
+    }
+
+    fn func_ref(&mut self, func: &mut Function, func_ref: FuncRef) -> FuncRef {
+        if let Some(new) = self.func_refs[func_ref].expand() {
+            return new;
+        }
+        let data = &self.callee.dfg.ext_funcs[func_ref];
+        let new_data = ExtFuncData {
+            name: self.external_name(func, &data.name),
+            signature: self.sig_ref(func, data.signature),
+            colocated: data.colocated,
+        };
+        let new = func.import_function(new_data);
+        self.func_refs[func_ref] = new.into();
+        new
+    }
+
+    fn sig_ref(&mut self, func: &mut Function, sig_ref: SigRef) -> SigRef {
+        if let Some(new) = self.sig_refs[sig_ref].expand() {
+            return new;
+        }
+        let new = func.import_signature(self.callee.dfg.signatures[sig_ref].clone());
+        self.sig_refs[sig_ref] = new.into();
+        new
+    }
+
+    fn stack_slot(&mut self, func: &mut Function, slot: StackSlot) -> StackSlot {
+        if let Some(new) = self.stack_slots[slot].expand() {
+            return new;
+        }
+        let new = func.create_sized_stack_slot(self.callee.sized_stack_slots[slot].clone());
+        self.stack_slots[slot] = new.into();
+        new
+    }
+
+    /// Names in user-defined symbol tables refer to the callee's table, so
+    /// they need to be declared in the caller's.
+    fn external_name(&self, func: &mut Function, name: &ExternalName) -> ExternalName {
+        match name {
+            ExternalName::User(name_ref) => {
+                let name = self.callee.params.user_named_funcs()[*name_ref].clone();
+                ExternalName::User(func.declare_imported_user_function(name))
+            }
+            name => name.clone(),
+        }
+    }
+
+    /// Copy the symbol global value `gv` to the caller.
+    fn symbol(&mut self, func: &mut Function, gv: GlobalValue) -> GlobalValue {
+        if let Some(new) = self.symbols[gv].expand() {
+            return new;
+        }
+        let mut data = self.callee.global_values[gv].clone();
+        if let GlobalValueData::Symbol { name, .. } = &mut data {
+            *name = self.external_name(func, name);
+        }
+        let new = func.create_global_value(data);
+        self.symbols[gv] = new.into();
+        new
+    }
+
+    /// Compute the value of the callee's global value `gv` at the end of
+    /// `block`.
+    ///
+    /// Global values are expressions over the callee's `vmctx` parameter,
+    /// which need not be the caller's, so they are expanded the same way the
+    /// legalizer expands them rather than being copied.
+    fn global_value(
+        &mut self,
+        func: &mut Function,
+        block: Block,
+        gv: GlobalValue,
+        ty: ir::Type,
+    ) -> Value {
+        let ptr_ty = self.isa.pointer_type();
+        match self.callee.global_values[gv] {
+            GlobalValueData::VMContext => {
+                let index = self
+                    .callee
+                    .signature
+                    .special_param_index(ArgumentPurpose::VMContext)
+                    .expect("checked by `can_inline`");
+                self.call_args[index]
+            }
+            GlobalValueData::IAddImm {
+                base,
+                offset,
+                global_type,
+            } => {
+                let base = self.global_value(func, block, base, global_type);
+                self.cursor(func, block).ins().iadd_imm(base, offset)
+            }
+            GlobalValueData::Load {
+                base,
+                offset,
+                global_type,
+                flags,
+            } => {
+                let base = self.global_value(func, block, base, ptr_ty);
+                self.cursor(func, block)
+                    .ins()
+                    .load(global_type, flags, base, offset)
+            }
+            GlobalValueData::Symbol { tls, .. } => {
+                let symbol = self.symbol(func, gv);
+                let mut pos = self.cursor(func, block);
+                if tls {
+                    pos.ins().tls_value(ty, symbol)
+                } else {
+                    pos.ins().symbol_value(ty, symbol)
+                }
+            }
+            GlobalValueData::DynScaleTargetConst { .. } => {
+                unreachable!("checked by `can_inline`")
+            }
+        }
+    }
+}
@@ -1119,8 +1119,11 @@ impl ABIMachineSpec for AArch64MachineDeps {
         }
     }
 
-    fn get_regs_clobbered_by_call(_call_conv: isa::CallConv) -> PRegSet {
-        DEFAULT_AAPCS_CLOBBERS
+    fn get_regs_clobbered_by_call(call_conv: isa::CallConv) -> PRegSet {
+        match call_conv {
+            isa::CallConv::Winch => ALL_CLOBBERS,
+            _ => DEFAULT_AAPCS_CLOBBERS,
+        }
     }
 
     fn get_ext_mode(
@@ -1436,6 +1439,24 @@ const fn default_aapcs_clobbers() -> PRegSet {
 
 const DEFAULT_AAPCS_CLOBBERS: PRegSet = default_aapcs_clobbers();
 
+/// Winch has no callee-saved registers, so calls to Winch code clobber the
+/// registers that are callee-saved in AAPCS64 as well.
+const fn all_clobbers() -> PRegSet {
+    default_aapcs_clobbers()
+        .with(xreg_preg(19))
+        .with(xreg_preg(20))
+        .with(xreg_preg(21))
+        .with(xreg_preg(22))
+        .with(xreg_preg(23))
+        .with(xreg_preg(24))
+        .with(xreg_preg(25))
+        .with(xreg_preg(26))
+        .with(xreg_preg(27))
+        .with(xreg_preg(28))
+}
+
+const ALL_CLOBBERS: PRegSet = all_clobbers();
+
 fn create_reg_env(enable_pinned_reg: bool) -> MachineEnv {
     fn preg(r: Reg) -> PReg {
         r.to_real_reg().unwrap().into()
@@ -30,6 +30,19 @@ where
     _phantom: PhantomData<P>,
 }
 
+impl<P> PulleyMachineDeps<P>
+where
+    P: PulleyTargetKind,
+{
+    /// Generates `dst = src1 + src2` at the width of a pointer.
+    fn gen_pointer_add(dst: WritableXReg, src1: XReg, src2: XReg) -> InstAndKind<P> {
+        match P::pointer_width() {
+            super::PointerWidth::PointerWidth32 => Inst::Xadd32 { dst, src1, src2 }.into(),
+            super::PointerWidth::PointerWidth64 => Inst::Xadd64 { dst, src1, src2 }.into(),
+        }
+    }
+}
+
 impl<P> ABIMachineSpec for PulleyMachineDeps<P>
 where
     P: PulleyTargetKind,
@@ -172,14 +185,28 @@ where
     }
 
     fn gen_extend(
-        _to_reg: Writable<Reg>,
-        _from_reg: Reg,
-        _signed: bool,
+        to_reg: Writable<Reg>,
+        from_reg: Reg,
+        signed: bool,
         from_bits: u8,
         to_bits: u8,
     ) -> Self::I {
         assert!(from_bits < to_bits);
-        todo!()
+        // Integer types narrower than a register are 8, 16 or 32 bits wide.
+        let op = match (signed, from_bits) {
+            (false, ..=8) => XUnaryOp::Zext8,
+            (false, 9..=16) => XUnaryOp::Zext16,
+            (false, _) => XUnaryOp::Zext32,
+            (true, ..=8) => XUnaryOp::Sext8,
+            (true, 9..=16) => XUnaryOp::Sext16,
+            (true, _) => XUnaryOp::Sext32,
+        };
+        Inst::XUnary {
+            op,
+            dst: to_reg.try_into().unwrap(),
+            src: from_reg.try_into().unwrap(),
+        }
+        .into()
     }
 
     fn get_ext_mode(
@@ -209,20 +236,22 @@ where
     ) -> SmallInstVec<Self::I> {
         let dst = into_reg.try_into().unwrap();
         let imm = imm as i32;
+        // `from_reg` may be the same register as `into_reg` (e.g. the stack
+        // limit register), so the immediate goes into the second scratch
+        // register rather than clobbering the source.
+        let scratch2 = WritableXReg::try_from(writable_spilltmp2_reg()).unwrap();
+        assert_ne!(scratch2.to_reg().to_reg(), from_reg);
         smallvec![
-            Inst::Xconst32 { dst, imm }.into(),
-            Inst::Xadd32 {
-                dst,
-                src1: from_reg.try_into().unwrap(),
-                src2: dst.to_reg(),
-            }
-            .into()
+            Inst::Xconst32 { dst: scratch2, imm }.into(),
+            Self::gen_pointer_add(dst, from_reg.try_into().unwrap(), scratch2.to_reg()),
         ]
     }
 
     fn gen_stack_lower_bound_trap(limit_reg: Reg) -> SmallInstVec<Self::I> {
+        // Trap if the stack pointer is below the limit, i.e. if
+        // `limit > sp`.
         smallvec![Inst::TrapIf {
-            cond: ir::condcodes::IntCC::UnsignedLessThan,
+            cond: ir::condcodes::IntCC::UnsignedGreaterThan,
             size: match P::pointer_width() {
                 super::PointerWidth::PointerWidth32 => OperandSize::Size32,
                 super::PointerWidth::PointerWidth64 => OperandSize::Size64,
@@ -249,8 +278,11 @@ where
         Inst::gen_load(into_reg, mem, ty, MemFlags::trusted()).into()
     }
 
-    fn gen_store_base_offset(_base: Reg, _offset: i32, _from_reg: Reg, _ty: Type) -> Self::I {
-        todo!()
+    fn gen_store_base_offset(base: Reg, offset: i32, from_reg: Reg, ty: Type) -> Self::I {
+        let offset = i64::from(offset);
+        let base = XReg::try_from(base).unwrap();
+        let mem = Amode::RegOffset { base, offset };
+        Inst::gen_store(mem, from_reg, ty, MemFlags::trusted()).into()
     }
 
     fn gen_sp_reg_adjust(amount: i32) -> SmallInstVec<Self::I> {
@@ -270,12 +302,11 @@ where
 
         smallvec![
             imm,
-            Inst::Xadd32 {
-                dst: WritableXReg::try_from(writable_stack_reg()).unwrap(),
-                src1: XReg::new(stack_reg()).unwrap(),
-                src2: temp.to_reg(),
-            }
-            .into()
+            Self::gen_pointer_add(
+                WritableXReg::try_from(writable_stack_reg()).unwrap(),
+                XReg::new(stack_reg()).unwrap(),
+                temp.to_reg(),
+            ),
         ]
     }
 
@@ -382,7 +413,8 @@ where
     }
 
     fn gen_probestack(_insts: &mut SmallInstVec<Self::I>, _frame_size: u32) {
-        todo!()
+        // The interpreter's stack has no guard pages to probe; stack overflow
+        // is detected by the explicit stack limit check instead.
     }
 
     fn gen_clobber_save(
@@ -541,36 +573,31 @@ where
         insts
     }
 
-    fn gen_call(dest: &CallDest, tmp: Writable<Reg>, info: CallInfo<()>) -> SmallVec<[Self::I; 2]> {
-        if info.callee_conv == isa::CallConv::Tail || info.callee_conv == isa::CallConv::Fast {
-            match &dest {
-                &CallDest::ExtName(ref name, RelocDistance::Near) => smallvec![Inst::Call {
-                    info: Box::new(info.map(|()| name.clone()))
-                }
-                .into()],
-                &CallDest::ExtName(ref name, RelocDistance::Far) => smallvec![
-                    Inst::LoadExtName {
-                        dst: WritableXReg::try_from(tmp).unwrap(),
-                        name: Box::new(name.clone()),
-                        offset: 0,
-                    }
-                    .into(),
-                    Inst::IndirectCall {
-                        info: Box::new(info.map(|()| XReg::new(tmp.to_reg()).unwrap()))
-                    }
-                    .into(),
-                ],
-                &CallDest::Reg(reg) => smallvec![Inst::IndirectCall {
-                    info: Box::new(info.map(|()| XReg::new(*reg).unwrap()))
-                }
-                .into()],
+    fn gen_call(
+        dest: &CallDest,
+        _tmp: Writable<Reg>,
+        info: CallInfo<()>,
+    ) -> SmallVec<[Self::I; 2]> {
+        match &dest {
+            // "near" calls are pulley->pulley calls so they use a normal "call"
+            // opcode.
+            &CallDest::ExtName(ref name, RelocDistance::Near) => smallvec![Inst::Call {
+                info: Box::new(info.map(|()| name.clone()))
             }
-        } else {
-            todo!(
-                "host calls? callee_conv = {:?}; caller_conv = {:?}",
-                info.callee_conv,
-                info.caller_conv,
-            )
+            .into()],
+
+            // "far" calls are pulley->host calls so they use a different opcode
+            // which is lowered with a special relocation in the backend.
+            &CallDest::ExtName(ref name, RelocDistance::Far) => smallvec![Inst::IndirectCallHost {
+                info: Box::new(info.map(|()| name.clone()))
+            }
+            .into()],
+
+            // Indirect calls are all assumed to be pulley->pulley calls
+            &CallDest::Reg(reg) => smallvec![Inst::IndirectCall {
+                info: Box::new(info.map(|()| XReg::new(*reg).unwrap()))
+            }
+            .into()],
         }
     }
 
@@ -585,11 +612,15 @@ where
     }
 
     fn get_number_of_spillslots_for_value(
-        _rc: RegClass,
-        _target_vector_bytes: u32,
+        rc: RegClass,
+        target_vector_bytes: u32,
         _isa_flags: &PulleyFlags,
     ) -> u32 {
-        todo!()
+        // We allocate in terms of 8-byte slots.
+        match rc {
+            RegClass::Int | RegClass::Float => 1,
+            RegClass::Vector => target_vector_bytes / 8,
+        }
     }
 
     fn get_machine_env(_flags: &settings::Flags, _call_conv: isa::CallConv) -> &MachineEnv {
@@ -654,7 +685,7 @@ where
         _frame_size: u32,
         _guard_size: u32,
     ) {
-        todo!()
+        // Like `gen_probestack`, nothing to probe.
     }
 }
 
@@ -691,22 +722,22 @@ const DEFAULT_CALLEE_SAVES: PRegSet = PRegSet::empty()
     .with(px_reg(30))
     .with(px_reg(31))
     // Float registers.
-    .with(px_reg(16))
-    .with(px_reg(17))
-    .with(px_reg(18))
-    .with(px_reg(19))
-    .with(px_reg(20))
-    .with(px_reg(21))
-    .with(px_reg(22))
-    .with(px_reg(23))
-    .with(px_reg(24))
-    .with(px_reg(25))
-    .with(px_reg(26))
-    .with(px_reg(27))
-    .with(px_reg(28))
-    .with(px_reg(29))
-    .with(px_reg(30))
-    .with(px_reg(31))
+    .with(pf_reg(16))
+    .with(pf_reg(17))
+    .with(pf_reg(18))
+    .with(pf_reg(19))
+    .with(pf_reg(20))
+    .with(pf_reg(21))
+    .with(pf_reg(22))
+    .with(pf_reg(23))
+    .with(pf_reg(24))
+    .with(pf_reg(25))
+    .with(pf_reg(26))
+    .with(pf_reg(27))
+    .with(pf_reg(28))
+    .with(pf_reg(29))
+    .with(pf_reg(30))
+    .with(pf_reg(31))
     // Note: no vector registers are callee-saved.
 ;
 
@@ -753,6 +784,7 @@ const DEFAULT_CLOBBERS: PRegSet = PRegSet::empty()
     .with(pf_reg(5))
     .with(pf_reg(6))
     .with(pf_reg(7))
+    .with(pf_reg(8))
     .with(pf_reg(9))
     .with(pf_reg(10))
     .with(pf_reg(11))
@@ -807,7 +839,11 @@ fn create_reg_enviroment() -> MachineEnv {
     };
 
     let non_preferred_regs_by_class: [Vec<PReg>; 3] = {
-        let x_registers: Vec<PReg> = (16..32).map(|x| px_reg(x)).collect();
+        // The remaining `x` registers are special (`sp`, `lr`, `fp` and the
+        // spill temporaries) and must not be handed out by the allocator.
+        let x_registers: Vec<PReg> = (16..pulley_interpreter::regs::XReg::sp as usize)
+            .map(|x| px_reg(x))
+            .collect();
         let f_registers: Vec<PReg> = (16..32).map(|x| pf_reg(x)).collect();
         let v_registers: Vec<PReg> = vec![];
         [x_registers, f_registers, v_registers]
@@ -126,6 +126,175 @@ pub use super::super::lower::isle::generated_code::ExtKind;
 
 pub use super::super::lower::isle::generated_code::Amode;
 
+pub use super::super::lower::isle::generated_code::{
+    FBinaryOp, FCmpOp, FUnaryOp, FloatFromIntOp, IntFromFloatOp, XBinaryOp, XUnaryOp,
+};
+
+impl XBinaryOp {
+    /// The name of the Pulley instruction implementing this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            XBinaryOp::Sub32 => "xsub32",
+            XBinaryOp::Sub64 => "xsub64",
+            XBinaryOp::Mul32 => "xmul32",
+            XBinaryOp::Mul64 => "xmul64",
+            XBinaryOp::Div32S => "xdiv32_s",
+            XBinaryOp::Div64S => "xdiv64_s",
+            XBinaryOp::Div32U => "xdiv32_u",
+            XBinaryOp::Div64U => "xdiv64_u",
+            XBinaryOp::Rem32S => "xrem32_s",
+            XBinaryOp::Rem64S => "xrem64_s",
+            XBinaryOp::Rem32U => "xrem32_u",
+            XBinaryOp::Rem64U => "xrem64_u",
+            XBinaryOp::Band32 => "xband32",
+            XBinaryOp::Band64 => "xband64",
+            XBinaryOp::Bor32 => "xbor32",
+            XBinaryOp::Bor64 => "xbor64",
+            XBinaryOp::Bxor32 => "xbxor32",
+            XBinaryOp::Bxor64 => "xbxor64",
+            XBinaryOp::Shl32 => "xshl32",
+            XBinaryOp::Shr32S => "xshr32_s",
+            XBinaryOp::Shr32U => "xshr32_u",
+            XBinaryOp::Shl64 => "xshl64",
+            XBinaryOp::Shr64S => "xshr64_s",
+            XBinaryOp::Shr64U => "xshr64_u",
+            XBinaryOp::Rotl32 => "xrotl32",
+            XBinaryOp::Rotr32 => "xrotr32",
+            XBinaryOp::Rotl64 => "xrotl64",
+            XBinaryOp::Rotr64 => "xrotr64",
+        }
+    }
+
+    /// Whether this operation traps on a zero divisor.
+    pub fn is_division(&self) -> bool {
+        match self {
+            XBinaryOp::Div32S
+            | XBinaryOp::Div64S
+            | XBinaryOp::Div32U
+            | XBinaryOp::Div64U
+            | XBinaryOp::Rem32S
+            | XBinaryOp::Rem64S
+            | XBinaryOp::Rem32U
+            | XBinaryOp::Rem64U => true,
+            _ => false,
+        }
+    }
+}
+
+impl XUnaryOp {
+    /// The name of the Pulley instruction implementing this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            XUnaryOp::Clz32 => "xclz32",
+            XUnaryOp::Clz64 => "xclz64",
+            XUnaryOp::Ctz32 => "xctz32",
+            XUnaryOp::Ctz64 => "xctz64",
+            XUnaryOp::Popcnt32 => "xpopcnt32",
+            XUnaryOp::Popcnt64 => "xpopcnt64",
+            XUnaryOp::Zext8 => "zext8",
+            XUnaryOp::Zext16 => "zext16",
+            XUnaryOp::Zext32 => "zext32",
+            XUnaryOp::Sext8 => "sext8",
+            XUnaryOp::Sext16 => "sext16",
+            XUnaryOp::Sext32 => "sext32",
+        }
+    }
+}
+
+impl FBinaryOp {
+    /// The name of the Pulley instruction implementing this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FBinaryOp::Add32 => "fadd32",
+            FBinaryOp::Add64 => "fadd64",
+            FBinaryOp::Sub32 => "fsub32",
+            FBinaryOp::Sub64 => "fsub64",
+            FBinaryOp::Mul32 => "fmul32",
+            FBinaryOp::Mul64 => "fmul64",
+            FBinaryOp::Div32 => "fdiv32",
+            FBinaryOp::Div64 => "fdiv64",
+            FBinaryOp::Min32 => "fmin32",
+            FBinaryOp::Min64 => "fmin64",
+            FBinaryOp::Max32 => "fmax32",
+            FBinaryOp::Max64 => "fmax64",
+            FBinaryOp::Copysign32 => "fcopysign32",
+            FBinaryOp::Copysign64 => "fcopysign64",
+        }
+    }
+}
+
+impl FUnaryOp {
+    /// The name of the Pulley instruction implementing this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FUnaryOp::Neg32 => "fneg32",
+            FUnaryOp::Neg64 => "fneg64",
+            FUnaryOp::Abs32 => "fabs32",
+            FUnaryOp::Abs64 => "fabs64",
+            FUnaryOp::Sqrt32 => "fsqrt32",
+            FUnaryOp::Sqrt64 => "fsqrt64",
+            FUnaryOp::Ceil32 => "fceil32",
+            FUnaryOp::Ceil64 => "fceil64",
+            FUnaryOp::Floor32 => "ffloor32",
+            FUnaryOp::Floor64 => "ffloor64",
+            FUnaryOp::Trunc32 => "ftrunc32",
+            FUnaryOp::Trunc64 => "ftrunc64",
+            FUnaryOp::Nearest32 => "fnearest32",
+            FUnaryOp::Nearest64 => "fnearest64",
+            FUnaryOp::F32FromF64 => "f32_from_f64",
+            FUnaryOp::F64FromF32 => "f64_from_f32",
+        }
+    }
+}
+
+impl FCmpOp {
+    /// The name of the Pulley instruction implementing this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FCmpOp::Eq32 => "feq32",
+            FCmpOp::Eq64 => "feq64",
+            FCmpOp::Neq32 => "fneq32",
+            FCmpOp::Neq64 => "fneq64",
+            FCmpOp::Lt32 => "flt32",
+            FCmpOp::Lt64 => "flt64",
+            FCmpOp::Lteq32 => "flteq32",
+            FCmpOp::Lteq64 => "flteq64",
+        }
+    }
+}
+
+impl FloatFromIntOp {
+    /// The name of the Pulley instruction implementing this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FloatFromIntOp::F32FromX32S => "f32_from_x32_s",
+            FloatFromIntOp::F32FromX32U => "f32_from_x32_u",
+            FloatFromIntOp::F32FromX64S => "f32_from_x64_s",
+            FloatFromIntOp::F32FromX64U => "f32_from_x64_u",
+            FloatFromIntOp::F64FromX32S => "f64_from_x32_s",
+            FloatFromIntOp::F64FromX32U => "f64_from_x32_u",
+            FloatFromIntOp::F64FromX64S => "f64_from_x64_s",
+            FloatFromIntOp::F64FromX64U => "f64_from_x64_u",
+        }
+    }
+}
+
+impl IntFromFloatOp {
+    /// The name of the Pulley instruction implementing this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntFromFloatOp::X32FromF32SSat => "x32_from_f32_s_sat",
+            IntFromFloatOp::X32FromF32USat => "x32_from_f32_u_sat",
+            IntFromFloatOp::X32FromF64SSat => "x32_from_f64_s_sat",
+            IntFromFloatOp::X32FromF64USat => "x32_from_f64_u_sat",
+            IntFromFloatOp::X64FromF32SSat => "x64_from_f32_s_sat",
+            IntFromFloatOp::X64FromF32USat => "x64_from_f32_u_sat",
+            IntFromFloatOp::X64FromF64SSat => "x64_from_f64_s_sat",
+            IntFromFloatOp::X64FromF64USat => "x64_from_f64_u_sat",
+        }
+    }
+}
+
 impl Amode {
     /// Add the registers referenced by this Amode to `collector`.
     pub(crate) fn get_operands(&mut self, collector: &mut impl OperandVisitor) {
@@ -160,7 +329,10 @@ impl Amode {
                         + frame_layout.outgoing_args_size;
                     i64::from(sp_offset) - offset
                 }
-                StackAMode::Slot(offset) => *offset + state.virtual_sp_offset,
+                StackAMode::Slot(offset) => {
+                    // Spill slots live just above the outgoing argument area.
+                    *offset + i64::from(state.frame_layout().outgoing_args_size)
+                }
                 StackAMode::OutgoingArg(offset) => *offset,
             },
         }
@@ -4,7 +4,6 @@ use super::*;
 use crate::ir;
 use crate::isa::pulley_shared::abi::PulleyMachineDeps;
 use crate::isa::pulley_shared::PointerWidth;
-use crate::trace;
 use core::marker::PhantomData;
 use cranelift_control::ControlPlane;
 use pulley_interpreter::encode as enc;
@@ -40,7 +39,6 @@ where
     _phantom: PhantomData<P>,
     ctrl_plane: ControlPlane,
     user_stack_map: Option<ir::UserStackMap>,
-    pub virtual_sp_offset: i64,
     frame_layout: FrameLayout,
 }
 
@@ -51,13 +49,6 @@ where
     fn take_stack_map(&mut self) -> Option<ir::UserStackMap> {
         self.user_stack_map.take()
     }
-
-    pub(crate) fn adjust_virtual_sp_offset(&mut self, amount: i64) {
-        let old = self.virtual_sp_offset;
-        let new = self.virtual_sp_offset + amount;
-        trace!("adjust virtual sp offset by {amount:#x}: {old:#x} -> {new:#x}",);
-        self.virtual_sp_offset = new;
-    }
 }
 
 impl<P> MachInstEmitState<InstAndKind<P>> for EmitState<P>
@@ -69,7 +60,6 @@ where
             _phantom: PhantomData,
             ctrl_plane,
             user_stack_map: None,
-            virtual_sp_offset: 0,
             frame_layout: abi.frame_layout().clone(),
         }
     }
@@ -109,7 +99,8 @@ where
 
         let end = sink.cur_offset();
         assert!(
-            (end - start) <= InstAndKind::<P>::worst_case_size(),
+            matches!(self.inst, Inst::BrTable { .. })
+                || (end - start) <= InstAndKind::<P>::worst_case_size(),
             "encoded inst {self:?} longer than worst-case size: length: {}, Inst::worst_case_size() = {}",
             end - start,
             InstAndKind::<P>::worst_case_size()
@@ -124,7 +115,7 @@ where
 fn pulley_emit<P>(
     inst: &Inst,
     sink: &mut MachBuffer<InstAndKind<P>>,
-    _emit_info: &EmitInfo,
+    emit_info: &EmitInfo,
     state: &mut EmitState<P>,
     start_offset: u32,
 ) where
@@ -148,8 +139,10 @@ fn pulley_emit<P>(
         } => {
             let label = sink.defer_trap(*code);
 
+            // The branch offset follows the opcode and the two register
+            // operands.
             let cur_off = sink.cur_offset();
-            sink.use_label_at_offset(cur_off, label, LabelUse::Jump(3));
+            sink.use_label_at_offset(cur_off + 3, label, LabelUse::Jump(3));
 
             use ir::condcodes::IntCC::*;
             use OperandSize::*;
@@ -186,10 +179,14 @@ fn pulley_emit<P>(
             }
         }
 
-        Inst::Nop => todo!(),
+        Inst::Nop => enc::nop(sink),
+
+        Inst::DummyUse { .. } => {}
 
         Inst::GetSp { dst } => enc::get_sp(sink, dst),
 
+        Inst::GetFp { dst } => enc::get_fp(sink, dst),
+
         Inst::Ret => enc::ret(sink),
 
         Inst::LoadExtName { .. } => todo!(),
@@ -211,11 +208,34 @@ fn pulley_emit<P>(
             }
             sink.add_call_site();
 
-            let callee_pop_size = i64::from(info.callee_pop_size);
-            state.adjust_virtual_sp_offset(-callee_pop_size);
+            emit_callee_pop_adjust(sink, emit_info, state, info.callee_pop_size);
         }
 
-        Inst::IndirectCall { .. } => todo!(),
+        Inst::IndirectCall { info } => {
+            enc::call_indirect(sink, &info.dest);
+            if let Some(s) = state.take_stack_map() {
+                let offset = sink.cur_offset();
+                sink.push_user_stack_map(state, offset, s);
+            }
+            sink.add_call_site();
+
+            emit_callee_pop_adjust(sink, emit_info, state, info.callee_pop_size);
+        }
+
+        Inst::IndirectCallHost { info } => {
+            // The embedder fills in which host function is being called
+            // through this relocation, which covers the whole instruction.
+            sink.add_reloc(Reloc::PulleyCallIndirectHost, &info.dest, 0);
+            enc::call_indirect_host(sink, 0_u8);
+            if let Some(s) = state.take_stack_map() {
+                let offset = sink.cur_offset();
+                sink.push_user_stack_map(state, offset, s);
+            }
+            sink.add_call_site();
+
+            // Host functions don't pop any stack arguments, so there's
+            // nothing to undo here.
+        }
 
         Inst::Jump { label } => {
             sink.use_label_at_offset(start_offset + 1, *label, LabelUse::Jump(1));
@@ -361,6 +381,23 @@ fn pulley_emit<P>(
             );
         }
 
+        Inst::BrTable {
+            idx,
+            default,
+            targets,
+        } => {
+            // `br_table32` is followed by a table of `jump` instructions, one
+            // per target and then one for the default, and it jumps to the
+            // selected entry.
+            let amt = u32::try_from(targets.len() + 1).expect("too many branch targets");
+            enc::br_table32(sink, idx, amt);
+            for target in targets.iter().chain(core::iter::once(default)) {
+                let offset = sink.cur_offset();
+                sink.use_label_at_offset(offset + 1, *target, LabelUse::Jump(1));
+                enc::jump(sink, 0x00000000);
+            }
+        }
+
         Inst::Xmov { dst, src } => enc::xmov(sink, dst, src),
         Inst::Fmov { dst, src } => enc::fmov(sink, dst, src),
         Inst::Vmov { dst, src } => enc::vmov(sink, dst, src),
@@ -394,6 +431,76 @@ fn pulley_emit<P>(
             enc::xulteq32(sink, BinaryOperands::new(dst, src1, src2))
         }
 
+        Inst::XBinary {
+            op,
+            dst,
+            src1,
+            src2,
+        } => {
+            if op.is_division() {
+                sink.add_trap(ir::TrapCode::INTEGER_DIVISION_BY_ZERO);
+            }
+            let operands = BinaryOperands::new(dst, src1, src2);
+            match op {
+                XBinaryOp::Sub32 => enc::xsub32(sink, operands),
+                XBinaryOp::Sub64 => enc::xsub64(sink, operands),
+                XBinaryOp::Mul32 => enc::xmul32(sink, operands),
+                XBinaryOp::Mul64 => enc::xmul64(sink, operands),
+                XBinaryOp::Div32S => enc::xdiv32_s(sink, operands),
+                XBinaryOp::Div64S => enc::xdiv64_s(sink, operands),
+                XBinaryOp::Div32U => enc::xdiv32_u(sink, operands),
+                XBinaryOp::Div64U => enc::xdiv64_u(sink, operands),
+                XBinaryOp::Rem32S => enc::xrem32_s(sink, operands),
+                XBinaryOp::Rem64S => enc::xrem64_s(sink, operands),
+                XBinaryOp::Rem32U => enc::xrem32_u(sink, operands),
+                XBinaryOp::Rem64U => enc::xrem64_u(sink, operands),
+                XBinaryOp::Band32 => enc::xband32(sink, operands),
+                XBinaryOp::Band64 => enc::xband64(sink, operands),
+                XBinaryOp::Bor32 => enc::xbor32(sink, operands),
+                XBinaryOp::Bor64 => enc::xbor64(sink, operands),
+                XBinaryOp::Bxor32 => enc::xbxor32(sink, operands),
+                XBinaryOp::Bxor64 => enc::xbxor64(sink, operands),
+                XBinaryOp::Shl32 => enc::xshl32(sink, operands),
+                XBinaryOp::Shr32S => enc::xshr32_s(sink, operands),
+                XBinaryOp::Shr32U => enc::xshr32_u(sink, operands),
+                XBinaryOp::Shl64 => enc::xshl64(sink, operands),
+                XBinaryOp::Shr64S => enc::xshr64_s(sink, operands),
+                XBinaryOp::Shr64U => enc::xshr64_u(sink, operands),
+                XBinaryOp::Rotl32 => enc::xrotl32(sink, operands),
+                XBinaryOp::Rotr32 => enc::xrotr32(sink, operands),
+                XBinaryOp::Rotl64 => enc::xrotl64(sink, operands),
+                XBinaryOp::Rotr64 => enc::xrotr64(sink, operands),
+            }
+        }
+
+        Inst::XUnary { op, dst, src } => match op {
+            XUnaryOp::Clz32 => enc::xclz32(sink, dst, src),
+            XUnaryOp::Clz64 => enc::xclz64(sink, dst, src),
+            XUnaryOp::Ctz32 => enc::xctz32(sink, dst, src),
+            XUnaryOp::Ctz64 => enc::xctz64(sink, dst, src),
+            XUnaryOp::Popcnt32 => enc::xpopcnt32(sink, dst, src),
+            XUnaryOp::Popcnt64 => enc::xpopcnt64(sink, dst, src),
+            XUnaryOp::Zext8 => enc::zext8(sink, dst, src),
+            XUnaryOp::Zext16 => enc::zext16(sink, dst, src),
+            XUnaryOp::Zext32 => enc::zext32(sink, dst, src),
+            XUnaryOp::Sext8 => enc::sext8(sink, dst, src),
+            XUnaryOp::Sext16 => enc::sext16(sink, dst, src),
+            XUnaryOp::Sext32 => enc::sext32(sink, dst, src),
+        },
+
+        Inst::XSelect {
+            dst,
+            cond,
+            if_nonzero,
+            if_zero,
+        } => enc::xselect(sink, dst, cond, if_nonzero, if_zero),
+        Inst::FSelect {
+            dst,
+            cond,
+            if_nonzero,
+            if_zero,
+        } => enc::fselect(sink, dst, cond, if_nonzero, if_zero),
+
         Inst::LoadAddr { dst, mem } => {
             let base = mem.get_base_register();
             let offset = mem.get_offset_with_state(state);
@@ -438,22 +545,37 @@ fn pulley_emit<P>(
             use ExtKind as X;
             let r = mem.get_base_register().unwrap();
             let r = reg_to_pulley_xreg(r);
-            let dst = reg_to_pulley_xreg(dst.to_reg());
             let x = mem.get_offset_with_state(state);
-            match (*ext, *ty, i8::try_from(x)) {
-                (X::Sign, types::I32, Ok(0)) => enc::load32_s(sink, dst, r),
-                (X::Sign, types::I32, Ok(x)) => enc::load32_s_offset8(sink, dst, r, x),
-                (X::Sign, types::I32, Err(_)) => enc::load32_s_offset64(sink, dst, r, x),
+            match *ty {
+                types::F32 => {
+                    return enc::fload32_offset64(sink, reg_to_pulley_freg(dst.to_reg()), r, x)
+                }
+                types::F64 => {
+                    return enc::fload64_offset64(sink, reg_to_pulley_freg(dst.to_reg()), r, x)
+                }
+                _ => {}
+            }
+            // Everything else lives in an X register (vector types are
+            // rejected by `rc_for_type`), so dispatch on the access width.
+            let dst = reg_to_pulley_xreg(dst.to_reg());
+            match (*ext, ty.bytes(), i8::try_from(x)) {
+                (X::Sign, 1, _) => enc::load8_s_offset64(sink, dst, r, x),
+                (_, 1, _) => enc::load8_u_offset64(sink, dst, r, x),
+
+                (X::Sign, 2, _) => enc::load16_s_offset64(sink, dst, r, x),
+                (_, 2, _) => enc::load16_u_offset64(sink, dst, r, x),
 
-                (X::Zero, types::I32, Ok(0)) => enc::load32_u(sink, dst, r),
-                (X::Zero, types::I32, Ok(x)) => enc::load32_u_offset8(sink, dst, r, x),
-                (X::Zero, types::I32, Err(_)) => enc::load32_u_offset64(sink, dst, r, x),
+                (X::Sign, 4, Ok(0)) => enc::load32_s(sink, dst, r),
+                (X::Sign, 4, Ok(x)) => enc::load32_s_offset8(sink, dst, r, x),
+                (X::Sign, 4, Err(_)) => enc::load32_s_offset64(sink, dst, r, x),
 
-                (_, types::I64, Ok(0)) => enc::load64(sink, dst, r),
-                (_, types::I64, Ok(x)) => enc::load64_offset8(sink, dst, r, x),
-                (_, types::I64, Err(_)) => enc::load64_offset64(sink, dst, r, x),
+                (_, 4, Ok(0)) => enc::load32_u(sink, dst, r),
+                (_, 4, Ok(x)) => enc::load32_u_offset8(sink, dst, r, x),
+                (_, 4, Err(_)) => enc::load32_u_offset64(sink, dst, r, x),
 
-                (..) => unimplemented!("load ext={ext:?} ty={ty}"),
+                (_, _, Ok(0)) => enc::load64(sink, dst, r),
+                (_, _, Ok(x)) => enc::load64_offset8(sink, dst, r, x),
+                (_, _, Err(_)) => enc::load64_offset64(sink, dst, r, x),
             }
         }
 
@@ -465,18 +587,25 @@ fn pulley_emit<P>(
         } => {
             let r = mem.get_base_register().unwrap();
             let r = reg_to_pulley_xreg(r);
-            let src = reg_to_pulley_xreg(*src);
             let x = mem.get_offset_with_state(state);
-            match (*ty, i8::try_from(x)) {
-                (types::I32, Ok(0)) => enc::store32(sink, r, src),
-                (types::I32, Ok(x)) => enc::store32_offset8(sink, r, x, src),
-                (types::I32, Err(_)) => enc::store32_offset64(sink, r, x, src),
+            match *ty {
+                types::F32 => return enc::fstore32_offset64(sink, r, x, reg_to_pulley_freg(*src)),
+                types::F64 => return enc::fstore64_offset64(sink, r, x, reg_to_pulley_freg(*src)),
+                _ => {}
+            }
+            // As with loads, anything left is an X register.
+            let src = reg_to_pulley_xreg(*src);
+            match (ty.bytes(), i8::try_from(x)) {
+                (1, _) => enc::store8_offset64(sink, r, x, src),
+                (2, _) => enc::store16_offset64(sink, r, x, src),
 
-                (types::I64, Ok(0)) => enc::store64(sink, r, src),
-                (types::I64, Ok(x)) => enc::store64_offset8(sink, r, x, src),
-                (types::I64, Err(_)) => enc::store64_offset64(sink, r, x, src),
+                (4, Ok(0)) => enc::store32(sink, r, src),
+                (4, Ok(x)) => enc::store32_offset8(sink, r, x, src),
+                (4, Err(_)) => enc::store32_offset64(sink, r, x, src),
 
-                (..) => todo!(),
+                (_, Ok(0)) => enc::store64(sink, r, src),
+                (_, Ok(x)) => enc::store64_offset8(sink, r, x, src),
+                (_, Err(_)) => enc::store64_offset64(sink, r, x, src),
             }
         }
 
@@ -484,6 +613,106 @@ fn pulley_emit<P>(
         Inst::BitcastIntFromFloat64 { dst, src } => enc::bitcast_int_from_float_64(sink, dst, src),
         Inst::BitcastFloatFromInt32 { dst, src } => enc::bitcast_float_from_int_32(sink, dst, src),
         Inst::BitcastFloatFromInt64 { dst, src } => enc::bitcast_float_from_int_64(sink, dst, src),
+
+        Inst::FBinary {
+            op,
+            dst,
+            src1,
+            src2,
+        } => {
+            let operands = BinaryOperands::new(dst, src1, src2);
+            match op {
+                FBinaryOp::Add32 => enc::fadd32(sink, operands),
+                FBinaryOp::Add64 => enc::fadd64(sink, operands),
+                FBinaryOp::Sub32 => enc::fsub32(sink, operands),
+                FBinaryOp::Sub64 => enc::fsub64(sink, operands),
+                FBinaryOp::Mul32 => enc::fmul32(sink, operands),
+                FBinaryOp::Mul64 => enc::fmul64(sink, operands),
+                FBinaryOp::Div32 => enc::fdiv32(sink, operands),
+                FBinaryOp::Div64 => enc::fdiv64(sink, operands),
+                FBinaryOp::Min32 => enc::fmin32(sink, operands),
+                FBinaryOp::Min64 => enc::fmin64(sink, operands),
+                FBinaryOp::Max32 => enc::fmax32(sink, operands),
+                FBinaryOp::Max64 => enc::fmax64(sink, operands),
+                FBinaryOp::Copysign32 => enc::fcopysign32(sink, operands),
+                FBinaryOp::Copysign64 => enc::fcopysign64(sink, operands),
+            }
+        }
+
+        Inst::FUnary { op, dst, src } => match op {
+            FUnaryOp::Neg32 => enc::fneg32(sink, dst, src),
+            FUnaryOp::Neg64 => enc::fneg64(sink, dst, src),
+            FUnaryOp::Abs32 => enc::fabs32(sink, dst, src),
+            FUnaryOp::Abs64 => enc::fabs64(sink, dst, src),
+            FUnaryOp::Sqrt32 => enc::fsqrt32(sink, dst, src),
+            FUnaryOp::Sqrt64 => enc::fsqrt64(sink, dst, src),
+            FUnaryOp::Ceil32 => enc::fceil32(sink, dst, src),
+            FUnaryOp::Ceil64 => enc::fceil64(sink, dst, src),
+            FUnaryOp::Floor32 => enc::ffloor32(sink, dst, src),
+            FUnaryOp::Floor64 => enc::ffloor64(sink, dst, src),
+            FUnaryOp::Trunc32 => enc::ftrunc32(sink, dst, src),
+            FUnaryOp::Trunc64 => enc::ftrunc64(sink, dst, src),
+            FUnaryOp::Nearest32 => enc::fnearest32(sink, dst, src),
+            FUnaryOp::Nearest64 => enc::fnearest64(sink, dst, src),
+            FUnaryOp::F32FromF64 => enc::f32_from_f64(sink, dst, src),
+            FUnaryOp::F64FromF32 => enc::f64_from_f32(sink, dst, src),
+        },
+
+        Inst::FCmp {
+            op,
+            dst,
+            src1,
+            src2,
+        } => match op {
+            FCmpOp::Eq32 => enc::feq32(sink, dst, src1, src2),
+            FCmpOp::Eq64 => enc::feq64(sink, dst, src1, src2),
+            FCmpOp::Neq32 => enc::fneq32(sink, dst, src1, src2),
+            FCmpOp::Neq64 => enc::fneq64(sink, dst, src1, src2),
+            FCmpOp::Lt32 => enc::flt32(sink, dst, src1, src2),
+            FCmpOp::Lt64 => enc::flt64(sink, dst, src1, src2),
+            FCmpOp::Lteq32 => enc::flteq32(sink, dst, src1, src2),
+            FCmpOp::Lteq64 => enc::flteq64(sink, dst, src1, src2),
+        },
+
+        Inst::FloatFromInt { op, dst, src } => match op {
+            FloatFromIntOp::F32FromX32S => enc::f32_from_x32_s(sink, dst, src),
+            FloatFromIntOp::F32FromX32U => enc::f32_from_x32_u(sink, dst, src),
+            FloatFromIntOp::F32FromX64S => enc::f32_from_x64_s(sink, dst, src),
+            FloatFromIntOp::F32FromX64U => enc::f32_from_x64_u(sink, dst, src),
+            FloatFromIntOp::F64FromX32S => enc::f64_from_x32_s(sink, dst, src),
+            FloatFromIntOp::F64FromX32U => enc::f64_from_x32_u(sink, dst, src),
+            FloatFromIntOp::F64FromX64S => enc::f64_from_x64_s(sink, dst, src),
+            FloatFromIntOp::F64FromX64U => enc::f64_from_x64_u(sink, dst, src),
+        },
+
+        Inst::IntFromFloat { op, dst, src } => match op {
+            IntFromFloatOp::X32FromF32SSat => enc::x32_from_f32_s_sat(sink, dst, src),
+            IntFromFloatOp::X32FromF32USat => enc::x32_from_f32_u_sat(sink, dst, src),
+            IntFromFloatOp::X32FromF64SSat => enc::x32_from_f64_s_sat(sink, dst, src),
+            IntFromFloatOp::X32FromF64USat => enc::x32_from_f64_u_sat(sink, dst, src),
+            IntFromFloatOp::X64FromF32SSat => enc::x64_from_f32_s_sat(sink, dst, src),
+            IntFromFloatOp::X64FromF32USat => enc::x64_from_f32_u_sat(sink, dst, src),
+            IntFromFloatOp::X64FromF64SSat => enc::x64_from_f64_s_sat(sink, dst, src),
+            IntFromFloatOp::X64FromF64USat => enc::x64_from_f64_u_sat(sink, dst, src),
+        },
+    }
+}
+
+/// A callee using the `tail` calling convention pops its stack arguments on
+/// return, so move SP back down to where the caller expects it to be.
+fn emit_callee_pop_adjust<P>(
+    sink: &mut MachBuffer<InstAndKind<P>>,
+    emit_info: &EmitInfo,
+    state: &mut EmitState<P>,
+    callee_pop_size: u32,
+) where
+    P: PulleyTargetKind,
+{
+    if callee_pop_size > 0 {
+        let callee_pop_size = i32::try_from(callee_pop_size).expect("callee popped more than 2GB");
+        for inst in PulleyMachineDeps::<P>::gen_sp_reg_adjust(-callee_pop_size) {
+            pulley_emit(&inst, sink, emit_info, state, sink.cur_offset());
+        }
     }
 }
 
@@ -527,3 +756,7 @@ fn br_if_cond_helper<P>(
 fn reg_to_pulley_xreg(r: Reg) -> pulley_interpreter::XReg {
     pulley_interpreter::XReg::new(r.to_real_reg().unwrap().hw_enc()).unwrap()
 }
+
+fn reg_to_pulley_freg(r: Reg) -> pulley_interpreter::FReg {
+    pulley_interpreter::FReg::new(r.to_real_reg().unwrap().hw_enc()).unwrap()
+}
@@ -10,6 +10,7 @@ use crate::isa::FunctionAlignment;
 use crate::{machinst::*, trace};
 use crate::{settings, CodegenError, CodegenResult};
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use regalloc2::RegClass;
 use smallvec::SmallVec;
 
@@ -68,6 +69,10 @@ fn pulley_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
 
         Inst::Unwind { .. } | Inst::Trap { .. } | Inst::Nop => {}
 
+        Inst::DummyUse { reg } => {
+            collector.reg_use(reg);
+        }
+
         Inst::TrapIf {
             cond: _,
             size: _,
@@ -79,7 +84,7 @@ fn pulley_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
             collector.reg_use(src2);
         }
 
-        Inst::GetSp { dst } => {
+        Inst::GetSp { dst } | Inst::GetFp { dst } => {
             collector.reg_def(dst);
         }
 
@@ -91,7 +96,7 @@ fn pulley_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
             collector.reg_def(dst);
         }
 
-        Inst::Call { info } => {
+        Inst::Call { info } | Inst::IndirectCallHost { info } => {
             let CallInfo { uses, defs, .. } = &mut **info;
             for CallArgPair { vreg, preg } in uses {
                 collector.reg_fixed_use(vreg, *preg);
@@ -163,6 +168,14 @@ fn pulley_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
             collector.reg_use(src2);
         }
 
+        Inst::BrTable {
+            idx,
+            default: _,
+            targets: _,
+        } => {
+            collector.reg_use(idx);
+        }
+
         Inst::Xmov { dst, src } => {
             collector.reg_use(src);
             collector.reg_def(dst);
@@ -202,6 +215,44 @@ fn pulley_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
             collector.reg_def(dst);
         }
 
+        Inst::XBinary {
+            op: _,
+            dst,
+            src1,
+            src2,
+        } => {
+            collector.reg_use(src1);
+            collector.reg_use(src2);
+            collector.reg_def(dst);
+        }
+        Inst::XUnary { op: _, dst, src } => {
+            collector.reg_use(src);
+            collector.reg_def(dst);
+        }
+
+        Inst::XSelect {
+            dst,
+            cond,
+            if_nonzero,
+            if_zero,
+        } => {
+            collector.reg_use(cond);
+            collector.reg_use(if_nonzero);
+            collector.reg_use(if_zero);
+            collector.reg_def(dst);
+        }
+        Inst::FSelect {
+            dst,
+            cond,
+            if_nonzero,
+            if_zero,
+        } => {
+            collector.reg_use(cond);
+            collector.reg_use(if_nonzero);
+            collector.reg_use(if_zero);
+            collector.reg_def(dst);
+        }
+
         Inst::LoadAddr { dst, mem } => {
             collector.reg_def(dst);
             mem.get_operands(collector);
@@ -244,6 +295,39 @@ fn pulley_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
             collector.reg_use(src);
             collector.reg_def(dst);
         }
+
+        Inst::FBinary {
+            op: _,
+            dst,
+            src1,
+            src2,
+        } => {
+            collector.reg_use(src1);
+            collector.reg_use(src2);
+            collector.reg_def(dst);
+        }
+        Inst::FUnary { op: _, dst, src } => {
+            collector.reg_use(src);
+            collector.reg_def(dst);
+        }
+        Inst::FCmp {
+            op: _,
+            dst,
+            src1,
+            src2,
+        } => {
+            collector.reg_use(src1);
+            collector.reg_use(src2);
+            collector.reg_def(dst);
+        }
+        Inst::FloatFromInt { op: _, dst, src } => {
+            collector.reg_use(src);
+            collector.reg_def(dst);
+        }
+        Inst::IntFromFloat { op: _, dst, src } => {
+            collector.reg_use(src);
+            collector.reg_def(dst);
+        }
     }
 }
 
@@ -309,10 +393,13 @@ where
     type LabelUse = LabelUse;
     type ABIMachineSpec = PulleyMachineDeps<P>;
 
-    const TRAP_OPCODE: &'static [u8] = &[0];
+    const TRAP_OPCODE: &'static [u8] = &{
+        let [a, b] = (pulley_interpreter::ExtendedOpcode::Trap as u16).to_le_bytes();
+        [pulley_interpreter::Opcode::ExtendedOp as u8, a, b]
+    };
 
-    fn gen_dummy_use(_reg: Reg) -> Self {
-        todo!()
+    fn gen_dummy_use(reg: Reg) -> Self {
+        Inst::DummyUse { reg }.into()
     }
 
     fn canonical_type_for_rc(rc: RegClass) -> Type {
@@ -325,7 +412,10 @@ where
 
     fn is_safepoint(&self) -> bool {
         match self.inst {
-            Inst::Trap { .. } => true,
+            Inst::Trap { .. }
+            | Inst::Call { .. }
+            | Inst::IndirectCall { .. }
+            | Inst::IndirectCallHost { .. } => true,
             _ => false,
         }
     }
@@ -342,7 +432,7 @@ where
     }
 
     fn is_included_in_clobbers(&self) -> bool {
-        self.is_args()
+        !self.is_args()
     }
 
     fn is_trap(&self) -> bool {
@@ -370,12 +460,16 @@ where
             | Inst::BrIfXslteq32 { .. }
             | Inst::BrIfXult32 { .. }
             | Inst::BrIfXulteq32 { .. } => MachTerminator::Cond,
+            Inst::BrTable { .. } => MachTerminator::Indirect,
             _ => MachTerminator::None,
         }
     }
 
     fn is_mem_access(&self) -> bool {
-        todo!()
+        match self.inst {
+            Inst::Load { .. } | Inst::Store { .. } => true,
+            _ => false,
+        }
     }
 
     fn gen_move(to_reg: Writable<Reg>, from_reg: Reg, ty: Type) -> Self {
@@ -400,7 +494,7 @@ where
     }
 
     fn gen_nop(_preferred_size: usize) -> Self {
-        todo!()
+        Inst::Nop.into()
     }
 
     fn rc_for_type(ty: Type) -> CodegenResult<(&'static [RegClass], &'static [Type])> {
@@ -412,48 +506,44 @@ where
             F32 => Ok((&[RegClass::Float], &[F32])),
             F64 => Ok((&[RegClass::Float], &[F64])),
             I128 => Ok((&[RegClass::Int, RegClass::Int], &[I64, I64])),
-            _ if ty.is_vector() => {
-                debug_assert!(ty.bits() <= 512);
-
-                // Here we only need to return a SIMD type with the same size as `ty`.
-                // We use these types for spills and reloads, so prefer types with lanes <= 31
-                // since that fits in the immediate field of `vsetivli`.
-                const SIMD_TYPES: [[Type; 1]; 6] = [
-                    [types::I8X2],
-                    [types::I8X4],
-                    [types::I8X8],
-                    [types::I8X16],
-                    [types::I16X16],
-                    [types::I32X16],
-                ];
-                let idx = (ty.bytes().ilog2() - 1) as usize;
-                let ty = &SIMD_TYPES[idx][..];
-
-                Ok((&[RegClass::Vector], ty))
-            }
+            // Pulley has no vector loads, stores or arithmetic yet, so vector
+            // values are rejected up front rather than failing during emission.
+            _ if ty.is_vector() => Err(CodegenError::Unsupported(format!(
+                "Pulley does not support vector types yet: {ty}"
+            ))),
             _ => Err(CodegenError::Unsupported(format!(
                 "Unexpected SSA-value type: {ty}"
             ))),
         }
     }
 
-    fn gen_jump(_target: MachLabel) -> Self {
-        todo!()
+    fn gen_jump(target: MachLabel) -> Self {
+        Inst::Jump { label: target }.into()
     }
 
     fn worst_case_size() -> CodeOffset {
-        // `BrIfXeq32 { a, b, taken, not_taken }` expands to `br_if_xeq32 a, b, taken; jump not_taken`.
+        // `Call { info }` whose callee pops a large stack argument area
+        // expands to `call offset; xconst32 spilltmp0, -n; xadd64 sp, sp,
+        // spilltmp0`.
+        //
+        // The first instruction is five bytes long:
+        //   * 1 byte opcode
+        //   * 4 byte `offset` displacement
         //
-        // The first instruction is seven bytes long:
+        // The second instruction is six bytes long:
         //   * 1 byte opcode
-        //   * 1 byte `a` register encoding
-        //   * 1 byte `b` register encoding
-        //   * 4 byte `taken` displacement
+        //   * 1 byte `dst` register encoding
+        //   * 4 byte immediate
         //
-        // And the second instruction is five bytes long:
+        // And the third instruction is three bytes long:
         //   * 1 byte opcode
-        //   * 4 byte `not_taken` displacement
-        12
+        //   * 2 bytes of register encodings
+        //
+        // This is longer than any other single instruction's expansion, e.g.
+        // `LoadAddr` with a 64-bit offset at thirteen bytes or `BrIfXeq32` at
+        // twelve bytes. `BrTable` is the exception: it is variable-length and
+        // exempt from this bound.
+        14
     }
 
     fn ref_type_regclass(_settings: &settings::Flags) -> RegClass {
@@ -548,6 +638,11 @@ impl Inst {
 
             Inst::Nop => format!("nop"),
 
+            Inst::DummyUse { reg } => {
+                let reg = format_reg(*reg);
+                format!("dummy_use {reg}")
+            }
+
             Inst::Ret => format!("ret"),
 
             Inst::GetSp { dst } => {
@@ -555,6 +650,11 @@ impl Inst {
                 format!("{dst} = get_sp")
             }
 
+            Inst::GetFp { dst } => {
+                let dst = format_reg(*dst.to_reg());
+                format!("{dst} = get_fp")
+            }
+
             Inst::LoadExtName { dst, name, offset } => {
                 let dst = format_reg(*dst.to_reg());
                 format!("{dst} = load_ext_name {name:?}, {offset}")
@@ -569,6 +669,10 @@ impl Inst {
                 format!("indirect_call {callee}, {info:?}")
             }
 
+            Inst::IndirectCallHost { info } => {
+                format!("indirect_call_host {info:?}")
+            }
+
             Inst::Jump { label } => format!("jump {}", label.to_string()),
 
             Inst::BrIf {
@@ -655,6 +759,21 @@ impl Inst {
                 format!("br_if_xulteq32 {src1}, {src2}, {taken}; jump {not_taken}")
             }
 
+            Inst::BrTable {
+                idx,
+                default,
+                targets,
+            } => {
+                let idx = format_reg(**idx);
+                let targets = targets
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let default = default.to_string();
+                format!("br_table32 {idx}, [{targets}], {default}")
+            }
+
             Inst::Xmov { dst, src } => {
                 let dst = format_reg(*dst.to_reg());
                 let src = format_reg(**src);
@@ -774,6 +893,50 @@ impl Inst {
                 format_reg(**src2)
             ),
 
+            Inst::XBinary {
+                op,
+                dst,
+                src1,
+                src2,
+            } => format!(
+                "{} = {} {}, {}",
+                format_reg(*dst.to_reg()),
+                op.name(),
+                format_reg(**src1),
+                format_reg(**src2)
+            ),
+            Inst::XUnary { op, dst, src } => format!(
+                "{} = {} {}",
+                format_reg(*dst.to_reg()),
+                op.name(),
+                format_reg(**src)
+            ),
+
+            Inst::XSelect {
+                dst,
+                cond,
+                if_nonzero,
+                if_zero,
+            } => format!(
+                "{} = xselect {}, {}, {}",
+                format_reg(*dst.to_reg()),
+                format_reg(**cond),
+                format_reg(**if_nonzero),
+                format_reg(**if_zero)
+            ),
+            Inst::FSelect {
+                dst,
+                cond,
+                if_nonzero,
+                if_zero,
+            } => format!(
+                "{} = fselect {}, {}, {}",
+                format_reg(*dst.to_reg()),
+                format_reg(**cond),
+                format_reg(**if_nonzero),
+                format_reg(**if_zero)
+            ),
+
             Inst::LoadAddr { dst, mem } => {
                 let dst = format_reg(*dst.to_reg());
                 let mem = mem.to_string();
@@ -826,6 +989,49 @@ impl Inst {
                 let src = format_reg(**src);
                 format!("{dst} = bitcast_float_from_int64 {src}")
             }
+
+            Inst::FBinary {
+                op,
+                dst,
+                src1,
+                src2,
+            } => format!(
+                "{} = {} {}, {}",
+                format_reg(*dst.to_reg()),
+                op.name(),
+                format_reg(**src1),
+                format_reg(**src2)
+            ),
+            Inst::FUnary { op, dst, src } => format!(
+                "{} = {} {}",
+                format_reg(*dst.to_reg()),
+                op.name(),
+                format_reg(**src)
+            ),
+            Inst::FCmp {
+                op,
+                dst,
+                src1,
+                src2,
+            } => format!(
+                "{} = {} {}, {}",
+                format_reg(*dst.to_reg()),
+                op.name(),
+                format_reg(**src1),
+                format_reg(**src2)
+            ),
+            Inst::FloatFromInt { op, dst, src } => format!(
+                "{} = {} {}",
+                format_reg(*dst.to_reg()),
+                op.name(),
+                format_reg(**src)
+            ),
+            Inst::IntFromFloat { op, dst, src } => format!(
+                "{} = {} {}",
+                format_reg(*dst.to_reg()),
+                op.name(),
+                format_reg(**src)
+            ),
         }
     }
 }
@@ -6,6 +6,7 @@ use super::{inst::*, PulleyBackend, PulleyTargetKind};
 use crate::{
     ir,
     machinst::{lower::*, *},
+    CodegenError,
 };
 
 impl<P> LowerBackend for PulleyBackend<P>
@@ -15,7 +16,22 @@ where
     type MInst = InstAndKind<P>;
 
     fn lower(&self, ctx: &mut Lower<Self::MInst>, ir_inst: ir::Inst) -> Option<InstOutput> {
-        isle::lower(ctx, self, ir_inst)
+        if let Some(output) = isle::lower(ctx, self, ir_inst) {
+            return Some(output);
+        }
+
+        // Pulley doesn't implement every CLIF instruction yet (e.g. SIMD and
+        // atomics), so report missing lowerings as an error instead of
+        // panicking. The placeholder results are never used since lowering
+        // stops at the end of this block.
+        unsupported(ctx, ir_inst);
+        let output = (0..ctx.num_outputs(ir_inst))
+            .map(|i| {
+                let ty = ctx.output_ty(ir_inst, i);
+                ctx.alloc_tmp(ty).map(|r| r.to_reg())
+            })
+            .collect();
+        Some(output)
     }
 
     fn lower_branch(
@@ -24,7 +40,10 @@ where
         ir_inst: ir::Inst,
         targets: &[MachLabel],
     ) -> Option<()> {
-        isle::lower_branch(ctx, self, ir_inst, targets)
+        if isle::lower_branch(ctx, self, ir_inst, targets).is_none() {
+            unsupported(ctx, ir_inst);
+        }
+        Some(())
     }
 
     fn maybe_pinned_reg(&self) -> Option<Reg> {
@@ -34,3 +53,15 @@ where
 
     type FactFlowState = ();
 }
+
+/// Records that `ir_inst` has no Pulley lowering.
+fn unsupported<P>(ctx: &mut Lower<InstAndKind<P>>, ir_inst: ir::Inst)
+where
+    P: PulleyTargetKind,
+{
+    let msg = format!(
+        "Pulley does not support `{}`",
+        ctx.dfg().display_inst(ir_inst)
+    );
+    ctx.defer_error(CodegenError::Unsupported(msg));
+}
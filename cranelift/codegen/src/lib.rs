@@ -46,6 +46,8 @@ pub mod data_value;
 pub mod dbg;
 pub mod dominator_tree;
 pub mod flowgraph;
+pub mod induction_vars;
+pub mod inline;
 pub mod ir;
 pub mod isa;
 pub mod loop_analysis;
@@ -76,12 +78,14 @@ mod inst_predicates;
 mod isle_prelude;
 mod iterators;
 mod legalizer;
+mod loop_unrolling;
 mod nan_canonicalization;
 mod opts;
 mod ranges;
 mod remove_constant_phis;
 mod result;
 mod scoped_hash_map;
+mod strength_reduction;
 mod unionfind;
 mod unreachable_code;
 mod value_label;
@@ -0,0 +1,348 @@
+//! Loop unrolling.
+//!
+//! Small innermost loops are unrolled by laying out copies of their body one
+//! after the other: the back edges of the original loop jump to the first
+//! copy's header, those of each copy to the next one's, and those of the last
+//! copy back to the original header. Every copy keeps its exit branches, so
+//! this doesn't need to know the loop's trip count, but the jumps between
+//! copies become fallthroughs and the mid-end can optimize across iterations,
+//! for example by folding the increments of induction variables.
+
+use crate::dominator_tree::DominatorTree;
+use crate::entity::{packed_option::PackedOption, EntitySet, SecondaryMap};
+use crate::flowgraph::ControlFlowGraph;
+use crate::induction_vars::loop_blocks;
+use crate::ir::{Block, Function, Inst, InstructionData, JumpTableData, Value};
+use crate::loop_analysis::{Loop, LoopAnalysis};
+use crate::trace;
+use alloc::vec::Vec;
+
+/// Loops are unrolled as long as the unrolled loop has at most this many
+/// instructions.
+const MAX_UNROLLED_SIZE: usize = 64;
+
+/// The maximum number of copies of a loop's body after unrolling.
+const MAX_UNROLL_FACTOR: usize = 4;
+
+/// Unroll the small innermost loops of `func`, returning whether any loop was
+/// unrolled.
+///
+/// Unrolling a loop changes the control flow graph, so `cfg`, `domtree` and
+/// `loop_analysis` are recomputed after each one and are up to date when this
+/// returns.
+pub(crate) fn do_loop_unrolling(
+    func: &mut Function,
+    cfg: &mut ControlFlowGraph,
+    domtree: &mut DominatorTree,
+    loop_analysis: &mut LoopAnalysis,
+) -> bool {
+    // Copying instructions maps their arguments, which is simpler without
+    // aliases in the way.
+    func.dfg.resolve_all_aliases();
+
+    // Loops are renumbered whenever the analyses are recomputed, but an
+    // unrolled loop keeps its header, so remember the headers of the loops
+    // already considered.
+    let mut visited = EntitySet::new();
+    let mut unrolled_any = false;
+    while let Some(lp) = next_innermost_loop(loop_analysis, &visited) {
+        visited.insert(loop_analysis.loop_header(lp));
+        if unroll_loop(func, cfg, domtree, loop_analysis, lp) {
+            unrolled_any = true;
+            cfg.compute(func);
+            domtree.compute(func, cfg);
+            loop_analysis.compute(func, cfg, domtree);
+        }
+    }
+    unrolled_any
+}
+
+/// The first innermost loop whose header isn't in `visited`.
+fn next_innermost_loop(loop_analysis: &LoopAnalysis, visited: &EntitySet<Block>) -> Option<Loop> {
+    loop_analysis.loops().find(|&lp| {
+        !visited.contains(loop_analysis.loop_header(lp))
+            && loop_analysis
+                .loops()
+                .all(|other| loop_analysis.loop_parent(other) != Some(lp))
+    })
+}
+
+/// Unroll `lp` if it is small enough and has a suitable shape.
+fn unroll_loop(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    domtree: &DominatorTree,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+) -> bool {
+    let header = loop_analysis.loop_header(lp);
+    if func.layout.entry_block() == Some(header) {
+        return false;
+    }
+
+    let blocks = loop_blocks(func, loop_analysis, lp);
+    let size: usize = blocks
+        .iter()
+        .map(|&block| func.layout.block_insts(block).count())
+        .sum();
+    let factor = (MAX_UNROLLED_SIZE / size.max(1)).min(MAX_UNROLL_FACTOR);
+    if factor < 2 {
+        return false;
+    }
+
+    // Values defined in the loop and used after it need to be passed to the
+    // code after the loop from whichever copy exits. That requires a single
+    // exit block to add parameters to, which all uses after the loop are
+    // dominated by.
+    let live_outs = live_outs(func, loop_analysis, lp, &blocks);
+    let exit = if live_outs.is_empty() {
+        None
+    } else {
+        match single_exit(cfg, loop_analysis, lp, &blocks) {
+            Some(exit) => Some(exit),
+            None => return false,
+        }
+    };
+
+    trace!("unrolling loop {lp} at {header} {factor} times");
+
+    // Copy instructions in reverse postorder so that values are copied before
+    // their uses.
+    let mut rpo = blocks.clone();
+    rpo.sort_by(|&a, &b| domtree.rpo_cmp_block(a, b));
+
+    let mut unrolled_blocks = EntitySet::new();
+    for &block in &blocks {
+        unrolled_blocks.insert(block);
+    }
+    let mut copies = Vec::new();
+    let mut prev_blocks = blocks.clone();
+    let mut insert_after = *blocks.last().unwrap();
+    for _ in 1..factor {
+        let copy = copy_loop(func, &blocks, &rpo, header, insert_after);
+        let new_header = copy.blocks[header].unwrap();
+        for &block in &prev_blocks {
+            retarget(func, block, header, new_header);
+        }
+        prev_blocks = blocks.iter().map(|&b| copy.blocks[b].unwrap()).collect();
+        for &block in &prev_blocks {
+            unrolled_blocks.insert(block);
+        }
+        insert_after = *prev_blocks.last().unwrap();
+        copies.push(copy);
+    }
+
+    if let Some(exit) = exit {
+        for value in live_outs {
+            let ty = func.dfg.value_type(value);
+            let param = func.dfg.append_block_param(exit, ty);
+            for &block in &blocks {
+                append_exit_arg(func, block, exit, value);
+                for copy in &copies {
+                    let copied_block = copy.blocks[block].unwrap();
+                    let copied_value = copy.values[value].expand().unwrap_or(value);
+                    append_exit_arg(func, copied_block, exit, copied_value);
+                }
+            }
+
+            // All uses after the loop are dominated by the exit block.
+            let outside: Vec<Block> = func
+                .layout
+                .blocks()
+                .filter(|&block| !unrolled_blocks.contains(block))
+                .collect();
+            for block in outside {
+                let mut next_inst = func.layout.first_inst(block);
+                while let Some(inst) = next_inst {
+                    next_inst = func.layout.next_inst(inst);
+                    func.dfg
+                        .map_inst_values(inst, |v| if v == value { param } else { v });
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// The values defined in `lp`, which consists of `blocks`, that are used
+/// outside of it.
+fn live_outs(
+    func: &Function,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    blocks: &[Block],
+) -> Vec<Value> {
+    let mut defined = EntitySet::new();
+    for &block in blocks {
+        for &param in func.dfg.block_params(block) {
+            defined.insert(param);
+        }
+        for inst in func.layout.block_insts(block) {
+            for &result in func.dfg.inst_results(inst) {
+                defined.insert(result);
+            }
+        }
+    }
+
+    let mut live_outs = Vec::new();
+    for block in func.layout.blocks() {
+        if loop_analysis.is_in_loop(block, lp) {
+            continue;
+        }
+        for inst in func.layout.block_insts(block) {
+            for value in func.dfg.inst_values(inst) {
+                if defined.contains(value) && !live_outs.contains(&value) {
+                    live_outs.push(value);
+                }
+            }
+        }
+    }
+    live_outs
+}
+
+/// The only block outside of `lp` that its `blocks` branch to, if all of that
+/// block's predecessors are in `lp`.
+fn single_exit(
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    blocks: &[Block],
+) -> Option<Block> {
+    let mut exit = None;
+    for &block in blocks {
+        for succ in cfg.succ_iter(block) {
+            if !loop_analysis.is_in_loop(succ, lp) && *exit.get_or_insert(succ) != succ {
+                return None;
+            }
+        }
+    }
+    let exit = exit?;
+    cfg.pred_iter(exit)
+        .all(|pred| loop_analysis.is_in_loop(pred.block, lp))
+        .then_some(exit)
+}
+
+/// A copy of a loop, mapping the original's blocks and values to the copy's.
+struct LoopCopy {
+    blocks: SecondaryMap<Block, PackedOption<Block>>,
+    values: SecondaryMap<Value, PackedOption<Value>>,
+}
+
+/// Copy the loop consisting of `blocks` after `insert_after`.
+///
+/// Branches to `header` are left pointing to the original header, so the
+/// copy's back edges lead to the original loop.
+fn copy_loop(
+    func: &mut Function,
+    blocks: &[Block],
+    rpo: &[Block],
+    header: Block,
+    insert_after: Block,
+) -> LoopCopy {
+    let mut copy = LoopCopy {
+        blocks: SecondaryMap::new(),
+        values: SecondaryMap::new(),
+    };
+
+    let mut insert_after = insert_after;
+    for &block in blocks {
+        let new_block = func.dfg.make_block();
+        func.layout.insert_block_after(new_block, insert_after);
+        insert_after = new_block;
+        if func.layout.is_cold(block) {
+            func.layout.set_cold(new_block);
+        }
+        for i in 0..func.dfg.num_block_params(block) {
+            let param = func.dfg.block_params(block)[i];
+            let ty = func.dfg.value_type(param);
+            copy.values[param] = func.dfg.append_block_param(new_block, ty).into();
+        }
+        copy.blocks[block] = new_block.into();
+    }
+
+    for &block in rpo {
+        let new_block = copy.blocks[block].unwrap();
+        let insts: Vec<Inst> = func.layout.block_insts(block).collect();
+        for inst in insts {
+            let new_inst = func.dfg.clone_inst(inst);
+            func.layout.append_inst(new_inst, new_block);
+            let srcloc = func.srcloc(inst);
+            if !srcloc.is_default() {
+                func.set_srcloc(new_inst, srcloc);
+            }
+
+            for i in 0..func.dfg.inst_results(inst).len() {
+                let result = func.dfg.inst_results(inst)[i];
+                copy.values[result] = func.dfg.inst_results(new_inst)[i].into();
+            }
+
+            let entries: Vec<_> = func
+                .dfg
+                .user_stack_map_entries(inst)
+                .unwrap_or(&[])
+                .to_vec();
+            for entry in entries {
+                func.dfg.append_user_stack_map_entry(new_inst, entry);
+            }
+
+            // `clone_inst` shares jump tables, which would make the copy's
+            // changes below apply to the original too.
+            let dfg = &mut func.dfg;
+            if let InstructionData::BranchTable { table, .. } = dfg.insts[new_inst] {
+                let data = &dfg.jump_tables[table];
+                let default = data.default_block().deep_clone(&mut dfg.value_lists);
+                let branches: Vec<_> = data
+                    .as_slice()
+                    .iter()
+                    .map(|branch| branch.deep_clone(&mut dfg.value_lists))
+                    .collect();
+                let new_table = dfg.jump_tables.push(JumpTableData::new(default, &branches));
+                if let InstructionData::BranchTable { table, .. } = &mut dfg.insts[new_inst] {
+                    *table = new_table;
+                }
+            }
+
+            dfg.map_inst_values(new_inst, |v| copy.values[v].expand().unwrap_or(v));
+            for dest in dfg.insts[new_inst].branch_destination_mut(&mut dfg.jump_tables) {
+                let target = dest.block(&dfg.value_lists);
+                if target == header {
+                    continue;
+                }
+                if let Some(new_target) = copy.blocks[target].expand() {
+                    dest.set_block(new_target, &mut dfg.value_lists);
+                }
+            }
+        }
+    }
+
+    copy
+}
+
+/// Make the branches at the end of `block` which go to `from` go to `to`
+/// instead.
+fn retarget(func: &mut Function, block: Block, from: Block, to: Block) {
+    let Some(inst) = func.layout.last_inst(block) else {
+        return;
+    };
+    let dfg = &mut func.dfg;
+    for dest in dfg.insts[inst].branch_destination_mut(&mut dfg.jump_tables) {
+        if dest.block(&dfg.value_lists) == from {
+            dest.set_block(to, &mut dfg.value_lists);
+        }
+    }
+}
+
+/// Pass `value` as an additional argument on the branches from `block` to
+/// `exit`.
+fn append_exit_arg(func: &mut Function, block: Block, exit: Block, value: Value) {
+    let Some(inst) = func.layout.last_inst(block) else {
+        return;
+    };
+    let dfg = &mut func.dfg;
+    for dest in dfg.insts[inst].branch_destination_mut(&mut dfg.jump_tables) {
+        if dest.block(&dfg.value_lists) == exit {
+            dest.append_argument(value, &mut dfg.value_lists);
+        }
+    }
+}
@@ -2033,6 +2033,11 @@ impl<I: VCodeInst> TextSectionBuilder for MachTextSectionBuilder<I> {
         }
     }
 
+    fn write(&mut self, offset: u64, data: &[u8]) {
+        let offset = usize::try_from(offset).unwrap();
+        self.buf.data[offset..][..data.len()].copy_from_slice(data);
+    }
+
     fn force_veneers(&mut self) {
         self.force_veneers = ForceVeneers::Yes;
     }
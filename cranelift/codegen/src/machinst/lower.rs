@@ -20,7 +20,7 @@ use crate::machinst::{
     VCodeConstants, VCodeInst, ValueRegs, Writable,
 };
 use crate::settings::Flags;
-use crate::{trace, CodegenResult};
+use crate::{trace, CodegenError, CodegenResult};
 use alloc::vec::Vec;
 use cranelift_control::ControlPlane;
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -228,6 +228,9 @@ pub struct Lower<'func, I: VCodeInst> {
 
     /// Compilation flags.
     flags: Flags,
+
+    /// The first error reported by the backend through `defer_error`.
+    deferred_error: Option<CodegenError>,
 }
 
 /// How is a value used in the IR?
@@ -493,6 +496,7 @@ impl<'func, I: VCodeInst> Lower<'func, I> {
             ir_insts: vec![],
             pinned_reg: None,
             flags,
+            deferred_error: None,
         })
     }
 
@@ -1031,6 +1035,9 @@ impl<'func, I: VCodeInst> Lower<'func, I> {
             if let Some(e) = self.vregs.take_deferred_error() {
                 return Err(e);
             }
+            if let Some(e) = self.deferred_error.take() {
+                return Err(e);
+            }
         }
 
         // Now that we've emitted all instructions into the
@@ -1476,6 +1483,13 @@ impl<'func, I: VCodeInst> Lower<'func, I> {
         writable_value_regs(self.vregs.alloc_with_deferred_error(ty))
     }
 
+    /// Report an error from within a backend's lowering hooks, which can't
+    /// return one directly. Lowering stops with this error once the current
+    /// block has been lowered.
+    pub fn defer_error(&mut self, e: CodegenError) {
+        self.deferred_error.get_or_insert(e);
+    }
+
     /// Emit a machine instruction.
     pub fn emit(&mut self, mach_inst: I) {
         trace!("emit: {:?}", mach_inst);
@@ -551,6 +551,12 @@ pub trait TextSectionBuilder {
     /// relocation will be resolved in the final bytes returned by `finish`.
     fn resolve_reloc(&mut self, offset: u64, reloc: Reloc, addend: Addend, target: usize) -> bool;
 
+    /// Overwrites previously appended bytes at `offset` with `data`.
+    ///
+    /// This is used to patch relocations which are resolved by the embedder
+    /// itself rather than by `resolve_reloc`.
+    fn write(&mut self, offset: u64, data: &[u8]);
+
     /// A debug-only option which is used to for
     fn force_veneers(&mut self);
 
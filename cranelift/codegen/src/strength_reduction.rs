@@ -0,0 +1,155 @@
+//! Strength reduction of multiplications by induction variables.
+//!
+//! A multiplication `v * c` of a basic induction variable `v` by a constant
+//! `c` in the variable's loop is replaced by a new induction variable which
+//! starts at `init * c` and advances by `step * c` on every iteration, turning
+//! a multiplication per iteration into an addition. This is the usual shape
+//! of address computations for arrays indexed by a loop counter.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::flowgraph::ControlFlowGraph;
+use crate::induction_vars::{iconst_value, truncate, InductionVar, InductionVars};
+use crate::ir::{Block, Function, Inst, InstBuilder, InstructionData, Opcode, Type, Value};
+use crate::loop_analysis::LoopAnalysis;
+use crate::trace;
+use alloc::vec::Vec;
+use smallvec::SmallVec;
+
+/// Perform strength reduction on all loops of `func`, returning whether
+/// anything changed.
+///
+/// This only adds header parameters and instructions, so the control flow
+/// graph and loop analysis stay valid.
+pub(crate) fn do_strength_reduction(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+) -> bool {
+    let ivs = InductionVars::compute(func, cfg, loop_analysis);
+
+    let mut candidates = Vec::new();
+    for block in func.layout.blocks() {
+        let Some(lp) = loop_analysis.innermost_loop(block) else {
+            continue;
+        };
+        for inst in func.layout.block_insts(block) {
+            let (a, b) = match func.dfg.insts[inst] {
+                InstructionData::Binary {
+                    opcode: Opcode::Imul,
+                    args: [a, b],
+                } => (a, b),
+                _ => continue,
+            };
+            let (iv, factor) = match (ivs.get(func, a), ivs.get(func, b)) {
+                (Some(iv), _) if iv.lp == lp => match iconst_value(func, b) {
+                    Some(factor) => (*iv, factor),
+                    None => continue,
+                },
+                (_, Some(iv)) if iv.lp == lp => match iconst_value(func, a) {
+                    Some(factor) => (*iv, factor),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            candidates.push((inst, iv, factor));
+        }
+    }
+
+    // Multiplications of the same variable by the same factor share the new
+    // variable.
+    let mut reduced: Vec<(Value, u64, Value)> = Vec::new();
+    for &(inst, iv, factor) in &candidates {
+        let new_var = match reduced
+            .iter()
+            .find(|&&(param, f, _)| param == iv.param && f == factor)
+        {
+            Some(&(_, _, new_var)) => new_var,
+            None => {
+                let new_var = add_scaled_var(func, cfg, loop_analysis, &iv, factor);
+                reduced.push((iv.param, factor, new_var));
+                new_var
+            }
+        };
+
+        trace!(
+            "strength reduction: replacing {} with {}",
+            func.dfg.display_inst(inst),
+            new_var
+        );
+        let result = func.dfg.first_result(inst);
+        func.dfg.clear_results(inst);
+        func.layout.remove_inst(inst);
+        func.dfg.change_to_alias(result, new_var);
+    }
+
+    !candidates.is_empty()
+}
+
+/// Add a parameter to the header of `iv`'s loop which is always `factor`
+/// times `iv`, and return it.
+fn add_scaled_var(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    iv: &InductionVar,
+    factor: u64,
+) -> Value {
+    let header = loop_analysis.loop_header(iv.lp);
+    let ty = func.dfg.value_type(iv.param);
+    let new_var = func.dfg.append_block_param(header, ty);
+    let step = truncate(ty, iv.step.wrapping_mul(factor));
+
+    for pred in cfg.pred_iter(header) {
+        let args: SmallVec<[Value; 2]> = if loop_analysis.is_in_loop(pred.block, iv.lp) {
+            // Back edges advance the new variable by its step.
+            let mut pos = FuncCursor::new(func).at_inst(pred.inst);
+            pos.use_srcloc(pred.inst);
+            let step = pos.ins().iconst(ty, step as i64);
+            let next = pos.ins().iadd(new_var, step);
+            let n = header_dests(pos.func, pred.inst, header).count();
+            core::iter::repeat(next).take(n).collect()
+        } else {
+            // Edges entering the loop start it at the scaled initial value,
+            // which may differ between edges.
+            let inits: SmallVec<[Value; 2]> = header_dests(func, pred.inst, header)
+                .map(|args| args[iv.index])
+                .collect();
+            inits
+                .into_iter()
+                .map(|init| scale(func, pred.inst, ty, init, factor))
+                .collect()
+        };
+
+        let dfg = &mut func.dfg;
+        let mut args = args.into_iter();
+        for dest in dfg.insts[pred.inst].branch_destination_mut(&mut dfg.jump_tables) {
+            if dest.block(&dfg.value_lists) == header {
+                dest.append_argument(args.next().unwrap(), &mut dfg.value_lists);
+            }
+        }
+    }
+
+    new_var
+}
+
+/// The arguments of each of `branch`'s edges to `header`.
+fn header_dests<'a>(
+    func: &'a Function,
+    branch: Inst,
+    header: Block,
+) -> impl Iterator<Item = &'a [Value]> + 'a {
+    let pool = &func.dfg.value_lists;
+    func.dfg.insts[branch]
+        .branch_destination(&func.dfg.jump_tables)
+        .iter()
+        .filter(move |dest| dest.block(pool) == header)
+        .map(move |dest| dest.args_slice(pool))
+}
+
+/// Insert `init * factor` before `branch`.
+fn scale(func: &mut Function, branch: Inst, ty: Type, init: Value, factor: u64) -> Value {
+    let mut pos = FuncCursor::new(func).at_inst(branch);
+    pos.use_srcloc(branch);
+    let factor = pos.ins().iconst(ty, factor as i64);
+    pos.ins().imul(init, factor)
+}
@@ -62,11 +62,14 @@ define_passes! {
     domtree: "Dominator tree",
     loop_analysis: "Loop analysis",
     preopt: "Pre-legalization rewriting",
+    inline: "Function inlining",
     egraph: "Egraph based optimizations",
     gvn: "Global value numbering",
     licm: "Loop invariant code motion",
     unreachable_code: "Remove unreachable blocks",
     remove_constant_phis: "Remove constant phi-nodes",
+    strength_reduction: "Strength reduction",
+    loop_unrolling: "Loop unrolling",
 
     vcode_lower: "VCode lowering",
     vcode_emit: "VCode emission",
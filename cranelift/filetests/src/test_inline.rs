@@ -0,0 +1,148 @@
+//! Test command for testing the inlining pass.
+//!
+//! The `inline` test command inlines calls to other functions defined in the
+//! same test file into each function, and sends the resulting CLIF through
+//! filecheck. No other optimizations are performed.
+//!
+//! The size budget can be set with the `max-callee-size=N` and `max-growth=N`
+//! options; by default every call to a function in the file is inlined, as
+//! long as the function doesn't grow by more than 1000 instructions.
+
+use crate::runone::FileUpdate;
+use crate::subtest::{check_precise_output, run_filecheck, Context, SubTest};
+use anyhow::{Context as _, Result};
+use cranelift_codegen::inline::{Inline, InlineBudget, InlineCommand};
+use cranelift_codegen::ir::{self, Function};
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::settings::Flags;
+use cranelift_reader::{TestCommand, TestFile, TestOption};
+use log::info;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+struct TestInline {
+    /// Flag indicating that the text expectation, comments after the function,
+    /// must be a precise 100% match on the inlined function.
+    precise_output: bool,
+    max_callee_size: usize,
+    max_growth: usize,
+}
+
+pub fn subtest(parsed: &TestCommand) -> Result<Box<dyn SubTest>> {
+    assert_eq!(parsed.command, "inline");
+    let mut test = TestInline {
+        precise_output: false,
+        max_callee_size: usize::MAX,
+        max_growth: 1000,
+    };
+    for option in parsed.options.iter() {
+        match option {
+            TestOption::Flag("precise-output") => test.precise_output = true,
+            TestOption::Value("max-callee-size", n) => test.max_callee_size = n.parse()?,
+            TestOption::Value("max-growth", n) => test.max_growth = n.parse()?,
+            _ => anyhow::bail!("unknown option on {}", parsed),
+        }
+    }
+    Ok(Box::new(test))
+}
+
+impl SubTest for TestInline {
+    fn name(&self) -> &'static str {
+        "inline"
+    }
+
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    fn needs_isa(&self) -> bool {
+        true
+    }
+
+    /// Runs the entire subtest for a given target, invokes [Self::run] for running
+    /// individual tests.
+    fn run_target<'a>(
+        &self,
+        testfile: &TestFile,
+        file_update: &mut FileUpdate,
+        file_path: &'a str,
+        flags: &'a Flags,
+        isa: Option<&'a dyn TargetIsa>,
+    ) -> Result<()> {
+        let isa = isa.expect("inline needs an ISA");
+
+        // Callees are looked up by name, the same way the interpreter
+        // resolves calls.
+        let functions: HashMap<String, &Function> = testfile
+            .functions
+            .iter()
+            .map(|(func, _)| (func.name.to_string(), func))
+            .collect();
+
+        for (func, details) in &testfile.functions {
+            info!("Test: {}({}) {}", self.name(), func.name, isa.name());
+
+            let context = Context {
+                preamble_comments: &testfile.preamble_comments,
+                details,
+                flags,
+                isa: Some(isa),
+                file_path,
+                file_update,
+            };
+
+            let mut comp_ctx = cranelift_codegen::Context::for_function(func.clone());
+            let inliner = FileInliner {
+                functions: &functions,
+                budget: InlineBudget::new(self.max_callee_size, self.max_growth),
+            };
+            comp_ctx
+                .inline(isa, inliner)
+                .map_err(|e| crate::pretty_anyhow_error(&comp_ctx.func, e))
+                .context(self.name())?;
+
+            let clif = format!("{:?}", comp_ctx.func);
+            let result = if self.precise_output {
+                let actual: Vec<_> = clif.lines().collect();
+                check_precise_output(&actual, &context)
+            } else {
+                run_filecheck(&clif, &context)
+            };
+            result.context(self.name())?;
+        }
+
+        Ok(())
+    }
+
+    fn run(&self, _func: Cow<ir::Function>, _context: &Context) -> Result<()> {
+        unreachable!()
+    }
+}
+
+/// Inlines calls to functions defined in the test file, within a budget.
+struct FileInliner<'a> {
+    functions: &'a HashMap<String, &'a Function>,
+    budget: InlineBudget,
+}
+
+impl Inline for FileInliner<'_> {
+    fn inline(
+        &mut self,
+        caller: &Function,
+        _call_inst: ir::Inst,
+        _call_opcode: ir::Opcode,
+        callee: ir::FuncRef,
+        _call_args: &[ir::Value],
+    ) -> InlineCommand<'_> {
+        let name = caller.dfg.ext_funcs[callee]
+            .name
+            .display(Some(&caller.params))
+            .to_string();
+        match self.functions.get(&name) {
+            Some(callee) if self.budget.try_spend(callee) => {
+                InlineCommand::Inline(Cow::Borrowed(*callee))
+            }
+            _ => InlineCommand::KeepCall,
+        }
+    }
+}
@@ -184,6 +184,8 @@ where
         //   aarch64: https://github.com/bytecodealliance/wasmtime/issues/2735
         let bool_settings = [
             "enable_alias_analysis",
+            "enable_strength_reduction",
+            "enable_loop_unrolling",
             "enable_safepoints",
             "unwind_info",
             "preserve_frame_pointers",
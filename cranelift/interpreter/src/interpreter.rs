@@ -11,8 +11,8 @@ use crate::step::{step, ControlFlow, CraneliftTrap, StepError};
 use crate::value::{DataValueExt, ValueError};
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::{
-    ArgumentPurpose, Block, Endianness, ExternalName, FuncRef, Function, GlobalValue,
-    GlobalValueData, LibCall, MemFlags, StackSlot, Type,
+    ArgumentPurpose, Block, DynamicStackSlot, Endianness, ExternalName, FuncRef, Function,
+    GlobalValue, GlobalValueData, LibCall, MemFlags, StackSlot, Type,
 };
 use log::trace;
 use smallvec::SmallVec;
@@ -252,12 +252,12 @@ impl<'a> State<'a> for InterpreterState<'a> {
 
     fn push_frame(&mut self, function: &'a Function) {
         if let Some(frame) = self.frame_stack.iter().last() {
-            self.frame_offset += frame.function().fixed_stack_size() as usize;
+            self.frame_offset += frame_size(frame.function());
         }
 
         // Grow the stack by the space necessary for this frame
         self.stack
-            .extend(iter::repeat(0).take(function.fixed_stack_size() as usize));
+            .extend(iter::repeat(0).take(frame_size(function)));
 
         self.frame_stack.push(Frame::new(function));
     }
@@ -265,11 +265,11 @@ impl<'a> State<'a> for InterpreterState<'a> {
         if let Some(frame) = self.frame_stack.pop() {
             // Shorten the stack after exiting the frame
             self.stack
-                .truncate(self.stack.len() - frame.function().fixed_stack_size() as usize);
+                .truncate(self.stack.len() - frame_size(frame.function()));
 
             // Reset frame_offset to the start of this function
             if let Some(frame) = self.frame_stack.iter().last() {
-                self.frame_offset -= frame.function().fixed_stack_size() as usize;
+                self.frame_offset -= frame_size(frame.function());
             }
         }
     }
@@ -318,6 +318,56 @@ impl<'a> State<'a> for InterpreterState<'a> {
         Address::from_parts(size, AddressRegion::Stack, 0, final_offset)
     }
 
+    fn dynamic_stack_address(
+        &self,
+        size: AddressSize,
+        slot: DynamicStackSlot,
+    ) -> Result<Address, MemoryError> {
+        let function = self.get_current_function();
+
+        // Dynamic stack slots are placed after all of the sized ones.
+        let slot_offset: u64 = function
+            .dynamic_stack_slots
+            .keys()
+            .filter(|k| k < &slot)
+            .map(|k| dynamic_slot_size(function, k))
+            .sum();
+
+        let final_offset =
+            self.frame_offset as u64 + u64::from(function.fixed_stack_size()) + slot_offset;
+        Address::from_parts(size, AddressRegion::Stack, 0, final_offset)
+    }
+
+    fn frame_pointer(&self, size: AddressSize) -> Result<Address, MemoryError> {
+        Address::from_parts(size, AddressRegion::Stack, 0, self.frame_offset as u64)
+    }
+
+    fn stack_pointer(&self, size: AddressSize) -> Result<Address, MemoryError> {
+        let frame_end = self.frame_offset + frame_size(self.get_current_function());
+        Address::from_parts(size, AddressRegion::Stack, 0, frame_end as u64)
+    }
+
+    fn return_address(&self, size: AddressSize) -> Result<Address, MemoryError> {
+        // There is no code to return into, so this is the address of the calling function
+        // instead. Functions called from outside of the interpreter return to a null address.
+        let num_frames = self.frame_stack.len();
+        let caller = match num_frames {
+            0 | 1 => None,
+            _ => self
+                .functions
+                .index_of(&self.frame_stack[num_frames - 2].function().name.to_string()),
+        };
+        match caller {
+            Some(index) => Address::from_parts(
+                size,
+                AddressRegion::Function,
+                AddressFunctionEntry::UserFunction as u64,
+                index.as_u32() as u64,
+            ),
+            None => Address::from_parts(size, AddressRegion::Stack, 0, 0),
+        }
+    }
+
     fn checked_load(
         &self,
         addr: Address,
@@ -525,7 +575,10 @@ impl<'a> State<'a> for InterpreterState<'a> {
                         action_stack.push(ResolveAction::Resolve(base));
                     }
                     GlobalValueData::Symbol { .. } => unimplemented!(),
-                    GlobalValueData::DynScaleTargetConst { .. } => unimplemented!(),
+                    GlobalValueData::DynScaleTargetConst { .. } => {
+                        // Dynamic vectors are interpreted with a scale of one.
+                        current_val = DataValue::I64(1);
+                    }
                 },
                 Some(ResolveAction::Add(dv)) => {
                     current_val = current_val
@@ -558,6 +611,23 @@ impl<'a> State<'a> for InterpreterState<'a> {
     }
 }
 
+/// The number of bytes of stack space used by `function`'s stack slots.
+fn frame_size(function: &Function) -> usize {
+    let dynamic_size: u64 = function
+        .dynamic_stack_slots
+        .keys()
+        .map(|slot| dynamic_slot_size(function, slot))
+        .sum();
+    function.fixed_stack_size() as usize + dynamic_size as usize
+}
+
+/// The size of a dynamic stack slot. Dynamic vectors are interpreted with a scale of one, so this
+/// is the size of the slot's base vector type.
+fn dynamic_slot_size(function: &Function, slot: DynamicStackSlot) -> u64 {
+    let dyn_ty = function.dynamic_stack_slots[slot].dyn_ty;
+    u64::from(function.dfg.dynamic_types[dyn_ty].base_vector_ty.bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1059,4 +1129,92 @@ mod tests {
         // Ensure that the correct trap was propagated.
         assert_eq!(trap, ControlFlow::Trap(CraneliftTrap::HeapMisaligned));
     }
+
+    // Dynamic stack slots are placed after the sized ones and hold a single base vector.
+    #[test]
+    fn dynamic_stack_slots() {
+        let code = "
+        function %dynamic_slots(i32, i64) -> i32x4, i64 {
+            gv0 = dyn_scale_target_const.i32x4
+            dt0 = i32x4*gv0
+            ss0 = explicit_slot 8
+            dss0 = explicit_dynamic_slot dt0
+
+        block0(v0: i32, v1: i64):
+            stack_store v1, ss0
+            v2 = splat.dt0 v0
+            dynamic_stack_store v2, dss0
+            v3 = dynamic_stack_load.dt0 dss0
+            v4 = extract_vector v3, 0
+            v5 = stack_load.i64 ss0
+            return v4, v5
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let mut env = FunctionStore::default();
+        env.add(func.name.to_string(), &func);
+        let state = InterpreterState::default().with_function_store(env);
+        let result = Interpreter::new(state)
+            .call_by_name("%dynamic_slots", &[DataValue::I32(7), DataValue::I64(-1)])
+            .unwrap();
+
+        assert_eq!(
+            result,
+            ControlFlow::Return(smallvec![
+                DataValue::V128([7, 0, 0, 0, 7, 0, 0, 0, 7, 0, 0, 0, 7, 0, 0, 0]),
+                DataValue::I64(-1)
+            ])
+        );
+    }
+
+    #[test]
+    fn frame_and_stack_pointers() {
+        let code = "
+        function %child() -> i64, i64, i64 {
+            ss0 = explicit_slot 16
+
+        block0:
+            v0 = get_frame_pointer.i64
+            v1 = get_stack_pointer.i64
+            v2 = get_return_address.i64
+            return v0, v1, v2
+        }
+
+        function %parent() -> i64, i64, i64 {
+            ss0 = explicit_slot 8
+            fn0 = %child() -> i64, i64, i64
+
+        block0:
+            v0, v1, v2 = call fn0()
+            return v0, v1, v2
+        }";
+
+        let mut env = FunctionStore::default();
+        let funcs = parse_functions(code).unwrap().to_vec();
+        funcs.iter().for_each(|f| env.add(f.name.to_string(), f));
+        let parent = env.index_of("%parent").unwrap();
+
+        let state = InterpreterState::default().with_function_store(env);
+        let result = Interpreter::new(state)
+            .call_by_name("%parent", &[])
+            .unwrap();
+
+        // The child's frame starts after the parent's and the return address points to the
+        // parent.
+        let return_address = Address::from_parts(
+            AddressSize::_64,
+            AddressRegion::Function,
+            AddressFunctionEntry::UserFunction as u64,
+            parent.as_u32() as u64,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            ControlFlow::Return(smallvec![
+                DataValue::I64(8),
+                DataValue::I64(24),
+                DataValue::try_from(return_address).unwrap()
+            ])
+        );
+    }
 }
@@ -6,6 +6,7 @@ use crate::state::{InterpreterFunctionRef, MemoryError, State};
 use crate::value::{DataValueExt, ValueConversionKind, ValueError, ValueResult};
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::immediates::Ieee128;
 use cranelift_codegen::ir::{
     types, AbiParam, AtomicRmwOp, Block, BlockCall, Endianness, ExternalName, FuncRef, Function,
     InstructionData, MemFlags, Opcode, TrapCode, Type, Value as ValueRef,
@@ -52,6 +53,13 @@ where
 {
     let inst = inst_context.data();
     let ctrl_ty = inst_context.controlling_type().unwrap();
+    // Dynamic vectors are interpreted with a scale of one, which makes them equivalent to their
+    // base vector type.
+    let ctrl_ty = if ctrl_ty.is_dynamic_vector() {
+        ctrl_ty.dynamic_to_vector().unwrap()
+    } else {
+        ctrl_ty
+    };
     trace!(
         "Step: {}{}",
         inst.opcode(),
@@ -200,7 +208,6 @@ where
     // instruction's results.
     let unary =
         |op: fn(DataValue) -> ValueResult<DataValue>, arg: DataValue| -> ValueResult<ControlFlow> {
-            let ctrl_ty = inst_context.controlling_type().unwrap();
             let res = unary_arith(arg, ctrl_ty, op)?;
             Ok(assign(res))
         };
@@ -211,7 +218,6 @@ where
                   left: DataValue,
                   right: DataValue|
      -> ValueResult<ControlFlow> {
-        let ctrl_ty = inst_context.controlling_type().unwrap();
         let res = binary_arith(left, right, ctrl_ty, op)?;
         Ok(assign(res))
     };
@@ -221,7 +227,6 @@ where
                            left: DataValue,
                            right: DataValue|
      -> ValueResult<ControlFlow> {
-        let ctrl_ty = inst_context.controlling_type().unwrap();
         let res = binary_arith(left, right, ctrl_ty, op);
         assign_or_trap(res)
     };
@@ -447,7 +452,6 @@ where
         | Opcode::Sload16x4
         | Opcode::Uload32x2
         | Opcode::Sload32x2 => {
-            let ctrl_ty = inst_context.controlling_type().unwrap();
             let (load_ty, kind) = match inst.opcode() {
                 Opcode::Load => (ctrl_ty, None),
                 Opcode::Uload8 => (types::I8, Some(ValueConversionKind::ZeroExtend(ctrl_ty))),
@@ -456,12 +460,31 @@ where
                 Opcode::Sload16 => (types::I16, Some(ValueConversionKind::SignExtend(ctrl_ty))),
                 Opcode::Uload32 => (types::I32, Some(ValueConversionKind::ZeroExtend(ctrl_ty))),
                 Opcode::Sload32 => (types::I32, Some(ValueConversionKind::SignExtend(ctrl_ty))),
-                Opcode::Uload8x8
-                | Opcode::Sload8x8
-                | Opcode::Uload16x4
-                | Opcode::Sload16x4
-                | Opcode::Uload32x2
-                | Opcode::Sload32x2 => unimplemented!(),
+                // The vector loads extend each of the loaded lanes.
+                Opcode::Uload8x8 => (
+                    types::I8X8,
+                    Some(ValueConversionKind::ZeroExtend(ctrl_ty.lane_type())),
+                ),
+                Opcode::Sload8x8 => (
+                    types::I8X8,
+                    Some(ValueConversionKind::SignExtend(ctrl_ty.lane_type())),
+                ),
+                Opcode::Uload16x4 => (
+                    types::I16X4,
+                    Some(ValueConversionKind::ZeroExtend(ctrl_ty.lane_type())),
+                ),
+                Opcode::Sload16x4 => (
+                    types::I16X4,
+                    Some(ValueConversionKind::SignExtend(ctrl_ty.lane_type())),
+                ),
+                Opcode::Uload32x2 => (
+                    types::I32X2,
+                    Some(ValueConversionKind::ZeroExtend(ctrl_ty.lane_type())),
+                ),
+                Opcode::Sload32x2 => (
+                    types::I32X2,
+                    Some(ValueConversionKind::SignExtend(ctrl_ty.lane_type())),
+                ),
                 _ => unreachable!(),
             };
 
@@ -475,7 +498,13 @@ where
             match (loaded, kind) {
                 (ControlFlow::Assign(ret), Some(c)) => ControlFlow::Assign(
                     ret.into_iter()
-                        .map(|loaded| loaded.convert(c.clone()))
+                        .map(|loaded| {
+                            let lanes = extractlanes(&loaded, load_ty)?
+                                .into_iter()
+                                .map(|lane| lane.convert(c.clone()))
+                                .collect::<ValueResult<SimdVec<DataValue>>>()?;
+                            vectorizelanes(&lanes, ctrl_ty)
+                        })
                         .collect::<ValueResult<SmallVec<[DataValue; 1]>>>()?,
                 ),
                 (cf, _) => cf,
@@ -536,9 +565,50 @@ where
                 })
             })
         }
-        Opcode::DynamicStackAddr => unimplemented!("DynamicStackSlot"),
-        Opcode::DynamicStackLoad => unimplemented!("DynamicStackLoad"),
-        Opcode::DynamicStackStore => unimplemented!("DynamicStackStore"),
+        Opcode::DynamicStackAddr => {
+            let slot = match inst {
+                InstructionData::DynamicStackLoad {
+                    dynamic_stack_slot, ..
+                } => dynamic_stack_slot,
+                _ => unreachable!(),
+            };
+            assign_or_memtrap({
+                AddressSize::try_from(ctrl_ty).and_then(|addr_size| {
+                    let addr = state.dynamic_stack_address(addr_size, slot)?;
+                    let dv = DataValue::try_from(addr)?;
+                    Ok(dv.into())
+                })
+            })
+        }
+        Opcode::DynamicStackLoad => {
+            let slot = match inst {
+                InstructionData::DynamicStackLoad {
+                    dynamic_stack_slot, ..
+                } => dynamic_stack_slot,
+                _ => unreachable!(),
+            };
+            let mem_flags = MemFlags::new();
+            assign_or_memtrap({
+                state
+                    .dynamic_stack_address(AddressSize::_64, slot)
+                    .and_then(|addr| state.checked_load(addr, ctrl_ty, mem_flags))
+            })
+        }
+        Opcode::DynamicStackStore => {
+            let slot = match inst {
+                InstructionData::DynamicStackStore {
+                    dynamic_stack_slot, ..
+                } => dynamic_stack_slot,
+                _ => unreachable!(),
+            };
+            let arg = arg(0);
+            let mem_flags = MemFlags::new();
+            continue_or_memtrap({
+                state
+                    .dynamic_stack_address(AddressSize::_64, slot)
+                    .and_then(|addr| state.checked_store(addr, arg, mem_flags))
+            })
+        }
         Opcode::GlobalValue | Opcode::SymbolValue | Opcode::TlsValue => {
             if let InstructionData::UnaryGlobalValue { global_value, .. } = inst {
                 assign_or_memtrap(state.resolve_global_value(global_value))
@@ -856,20 +926,8 @@ where
         Opcode::Fneg => unary(DataValueExt::neg, arg(0))?,
         Opcode::Fabs => unary(DataValueExt::abs, arg(0))?,
         Opcode::Fcopysign => binary(DataValueExt::copysign, arg(0), arg(1))?,
-        Opcode::Fmin => assign(match (arg(0), arg(1)) {
-            (a, _) if a.is_nan()? => a,
-            (_, b) if b.is_nan()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => a,
-            (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => b,
-            (a, b) => a.smin(b)?,
-        }),
-        Opcode::Fmax => assign(match (arg(0), arg(1)) {
-            (a, _) if a.is_nan()? => a,
-            (_, b) if b.is_nan()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => a,
-            (a, b) => a.smax(b)?,
-        }),
+        Opcode::Fmin => binary(fmin, arg(0), arg(1))?,
+        Opcode::Fmax => binary(fmax, arg(0), arg(1))?,
         Opcode::Ceil => unary(DataValueExt::ceil, arg(0))?,
         Opcode::Floor => unary(DataValueExt::floor, arg(0))?,
         Opcode::Trunc => unary(DataValueExt::trunc, arg(0))?,
@@ -885,6 +943,20 @@ where
                     "Only little endian bitcasts on vectors are supported"
                 );
                 extractlanes(&arg(0), ctrl_ty)?
+            } else if inst.opcode() == Opcode::Bitcast && ctrl_ty.is_vector() {
+                assert_eq!(
+                    inst.memflags()
+                        .expect("byte order flag to be set")
+                        .endianness(Endianness::Little),
+                    Endianness::Little,
+                    "Only little endian bitcasts on vectors are supported"
+                );
+                // Reinterpret the bytes of the scalar as the lanes of the vector.
+                let bits = arg(0)
+                    .convert(ValueConversionKind::Exact(input_ty.as_int()))?
+                    .into_int_unsigned()?;
+                let vector = DataValueExt::vector(bits.to_le_bytes(), ctrl_ty)?;
+                extractlanes(&vector, ctrl_ty)?
             } else {
                 extractlanes(&arg(0), input_ty)?
                     .into_iter()
@@ -1275,17 +1347,113 @@ where
             assign(binary_pairwise(arg(0), arg(1), ctrl_ty, DataValueExt::add)?)
         }
         Opcode::ExtractVector => {
-            unimplemented!("ExtractVector not supported");
-        }
-        Opcode::GetFramePointer => unimplemented!("GetFramePointer"),
-        Opcode::GetStackPointer => unimplemented!("GetStackPointer"),
-        Opcode::GetReturnAddress => unimplemented!("GetReturnAddress"),
-        Opcode::X86Pshufb => unimplemented!("X86Pshufb"),
-        Opcode::X86Blendv => unimplemented!("X86Blendv"),
-        Opcode::X86Pmulhrsw => unimplemented!("X86Pmulhrsw"),
-        Opcode::X86Pmaddubsw => unimplemented!("X86Pmaddubsw"),
-        Opcode::X86Cvtt2dq => unimplemented!("X86Cvtt2dq"),
-        Opcode::StackSwitch => unimplemented!("StackSwitch"),
+            // With a scale of one, a dynamic vector consists of a single fixed vector.
+            let idx = imm().into_int_unsigned()?;
+            if idx != 0 {
+                return Err(StepError::ValueError(ValueError::InvalidValue(ctrl_ty)));
+            }
+            assign(arg(0))
+        }
+        Opcode::GetFramePointer | Opcode::GetStackPointer | Opcode::GetReturnAddress => {
+            assign_or_memtrap({
+                AddressSize::try_from(ctrl_ty).and_then(|addr_size| {
+                    let addr = match inst.opcode() {
+                        Opcode::GetFramePointer => state.frame_pointer(addr_size)?,
+                        Opcode::GetStackPointer => state.stack_pointer(addr_size)?,
+                        Opcode::GetReturnAddress => state.return_address(addr_size)?,
+                        _ => unreachable!(),
+                    };
+                    let dv = DataValue::try_from(addr)?;
+                    Ok(dv.into())
+                })
+            })
+        }
+        Opcode::X86Pshufb => {
+            let x = DataValueExt::into_array(&arg(0))?;
+            let s = DataValueExt::into_array(&arg(1))?;
+            let mut new = [0u8; 16];
+            for i in 0..new.len() {
+                if s[i] & 0x80 == 0 {
+                    new[i] = x[(s[i] & 0x0f) as usize];
+                } // else leave as 0
+            }
+            assign(DataValueExt::vector(new, types::I8X16)?)
+        }
+        Opcode::X86Blendv => {
+            // Only the top bit of each lane matters, so lanes are handled as integers.
+            let ty = ctrl_ty.as_int();
+            let c = extractlanes(&arg(0), ty)?;
+            let x = extractlanes(&arg(1), ty)?;
+            let y = extractlanes(&arg(2), ty)?;
+            let new_vec = c
+                .into_iter()
+                .zip(x.into_iter().zip(y.into_iter()))
+                .map(|(c, (x, y))| Ok(if c.into_int_signed()? < 0 { x } else { y }))
+                .collect::<ValueResult<SimdVec<_>>>()?;
+            assign(vectorizelanes(&new_vec, ctrl_ty)?)
+        }
+        Opcode::X86Pmulhrsw => {
+            // Like `sqmul_round_sat`, except that the result wraps instead of saturating.
+            let lane_type = ctrl_ty.lane_type();
+            let arg0 = extractlanes(&arg(0), ctrl_ty)?;
+            let arg1 = extractlanes(&arg(1), ctrl_ty)?;
+            let new_vec = arg0
+                .into_iter()
+                .zip(arg1.into_iter())
+                .map(|(x, y)| {
+                    let x = x.into_int_signed()?;
+                    let y = y.into_int_signed()?;
+                    let z = (x * y + (1 << (lane_type.bits() - 2))) >> (lane_type.bits() - 1);
+                    DataValueExt::int(z, lane_type)
+                })
+                .collect::<ValueResult<SimdVec<_>>>()?;
+            assign(vectorizelanes(&new_vec, ctrl_ty)?)
+        }
+        Opcode::X86Pmaddubsw => {
+            // Signed bytes from the first argument are multiplied by unsigned bytes from the
+            // second one.
+            let x = DataValueExt::into_array(&arg(0))?;
+            let y = DataValueExt::into_array(&arg(1))?;
+            let new_vec = x
+                .chunks(2)
+                .zip(y.chunks(2))
+                .map(|(x, y)| {
+                    let sum =
+                        (x[0] as i8 as i32) * (y[0] as i32) + (x[1] as i8 as i32) * (y[1] as i32);
+                    let sum = sum.clamp(i16::MIN.into(), i16::MAX.into());
+                    DataValueExt::int(sum.into(), types::I16)
+                })
+                .collect::<ValueResult<SimdVec<_>>>()?;
+            assign(vectorizelanes(&new_vec, types::I16X8)?)
+        }
+        Opcode::X86Cvtt2dq => {
+            // Lanes are converted to 32-bit integers, with NaN and out of bounds lanes producing
+            // `i32::MIN`, and then sign extended to the result's lane type.
+            let in_ty = inst_context.type_of(inst_context.args()[0]).unwrap();
+            let cvt = |x: DataValue| -> ValueResult<DataValue> {
+                let x = if x.is_nan()? {
+                    i32::MIN.into()
+                } else {
+                    let x = x.into_float()? as i128;
+                    if x < i32::MIN.into() || x > i32::MAX.into() {
+                        i32::MIN.into()
+                    } else {
+                        x
+                    }
+                };
+                DataValue::int(x, ctrl_ty.lane_type())
+            };
+            let x = extractlanes(&arg(0), in_ty)?;
+            assign(vectorizelanes(
+                &x.into_iter()
+                    .map(cvt)
+                    .collect::<ValueResult<SimdVec<DataValue>>>()?,
+                ctrl_ty,
+            )?)
+        }
+        // The state that needs to be saved to switch stacks is platform specific, so there's no
+        // way to interpret this.
+        Opcode::StackSwitch => return Err(StepError::Unsupported(inst.opcode())),
     })
 }
 
@@ -1299,6 +1467,8 @@ pub enum StepError {
     ValueError(#[from] ValueError),
     #[error("failed to access memory")]
     MemoryError(#[from] MemoryError),
+    #[error("the interpreter does not support the following instruction: {0}")]
+    Unsupported(Opcode),
 }
 
 /// Enumerate the ways in which the control flow can change based on a single step in a Cranelift
@@ -1392,6 +1562,28 @@ fn icmp(
     Ok(vectorizelanes(&res, dst_ty)?)
 }
 
+/// Returns the smaller of two floats, propagating NaNs and treating -0.0 as smaller than +0.0.
+fn fmin(a: DataValue, b: DataValue) -> ValueResult<DataValue> {
+    Ok(match (a, b) {
+        (a, _) if a.is_nan()? => a,
+        (_, b) if b.is_nan()? => b,
+        (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => a,
+        (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => b,
+        (a, b) => a.smin(b)?,
+    })
+}
+
+/// Returns the larger of two floats, propagating NaNs and treating +0.0 as larger than -0.0.
+fn fmax(a: DataValue, b: DataValue) -> ValueResult<DataValue> {
+    Ok(match (a, b) {
+        (a, _) if a.is_nan()? => a,
+        (_, b) if b.is_nan()? => b,
+        (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => b,
+        (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => a,
+        (a, b) => a.smax(b)?,
+    })
+}
+
 /// Compare two values using the given floating point condition `code`.
 fn fcmp(code: FloatCC, left: &DataValue, right: &DataValue) -> ValueResult<bool> {
     Ok(match code {
@@ -1428,25 +1620,19 @@ pub(crate) fn extractlanes(
         return Ok(lanes);
     }
 
-    let iterations = match lane_type {
-        types::I8 => 1,
-        types::I16 | types::F16 => 2,
-        types::I32 | types::F32 => 4,
-        types::I64 | types::F64 => 8,
-        _ => unimplemented!("vectors with lanes wider than 64-bits are currently unsupported."),
-    };
+    let iterations = lane_type.bytes();
 
     let x = x.into_array()?;
     for i in 0..vector_type.lane_count() {
-        let mut lane: i128 = 0;
+        let mut lane: u128 = 0;
         for j in 0..iterations {
-            lane += (x[((i * iterations) + j) as usize] as i128) << (8 * j);
+            lane |= (x[((i * iterations) + j) as usize] as u128) << (8 * j);
         }
 
-        let lane_val: DataValue = if lane_type.is_float() {
-            DataValueExt::float(lane as u64, lane_type)?
-        } else {
-            DataValueExt::int(lane, lane_type)?
+        let lane_val: DataValue = match lane_type {
+            types::F128 => DataValue::F128(Ieee128::with_bits(lane)),
+            _ if lane_type.is_float() => DataValueExt::float(lane as u64, lane_type)?,
+            _ => DataValueExt::int(lane as i128, lane_type)?,
         };
         lanes.push(lane_val);
     }
@@ -1467,19 +1653,13 @@ fn vectorizelanes(x: &[DataValue], vector_type: types::Type) -> ValueResult<Data
 /// Convert a Rust array of [Value] back into a `Value::vector`.
 fn vectorizelanes_all(x: &[DataValue], vector_type: types::Type) -> ValueResult<DataValue> {
     let lane_type = vector_type.lane_type();
-    let iterations = match lane_type {
-        types::I8 => 1,
-        types::I16 | types::F16 => 2,
-        types::I32 | types::F32 => 4,
-        types::I64 | types::F64 => 8,
-        _ => unimplemented!("vectors with lanes wider than 64-bits are currently unsupported."),
-    };
+    let iterations = lane_type.bytes() as usize;
     let mut result: [u8; 16] = [0; 16];
     for (i, val) in x.iter().enumerate() {
-        let lane_val: i128 = val
+        let lane_val: u128 = val
             .clone()
             .convert(ValueConversionKind::Exact(lane_type.as_int()))?
-            .into_int_unsigned()? as i128;
+            .into_int_unsigned()?;
 
         for j in 0..iterations {
             result[(i * iterations) + j] = (lane_val >> (8 * j)) as u8;
@@ -287,6 +287,7 @@ impl DataValueExt for DataValue {
 
     fn float(bits: u64, ty: Type) -> ValueResult<Self> {
         match ty {
+            types::F16 => Ok(DataValue::F16(Ieee16::with_bits(u16::try_from(bits)?))),
             types::F32 => Ok(DataValue::F32(Ieee32::with_bits(u32::try_from(bits)?))),
             types::F64 => Ok(DataValue::F64(Ieee64::with_bits(bits))),
             _ => Err(ValueError::InvalidType(ValueTypeClass::Float, ty)),
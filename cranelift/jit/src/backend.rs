@@ -2,6 +2,8 @@
 
 use crate::{compiled_blob::CompiledBlob, memory::BranchProtection, memory::Memory};
 use cranelift_codegen::binemit::Reloc;
+use cranelift_codegen::cursor::{Cursor, FuncCursor};
+use cranelift_codegen::ir::InstBuilder;
 use cranelift_codegen::isa::{OwnedTargetIsa, TargetIsa};
 use cranelift_codegen::settings::Configurable;
 use cranelift_codegen::{ir, settings, FinalizedMachReloc};
@@ -16,9 +18,11 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::CString;
 use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
 use target_lexicon::PointerWidth;
 
 const WRITABLE_DATA_ALIGNMENT: u64 = 0x8;
@@ -31,8 +35,21 @@ pub struct JITBuilder {
     lookup_symbols: Vec<Box<dyn Fn(&str) -> Option<*const u8> + Send>>,
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
     hotswap_enabled: bool,
+    lazy_compile_hook: Option<LazyCompileHook>,
+    lazy_compile_failure: Option<LazyCompileFailure>,
 }
 
+/// The hook called by lazy stubs to compile their function. See
+/// [`JITBuilder::lazy_compile_hook`].
+type LazyCompileHook = Box<dyn Fn(&mut JITModule, FuncId) -> ModuleResult<()> + Send + Sync>;
+
+/// The callback called by lazy stubs when compiling their function fails. See
+/// [`JITBuilder::lazy_compile_failure`].
+type LazyCompileFailure = Box<dyn Fn(FuncId, ModuleError) + Send + Sync>;
+
+/// The trap code of the trap executed by a lazy stub whose function failed to compile.
+const LAZY_COMPILE_FAILED: ir::TrapCode = ir::TrapCode::unwrap_user(1);
+
 impl JITBuilder {
     /// Create a new `JITBuilder`.
     ///
@@ -95,6 +112,8 @@ impl JITBuilder {
             lookup_symbols,
             libcall_names,
             hotswap_enabled: false,
+            lazy_compile_hook: None,
+            lazy_compile_failure: None,
         }
     }
 
@@ -154,6 +173,44 @@ impl JITBuilder {
         self.hotswap_enabled = enabled;
         self
     }
+
+    /// Set the hook used to compile functions defined with
+    /// [`JITModule::define_lazy_function`].
+    ///
+    /// The hook is called with the module and the function's id the first time its lazy stub is
+    /// called, and must define the function with [`Module::define_function`]. It may declare
+    /// and define other functions and data objects too. The module then finalizes the new
+    /// definitions and the stub patches the function's GOT entry to point to the compiled code,
+    /// so later calls don't go through the stub.
+    ///
+    /// Lazy stubs may only be called within [`JITModule::with_lazy_compilation`], which is what
+    /// gives the hook access to the module. Stubs called concurrently from several threads
+    /// compile one function at a time, and the hook must not itself call any lazy stub.
+    ///
+    /// If the hook returns an error or panics, the stub passes the error to the callback set with
+    /// [`JITBuilder::lazy_compile_failure`] and then traps.
+    pub fn lazy_compile_hook(
+        &mut self,
+        hook: Box<dyn Fn(&mut JITModule, FuncId) -> ModuleResult<()> + Send + Sync>,
+    ) -> &mut Self {
+        self.lazy_compile_hook = Some(hook);
+        self
+    }
+
+    /// Set the callback called when a lazy stub fails to compile its function.
+    ///
+    /// The callback is called with the function's id and the error, which is either the error
+    /// returned by the hook set with [`JITBuilder::lazy_compile_hook`], or describes why the hook
+    /// couldn't be called or didn't define the function. Once the callback returns, the stub
+    /// executes a trap instruction, just like it does when no callback is set. The callback may
+    /// also not return, for example by exiting the process, but it must not unwind.
+    pub fn lazy_compile_failure(
+        &mut self,
+        callback: Box<dyn Fn(FuncId, ModuleError) + Send + Sync>,
+    ) -> &mut Self {
+        self.lazy_compile_failure = Some(callback);
+        self
+    }
 }
 
 /// A pending update to the GOT.
@@ -196,6 +253,15 @@ pub struct JITModule {
     functions_to_finalize: Vec<FuncId>,
     data_objects_to_finalize: Vec<DataId>,
 
+    /// The state shared with lazy stubs, which refer to it with a pointer that stays valid when
+    /// the module is moved.
+    lazy_state: Option<Arc<LazyState>>,
+    lazy_stubs: SecondaryMap<FuncId, Option<SendWrapper<*const u8>>>,
+    lazy_stub_cells: SecondaryMap<FuncId, Option<SendWrapper<NonNull<AtomicPtr<u8>>>>>,
+
+    /// Code of functions which have been replaced using `prepare_for_function_redefine`.
+    replaced_functions: Vec<CompiledBlob>,
+
     /// Updates to the GOT awaiting relocations to be made and region protections to be set
     pending_got_updates: Vec<GotUpdate>,
 }
@@ -318,6 +384,9 @@ impl JITModule {
                         let func_id = FuncId::from_name(name);
                         match &self.compiled_functions[func_id] {
                             Some(compiled) => return compiled.ptr,
+                            None if self.lazy_stubs[func_id].is_some() => {
+                                return self.lazy_stubs[func_id].unwrap().0;
+                            }
                             None => {
                                 let decl = self.declarations.get_function_decl(func_id);
                                 (&decl.name, decl.linkage)
@@ -410,23 +479,29 @@ impl JITModule {
 
     /// Returns the address of a finalized function.
     ///
-    /// The pointer remains valid until either [`JITModule::free_memory`] is called or in the future
-    /// some way of deallocating this individual function is used.
+    /// For a function defined with [`JITModule::define_lazy_function`] which hasn't been compiled
+    /// yet, this is the address of its lazy stub.
+    ///
+    /// The pointer remains valid until either [`JITModule::free_memory`] or
+    /// [`JITModule::free_function`] is called, or the function is replaced and
+    /// [`JITModule::free_replaced_functions`] is called.
     pub fn get_finalized_function(&self, func_id: FuncId) -> *const u8 {
         let info = &self.compiled_functions[func_id];
         assert!(
             !self.functions_to_finalize.iter().any(|x| *x == func_id),
             "function not yet finalized"
         );
-        info.as_ref()
-            .expect("function must be compiled before it can be finalized")
-            .ptr
+        match (info, self.lazy_stubs[func_id]) {
+            (Some(compiled), _) => compiled.ptr,
+            (None, Some(stub)) => stub.0,
+            (None, None) => panic!("function must be compiled before it can be finalized"),
+        }
     }
 
     /// Returns the address and size of a finalized data object.
     ///
-    /// The pointer remains valid until either [`JITModule::free_memory`] is called or in the future
-    /// some way of deallocating this individual data object is used.
+    /// The pointer remains valid until either [`JITModule::free_memory`] or
+    /// [`JITModule::free_data`] is called.
     pub fn get_finalized_data(&self, data_id: DataId) -> (*const u8, usize) {
         let info = &self.compiled_data_objects[data_id];
         assert!(
@@ -539,6 +614,16 @@ impl JITModule {
             compiled_data_objects: SecondaryMap::new(),
             functions_to_finalize: Vec::new(),
             data_objects_to_finalize: Vec::new(),
+            lazy_state: builder.lazy_compile_hook.map(|hook| {
+                Arc::new(LazyState {
+                    hook,
+                    on_failure: builder.lazy_compile_failure,
+                    module: Mutex::new(None),
+                })
+            }),
+            lazy_stubs: SecondaryMap::new(),
+            lazy_stub_cells: SecondaryMap::new(),
+            replaced_functions: Vec::new(),
             pending_got_updates: Vec::new(),
         };
 
@@ -571,6 +656,9 @@ impl JITModule {
     /// Allow a single future `define_function` on a previously defined function. This allows for
     /// hot code swapping and lazy compilation of functions.
     ///
+    /// The code of the previous definition stays allocated, as it may still be executing, until
+    /// [`JITModule::free_replaced_functions`] is called.
+    ///
     /// This requires hotswap support to be enabled first using [`JITBuilder::hotswap`].
     pub fn prepare_for_function_redefine(&mut self, func_id: FuncId) -> ModuleResult<()> {
         assert!(self.hotswap_enabled, "Hotswap support is not enabled");
@@ -588,12 +676,322 @@ impl JITModule {
             )));
         }
 
-        self.compiled_functions[func_id] = None;
+        let replaced = self.compiled_functions[func_id].take().unwrap();
+        self.replaced_functions.push(replaced);
+
+        Ok(())
+    }
+
+    /// Free the code of all functions replaced using
+    /// [`JITModule::prepare_for_function_redefine`] so far.
+    ///
+    /// # Safety
+    ///
+    /// None of the replaced functions may be executing, and pointers to them, such as those
+    /// previously returned by [`JITModule::get_finalized_function`] or embedded in code which
+    /// was compiled before hotswap support was enabled, must not be called afterwards.
+    pub unsafe fn free_replaced_functions(&mut self) {
+        for replaced in std::mem::take(&mut self.replaced_functions) {
+            self.pending_got_updates
+                .retain(|update| update.ptr != replaced.ptr.cast_const());
+            self.memory.code.free(replaced.ptr, replaced.size);
+        }
+    }
+
+    /// Free the code of a defined function, after which it can be defined again.
+    ///
+    /// The function's GOT entry is reset to its lazy stub if it has one, in which case the next
+    /// call compiles it again, and to null otherwise.
+    ///
+    /// # Safety
+    ///
+    /// The function may not be executing, and pointers to it, such as those previously returned
+    /// by [`JITModule::get_finalized_function`] or embedded in other functions calling it
+    /// directly, must not be called afterwards.
+    pub unsafe fn free_function(&mut self, func_id: FuncId) -> ModuleResult<()> {
+        let Some(compiled) = self.compiled_functions[func_id].take() else {
+            let decl = self.declarations.get_function_decl(func_id);
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "Tried to free not yet defined function {}",
+                decl.linkage_name(func_id),
+            )));
+        };
+
+        self.functions_to_finalize.retain(|&id| id != func_id);
+        self.pending_got_updates
+            .retain(|update| update.ptr != compiled.ptr.cast_const());
+        if let Some(cell) = self.lazy_stub_cells[func_id] {
+            cell.0.as_ref().store(ptr::null_mut(), Ordering::SeqCst);
+        }
+        if let Some(got_entry) = self.function_got_entries[func_id] {
+            let ptr = self.lazy_stubs[func_id].map_or(ptr::null(), |stub| stub.0);
+            got_entry.0.as_ref().store(ptr.cast_mut(), Ordering::SeqCst);
+        }
+        self.memory.code.free(compiled.ptr, compiled.size);
+
+        Ok(())
+    }
+
+    /// Free the memory of a defined data object, after which it can be defined again.
+    ///
+    /// The data object's GOT entry is reset to null.
+    ///
+    /// # Safety
+    ///
+    /// Pointers to the data object, such as those previously returned by
+    /// [`JITModule::get_finalized_data`] or embedded in code or data referring to it, must not be
+    /// used afterwards.
+    pub unsafe fn free_data(&mut self, data_id: DataId) -> ModuleResult<()> {
+        let decl = self.declarations.get_data_decl(data_id);
+        let Some(compiled) = self.compiled_data_objects[data_id].take() else {
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "Tried to free not yet defined data object {}",
+                decl.linkage_name(data_id),
+            )));
+        };
+
+        self.data_objects_to_finalize.retain(|&id| id != data_id);
+        if let Some(got_entry) = self.data_object_got_entries[data_id] {
+            self.pending_got_updates
+                .retain(|update| update.entry != got_entry.0);
+            got_entry
+                .0
+                .as_ref()
+                .store(ptr::null_mut(), Ordering::SeqCst);
+        }
+        if decl.writable {
+            self.memory.writable.free(compiled.ptr, compiled.size);
+        } else {
+            self.memory.readonly.free(compiled.ptr, compiled.size);
+        }
+
+        Ok(())
+    }
+
+    /// Define a function with a stub which compiles it on its first call.
+    ///
+    /// The stub calls the hook set with [`JITBuilder::lazy_compile_hook`] to define the function,
+    /// patches the function's GOT entry to point to the compiled code and then calls it. Once
+    /// [`JITModule::finalize_definitions`] has been called, the stub can be called through the
+    /// GOT or the pointer returned by [`JITModule::get_finalized_function`], from within
+    /// [`JITModule::with_lazy_compilation`]. The function can also still be defined eagerly with
+    /// [`Module::define_function`], in which case the hook isn't called.
+    ///
+    /// This requires PIC code and a lazy compile hook.
+    pub fn define_lazy_function(&mut self, func_id: FuncId) -> ModuleResult<()> {
+        let decl = self.declarations.get_function_decl(func_id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(
+                decl.linkage_name(func_id).into_owned(),
+            ));
+        }
+
+        if self.compiled_functions[func_id].is_some() || self.lazy_stubs[func_id].is_some() {
+            return Err(ModuleError::DuplicateDefinition(
+                decl.linkage_name(func_id).into_owned(),
+            ));
+        }
+
+        if !self.isa.flags().is_pic() {
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "Lazy functions require PIC code"
+            )));
+        }
+
+        let state = match &self.lazy_state {
+            Some(state) => Arc::as_ptr(state),
+            None => {
+                return Err(ModuleError::Backend(anyhow::anyhow!(
+                    "Tried to define lazy function {} without a lazy compile hook",
+                    decl.linkage_name(func_id),
+                )))
+            }
+        };
+
+        // The stub caches the compiled code in its own cell rather than relying on the GOT
+        // entry, as direct calls to the stub don't go through the GOT.
+        let cell = self.new_got_entry(ptr::null());
+        let got_entry = self.function_got_entries[func_id].unwrap().0;
+        let func = self.lazy_stub(func_id, state, cell, got_entry);
+
+        let mut ctx = cranelift_codegen::Context::for_function(func);
+        let res = ctx.compile(self.isa(), &mut ControlPlane::default())?;
+        let alignment = res.buffer.alignment as u64;
+        let compiled_code = ctx.compiled_code().unwrap();
+        assert!(
+            compiled_code.buffer.relocs().is_empty(),
+            "lazy stubs don't need relocations"
+        );
+
+        let size = compiled_code.code_info().total_size as usize;
+        let align = alignment
+            .max(self.isa.function_alignment().minimum as u64)
+            .max(self.isa.symbol_alignment());
+        let ptr = self
+            .memory
+            .code
+            .allocate(size, align)
+            .map_err(|e| ModuleError::Allocation {
+                message: "unable to alloc lazy stub",
+                err: e,
+            })?;
+
+        {
+            let mem = unsafe { std::slice::from_raw_parts_mut(ptr, size) };
+            mem.copy_from_slice(compiled_code.code_buffer());
+        }
 
-        // FIXME return some kind of handle that allows for deallocating the function
+        let decl = self.declarations.get_function_decl(func_id);
+        self.record_function_for_perf(ptr, size, &format!("{}@lazy", decl.linkage_name(func_id)));
+        self.lazy_stubs[func_id] = Some(SendWrapper(ptr));
+        self.lazy_stub_cells[func_id] = Some(SendWrapper(cell));
+        self.pending_got_updates.push(GotUpdate {
+            entry: got_entry,
+            ptr,
+        });
 
         Ok(())
     }
+
+    /// Run `f`, which may call lazy stubs, with lazy compilation enabled.
+    ///
+    /// While `f` runs, lazy stubs called by it or by other threads pass this module to the hook
+    /// set with [`JITBuilder::lazy_compile_hook`] to compile their function. A lazy stub called
+    /// while no call to this method is running fails to compile its function.
+    pub fn with_lazy_compilation<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let Some(state) = self.lazy_state.clone() else {
+            return f();
+        };
+
+        /// Unsets the module again, even if `f` panics.
+        struct Reset<'a>(&'a LazyState);
+        impl Drop for Reset<'_> {
+            fn drop(&mut self) {
+                *self.0.module.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            }
+        }
+
+        *state.module.lock().unwrap() = Some(SendWrapper(self as *mut JITModule));
+        let _reset = Reset(&state);
+        f()
+    }
+
+    /// Build the lazy stub for `func_id`, which has the same signature as the function and
+    /// calls `lazy_compile` with `state` unless `cell` already holds the compiled code. The stub
+    /// traps if `lazy_compile` fails.
+    fn lazy_stub(
+        &self,
+        func_id: FuncId,
+        state: *const LazyState,
+        cell: NonNull<AtomicPtr<u8>>,
+        got_entry: NonNull<AtomicPtr<u8>>,
+    ) -> ir::Function {
+        let ptr_ty = self.isa.pointer_type();
+        let sig = self
+            .declarations
+            .get_function_decl(func_id)
+            .signature
+            .clone();
+        let mut func = ir::Function::with_name_signature(ir::UserFuncName::default(), sig.clone());
+        let callee_sig = func.import_signature(sig.clone());
+        let mut compile_sig = ir::Signature::new(self.isa.default_call_conv());
+        compile_sig.params.push(ir::AbiParam::new(ptr_ty));
+        compile_sig.params.push(ir::AbiParam::new(ir::types::I32));
+        compile_sig.returns.push(ir::AbiParam::new(ptr_ty));
+        let compile_sig = func.import_signature(compile_sig);
+
+        let entry = func.dfg.make_block();
+        let compile = func.dfg.make_block();
+        let call = func.dfg.make_block();
+        let args: Vec<ir::Value> = sig
+            .params
+            .iter()
+            .map(|param| func.dfg.append_block_param(entry, param.value_type))
+            .collect();
+        let target = func.dfg.append_block_param(call, ptr_ty);
+        let flags = ir::MemFlags::trusted();
+
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(entry);
+        let cell = pos.ins().iconst(ptr_ty, cell.as_ptr() as i64);
+        let compiled = pos.ins().atomic_load(ptr_ty, flags, cell);
+        pos.ins().brif(compiled, call, &[compiled], compile, &[]);
+
+        pos.insert_block(compile);
+        let state = pos.ins().iconst(ptr_ty, state as i64);
+        let id = pos
+            .ins()
+            .iconst(ir::types::I32, i64::from(func_id.as_u32()));
+        let callback = pos.ins().iconst(ptr_ty, lazy_compile as *const () as i64);
+        let inst = pos.ins().call_indirect(compile_sig, callback, &[state, id]);
+        let compiled = pos.func.dfg.first_result(inst);
+        pos.ins().trapz(compiled, LAZY_COMPILE_FAILED);
+        pos.ins().atomic_store(flags, compiled, cell);
+        let got_entry = pos.ins().iconst(ptr_ty, got_entry.as_ptr() as i64);
+        pos.ins().atomic_store(flags, compiled, got_entry);
+        pos.ins().jump(call, &[compiled]);
+
+        pos.insert_block(call);
+        let inst = pos.ins().call_indirect(callee_sig, target, &args);
+        let results = pos.func.dfg.inst_results(inst).to_vec();
+        pos.ins().return_(&results);
+
+        func
+    }
+}
+
+/// The state shared between a `JITModule` and its lazy stubs.
+struct LazyState {
+    hook: LazyCompileHook,
+    on_failure: Option<LazyCompileFailure>,
+
+    /// The module, while [`JITModule::with_lazy_compilation`] is running. The lock is held for
+    /// the duration of each compilation.
+    module: Mutex<Option<SendWrapper<*mut JITModule>>>,
+}
+
+/// Called by lazy stubs to compile the function `func_id` with the hook in `state`.
+///
+/// Returns a null pointer, which makes the stub trap, if that fails.
+extern "C" fn lazy_compile(state: *const LazyState, func_id: u32) -> *const u8 {
+    let state = unsafe { &*state };
+    let func_id = FuncId::from_u32(func_id);
+    // Unwinding into JIT code isn't supported.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let guard = state.module.lock().map_err(|_| {
+            ModuleError::Backend(anyhow::anyhow!("an earlier lazy compilation panicked"))
+        })?;
+        let Some(module) = *guard else {
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "lazy function called outside of `JITModule::with_lazy_compilation`"
+            )));
+        };
+        // SAFETY: `with_lazy_compilation` holds a mutable borrow of the module while the
+        // pointer is set, and the lock is held until we're done with it.
+        let module = unsafe { &mut *module.0 };
+        // Another thread may have compiled the function while we waited for the lock.
+        if module.compiled_functions[func_id].is_none() {
+            (state.hook)(module, func_id)?;
+            if module.compiled_functions[func_id].is_none() {
+                let decl = module.declarations.get_function_decl(func_id);
+                return Err(ModuleError::Backend(anyhow::anyhow!(
+                    "lazy compile hook didn't define function {}",
+                    decl.linkage_name(func_id),
+                )));
+            }
+        }
+        module.finalize_definitions()?;
+        Ok(module.get_finalized_function(func_id))
+    }));
+    let err = match result {
+        Ok(Ok(ptr)) => return ptr,
+        Ok(Err(e)) => e,
+        Err(_) => ModuleError::Backend(anyhow::anyhow!("lazy compile hook panicked")),
+    };
+    if let Some(on_failure) = &state.on_failure {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| on_failure(func_id, err)));
+    }
+    ptr::null()
 }
 
 impl Module for JITModule {
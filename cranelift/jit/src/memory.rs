@@ -18,6 +18,9 @@ struct PtrLen {
 
     ptr: *mut u8,
     len: usize,
+
+    /// The number of allocations in this region which haven't been freed yet.
+    live: usize,
 }
 
 impl PtrLen {
@@ -29,6 +32,7 @@ impl PtrLen {
 
             ptr: ptr::null_mut(),
             len: 0,
+            live: 0,
         }
     }
 
@@ -44,6 +48,7 @@ impl PtrLen {
                 ptr: mmap.as_mut_ptr(),
                 map: Some(mmap),
                 len: alloc_size,
+                live: 0,
             }
         })
     }
@@ -61,6 +66,7 @@ impl PtrLen {
             Ok(Self {
                 ptr,
                 len: alloc_size,
+                live: 0,
             })
         } else {
             Err(io::Error::from(io::ErrorKind::OutOfMemory))
@@ -86,6 +92,7 @@ impl PtrLen {
             Ok(Self {
                 ptr: ptr as *mut u8,
                 len: region::page::ceil(size as *const ()) as usize,
+                live: 0,
             })
         } else {
             Err(io::Error::last_os_error())
@@ -109,7 +116,17 @@ impl Drop for PtrLen {
     }
 }
 
-// TODO: add a `Drop` impl for `cfg(target_os = "windows")`
+#[cfg(target_os = "windows")]
+impl Drop for PtrLen {
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Memory::{VirtualFree, MEM_RELEASE};
+
+        if !self.ptr.is_null() {
+            let ok = unsafe { VirtualFree(self.ptr.cast(), 0, MEM_RELEASE) };
+            assert!(ok != 0, "unable to free memory");
+        }
+    }
+}
 
 /// Type of branch protection to apply to executable memory.
 #[derive(Clone, Debug, PartialEq)]
@@ -123,7 +140,7 @@ pub(crate) enum BranchProtection {
 /// JIT memory manager. This manages pages of suitably aligned and
 /// accessible memory. Memory will be leaked by default to have
 /// function pointers remain valid for the remainder of the
-/// program's life.
+/// program's life, unless it is explicitly freed with `free`.
 pub(crate) struct Memory {
     allocations: Vec<PtrLen>,
     already_protected: usize,
@@ -146,8 +163,11 @@ impl Memory {
     }
 
     fn finish_current(&mut self) {
-        self.allocations
-            .push(mem::replace(&mut self.current, PtrLen::new()));
+        let current = mem::replace(&mut self.current, PtrLen::new());
+        // A region without live allocations can be returned right away.
+        if current.live > 0 {
+            self.allocations.push(current);
+        }
         self.position = 0;
     }
 
@@ -162,6 +182,9 @@ impl Memory {
             // TODO: Ensure overflow is not possible.
             let ptr = unsafe { self.current.ptr.add(self.position) };
             self.position += size;
+            if size != 0 {
+                self.current.live += 1;
+            }
             return Ok(ptr);
         }
 
@@ -169,11 +192,51 @@ impl Memory {
 
         // TODO: Allocate more at a time.
         self.current = PtrLen::with_size(size)?;
+        self.current.live = 1;
         self.position = size;
 
         Ok(self.current.ptr)
     }
 
+    /// Free the allocation of `size` bytes at `ptr`.
+    ///
+    /// Allocations share pages, so the memory is only returned to the system once all
+    /// allocations in the same region have been freed.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` and `size` must be those of an allocation made by this `Memory` which hasn't been
+    /// freed yet, and nothing may use the allocation afterwards.
+    pub(crate) unsafe fn free(&mut self, ptr: *const u8, size: usize) {
+        // Zero-sized allocations aren't counted, as they may not be inside their region.
+        if size == 0 {
+            return;
+        }
+
+        let contains = |region: &PtrLen| {
+            let start = region.ptr as usize;
+            (start..start + region.len).contains(&(ptr as usize))
+        };
+
+        if contains(&self.current) {
+            self.current.live -= 1;
+            return;
+        }
+
+        let index = self
+            .allocations
+            .iter()
+            .position(contains)
+            .expect("freed memory must have been allocated by this `Memory`");
+        self.allocations[index].live -= 1;
+        if self.allocations[index].live == 0 {
+            self.allocations.remove(index);
+            if index < self.already_protected {
+                self.already_protected -= 1;
+            }
+        }
+    }
+
     /// Set all memory allocated in this `Memory` up to now as readable and executable.
     pub(crate) fn set_readable_and_executable(&mut self) -> ModuleResult<()> {
         self.finish_current();
@@ -237,3 +237,306 @@ fn empty_data_object() {
     data.define(Box::new([]));
     module.define_data(data_id, &data).unwrap();
 }
+
+#[test]
+#[cfg(target_arch = "x86_64")] // PLT entries are only supported on x86_64.
+fn lazy_function() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mut flag_builder = settings::builder();
+    flag_builder.set("use_colocated_libcalls", "false").unwrap();
+    flag_builder.set("is_pic", "true").unwrap();
+    let isa_builder = cranelift_native::builder().unwrap_or_else(|msg| {
+        panic!("host machine is not supported: {msg}");
+    });
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+
+    // The hook compiles `double` in the module the first time it's called.
+    let compiles = Arc::new(AtomicUsize::new(0));
+    let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+    builder.lazy_compile_hook({
+        let compiles = compiles.clone();
+        Box::new(move |module, func_id| {
+            compiles.fetch_add(1, Ordering::SeqCst);
+            let sig = module
+                .declarations()
+                .get_function_decl(func_id)
+                .signature
+                .clone();
+            let mut ctx = Context::new();
+            ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+            let mut func_ctx = FunctionBuilderContext::new();
+            {
+                let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+                let block = bcx.create_block();
+                bcx.switch_to_block(block);
+                bcx.append_block_params_for_function_params(block);
+                let x = bcx.block_params(block)[0];
+                let result = bcx.ins().imul_imm(x, 2);
+                bcx.ins().return_(&[result]);
+                bcx.seal_all_blocks();
+                bcx.finalize();
+            }
+            module.define_function(func_id, &mut ctx)
+        })
+    });
+    let mut module = JITModule::new(builder);
+
+    let mut sig = module.make_signature();
+    sig.params.push(AbiParam::new(types::I64));
+    sig.returns.push(AbiParam::new(types::I64));
+
+    let lazy_id = module
+        .declare_function("double", Linkage::Local, &sig)
+        .unwrap();
+    module.define_lazy_function(lazy_id).unwrap();
+
+    // A function calling the lazy one, which is resolved to its stub.
+    let caller_id = module
+        .declare_function("caller", Linkage::Local, &sig)
+        .unwrap();
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, caller_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        bcx.append_block_params_for_function_params(block);
+        let x = bcx.block_params(block)[0];
+        let callee = module.declare_func_in_func(lazy_id, &mut bcx.func);
+        let call = bcx.ins().call(callee, &[x]);
+        let result = bcx.inst_results(call)[0];
+        bcx.ins().return_(&[result]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(caller_id, &mut ctx).unwrap();
+    module.finalize_definitions().unwrap();
+
+    let stub = module.get_finalized_function(lazy_id);
+    assert_eq!(module.read_got_entry(lazy_id), stub);
+    assert_eq!(compiles.load(Ordering::SeqCst), 0);
+
+    let caller: extern "C" fn(i64) -> i64 =
+        unsafe { std::mem::transmute(module.get_finalized_function(caller_id)) };
+    let stub: extern "C" fn(i64) -> i64 = unsafe { std::mem::transmute(stub) };
+    module.with_lazy_compilation(|| {
+        assert_eq!(caller(21), 42);
+        assert_eq!(caller(4), 8);
+        assert_eq!(stub(5), 10);
+    });
+    assert_eq!(compiles.load(Ordering::SeqCst), 1);
+    let compiled = module.get_finalized_function(lazy_id);
+    assert_ne!(compiled, stub as *const u8);
+    assert_eq!(module.read_got_entry(lazy_id), compiled);
+
+    // Once compiled, calls no longer go through the hook.
+    assert_eq!(caller(3), 6);
+
+    // Freeing the function makes the next call compile it again.
+    unsafe { module.free_function(lazy_id).unwrap() };
+    assert_eq!(module.read_got_entry(lazy_id), stub as *const u8);
+    module.with_lazy_compilation(|| assert_eq!(caller(8), 16));
+    assert_eq!(compiles.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", unix))] // PLT entries are only supported on x86_64.
+fn lazy_function_failure() {
+    use std::os::unix::process::ExitStatusExt;
+
+    // A failed lazy compilation traps, so the test runs itself in a child process to observe it.
+    const CHILD: &str = "CRANELIFT_JIT_LAZY_FAILURE";
+    if let Some(mode) = std::env::var_os(CHILD) {
+        lazy_function_failure_child(mode == "callback");
+        unreachable!("the lazy stub should have trapped");
+    }
+
+    for mode in ["callback", "default"] {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["lazy_function_failure", "--exact", "--nocapture"])
+            .env(CHILD, mode)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            output.status.signal().is_some(),
+            "{mode}: {:?}\n{stderr}",
+            output.status
+        );
+        let reported = stderr.contains("failed to compile funcid0: Backend error: no code for you");
+        assert_eq!(reported, mode == "callback", "{mode}:\n{stderr}");
+    }
+}
+
+/// Calls a lazy function whose hook fails, reporting the error on stderr if `callback` is set.
+fn lazy_function_failure_child(callback: bool) {
+    let mut flag_builder = settings::builder();
+    flag_builder.set("use_colocated_libcalls", "false").unwrap();
+    flag_builder.set("is_pic", "true").unwrap();
+    let isa_builder = cranelift_native::builder().unwrap_or_else(|msg| {
+        panic!("host machine is not supported: {msg}");
+    });
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+
+    let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+    builder.lazy_compile_hook(Box::new(|_module, _func_id| {
+        Err(ModuleError::Backend(anyhow::anyhow!("no code for you")))
+    }));
+    if callback {
+        builder.lazy_compile_failure(Box::new(|func_id, err| {
+            eprintln!("failed to compile {func_id}: {err}");
+        }));
+    }
+    let mut module = JITModule::new(builder);
+
+    let lazy_id = module
+        .declare_function("lazy", Linkage::Local, &const_function_signature(&module))
+        .unwrap();
+    module.define_lazy_function(lazy_id).unwrap();
+    module.finalize_definitions().unwrap();
+
+    let lazy: extern "C" fn() -> i64 =
+        unsafe { std::mem::transmute(module.get_finalized_function(lazy_id)) };
+    module.with_lazy_compilation(|| lazy());
+}
+
+/// Defines `func_id`, of type `() -> i64`, to return `value`.
+fn define_const_function(module: &mut JITModule, func_id: FuncId, value: i64) {
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(
+        UserFuncName::user(0, func_id.as_u32()),
+        const_function_signature(module),
+    );
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let value = bcx.ins().iconst(types::I64, value);
+        bcx.ins().return_(&[value]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+}
+
+fn const_function_signature(module: &JITModule) -> Signature {
+    let mut sig = module.make_signature();
+    sig.returns.push(AbiParam::new(types::I64));
+    sig
+}
+
+#[test]
+fn free_and_redefine() {
+    let mut flag_builder = settings::builder();
+    flag_builder.set("use_colocated_libcalls", "false").unwrap();
+    // FIXME set back to true once the x64 backend supports it.
+    flag_builder.set("is_pic", "false").unwrap();
+    let isa_builder = cranelift_native::builder().unwrap_or_else(|msg| {
+        panic!("host machine is not supported: {msg}");
+    });
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+
+    let sig = const_function_signature(&module);
+    let func_id = module
+        .declare_function("value", Linkage::Local, &sig)
+        .unwrap();
+    define_const_function(&mut module, func_id, 1);
+    module.finalize_definitions().unwrap();
+    let f: extern "C" fn() -> i64 =
+        unsafe { std::mem::transmute(module.get_finalized_function(func_id)) };
+    assert_eq!(f(), 1);
+
+    unsafe { module.free_function(func_id).unwrap() };
+    assert!(unsafe { module.free_function(func_id) }.is_err());
+    define_const_function(&mut module, func_id, 2);
+    module.finalize_definitions().unwrap();
+    let f: extern "C" fn() -> i64 =
+        unsafe { std::mem::transmute(module.get_finalized_function(func_id)) };
+    assert_eq!(f(), 2);
+
+    let data_id = module
+        .declare_data("data", Linkage::Local, false, false)
+        .unwrap();
+    for contents in [[1u8, 2, 3, 4], [5, 6, 7, 8]] {
+        let mut data = DataDescription::new();
+        data.define(Box::new(contents));
+        module.define_data(data_id, &data).unwrap();
+        module.finalize_definitions().unwrap();
+        let (ptr, size) = module.get_finalized_data(data_id);
+        assert_eq!(unsafe { std::slice::from_raw_parts(ptr, size) }, contents);
+        unsafe { module.free_data(data_id).unwrap() };
+    }
+    assert!(unsafe { module.free_data(data_id) }.is_err());
+}
+
+#[test]
+#[cfg(target_arch = "x86_64")] // Hotswapping requires PIC, which is only supported on x86_64.
+fn free_replaced_functions() {
+    let mut flag_builder = settings::builder();
+    flag_builder.set("use_colocated_libcalls", "false").unwrap();
+    flag_builder.set("is_pic", "true").unwrap();
+    let isa_builder = cranelift_native::builder().unwrap_or_else(|msg| {
+        panic!("host machine is not supported: {msg}");
+    });
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+    builder.hotswap(true);
+    let mut module = JITModule::new(builder);
+
+    let sig = const_function_signature(&module);
+    let callee_id = module
+        .declare_function("callee", Linkage::Local, &sig)
+        .unwrap();
+    define_const_function(&mut module, callee_id, 1);
+
+    // A function calling the hotswapped one, which goes through its GOT entry
+    // and so observes redefinitions.
+    let caller_id = module
+        .declare_function("caller", Linkage::Local, &sig)
+        .unwrap();
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, caller_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let callee = module.declare_func_in_func(callee_id, &mut bcx.func);
+        let call = bcx.ins().call(callee, &[]);
+        let result = bcx.inst_results(call)[0];
+        bcx.ins().return_(&[result]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(caller_id, &mut ctx).unwrap();
+    module.finalize_definitions().unwrap();
+
+    let caller: extern "C" fn() -> i64 =
+        unsafe { std::mem::transmute(module.get_finalized_function(caller_id)) };
+    assert_eq!(caller(), 1);
+
+    for value in [2, 3] {
+        module.prepare_for_function_redefine(callee_id).unwrap();
+        define_const_function(&mut module, callee_id, value);
+        module.finalize_definitions().unwrap();
+        unsafe { module.free_replaced_functions() };
+
+        assert_eq!(caller(), value);
+        let callee: extern "C" fn() -> i64 =
+            unsafe { std::mem::transmute(module.get_finalized_function(callee_id)) };
+        assert_eq!(callee(), value);
+    }
+}
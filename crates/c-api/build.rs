@@ -17,8 +17,10 @@ const FEATURES: &[&str] = &[
     "GC",
     "GC_DRC",
     "GC_NULL",
+    "GC_COPYING",
     "CRANELIFT",
     "WINCH",
+    "COMPONENT_MODEL",
 ];
 // ... if you add a line above this be sure to change the other locations
 // marked WASMTIME_FEATURE_LIST
@@ -40,8 +40,10 @@ feature(threads ON)
 feature(gc ON)
 feature(gc-drc ON)
 feature(gc-null ON)
+feature(gc-copying ON)
 feature(async ON)
 feature(cranelift ON)
 feature(winch ON)
+feature(component-model ON)
 # ... if you add a line above this be sure to change the other locations
 # marked WASMTIME_FEATURE_LIST
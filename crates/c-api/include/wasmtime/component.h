@@ -0,0 +1,24 @@
+/**
+ * \file wasmtime/component.h
+ *
+ * \brief Wasmtime APIs for the WebAssembly component model.
+ *
+ * This header includes all of the APIs related to components such as
+ * #wasmtime_component_t, #wasmtime_component_linker_t and
+ * #wasmtime_component_val_t. These APIs are only available when the C API is
+ * built with the `component-model` feature enabled.
+ */
+
+#ifndef WASMTIME_COMPONENT_H
+#define WASMTIME_COMPONENT_H
+
+// IWYU pragma: begin_exports
+#include <wasmtime/component/component.h>
+#include <wasmtime/component/func.h>
+#include <wasmtime/component/instance.h>
+#include <wasmtime/component/linker.h>
+#include <wasmtime/component/resource.h>
+#include <wasmtime/component/val.h>
+// IWYU pragma: end_exports
+
+#endif // WASMTIME_COMPONENT_H
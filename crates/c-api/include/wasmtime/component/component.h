@@ -0,0 +1,162 @@
+/**
+ * \file wasmtime/component/component.h
+ *
+ * APIs for interacting with compiled components in Wasmtime.
+ */
+
+#ifndef WASMTIME_COMPONENT_COMPONENT_H
+#define WASMTIME_COMPONENT_COMPONENT_H
+
+#include <wasm.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \typedef wasmtime_component_t
+ * \brief Convenience alias for #wasmtime_component
+ *
+ * \struct wasmtime_component
+ * \brief A compiled Wasmtime component.
+ *
+ * This type corresponds to `wasmtime::component::Component` in Rust. It
+ * represents a compiled WebAssembly component which is ready to be
+ * instantiated with a #wasmtime_component_linker_t. It is safe to use a
+ * component across multiple threads simultaneously.
+ */
+typedef struct wasmtime_component wasmtime_component_t;
+
+#ifdef WASMTIME_FEATURE_COMPILER
+
+/**
+ * \brief Compiles a WebAssembly binary into a #wasmtime_component_t
+ *
+ * This function will compile a WebAssembly component binary into an owned
+ * #wasmtime_component_t. Note that the text format is not accepted here, use
+ * #wasmtime_wat2wasm first to convert text to a binary.
+ *
+ * On success the returned #wasmtime_error_t is `NULL` and the `ret` pointer is
+ * filled in with a #wasmtime_component_t. On failure the #wasmtime_error_t is
+ * non-`NULL` and the `ret` pointer is unmodified.
+ *
+ * This function does not take ownership of any of its arguments, but the
+ * returned error and component are owned by the caller.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_new(const wasm_engine_t *engine, const uint8_t *buf,
+                       size_t len, wasmtime_component_t **ret);
+
+/**
+ * \brief This function serializes compiled component artifacts as blob data.
+ *
+ * \param component the component
+ * \param ret if the conversion is successful, this byte vector is filled in
+ * with the serialized compiled component.
+ *
+ * \return a non-null error if serialization fails, or returns `NULL`. If
+ * serialization fails then `ret` isn't touched.
+ *
+ * This function does not take ownership of `component`, and the caller is
+ * expected to deallocate the returned #wasmtime_error_t and #wasm_byte_vec_t.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_serialize(const wasmtime_component_t *component,
+                             wasm_byte_vec_t *ret);
+
+#endif // WASMTIME_FEATURE_COMPILER
+
+/**
+ * \brief Build a component from serialized data.
+ *
+ * This function does not take ownership of any of its arguments, but the
+ * returned error and component are owned by the caller.
+ *
+ * This function is not safe to receive arbitrary user input. See the Rust
+ * documentation for more information on what inputs are safe to pass in here
+ * (e.g. only that of #wasmtime_component_serialize)
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_deserialize(const wasm_engine_t *engine, const uint8_t *buf,
+                               size_t len, wasmtime_component_t **ret);
+
+/**
+ * \brief Deserialize a component from an on-disk file.
+ *
+ * This function is the same as #wasmtime_component_deserialize except that it
+ * reads the data for the serialized component from the path on disk.
+ *
+ * This function does not take ownership of any of its arguments, but the
+ * returned error and component are owned by the caller.
+ *
+ * This function is not safe to receive arbitrary user input. See the Rust
+ * documentation for more information on what inputs are safe to pass in here
+ * (e.g. only that of #wasmtime_component_serialize)
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_deserialize_file(const wasm_engine_t *engine,
+                                    const char *path,
+                                    wasmtime_component_t **ret);
+
+/**
+ * \brief Creates a shallow clone of the specified component, increasing the
+ * internal reference count.
+ */
+WASM_API_EXTERN wasmtime_component_t *
+wasmtime_component_clone(const wasmtime_component_t *component);
+
+/**
+ * \brief Deletes a #wasmtime_component_t.
+ */
+WASM_API_EXTERN void wasmtime_component_delete(wasmtime_component_t *component);
+
+/**
+ * \typedef wasmtime_component_export_index_t
+ * \brief Convenience alias for #wasmtime_component_export_index
+ *
+ * \struct wasmtime_component_export_index
+ * \brief A pre-computed index of an export of a component.
+ *
+ * This type corresponds to `wasmtime::component::ComponentExportIndex` in
+ * Rust. It can be looked up once on a #wasmtime_component_t and then used
+ * with any instance of that component to find exports quickly.
+ */
+typedef struct wasmtime_component_export_index
+    wasmtime_component_export_index_t;
+
+/**
+ * \brief Looks up an export of a component.
+ *
+ * \param component the component to look up the export in.
+ * \param instance_export_index optional export index of an exported instance
+ * to look inside of, or `NULL` to look at the root of the component.
+ * \param name the name of the export.
+ * \param name_len the byte length of `name`.
+ *
+ * \return an owned export index if the export was found, or `NULL` otherwise.
+ * The returned value must be deleted with
+ * #wasmtime_component_export_index_delete.
+ */
+WASM_API_EXTERN wasmtime_component_export_index_t *
+wasmtime_component_get_export_index(
+    const wasmtime_component_t *component,
+    const wasmtime_component_export_index_t *instance_export_index,
+    const char *name, size_t name_len);
+
+/**
+ * \brief Deletes a #wasmtime_component_export_index_t.
+ */
+WASM_API_EXTERN void wasmtime_component_export_index_delete(
+    wasmtime_component_export_index_t *export_index);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_COMPONENT_H
@@ -0,0 +1,69 @@
+/**
+ * \file wasmtime/component/func.h
+ *
+ * APIs for calling functions exported from components.
+ */
+
+#ifndef WASMTIME_COMPONENT_FUNC_H
+#define WASMTIME_COMPONENT_FUNC_H
+
+#include <wasm.h>
+#include <wasmtime/component/val.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/// \brief Representation of a function exported from a component instance.
+///
+/// Functions are represented as an index into a store and don't have any data
+/// or destructor associated with the #wasmtime_component_func_t value.
+/// Functions cannot interoperate between #wasmtime_store_t instances and if the
+/// wrong function is passed to the wrong store then it may trigger an
+/// assertion to abort the process.
+typedef struct wasmtime_component_func {
+  /// Internal identifier of what store this belongs to, never zero.
+  uint64_t store_id;
+  /// Private field for Wasmtime.
+  size_t __private;
+} wasmtime_component_func_t;
+
+/**
+ * \brief Calls a component function.
+ *
+ * \param func the function to call.
+ * \param context the store that owns `func`.
+ * \param args the arguments to the function.
+ * \param args_len the number of arguments in `args`.
+ * \param results where to write the results of the function.
+ * \param results_len the number of results in `results`, which must match the
+ * number of results of the function.
+ *
+ * \return `NULL` on success, or an error if the arguments didn't typecheck,
+ * the function trapped, or another error happened.
+ *
+ * The `args` are not taken ownership of and still need to be deleted by the
+ * caller. The `results` are considered uninitialized before this call and on
+ * success they are initialized and owned by the caller, so they must each be
+ * deleted with #wasmtime_component_val_delete.
+ *
+ * After the call the function's `post-return` cleanup, if any, is also run,
+ * so the results don't borrow any state from the guest.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_func_call(
+    const wasmtime_component_func_t *func, wasmtime_context_t *context,
+    const wasmtime_component_val_t *args, size_t args_len,
+    wasmtime_component_val_t *results, size_t results_len);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_FUNC_H
@@ -0,0 +1,79 @@
+/**
+ * \file wasmtime/component/instance.h
+ *
+ * APIs for interacting with instantiated components.
+ */
+
+#ifndef WASMTIME_COMPONENT_INSTANCE_H
+#define WASMTIME_COMPONENT_INSTANCE_H
+
+#include <wasm.h>
+#include <wasmtime/component/component.h>
+#include <wasmtime/component/func.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/// \brief Representation of an instantiated component in Wasmtime.
+///
+/// Component instances are represented as an index into a store and don't
+/// have any data or destructor associated with the
+/// #wasmtime_component_instance_t value. Instances cannot interoperate between
+/// #wasmtime_store_t instances and if the wrong instance is passed to the wrong
+/// store then it may trigger an assertion to abort the process.
+typedef struct wasmtime_component_instance {
+  /// Internal identifier of what store this belongs to, never zero.
+  uint64_t store_id;
+  /// Private field for Wasmtime.
+  size_t __private;
+} wasmtime_component_instance_t;
+
+/**
+ * \brief Looks up an export of a component instance.
+ *
+ * \param instance the instance to look up the export in.
+ * \param context the store that owns `instance`.
+ * \param instance_export_index optional export index of an exported instance
+ * to look inside of, or `NULL` to look at the root of the component.
+ * \param name the name of the export.
+ * \param name_len the byte length of `name`.
+ *
+ * \return an owned export index if the export was found, or `NULL` otherwise.
+ * The returned value must be deleted with
+ * #wasmtime_component_export_index_delete.
+ */
+WASM_API_EXTERN wasmtime_component_export_index_t *
+wasmtime_component_instance_get_export_index(
+    const wasmtime_component_instance_t *instance, wasmtime_context_t *context,
+    const wasmtime_component_export_index_t *instance_export_index,
+    const char *name, size_t name_len);
+
+/**
+ * \brief Looks up an exported function of a component instance.
+ *
+ * \param instance the instance to look up the function in.
+ * \param context the store that owns `instance`.
+ * \param export_index the index of the export, obtained from either
+ * #wasmtime_component_get_export_index or
+ * #wasmtime_component_instance_get_export_index.
+ * \param func_out on success, filled in with the function.
+ *
+ * \return `true` if the export was found and is a function, `false` otherwise.
+ */
+WASM_API_EXTERN bool wasmtime_component_instance_get_func(
+    const wasmtime_component_instance_t *instance, wasmtime_context_t *context,
+    const wasmtime_component_export_index_t *export_index,
+    wasmtime_component_func_t *func_out);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_INSTANCE_H
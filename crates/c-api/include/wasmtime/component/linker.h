@@ -0,0 +1,232 @@
+/**
+ * \file wasmtime/component/linker.h
+ *
+ * Wasmtime API for a name-based linker used to instantiate components.
+ */
+
+#ifndef WASMTIME_COMPONENT_LINKER_H
+#define WASMTIME_COMPONENT_LINKER_H
+
+#include <wasm.h>
+#include <wasmtime/component/component.h>
+#include <wasmtime/component/instance.h>
+#include <wasmtime/component/resource.h>
+#include <wasmtime/component/val.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/module.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \typedef wasmtime_component_linker_t
+ * \brief Alias to #wasmtime_component_linker
+ *
+ * \struct #wasmtime_component_linker
+ * \brief Object used to define the imports of components and instantiate
+ * them.
+ *
+ * This type corresponds to the `wasmtime::component::Linker` type in Rust.
+ * Definitions are added to the linker through
+ * #wasmtime_component_linker_instance_t values, the root of which is
+ * acquired with #wasmtime_component_linker_root.
+ */
+typedef struct wasmtime_component_linker wasmtime_component_linker_t;
+
+/**
+ * \typedef wasmtime_component_linker_instance_t
+ * \brief Alias to #wasmtime_component_linker_instance
+ *
+ * \struct #wasmtime_component_linker_instance
+ * \brief A namespace of definitions within a #wasmtime_component_linker_t.
+ *
+ * This type corresponds to the `wasmtime::component::LinkerInstance` type in
+ * Rust. It mutably borrows the linker (or the parent linker instance) it was
+ * created from, so while it's alive its parent must not be used, and it must
+ * be deleted with #wasmtime_component_linker_instance_delete before the
+ * parent is used again.
+ */
+typedef struct wasmtime_component_linker_instance
+    wasmtime_component_linker_instance_t;
+
+/**
+ * \brief Creates a new component linker for the specified engine.
+ *
+ * This function does not take ownership of the engine argument, and the caller
+ * is expected to delete the returned linker.
+ */
+WASM_API_EXTERN wasmtime_component_linker_t *
+wasmtime_component_linker_new(const wasm_engine_t *engine);
+
+/**
+ * \brief Configures whether this linker allows later definitions to shadow
+ * previous definitions.
+ *
+ * By default this setting is `false`.
+ */
+WASM_API_EXTERN void
+wasmtime_component_linker_allow_shadowing(wasmtime_component_linker_t *linker,
+                                          bool allow_shadowing);
+
+/**
+ * \brief Defines all imports of `component` which aren't already defined in
+ * this linker as functions which trap when called.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_linker_define_unknown_imports_as_traps(
+    wasmtime_component_linker_t *linker,
+    const wasmtime_component_t *component);
+
+/**
+ * \brief Returns the root namespace of this linker.
+ *
+ * The returned value is owned by the caller and must be deleted with
+ * #wasmtime_component_linker_instance_delete before `linker` is used again.
+ */
+WASM_API_EXTERN wasmtime_component_linker_instance_t *
+wasmtime_component_linker_root(wasmtime_component_linker_t *linker);
+
+/**
+ * \brief Instantiates a component with the definitions in this linker.
+ *
+ * \param linker the linker to use for the imports of `component`.
+ * \param context the store to instantiate the component in.
+ * \param component the component to instantiate.
+ * \param instance_out on success, filled in with the new instance.
+ *
+ * \return `NULL` on success, or an error if an import wasn't satisfied or
+ * instantiation failed, for example because of a trap.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_linker_instantiate(
+    const wasmtime_component_linker_t *linker, wasmtime_context_t *context,
+    const wasmtime_component_t *component,
+    wasmtime_component_instance_t *instance_out);
+
+/**
+ * \brief Deletes a #wasmtime_component_linker_t.
+ */
+WASM_API_EXTERN void
+wasmtime_component_linker_delete(wasmtime_component_linker_t *linker);
+
+/**
+ * \brief Defines a nested instance within this namespace.
+ *
+ * \param linker_instance the namespace to define the instance in.
+ * \param name the name of the instance, for example `wasi:cli/stdout@0.2.0`.
+ * \param name_len the byte length of `name`.
+ * \param linker_instance_out on success, filled in with the new namespace.
+ * This borrows `linker_instance` and must be deleted with
+ * #wasmtime_component_linker_instance_delete before `linker_instance` is used
+ * again.
+ *
+ * \return `NULL` on success, or an error if `name` is already defined.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_linker_instance_add_instance(
+    wasmtime_component_linker_instance_t *linker_instance, const char *name,
+    size_t name_len,
+    wasmtime_component_linker_instance_t **linker_instance_out);
+
+/**
+ * \brief Defines a core wasm module within this namespace.
+ *
+ * \return `NULL` on success, or an error if `name` is already defined.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_linker_instance_add_module(
+    wasmtime_component_linker_instance_t *linker_instance, const char *name,
+    size_t name_len, const wasmtime_module_t *module);
+
+/**
+ * \brief Callback signature for #wasmtime_component_linker_instance_add_func.
+ *
+ * The first argument is the `data` provided when the function was defined and
+ * the second is the store that the function is being called in.
+ *
+ * The `args` are owned by Wasmtime and must not be deleted by the callback.
+ * The `results` are initialized to `bool` values and should each be
+ * overwritten with a value of the appropriate type, which Wasmtime then takes
+ * ownership of.
+ *
+ * Returning a non-`NULL` error causes the guest to trap with that error.
+ */
+typedef wasmtime_error_t *(*wasmtime_component_func_callback_t)(
+    void *data, wasmtime_context_t *context,
+    const wasmtime_component_val_t *args, size_t args_len,
+    wasmtime_component_val_t *results, size_t results_len);
+
+/**
+ * \brief Defines a host function within this namespace.
+ *
+ * \param linker_instance the namespace to define the function in.
+ * \param name the name of the function.
+ * \param name_len the byte length of `name`.
+ * \param callback the host function implementation.
+ * \param data host-specific data passed to `callback`.
+ * \param finalizer optional finalizer for `data`, run when the linker is
+ * deleted.
+ *
+ * The function is dynamically typed: its arguments and results are checked
+ * against the type of the import during instantiation and calls.
+ *
+ * \return `NULL` on success, or an error if `name` is already defined.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_linker_instance_add_func(
+    wasmtime_component_linker_instance_t *linker_instance, const char *name,
+    size_t name_len, wasmtime_component_func_callback_t callback, void *data,
+    void (*finalizer)(void *));
+
+/**
+ * \brief Callback signature for the destructor of a resource defined with
+ * #wasmtime_component_linker_instance_add_resource.
+ *
+ * The arguments are the `data` provided when the resource was defined, the
+ * store and the representation of the resource being destroyed.
+ */
+typedef wasmtime_error_t *(*wasmtime_component_resource_destructor_t)(
+    void *data, wasmtime_context_t *context, uint32_t rep);
+
+/**
+ * \brief Defines a host resource type within this namespace.
+ *
+ * \param linker_instance the namespace to define the resource in.
+ * \param name the name of the resource.
+ * \param name_len the byte length of `name`.
+ * \param ty the type of the resource, usually created with
+ * #wasmtime_component_resource_type_new_host.
+ * \param destructor invoked when the guest destroys an owned handle to this
+ * resource.
+ * \param data host-specific data passed to `destructor`.
+ * \param finalizer optional finalizer for `data`, run when the linker is
+ * deleted.
+ *
+ * \return `NULL` on success, or an error if `name` is already defined.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_linker_instance_add_resource(
+    wasmtime_component_linker_instance_t *linker_instance, const char *name,
+    size_t name_len, const wasmtime_component_resource_type_t *ty,
+    wasmtime_component_resource_destructor_t destructor, void *data,
+    void (*finalizer)(void *));
+
+/**
+ * \brief Deletes a #wasmtime_component_linker_instance_t.
+ *
+ * This releases the borrow on the parent linker or linker instance, which
+ * may be used again afterwards.
+ */
+WASM_API_EXTERN void wasmtime_component_linker_instance_delete(
+    wasmtime_component_linker_instance_t *linker_instance);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_LINKER_H
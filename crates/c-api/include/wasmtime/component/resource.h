@@ -0,0 +1,166 @@
+/**
+ * \file wasmtime/component/resource.h
+ *
+ * APIs for working with resources of the component model.
+ */
+
+#ifndef WASMTIME_COMPONENT_RESOURCE_H
+#define WASMTIME_COMPONENT_RESOURCE_H
+
+#include <wasm.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \typedef wasmtime_component_resource_type_t
+ * \brief Convenience alias for #wasmtime_component_resource_type
+ *
+ * \struct wasmtime_component_resource_type
+ * \brief The type of a resource in the component model.
+ *
+ * This type corresponds to `wasmtime::component::ResourceType` in Rust. Host
+ * resource types are identified by a 32-bit integer chosen by the embedder,
+ * see #wasmtime_component_resource_type_new_host.
+ */
+typedef struct wasmtime_component_resource_type
+    wasmtime_component_resource_type_t;
+
+/**
+ * \brief Creates a new host-defined resource type.
+ *
+ * Two host resource types are the same if they were created with the same
+ * `payload`. The returned type is owned by the caller.
+ */
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_type_new_host(uint32_t payload);
+
+/**
+ * \brief Creates a copy of the provided resource type.
+ */
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_type_clone(
+    const wasmtime_component_resource_type_t *ty);
+
+/**
+ * \brief Returns whether two resource types are the same type.
+ */
+WASM_API_EXTERN bool wasmtime_component_resource_type_equal(
+    const wasmtime_component_resource_type_t *a,
+    const wasmtime_component_resource_type_t *b);
+
+/**
+ * \brief Deletes a #wasmtime_component_resource_type_t.
+ */
+WASM_API_EXTERN void wasmtime_component_resource_type_delete(
+    wasmtime_component_resource_type_t *ty);
+
+/**
+ * \typedef wasmtime_component_resource_any_t
+ * \brief Convenience alias for #wasmtime_component_resource_any
+ *
+ * \struct wasmtime_component_resource_any
+ * \brief A handle to a resource, either host-defined or guest-defined.
+ *
+ * This type corresponds to `wasmtime::component::ResourceAny` in Rust. It is
+ * an owned or borrowed handle to a resource within a #wasmtime_store_t.
+ *
+ * Note that deleting this value with #wasmtime_component_resource_any_delete
+ * only frees the memory of the handle. An owned resource additionally needs
+ * to be destroyed with #wasmtime_component_resource_any_drop, or passed to the
+ * guest, to release its state within the store.
+ */
+typedef struct wasmtime_component_resource_any
+    wasmtime_component_resource_any_t;
+
+/**
+ * \brief Creates a handle to a host-defined resource.
+ *
+ * \param context the store that the resource will be owned by.
+ * \param payload the payload of the host resource type, as passed to
+ * #wasmtime_component_resource_type_new_host.
+ * \param rep the host-defined representation of this resource.
+ * \param owned whether an owned or a borrowed handle is created.
+ * \param ret on success, filled in with the new owned handle.
+ *
+ * \return `NULL` on success, or an error if the handle couldn't be created.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_any_new_host(
+    wasmtime_context_t *context, uint32_t payload, uint32_t rep, bool owned,
+    wasmtime_component_resource_any_t **ret);
+
+/**
+ * \brief Converts a handle to a host resource back to its representation.
+ *
+ * This consumes the handle within the store: an owned handle is removed from
+ * the store and a borrowed handle has its borrow released. The
+ * #wasmtime_component_resource_any_t itself must still be deleted.
+ *
+ * \param context the store that owns the resource.
+ * \param resource the resource handle.
+ * \param payload the expected payload of the host resource type.
+ * \param rep on success, filled in with the representation of the resource.
+ *
+ * \return `NULL` on success, or an error if the resource is not of the host
+ * resource type identified by `payload` or the handle is invalid.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_any_to_host(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_any_t *resource, uint32_t payload,
+    uint32_t *rep);
+
+/**
+ * \brief Creates a copy of the provided resource handle.
+ *
+ * Both the original and the copy refer to the same resource in the store.
+ */
+WASM_API_EXTERN wasmtime_component_resource_any_t *
+wasmtime_component_resource_any_clone(
+    const wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief Returns the type of the provided resource.
+ *
+ * The returned type is owned by the caller.
+ */
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_any_type(
+    const wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief Returns whether this handle is an owned handle, as opposed to a
+ * borrowed one.
+ */
+WASM_API_EXTERN bool wasmtime_component_resource_any_owned(
+    const wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief Destroys the resource within the store.
+ *
+ * For owned resources this may run the guest-defined destructor, or the
+ * host-defined one registered with
+ * #wasmtime_component_linker_instance_add_resource.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_any_drop(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief Deletes a #wasmtime_component_resource_any_t.
+ */
+WASM_API_EXTERN void wasmtime_component_resource_any_delete(
+    wasmtime_component_resource_any_t *resource);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_RESOURCE_H
@@ -0,0 +1,383 @@
+/**
+ * \file wasmtime/component/val.h
+ *
+ * APIs for values of the component model.
+ *
+ * Component values are represented with #wasmtime_component_val_t which is a
+ * tagged union mirroring `wasmtime::component::Val` in Rust. Unlike core wasm
+ * values these values may own heap allocations, such as strings or lists, and
+ * must be deallocated with #wasmtime_component_val_delete when the value was
+ * produced by Wasmtime or created by the embedder.
+ */
+
+#ifndef WASMTIME_COMPONENT_VAL_H
+#define WASMTIME_COMPONENT_VAL_H
+
+#include <wasm.h>
+#include <wasmtime/component/resource.h>
+#include <wasmtime/conf.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/// \brief Discriminant stored in #wasmtime_component_val::kind
+typedef uint8_t wasmtime_component_valkind_t;
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a bool
+#define WASMTIME_COMPONENT_BOOL 0
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a s8
+#define WASMTIME_COMPONENT_S8 1
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a u8
+#define WASMTIME_COMPONENT_U8 2
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a s16
+#define WASMTIME_COMPONENT_S16 3
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a u16
+#define WASMTIME_COMPONENT_U16 4
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a s32
+#define WASMTIME_COMPONENT_S32 5
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a u32
+#define WASMTIME_COMPONENT_U32 6
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a s64
+#define WASMTIME_COMPONENT_S64 7
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a u64
+#define WASMTIME_COMPONENT_U64 8
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a f32
+#define WASMTIME_COMPONENT_F32 9
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a f64
+#define WASMTIME_COMPONENT_F64 10
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a char
+#define WASMTIME_COMPONENT_CHAR 11
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a string
+#define WASMTIME_COMPONENT_STRING 12
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a list
+#define WASMTIME_COMPONENT_LIST 13
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a record
+#define WASMTIME_COMPONENT_RECORD 14
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a tuple
+#define WASMTIME_COMPONENT_TUPLE 15
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a variant
+#define WASMTIME_COMPONENT_VARIANT 16
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is an enum
+#define WASMTIME_COMPONENT_ENUM 17
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is an option
+#define WASMTIME_COMPONENT_OPTION 18
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a result
+#define WASMTIME_COMPONENT_RESULT 19
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is flags
+#define WASMTIME_COMPONENT_FLAGS 20
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a resource
+#define WASMTIME_COMPONENT_RESOURCE 21
+
+struct wasmtime_component_val;
+struct wasmtime_component_valrecord_entry;
+
+/// \brief A vector of component values used for `list` values.
+///
+/// This type follows the same conventions as #wasm_byte_vec_t: it owns its
+/// elements, and is created and deleted with the functions below.
+typedef struct wasmtime_component_vallist {
+  /// \brief Number of elements in `data`.
+  size_t size;
+  /// \brief Pointer to the elements of this vector.
+  struct wasmtime_component_val *data;
+} wasmtime_component_vallist_t;
+
+/// \brief Creates a new vector of `size` elements copied from `ptr`, taking
+/// ownership of the elements.
+WASM_API_EXTERN void
+wasmtime_component_vallist_new(wasmtime_component_vallist_t *out, size_t size,
+                               const struct wasmtime_component_val *ptr);
+
+/// \brief Creates a new empty vector.
+WASM_API_EXTERN void
+wasmtime_component_vallist_new_empty(wasmtime_component_vallist_t *out);
+
+/// \brief Creates a new vector of `size` default-initialized elements.
+WASM_API_EXTERN void
+wasmtime_component_vallist_new_uninit(wasmtime_component_vallist_t *out,
+                                      size_t size);
+
+/// \brief Creates a deep copy of `src` in `out`.
+WASM_API_EXTERN void
+wasmtime_component_vallist_copy(wasmtime_component_vallist_t *out,
+                                const wasmtime_component_vallist_t *src);
+
+/// \brief Deletes the vector and all of its elements.
+WASM_API_EXTERN void
+wasmtime_component_vallist_delete(wasmtime_component_vallist_t *value);
+
+/// \brief A vector of named fields used for `record` values.
+///
+/// This type follows the same conventions as #wasm_byte_vec_t: it owns its
+/// elements, and is created and deleted with the functions below.
+typedef struct wasmtime_component_valrecord {
+  /// \brief Number of elements in `data`.
+  size_t size;
+  /// \brief Pointer to the elements of this vector.
+  struct wasmtime_component_valrecord_entry *data;
+} wasmtime_component_valrecord_t;
+
+/// \brief Creates a new vector of `size` elements copied from `ptr`, taking
+/// ownership of the elements.
+WASM_API_EXTERN void wasmtime_component_valrecord_new(
+    wasmtime_component_valrecord_t *out, size_t size,
+    const struct wasmtime_component_valrecord_entry *ptr);
+
+/// \brief Creates a new empty vector.
+WASM_API_EXTERN void
+wasmtime_component_valrecord_new_empty(wasmtime_component_valrecord_t *out);
+
+/// \brief Creates a new vector of `size` default-initialized elements.
+WASM_API_EXTERN void
+wasmtime_component_valrecord_new_uninit(wasmtime_component_valrecord_t *out,
+                                        size_t size);
+
+/// \brief Creates a deep copy of `src` in `out`.
+WASM_API_EXTERN void
+wasmtime_component_valrecord_copy(wasmtime_component_valrecord_t *out,
+                                  const wasmtime_component_valrecord_t *src);
+
+/// \brief Deletes the vector and all of its elements.
+WASM_API_EXTERN void
+wasmtime_component_valrecord_delete(wasmtime_component_valrecord_t *value);
+
+/// \brief A vector of component values used for `tuple` values.
+///
+/// This type follows the same conventions as #wasm_byte_vec_t: it owns its
+/// elements, and is created and deleted with the functions below.
+typedef struct wasmtime_component_valtuple {
+  /// \brief Number of elements in `data`.
+  size_t size;
+  /// \brief Pointer to the elements of this vector.
+  struct wasmtime_component_val *data;
+} wasmtime_component_valtuple_t;
+
+/// \brief Creates a new vector of `size` elements copied from `ptr`, taking
+/// ownership of the elements.
+WASM_API_EXTERN void
+wasmtime_component_valtuple_new(wasmtime_component_valtuple_t *out, size_t size,
+                                const struct wasmtime_component_val *ptr);
+
+/// \brief Creates a new empty vector.
+WASM_API_EXTERN void
+wasmtime_component_valtuple_new_empty(wasmtime_component_valtuple_t *out);
+
+/// \brief Creates a new vector of `size` default-initialized elements.
+WASM_API_EXTERN void
+wasmtime_component_valtuple_new_uninit(wasmtime_component_valtuple_t *out,
+                                       size_t size);
+
+/// \brief Creates a deep copy of `src` in `out`.
+WASM_API_EXTERN void
+wasmtime_component_valtuple_copy(wasmtime_component_valtuple_t *out,
+                                 const wasmtime_component_valtuple_t *src);
+
+/// \brief Deletes the vector and all of its elements.
+WASM_API_EXTERN void
+wasmtime_component_valtuple_delete(wasmtime_component_valtuple_t *value);
+
+/// \brief A vector of names of the flags which are set in a `flags` value.
+///
+/// This type follows the same conventions as #wasm_byte_vec_t: it owns its
+/// elements, and is created and deleted with the functions below.
+typedef struct wasmtime_component_valflags {
+  /// \brief Number of elements in `data`.
+  size_t size;
+  /// \brief Pointer to the elements of this vector.
+  wasm_name_t *data;
+} wasmtime_component_valflags_t;
+
+/// \brief Creates a new vector of `size` elements copied from `ptr`, taking
+/// ownership of the elements.
+WASM_API_EXTERN void
+wasmtime_component_valflags_new(wasmtime_component_valflags_t *out, size_t size,
+                                const wasm_name_t *ptr);
+
+/// \brief Creates a new empty vector.
+WASM_API_EXTERN void
+wasmtime_component_valflags_new_empty(wasmtime_component_valflags_t *out);
+
+/// \brief Creates a new vector of `size` default-initialized elements.
+WASM_API_EXTERN void
+wasmtime_component_valflags_new_uninit(wasmtime_component_valflags_t *out,
+                                       size_t size);
+
+/// \brief Creates a deep copy of `src` in `out`.
+WASM_API_EXTERN void
+wasmtime_component_valflags_copy(wasmtime_component_valflags_t *out,
+                                 const wasmtime_component_valflags_t *src);
+
+/// \brief Deletes the vector and all of its elements.
+WASM_API_EXTERN void
+wasmtime_component_valflags_delete(wasmtime_component_valflags_t *value);
+
+/// \brief Representation of a `variant` value.
+typedef struct wasmtime_component_valvariant {
+  /// \brief The name of the case of this variant.
+  wasm_name_t discriminant;
+  /// \brief The payload of this case, or `NULL` if the case has no payload.
+  struct wasmtime_component_val *val;
+} wasmtime_component_valvariant_t;
+
+/// \brief Representation of a `result` value.
+typedef struct wasmtime_component_valresult {
+  /// \brief Whether this is the `ok` case of the result.
+  bool is_ok;
+  /// \brief The payload of the `ok` or `err` case, or `NULL` if there is
+  /// none.
+  struct wasmtime_component_val *val;
+} wasmtime_component_valresult_t;
+
+/// \brief Container for the payload of a #wasmtime_component_val_t.
+typedef union wasmtime_component_valunion {
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_BOOL
+  bool boolean;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_S8
+  int8_t s8;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_U8
+  uint8_t u8;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_S16
+  int16_t s16;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_U16
+  uint16_t u16;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_S32
+  int32_t s32;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_U32
+  uint32_t u32;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_S64
+  int64_t s64;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_U64
+  uint64_t u64;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_F32
+  float f32;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_F64
+  double f64;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_CHAR,
+  /// a Unicode scalar value.
+  uint32_t character;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_STRING, a UTF-8 string.
+  wasm_name_t string;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_LIST
+  wasmtime_component_vallist_t list;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_RECORD
+  wasmtime_component_valrecord_t record;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_TUPLE
+  wasmtime_component_valtuple_t tuple;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_VARIANT
+  wasmtime_component_valvariant_t variant;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_ENUM,
+  /// the name of the case.
+  wasm_name_t enumeration;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_OPTION, `NULL` for `none`.
+  struct wasmtime_component_val *option;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_RESULT
+  wasmtime_component_valresult_t result;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_FLAGS
+  wasmtime_component_valflags_t flags;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_RESOURCE, never `NULL`.
+  wasmtime_component_resource_any_t *resource;
+} wasmtime_component_valunion_t;
+
+/**
+ * \typedef wasmtime_component_val_t
+ * \brief Convenience alias for #wasmtime_component_val
+ *
+ * \struct wasmtime_component_val
+ * \brief A value of the component model.
+ *
+ * The `kind` field determines which field of `of` is active. All pointers and
+ * vectors within a value are owned by the value. Vectors must be created with
+ * their `*_new` functions, payloads of `option`, `variant` and `result` values
+ * with #wasmtime_component_val_new and resources with the functions in
+ * wasmtime/component/resource.h.
+ */
+typedef struct wasmtime_component_val {
+  /// \brief Discriminant of which field of `of` is valid.
+  wasmtime_component_valkind_t kind;
+  /// \brief Container for the payload of this value.
+  wasmtime_component_valunion_t of;
+} wasmtime_component_val_t;
+
+/// \brief A named field of a `record` value.
+typedef struct wasmtime_component_valrecord_entry {
+  /// \brief The name of this field.
+  wasm_name_t name;
+  /// \brief The value of this field.
+  wasmtime_component_val_t val;
+} wasmtime_component_valrecord_entry_t;
+
+/**
+ * \brief Allocates a new #wasmtime_component_val_t on the heap, moving `val`
+ * into it.
+ *
+ * This is the way to create the payload of `option`, `variant` and `result`
+ * values. Ownership of `val` is transferred to the returned value which is
+ * owned by the caller, or by the value it is stored in.
+ */
+WASM_API_EXTERN wasmtime_component_val_t *
+wasmtime_component_val_new(wasmtime_component_val_t *val);
+
+/**
+ * \brief Frees a value previously allocated with
+ * #wasmtime_component_val_new, including its contents.
+ */
+WASM_API_EXTERN void wasmtime_component_val_free(wasmtime_component_val_t *val);
+
+/**
+ * \brief Creates a deep copy of `src` in `dst`.
+ *
+ * The `dst` value is uninitialized before this call and owned by the caller
+ * afterwards.
+ */
+WASM_API_EXTERN void
+wasmtime_component_val_clone(const wasmtime_component_val_t *src,
+                             wasmtime_component_val_t *dst);
+
+/**
+ * \brief Deallocates the contents of `val`.
+ *
+ * After this call `val` is reset to a `bool` value, so it is safe to delete a
+ * value twice.
+ */
+WASM_API_EXTERN void
+wasmtime_component_val_delete(wasmtime_component_val_t *val);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_VAL_H
@@ -0,0 +1,13 @@
+mod component;
+mod func;
+mod instance;
+mod linker;
+mod resource;
+mod val;
+
+pub use self::component::*;
+pub use self::func::*;
+pub use self::instance::*;
+pub use self::linker::*;
+pub use self::resource::*;
+pub use self::val::*;
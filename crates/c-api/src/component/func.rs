@@ -0,0 +1,31 @@
+use crate::{handle_result, wasmtime_component_val_t, wasmtime_error_t, WasmtimeStoreContextMut};
+use anyhow::Result;
+use std::mem::MaybeUninit;
+use wasmtime::component::{Func, Val};
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_func_call(
+    func: &Func,
+    mut store: WasmtimeStoreContextMut<'_>,
+    args: *const wasmtime_component_val_t,
+    args_len: usize,
+    results: *mut MaybeUninit<wasmtime_component_val_t>,
+    results_len: usize,
+) -> Option<Box<wasmtime_error_t>> {
+    let args = crate::slice_from_raw_parts(args, args_len);
+    let results = crate::slice_from_raw_parts_mut(results, results_len);
+    let result = (|| -> Result<()> {
+        let params = args
+            .iter()
+            .map(|a| a.to_val())
+            .collect::<Result<Vec<_>>>()?;
+        let mut vals = vec![Val::Bool(false); results.len()];
+        func.call(&mut store, &params, &mut vals)?;
+        func.post_return(&mut store)?;
+        for (slot, val) in results.iter_mut().zip(vals.iter()) {
+            crate::initialize(slot, wasmtime_component_val_t::from_val(val));
+        }
+        Ok(())
+    })();
+    handle_result(result, |()| ())
+}
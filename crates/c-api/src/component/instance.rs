@@ -0,0 +1,34 @@
+use crate::{wasmtime_component_export_index_t, WasmtimeStoreContextMut};
+use std::mem::MaybeUninit;
+use std::str;
+use wasmtime::component::{Func, Instance};
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_instance_get_export_index(
+    instance: &Instance,
+    store: WasmtimeStoreContextMut<'_>,
+    instance_export_index: Option<&wasmtime_component_export_index_t>,
+    name: *const u8,
+    name_len: usize,
+) -> Option<Box<wasmtime_component_export_index_t>> {
+    let name = str::from_utf8(crate::slice_from_raw_parts(name, name_len)).ok()?;
+    let export_index =
+        instance.get_export(store, instance_export_index.map(|i| &i.export_index), name)?;
+    Some(Box::new(wasmtime_component_export_index_t { export_index }))
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_instance_get_func(
+    instance: &Instance,
+    store: WasmtimeStoreContextMut<'_>,
+    index: &wasmtime_component_export_index_t,
+    func_out: &mut MaybeUninit<Func>,
+) -> bool {
+    match instance.get_func(store, &index.export_index) {
+        Some(func) => {
+            crate::initialize(func_out, func);
+            true
+        }
+        None => false,
+    }
+}
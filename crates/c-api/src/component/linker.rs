@@ -0,0 +1,190 @@
+use crate::linker::to_str;
+use crate::{
+    bad_utf8, handle_result, wasm_engine_t, wasmtime_component_resource_type_t,
+    wasmtime_component_t, wasmtime_component_val_t, wasmtime_error_t, wasmtime_module_t,
+    WasmtimeStoreContextMut, WasmtimeStoreData,
+};
+use anyhow::Result;
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+use std::str;
+use wasmtime::component::{Instance, Linker, LinkerInstance, Val};
+use wasmtime::StoreContextMut;
+
+pub struct wasmtime_component_linker_t {
+    pub(crate) linker: Linker<WasmtimeStoreData>,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_component_linker_t);
+
+pub struct wasmtime_component_linker_instance_t<'a> {
+    pub(crate) linker_instance: LinkerInstance<'a, WasmtimeStoreData>,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_component_linker_instance_t);
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_linker_new(
+    engine: &wasm_engine_t,
+) -> Box<wasmtime_component_linker_t> {
+    Box::new(wasmtime_component_linker_t {
+        linker: Linker::new(&engine.engine),
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_linker_allow_shadowing(
+    linker: &mut wasmtime_component_linker_t,
+    allow_shadowing: bool,
+) {
+    linker.linker.allow_shadowing(allow_shadowing);
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_linker_define_unknown_imports_as_traps(
+    linker: &mut wasmtime_component_linker_t,
+    component: &wasmtime_component_t,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(
+        linker
+            .linker
+            .define_unknown_imports_as_traps(&component.component),
+        |()| (),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_linker_root(
+    linker: &mut wasmtime_component_linker_t,
+) -> Box<wasmtime_component_linker_instance_t<'_>> {
+    Box::new(wasmtime_component_linker_instance_t {
+        linker_instance: linker.linker.root(),
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_linker_instantiate(
+    linker: &wasmtime_component_linker_t,
+    store: WasmtimeStoreContextMut<'_>,
+    component: &wasmtime_component_t,
+    instance_out: &mut MaybeUninit<Instance>,
+) -> Option<Box<wasmtime_error_t>> {
+    let result = linker.linker.instantiate(store, &component.component);
+    handle_result(result, |instance| {
+        crate::initialize(instance_out, instance);
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_linker_instance_add_instance<'a, 'b>(
+    linker_instance: &'b mut wasmtime_component_linker_instance_t<'a>,
+    name: *const u8,
+    name_len: usize,
+    linker_instance_out: &mut *mut wasmtime_component_linker_instance_t<'b>,
+) -> Option<Box<wasmtime_error_t>> {
+    let name = to_str!(name, name_len);
+    handle_result(
+        linker_instance.linker_instance.instance(name),
+        |linker_instance| {
+            *linker_instance_out = Box::into_raw(Box::new(wasmtime_component_linker_instance_t {
+                linker_instance,
+            }));
+        },
+    )
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_linker_instance_add_module(
+    linker_instance: &mut wasmtime_component_linker_instance_t<'_>,
+    name: *const u8,
+    name_len: usize,
+    module: &wasmtime_module_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let name = to_str!(name, name_len);
+    handle_result(
+        linker_instance.linker_instance.module(name, &module.module),
+        |()| (),
+    )
+}
+
+pub type wasmtime_component_func_callback_t = extern "C" fn(
+    *mut c_void,
+    WasmtimeStoreContextMut<'_>,
+    *const wasmtime_component_val_t,
+    usize,
+    *mut wasmtime_component_val_t,
+    usize,
+) -> Option<Box<wasmtime_error_t>>;
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_linker_instance_add_func(
+    linker_instance: &mut wasmtime_component_linker_instance_t<'_>,
+    name: *const u8,
+    name_len: usize,
+    callback: wasmtime_component_func_callback_t,
+    data: *mut c_void,
+    finalizer: Option<extern "C" fn(*mut c_void)>,
+) -> Option<Box<wasmtime_error_t>> {
+    let name = to_str!(name, name_len);
+    let foreign = crate::ForeignData { data, finalizer };
+    let cb = move |store: StoreContextMut<'_, WasmtimeStoreData>,
+                   params: &[Val],
+                   results: &mut [Val]|
+          -> Result<()> {
+        let _ = &foreign; // move entire foreign into this closure
+
+        let params = params
+            .iter()
+            .map(wasmtime_component_val_t::from_val)
+            .collect::<Vec<_>>();
+        let mut out_results = results
+            .iter()
+            .map(|_| wasmtime_component_val_t::default())
+            .collect::<Vec<_>>();
+        let out = callback(
+            foreign.data,
+            store,
+            params.as_ptr(),
+            params.len(),
+            out_results.as_mut_ptr(),
+            out_results.len(),
+        );
+        if let Some(err) = out {
+            return Err(err.error);
+        }
+
+        for (result, out_result) in results.iter_mut().zip(&out_results) {
+            *result = out_result.to_val()?;
+        }
+        Ok(())
+    };
+    handle_result(linker_instance.linker_instance.func_new(name, cb), |()| ())
+}
+
+pub type wasmtime_component_resource_destructor_t =
+    extern "C" fn(*mut c_void, WasmtimeStoreContextMut<'_>, u32) -> Option<Box<wasmtime_error_t>>;
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_linker_instance_add_resource(
+    linker_instance: &mut wasmtime_component_linker_instance_t<'_>,
+    name: *const u8,
+    name_len: usize,
+    ty: &wasmtime_component_resource_type_t,
+    destructor: wasmtime_component_resource_destructor_t,
+    data: *mut c_void,
+    finalizer: Option<extern "C" fn(*mut c_void)>,
+) -> Option<Box<wasmtime_error_t>> {
+    let name = to_str!(name, name_len);
+    let foreign = crate::ForeignData { data, finalizer };
+    let dtor = move |store: StoreContextMut<'_, WasmtimeStoreData>, rep: u32| -> Result<()> {
+        let _ = &foreign; // move entire foreign into this closure
+        match destructor(foreign.data, store, rep) {
+            Some(err) => Err(err.error),
+            None => Ok(()),
+        }
+    };
+    handle_result(
+        linker_instance.linker_instance.resource(name, ty.ty, dtor),
+        |()| (),
+    )
+}
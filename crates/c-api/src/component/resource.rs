@@ -0,0 +1,100 @@
+use crate::{handle_result, wasmtime_error_t, WasmtimeStoreContextMut};
+use wasmtime::component::{ResourceAny, ResourceType};
+
+#[derive(Clone)]
+pub struct wasmtime_component_resource_type_t {
+    pub(crate) ty: ResourceType,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_component_resource_type_t);
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_type_new_host(
+    payload: u32,
+) -> Box<wasmtime_component_resource_type_t> {
+    Box::new(wasmtime_component_resource_type_t {
+        ty: ResourceType::host_dynamic(payload),
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_type_clone(
+    ty: &wasmtime_component_resource_type_t,
+) -> Box<wasmtime_component_resource_type_t> {
+    Box::new(ty.clone())
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_type_equal(
+    a: &wasmtime_component_resource_type_t,
+    b: &wasmtime_component_resource_type_t,
+) -> bool {
+    a.ty == b.ty
+}
+
+#[derive(Clone)]
+pub struct wasmtime_component_resource_any_t {
+    pub(crate) resource: ResourceAny,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_component_resource_any_t);
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_any_new_host(
+    store: WasmtimeStoreContextMut<'_>,
+    payload: u32,
+    rep: u32,
+    owned: bool,
+    out: &mut *mut wasmtime_component_resource_any_t,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(
+        ResourceAny::try_from_host_dynamic(store, payload, rep, owned),
+        |resource| {
+            *out = Box::into_raw(Box::new(wasmtime_component_resource_any_t { resource }));
+        },
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_any_to_host(
+    store: WasmtimeStoreContextMut<'_>,
+    resource: &wasmtime_component_resource_any_t,
+    payload: u32,
+    rep: &mut u32,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(
+        resource.resource.try_into_host_dynamic(store, payload),
+        |r| *rep = r,
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_any_clone(
+    resource: &wasmtime_component_resource_any_t,
+) -> Box<wasmtime_component_resource_any_t> {
+    Box::new(resource.clone())
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_any_type(
+    resource: &wasmtime_component_resource_any_t,
+) -> Box<wasmtime_component_resource_type_t> {
+    Box::new(wasmtime_component_resource_type_t {
+        ty: resource.resource.ty(),
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_any_owned(
+    resource: &wasmtime_component_resource_any_t,
+) -> bool {
+    resource.resource.owned()
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_resource_any_drop(
+    store: WasmtimeStoreContextMut<'_>,
+    resource: &wasmtime_component_resource_any_t,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(resource.resource.resource_drop(store), |()| ())
+}
@@ -0,0 +1,276 @@
+use crate::vec::declare_vecs;
+use crate::{wasm_name_t, wasmtime_component_resource_any_t};
+use anyhow::{anyhow, Result};
+use std::mem;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
+use std::str;
+use wasmtime::component::Val;
+
+declare_vecs! {
+    (
+        name: wasmtime_component_vallist_t,
+        ty: wasmtime_component_val_t,
+        new: wasmtime_component_vallist_new,
+        empty: wasmtime_component_vallist_new_empty,
+        uninit: wasmtime_component_vallist_new_uninit,
+        copy: wasmtime_component_vallist_copy,
+        delete: wasmtime_component_vallist_delete,
+    )
+    (
+        name: wasmtime_component_valrecord_t,
+        ty: wasmtime_component_valrecord_entry_t,
+        new: wasmtime_component_valrecord_new,
+        empty: wasmtime_component_valrecord_new_empty,
+        uninit: wasmtime_component_valrecord_new_uninit,
+        copy: wasmtime_component_valrecord_copy,
+        delete: wasmtime_component_valrecord_delete,
+    )
+    (
+        name: wasmtime_component_valtuple_t,
+        ty: wasmtime_component_val_t,
+        new: wasmtime_component_valtuple_new,
+        empty: wasmtime_component_valtuple_new_empty,
+        uninit: wasmtime_component_valtuple_new_uninit,
+        copy: wasmtime_component_valtuple_copy,
+        delete: wasmtime_component_valtuple_delete,
+    )
+    (
+        name: wasmtime_component_valflags_t,
+        ty: wasm_name_t,
+        new: wasmtime_component_valflags_new,
+        empty: wasmtime_component_valflags_new_empty,
+        uninit: wasmtime_component_valflags_new_uninit,
+        copy: wasmtime_component_valflags_copy,
+        delete: wasmtime_component_valflags_delete,
+    )
+}
+
+#[repr(C)]
+#[derive(Clone, Default)]
+pub struct wasmtime_component_valrecord_entry_t {
+    pub name: wasm_name_t,
+    pub val: wasmtime_component_val_t,
+}
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct wasmtime_component_valvariant_t {
+    pub discriminant: wasm_name_t,
+    pub val: Option<Box<wasmtime_component_val_t>>,
+}
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct wasmtime_component_valresult_t {
+    pub is_ok: bool,
+    pub val: Option<Box<wasmtime_component_val_t>>,
+}
+
+/// Representation of a component model value in the C API, mirroring
+/// `wasmtime::component::Val`.
+///
+/// The layout of this enum must be kept in sync with
+/// `wasmtime_component_val_t` in `wasmtime/component/val.h`.
+#[repr(C, u8)]
+#[derive(Clone)]
+pub enum wasmtime_component_val_t {
+    Bool(bool),
+    S8(i8),
+    U8(u8),
+    S16(i16),
+    U16(u16),
+    S32(i32),
+    U32(u32),
+    S64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Char(u32),
+    String(wasm_name_t),
+    List(wasmtime_component_vallist_t),
+    Record(wasmtime_component_valrecord_t),
+    Tuple(wasmtime_component_valtuple_t),
+    Variant(wasmtime_component_valvariant_t),
+    Enum(wasm_name_t),
+    Option(Option<Box<wasmtime_component_val_t>>),
+    Result(wasmtime_component_valresult_t),
+    Flags(wasmtime_component_valflags_t),
+    Resource(Box<wasmtime_component_resource_any_t>),
+}
+
+impl Default for wasmtime_component_val_t {
+    fn default() -> Self {
+        wasmtime_component_val_t::Bool(false)
+    }
+}
+
+fn name_to_string(name: &wasm_name_t) -> Result<String> {
+    let name = str::from_utf8(name.as_slice()).map_err(|_| anyhow!("invalid utf-8 string"))?;
+    Ok(name.to_string())
+}
+
+fn boxed_to_val(val: &Option<Box<wasmtime_component_val_t>>) -> Result<Option<Box<Val>>> {
+    match val {
+        Some(val) => Ok(Some(Box::new(val.to_val()?))),
+        None => Ok(None),
+    }
+}
+
+fn boxed_from_val(val: &Option<Box<Val>>) -> Option<Box<wasmtime_component_val_t>> {
+    val.as_ref()
+        .map(|val| Box::new(wasmtime_component_val_t::from_val(val)))
+}
+
+impl wasmtime_component_val_t {
+    pub fn from_val(val: &Val) -> wasmtime_component_val_t {
+        match val {
+            Val::Bool(b) => wasmtime_component_val_t::Bool(*b),
+            Val::S8(i) => wasmtime_component_val_t::S8(*i),
+            Val::U8(i) => wasmtime_component_val_t::U8(*i),
+            Val::S16(i) => wasmtime_component_val_t::S16(*i),
+            Val::U16(i) => wasmtime_component_val_t::U16(*i),
+            Val::S32(i) => wasmtime_component_val_t::S32(*i),
+            Val::U32(i) => wasmtime_component_val_t::U32(*i),
+            Val::S64(i) => wasmtime_component_val_t::S64(*i),
+            Val::U64(i) => wasmtime_component_val_t::U64(*i),
+            Val::Float32(f) => wasmtime_component_val_t::F32(*f),
+            Val::Float64(f) => wasmtime_component_val_t::F64(*f),
+            Val::Char(c) => wasmtime_component_val_t::Char(u32::from(*c)),
+            Val::String(s) => wasmtime_component_val_t::String(wasm_name_t::from_name(s.clone())),
+            Val::List(vals) => wasmtime_component_val_t::List(
+                vals.iter()
+                    .map(wasmtime_component_val_t::from_val)
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            Val::Record(fields) => wasmtime_component_val_t::Record(
+                fields
+                    .iter()
+                    .map(|(name, val)| wasmtime_component_valrecord_entry_t {
+                        name: wasm_name_t::from_name(name.clone()),
+                        val: wasmtime_component_val_t::from_val(val),
+                    })
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            Val::Tuple(vals) => wasmtime_component_val_t::Tuple(
+                vals.iter()
+                    .map(wasmtime_component_val_t::from_val)
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            Val::Variant(discriminant, val) => {
+                wasmtime_component_val_t::Variant(wasmtime_component_valvariant_t {
+                    discriminant: wasm_name_t::from_name(discriminant.clone()),
+                    val: boxed_from_val(val),
+                })
+            }
+            Val::Enum(name) => wasmtime_component_val_t::Enum(wasm_name_t::from_name(name.clone())),
+            Val::Option(val) => wasmtime_component_val_t::Option(boxed_from_val(val)),
+            Val::Result(result) => wasmtime_component_val_t::Result(match result {
+                Ok(val) => wasmtime_component_valresult_t {
+                    is_ok: true,
+                    val: boxed_from_val(val),
+                },
+                Err(val) => wasmtime_component_valresult_t {
+                    is_ok: false,
+                    val: boxed_from_val(val),
+                },
+            }),
+            Val::Flags(flags) => wasmtime_component_val_t::Flags(
+                flags
+                    .iter()
+                    .map(|name| wasm_name_t::from_name(name.clone()))
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            Val::Resource(resource) => {
+                wasmtime_component_val_t::Resource(Box::new(wasmtime_component_resource_any_t {
+                    resource: *resource,
+                }))
+            }
+        }
+    }
+
+    pub fn to_val(&self) -> Result<Val> {
+        Ok(match self {
+            wasmtime_component_val_t::Bool(b) => Val::Bool(*b),
+            wasmtime_component_val_t::S8(i) => Val::S8(*i),
+            wasmtime_component_val_t::U8(i) => Val::U8(*i),
+            wasmtime_component_val_t::S16(i) => Val::S16(*i),
+            wasmtime_component_val_t::U16(i) => Val::U16(*i),
+            wasmtime_component_val_t::S32(i) => Val::S32(*i),
+            wasmtime_component_val_t::U32(i) => Val::U32(*i),
+            wasmtime_component_val_t::S64(i) => Val::S64(*i),
+            wasmtime_component_val_t::U64(i) => Val::U64(*i),
+            wasmtime_component_val_t::F32(f) => Val::Float32(*f),
+            wasmtime_component_val_t::F64(f) => Val::Float64(*f),
+            wasmtime_component_val_t::Char(c) => Val::Char(
+                char::from_u32(*c).ok_or_else(|| anyhow!("invalid unicode scalar value {c:#x}"))?,
+            ),
+            wasmtime_component_val_t::String(s) => Val::String(name_to_string(s)?),
+            wasmtime_component_val_t::List(vals) => Val::List(
+                vals.as_slice()
+                    .iter()
+                    .map(|v| v.to_val())
+                    .collect::<Result<_>>()?,
+            ),
+            wasmtime_component_val_t::Record(fields) => Val::Record(
+                fields
+                    .as_slice()
+                    .iter()
+                    .map(|entry| Ok((name_to_string(&entry.name)?, entry.val.to_val()?)))
+                    .collect::<Result<_>>()?,
+            ),
+            wasmtime_component_val_t::Tuple(vals) => Val::Tuple(
+                vals.as_slice()
+                    .iter()
+                    .map(|v| v.to_val())
+                    .collect::<Result<_>>()?,
+            ),
+            wasmtime_component_val_t::Variant(variant) => Val::Variant(
+                name_to_string(&variant.discriminant)?,
+                boxed_to_val(&variant.val)?,
+            ),
+            wasmtime_component_val_t::Enum(name) => Val::Enum(name_to_string(name)?),
+            wasmtime_component_val_t::Option(val) => Val::Option(boxed_to_val(val)?),
+            wasmtime_component_val_t::Result(result) => {
+                let val = boxed_to_val(&result.val)?;
+                Val::Result(if result.is_ok { Ok(val) } else { Err(val) })
+            }
+            wasmtime_component_val_t::Flags(flags) => Val::Flags(
+                flags
+                    .as_slice()
+                    .iter()
+                    .map(name_to_string)
+                    .collect::<Result<_>>()?,
+            ),
+            wasmtime_component_val_t::Resource(resource) => Val::Resource(resource.resource),
+        })
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_val_new(
+    val: &mut wasmtime_component_val_t,
+) -> Box<wasmtime_component_val_t> {
+    Box::new(mem::take(val))
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_val_free(_val: Option<Box<wasmtime_component_val_t>>) {}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_val_clone(
+    src: &wasmtime_component_val_t,
+    dst: &mut MaybeUninit<wasmtime_component_val_t>,
+) {
+    crate::initialize(dst, src.clone());
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_val_delete(val: &mut wasmtime_component_val_t) {
+    drop(mem::take(val));
+}
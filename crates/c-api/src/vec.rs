@@ -97,6 +97,12 @@ macro_rules! declare_vecs {
             }
         }
 
+        impl$(<$lt>)? Default for $name $(<$lt>)? {
+            fn default() -> Self {
+                Vec::new().into()
+            }
+        }
+
         impl$(<$lt>)? Drop for $name $(<$lt>)? {
             fn drop(&mut self) {
                 drop(self.take());
@@ -139,6 +145,8 @@ macro_rules! declare_vecs {
     )*};
 }
 
+pub(crate) use declare_vecs;
+
 declare_vecs! {
     (
         name: wasm_byte_vec_t,
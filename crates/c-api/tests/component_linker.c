@@ -0,0 +1,218 @@
+// Tests for defining the imports of a component with
+// `wasmtime_component_linker_t` and for host functions.
+
+#include "component_test.h"
+
+static const char *component_wat =
+    "(component\n"
+    "  (import \"host:test/math\" (instance $math\n"
+    "    (export \"add\" (func (param \"a\" u32) (param \"b\" u32) (result u32)))\n"
+    "  ))\n"
+    "  (import \"fail\" (func $fail))\n"
+    "\n"
+    "  (core func $add (canon lower (func $math \"add\")))\n"
+    "  (core func $fail (canon lower (func $fail)))\n"
+    "\n"
+    "  (core module $m\n"
+    "    (import \"host\" \"add\" (func $add (param i32 i32) (result i32)))\n"
+    "    (import \"host\" \"fail\" (func $fail))\n"
+    "    (func (export \"add\") (param i32 i32) (result i32)\n"
+    "      (call $add (local.get 0) (local.get 1)))\n"
+    "    (func (export \"fail\") (call $fail))\n"
+    "  )\n"
+    "  (core instance $i (instantiate $m\n"
+    "    (with \"host\" (instance\n"
+    "      (export \"add\" (func $add))\n"
+    "      (export \"fail\" (func $fail))\n"
+    "    ))\n"
+    "  ))\n"
+    "\n"
+    "  (func $add-lifted (param \"a\" u32) (param \"b\" u32) (result u32)\n"
+    "    (canon lift (core func $i \"add\")))\n"
+    "  (export \"add\" (func $add-lifted))\n"
+    "  (func (export \"fail\") (canon lift (core func $i \"fail\")))\n"
+    "  (instance $nested (export \"add\" (func $add-lifted)))\n"
+    "  (export \"nested\" (instance $nested))\n"
+    ")\n";
+
+struct host_state {
+  int calls;
+  int finalized;
+  // Whether `add` should return a value of the wrong type.
+  int wrong_result;
+};
+
+static wasmtime_error_t *add_callback(void *data, wasmtime_context_t *context,
+                                      const wasmtime_component_val_t *args,
+                                      size_t nargs,
+                                      wasmtime_component_val_t *results,
+                                      size_t nresults) {
+  struct host_state *state = data;
+  state->calls++;
+  CHECK(nargs == 2 && nresults == 1);
+  CHECK(args[0].kind == WASMTIME_COMPONENT_U32);
+  CHECK(args[1].kind == WASMTIME_COMPONENT_U32);
+  // Results start out as `bool` values.
+  CHECK(results[0].kind == WASMTIME_COMPONENT_BOOL);
+  if (state->wrong_result) {
+    results[0].kind = WASMTIME_COMPONENT_S64;
+    results[0].of.s64 = 0;
+  } else {
+    results[0].kind = WASMTIME_COMPONENT_U32;
+    results[0].of.u32 = args[0].of.u32 + args[1].of.u32;
+  }
+  return NULL;
+}
+
+static wasmtime_error_t *fail_callback(void *data, wasmtime_context_t *context,
+                                       const wasmtime_component_val_t *args,
+                                       size_t nargs,
+                                       wasmtime_component_val_t *results,
+                                       size_t nresults) {
+  CHECK(nargs == 0 && nresults == 0);
+  return wasmtime_error_new("host function failed");
+}
+
+static void finalize_state(void *data) {
+  struct host_state *state = data;
+  state->finalized++;
+}
+
+static uint32_t call_add(wasmtime_component_func_t *func,
+                         wasmtime_context_t *context, uint32_t a, uint32_t b) {
+  wasmtime_component_val_t args[2];
+  args[0].kind = WASMTIME_COMPONENT_U32;
+  args[0].of.u32 = a;
+  args[1].kind = WASMTIME_COMPONENT_U32;
+  args[1].of.u32 = b;
+  wasmtime_component_val_t result;
+  CHECK_OK(wasmtime_component_func_call(func, context, args, 2, &result, 1));
+  CHECK(result.kind == WASMTIME_COMPONENT_U32);
+  return result.of.u32;
+}
+
+// Instantiation fails if imports are missing, and unknown imports can be
+// defined as functions which trap.
+static void test_missing_imports(wasm_engine_t *engine,
+                                 wasmtime_component_t *component) {
+  wasmtime_store_t *store = wasmtime_store_new(engine, NULL, NULL);
+  wasmtime_context_t *context = wasmtime_store_context(store);
+  wasmtime_component_linker_t *linker = wasmtime_component_linker_new(engine);
+  wasmtime_component_instance_t instance;
+
+  CHECK_ERR(wasmtime_component_linker_instantiate(linker, context, component,
+                                                  &instance),
+            "host:test/math");
+
+  CHECK_OK(wasmtime_component_linker_define_unknown_imports_as_traps(
+      linker, component));
+  CHECK_OK(wasmtime_component_linker_instantiate(linker, context, component,
+                                                 &instance));
+  wasmtime_component_func_t fail =
+      get_func(component, &instance, context, NULL, "fail");
+  CHECK_ERR(wasmtime_component_func_call(&fail, context, NULL, 0, NULL, 0),
+            "fail");
+
+  wasmtime_component_linker_delete(linker);
+  wasmtime_store_delete(store);
+}
+
+static void test_host_functions(wasm_engine_t *engine,
+                                wasmtime_component_t *component) {
+  struct host_state state = {0, 0, 0};
+  wasmtime_store_t *store = wasmtime_store_new(engine, NULL, NULL);
+  wasmtime_context_t *context = wasmtime_store_context(store);
+  wasmtime_component_linker_t *linker = wasmtime_component_linker_new(engine);
+
+  wasmtime_component_linker_instance_t *root =
+      wasmtime_component_linker_root(linker);
+  wasmtime_component_linker_instance_t *math = NULL;
+  CHECK_OK(wasmtime_component_linker_instance_add_instance(
+      root, "host:test/math", strlen("host:test/math"), &math));
+  CHECK_OK(wasmtime_component_linker_instance_add_func(
+      math, "add", strlen("add"), add_callback, &state, finalize_state));
+  wasmtime_component_linker_instance_delete(math);
+  CHECK_OK(wasmtime_component_linker_instance_add_func(
+      root, "fail", strlen("fail"), fail_callback, NULL, NULL));
+
+  // Definitions can't be shadowed by default.
+  CHECK_ERR(wasmtime_component_linker_instance_add_func(
+                root, "fail", strlen("fail"), fail_callback, NULL, NULL),
+            "fail");
+  CHECK_ERR(wasmtime_component_linker_instance_add_instance(
+                root, "host:test/math", strlen("host:test/math"), &math),
+            "host:test/math");
+  wasmtime_component_linker_instance_delete(root);
+
+  wasmtime_component_linker_allow_shadowing(linker, true);
+  root = wasmtime_component_linker_root(linker);
+  CHECK_OK(wasmtime_component_linker_instance_add_func(
+      root, "fail", strlen("fail"), fail_callback, NULL, NULL));
+  wasmtime_component_linker_instance_delete(root);
+
+  wasmtime_component_instance_t instance;
+  CHECK_OK(wasmtime_component_linker_instantiate(linker, context, component,
+                                                 &instance));
+
+  // Call `add` through both the root export and the nested instance.
+  wasmtime_component_func_t add =
+      get_func(component, &instance, context, NULL, "add");
+  CHECK(call_add(&add, context, 1, 2) == 3);
+  wasmtime_component_export_index_t *nested =
+      wasmtime_component_get_export_index(component, NULL, "nested",
+                                          strlen("nested"));
+  CHECK(nested != NULL);
+  wasmtime_component_func_t nested_add =
+      get_func(component, &instance, context, nested, "add");
+  wasmtime_component_export_index_delete(nested);
+  CHECK(call_add(&nested_add, context, 3, 4) == 7);
+  CHECK(state.calls == 2);
+
+  // Arguments and results are type-checked.
+  wasmtime_component_val_t arg;
+  arg.kind = WASMTIME_COMPONENT_STRING;
+  wasm_name_new_from_string(&arg.of.string, "not a u32");
+  wasmtime_component_val_t args[2] = {arg, arg};
+  wasmtime_component_val_t result;
+  CHECK_ERR(wasmtime_component_func_call(&add, context, args, 2, &result, 1),
+            "type mismatch");
+  CHECK_ERR(wasmtime_component_func_call(&add, context, args, 1, &result, 1),
+            "expected 2 argument(s), got 1");
+  wasmtime_component_val_delete(&arg);
+  CHECK(state.calls == 2);
+
+  // A host function returning a value of the wrong type traps the guest.
+  state.wrong_result = 1;
+  wasmtime_component_val_t good[2];
+  good[0].kind = WASMTIME_COMPONENT_U32;
+  good[0].of.u32 = 1;
+  good[1] = good[0];
+  CHECK_ERR(wasmtime_component_func_call(&nested_add, context, good, 2,
+                                         &result, 1),
+            "type mismatch");
+  CHECK(state.calls == 3);
+
+  // Errors returned from host functions are propagated to the caller.
+  wasmtime_component_func_t fail =
+      get_func(component, &instance, context, NULL, "fail");
+  CHECK_ERR(wasmtime_component_func_call(&fail, context, NULL, 0, NULL, 0),
+            "host function failed");
+
+  // The host data is finalized along with the linker.
+  CHECK(state.finalized == 0);
+  wasmtime_component_linker_delete(linker);
+  wasmtime_store_delete(store);
+  CHECK(state.finalized == 1);
+}
+
+int main() {
+  wasm_engine_t *engine = wasm_engine_new();
+  wasmtime_component_t *component = compile_component(engine, component_wat);
+
+  test_missing_imports(engine, component);
+  test_host_functions(engine, component);
+
+  wasmtime_component_delete(component);
+  wasm_engine_delete(engine);
+  return 0;
+}
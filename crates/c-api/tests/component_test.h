@@ -0,0 +1,92 @@
+#ifndef WASMTIME_COMPONENT_TEST_H
+#define WASMTIME_COMPONENT_TEST_H
+
+// Small helpers shared by the component model tests of the C API.
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <wasm.h>
+#include <wasmtime.h>
+
+// Like `assert`, but not compiled out in release builds.
+#define CHECK(cond)                                                            \
+  do {                                                                         \
+    if (!(cond)) {                                                             \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,        \
+              #cond);                                                          \
+      abort();                                                                 \
+    }                                                                          \
+  } while (0)
+
+// Aborts with the message of `error` if it's not `NULL`.
+#define CHECK_OK(error)                                                        \
+  check_ok(__FILE__, __LINE__, #error, (error))
+
+// Checks that `error` is not `NULL` and that its message contains `needle`,
+// then deletes it.
+#define CHECK_ERR(error, needle)                                               \
+  check_err(__FILE__, __LINE__, #error, (error), (needle))
+
+static inline void check_ok(const char *file, int line, const char *expr,
+                            wasmtime_error_t *error) {
+  if (error == NULL)
+    return;
+  wasm_name_t message;
+  wasmtime_error_message(error, &message);
+  fprintf(stderr, "%s:%d: %s failed: %.*s\n", file, line, expr,
+          (int)message.size, message.data);
+  abort();
+}
+
+static inline void check_err(const char *file, int line, const char *expr,
+                             wasmtime_error_t *error, const char *needle) {
+  if (error == NULL) {
+    fprintf(stderr, "%s:%d: %s unexpectedly succeeded\n", file, line, expr);
+    abort();
+  }
+  wasm_name_t message;
+  wasmtime_error_message(error, &message);
+  wasmtime_error_delete(error);
+  // Copy the message to make it nul-terminated for `strstr`.
+  char *s = malloc(message.size + 1);
+  memcpy(s, message.data, message.size);
+  s[message.size] = '\0';
+  wasm_name_delete(&message);
+  if (strstr(s, needle) == NULL) {
+    fprintf(stderr, "%s:%d: %s failed with `%s`, expected `%s`\n", file, line,
+            expr, s, needle);
+    abort();
+  }
+  free(s);
+}
+
+// Compiles the text format of a component.
+static inline wasmtime_component_t *compile_component(wasm_engine_t *engine,
+                                                      const char *wat) {
+  wasm_byte_vec_t wasm;
+  CHECK_OK(wasmtime_wat2wasm(wat, strlen(wat), &wasm));
+  wasmtime_component_t *component = NULL;
+  CHECK_OK(wasmtime_component_new(engine, (uint8_t *)wasm.data, wasm.size,
+                                  &component));
+  wasm_byte_vec_delete(&wasm);
+  return component;
+}
+
+// Looks up the function exported as `name` from `instance`, optionally within
+// the exported instance `parent`.
+static inline wasmtime_component_func_t
+get_func(const wasmtime_component_t *component,
+         wasmtime_component_instance_t *instance, wasmtime_context_t *context,
+         const wasmtime_component_export_index_t *parent, const char *name) {
+  wasmtime_component_export_index_t *index =
+      wasmtime_component_get_export_index(component, parent, name,
+                                          strlen(name));
+  CHECK(index != NULL);
+  wasmtime_component_func_t func;
+  CHECK(wasmtime_component_instance_get_func(instance, context, index, &func));
+  wasmtime_component_export_index_delete(index);
+  return func;
+}
+
+#endif // WASMTIME_COMPONENT_TEST_H
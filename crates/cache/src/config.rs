@@ -1,6 +1,6 @@
 //! Module for configuring the cache system.
 
-use super::Worker;
+use super::{CacheStorage, Worker};
 use anyhow::{anyhow, bail, Context, Result};
 use directories_next::ProjectDirs;
 use log::{trace, warn};
@@ -92,6 +92,8 @@ pub struct CacheConfig {
     worker: Option<Worker>,
     #[serde(skip)]
     state: Arc<CacheState>,
+    #[serde(skip)]
+    storage: Option<Arc<dyn CacheStorage>>,
 }
 
 #[derive(Default, Debug)]
@@ -333,6 +335,7 @@ impl CacheConfig {
             files_total_size_limit_percent_if_deleting: None,
             worker: None,
             state: Arc::new(CacheState::default()),
+            storage: None,
         }
     }
 
@@ -370,6 +373,29 @@ impl CacheConfig {
         }
     }
 
+    /// Stores cache entries in `storage` instead of the cache directory.
+    ///
+    /// This enables the cache, with default settings, if it's disabled.
+    /// Settings which only apply to the cache directory, such as its
+    /// recompression and cleanup, have no effect on entries in `storage`.
+    ///
+    /// Entries read from `storage` are executed as native code, so it must
+    /// only be writable by trusted parties; see [`CacheStorage`].
+    pub fn set_storage(&mut self, storage: Arc<dyn CacheStorage>) {
+        if !self.enabled {
+            *self = Self::new_cache_enabled_template();
+            self.validate_baseline_compression_level_or_default()
+                .expect("the default compression level is valid");
+        }
+        self.storage = Some(storage);
+    }
+
+    /// Returns the storage configured with [`CacheConfig::set_storage`], if
+    /// any.
+    pub fn storage(&self) -> Option<&Arc<dyn CacheStorage>> {
+        self.storage.as_ref()
+    }
+
     pub(super) fn worker(&self) -> &Worker {
         assert!(self.enabled);
         self.worker.as_ref().unwrap()
@@ -386,15 +412,23 @@ impl CacheConfig {
     }
 
     pub(crate) fn on_cache_get_async(&self, path: impl AsRef<Path>) {
-        self.state.hits.fetch_add(1, SeqCst);
+        self.record_hit();
         self.worker().on_cache_get_async(path)
     }
 
     pub(crate) fn on_cache_update_async(&self, path: impl AsRef<Path>) {
-        self.state.misses.fetch_add(1, SeqCst);
+        self.record_miss();
         self.worker().on_cache_update_async(path)
     }
 
+    pub(crate) fn record_hit(&self) {
+        self.state.hits.fetch_add(1, SeqCst);
+    }
+
+    pub(crate) fn record_miss(&self) {
+        self.state.misses.fetch_add(1, SeqCst);
+    }
+
     fn load_and_parse_file(config_file: Option<&Path>) -> Result<Self> {
         // get config file path
         let (config_file, user_custom_file) = match config_file {
@@ -10,16 +10,20 @@ use std::{fs, io};
 
 #[macro_use] // for tests
 mod config;
+mod storage;
 mod worker;
 
 pub use config::{create_new_config, CacheConfig};
+pub use storage::{CacheStorage, DirectoryStorage};
 use worker::Worker;
 
 /// Module level cache entry.
 pub struct ModuleCacheEntry<'config>(Option<ModuleCacheEntryInner<'config>>);
 
 struct ModuleCacheEntryInner<'config> {
-    root_path: PathBuf,
+    /// Identifies the compiler which produced this entry, so that entries of
+    /// different compiler versions don't collide.
+    compiler_dir: String,
     cache_config: &'config CacheConfig,
 }
 
@@ -91,16 +95,14 @@ impl<'config> ModuleCacheEntry<'config> {
 
         if let Some(cached_val) = inner.get_data(&hash) {
             if let Some(val) = deserialize(state, cached_val) {
-                let mod_cache_path = inner.root_path.join(&hash);
-                inner.cache_config.on_cache_get_async(&mod_cache_path); // call on success
+                inner.on_cache_get(&hash); // call on success
                 return Ok(val);
             }
         }
         let val_to_cache = compute(state)?;
         if let Some(bytes) = serialize(state, &val_to_cache) {
             if inner.update_data(&hash, &bytes).is_some() {
-                let mod_cache_path = inner.root_path.join(&hash);
-                inner.cache_config.on_cache_update_async(&mod_cache_path); // call on success
+                inner.on_cache_update(&hash); // call on success
             }
         }
         Ok(val_to_cache)
@@ -142,34 +144,75 @@ impl<'config> ModuleCacheEntryInner<'config> {
                 comp_ver = env!("GIT_REV"),
             )
         };
-        let root_path = cache_config.directory().join("modules").join(compiler_dir);
 
         Self {
-            root_path,
+            compiler_dir,
             cache_config,
         }
     }
 
+    /// Path of the entry for `hash` in the cache directory.
+    fn entry_path(&self, hash: &str) -> PathBuf {
+        self.cache_config
+            .directory()
+            .join("modules")
+            .join(&self.compiler_dir)
+            .join(hash)
+    }
+
+    /// Key of the entry for `hash` in custom cache storage.
+    fn storage_key(&self, hash: &str) -> String {
+        format!("{}/{}", self.compiler_dir, hash)
+    }
+
+    fn on_cache_get(&self, hash: &str) {
+        match self.cache_config.storage() {
+            Some(_) => self.cache_config.record_hit(),
+            None => self.cache_config.on_cache_get_async(self.entry_path(hash)),
+        }
+    }
+
+    fn on_cache_update(&self, hash: &str) {
+        match self.cache_config.storage() {
+            Some(_) => self.cache_config.record_miss(),
+            None => self
+                .cache_config
+                .on_cache_update_async(self.entry_path(hash)),
+        }
+    }
+
     fn get_data(&self, hash: &str) -> Option<Vec<u8>> {
-        let mod_cache_path = self.root_path.join(hash);
-        trace!("get_data() for path: {}", mod_cache_path.display());
-        let compressed_cache_bytes = fs::read(&mod_cache_path).ok()?;
+        let compressed_cache_bytes = match self.cache_config.storage() {
+            Some(storage) => storage.get(&self.storage_key(hash))?,
+            None => {
+                let mod_cache_path = self.entry_path(hash);
+                trace!("get_data() for path: {}", mod_cache_path.display());
+                fs::read(&mod_cache_path).ok()?
+            }
+        };
         let cache_bytes = zstd::decode_all(&compressed_cache_bytes[..])
             .map_err(|err| warn!("Failed to decompress cached code: {}", err))
             .ok()?;
-        Some(cache_bytes)
+        unseal(cache_bytes)
     }
 
     fn update_data(&self, hash: &str, serialized_data: &[u8]) -> Option<()> {
-        let mod_cache_path = self.root_path.join(hash);
-        trace!("update_data() for path: {}", mod_cache_path.display());
         let compressed_data = zstd::encode_all(
-            &serialized_data[..],
+            &seal(serialized_data)[..],
             self.cache_config.baseline_compression_level(),
         )
         .map_err(|err| warn!("Failed to compress cached code: {}", err))
         .ok()?;
 
+        if let Some(storage) = self.cache_config.storage() {
+            return storage
+                .insert(&self.storage_key(hash), &compressed_data)
+                .then_some(());
+        }
+
+        let mod_cache_path = self.entry_path(hash);
+        trace!("update_data() for path: {}", mod_cache_path.display());
+
         // Optimize syscalls: first, try writing to disk. It should succeed in most cases.
         // Otherwise, try creating the cache directory and retry writing to the file.
         if fs_write_atomic(&mod_cache_path, "mod", &compressed_data).is_ok() {
@@ -207,6 +250,36 @@ impl<'config> ModuleCacheEntryInner<'config> {
     }
 }
 
+/// Prefixes `data` with its SHA-256 digest so that it can be checked for
+/// integrity by [`unseal`] when read back from storage.
+///
+/// This detects corruption, not tampering: whoever can write an entry can
+/// also write its digest, which is why storage must be trusted.
+fn seal(data: &[u8]) -> Vec<u8> {
+    let digest = Sha256::digest(data);
+    let mut sealed = Vec::with_capacity(digest.len() + data.len());
+    sealed.extend_from_slice(&digest);
+    sealed.extend_from_slice(data);
+    sealed
+}
+
+/// Checks the digest of data prefixed by [`seal`], returning the data if it's
+/// intact.
+fn unseal(mut sealed: Vec<u8>) -> Option<Vec<u8>> {
+    const DIGEST_LEN: usize = 32;
+    if sealed.len() < DIGEST_LEN {
+        warn!("Cached code is truncated");
+        return None;
+    }
+    let (digest, data) = sealed.split_at(DIGEST_LEN);
+    if Sha256::digest(data)[..] != *digest {
+        warn!("Cached code failed its integrity check");
+        return None;
+    }
+    sealed.drain(..DIGEST_LEN);
+    Some(sealed)
+}
+
 impl Hasher for Sha256Hasher {
     fn finish(&self) -> u64 {
         panic!("Sha256Hasher doesn't support finish!");
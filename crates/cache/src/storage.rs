@@ -0,0 +1,121 @@
+//! Pluggable storage backends for the cache.
+
+use log::{trace, warn};
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A storage backend for cached compilation artifacts.
+///
+/// By default the cache stores its entries in the cache directory, managed by
+/// a background worker that recompresses and evicts them. Embedders can
+/// instead provide their own storage, for example to share compiled artifacts
+/// between machines through a network file system or a blob server.
+///
+/// Entries are content-addressed: keys are derived from a hash of everything
+/// that went into compiling an entry, so the value stored under a key never
+/// changes and entries may be written concurrently by several processes.
+/// Values are checked for integrity when read, so storage doesn't need to
+/// guarantee that reads return exactly what was written; corrupted or
+/// truncated entries are treated as cache misses.
+///
+/// # Security
+///
+/// Entries contain native code which is loaded and executed without being
+/// validated again, so storage must be trusted as much as the compiler
+/// itself. The integrity check is an unkeyed digest stored alongside each
+/// entry, which only detects accidental damage: anyone who can write to the
+/// storage can replace an entry and its digest, and so run arbitrary code in
+/// every process reading from it. Only use a shared network file system or
+/// blob server that is writable solely by trusted parties.
+pub trait CacheStorage: Send + Sync + Debug {
+    /// Returns the value stored under `key`, if any.
+    ///
+    /// Keys are relative paths made of URL-safe characters, with `/`
+    /// separating a component identifying the compiler from the entry's hash.
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+
+    /// Stores `value` under `key`, returning whether it was stored.
+    ///
+    /// Failing to store an entry isn't an error, it just means the entry will
+    /// be compiled again next time.
+    fn insert(&self, key: &str, value: &[u8]) -> bool;
+}
+
+/// A [`CacheStorage`] storing entries as files in a directory, without any
+/// eviction.
+///
+/// Writes are atomic, so the directory may be shared between processes and
+/// machines, for example on a network file system. See the security notes on
+/// [`CacheStorage`] before doing so.
+#[derive(Debug, Clone)]
+pub struct DirectoryStorage {
+    root: PathBuf,
+}
+
+impl DirectoryStorage {
+    /// Creates storage for entries in the directory `root`, which is created
+    /// when the first entry is stored if it doesn't exist.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl CacheStorage for DirectoryStorage {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let path = self.root.join(key);
+        trace!("DirectoryStorage::get() for path: {}", path.display());
+        fs::read(&path).ok()
+    }
+
+    fn insert(&self, key: &str, value: &[u8]) -> bool {
+        let path = self.root.join(key);
+        trace!("DirectoryStorage::insert() for path: {}", path.display());
+        if write_atomic(&path, value).is_ok() {
+            return true;
+        }
+
+        let dir = path.parent().unwrap();
+        if let Err(err) = fs::create_dir_all(dir) {
+            warn!(
+                "Failed to create cache directory, path: {}, message: {}",
+                dir.display(),
+                err
+            );
+            return false;
+        }
+        match write_atomic(&path, value) {
+            Ok(()) => true,
+            Err(err) => {
+                warn!(
+                    "Failed to write file with rename, target path: {}, err: {}",
+                    path.display(),
+                    err
+                );
+                false
+            }
+        }
+    }
+}
+
+/// Writes `contents` to `path` by writing a temporary file next to it and
+/// renaming it into place.
+///
+/// Unlike the cache directory, shared storage has no worker cleaning up after
+/// writers which crashed midway, so temporary files are named uniquely per
+/// writer rather than doubling as a lock, and are removed on failure.
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let tmp_path = path.with_extension(format!(
+        "wip-{}-{}",
+        std::process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = fs::write(&tmp_path, contents).and_then(|()| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
@@ -1,5 +1,7 @@
 use super::config::tests::test_prolog;
 use super::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 // Since cache system is a global thing, each test needs to be run in separate process.
 // So, init() tests are run as integration tests.
@@ -89,3 +91,82 @@ fn test_write_read_cache() {
     entry1.get_data::<_, i32, i32>(4, |_| panic!()).unwrap();
     entry2.get_data::<_, i32, i32>(1, |_| panic!()).unwrap();
 }
+
+#[derive(Debug, Default)]
+struct MemoryStorage(Mutex<HashMap<String, Vec<u8>>>);
+
+impl CacheStorage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: &str, value: &[u8]) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_vec());
+        true
+    }
+}
+
+#[test]
+fn test_write_read_custom_storage() {
+    let storage = Arc::new(MemoryStorage::default());
+    let mut cache_config = CacheConfig::new_cache_disabled();
+    cache_config.set_storage(storage.clone());
+    assert!(cache_config.enabled());
+
+    let entry = ModuleCacheEntry::new("test", &cache_config);
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap(), 100);
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| panic!()).unwrap(), 100);
+    assert_eq!(entry.get_data::<_, i32, i32>(2, |_| Ok(200)).unwrap(), 200);
+    assert_eq!(entry.get_data::<_, i32, i32>(2, |_| panic!()).unwrap(), 200);
+
+    assert_eq!(storage.0.lock().unwrap().len(), 2);
+    assert_eq!(cache_config.cache_hits(), 2);
+    assert_eq!(cache_config.cache_misses(), 2);
+}
+
+#[test]
+fn test_corrupted_entries_are_recomputed() {
+    let storage = Arc::new(MemoryStorage::default());
+    let mut cache_config = CacheConfig::new_cache_disabled();
+    cache_config.set_storage(storage.clone());
+    let entry = ModuleCacheEntry::new("test", &cache_config);
+    entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap();
+
+    // Truncated entries fail to decompress.
+    for value in storage.0.lock().unwrap().values_mut() {
+        value.truncate(value.len() / 2);
+    }
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap(), 100);
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| panic!()).unwrap(), 100);
+
+    // Entries whose contents changed fail their integrity check.
+    for value in storage.0.lock().unwrap().values_mut() {
+        let mut data = zstd::decode_all(&value[..]).unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        *value = zstd::encode_all(&data[..], 3).unwrap();
+    }
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap(), 100);
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| panic!()).unwrap(), 100);
+}
+
+#[test]
+fn test_write_read_directory_storage() {
+    let (_tempdir, cache_dir, _config_path) = test_prolog();
+    let mut cache_config = CacheConfig::new_cache_disabled();
+    cache_config.set_storage(Arc::new(DirectoryStorage::new(&cache_dir)));
+
+    let entry = ModuleCacheEntry::new("test", &cache_config);
+    entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap();
+    entry.get_data::<_, i32, i32>(1, |_| panic!()).unwrap();
+
+    // Another process sharing the directory sees the same entries.
+    let mut other_config = CacheConfig::new_cache_disabled();
+    other_config.set_storage(Arc::new(DirectoryStorage::new(&cache_dir)));
+    let other_entry = ModuleCacheEntry::new("test", &other_config);
+    other_entry
+        .get_data::<_, i32, i32>(1, |_| panic!())
+        .unwrap();
+}
@@ -188,18 +188,23 @@ wasmtime_option_group! {
 wasmtime_option_group! {
     #[derive(PartialEq, Clone)]
     pub struct CodegenOptions {
-        /// Either `cranelift` or `winch`.
+        /// Either `cranelift`, `winch` or `tiered`.
         ///
-        /// Currently only `cranelift` and `winch` are supported, but not all
-        /// builds of Wasmtime have both built in.
+        /// `tiered` starts out with `winch` and recompiles hot functions with
+        /// `cranelift`. Not all builds of Wasmtime have both built in.
         pub compiler: Option<wasmtime::Strategy>,
-        /// Which garbage collector to use: `drc` or `null`.
+        /// Number of calls and loop iterations after which a function
+        /// compiled with `-C compiler=tiered` is recompiled with `cranelift`.
+        pub tier_up_threshold: Option<u32>,
+        /// Which garbage collector to use: `drc`, `null`, or `copying`.
         ///
         /// `drc` is the deferred reference-counting collector.
         ///
         /// `null` is the null garbage collector, which does not collect any
         /// garbage.
         ///
+        /// `copying` is the semi-space copying collector.
+        ///
         /// Note that not all builds of Wasmtime will have support for garbage
         /// collection included.
         pub collector: Option<wasmtime::Collector>,
@@ -213,6 +218,9 @@ wasmtime_option_group! {
         pub parallel_compilation: Option<bool>,
         /// Whether to enable proof-carrying code (PCC)-based validation.
         pub pcc: Option<bool>,
+        /// Whether to inline calls to small functions defined in the same
+        /// module.
+        pub inlining: Option<bool>,
         /// Controls whether native unwind information is present in compiled
         /// object files.
         pub native_unwind_info: Option<bool>,
@@ -415,8 +423,21 @@ wasmtime_option_group! {
         pub inherit_env: Option<bool>,
         /// Pass a wasi config variable to the program.
         pub config_var: Vec<KeyValuePair>,
+        /// Read wasi config variables from the given TOML or JSON file, which
+        /// is checked for changes every second. Variables passed with `config-var`
+        /// take precedence over those in the file.
+        pub config_file: Option<String>,
+        /// Expose host environment variables starting with the given prefix as
+        /// wasi config variables. The prefix is stripped and the rest of the
+        /// name lowercased, so `APP_PORT` with a prefix of `APP_` is visible
+        /// as `port`. These take precedence over `config-file`.
+        pub config_env_prefix: Option<String>,
         /// Preset data for the In-Memory provider of WASI key-value API.
         pub keyvalue_in_memory_data: Vec<KeyValuePair>,
+        /// Store the data of the WASI key-value API in an append-only log at
+        /// the given path, which is created if it doesn't exist. Data persists
+        /// between runs, and any bucket identifier may be opened.
+        pub keyvalue_file: Option<String>,
     }
 
     enum Wasi {
@@ -556,6 +577,9 @@ impl CommonOptions {
             strategy => config.strategy(strategy),
             _ => err,
         }
+        if let Some(threshold) = self.codegen.tier_up_threshold {
+            config.tier_up_threshold(threshold);
+        }
         match_feature! {
             ["gc" : self.codegen.collector]
             collector => config.collector(collector),
@@ -595,6 +619,11 @@ impl CommonOptions {
             enable => config.cranelift_pcc(enable),
             true => err,
         }
+        match_feature! {
+            ["cranelift" : self.codegen.inlining]
+            enable => config.cranelift_inlining(enable),
+            true => err,
+        }
 
         self.enable_wasm_features(&mut config)?;
 
@@ -375,23 +375,29 @@ impl WasmtimeOptionValue for wasmtime::OptLevel {
 }
 
 impl WasmtimeOptionValue for wasmtime::Strategy {
-    const VAL_HELP: &'static str = "=winch|cranelift";
+    const VAL_HELP: &'static str = "=winch|cranelift|tiered";
     fn parse(val: Option<&str>) -> Result<Self> {
         match String::parse(val)?.as_str() {
             "cranelift" => Ok(wasmtime::Strategy::Cranelift),
             "winch" => Ok(wasmtime::Strategy::Winch),
-            other => bail!("unknown compiler `{other}` only `cranelift` and `winch` accepted",),
+            "tiered" => Ok(wasmtime::Strategy::Tiered),
+            other => {
+                bail!("unknown compiler `{other}` only `cranelift`, `winch` and `tiered` accepted",)
+            }
         }
     }
 }
 
 impl WasmtimeOptionValue for wasmtime::Collector {
-    const VAL_HELP: &'static str = "=drc|null";
+    const VAL_HELP: &'static str = "=drc|null|copying";
     fn parse(val: Option<&str>) -> Result<Self> {
         match String::parse(val)?.as_str() {
             "drc" => Ok(wasmtime::Collector::DeferredReferenceCounting),
             "null" => Ok(wasmtime::Collector::Null),
-            other => bail!("unknown collector `{other}` only `drc` and `null` accepted",),
+            "copying" => Ok(wasmtime::Collector::Copying),
+            other => {
+                bail!("unknown collector `{other}` only `drc`, `null`, and `copying` accepted")
+            }
         }
     }
 }
@@ -7,6 +7,7 @@ use crate::{builder::LinkOptions, wasm_call_signature, BuiltinFunctionSignatures
 use anyhow::{Context as _, Result};
 use cranelift_codegen::binemit::CodeOffset;
 use cranelift_codegen::bitset::CompoundBitSet;
+use cranelift_codegen::inline::{Inline, InlineBudget, InlineCommand};
 use cranelift_codegen::ir::{self, InstBuilder, MemFlags, UserExternalName, UserFuncName, Value};
 use cranelift_codegen::isa::{
     unwind::{UnwindInfo, UnwindInfoKind},
@@ -19,17 +20,21 @@ use cranelift_frontend::FunctionBuilder;
 use object::write::{Object, StandardSegment, SymbolId};
 use object::{RelocationEncoding, RelocationFlags, RelocationKind, SectionKind};
 use std::any::Any;
+use std::borrow::Cow;
 use std::cmp;
 use std::collections::HashMap;
 use std::mem;
 use std::path;
 use std::sync::{Arc, Mutex};
-use wasmparser::{FuncValidatorAllocations, FunctionBody};
+use wasmparser::{
+    FuncToValidate, FuncValidator, FuncValidatorAllocations, FunctionBody, ValidatorResources,
+    WasmFeatures, WasmModuleResources,
+};
 use wasmtime_environ::{
     AddressMapSection, BuiltinFunctionIndex, CacheStore, CompileError, DefinedFuncIndex, FlagValue,
-    FunctionBodyData, FunctionLoc, ModuleTranslation, ModuleTypesBuilder, PtrSize,
-    RelocationTarget, StackMapInformation, StaticModuleIndex, TrapEncodingBuilder, Tunables,
-    VMOffsets, WasmFuncType, WasmFunctionInfo, WasmValType,
+    FuncIndex, FunctionBodyData, FunctionLoc, HostCall, ModuleTranslation, ModuleTypesBuilder,
+    PtrSize, RelocationTarget, StackMapInformation, StaticModuleIndex, TrapEncodingBuilder,
+    Tunables, VMOffsets, WasmFuncType, WasmFunctionInfo, WasmValType,
 };
 
 #[cfg(feature = "component-model")]
@@ -121,6 +126,136 @@ impl Compiler {
     }
 }
 
+/// The largest callee, in CLIF instructions, that is inlined.
+const INLINE_MAX_CALLEE_SIZE: usize = 100;
+
+/// How many CLIF instructions inlining may add to a function in total.
+const INLINE_MAX_GROWTH: usize = 1000;
+
+/// Supplies Cranelift's inliner with the bodies of the functions defined in
+/// the module being compiled.
+///
+/// Callees are translated from their wasm bodies on demand. Calls to
+/// imported functions are never inlined.
+struct WasmInliner<'a> {
+    compiler: &'a Compiler,
+    translation: &'a ModuleTranslation<'a>,
+    types: &'a ModuleTypesBuilder,
+    resources: &'a ValidatorResources,
+    features: WasmFeatures,
+    budget: InlineBudget,
+    func_translator: FuncTranslator,
+    validator_allocations: FuncValidatorAllocations,
+    callees: HashMap<DefinedFuncIndex, ir::Function>,
+}
+
+impl<'a> WasmInliner<'a> {
+    fn new(
+        compiler: &'a Compiler,
+        translation: &'a ModuleTranslation<'a>,
+        types: &'a ModuleTypesBuilder,
+        caller: &'a FuncValidator<ValidatorResources>,
+    ) -> Self {
+        WasmInliner {
+            compiler,
+            translation,
+            types,
+            resources: caller.resources(),
+            features: *caller.features(),
+            budget: InlineBudget::new(INLINE_MAX_CALLEE_SIZE, INLINE_MAX_GROWTH),
+            func_translator: FuncTranslator::new(),
+            validator_allocations: Default::default(),
+            callees: HashMap::new(),
+        }
+    }
+
+    /// Translates the body of the defined function `index` to CLIF.
+    ///
+    /// Returns `None` if the body isn't available or fails to translate, in
+    /// which case the error is reported when the function itself is
+    /// compiled.
+    fn translate_callee(&mut self, index: DefinedFuncIndex) -> Option<ir::Function> {
+        let isa = &*self.compiler.isa;
+        let body = self.translation.function_bodies.get(index)?.clone();
+        let func_index = self.translation.module.func_index(index);
+        let sig = self.translation.module.functions[func_index].signature;
+        let wasm_func_ty = self.types[sig].unwrap_func();
+
+        let mut func = ir::Function::with_name_signature(
+            UserFuncName::User(UserExternalName {
+                namespace: crate::NS_WASM_FUNC,
+                index: func_index.as_u32(),
+            }),
+            wasm_call_signature(isa, wasm_func_ty, &self.compiler.tunables),
+        );
+        let mut func_env = FuncEnvironment::new(
+            isa,
+            self.translation,
+            self.types,
+            &self.compiler.tunables,
+            self.compiler.wmemcheck,
+            wasm_func_ty,
+        );
+        // No stack limit is configured here: the caller's own check covers
+        // the stack used by inlined code.
+        // Callees are validated again as they're translated, against the
+        // same module as the caller.
+        let validator = FuncToValidate {
+            resources: self.resources,
+            index: func_index.as_u32(),
+            ty: self.resources.type_index_of_function(func_index.as_u32())?,
+            features: self.features,
+        };
+        let mut validator = validator.into_validator(mem::take(&mut self.validator_allocations));
+        let result =
+            self.func_translator
+                .translate_body(&mut validator, body, &mut func, &mut func_env);
+        self.validator_allocations = validator.into_allocations();
+        result.ok()?;
+
+        // Source locations of the callee's instructions are offsets into a
+        // different function than the caller's, which the address map can't
+        // represent. Attribute inlined code to the call site instead.
+        func.srclocs.clear();
+        Some(func)
+    }
+}
+
+impl Inline for WasmInliner<'_> {
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        _call_inst: ir::Inst,
+        _call_opcode: ir::Opcode,
+        callee: ir::FuncRef,
+        _call_args: &[Value],
+    ) -> InlineCommand<'_> {
+        let ir::ExternalName::User(name) = caller.dfg.ext_funcs[callee].name else {
+            return InlineCommand::KeepCall;
+        };
+        let name = &caller.params.user_named_funcs()[name];
+        if name.namespace != crate::NS_WASM_FUNC {
+            return InlineCommand::KeepCall;
+        }
+        let func_index = FuncIndex::from_u32(name.index);
+        let Some(index) = self.translation.module.defined_func_index(func_index) else {
+            return InlineCommand::KeepCall;
+        };
+
+        if !self.callees.contains_key(&index) {
+            let Some(func) = self.translate_callee(index) else {
+                return InlineCommand::KeepCall;
+            };
+            self.callees.insert(index, func);
+        }
+        let func = &self.callees[&index];
+        if !self.budget.try_spend(func) {
+            return InlineCommand::KeepCall;
+        }
+        InlineCommand::Inline(Cow::Borrowed(func))
+    }
+}
+
 impl wasmtime_environ::Compiler for Compiler {
     fn compile_function(
         &self,
@@ -204,7 +339,15 @@ impl wasmtime_environ::Compiler for Compiler {
             global_type: isa.pointer_type(),
             flags: MemFlags::trusted(),
         });
-        if func_env.signals_based_traps() {
+        // Pulley's prologue check is an explicit trapping instruction rather
+        // than a guard-page fault, so it doesn't need signals. It's also the
+        // only check that accounts for the frame being allocated, which
+        // matters because nothing guards the end of the interpreter's stack.
+        let is_pulley = matches!(
+            isa.triple().architecture,
+            target_lexicon::Architecture::Pulley32 | target_lexicon::Architecture::Pulley64
+        );
+        if func_env.signals_based_traps() || is_pulley {
             context.func.stack_limit = Some(stack_limit);
         } else {
             func_env.stack_limit_at_function_entry = Some(stack_limit);
@@ -219,6 +362,15 @@ impl wasmtime_environ::Compiler for Compiler {
             &mut func_env,
         )?;
 
+        // Inlined code loses its own debug information, so don't inline when
+        // it was requested.
+        if self.tunables.inlining && !self.tunables.generate_native_debuginfo {
+            let inliner = WasmInliner::new(self, translation, types, &validator);
+            context
+                .inline(isa, inliner)
+                .map_err(|e| CompileError::Codegen(pretty_error(&context.func, e)))?;
+        }
+
         if let Some(path) = &self.clif_dir {
             use std::io::Write;
 
@@ -359,9 +511,10 @@ impl wasmtime_environ::Compiler for Compiler {
         );
 
         // Do an indirect call to the callee.
-        let callee_signature = builder.func.import_signature(array_call_sig);
-        builder.ins().call_indirect(
-            callee_signature,
+        self.call_indirect_host(
+            &mut builder,
+            HostCall::ArrayCall,
+            array_call_sig,
             callee,
             &[callee_vmctx, caller_vmctx, args_base, args_len],
         );
@@ -554,8 +707,13 @@ impl wasmtime_environ::Compiler for Compiler {
         // Forward all our own arguments to the libcall itself, and then return
         // all the same results as the libcall.
         let block_params = builder.block_params(block0).to_vec();
-        let sig = builder.func.import_signature(sig);
-        let call = builder.ins().call_indirect(sig, func_addr, &block_params);
+        let call = self.call_indirect_host(
+            &mut builder,
+            HostCall::Builtin(index),
+            sig,
+            func_addr,
+            &block_params,
+        );
         let results = builder.func.dfg.inst_results(call).to_vec();
         builder.ins().return_(&results);
         builder.finalize();
@@ -753,6 +911,48 @@ impl Compiler {
         results
     }
 
+    /// Invokes the native host function `addr` with signature `sig`.
+    ///
+    /// Native targets use a plain `call_indirect`. Pulley bytecode can't jump
+    /// to native code so instead a call to a `NS_PULLEY_HOSTCALL` function is
+    /// emitted, with `addr` as an extra leading argument, which the
+    /// interpreter hands back to the embedder to perform.
+    fn call_indirect_host(
+        &self,
+        builder: &mut FunctionBuilder<'_>,
+        hostcall: HostCall,
+        mut sig: ir::Signature,
+        addr: ir::Value,
+        args: &[ir::Value],
+    ) -> ir::Inst {
+        let is_pulley = matches!(
+            self.isa.triple().architecture,
+            target_lexicon::Architecture::Pulley32 | target_lexicon::Architecture::Pulley64
+        );
+        if !is_pulley {
+            let sig = builder.func.import_signature(sig);
+            return builder.ins().call_indirect(sig, addr, args);
+        }
+
+        sig.params
+            .insert(0, ir::AbiParam::new(self.isa.pointer_type()));
+        let name = ir::ExternalName::User(builder.func.declare_imported_user_function(
+            ir::UserExternalName {
+                namespace: crate::NS_PULLEY_HOSTCALL,
+                index: hostcall.index(),
+            },
+        ));
+        let signature = builder.func.import_signature(sig);
+        let callee = builder.func.dfg.ext_funcs.push(ir::ExtFuncData {
+            name,
+            signature,
+            colocated: false,
+        });
+        let mut raw_args = vec![addr];
+        raw_args.extend_from_slice(args);
+        builder.ins().call(callee, &raw_args)
+    }
+
     fn function_compiler(&self) -> FunctionCompiler<'_> {
         let saved_context = self.contexts.lock().unwrap().pop();
         FunctionCompiler {
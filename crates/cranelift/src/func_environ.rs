@@ -19,12 +19,13 @@ use smallvec::SmallVec;
 use std::mem;
 use wasmparser::{Operator, WasmFeatures};
 use wasmtime_environ::{
-    BuiltinFunctionIndex, DataIndex, ElemIndex, EngineOrModuleTypeIndex, FuncIndex, GlobalIndex,
-    IndexType, Memory, MemoryIndex, Module, ModuleInternedTypeIndex, ModuleTranslation,
-    ModuleTypesBuilder, PtrSize, Table, TableIndex, Tunables, TypeConvert, TypeIndex, VMOffsets,
-    WasmCompositeInnerType, WasmFuncType, WasmHeapTopType, WasmHeapType, WasmRefType, WasmResult,
-    WasmValType,
+    BuiltinFunctionIndex, DataIndex, DebugSlotKind, ElemIndex, EngineOrModuleTypeIndex, FuncIndex,
+    GlobalIndex, IndexType, Memory, MemoryIndex, Module, ModuleInternedTypeIndex,
+    ModuleTranslation, ModuleTypesBuilder, PtrSize, Table, TableIndex, Tunables, TypeConvert,
+    TypeIndex, VMOffsets, WasmCompositeInnerType, WasmFuncType, WasmHeapTopType, WasmHeapType,
+    WasmRefType, WasmResult, WasmValType,
 };
+use wasmtime_environ::{DEBUG_SLOT_ALIGN, DEBUG_SLOT_SIZE, DEBUG_SLOT_VALUE_OFFSET};
 use wasmtime_environ::{FUNCREF_INIT_BIT, FUNCREF_MASK};
 
 /// A struct with an `Option<ir::FuncRef>` member for every builtin
@@ -72,12 +73,20 @@ macro_rules! declare_function_signatures {
         $( #[$attr:meta] )*
         $name:ident( $( $pname:ident: $param:ident ),* ) $( -> $result:ident )?;
     )*) => {
-        $(impl BuiltinFunctions {
+        $(declare_function_signatures!(@method $( #[$attr] )* $name);)*
+    };
+
+    // Only Winch's baseline tier counts calls in order to tier up, so there's
+    // no helper for this builtin.
+    (@method $( #[$attr:meta] )* tier_up) => {};
+
+    (@method $( #[$attr:meta] )* $name:ident) => {
+        impl BuiltinFunctions {
             $( #[$attr] )*
             pub(crate) fn $name(&mut self, func: &mut Function) -> ir::FuncRef {
                 self.load_builtin(func, BuiltinFunctionIndex::$name())
             }
-        })*
+        }
     };
 }
 wasmtime_environ::foreach_builtin_function!(declare_function_signatures);
@@ -158,6 +167,11 @@ pub struct FuncEnvironment<'module_environment> {
     /// always present even if this is a "leaf" function, as we have to call
     /// into the host to trap when signal handlers are disabled.
     pub(crate) stack_limit_at_function_entry: Option<ir::GlobalValue>,
+
+    /// The stack slot that locals and operand stack values are spilled into
+    /// before calling the `debug_hook` libcall, when guest debugging is
+    /// enabled. Created lazily and grown to fit the deepest operand stack.
+    debug_slots: Option<ir::StackSlot>,
 }
 
 impl<'module_environment> FuncEnvironment<'module_environment> {
@@ -207,6 +221,7 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             translation,
 
             stack_limit_at_function_entry: None,
+            debug_slots: None,
         }
     }
 
@@ -695,6 +710,46 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         builder.switch_to_block(continuation_block);
     }
 
+    /// Returns the stack slot that guest-debugging hooks spill values into,
+    /// ensuring that it is at least `size` bytes large.
+    fn debug_slots(&mut self, builder: &mut FunctionBuilder<'_>, size: u32) -> ir::StackSlot {
+        match self.debug_slots {
+            Some(slot) => {
+                let data = &mut builder.func.sized_stack_slots[slot];
+                data.size = data.size.max(size);
+                slot
+            }
+            None => {
+                let slot = builder.func.create_sized_stack_slot(ir::StackSlotData::new(
+                    ir::StackSlotKind::ExplicitSlot,
+                    size,
+                    u8::try_from(DEBUG_SLOT_ALIGN.ilog2()).unwrap(),
+                ));
+                self.debug_slots = Some(slot);
+                slot
+            }
+        }
+    }
+
+    /// Store `val`, tagged with `kind`, into the `index`th debug slot.
+    fn debug_slot_store(
+        &self,
+        builder: &mut FunctionBuilder<'_>,
+        slots: ir::StackSlot,
+        index: usize,
+        kind: DebugSlotKind,
+        val: ir::Value,
+    ) {
+        let offset = i32::try_from(index).unwrap() * i32::try_from(DEBUG_SLOT_SIZE).unwrap();
+        let tag = builder.ins().iconst(I32, i64::from(kind as u32));
+        builder.ins().stack_store(tag, slots, offset);
+        builder.ins().stack_store(
+            val,
+            slots,
+            offset + i32::try_from(DEBUG_SLOT_VALUE_OFFSET).unwrap(),
+        );
+    }
+
     /// Get the Memory for the given index.
     fn memory(&self, index: MemoryIndex) -> Memory {
         self.module.memories[index]
@@ -3047,6 +3102,87 @@ impl<'module_environment> crate::translate::FuncEnvironment
         Ok(())
     }
 
+    fn guest_debug(&self) -> bool {
+        self.tunables.guest_debug
+    }
+
+    fn translate_debug_hook(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        state: &FuncTranslationState,
+        offset: u32,
+        locals: &[WasmValType],
+        stack: &[Option<WasmValType>],
+    ) -> WasmResult<()> {
+        // The hook only needs to be called while the store has breakpoints
+        // set or is single-stepping, so check that first and keep spilling
+        // values and calling into the host out of line.
+        let hook_block = builder.create_block();
+        let continuation_block = builder.create_block();
+        builder.set_cold_block(hook_block);
+        let active = builder.ins().load(
+            I32,
+            ir::MemFlags::trusted(),
+            self.vmruntime_limits_ptr,
+            i32::from(self.offsets.ptr.vmruntime_limits_debug_hook_active()),
+        );
+        builder
+            .ins()
+            .brif(active, hook_block, &[], continuation_block, &[]);
+        builder.seal_block(hook_block);
+        builder.switch_to_block(hook_block);
+
+        // Spill the locals followed by the operand stack, from bottom to top,
+        // so that the host can inspect them.
+        let num_slots = locals.len() + state.stack.len();
+        let size = u32::try_from(num_slots).unwrap() * DEBUG_SLOT_SIZE;
+        let slots = self.debug_slots(builder, size);
+        for (i, ty) in locals.iter().enumerate() {
+            let val = builder.use_var(Variable::new(i));
+            self.debug_slot_store(builder, slots, i, DebugSlotKind::from_wasm_type(ty), val);
+        }
+        for (i, (val, ty)) in state.stack.iter().zip(stack).enumerate() {
+            let kind = match ty {
+                Some(ty) => DebugSlotKind::from_wasm_type(ty),
+                // The validator doesn't know the type of this value, so
+                // describe it by its machine representation instead.
+                None => match builder.func.dfg.value_type(*val) {
+                    I32 => DebugSlotKind::I32,
+                    F32 => DebugSlotKind::F32,
+                    F64 => DebugSlotKind::F64,
+                    ty if ty.is_vector() => DebugSlotKind::V128,
+                    _ => DebugSlotKind::I64,
+                },
+            };
+            self.debug_slot_store(builder, slots, locals.len() + i, kind, *val);
+        }
+
+        let func_index = match &builder.func.name {
+            ir::UserFuncName::User(user) => user.index,
+            _ => panic!("function name not a UserFuncName::User as expected"),
+        };
+        let debug_hook = self.builtin_functions.debug_hook(builder.func);
+        let vmctx = self.vmctx_val(&mut builder.cursor());
+        let func_index = builder.ins().iconst(I32, i64::from(func_index));
+        let offset = builder.ins().iconst(I32, i64::from(offset));
+        let slots = builder.ins().stack_addr(self.pointer_type(), slots, 0);
+        let num_locals = builder
+            .ins()
+            .iconst(I32, i64::try_from(locals.len()).unwrap());
+        let num_stack = builder
+            .ins()
+            .iconst(I32, i64::try_from(state.stack.len()).unwrap());
+        builder.ins().call(
+            debug_hook,
+            &[vmctx, func_index, offset, slots, num_locals, num_stack],
+        );
+        builder.ins().jump(continuation_block, &[]);
+        builder.seal_block(continuation_block);
+
+        builder.switch_to_block(continuation_block);
+        Ok(())
+    }
+
     fn before_unconditionally_trapping_memory_access(
         &mut self,
         builder: &mut FunctionBuilder,
@@ -3074,7 +3210,10 @@ impl<'module_environment> crate::translate::FuncEnvironment
 
         // If the `vmruntime_limits_ptr` variable will get used then we initialize
         // it here.
-        if self.tunables.consume_fuel || self.tunables.epoch_interruption {
+        if self.tunables.consume_fuel
+            || self.tunables.epoch_interruption
+            || self.tunables.guest_debug
+        {
             self.declare_vmruntime_limits_ptr(builder);
         }
         // Additionally we initialize `fuel_var` if it will get used.
@@ -0,0 +1,169 @@
+//! Compiler for the semi-space copying collector.
+//!
+//! The copying collector moves objects during collection, so every GC
+//! reference that is live across a safepoint must be included in stack maps,
+//! so that the collector can update it with the object's new location. The
+//! collector does not otherwise require any read or write barriers.
+//!
+//! Allocation is done out of line, via the `gc_alloc_raw` libcall, which will
+//! trigger a collection if the current semi-space is exhausted.
+
+use super::*;
+use crate::gc::gc_compiler;
+use crate::{func_environ::FuncEnvironment, gc::GcCompiler};
+use cranelift_codegen::ir::{self, InstBuilder};
+use cranelift_frontend::FunctionBuilder;
+use wasmtime_environ::{
+    copying::{CopyingTypeLayouts, OBJECT_ALIGN},
+    GcTypeLayouts, TypeIndex, VMGcKind, WasmRefType, WasmResult,
+};
+
+#[derive(Default)]
+pub struct CopyingCompiler {
+    layouts: CopyingTypeLayouts,
+}
+
+impl GcCompiler for CopyingCompiler {
+    fn layouts(&self) -> &dyn GcTypeLayouts {
+        &self.layouts
+    }
+
+    fn alloc_array(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        array_type_index: TypeIndex,
+        init: super::ArrayInit<'_>,
+    ) -> WasmResult<ir::Value> {
+        let interned_type_index = func_env.module.types[array_type_index];
+
+        let len_offset = gc_compiler(func_env)?.layouts().array_length_field_offset();
+        let array_layout = func_env.array_layout(interned_type_index).clone();
+        let base_size = array_layout.base_size;
+        let align = core::cmp::max(array_layout.align, OBJECT_ALIGN);
+        let len_to_elems_delta = base_size.checked_sub(len_offset).unwrap();
+
+        // First, compute the array's total size from its base size, element
+        // size, and length.
+        let size = emit_array_size(func_env, builder, &array_layout, init);
+
+        // Second, call the `gc_alloc_raw` builtin libcall to allocate the
+        // array. This may trigger a collection that moves objects, so we must
+        // not derive any raw pointers into the GC heap until after the call.
+        let array_ref = emit_gc_raw_alloc(
+            func_env,
+            builder,
+            VMGcKind::ArrayRef,
+            interned_type_index,
+            size,
+            align,
+        );
+
+        // Write the array's length into the appropriate slot.
+        //
+        // Note: we don't need to bounds-check the GC ref access here, since we
+        // trust the results of the allocation libcall.
+        let base = func_env.get_gc_heap_base(builder);
+        let extended_array_ref =
+            uextend_i32_to_pointer_type(builder, func_env.pointer_type(), array_ref);
+        let object_addr = builder.ins().iadd(base, extended_array_ref);
+        let len_addr = builder.ins().iadd_imm(object_addr, i64::from(len_offset));
+        let len = init.len(&mut builder.cursor());
+        builder
+            .ins()
+            .store(ir::MemFlags::trusted(), len, len_addr, 0);
+
+        // Finally, initialize the elements.
+        let len_to_elems_delta = builder
+            .ins()
+            .iconst(ir::types::I64, i64::from(len_to_elems_delta));
+        let elems_addr = builder.ins().iadd(len_addr, len_to_elems_delta);
+        init.initialize(
+            func_env,
+            builder,
+            interned_type_index,
+            base_size,
+            size,
+            elems_addr,
+            |func_env, builder, elem_ty, elem_addr, val| {
+                write_field_at_addr(func_env, builder, elem_ty, elem_addr, val)
+            },
+        )?;
+        Ok(array_ref)
+    }
+
+    fn alloc_struct(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        struct_type_index: TypeIndex,
+        field_vals: &[ir::Value],
+    ) -> WasmResult<ir::Value> {
+        let interned_type_index = func_env.module.types[struct_type_index];
+        let struct_layout = func_env.struct_layout(interned_type_index);
+
+        // Copy some stuff out of the struct layout to avoid borrowing issues.
+        let struct_size = struct_layout.size;
+        let struct_align = core::cmp::max(struct_layout.align, OBJECT_ALIGN);
+
+        assert_eq!(VMGcKind::MASK & struct_size, 0);
+        assert_eq!(VMGcKind::UNUSED_MASK & struct_size, struct_size);
+        let struct_size_val = builder.ins().iconst(ir::types::I32, i64::from(struct_size));
+
+        let struct_ref = emit_gc_raw_alloc(
+            func_env,
+            builder,
+            VMGcKind::StructRef,
+            interned_type_index,
+            struct_size_val,
+            struct_align,
+        );
+
+        // Initialize the struct's fields.
+        //
+        // Note: we don't need to bounds-check the GC ref access here, since we
+        // trust the results of the allocation libcall.
+        let base = func_env.get_gc_heap_base(builder);
+        let extended_struct_ref =
+            uextend_i32_to_pointer_type(builder, func_env.pointer_type(), struct_ref);
+        let raw_ptr_to_struct = builder.ins().iadd(base, extended_struct_ref);
+        initialize_struct_fields(
+            func_env,
+            builder,
+            interned_type_index,
+            raw_ptr_to_struct,
+            field_vals,
+            |func_env, builder, ty, field_addr, val| {
+                write_field_at_addr(func_env, builder, ty, field_addr, val)
+            },
+        )?;
+
+        Ok(struct_ref)
+    }
+
+    fn translate_read_gc_reference(
+        &mut self,
+        _func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder,
+        ty: WasmRefType,
+        src: ir::Value,
+        flags: ir::MemFlags,
+    ) -> WasmResult<ir::Value> {
+        // Use `unbarriered_load_gc_ref` so that the loaded reference is
+        // included in stack maps and updated if the collector moves its
+        // referent.
+        unbarriered_load_gc_ref(builder, ty.heap_type, src, flags)
+    }
+
+    fn translate_write_gc_reference(
+        &mut self,
+        _func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder,
+        ty: WasmRefType,
+        dst: ir::Value,
+        new_val: ir::Value,
+        flags: ir::MemFlags,
+    ) -> WasmResult<()> {
+        unbarriered_store_gc_ref(builder, ty.heap_type, dst, new_val, flags)
+    }
+}
@@ -207,6 +207,12 @@ pub const NS_WASM_FUNC: u32 = 0;
 /// function through an indirect function call loaded by the `VMContext`.
 pub const NS_WASMTIME_BUILTIN: u32 = 1;
 
+/// Namespace for calls from Pulley bytecode out to the host. The index is the
+/// [`HostCall::index`](wasmtime_environ::HostCall::index) of the function
+/// being called, and the callee's native address is passed as the first
+/// argument.
+pub const NS_PULLEY_HOSTCALL: u32 = 2;
+
 /// A record of a relocation to perform.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Relocation {
@@ -285,6 +291,7 @@ fn mach_reloc_to_reloc(
                 NS_WASMTIME_BUILTIN => {
                     RelocationTarget::Builtin(BuiltinFunctionIndex::from_u32(name.index))
                 }
+                NS_PULLEY_HOSTCALL => RelocationTarget::PulleyHostcall(name.index),
                 _ => panic!("unknown namespace {}", name.namespace),
             }
         }
@@ -204,6 +204,16 @@ impl<'a> ModuleTextBuilder<'a> {
                         )
                         .unwrap();
                 }
+
+                // Calls from Pulley bytecode to the host are encoded as an
+                // instruction whose immediate is the index of the host
+                // function, which is filled in here. The opcode itself
+                // occupies the first three bytes of the instruction.
+                RelocationTarget::PulleyHostcall(n) => {
+                    assert_eq!(r.reloc, Reloc::PulleyCallIndirectHost);
+                    let byte = u8::try_from(n).unwrap();
+                    self.text.write(off + u64::from(r.offset) + 3, &[byte]);
+                }
             };
         }
         (symbol_id, off..off + body_len)
@@ -18,7 +18,7 @@ use smallvec::SmallVec;
 use wasmparser::{Operator, WasmFeatures};
 use wasmtime_environ::{
     DataIndex, ElemIndex, FuncIndex, GlobalIndex, MemoryIndex, TableIndex, Tunables, TypeConvert,
-    TypeIndex, WasmHeapType, WasmRefType, WasmResult,
+    TypeIndex, WasmHeapType, WasmRefType, WasmResult, WasmValType,
 };
 
 /// The value of a WebAssembly global variable.
@@ -734,6 +734,29 @@ pub trait FuncEnvironment: TargetEnvironment {
         Ok(())
     }
 
+    /// Whether to call [`FuncEnvironment::translate_debug_hook`] before each
+    /// reachable operator to instrument the function for guest debugging.
+    fn guest_debug(&self) -> bool {
+        false
+    }
+
+    /// Emit guest-debugging instrumentation before the operator at the given
+    /// Wasm `offset`.
+    ///
+    /// The function's locals are the variables `0..locals.len()` and have the
+    /// given types. The operand stack is `state.stack`, and `stack` contains
+    /// the type of each of its values, when the validator knows it.
+    fn translate_debug_hook(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _state: &FuncTranslationState,
+        _offset: u32,
+        _locals: &[WasmValType],
+        _stack: &[Option<WasmValType>],
+    ) -> WasmResult<()> {
+        Ok(())
+    }
+
     /// Optional callback for the `FunctionEnvironment` performing this translation to maintain
     /// internal state or finalize custom state for the operator that was translated
     fn after_translate_operator(
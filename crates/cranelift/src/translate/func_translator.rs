@@ -13,7 +13,7 @@ use cranelift_codegen::ir::{self, Block, InstBuilder, ValueLabel};
 use cranelift_codegen::timing;
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
 use wasmparser::{BinaryReader, FuncValidator, FunctionBody, WasmModuleResources};
-use wasmtime_environ::WasmResult;
+use wasmtime_environ::{WasmResult, WasmValType};
 
 /// WebAssembly to Cranelift IR function translator.
 ///
@@ -241,11 +241,24 @@ fn parse_function_body<FE: FuncEnvironment + ?Sized>(
     // The control stack is initialized with a single block representing the whole function.
     debug_assert_eq!(state.control_stack.len(), 1, "State not initialized");
 
+    // When instrumenting for guest debugging, the types of locals are needed
+    // to describe them to the debugger.
+    let locals = if environ.guest_debug() {
+        (0..validator.len_locals())
+            .map(|i| environ.convert_valtype(validator.get_local_type(i).unwrap()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
     environ.before_translate_function(builder, state)?;
     while !reader.eof() {
         let pos = reader.original_position();
         builder.set_srcloc(cur_srcloc(&reader));
         let op = reader.read_operator()?;
+        if environ.guest_debug() && state.reachable() {
+            translate_debug_hook(validator, builder, state, environ, pos, &locals)?;
+        }
         validator.op(pos, &op)?;
         environ.before_translate_operator(&op, builder, state)?;
         translate_operator(validator, &op, builder, state, environ)?;
@@ -275,6 +288,31 @@ fn parse_function_body<FE: FuncEnvironment + ?Sized>(
     Ok(())
 }
 
+/// Emit guest-debugging instrumentation before the operator at `pos`.
+///
+/// This must be called before the operator is fed to the validator, so that
+/// the validator's operand stack still describes `state.stack`.
+fn translate_debug_hook<FE: FuncEnvironment + ?Sized>(
+    validator: &FuncValidator<impl WasmModuleResources>,
+    builder: &mut FunctionBuilder,
+    state: &FuncTranslationState,
+    environ: &mut FE,
+    pos: usize,
+    locals: &[WasmValType],
+) -> WasmResult<()> {
+    let len = state.stack.len();
+    let stack = (0..len)
+        .map(|i| {
+            validator
+                .get_operand_type(len - 1 - i)
+                .flatten()
+                .map(|ty| environ.convert_valtype(ty))
+        })
+        .collect::<Vec<_>>();
+    let offset = u32::try_from(pos).unwrap();
+    environ.translate_debug_hook(builder, state, offset, locals, &stack)
+}
+
 /// Get the current source location from a reader.
 fn cur_srcloc(reader: &BinaryReader) -> ir::SourceLoc {
     // We record source locations as byte code offsets relative to the beginning of the file.
@@ -41,6 +41,11 @@ macro_rules! foreach_builtin_function {
             out_of_gas(vmctx: vmctx);
             // Invoked when we reach a new epoch.
             new_epoch(vmctx: vmctx) -> i64;
+            // Invoked when a function's tier-up counter reaches zero.
+            tier_up(vmctx: vmctx, func: i32);
+            // Invoked before each instruction when guest debugging is active,
+            // with the function's locals and operand stack spilled to `slots`.
+            debug_hook(vmctx: vmctx, func: i32, offset: i32, slots: pointer, num_locals: i32, num_stack: i32);
             // Invoked before malloc returns.
             #[cfg(feature = "wmemcheck")]
             check_malloc(vmctx: vmctx, addr: i32, len: i32) -> i32;
@@ -81,7 +86,7 @@ macro_rules! foreach_builtin_function {
 
             // Allocate a new, uninitialized GC object and return a reference to
             // it.
-            #[cfg(feature = "gc-drc")]
+            #[cfg(any(feature = "gc-drc", feature = "gc-copying"))]
             gc_alloc_raw(
                 vmctx: vmctx,
                 kind: i32,
@@ -228,6 +233,42 @@ impl BuiltinFunctionIndex {
     }
 }
 
+/// A native function which Pulley bytecode calls out to on the host.
+///
+/// Pulley can't call native function pointers directly, so calls from
+/// trampolines to the host are compiled to an instruction carrying this
+/// function's index which the interpreter hands back to the embedder.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HostCall {
+    /// A call to a `VMArrayCallFunction`, used by wasm-to-array trampolines
+    /// to invoke host functions.
+    ArrayCall,
+    /// A call to one of Wasmtime's builtin libcalls.
+    Builtin(BuiltinFunctionIndex),
+}
+
+impl HostCall {
+    /// Returns the index of this host call as encoded in Pulley bytecode.
+    pub const fn index(&self) -> u32 {
+        match self {
+            HostCall::ArrayCall => 0,
+            HostCall::Builtin(i) => 1 + i.index(),
+        }
+    }
+
+    /// Inverse of [`HostCall::index`], returning `None` for out-of-bounds
+    /// indices.
+    pub fn from_index(index: u32) -> Option<HostCall> {
+        match index {
+            0 => Some(HostCall::ArrayCall),
+            n if n <= BuiltinFunctionIndex::builtin_functions_total_number() => {
+                Some(HostCall::Builtin(BuiltinFunctionIndex::from_u32(n - 1)))
+            }
+            _ => None,
+        }
+    }
+}
+
 macro_rules! declare_indexes {
     (
         $(
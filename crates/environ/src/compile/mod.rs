@@ -80,6 +80,9 @@ pub enum RelocationTarget {
     Builtin(BuiltinFunctionIndex),
     /// A compiler-generated libcall.
     HostLibcall(obj::LibCall),
+    /// A call from Pulley bytecode to the host, identified by
+    /// [`HostCall::index`](crate::HostCall::index).
+    PulleyHostcall(u32),
 }
 
 /// Implementation of an incremental compilation's key/value cache store.
@@ -229,7 +229,6 @@ impl<'a> ObjectBuilder<'a> {
             self.triple.architecture,
             target_lexicon::Architecture::Pulley32 | target_lexicon::Architecture::Pulley64
         );
-        assert!(!is_pulley || wasm_to_array_trampolines.is_empty());
 
         Ok(CompiledModuleInfo {
             module,
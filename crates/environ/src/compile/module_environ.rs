@@ -54,6 +54,11 @@ pub struct ModuleTranslation<'data> {
     /// References to the function bodies.
     pub function_body_inputs: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'data>>,
 
+    /// The bodies of the defined functions, which remain available for
+    /// inlining callees into their callers once `function_body_inputs` has
+    /// been taken for compilation. Only populated when inlining is enabled.
+    pub function_bodies: PrimaryMap<DefinedFuncIndex, FunctionBody<'data>>,
+
     /// A list of type signatures which are considered exported from this
     /// module, or those that can possibly be called. This list is sorted, and
     /// trampolines for each of these signatures are required.
@@ -216,6 +221,11 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
             Payload::End(offset) => {
                 self.result.types = Some(self.validator.end(offset)?);
 
+                if self.tunables.tiered_compilation {
+                    self.result.module.num_tier_up_counters =
+                        self.result.module.num_defined_funcs();
+                }
+
                 // With the `escaped_funcs` set of functions finished
                 // we can calculate the set of signatures that are exported as
                 // the set of exported functions' signatures.
@@ -550,6 +560,9 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
                             params: sig.params().into(),
                         });
                 }
+                if self.tunables.inlining {
+                    self.result.function_bodies.push(body.clone());
+                }
                 self.result
                     .function_body_inputs
                     .push(FunctionBodyData { validator, body });
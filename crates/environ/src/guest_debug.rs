@@ -0,0 +1,80 @@
+//! Layout of the frame state that instrumented code hands to the guest
+//! debugger.
+//!
+//! When guest debugging is enabled, compiled code spills the function's Wasm
+//! locals, followed by its operand stack from bottom to top, into an array of
+//! [`DEBUG_SLOT_SIZE`]-byte slots before calling the `debug_hook` libcall.
+//! Each slot begins with a 32-bit [`DebugSlotKind`] tag describing how to
+//! interpret the value, which is stored at [`DEBUG_SLOT_VALUE_OFFSET`].
+
+use crate::{WasmHeapTopType, WasmValType};
+
+/// The size, in bytes, of a single debug slot.
+pub const DEBUG_SLOT_SIZE: u32 = 32;
+
+/// The alignment, in bytes, of the array of debug slots.
+pub const DEBUG_SLOT_ALIGN: u32 = 16;
+
+/// The offset, in bytes, of the value within a debug slot.
+///
+/// Values are stored in their native in-register representation: integers
+/// and floats in the low bytes, `v128`s using all 16 bytes, function
+/// references as a raw `*mut VMFuncRef` pointer, and GC references as their
+/// raw 32-bit `VMGcRef` bits.
+pub const DEBUG_SLOT_VALUE_OFFSET: u32 = 16;
+
+/// The kind of value stored in a debug slot.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugSlotKind {
+    /// A 32-bit integer.
+    I32 = 0,
+    /// A 64-bit integer.
+    I64 = 1,
+    /// A 32-bit float.
+    F32 = 2,
+    /// A 64-bit float.
+    F64 = 3,
+    /// A 128-bit vector.
+    V128 = 4,
+    /// A reference in the `func` type hierarchy.
+    FuncRef = 5,
+    /// A reference in the `extern` type hierarchy.
+    ExternRef = 6,
+    /// A reference in the `any` type hierarchy.
+    AnyRef = 7,
+}
+
+impl DebugSlotKind {
+    /// Get the slot kind for a value of the given Wasm type.
+    pub fn from_wasm_type(ty: &WasmValType) -> DebugSlotKind {
+        match ty {
+            WasmValType::I32 => DebugSlotKind::I32,
+            WasmValType::I64 => DebugSlotKind::I64,
+            WasmValType::F32 => DebugSlotKind::F32,
+            WasmValType::F64 => DebugSlotKind::F64,
+            WasmValType::V128 => DebugSlotKind::V128,
+            WasmValType::Ref(r) => match r.heap_type.top() {
+                WasmHeapTopType::Func => DebugSlotKind::FuncRef,
+                WasmHeapTopType::Extern => DebugSlotKind::ExternRef,
+                WasmHeapTopType::Any => DebugSlotKind::AnyRef,
+            },
+        }
+    }
+
+    /// Decode a slot kind from its raw tag, returning `None` if the tag is
+    /// not valid.
+    pub fn from_u32(tag: u32) -> Option<DebugSlotKind> {
+        Some(match tag {
+            0 => DebugSlotKind::I32,
+            1 => DebugSlotKind::I64,
+            2 => DebugSlotKind::F32,
+            3 => DebugSlotKind::F64,
+            4 => DebugSlotKind::V128,
+            5 => DebugSlotKind::FuncRef,
+            6 => DebugSlotKind::ExternRef,
+            7 => DebugSlotKind::AnyRef,
+            _ => return None,
+        })
+    }
+}
@@ -19,6 +19,7 @@ mod builtin;
 mod demangling;
 mod error;
 mod gc;
+mod guest_debug;
 mod module;
 mod module_artifacts;
 mod module_types;
@@ -36,6 +37,7 @@ pub use crate::builtin::*;
 pub use crate::demangling::*;
 pub use crate::error::*;
 pub use crate::gc::*;
+pub use crate::guest_debug::*;
 pub use crate::module::*;
 pub use crate::module_artifacts::*;
 pub use crate::module_types::*;
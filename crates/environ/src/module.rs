@@ -343,6 +343,10 @@ pub struct Module {
     /// Number of call-indirect caches.
     pub num_call_indirect_caches: usize,
 
+    /// Number of tier-up counters, one per defined function when the module
+    /// is compiled for tiered compilation and zero otherwise.
+    pub num_tier_up_counters: usize,
+
     /// Types of functions, imported and local.
     pub functions: PrimaryMap<FuncIndex, FunctionType>,
 
@@ -541,6 +545,12 @@ impl Module {
         (0..self.functions.len() - self.num_imported_funcs).map(|i| DefinedFuncIndex::new(i))
     }
 
+    /// Returns the number of functions defined by this module itself: all
+    /// functions minus imported functions.
+    pub fn num_defined_funcs(&self) -> usize {
+        self.functions.len() - self.num_imported_funcs
+    }
+
     /// Returns the number of tables defined by this module itself: all tables
     /// minus imported tables.
     pub fn num_defined_tables(&self) -> usize {
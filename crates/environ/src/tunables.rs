@@ -85,6 +85,10 @@ define_tunables! {
         /// Whether or not we use epoch-based interruption.
         pub epoch_interruption: bool,
 
+        /// Whether or not generated code is instrumented with hooks for the
+        /// in-process guest debugger.
+        pub guest_debug: bool,
+
         /// Whether or not linear memories are allowed to be reallocated after
         /// initial allocation at runtime.
         pub memory_may_move: bool,
@@ -114,6 +118,14 @@ define_tunables! {
         /// Whether or not Wasm functions target the winch abi.
         pub winch_callable: bool,
 
+        /// Whether or not Wasm functions count their calls and loop iterations
+        /// so hot functions can be recompiled with an optimizing compiler.
+        pub tiered_compilation: bool,
+
+        /// Whether or not Cranelift inlines calls to small functions defined
+        /// in the same module into their callers.
+        pub inlining: bool,
+
         /// Whether or not the host will be using native signals (e.g. SIGILL,
         /// SIGSEGV, etc) to implement traps.
         pub signals_based_traps: bool,
@@ -140,14 +152,24 @@ impl Tunables {
 
     /// Returns the default set of tunables for the given target triple.
     pub fn default_for_target(target: &Triple) -> Result<Self> {
-        match target
+        let mut ret = match target
             .pointer_width()
             .map_err(|_| anyhow!("failed to retrieve target pointer width"))?
         {
-            PointerWidth::U32 => Ok(Tunables::default_u32()),
-            PointerWidth::U64 => Ok(Tunables::default_u64()),
+            PointerWidth::U32 => Tunables::default_u32(),
+            PointerWidth::U64 => Tunables::default_u64(),
             _ => bail!("unsupported target pointer width"),
+        };
+
+        // Pulley bytecode is interpreted, so a fault while executing it can't
+        // be attributed to wasm and all traps must be explicit instead.
+        if matches!(
+            target.architecture,
+            target_lexicon::Architecture::Pulley32 | target_lexicon::Architecture::Pulley64
+        ) {
+            ret.signals_based_traps = false;
         }
+        Ok(ret)
     }
 
     /// Returns the default set of tunables for running under MIRI.
@@ -167,6 +189,7 @@ impl Tunables {
             parse_wasm_debuginfo: true,
             consume_fuel: false,
             epoch_interruption: false,
+            guest_debug: false,
             memory_may_move: true,
             guard_before_linear_memory: true,
             table_lazy_init: true,
@@ -174,6 +197,8 @@ impl Tunables {
             debug_adapter_modules: false,
             relaxed_simd_deterministic: false,
             winch_callable: false,
+            tiered_compilation: false,
+            inlining: false,
             signals_based_traps: true,
         }
     }
@@ -222,6 +247,8 @@ pub enum Collector {
     DeferredReferenceCounting,
     /// The null collector.
     Null,
+    /// The semi-space copying collector.
+    Copying,
 }
 
 impl fmt::Display for Collector {
@@ -229,6 +256,7 @@ impl fmt::Display for Collector {
         match self {
             Collector::DeferredReferenceCounting => write!(f, "deferred reference-counting"),
             Collector::Null => write!(f, "null"),
+            Collector::Copying => write!(f, "copying"),
         }
     }
 }
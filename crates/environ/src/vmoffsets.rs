@@ -24,11 +24,13 @@
 //      owned_memories: [VMMemoryDefinition; module.num_owned_memories],
 //      globals: [VMGlobalDefinition; module.num_defined_globals],
 //      func_refs: [VMFuncRef; module.num_escaped_funcs],
+//      tier_up_wasm_calls: [*const VMFunctionBody; module.num_tier_up_counters],
+//      tier_up_counters: [u32; module.num_tier_up_counters],
 // }
 
 use crate::{
-    DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, FuncIndex, FuncRefIndex,
-    GlobalIndex, MemoryIndex, Module, OwnedMemoryIndex, TableIndex,
+    DefinedFuncIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, FuncIndex,
+    FuncRefIndex, GlobalIndex, MemoryIndex, Module, OwnedMemoryIndex, TableIndex,
 };
 use cranelift_entity::packed_option::ReservedValue;
 
@@ -72,6 +74,8 @@ pub struct VMOffsets<P> {
     /// The number of escaped functions in the module, the size of the func_refs
     /// array.
     pub num_escaped_funcs: u32,
+    /// The number of tier-up counters in the module.
+    pub num_tier_up_counters: u32,
 
     // precalculated offsets of various member fields
     imported_functions: u32,
@@ -83,6 +87,8 @@ pub struct VMOffsets<P> {
     owned_memories: u32,
     defined_globals: u32,
     defined_func_refs: u32,
+    tier_up_wasm_calls: u32,
+    tier_up_counters: u32,
     size: u32,
 }
 
@@ -177,6 +183,11 @@ pub trait PtrSize {
         self.vmruntime_limits_last_wasm_exit_pc() + self.size()
     }
 
+    /// Return the offset of the `debug_hook_active` field of `VMRuntimeLimits`.
+    fn vmruntime_limits_debug_hook_active(&self) -> u8 {
+        self.vmruntime_limits_last_wasm_entry_fp() + self.size()
+    }
+
     // Offsets within `VMMemoryDefinition`
 
     /// The offset of the `base` field.
@@ -332,6 +343,8 @@ pub struct VMOffsetsFields<P> {
     /// The number of escaped functions in the module, the size of the function
     /// references array.
     pub num_escaped_funcs: u32,
+    /// The number of tier-up counters in the module.
+    pub num_tier_up_counters: u32,
 }
 
 impl<P: PtrSize> VMOffsets<P> {
@@ -356,6 +369,7 @@ impl<P: PtrSize> VMOffsets<P> {
             num_owned_memories,
             num_defined_globals: cast_to_u32(module.globals.len() - module.num_imported_globals),
             num_escaped_funcs: cast_to_u32(module.num_escaped_funcs),
+            num_tier_up_counters: cast_to_u32(module.num_tier_up_counters),
         })
     }
 
@@ -385,6 +399,7 @@ impl<P: PtrSize> VMOffsets<P> {
                     num_defined_memories: _,
                     num_owned_memories: _,
                     num_escaped_funcs: _,
+                    num_tier_up_counters: _,
 
                     // used as the initial size below
                     size,
@@ -413,6 +428,8 @@ impl<P: PtrSize> VMOffsets<P> {
         }
 
         calculate_sizes! {
+            tier_up_counters: "tier-up counters",
+            tier_up_wasm_calls: "tier-up function pointers",
             defined_func_refs: "module functions",
             defined_globals: "defined globals",
             owned_memories: "owned memories",
@@ -439,6 +456,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             num_owned_memories: fields.num_owned_memories,
             num_defined_globals: fields.num_defined_globals,
             num_escaped_funcs: fields.num_escaped_funcs,
+            num_tier_up_counters: fields.num_tier_up_counters,
             imported_functions: 0,
             imported_tables: 0,
             imported_memories: 0,
@@ -448,6 +466,8 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             owned_memories: 0,
             defined_globals: 0,
             defined_func_refs: 0,
+            tier_up_wasm_calls: 0,
+            tier_up_counters: 0,
             size: 0,
         };
 
@@ -502,6 +522,8 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
                 ret.num_escaped_funcs,
                 ret.ptr.size_of_vm_func_ref(),
             ),
+            size(tier_up_wasm_calls) = cmul(ret.num_tier_up_counters, ret.ptr.size()),
+            size(tier_up_counters) = cmul(ret.num_tier_up_counters, 4),
         }
 
         ret.size = next_field_offset;
@@ -692,6 +714,18 @@ impl<P: PtrSize> VMOffsets<P> {
         self.defined_func_refs
     }
 
+    /// The offset of the `tier_up_wasm_calls` array.
+    #[inline]
+    pub fn vmctx_tier_up_wasm_calls_begin(&self) -> u32 {
+        self.tier_up_wasm_calls
+    }
+
+    /// The offset of the `tier_up_counters` array.
+    #[inline]
+    pub fn vmctx_tier_up_counters_begin(&self) -> u32 {
+        self.tier_up_counters
+    }
+
     /// Return the size of the `VMContext` allocation.
     #[inline]
     pub fn size_of_vmctx(&self) -> u32 {
@@ -770,6 +804,22 @@ impl<P: PtrSize> VMOffsets<P> {
         self.vmctx_func_refs_begin() + index.as_u32() * u32::from(self.ptr.size_of_vm_func_ref())
     }
 
+    /// Return the offset to the code which calls from baseline code to the
+    /// defined function `index` jump to, when compiled for tiered compilation.
+    #[inline]
+    pub fn vmctx_tier_up_wasm_call(&self, index: DefinedFuncIndex) -> u32 {
+        assert!(index.as_u32() < self.num_tier_up_counters);
+        self.vmctx_tier_up_wasm_calls_begin() + index.as_u32() * u32::from(self.ptr.size())
+    }
+
+    /// Return the offset to the tier-up counter of the defined function
+    /// `index`.
+    #[inline]
+    pub fn vmctx_tier_up_counter(&self, index: DefinedFuncIndex) -> u32 {
+        assert!(index.as_u32() < self.num_tier_up_counters);
+        self.vmctx_tier_up_counters_begin() + index.as_u32() * 4
+    }
+
     /// Return the offset to the `wasm_call` field in `*const VMFunctionBody` index `index`.
     #[inline]
     pub fn vmctx_vmfunction_import_wasm_call(&self, index: FuncIndex) -> u32 {
@@ -207,6 +207,7 @@ impl Config {
         WastConfig {
             collector: match self.wasmtime.collector {
                 Collector::Null => wasmtime_wast_util::Collector::Null,
+                Collector::Copying => wasmtime_wast_util::Collector::Copying,
                 Collector::DeferredReferenceCounting => {
                     wasmtime_wast_util::Collector::DeferredReferenceCounting
                 }
@@ -724,6 +725,7 @@ impl Arbitrary<'_> for CompilerStrategy {
 pub enum Collector {
     DeferredReferenceCounting,
     Null,
+    Copying,
 }
 
 impl Collector {
@@ -731,6 +733,7 @@ impl Collector {
         match self {
             Collector::DeferredReferenceCounting => wasmtime::Collector::DeferredReferenceCounting,
             Collector::Null => wasmtime::Collector::Null,
+            Collector::Copying => wasmtime::Collector::Copying,
         }
     }
 }
@@ -0,0 +1,37 @@
+use test_programs::proxy;
+use test_programs::wasi::clocks::monotonic_clock;
+use test_programs::wasi::http::types::{
+    Fields, IncomingRequest, OutgoingBody, OutgoingResponse, ResponseOutparam,
+};
+
+struct T;
+
+proxy::export!(T);
+
+impl proxy::exports::wasi::http::incoming_handler::Guest for T {
+    fn handle(request: IncomingRequest, outparam: ResponseOutparam) {
+        // Sleep for the number of milliseconds in the `sleep-ms` header, if
+        // any, before responding, and for those in the `body-sleep-ms` header
+        // after sending the response headers but before finishing the body.
+        // The latter lets clients know the request is being handled while it
+        // sleeps.
+        let headers = request.headers();
+        let header_ms = |name: &str| match headers.get(&name.to_string()).first() {
+            Some(ms) => std::str::from_utf8(ms).unwrap().parse::<u64>().unwrap(),
+            None => 0,
+        };
+        let sleep_ms = header_ms("sleep-ms");
+        let body_sleep_ms = header_ms("body-sleep-ms");
+
+        monotonic_clock::subscribe_duration(sleep_ms * 1_000_000).block();
+
+        let resp = OutgoingResponse::new(Fields::new());
+        let body = resp.body().expect("outgoing response");
+        ResponseOutparam::set(outparam, Ok(resp));
+
+        monotonic_clock::subscribe_duration(body_sleep_ms * 1_000_000).block();
+        OutgoingBody::finish(body, None).expect("outgoing-body.finish");
+    }
+}
+
+fn main() {}
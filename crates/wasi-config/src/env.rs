@@ -0,0 +1,45 @@
+use crate::{ConfigProvider, Error};
+
+/// A [`ConfigProvider`] which exposes host environment variables.
+///
+/// Only variables whose name starts with the configured prefix are visible.
+/// The prefix is stripped and the remainder lowercased to form the key, so
+/// with a prefix of `APP_` the variable `APP_DATABASE_URL` is visible to the
+/// guest as `database_url`.
+///
+/// The environment is read on every lookup, so changes made to it by the host
+/// process are visible immediately. Variables whose name or value is not valid
+/// Unicode are ignored.
+pub struct EnvConfigProvider {
+    prefix: String,
+}
+
+impl EnvConfigProvider {
+    /// Creates a provider for variables starting with `prefix`.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+
+    fn vars(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        std::env::vars_os().filter_map(|(name, value)| {
+            let name = name.into_string().ok()?;
+            let key = name.strip_prefix(&self.prefix)?;
+            if key.is_empty() {
+                return None;
+            }
+            Some((key.to_lowercase(), value.into_string().ok()?))
+        })
+    }
+}
+
+impl ConfigProvider for EnvConfigProvider {
+    fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        Ok(self.vars().find(|(k, _)| k == key).map(|(_, v)| v))
+    }
+
+    fn get_all(&self) -> Result<Vec<(String, String)>, Error> {
+        Ok(self.vars().collect())
+    }
+}
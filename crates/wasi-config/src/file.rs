@@ -0,0 +1,223 @@
+use crate::{ChangeCallback, ConfigProvider, Error, Subscribers};
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+/// A [`ConfigProvider`] which reads values from a TOML or JSON file.
+///
+/// The format is chosen based on the file extension: `.json` files are parsed
+/// as JSON and everything else as TOML. The top level of the file must be a
+/// table. Nested tables are flattened by joining keys with `.`, so
+/// `[database] url = "..."` is visible to the guest as `database.url`. Numbers
+/// and booleans are converted to their textual representation, and arrays are
+/// not supported.
+///
+/// Lookups are served from the values loaded into memory, without touching
+/// the file. The file is reloaded by [`FileConfigProvider::refresh`] if it has
+/// been modified since it was last read, which [`FileConfigProvider::watch`]
+/// does periodically in the background. If the new contents cannot be read or
+/// parsed the previously loaded values continue to be served. Subscribers are
+/// notified whenever a reload changes the values.
+pub struct FileConfigProvider {
+    path: PathBuf,
+    format: Format,
+    state: Mutex<State>,
+    subscribers: Subscribers,
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Toml,
+    Json,
+}
+
+struct State {
+    stamp: Stamp,
+    vars: HashMap<String, String>,
+}
+
+/// Used to detect modifications of the file without re-reading it.
+#[derive(PartialEq, Clone, Copy)]
+struct Stamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileConfigProvider {
+    /// Loads configuration from the file at `path`.
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let format = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Format::Json,
+            _ => Format::Toml,
+        };
+        let (stamp, vars) = load(&path, format)?;
+        Ok(Self {
+            path,
+            format,
+            state: Mutex::new(State { stamp, vars }),
+            subscribers: Subscribers::default(),
+        })
+    }
+
+    /// Reloads the file if it has been modified since it was last read.
+    ///
+    /// Returns whether the values changed as a result.
+    pub fn refresh(&self) -> Result<bool> {
+        let stamp = stamp(&self.path)?;
+        let changed = {
+            let mut state = self.state.lock().unwrap();
+            if state.stamp == stamp {
+                return Ok(false);
+            }
+            let (stamp, vars) = load(&self.path, self.format)?;
+            state.stamp = stamp;
+            if state.vars == vars {
+                false
+            } else {
+                state.vars = vars;
+                true
+            }
+        };
+        if changed {
+            self.subscribers.notify();
+        }
+        Ok(changed)
+    }
+
+    /// Spawns a background thread which calls [`FileConfigProvider::refresh`]
+    /// every `interval`.
+    ///
+    /// Errors are ignored so that a partially written file doesn't interrupt
+    /// watching it. The thread exits once all other references to this
+    /// provider are dropped.
+    pub fn watch(self: &Arc<Self>, interval: Duration) {
+        let provider = Arc::downgrade(self);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            match provider.upgrade() {
+                Some(provider) => {
+                    let _ = provider.refresh();
+                }
+                None => break,
+            }
+        });
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl ConfigProvider for FileConfigProvider {
+    fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        Ok(self.state().vars.get(key).cloned())
+    }
+
+    fn get_all(&self) -> Result<Vec<(String, String)>, Error> {
+        Ok(self
+            .state()
+            .vars
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+
+    fn subscribe(&self, callback: ChangeCallback) {
+        self.subscribers.push(callback);
+    }
+}
+
+fn stamp(path: &Path) -> Result<Stamp> {
+    let metadata = std::fs::metadata(path)
+        .with_context(|| format!("failed to read metadata of `{}`", path.display()))?;
+    Ok(Stamp {
+        modified: metadata.modified()?,
+        len: metadata.len(),
+    })
+}
+
+fn load(path: &Path, format: Format) -> Result<(Stamp, HashMap<String, String>)> {
+    // Take the stamp before reading so that a write racing with this load is
+    // picked up by the next refresh.
+    let stamp = stamp(path)?;
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read `{}`", path.display()))?;
+    let mut vars = HashMap::new();
+    match format {
+        Format::Toml => {
+            let table: toml::Table = contents
+                .parse()
+                .with_context(|| format!("failed to parse `{}` as TOML", path.display()))?;
+            flatten_toml("", table, &mut vars)?;
+        }
+        Format::Json => {
+            let value: serde_json::Value = serde_json::from_str(&contents)
+                .with_context(|| format!("failed to parse `{}` as JSON", path.display()))?;
+            match value {
+                serde_json::Value::Object(map) => flatten_json("", map, &mut vars)?,
+                _ => bail!("`{}` must contain a JSON object", path.display()),
+            }
+        }
+    }
+    Ok((stamp, vars))
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+fn flatten_toml(
+    prefix: &str,
+    table: toml::Table,
+    vars: &mut HashMap<String, String>,
+) -> Result<()> {
+    for (key, value) in table {
+        let key = join(prefix, &key);
+        let value = match value {
+            toml::Value::String(s) => s,
+            toml::Value::Integer(i) => i.to_string(),
+            toml::Value::Float(f) => f.to_string(),
+            toml::Value::Boolean(b) => b.to_string(),
+            toml::Value::Datetime(d) => d.to_string(),
+            toml::Value::Table(t) => {
+                flatten_toml(&key, t, vars)?;
+                continue;
+            }
+            toml::Value::Array(_) => bail!("arrays are not supported, found one at `{key}`"),
+        };
+        vars.insert(key, value);
+    }
+    Ok(())
+}
+
+fn flatten_json(
+    prefix: &str,
+    map: serde_json::Map<String, serde_json::Value>,
+    vars: &mut HashMap<String, String>,
+) -> Result<()> {
+    for (key, value) in map {
+        let key = join(prefix, &key);
+        let value = match value {
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Bool(b) => b.to_string(),
+            serde_json::Value::Object(m) => {
+                flatten_json(&key, m, vars)?;
+                continue;
+            }
+            serde_json::Value::Null => bail!("null values are not supported, found one at `{key}`"),
+            serde_json::Value::Array(_) => bail!("arrays are not supported, found one at `{key}`"),
+        };
+        vars.insert(key, value);
+    }
+    Ok(())
+}
@@ -59,6 +59,24 @@
 //! }
 //! ```
 //!
+//! # Configuration providers
+//!
+//! Values are looked up through the [`ConfigProvider`] trait each time the
+//! guest asks for them, so a provider may change its values while components
+//! are running. In addition to the static [`WasiConfigVariables`] this crate
+//! provides:
+//!
+//! * [`FileConfigProvider`] - values read from a TOML or JSON file, which is
+//!   reloaded when it changes on disk.
+//! * [`EnvConfigProvider`] - values taken from host environment variables
+//!   with a given prefix.
+//! * [`FnConfigProvider`] - values produced by a host callback.
+//! * [`LayeredConfigProvider`] - a stack of other providers, where later
+//!   layers take precedence over earlier ones.
+//!
+//! Hosts which need to react to configuration changes can register a
+//! callback with [`ConfigProvider::subscribe`].
+//!
 //! [wasi-config]: https://github.com/WebAssembly/wasi-config
 //! [wasi:cli]: https://docs.rs/wasmtime-wasi/latest
 //! [wasi:http]: https://docs.rs/wasmtime-wasi-http/latest
@@ -67,6 +85,14 @@
 
 use anyhow::Result;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+mod env;
+mod file;
+
+pub use self::env::EnvConfigProvider;
+pub use self::file::FileConfigProvider;
 
 mod gen_ {
     wasmtime::component::bindgen!({
@@ -77,6 +103,82 @@ mod gen_ {
 }
 use self::gen_::wasi::config::store as generated;
 
+/// Errors which may be returned by a [`ConfigProvider`].
+///
+/// These are passed through to the guest as the `wasi:config/store.error`
+/// type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// An error from the source backing the provider.
+    Upstream(String),
+    /// An I/O error which occurred while fetching configuration.
+    Io(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Upstream(e) => write!(f, "upstream error: {e}"),
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for generated::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Upstream(e) => generated::Error::Upstream(e),
+            Error::Io(e) => generated::Error::Io(e),
+        }
+    }
+}
+
+/// A callback registered with [`ConfigProvider::subscribe`].
+pub type ChangeCallback = Arc<dyn Fn() + Send + Sync>;
+
+/// A source of configuration values for the `wasi-config` API.
+///
+/// Providers are queried every time the guest calls `get` or `get-all`, so
+/// the values returned may change over the lifetime of a component.
+pub trait ConfigProvider: Send + Sync {
+    /// Returns the value associated with `key`, if any.
+    fn get(&self, key: &str) -> Result<Option<String>, Error>;
+
+    /// Returns all key-value pairs known to this provider.
+    fn get_all(&self) -> Result<Vec<(String, String)>, Error>;
+
+    /// Registers `callback` to be invoked whenever the values of this
+    /// provider change.
+    ///
+    /// The callback may be invoked from any thread. Providers whose values
+    /// never change can ignore this, which is what the default implementation
+    /// does.
+    fn subscribe(&self, callback: ChangeCallback) {
+        let _ = callback;
+    }
+}
+
+/// A list of [`ChangeCallback`]s, shared by the providers in this crate which
+/// support change notification.
+#[derive(Default)]
+pub(crate) struct Subscribers(Mutex<Vec<ChangeCallback>>);
+
+impl Subscribers {
+    pub(crate) fn push(&self, callback: ChangeCallback) {
+        self.0.lock().unwrap().push(callback);
+    }
+
+    pub(crate) fn notify(&self) {
+        // Clone the list so callbacks may subscribe without deadlocking.
+        let callbacks = self.0.lock().unwrap().clone();
+        for callback in callbacks {
+            callback();
+        }
+    }
+}
+
 /// Capture the state necessary for use in the `wasi-config` API implementation.
 #[derive(Default)]
 pub struct WasiConfigVariables(HashMap<String, String>);
@@ -104,36 +206,154 @@ impl WasiConfigVariables {
     }
 }
 
+impl ConfigProvider for WasiConfigVariables {
+    fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        Ok(self.0.get(key).cloned())
+    }
+
+    fn get_all(&self) -> Result<Vec<(String, String)>, Error> {
+        Ok(self
+            .0
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect())
+    }
+}
+
+/// A [`ConfigProvider`] which combines several other providers.
+///
+/// Layers added later take precedence over those added earlier when both
+/// define the same key. Subscribers are notified when any layer changes.
+#[derive(Default)]
+pub struct LayeredConfigProvider {
+    layers: Vec<Arc<dyn ConfigProvider>>,
+}
+
+impl LayeredConfigProvider {
+    /// Creates a new provider with no layers.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds `provider` on top of the existing layers.
+    pub fn push(&mut self, provider: Arc<dyn ConfigProvider>) -> &mut Self {
+        self.layers.push(provider);
+        self
+    }
+}
+
+impl ConfigProvider for LayeredConfigProvider {
+    fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        for layer in self.layers.iter().rev() {
+            if let Some(value) = layer.get(key)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    fn get_all(&self) -> Result<Vec<(String, String)>, Error> {
+        let mut all = HashMap::new();
+        for layer in self.layers.iter() {
+            all.extend(layer.get_all()?);
+        }
+        Ok(all.into_iter().collect())
+    }
+
+    fn subscribe(&self, callback: ChangeCallback) {
+        for layer in self.layers.iter() {
+            layer.subscribe(callback.clone());
+        }
+    }
+}
+
+type GetFn = dyn Fn(&str) -> Result<Option<String>, Error> + Send + Sync;
+type GetAllFn = dyn Fn() -> Result<Vec<(String, String)>, Error> + Send + Sync;
+
+/// A [`ConfigProvider`] backed by host callbacks.
+///
+/// The host is responsible for calling [`FnConfigProvider::notify`] when the
+/// values returned by its callbacks change.
+pub struct FnConfigProvider {
+    get: Box<GetFn>,
+    get_all: Option<Box<GetAllFn>>,
+    subscribers: Subscribers,
+}
+
+impl FnConfigProvider {
+    /// Creates a provider which looks up values with `get`.
+    ///
+    /// Unless [`FnConfigProvider::with_get_all`] is also used, listing all
+    /// values fails with an [`Error::Upstream`].
+    pub fn new(
+        get: impl Fn(&str) -> Result<Option<String>, Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            get: Box::new(get),
+            get_all: None,
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    /// Sets the callback used to list all values.
+    pub fn with_get_all(
+        mut self,
+        get_all: impl Fn() -> Result<Vec<(String, String)>, Error> + Send + Sync + 'static,
+    ) -> Self {
+        self.get_all = Some(Box::new(get_all));
+        self
+    }
+
+    /// Notifies all subscribers that the values of this provider changed.
+    pub fn notify(&self) {
+        self.subscribers.notify();
+    }
+}
+
+impl ConfigProvider for FnConfigProvider {
+    fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        (self.get)(key)
+    }
+
+    fn get_all(&self) -> Result<Vec<(String, String)>, Error> {
+        match &self.get_all {
+            Some(get_all) => get_all(),
+            None => Err(Error::Upstream(
+                "listing configuration values is not supported".to_string(),
+            )),
+        }
+    }
+
+    fn subscribe(&self, callback: ChangeCallback) {
+        self.subscribers.push(callback);
+    }
+}
+
 /// A wrapper capturing the needed internal `wasi-config` state.
 pub struct WasiConfig<'a> {
-    vars: &'a WasiConfigVariables,
+    provider: &'a dyn ConfigProvider,
 }
 
 impl<'a> From<&'a WasiConfigVariables> for WasiConfig<'a> {
     fn from(vars: &'a WasiConfigVariables) -> Self {
-        Self { vars }
+        Self { provider: vars }
     }
 }
 
 impl<'a> WasiConfig<'a> {
     /// Create a new view into the `wasi-config` state.
-    pub fn new(vars: &'a WasiConfigVariables) -> Self {
-        Self { vars }
+    pub fn new(provider: &'a dyn ConfigProvider) -> Self {
+        Self { provider }
     }
 }
 
 impl generated::Host for WasiConfig<'_> {
     fn get(&mut self, key: String) -> Result<Result<Option<String>, generated::Error>> {
-        Ok(Ok(self.vars.0.get(&key).map(|s| s.to_owned())))
+        Ok(self.provider.get(&key).map_err(Into::into))
     }
 
     fn get_all(&mut self) -> Result<Result<Vec<(String, String)>, generated::Error>> {
-        Ok(Ok(self
-            .vars
-            .0
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_string()))
-            .collect()))
+        Ok(self.provider.get_all().map_err(Into::into))
     }
 }
 
@@ -1,16 +1,22 @@
 use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use test_programs_artifacts::{foreach_config, CONFIG_GET_COMPONENT};
 use wasmtime::{
     component::{Component, Linker, ResourceTable},
     Store,
 };
 use wasmtime_wasi::{add_to_linker_async, bindings::Command, WasiCtx, WasiCtxBuilder, WasiView};
-use wasmtime_wasi_config::{WasiConfig, WasiConfigVariables};
+use wasmtime_wasi_config::{
+    ConfigProvider, EnvConfigProvider, Error, FileConfigProvider, FnConfigProvider,
+    LayeredConfigProvider, WasiConfig, WasiConfigVariables,
+};
 
 struct Ctx {
     table: ResourceTable,
     wasi_ctx: WasiCtx,
-    wasi_config_vars: WasiConfigVariables,
+    wasi_config: Arc<dyn ConfigProvider>,
 }
 
 impl WasiView for Ctx {
@@ -33,7 +39,7 @@ async fn run_wasi(path: &str, ctx: Ctx) -> Result<()> {
     let mut linker = Linker::new(&engine);
     add_to_linker_async(&mut linker)?;
     wasmtime_wasi_config::add_to_linker(&mut linker, |h: &mut Ctx| {
-        WasiConfig::from(&h.wasi_config_vars)
+        WasiConfig::new(&*h.wasi_config)
     })?;
 
     let command = Command::instantiate_async(&mut store, &component, &linker).await?;
@@ -60,8 +66,169 @@ async fn config_get() -> Result<()> {
         Ctx {
             table: ResourceTable::new(),
             wasi_ctx: WasiCtxBuilder::new().build(),
-            wasi_config_vars: WasiConfigVariables::from_iter(vec![("hello", "world")]),
+            wasi_config: Arc::new(WasiConfigVariables::from_iter(vec![("hello", "world")])),
         },
     )
     .await
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn config_get_from_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "hello = \"world\"\n")?;
+    run_wasi(
+        CONFIG_GET_COMPONENT,
+        Ctx {
+            table: ResourceTable::new(),
+            wasi_ctx: WasiCtxBuilder::new().build(),
+            wasi_config: Arc::new(FileConfigProvider::open(&path)?),
+        },
+    )
+    .await
+}
+
+fn sorted(provider: &dyn ConfigProvider) -> Vec<(String, String)> {
+    let mut all = provider.get_all().unwrap();
+    all.sort();
+    all
+}
+
+#[test]
+fn file_provider_formats() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let toml = dir.path().join("config.toml");
+    std::fs::write(
+        &toml,
+        "name = \"app\"\nport = 8080\n[database]\nurl = \"db://\"\nssl = true\n",
+    )?;
+    let provider = FileConfigProvider::open(&toml)?;
+    assert_eq!(
+        sorted(&provider),
+        [
+            ("database.ssl".to_string(), "true".to_string()),
+            ("database.url".to_string(), "db://".to_string()),
+            ("name".to_string(), "app".to_string()),
+            ("port".to_string(), "8080".to_string()),
+        ]
+    );
+
+    let json = dir.path().join("config.json");
+    std::fs::write(&json, r#"{"name": "app", "database": {"port": 5432}}"#)?;
+    let provider = FileConfigProvider::open(&json)?;
+    assert_eq!(provider.get("database.port")?, Some("5432".to_string()));
+    assert_eq!(provider.get("database")?, None);
+
+    std::fs::write(&json, r#"{"list": [1, 2]}"#)?;
+    assert!(FileConfigProvider::open(&json).is_err());
+    std::fs::write(&json, "[]")?;
+    assert!(FileConfigProvider::open(&json).is_err());
+    Ok(())
+}
+
+#[test]
+fn file_provider_reloads() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "key = \"one\"\n")?;
+    let provider = FileConfigProvider::open(&path)?;
+
+    let notified = Arc::new(AtomicUsize::new(0));
+    let n = notified.clone();
+    provider.subscribe(Arc::new(move || {
+        n.fetch_add(1, Ordering::SeqCst);
+    }));
+    assert_eq!(provider.get("key")?, Some("one".to_string()));
+
+    // Use a different length so the change is noticed even on filesystems
+    // with coarse modification times. Lookups only see it once the file is
+    // refreshed.
+    std::fs::write(&path, "key = \"three\"\n")?;
+    assert_eq!(provider.get("key")?, Some("one".to_string()));
+    assert!(provider.refresh()?);
+    assert_eq!(provider.get("key")?, Some("three".to_string()));
+    assert_eq!(notified.load(Ordering::SeqCst), 1);
+    assert!(!provider.refresh()?);
+
+    // Invalid contents keep the previous values around.
+    std::fs::write(&path, "key = ")?;
+    assert!(provider.refresh().is_err());
+    assert_eq!(provider.get("key")?, Some("three".to_string()));
+    assert_eq!(notified.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[test]
+fn file_provider_watch() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "key = \"one\"\n")?;
+    let provider = Arc::new(FileConfigProvider::open(&path)?);
+
+    let notified = Arc::new(AtomicUsize::new(0));
+    let n = notified.clone();
+    provider.subscribe(Arc::new(move || {
+        n.fetch_add(1, Ordering::SeqCst);
+    }));
+    provider.watch(Duration::from_millis(10));
+
+    // Subscribers are notified without the value being looked up.
+    std::fs::write(&path, "key = \"three\"\n")?;
+    let start = Instant::now();
+    while notified.load(Ordering::SeqCst) == 0 {
+        assert!(start.elapsed() < Duration::from_secs(10), "change not seen");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(provider.get("key")?, Some("three".to_string()));
+    Ok(())
+}
+
+#[test]
+fn env_provider() -> Result<()> {
+    std::env::set_var("WASI_CONFIG_TEST_DATABASE_URL", "db://");
+    std::env::set_var("WASI_CONFIG_TEST_NAME", "app");
+    let provider = EnvConfigProvider::new("WASI_CONFIG_TEST_");
+    assert_eq!(provider.get("database_url")?, Some("db://".to_string()));
+    assert_eq!(provider.get("DATABASE_URL")?, None);
+    assert_eq!(
+        sorted(&provider),
+        [
+            ("database_url".to_string(), "db://".to_string()),
+            ("name".to_string(), "app".to_string()),
+        ]
+    );
+
+    std::env::set_var("WASI_CONFIG_TEST_NAME", "other");
+    assert_eq!(provider.get("name")?, Some("other".to_string()));
+    Ok(())
+}
+
+#[test]
+fn layered_and_fn_providers() -> Result<()> {
+    let callback = Arc::new(FnConfigProvider::new(|key| match key {
+        "secret" => Ok(Some("hunter2".to_string())),
+        "broken" => Err(Error::Upstream("vault is sealed".to_string())),
+        _ => Ok(None),
+    }));
+    assert!(matches!(callback.get_all(), Err(Error::Upstream(_))));
+
+    let base = Arc::new(WasiConfigVariables::from_iter([("a", "1"), ("b", "2")]));
+    let top = Arc::new(WasiConfigVariables::from_iter([("b", "3")]));
+    let mut layered = LayeredConfigProvider::new();
+    layered.push(base).push(top).push(callback.clone());
+
+    assert_eq!(layered.get("a")?, Some("1".to_string()));
+    assert_eq!(layered.get("b")?, Some("3".to_string()));
+    assert_eq!(layered.get("secret")?, Some("hunter2".to_string()));
+    assert!(layered.get("broken").is_err());
+
+    let notified = Arc::new(AtomicUsize::new(0));
+    let n = notified.clone();
+    layered.subscribe(Arc::new(move || {
+        n.fetch_add(1, Ordering::SeqCst);
+    }));
+    callback.notify();
+    assert_eq!(notified.load(Ordering::SeqCst), 1);
+    Ok(())
+}
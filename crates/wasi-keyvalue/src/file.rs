@@ -0,0 +1,484 @@
+use crate::{increment_value, list_keys_page, Error, KeyValueBucket, KeyValueStore};
+use anyhow::{bail, Context};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Identifies a key-value log file, and its format version.
+const MAGIC: &[u8; 8] = b"WKVLOG02";
+
+/// The size of the header preceding each transaction: the length of its body,
+/// a checksum of that length, and a checksum of the body.
+const HEADER_SIZE: usize = 24;
+
+const OP_SET: u8 = 0;
+const OP_DELETE: u8 = 1;
+
+/// Logs which are larger than this many bytes and more than twice the size of
+/// their live data are compacted when opened.
+const COMPACT_THRESHOLD: u64 = 1 << 20;
+
+/// A [`KeyValueStore`] which persists its data to a file.
+///
+/// Every bucket identifier is valid, and buckets are created when first
+/// written to. All buckets are stored in a single append-only log: each
+/// modification, including each call to `wasi:keyvalue/batch.set-many` or
+/// `delete-many`, is appended as one checksummed transaction and synced to
+/// disk before returning to the guest. When the store is opened, the log is
+/// replayed, and a partially-written transaction at its end, as left by a
+/// crash, is discarded, while damage anywhere else in the log is reported as an
+/// error. This makes both the atomics and batch operations atomic with respect
+/// to crashes.
+///
+/// The full contents of the store are also kept in memory, so this is
+/// intended for modest amounts of data. Logs are compacted when opened if
+/// most of their contents have been superseded, and may be explicitly
+/// compacted with [`FileStore::compact`].
+///
+/// A log file can only be opened by one `FileStore` at a time, which is
+/// enforced with an exclusive advisory lock on the file. To share a store
+/// between many guests, share the `FileStore` itself.
+pub struct FileStore {
+    log: Arc<Mutex<Log>>,
+}
+
+struct Log {
+    path: PathBuf,
+    file: File,
+    /// The length of the valid prefix of `file`.
+    len: u64,
+    buckets: HashMap<String, BTreeMap<String, Vec<u8>>>,
+}
+
+struct FileBucket {
+    log: Arc<Mutex<Log>>,
+    name: String,
+}
+
+enum Op<'a> {
+    Set(&'a str, &'a [u8]),
+    Delete(&'a str),
+}
+
+impl FileStore {
+    /// Opens the log at `path`, creating it if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be opened, is not a key-value log,
+    /// or is already open in another `FileStore`, possibly in another process.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<FileStore> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("failed to open key-value log {}", path.display()))?;
+        lock(&file, path)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        let mut buckets = HashMap::new();
+        let len = if contents.is_empty() {
+            file.write_all(MAGIC)?;
+            file.sync_all()?;
+            MAGIC.len()
+        } else {
+            if !contents.starts_with(MAGIC) {
+                bail!("{} is not a key-value log", path.display());
+            }
+            let len = replay(&contents, &mut buckets)
+                .with_context(|| format!("failed to replay key-value log {}", path.display()))?;
+            if len < contents.len() {
+                // Discard a transaction which was torn by a crash.
+                file.set_len(len as u64)?;
+                file.sync_all()?;
+            }
+            len
+        };
+
+        let mut log = Log {
+            path: path.to_path_buf(),
+            file,
+            len: len as u64,
+            buckets,
+        };
+        if log.len > COMPACT_THRESHOLD && log.len > 2 * log.live_size() {
+            log.compact()?;
+        }
+        Ok(FileStore {
+            log: Arc::new(Mutex::new(log)),
+        })
+    }
+
+    /// Rewrites the log to contain only the current contents of the store.
+    ///
+    /// The new log is written to a temporary file alongside the original,
+    /// which then atomically replaces it.
+    pub fn compact(&self) -> anyhow::Result<()> {
+        self.log.lock().unwrap().compact()
+    }
+}
+
+impl KeyValueStore for FileStore {
+    fn open(&self, identifier: &str) -> Result<Arc<dyn KeyValueBucket>, Error> {
+        Ok(Arc::new(FileBucket {
+            log: self.log.clone(),
+            name: identifier.to_string(),
+        }))
+    }
+}
+
+impl Log {
+    /// Appends a transaction containing `ops` to bucket `name`, and applies
+    /// them once they are durable.
+    fn commit(&mut self, name: &str, ops: &[Op<'_>]) -> Result<(), Error> {
+        if ops.is_empty() {
+            return Ok(());
+        }
+        let mut body = Vec::new();
+        for op in ops {
+            encode(&mut body, name, op);
+        }
+        let mut txn = Vec::with_capacity(HEADER_SIZE + body.len());
+        push_transaction(&mut txn, &body);
+
+        let result = self
+            .file
+            .seek(SeekFrom::Start(self.len))
+            .and_then(|_| self.file.write_all(&txn))
+            .and_then(|()| self.file.sync_data());
+        if let Err(e) = result {
+            // Try not to leave a torn transaction behind, although it would
+            // be discarded when the log is next opened anyway.
+            let _ = self.file.set_len(self.len);
+            return Err(Error::Other(format!("failed to write key-value log: {e}")));
+        }
+        self.len += txn.len() as u64;
+
+        let bucket = self.buckets.entry(name.to_string()).or_default();
+        for op in ops {
+            apply(bucket, op);
+        }
+        Ok(())
+    }
+
+    fn contains(&self, name: &str, key: &str) -> bool {
+        self.buckets
+            .get(name)
+            .is_some_and(|bucket| bucket.contains_key(key))
+    }
+
+    /// Returns the size a log containing only the current data would have.
+    fn live_size(&self) -> u64 {
+        let mut size = MAGIC.len() as u64;
+        for (name, bucket) in &self.buckets {
+            for (key, value) in bucket {
+                size += (HEADER_SIZE + 17 + name.len() + key.len() + value.len()) as u64;
+            }
+        }
+        size
+    }
+
+    fn compact(&mut self) -> anyhow::Result<()> {
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".compact");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut contents = MAGIC.to_vec();
+        for (name, bucket) in &self.buckets {
+            if bucket.is_empty() {
+                continue;
+            }
+            let mut body = Vec::new();
+            for (key, value) in bucket {
+                encode(&mut body, name, &Op::Set(key, value));
+            }
+            push_transaction(&mut contents, &body);
+        }
+
+        // The lock is held on the file rather than its path, so lock the new
+        // log before it replaces the old one.
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)
+            .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+        lock(&file, &tmp_path)?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("failed to replace key-value log {}", self.path.display()))?;
+        sync_parent_dir(&self.path)?;
+
+        self.file = file;
+        self.len = contents.len() as u64;
+        Ok(())
+    }
+}
+
+/// Takes an exclusive advisory lock on `file`, which is released when it is
+/// closed.
+#[cfg(unix)]
+fn lock(file: &File, path: &Path) -> anyhow::Result<()> {
+    use rustix::fs::{flock, FlockOperation};
+    match flock(file, FlockOperation::NonBlockingLockExclusive) {
+        Ok(()) => Ok(()),
+        Err(rustix::io::Errno::WOULDBLOCK) => {
+            bail!("key-value log {} is already in use", path.display())
+        }
+        Err(e) => Err(std::io::Error::from(e))
+            .with_context(|| format!("failed to lock key-value log {}", path.display())),
+    }
+}
+
+#[cfg(windows)]
+fn lock(file: &File, path: &Path) -> anyhow::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::ERROR_LOCK_VIOLATION;
+    use windows_sys::Win32::Storage::FileSystem::{
+        LockFileEx, LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY,
+    };
+
+    // SAFETY: the handle is valid for as long as `file` is, and `overlapped`
+    // only needs to outlive this call since the lock doesn't block.
+    let ok = unsafe {
+        let mut overlapped = std::mem::zeroed();
+        LockFileEx(
+            file.as_raw_handle() as _,
+            LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY,
+            0,
+            u32::MAX,
+            u32::MAX,
+            &mut overlapped,
+        )
+    };
+    if ok != 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+        bail!("key-value log {} is already in use", path.display());
+    }
+    Err(err).with_context(|| format!("failed to lock key-value log {}", path.display()))
+}
+
+/// Makes a rename of `path` durable by syncing the directory containing it.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> anyhow::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .with_context(|| format!("failed to sync directory {}", dir.display()))
+}
+
+/// Directories can't be synced on Windows, where renames are made durable
+/// by the file system's own journal.
+#[cfg(windows)]
+fn sync_parent_dir(_path: &Path) -> anyhow::Result<()> {
+    Ok(())
+}
+
+impl KeyValueBucket for FileBucket {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let log = self.log.lock().unwrap();
+        Ok(log
+            .buckets
+            .get(&self.name)
+            .and_then(|bucket| bucket.get(key))
+            .cloned())
+    }
+
+    fn set(&self, key: &str, value: Vec<u8>) -> Result<(), Error> {
+        let mut log = self.log.lock().unwrap();
+        log.commit(&self.name, &[Op::Set(key, &value)])
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Error> {
+        let mut log = self.log.lock().unwrap();
+        if !log.contains(&self.name, key) {
+            return Ok(());
+        }
+        log.commit(&self.name, &[Op::Delete(key)])
+    }
+
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.log.lock().unwrap().contains(&self.name, key))
+    }
+
+    fn list_keys(&self, cursor: Option<u64>) -> Result<(Vec<String>, Option<u64>), Error> {
+        let log = self.log.lock().unwrap();
+        Ok(match log.buckets.get(&self.name) {
+            Some(bucket) => list_keys_page(bucket.keys(), cursor),
+            None => (Vec::new(), None),
+        })
+    }
+
+    fn increment(&self, key: &str, delta: u64) -> Result<u64, Error> {
+        let mut log = self.log.lock().unwrap();
+        let current = log
+            .buckets
+            .get(&self.name)
+            .and_then(|bucket| bucket.get(key));
+        let new_value = increment_value(current.map(|v| &v[..]), delta)?;
+        let encoded = new_value.to_string();
+        log.commit(&self.name, &[Op::Set(key, encoded.as_bytes())])?;
+        Ok(new_value)
+    }
+
+    fn set_many(&self, key_values: Vec<(String, Vec<u8>)>) -> Result<(), Error> {
+        let ops = key_values
+            .iter()
+            .map(|(key, value)| Op::Set(key, value))
+            .collect::<Vec<_>>();
+        self.log.lock().unwrap().commit(&self.name, &ops)
+    }
+
+    fn delete_many(&self, keys: Vec<String>) -> Result<(), Error> {
+        let mut log = self.log.lock().unwrap();
+        let ops = keys
+            .iter()
+            .filter(|key| log.contains(&self.name, key))
+            .map(|key| Op::Delete(key))
+            .collect::<Vec<_>>();
+        log.commit(&self.name, &ops)
+    }
+}
+
+fn apply(bucket: &mut BTreeMap<String, Vec<u8>>, op: &Op<'_>) {
+    match op {
+        Op::Set(key, value) => {
+            bucket.insert(key.to_string(), value.to_vec());
+        }
+        Op::Delete(key) => {
+            bucket.remove(*key);
+        }
+    }
+}
+
+/// Appends the encoding of `op` on bucket `name` to `buf`.
+///
+/// Each operation is a one-byte opcode followed by the bucket name and key,
+/// each prefixed by a 32-bit length, and for sets the value, prefixed by a
+/// 64-bit length. All integers are little-endian.
+fn encode(buf: &mut Vec<u8>, name: &str, op: &Op<'_>) {
+    let (opcode, key, value) = match op {
+        Op::Set(key, value) => (OP_SET, key, Some(value)),
+        Op::Delete(key) => (OP_DELETE, key, None),
+    };
+    buf.push(opcode);
+    buf.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    buf.extend_from_slice(name.as_bytes());
+    buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    buf.extend_from_slice(key.as_bytes());
+    if let Some(value) = value {
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        buf.extend_from_slice(value);
+    }
+}
+
+/// Appends a transaction with the given `body` to `buf`.
+fn push_transaction(buf: &mut Vec<u8>, body: &[u8]) {
+    let len = (body.len() as u64).to_le_bytes();
+    buf.extend_from_slice(&len);
+    buf.extend_from_slice(&checksum(&len).to_le_bytes());
+    buf.extend_from_slice(&checksum(body).to_le_bytes());
+    buf.extend_from_slice(body);
+}
+
+/// Applies every complete transaction in `contents` to `buckets`, returning
+/// the length of the valid prefix of `contents`.
+///
+/// Only the last transaction may be incomplete or fail its checksums, which is
+/// what a crash while appending it leaves behind. A damaged transaction
+/// followed by more data means the log is corrupt, and is an error.
+///
+/// A transaction's length is only trusted once its own checksum matches, as
+/// otherwise a damaged length could make the transaction appear to run past
+/// the end of the log, and every transaction after it would be discarded.
+fn replay(
+    contents: &[u8],
+    buckets: &mut HashMap<String, BTreeMap<String, Vec<u8>>>,
+) -> anyhow::Result<usize> {
+    let mut pos = MAGIC.len();
+    while let Some(header) = contents.get(pos..pos + HEADER_SIZE) {
+        let len = u64::from_le_bytes(header[..8].try_into().unwrap());
+        let len_sum = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let sum = u64::from_le_bytes(header[16..].try_into().unwrap());
+        if checksum(&header[..8]) != len_sum {
+            if pos + HEADER_SIZE == contents.len() {
+                break;
+            }
+            bail!("corrupt transaction header at offset {pos} is followed by more data");
+        }
+        let body = match usize::try_from(len)
+            .ok()
+            .and_then(|len| contents.get(pos + HEADER_SIZE..)?.get(..len))
+        {
+            Some(body) => body,
+            // The transaction was cut short while being appended.
+            None => break,
+        };
+        let end = pos + HEADER_SIZE + body.len();
+        let ops = match (checksum(body) == sum).then(|| decode(body)).flatten() {
+            Some(ops) => ops,
+            None if end == contents.len() => break,
+            None => bail!("corrupt transaction at offset {pos} is followed by more data"),
+        };
+        for (name, op) in ops {
+            apply(buckets.entry(name.to_string()).or_default(), &op);
+        }
+        pos = end;
+    }
+    Ok(pos)
+}
+
+/// Decodes the operations in a transaction body, or returns `None` if it is
+/// malformed.
+fn decode(mut body: &[u8]) -> Option<Vec<(&str, Op<'_>)>> {
+    fn take<'a>(body: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+        let (head, tail) = (body.get(..n)?, body.get(n..)?);
+        *body = tail;
+        Some(head)
+    }
+    fn take_str<'a>(body: &mut &'a [u8]) -> Option<&'a str> {
+        let len = u32::from_le_bytes(take(body, 4)?.try_into().unwrap());
+        std::str::from_utf8(take(body, usize::try_from(len).ok()?)?).ok()
+    }
+
+    let mut ops = Vec::new();
+    while !body.is_empty() {
+        let opcode = take(&mut body, 1)?[0];
+        let name = take_str(&mut body)?;
+        let key = take_str(&mut body)?;
+        let op = match opcode {
+            OP_SET => {
+                let len = u64::from_le_bytes(take(&mut body, 8)?.try_into().unwrap());
+                Op::Set(key, take(&mut body, usize::try_from(len).ok()?)?)
+            }
+            OP_DELETE => Op::Delete(key),
+            _ => return None,
+        };
+        ops.push((name, op));
+    }
+    Some(ops)
+}
+
+/// The 64-bit FNV-1a hash of `data`, used to detect torn writes.
+fn checksum(data: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in data {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
@@ -5,7 +5,12 @@
 //! [wasi-keyvalue] and provide components with access to key-value storages.
 //!
 //! Currently supported storage backends:
-//! * In-Memory (empty identifier)
+//! * In-Memory (empty identifier), see [`InMemoryStore`]
+//! * File-backed append-only log (any identifier), see [`FileStore`]
+//!
+//! Other backends can be provided by implementing [`KeyValueStore`] and
+//! [`KeyValueBucket`] and configuring them with
+//! [`WasiKeyValueCtxBuilder::store`].
 //!
 //! # Examples
 //!
@@ -65,6 +70,9 @@
 
 #![deny(missing_docs)]
 
+mod file;
+mod memory;
+
 mod generated {
     wasmtime::component::bindgen!({
         path: "wit",
@@ -81,31 +89,165 @@ mod generated {
 
 use self::generated::wasi::keyvalue;
 use anyhow::Result;
-use std::collections::HashMap;
+use std::sync::Arc;
 use wasmtime::component::{Resource, ResourceTable, ResourceTableError};
 
-#[doc(hidden)]
+pub use self::file::FileStore;
+pub use self::memory::InMemoryStore;
+
+/// An error returned by a [`KeyValueStore`] or [`KeyValueBucket`], which is
+/// reported to the guest as a `wasi:keyvalue/store.error`.
+#[derive(Debug)]
 pub enum Error {
+    /// The requested store or bucket does not exist.
     NoSuchStore,
+    /// The requested store or bucket exists, but access to it is denied.
     AccessDenied,
+    /// Some other implementation-specific error.
     Other(String),
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NoSuchStore => f.write_str("no such store"),
+            Error::AccessDenied => f.write_str("access denied"),
+            Error::Other(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<ResourceTableError> for Error {
     fn from(err: ResourceTableError) -> Self {
         Self::Other(err.to_string())
     }
 }
 
+/// A key-value storage backend, from which the guest opens buckets with
+/// `wasi:keyvalue/store.open`.
+///
+/// A store is shared by every [`WasiKeyValueCtx`] it is configured in, so
+/// implementations must be safe to use concurrently.
+pub trait KeyValueStore: Send + Sync + 'static {
+    /// Opens the bucket named `identifier`.
+    ///
+    /// Returns [`Error::NoSuchStore`] if this store has no bucket by that
+    /// name.
+    fn open(&self, identifier: &str) -> Result<Arc<dyn KeyValueBucket>, Error>;
+}
+
+/// A bucket of key-value pairs opened from a [`KeyValueStore`].
+///
+/// This is the host side of the `wasi:keyvalue/store.bucket` resource, along
+/// with the `wasi:keyvalue/atomics` and `wasi:keyvalue/batch` interfaces.
+pub trait KeyValueBucket: Send + Sync + 'static {
+    /// Returns the value associated with `key`, if any.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Associates `value` with `key`, replacing any existing value.
+    fn set(&self, key: &str, value: Vec<u8>) -> Result<(), Error>;
+
+    /// Removes `key`, if it exists.
+    fn delete(&self, key: &str) -> Result<(), Error>;
+
+    /// Returns whether `key` exists.
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Returns a page of keys, starting from `cursor`, along with the cursor
+    /// for the next page or `None` if this is the last page.
+    ///
+    /// The meaning of a cursor is up to the implementation, but `None`
+    /// always starts from the beginning.
+    fn list_keys(&self, cursor: Option<u64>) -> Result<(Vec<String>, Option<u64>), Error>;
+
+    /// Atomically adds `delta` to the number stored as a decimal string in
+    /// `key`, treating a missing key as zero, and returns the new value.
+    fn increment(&self, key: &str, delta: u64) -> Result<u64, Error>;
+
+    /// Returns the values associated with each of `keys`.
+    ///
+    /// The default implementation calls [`KeyValueBucket::get`] for each key.
+    fn get_many(&self, keys: Vec<String>) -> Result<Vec<Option<(String, Vec<u8>)>>, Error> {
+        keys.into_iter()
+            .map(|key| Ok(self.get(&key)?.map(|value| (key, value))))
+            .collect()
+    }
+
+    /// Sets each of `key_values`.
+    ///
+    /// The default implementation calls [`KeyValueBucket::set`] for each
+    /// pair, which is permitted by `wasi:keyvalue/batch` even though it is not
+    /// atomic.
+    fn set_many(&self, key_values: Vec<(String, Vec<u8>)>) -> Result<(), Error> {
+        for (key, value) in key_values {
+            self.set(&key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes each of `keys`.
+    ///
+    /// The default implementation calls [`KeyValueBucket::delete`] for each
+    /// key.
+    fn delete_many(&self, keys: Vec<String>) -> Result<(), Error> {
+        for key in keys {
+            self.delete(&key)?;
+        }
+        Ok(())
+    }
+}
+
+/// The maximum number of keys returned by one call to `list-keys` for the
+/// built-in stores.
+const LIST_KEYS_PAGE_SIZE: usize = 1000;
+
+/// Returns the page of `keys` starting at the index `cursor`.
+fn list_keys_page<'a>(
+    keys: impl Iterator<Item = &'a String>,
+    cursor: Option<u64>,
+) -> (Vec<String>, Option<u64>) {
+    let start = cursor.unwrap_or(0);
+    let mut page = keys
+        .skip(usize::try_from(start).unwrap_or(usize::MAX))
+        .take(LIST_KEYS_PAGE_SIZE + 1)
+        .cloned()
+        .collect::<Vec<_>>();
+    if page.len() > LIST_KEYS_PAGE_SIZE {
+        page.truncate(LIST_KEYS_PAGE_SIZE);
+        (page, Some(start + LIST_KEYS_PAGE_SIZE as u64))
+    } else {
+        (page, None)
+    }
+}
+
+/// Computes the result of `wasi:keyvalue/atomics.increment` on the existing
+/// value `value`.
+fn increment_value(value: Option<&[u8]>, delta: u64) -> Result<u64, Error> {
+    let current = match value {
+        Some(value) => std::str::from_utf8(value)
+            .map_err(|e| Error::Other(e.to_string()))?
+            .parse::<u64>()
+            .map_err(|e| Error::Other(e.to_string()))?,
+        None => 0,
+    };
+    current
+        .checked_add(delta)
+        .ok_or_else(|| Error::Other("increment overflowed".to_string()))
+}
+
 #[doc(hidden)]
 pub struct Bucket {
-    in_memory_data: HashMap<String, Vec<u8>>,
+    inner: Arc<dyn KeyValueBucket>,
 }
 
 /// Builder-style structure used to create a [`WasiKeyValueCtx`].
 #[derive(Default)]
 pub struct WasiKeyValueCtxBuilder {
-    in_memory_data: HashMap<String, Vec<u8>>,
+    store: Option<Arc<dyn KeyValueStore>>,
 }
 
 impl WasiKeyValueCtxBuilder {
@@ -115,30 +257,40 @@ impl WasiKeyValueCtxBuilder {
     }
 
     /// Preset data for the In-Memory provider.
-    pub fn in_memory_data<I, K, V>(mut self, data: I) -> Self
+    ///
+    /// This replaces any store configured with
+    /// [`WasiKeyValueCtxBuilder::store`] with a new [`InMemoryStore`].
+    pub fn in_memory_data<I, K, V>(self, data: I) -> Self
     where
         I: IntoIterator<Item = (K, V)>,
         K: Into<String>,
         V: Into<Vec<u8>>,
     {
-        self.in_memory_data = data
-            .into_iter()
-            .map(|(k, v)| (k.into(), v.into()))
-            .collect();
+        self.store(Arc::new(InMemoryStore::with_data(data)))
+    }
+
+    /// Use `store` to provide buckets to the guest.
+    ///
+    /// Defaults to an empty [`InMemoryStore`].
+    pub fn store(mut self, store: Arc<dyn KeyValueStore>) -> Self {
+        self.store = Some(store);
         self
     }
 
     /// Uses the configured context so far to construct the final [`WasiKeyValueCtx`].
     pub fn build(self) -> WasiKeyValueCtx {
         WasiKeyValueCtx {
-            in_memory_data: self.in_memory_data,
+            store: self.store.unwrap_or_else(|| Arc::new(InMemoryStore::new())),
         }
     }
 }
 
 /// Capture the state necessary for use in the `wasi-keyvalue` API implementation.
+///
+/// Cloning a context shares its underlying store.
+#[derive(Clone)]
 pub struct WasiKeyValueCtx {
-    in_memory_data: HashMap<String, Vec<u8>>,
+    store: Arc<dyn KeyValueStore>,
 }
 
 impl WasiKeyValueCtx {
@@ -163,12 +315,8 @@ impl<'a> WasiKeyValue<'a> {
 
 impl keyvalue::store::Host for WasiKeyValue<'_> {
     fn open(&mut self, identifier: String) -> Result<Resource<Bucket>, Error> {
-        match identifier.as_str() {
-            "" => Ok(self.table.push(Bucket {
-                in_memory_data: self.ctx.in_memory_data.clone(),
-            })?),
-            _ => Err(Error::NoSuchStore),
-        }
+        let inner = self.ctx.store.open(&identifier)?;
+        Ok(self.table.push(Bucket { inner })?)
     }
 
     fn convert_error(&mut self, err: Error) -> Result<keyvalue::store::Error> {
@@ -182,25 +330,23 @@ impl keyvalue::store::Host for WasiKeyValue<'_> {
 
 impl keyvalue::store::HostBucket for WasiKeyValue<'_> {
     fn get(&mut self, bucket: Resource<Bucket>, key: String) -> Result<Option<Vec<u8>>, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        Ok(bucket.in_memory_data.get(&key).cloned())
+        let bucket = self.table.get(&bucket)?;
+        bucket.inner.get(&key)
     }
 
     fn set(&mut self, bucket: Resource<Bucket>, key: String, value: Vec<u8>) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        bucket.in_memory_data.insert(key, value);
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.inner.set(&key, value)
     }
 
     fn delete(&mut self, bucket: Resource<Bucket>, key: String) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        bucket.in_memory_data.remove(&key);
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.inner.delete(&key)
     }
 
     fn exists(&mut self, bucket: Resource<Bucket>, key: String) -> Result<bool, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        Ok(bucket.in_memory_data.contains_key(&key))
+        let bucket = self.table.get(&bucket)?;
+        bucket.inner.exists(&key)
     }
 
     fn list_keys(
@@ -208,14 +354,9 @@ impl keyvalue::store::HostBucket for WasiKeyValue<'_> {
         bucket: Resource<Bucket>,
         cursor: Option<u64>,
     ) -> Result<keyvalue::store::KeyResponse, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        let keys: Vec<String> = bucket.in_memory_data.keys().cloned().collect();
-        let cursor = cursor.unwrap_or(0) as usize;
-        let keys_slice = &keys[cursor..];
-        Ok(keyvalue::store::KeyResponse {
-            keys: keys_slice.to_vec(),
-            cursor: None,
-        })
+        let bucket = self.table.get(&bucket)?;
+        let (keys, cursor) = bucket.inner.list_keys(cursor)?;
+        Ok(keyvalue::store::KeyResponse { keys, cursor })
     }
 
     fn drop(&mut self, bucket: Resource<Bucket>) -> Result<()> {
@@ -231,18 +372,8 @@ impl keyvalue::atomics::Host for WasiKeyValue<'_> {
         key: String,
         delta: u64,
     ) -> Result<u64, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        let value = bucket
-            .in_memory_data
-            .entry(key.clone())
-            .or_insert("0".to_string().into_bytes());
-        let current_value = String::from_utf8(value.clone())
-            .map_err(|e| Error::Other(e.to_string()))?
-            .parse::<u64>()
-            .map_err(|e| Error::Other(e.to_string()))?;
-        let new_value = current_value + delta;
-        *value = new_value.to_string().into_bytes();
-        Ok(new_value)
+        let bucket = self.table.get(&bucket)?;
+        bucket.inner.increment(&key, delta)
     }
 }
 
@@ -252,16 +383,8 @@ impl keyvalue::batch::Host for WasiKeyValue<'_> {
         bucket: Resource<Bucket>,
         keys: Vec<String>,
     ) -> Result<Vec<Option<(String, Vec<u8>)>>, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        Ok(keys
-            .into_iter()
-            .map(|key| {
-                bucket
-                    .in_memory_data
-                    .get(&key)
-                    .map(|value| (key.clone(), value.clone()))
-            })
-            .collect())
+        let bucket = self.table.get(&bucket)?;
+        bucket.inner.get_many(keys)
     }
 
     fn set_many(
@@ -269,19 +392,13 @@ impl keyvalue::batch::Host for WasiKeyValue<'_> {
         bucket: Resource<Bucket>,
         key_values: Vec<(String, Vec<u8>)>,
     ) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        for (key, value) in key_values {
-            bucket.in_memory_data.insert(key, value);
-        }
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.inner.set_many(key_values)
     }
 
     fn delete_many(&mut self, bucket: Resource<Bucket>, keys: Vec<String>) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        for key in keys {
-            bucket.in_memory_data.remove(&key);
-        }
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.inner.delete_many(keys)
     }
 }
 
@@ -0,0 +1,83 @@
+use crate::{increment_value, list_keys_page, Error, KeyValueBucket, KeyValueStore};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// A [`KeyValueStore`] which keeps its data in memory.
+///
+/// This store has a single bucket, which is opened with the empty identifier.
+/// Data is shared between all handles to the bucket, but is lost when the
+/// store is dropped.
+#[derive(Default)]
+pub struct InMemoryStore {
+    bucket: Arc<InMemoryBucket>,
+}
+
+#[derive(Default)]
+struct InMemoryBucket {
+    data: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl InMemoryStore {
+    /// Creates a new, empty, store.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a new store whose bucket is populated with `data`.
+    pub fn with_data<I, K, V>(data: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Vec<u8>>,
+    {
+        let data = data
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        InMemoryStore {
+            bucket: Arc::new(InMemoryBucket {
+                data: Mutex::new(data),
+            }),
+        }
+    }
+}
+
+impl KeyValueStore for InMemoryStore {
+    fn open(&self, identifier: &str) -> Result<Arc<dyn KeyValueBucket>, Error> {
+        match identifier {
+            "" => Ok(self.bucket.clone()),
+            _ => Err(Error::NoSuchStore),
+        }
+    }
+}
+
+impl KeyValueBucket for InMemoryBucket {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.data.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: Vec<u8>) -> Result<(), Error> {
+        self.data.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Error> {
+        self.data.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.data.lock().unwrap().contains_key(key))
+    }
+
+    fn list_keys(&self, cursor: Option<u64>) -> Result<(Vec<String>, Option<u64>), Error> {
+        Ok(list_keys_page(self.data.lock().unwrap().keys(), cursor))
+    }
+
+    fn increment(&self, key: &str, delta: u64) -> Result<u64, Error> {
+        let mut data = self.data.lock().unwrap();
+        let new_value = increment_value(data.get(key).map(|v| &v[..]), delta)?;
+        data.insert(key.to_string(), new_value.to_string().into_bytes());
+        Ok(new_value)
+    }
+}
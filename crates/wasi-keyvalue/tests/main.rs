@@ -1,11 +1,15 @@
 use anyhow::{anyhow, Result};
+use std::io::Write;
+use std::sync::Arc;
 use test_programs_artifacts::{foreach_keyvalue, KEYVALUE_MAIN_COMPONENT};
 use wasmtime::{
     component::{Component, Linker, ResourceTable},
     Store,
 };
 use wasmtime_wasi::{bindings::Command, WasiCtx, WasiCtxBuilder, WasiView};
-use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder};
+use wasmtime_wasi_keyvalue::{
+    Error, FileStore, KeyValueStore, WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder,
+};
 
 struct Ctx {
     table: ResourceTable,
@@ -67,3 +71,225 @@ async fn keyvalue_main() -> Result<()> {
     )
     .await
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keyvalue_main_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("kv.log");
+
+    let store = FileStore::open(&path)?;
+    store.open("")?.set("atomics_key", b"5".to_vec())?;
+    run_wasi(
+        KEYVALUE_MAIN_COMPONENT,
+        Ctx {
+            table: ResourceTable::new(),
+            wasi_ctx: WasiCtxBuilder::new().inherit_stderr().build(),
+            wasi_keyvalue_ctx: WasiKeyValueCtxBuilder::new().store(Arc::new(store)).build(),
+        },
+    )
+    .await?;
+
+    // Everything the guest did should be visible once the log is reopened.
+    let bucket = FileStore::open(&path)?.open("")?;
+    assert_eq!(bucket.get("atomics_key")?, Some(b"6".to_vec()));
+    assert_eq!(bucket.get("hello")?, None);
+    assert_eq!(
+        bucket.list_keys(None)?,
+        (vec!["atomics_key".to_string(), "b1".to_string()], None)
+    );
+    Ok(())
+}
+
+#[test]
+fn file_store_buckets_and_batches() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("kv.log");
+
+    let store = FileStore::open(&path)?;
+    let a = store.open("a")?;
+    let b = store.open("b")?;
+    a.set_many(vec![
+        ("x".to_string(), b"1".to_vec()),
+        ("y".to_string(), b"2".to_vec()),
+    ])?;
+    b.set("x", b"other".to_vec())?;
+    assert_eq!(a.increment("counter", 3)?, 3);
+    assert_eq!(a.increment("counter", 4)?, 7);
+    assert!(matches!(a.increment("x", u64::MAX), Err(Error::Other(_))));
+    b.delete_many(vec!["x".to_string(), "missing".to_string()])?;
+    drop((a, b, store));
+
+    let store = FileStore::open(&path)?;
+    let a = store.open("a")?;
+    assert_eq!(
+        a.get_many(vec!["x".to_string(), "y".to_string(), "z".to_string()])?,
+        vec![
+            Some(("x".to_string(), b"1".to_vec())),
+            Some(("y".to_string(), b"2".to_vec())),
+            None,
+        ]
+    );
+    assert_eq!(a.get("counter")?, Some(b"7".to_vec()));
+    assert!(!store.open("b")?.exists("x")?);
+
+    // Compaction preserves the contents of the store.
+    let len = std::fs::metadata(&path)?.len();
+    store.compact()?;
+    assert!(std::fs::metadata(&path)?.len() < len);
+    a.set("z", b"3".to_vec())?;
+    drop((a, store));
+    let a = FileStore::open(&path)?.open("a")?;
+    assert_eq!(
+        a.list_keys(None)?.0,
+        ["counter", "x", "y", "z"].map(String::from)
+    );
+    Ok(())
+}
+
+#[test]
+fn file_store_discards_torn_writes() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("kv.log");
+
+    let store = FileStore::open(&path)?;
+    store.open("")?.set("key", b"value".to_vec())?;
+    drop(store);
+    let len = std::fs::metadata(&path)?.len();
+
+    // Simulate a crash part of the way through writing a transaction.
+    let mut file = std::fs::OpenOptions::new().append(true).open(&path)?;
+    file.write_all(&[100, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3])?;
+    drop(file);
+
+    let store = FileStore::open(&path)?;
+    assert_eq!(std::fs::metadata(&path)?.len(), len);
+    let bucket = store.open("")?;
+    assert_eq!(bucket.get("key")?, Some(b"value".to_vec()));
+    bucket.set("key", b"new".to_vec())?;
+    drop((bucket, store));
+    assert_eq!(
+        FileStore::open(&path)?.open("")?.get("key")?,
+        Some(b"new".to_vec())
+    );
+
+    // A crash after the header of a transaction was written, but before all
+    // of its body was.
+    let len = std::fs::metadata(&path)?.len();
+    FileStore::open(&path)?
+        .open("")?
+        .set("key", b"newer".to_vec())?;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)?
+        .set_len(len + 30)?;
+    let bucket = FileStore::open(&path)?.open("")?;
+    assert_eq!(std::fs::metadata(&path)?.len(), len);
+    assert_eq!(bucket.get("key")?, Some(b"new".to_vec()));
+    drop(bucket);
+
+    std::fs::write(&path, b"not a log")?;
+    assert!(FileStore::open(&path).is_err());
+    Ok(())
+}
+
+#[test]
+fn file_store_rejects_corruption_before_the_end() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("kv.log");
+
+    let store = FileStore::open(&path)?;
+    let bucket = store.open("")?;
+    bucket.set("a", b"1".to_vec())?;
+    let first_end = std::fs::metadata(&path)?.len();
+    bucket.set("b", b"2".to_vec())?;
+    drop((bucket, store));
+
+    // Damage the last byte of the first transaction, which is followed by the
+    // second one, so this can't be a torn write.
+    let mut contents = std::fs::read(&path)?;
+    let len = contents.len() as u64;
+    contents[first_end as usize - 1] ^= 0xff;
+    std::fs::write(&path, &contents)?;
+    let err = FileStore::open(&path)
+        .err()
+        .expect("corrupt log should fail to open");
+    assert!(
+        format!("{err:?}").contains("corrupt transaction"),
+        "{err:?}"
+    );
+    assert_eq!(std::fs::metadata(&path)?.len(), len);
+
+    // The same damage to the last transaction is treated as a torn write.
+    contents[first_end as usize - 1] ^= 0xff;
+    *contents.last_mut().unwrap() ^= 0xff;
+    std::fs::write(&path, &contents)?;
+    let bucket = FileStore::open(&path)?.open("")?;
+    assert_eq!(std::fs::metadata(&path)?.len(), first_end);
+    assert_eq!(bucket.get("a")?, Some(b"1".to_vec()));
+    assert_eq!(bucket.get("b")?, None);
+    Ok(())
+}
+
+#[test]
+fn file_store_rejects_corrupt_header_before_the_end() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("kv.log");
+
+    let store = FileStore::open(&path)?;
+    let bucket = store.open("")?;
+    bucket.set("a", b"1".to_vec())?;
+    let first_end = std::fs::metadata(&path)?.len() as usize;
+    bucket.set("b", b"2".to_vec())?;
+    let second_end = std::fs::metadata(&path)?.len() as usize;
+    bucket.set("c", b"3".to_vec())?;
+    drop((bucket, store));
+
+    // Damage the length of the second transaction so that it appears to run
+    // past the end of the log, which must not discard the third one.
+    let mut contents = std::fs::read(&path)?;
+    let len = contents.len() as u64;
+    contents[first_end + 7] ^= 0xff;
+    std::fs::write(&path, &contents)?;
+    let err = FileStore::open(&path)
+        .err()
+        .expect("corrupt log should fail to open");
+    assert!(
+        format!("{err:?}").contains("corrupt transaction header"),
+        "{err:?}"
+    );
+    assert_eq!(std::fs::metadata(&path)?.len(), len);
+
+    // A damaged header is only treated as a torn write if nothing follows it.
+    contents[first_end + 7] ^= 0xff;
+    contents.truncate(second_end + 24);
+    contents[second_end + 7] ^= 0xff;
+    std::fs::write(&path, &contents)?;
+    let bucket = FileStore::open(&path)?.open("")?;
+    assert_eq!(std::fs::metadata(&path)?.len(), second_end as u64);
+    assert_eq!(bucket.get("b")?, Some(b"2".to_vec()));
+    assert_eq!(bucket.get("c")?, None);
+    Ok(())
+}
+
+#[test]
+fn file_store_is_locked_while_open() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("kv.log");
+
+    let store = FileStore::open(&path)?;
+    store.open("")?.set("key", b"value".to_vec())?;
+    let err = FileStore::open(&path)
+        .err()
+        .expect("log should be locked by the first store");
+    assert!(format!("{err:?}").contains("already in use"), "{err:?}");
+
+    // The lock is kept on the new log when compacting.
+    store.compact()?;
+    assert!(FileStore::open(&path).is_err());
+    store.open("")?.set("key", b"new".to_vec())?;
+
+    drop(store);
+    let bucket = FileStore::open(&path)?.open("")?;
+    assert_eq!(bucket.get("key")?, Some(b"new".to_vec()));
+    Ok(())
+}
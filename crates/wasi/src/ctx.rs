@@ -3,11 +3,11 @@ use crate::{
         host::{monotonic_clock, wall_clock},
         HostMonotonicClock, HostWallClock,
     },
-    filesystem::{Dir, OpenMode},
+    filesystem::{Descriptor, Dir, OpenMode, VirtualDescriptor},
     network::{SocketAddrCheck, SocketAddrUse},
     pipe, random, stdio,
     stdio::{StdinStream, StdoutStream},
-    DirPerms, FilePerms,
+    DirPerms, FilePerms, VirtualFilesystem,
 };
 use anyhow::Result;
 use cap_rand::{Rng, RngCore, SeedableRng};
@@ -44,7 +44,7 @@ pub struct WasiCtxBuilder {
     stderr: Box<dyn StdoutStream>,
     env: Vec<(String, String)>,
     args: Vec<String>,
-    preopens: Vec<(Dir, String)>,
+    preopens: Vec<(Descriptor, String)>,
     socket_addr_check: SocketAddrCheck,
     random: Box<dyn RngCore + Send>,
     insecure_random: Box<dyn RngCore + Send>,
@@ -332,26 +332,71 @@ impl WasiCtxBuilder {
         file_perms: FilePerms,
     ) -> Result<&mut Self> {
         let dir = cap_std::fs::Dir::open_ambient_dir(host_path.as_ref(), ambient_authority())?;
-        let mut open_mode = OpenMode::empty();
-        if dir_perms.contains(DirPerms::READ) {
-            open_mode |= OpenMode::READ;
-        }
-        if dir_perms.contains(DirPerms::MUTATE) {
-            open_mode |= OpenMode::WRITE;
-        }
         self.preopens.push((
-            Dir::new(
+            Descriptor::Dir(Dir::new(
                 dir,
                 dir_perms,
                 file_perms,
-                open_mode,
+                preopen_open_mode(dir_perms),
                 self.allow_blocking_current_thread,
-            ),
+            )),
             guest_path.as_ref().to_owned(),
         ));
         Ok(self)
     }
 
+    /// Configures a [`VirtualFilesystem`] to be available to WebAssembly as a
+    /// "preopened directory".
+    ///
+    /// This is like [`WasiCtxBuilder::preopened_dir`], except that rather
+    /// than a directory on the host, the root directory of `fs` is made
+    /// available as `guest_path`. This can be used, for example, to give each
+    /// guest its own [`MemoryFilesystem`](crate::MemoryFilesystem), or an
+    /// [`OverlayFilesystem`](crate::OverlayFilesystem) over a shared image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use wasmtime_wasi::{DirPerms, FilePerms, MemoryFilesystem, OverlayFilesystem, WasiCtxBuilder};
+    ///
+    /// # fn main() {}
+    /// # fn foo(image: Vec<u8>) -> wasmtime::Result<()> {
+    /// // Shared between all guests.
+    /// let image = Arc::new(MemoryFilesystem::from_tar(image)?);
+    ///
+    /// // Each guest gets its own writable view of the image, as well as
+    /// // read-only access to the image itself.
+    /// let mut wasi = WasiCtxBuilder::new();
+    /// wasi.preopened_virtual_dir(
+    ///     Arc::new(OverlayFilesystem::new(image.clone())),
+    ///     "/",
+    ///     DirPerms::all(),
+    ///     FilePerms::all(),
+    /// );
+    /// wasi.preopened_virtual_dir(image, "/image", DirPerms::READ, FilePerms::READ);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn preopened_virtual_dir(
+        &mut self,
+        fs: Arc<dyn VirtualFilesystem>,
+        guest_path: impl AsRef<str>,
+        dir_perms: DirPerms,
+        file_perms: FilePerms,
+    ) -> &mut Self {
+        self.preopens.push((
+            Descriptor::Virtual(VirtualDescriptor::new_root(
+                fs,
+                dir_perms,
+                file_perms,
+                preopen_open_mode(dir_perms),
+            )),
+            guest_path.as_ref().to_owned(),
+        ));
+        self
+    }
+
     /// Set the generator for the `wasi:random/random` number generator to the
     /// custom generator specified.
     ///
@@ -627,6 +672,18 @@ impl<T: WasiView> WasiView for WasiImpl<T> {
     }
 }
 
+/// The mode that preopened directories are reported to have been opened with.
+fn preopen_open_mode(dir_perms: DirPerms) -> OpenMode {
+    let mut open_mode = OpenMode::empty();
+    if dir_perms.contains(DirPerms::READ) {
+        open_mode |= OpenMode::READ;
+    }
+    if dir_perms.contains(DirPerms::MUTATE) {
+        open_mode |= OpenMode::WRITE;
+    }
+    open_mode
+}
+
 /// Per-[`Store`] state which holds state necessary to implement WASI from this
 /// crate.
 ///
@@ -647,7 +704,7 @@ pub struct WasiCtx {
     pub(crate) monotonic_clock: Box<dyn HostMonotonicClock + Send>,
     pub(crate) env: Vec<(String, String)>,
     pub(crate) args: Vec<String>,
-    pub(crate) preopens: Vec<(Dir, String)>,
+    pub(crate) preopens: Vec<(Descriptor, String)>,
     pub(crate) stdin: Box<dyn StdinStream>,
     pub(crate) stdout: Box<dyn StdoutStream>,
     pub(crate) stderr: Box<dyn StdoutStream>,
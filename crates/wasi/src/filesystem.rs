@@ -9,6 +9,15 @@ use std::io;
 use std::mem;
 use std::sync::Arc;
 
+mod memory;
+mod overlay;
+mod vfs;
+
+pub use self::memory::MemoryFilesystem;
+pub use self::overlay::OverlayFilesystem;
+pub use self::vfs::VirtualFilesystem;
+pub(crate) use self::vfs::VirtualDescriptor;
+
 pub type FsResult<T> = Result<T, FsError>;
 
 pub type FsError = TrappableError<types::ErrorCode>;
@@ -25,9 +34,12 @@ impl From<io::Error> for FsError {
     }
 }
 
+#[derive(Clone)]
 pub enum Descriptor {
     File(File),
     Dir(Dir),
+    /// A file or directory within a [`VirtualFilesystem`].
+    Virtual(VirtualDescriptor),
 }
 
 impl Descriptor {
@@ -35,6 +47,10 @@ impl Descriptor {
         match self {
             Descriptor::File(f) => Ok(f),
             Descriptor::Dir(_) => Err(types::ErrorCode::BadDescriptor),
+            Descriptor::Virtual(v) if v.is_dir() => Err(types::ErrorCode::BadDescriptor),
+            // Operations on virtual files are dispatched to their filesystem
+            // before needing a host file.
+            Descriptor::Virtual(_) => Err(types::ErrorCode::Unsupported),
         }
     }
 
@@ -42,6 +58,8 @@ impl Descriptor {
         match self {
             Descriptor::Dir(d) => Ok(d),
             Descriptor::File(_) => Err(types::ErrorCode::NotDirectory),
+            Descriptor::Virtual(v) if !v.is_dir() => Err(types::ErrorCode::NotDirectory),
+            Descriptor::Virtual(_) => Err(types::ErrorCode::CrossDevice),
         }
     }
 
@@ -49,6 +67,7 @@ impl Descriptor {
         match self {
             Descriptor::File(_) => true,
             Descriptor::Dir(_) => false,
+            Descriptor::Virtual(v) => !v.is_dir(),
         }
     }
 
@@ -56,6 +75,16 @@ impl Descriptor {
         match self {
             Descriptor::File(_) => false,
             Descriptor::Dir(_) => true,
+            Descriptor::Virtual(v) => v.is_dir(),
+        }
+    }
+
+    /// Returns a value identifying the object a virtual descriptor refers to,
+    /// or `None` for host files and directories.
+    pub(crate) fn virtual_identity(&self) -> Option<(usize, u64)> {
+        match self {
+            Descriptor::Virtual(v) => Some(v.identity()),
+            Descriptor::File(_) | Descriptor::Dir(_) => None,
         }
     }
 }
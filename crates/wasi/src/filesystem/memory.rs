@@ -0,0 +1,1006 @@
+use crate::bindings::clocks::wall_clock::Datetime;
+use crate::bindings::filesystem::types::{
+    DescriptorStat, DescriptorType, DirectoryEntry, ErrorCode,
+};
+use crate::filesystem::vfs::now;
+use crate::{FsResult, VirtualFilesystem};
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// A [`VirtualFilesystem`] whose contents are held entirely in memory.
+///
+/// The filesystem starts out empty, or may be populated from a tar or zip
+/// archive with [`MemoryFilesystem::from_tar`] or
+/// [`MemoryFilesystem::from_zip`]. The contents of uncompressed files loaded
+/// from an archive share the archive's buffer until they are first written to.
+///
+/// To share a single read-only image between many guests, preopen it with
+/// read-only [`DirPerms`](crate::DirPerms) and
+/// [`FilePerms`](crate::FilePerms), or wrap it in an
+/// [`OverlayFilesystem`](crate::OverlayFilesystem) to give each guest its own
+/// writable view.
+pub struct MemoryFilesystem {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    nodes: HashMap<u64, Node>,
+    next_id: u64,
+}
+
+struct Node {
+    kind: NodeKind,
+    /// The number of directory entries referring to this node.
+    link_count: u64,
+    atim: Datetime,
+    mtim: Datetime,
+}
+
+enum NodeKind {
+    File(Contents),
+    Dir(BTreeMap<String, u64>),
+}
+
+/// The contents of a file, which are shared with the buffer they were loaded
+/// from until they are first modified.
+enum Contents {
+    Shared(Bytes),
+    Owned(Vec<u8>),
+}
+
+impl Contents {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Contents::Shared(b) => b,
+            Contents::Owned(v) => v,
+        }
+    }
+
+    fn to_mut(&mut self) -> &mut Vec<u8> {
+        if let Contents::Shared(b) = self {
+            *self = Contents::Owned(b.to_vec());
+        }
+        match self {
+            Contents::Owned(v) => v,
+            Contents::Shared(_) => unreachable!(),
+        }
+    }
+}
+
+const ROOT: u64 = 1;
+
+impl Default for MemoryFilesystem {
+    fn default() -> Self {
+        MemoryFilesystem::new()
+    }
+}
+
+impl MemoryFilesystem {
+    /// Creates a new filesystem containing only an empty root directory.
+    pub fn new() -> MemoryFilesystem {
+        let mut nodes = HashMap::new();
+        nodes.insert(ROOT, Node::new(NodeKind::Dir(BTreeMap::new())));
+        MemoryFilesystem {
+            inner: Mutex::new(Inner {
+                nodes,
+                next_id: ROOT + 1,
+            }),
+        }
+    }
+
+    /// Creates a new filesystem populated with the contents of the tar
+    /// archive in `archive`.
+    ///
+    /// Regular files and directories are extracted, along with any missing
+    /// parent directories. Other kinds of entries, such as links and device
+    /// files, are skipped. Both the ustar and GNU long-name formats are
+    /// supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `archive` is not a well-formed tar archive.
+    pub fn from_tar(archive: impl Into<Bytes>) -> anyhow::Result<MemoryFilesystem> {
+        let fs = MemoryFilesystem::new();
+        let archive = archive.into();
+        let mut inner = fs.inner.lock().unwrap();
+        let mut offset = 0;
+        let mut long_name = None;
+        while offset + 512 <= archive.len() {
+            let header = &archive[offset..offset + 512];
+            if header.iter().all(|b| *b == 0) {
+                break;
+            }
+            let size = usize::try_from(tar_octal(&header[124..136])?)?;
+            let data_start = offset + 512;
+            let data_end = data_start
+                .checked_add(size)
+                .filter(|end| *end <= archive.len())
+                .ok_or_else(|| anyhow::anyhow!("truncated tar entry"))?;
+            offset = data_start + size.div_ceil(512) * 512;
+
+            let name = match long_name.take() {
+                Some(name) => name,
+                None => {
+                    let mut name = tar_str(&header[0..100])?.to_string();
+                    if &header[257..262] == b"ustar" {
+                        let prefix = tar_str(&header[345..500])?;
+                        if !prefix.is_empty() {
+                            name = format!("{prefix}/{name}");
+                        }
+                    }
+                    name
+                }
+            };
+            match header[156] {
+                b'0' | 0 | b'7' => {
+                    inner.add_file(
+                        &name,
+                        Contents::Shared(archive.slice(data_start..data_end)),
+                        tar_octal(&header[136..148])?,
+                    )?;
+                }
+                b'5' => inner.add_dir(&name)?,
+                b'L' => {
+                    long_name = Some(tar_str(&archive[data_start..data_end])?.to_string());
+                }
+                _ => {}
+            }
+        }
+        drop(inner);
+        Ok(fs)
+    }
+
+    /// Creates a new filesystem populated with the contents of the zip
+    /// archive in `archive`.
+    ///
+    /// Files and directories are extracted from the archive's central
+    /// directory, along with any missing parent directories, and symbolic
+    /// links are skipped. Entries may be stored or compressed with deflate,
+    /// and are checked against their CRC-32. Compressed files are
+    /// decompressed up front, so only stored files share the archive's
+    /// buffer. Zip64 and encrypted archives are not supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `archive` is not a well-formed zip archive, or uses
+    /// an unsupported feature.
+    pub fn from_zip(archive: impl Into<Bytes>) -> anyhow::Result<MemoryFilesystem> {
+        let fs = MemoryFilesystem::new();
+        let archive = archive.into();
+        let mut inner = fs.inner.lock().unwrap();
+
+        // The end of central directory record is at least 22 bytes long, and
+        // ends with a comment of up to 65535 bytes.
+        let eocd = (0..=archive.len().saturating_sub(22))
+            .rev()
+            .take(65536)
+            .find(|&i| archive[i..].starts_with(b"PK\x05\x06"))
+            .ok_or_else(|| anyhow::anyhow!("zip archive has no end of central directory"))?;
+        let count = zip_u16(&archive, eocd + 10)?;
+        let mut offset = zip_u32(&archive, eocd + 16)?;
+        if count == usize::from(u16::MAX) || offset == u32::MAX as usize {
+            anyhow::bail!("zip64 archives are not supported");
+        }
+
+        for _ in 0..count {
+            let header = archive
+                .get(offset..offset + 46)
+                .filter(|h| h.starts_with(b"PK\x01\x02"))
+                .ok_or_else(|| anyhow::anyhow!("invalid zip central directory"))?;
+            let flags = zip_u16(header, 8)?;
+            let method = zip_u16(header, 10)?;
+            let time = zip_u16(header, 12)?;
+            let date = zip_u16(header, 14)?;
+            let crc = zip_u32(header, 16)? as u32;
+            let compressed_size = zip_u32(header, 20)?;
+            let size = zip_u32(header, 24)?;
+            let name_len = zip_u16(header, 28)?;
+            let extra_len = zip_u16(header, 30)?;
+            let comment_len = zip_u16(header, 32)?;
+            let made_by_unix = header[5] == 3;
+            let mode = zip_u32(header, 38)? >> 16;
+            let local = zip_u32(header, 42)?;
+            let name = archive
+                .get(offset + 46..offset + 46 + name_len)
+                .ok_or_else(|| anyhow::anyhow!("invalid zip central directory"))?;
+            let name = std::str::from_utf8(name)
+                .map_err(|_| anyhow::anyhow!("zip entry name is not UTF-8"))?;
+            offset += 46 + name_len + extra_len + comment_len;
+
+            if flags & 1 != 0 {
+                anyhow::bail!("zip entry `{name}` is encrypted");
+            }
+            if name.ends_with('/') {
+                inner.add_dir(name)?;
+                continue;
+            }
+            if made_by_unix && mode & 0o170000 == 0o120000 {
+                continue;
+            }
+
+            let local_header = archive
+                .get(local..local + 30)
+                .filter(|h| h.starts_with(b"PK\x03\x04"))
+                .ok_or_else(|| anyhow::anyhow!("invalid local header for zip entry `{name}`"))?;
+            let data_start = local + 30 + zip_u16(local_header, 26)? + zip_u16(local_header, 28)?;
+            let data_end = data_start
+                .checked_add(compressed_size)
+                .filter(|end| *end <= archive.len())
+                .ok_or_else(|| anyhow::anyhow!("truncated zip entry `{name}`"))?;
+            let contents = match method {
+                0 => Contents::Shared(archive.slice(data_start..data_end)),
+                8 => Contents::Owned(
+                    miniz_oxide::inflate::decompress_to_vec_with_limit(
+                        &archive[data_start..data_end],
+                        size,
+                    )
+                    .map_err(|_| anyhow::anyhow!("failed to decompress zip entry `{name}`"))?,
+                ),
+                _ => {
+                    anyhow::bail!("zip entry `{name}` uses unsupported compression method {method}")
+                }
+            };
+            let data = contents.as_slice();
+            if data.len() != size || crc32(data) != crc {
+                anyhow::bail!("zip entry `{name}` is corrupt");
+            }
+            inner.add_file(name, contents, dos_time_to_unix(date, time))?;
+        }
+        drop(inner);
+        Ok(fs)
+    }
+
+    fn with_node<R>(&self, id: u64, f: impl FnOnce(&mut Node) -> FsResult<R>) -> FsResult<R> {
+        let mut inner = self.inner.lock().unwrap();
+        let node = inner.nodes.get_mut(&id).ok_or(ErrorCode::BadDescriptor)?;
+        f(node)
+    }
+}
+
+impl Node {
+    fn new(kind: NodeKind) -> Node {
+        let now = now();
+        Node {
+            kind,
+            link_count: 0,
+            atim: now,
+            mtim: now,
+        }
+    }
+
+    fn file(&mut self) -> Result<&mut Contents, ErrorCode> {
+        match &mut self.kind {
+            NodeKind::File(contents) => Ok(contents),
+            NodeKind::Dir(_) => Err(ErrorCode::IsDirectory),
+        }
+    }
+
+    fn descriptor_type(&self) -> DescriptorType {
+        match self.kind {
+            NodeKind::File(_) => DescriptorType::RegularFile,
+            NodeKind::Dir(_) => DescriptorType::Directory,
+        }
+    }
+}
+
+impl Inner {
+    fn dir(&self, id: u64) -> Result<&BTreeMap<String, u64>, ErrorCode> {
+        match &self.nodes.get(&id).ok_or(ErrorCode::BadDescriptor)?.kind {
+            NodeKind::Dir(entries) => Ok(entries),
+            NodeKind::File(_) => Err(ErrorCode::NotDirectory),
+        }
+    }
+
+    fn dir_mut(&mut self, id: u64) -> Result<&mut BTreeMap<String, u64>, ErrorCode> {
+        let node = self.nodes.get_mut(&id).ok_or(ErrorCode::BadDescriptor)?;
+        node.mtim = now();
+        match &mut node.kind {
+            NodeKind::Dir(entries) => Ok(entries),
+            NodeKind::File(_) => Err(ErrorCode::NotDirectory),
+        }
+    }
+
+    fn child(&self, dir: u64, name: &str) -> Result<u64, ErrorCode> {
+        self.dir(dir)?.get(name).copied().ok_or(ErrorCode::NoEntry)
+    }
+
+    fn is_dir(&self, id: u64) -> bool {
+        matches!(self.nodes.get(&id).map(|n| &n.kind), Some(NodeKind::Dir(_)))
+    }
+
+    /// Adds a new node called `name` in `dir`, which must not already exist.
+    fn insert(&mut self, dir: u64, name: &str, kind: NodeKind) -> Result<u64, ErrorCode> {
+        if self.dir(dir)?.contains_key(name) {
+            return Err(ErrorCode::Exist);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut node = Node::new(kind);
+        node.link_count = 1;
+        self.nodes.insert(id, node);
+        self.dir_mut(dir)?.insert(name.to_string(), id);
+        Ok(id)
+    }
+
+    /// Removes the entry `name` from `dir`, freeing its node once nothing
+    /// refers to it.
+    fn unlink(&mut self, dir: u64, name: &str) -> Result<(), ErrorCode> {
+        let id = self.dir_mut(dir)?.remove(name).ok_or(ErrorCode::NoEntry)?;
+        let node = self.nodes.get_mut(&id).unwrap();
+        node.link_count -= 1;
+        if node.link_count == 0 {
+            self.nodes.remove(&id);
+        }
+        Ok(())
+    }
+
+    /// Adds the file at archive path `path`, modified at `mtime` seconds
+    /// since the Unix epoch, creating its parent directories.
+    fn add_file(&mut self, path: &str, contents: Contents, mtime: u64) -> anyhow::Result<()> {
+        let (dir, file) = self.make_parents(path)?;
+        if file.is_empty() {
+            anyhow::bail!("archive entry `{path}` has an empty file name");
+        }
+        let id = self.insert(dir, file, NodeKind::File(contents))?;
+        self.nodes.get_mut(&id).unwrap().mtim = Datetime {
+            seconds: mtime,
+            nanoseconds: 0,
+        };
+        Ok(())
+    }
+
+    /// Adds the directory at archive path `path`, if it doesn't already
+    /// exist, along with its parent directories.
+    fn add_dir(&mut self, path: &str) -> anyhow::Result<()> {
+        let (dir, name) = self.make_parents(path)?;
+        if !name.is_empty() && self.child(dir, name).is_err() {
+            self.insert(dir, name, NodeKind::Dir(BTreeMap::new()))?;
+        }
+        Ok(())
+    }
+
+    /// Creates all of the parent directories of the archive path `path`,
+    /// returning the innermost one and the final component of the path.
+    fn make_parents<'a>(&mut self, path: &'a str) -> anyhow::Result<(u64, &'a str)> {
+        let mut components = path
+            .split('/')
+            .filter(|c| !c.is_empty() && *c != ".")
+            .collect::<Vec<_>>();
+        if components.contains(&"..") {
+            anyhow::bail!("archive entry `{path}` contains `..`");
+        }
+        let name = components.pop().unwrap_or("");
+        let mut dir = ROOT;
+        for component in components {
+            dir = match self.child(dir, component) {
+                Ok(id) if self.is_dir(id) => id,
+                Ok(_) => anyhow::bail!("archive entry `{path}` is beneath a file"),
+                Err(_) => self.insert(dir, component, NodeKind::Dir(BTreeMap::new()))?,
+            };
+        }
+        Ok((dir, name))
+    }
+}
+
+impl VirtualFilesystem for MemoryFilesystem {
+    fn root(&self) -> u64 {
+        ROOT
+    }
+
+    fn lookup(&self, dir: u64, name: &str) -> FsResult<u64> {
+        Ok(self.inner.lock().unwrap().child(dir, name)?)
+    }
+
+    fn stat(&self, node: u64) -> FsResult<DescriptorStat> {
+        self.with_node(node, |node| {
+            let size = match &node.kind {
+                NodeKind::File(contents) => contents.as_slice().len() as u64,
+                NodeKind::Dir(entries) => entries.len() as u64,
+            };
+            Ok(DescriptorStat {
+                type_: node.descriptor_type(),
+                link_count: node.link_count,
+                size,
+                data_access_timestamp: Some(node.atim),
+                data_modification_timestamp: Some(node.mtim),
+                status_change_timestamp: Some(node.mtim),
+            })
+        })
+    }
+
+    fn read_dir(&self, dir: u64) -> FsResult<Vec<DirectoryEntry>> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner
+            .dir(dir)?
+            .iter()
+            .map(|(name, id)| DirectoryEntry {
+                type_: inner.nodes[id].descriptor_type(),
+                name: name.clone(),
+            })
+            .collect())
+    }
+
+    fn read(&self, file: u64, offset: u64, len: u64) -> FsResult<Bytes> {
+        self.with_node(file, |node| {
+            node.atim = now();
+            let contents = node.file()?;
+            let start = usize::try_from(offset)
+                .unwrap_or(usize::MAX)
+                .min(contents.as_slice().len());
+            let end = start.saturating_add(usize::try_from(len).unwrap_or(usize::MAX));
+            let end = end.min(contents.as_slice().len());
+            Ok(match contents {
+                Contents::Shared(b) => b.slice(start..end),
+                Contents::Owned(v) => Bytes::copy_from_slice(&v[start..end]),
+            })
+        })
+    }
+
+    fn write(&self, file: u64, offset: u64, buf: &[u8]) -> FsResult<u64> {
+        self.with_node(file, |node| {
+            node.mtim = now();
+            let contents = node.file()?.to_mut();
+            let start = usize::try_from(offset).map_err(|_| ErrorCode::FileTooLarge)?;
+            let end = start
+                .checked_add(buf.len())
+                .ok_or(ErrorCode::FileTooLarge)?;
+            if contents.len() < end {
+                contents.resize(end, 0);
+            }
+            contents[start..end].copy_from_slice(buf);
+            Ok(buf.len() as u64)
+        })
+    }
+
+    fn set_size(&self, file: u64, size: u64) -> FsResult<()> {
+        self.with_node(file, |node| {
+            node.mtim = now();
+            let size = usize::try_from(size).map_err(|_| ErrorCode::FileTooLarge)?;
+            node.file()?.to_mut().resize(size, 0);
+            Ok(())
+        })
+    }
+
+    fn set_times(&self, node: u64, atim: Option<Datetime>, mtim: Option<Datetime>) -> FsResult<()> {
+        self.with_node(node, |node| {
+            if let Some(atim) = atim {
+                node.atim = atim;
+            }
+            if let Some(mtim) = mtim {
+                node.mtim = mtim;
+            }
+            Ok(())
+        })
+    }
+
+    fn create_file(&self, dir: u64, name: &str, exclusive: bool) -> FsResult<u64> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.child(dir, name) {
+            Ok(_) if exclusive => Err(ErrorCode::Exist.into()),
+            Ok(id) if inner.is_dir(id) => Err(ErrorCode::IsDirectory.into()),
+            Ok(id) => Ok(id),
+            Err(ErrorCode::NoEntry) => {
+                Ok(inner.insert(dir, name, NodeKind::File(Contents::Owned(Vec::new())))?)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn create_dir(&self, dir: u64, name: &str) -> FsResult<()> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.insert(dir, name, NodeKind::Dir(BTreeMap::new()))?;
+        Ok(())
+    }
+
+    fn remove_file(&self, dir: u64, name: &str) -> FsResult<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.child(dir, name)?;
+        if inner.is_dir(id) {
+            return Err(ErrorCode::IsDirectory.into());
+        }
+        Ok(inner.unlink(dir, name)?)
+    }
+
+    fn remove_dir(&self, dir: u64, name: &str) -> FsResult<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.child(dir, name)?;
+        if !inner.dir(id)?.is_empty() {
+            return Err(ErrorCode::NotEmpty.into());
+        }
+        Ok(inner.unlink(dir, name)?)
+    }
+
+    fn rename(&self, old_dir: u64, old_name: &str, new_dir: u64, new_name: &str) -> FsResult<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.child(old_dir, old_name)?;
+        if old_dir == new_dir && old_name == new_name {
+            return Ok(());
+        }
+        if inner.is_dir(id) {
+            // Refuse to move a directory beneath itself.
+            let mut ancestors = vec![new_dir];
+            while let Some(ancestor) = ancestors.pop() {
+                if ancestor == id {
+                    return Err(ErrorCode::Invalid.into());
+                }
+                ancestors.extend(inner.nodes.iter().filter_map(
+                    |(parent, node)| match &node.kind {
+                        NodeKind::Dir(entries) if entries.values().any(|c| *c == ancestor) => {
+                            Some(*parent)
+                        }
+                        _ => None,
+                    },
+                ));
+            }
+        }
+        match inner.child(new_dir, new_name) {
+            Ok(existing) => match (inner.is_dir(id), inner.is_dir(existing)) {
+                (false, true) => return Err(ErrorCode::IsDirectory.into()),
+                (true, false) => return Err(ErrorCode::NotDirectory.into()),
+                (true, true) if !inner.dir(existing)?.is_empty() => {
+                    return Err(ErrorCode::NotEmpty.into())
+                }
+                _ => inner.unlink(new_dir, new_name)?,
+            },
+            Err(ErrorCode::NoEntry) => {}
+            Err(e) => return Err(e.into()),
+        }
+        inner.dir_mut(old_dir)?.remove(old_name);
+        inner.dir_mut(new_dir)?.insert(new_name.to_string(), id);
+        Ok(())
+    }
+}
+
+/// Parses a NUL- or space-terminated octal number from a tar header.
+fn tar_octal(field: &[u8]) -> anyhow::Result<u64> {
+    let s = tar_str(field)?.trim();
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, 8).map_err(|_| anyhow::anyhow!("invalid number in tar header: {s:?}"))
+}
+
+/// Parses a NUL-terminated string from a tar header.
+fn tar_str(field: &[u8]) -> anyhow::Result<&str> {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    std::str::from_utf8(&field[..end]).map_err(|_| anyhow::anyhow!("tar entry name is not UTF-8"))
+}
+
+/// Reads a little-endian `u16` at `offset` in a zip header.
+fn zip_u16(data: &[u8], offset: usize) -> anyhow::Result<usize> {
+    data.get(offset..offset + 2)
+        .map(|b| usize::from(u16::from_le_bytes(b.try_into().unwrap())))
+        .ok_or_else(|| anyhow::anyhow!("truncated zip header"))
+}
+
+/// Reads a little-endian `u32` at `offset` in a zip header.
+fn zip_u32(data: &[u8], offset: usize) -> anyhow::Result<usize> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize)
+        .ok_or_else(|| anyhow::anyhow!("truncated zip header"))
+}
+
+/// The CRC-32 of `data`, as used by zip archives.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// Converts an MS-DOS date and time, as stored in zip headers, to seconds
+/// since the Unix epoch, treating it as UTC.
+fn dos_time_to_unix(date: usize, time: usize) -> u64 {
+    let year = 1980 + (date >> 9) as i64;
+    let month = ((date >> 5) & 0xf).clamp(1, 12) as i64;
+    let day = (date & 0x1f).max(1) as i64;
+
+    // Days since the epoch of the proleptic Gregorian date, from
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let secs = (time >> 11) * 3600 + ((time >> 5) & 0x3f) * 60 + (time & 0x1f) * 2;
+    days as u64 * 86400 + secs as u64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tar_entry(archive: &mut Vec<u8>, name: &str, kind: u8, data: &[u8]) {
+        let mut header = [0; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        let size = format!("{:011o}", data.len());
+        header[124..135].copy_from_slice(size.as_bytes());
+        header[136..147].copy_from_slice(b"00000000144");
+        header[156] = kind;
+        header[257..262].copy_from_slice(b"ustar");
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(data);
+        archive.resize(archive.len().div_ceil(512) * 512, 0);
+    }
+
+    /// A zip entry's name, contents, compression method, and Unix mode.
+    type ZipEntry<'a> = (&'a str, &'a [u8], u16, u32);
+
+    /// Builds a zip archive of `entries`, all modified at 2000-01-02 03:04:06.
+    fn zip_archive(entries: &[ZipEntry<'_>]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        let mut central = Vec::new();
+        for &(name, data, method, mode) in entries {
+            let compressed = match method {
+                8 => miniz_oxide::deflate::compress_to_vec(data, 6),
+                _ => data.to_vec(),
+            };
+            let mut fields = Vec::new();
+            fields.extend_from_slice(&20u16.to_le_bytes());
+            fields.extend_from_slice(&0u16.to_le_bytes());
+            fields.extend_from_slice(&method.to_le_bytes());
+            fields.extend_from_slice(&0x1883u16.to_le_bytes());
+            fields.extend_from_slice(&0x2822u16.to_le_bytes());
+            fields.extend_from_slice(&crc32(data).to_le_bytes());
+            fields.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            fields.extend_from_slice(&0u16.to_le_bytes());
+
+            central.extend_from_slice(b"PK\x01\x02");
+            central.extend_from_slice(&[20, 3]);
+            central.extend_from_slice(&fields);
+            central.extend_from_slice(&[0; 6]);
+            central.extend_from_slice(&(mode << 16).to_le_bytes());
+            central.extend_from_slice(&(archive.len() as u32).to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+
+            archive.extend_from_slice(b"PK\x03\x04");
+            archive.extend_from_slice(&fields);
+            archive.extend_from_slice(name.as_bytes());
+            archive.extend_from_slice(&compressed);
+        }
+        let central_offset = archive.len() as u32;
+        archive.extend_from_slice(&central);
+        archive.extend_from_slice(b"PK\x05\x06");
+        archive.extend_from_slice(&[0; 4]);
+        archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&central_offset.to_le_bytes());
+        archive.extend_from_slice(&[0; 2]);
+        archive
+    }
+
+    fn read_all(fs: &MemoryFilesystem, file: u64) -> Vec<u8> {
+        fs.read(file, 0, u64::MAX).unwrap().to_vec()
+    }
+
+    #[test]
+    fn from_tar() {
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "etc/", b'5', &[]);
+        tar_entry(&mut archive, "etc/hosts", b'0', b"127.0.0.1 localhost\n");
+        tar_entry(&mut archive, "usr/share/doc/README", b'0', b"hello");
+        tar_entry(&mut archive, "etc/hostname", b'2', &[]);
+        tar_entry(&mut archive, "././@LongLink", b'L', b"a/long/name\0");
+        tar_entry(&mut archive, "truncated", b'0', b"long");
+        archive.extend_from_slice(&[0; 1024]);
+
+        let fs = MemoryFilesystem::from_tar(archive).unwrap();
+        let etc = fs.lookup(fs.root(), "etc").unwrap();
+        let hosts = fs.lookup(etc, "hosts").unwrap();
+        assert_eq!(read_all(&fs, hosts), b"127.0.0.1 localhost\n");
+        assert_eq!(fs.stat(hosts).unwrap().size, 20);
+        assert_eq!(
+            fs.stat(hosts)
+                .unwrap()
+                .data_modification_timestamp
+                .unwrap()
+                .seconds,
+            100
+        );
+        assert_eq!(
+            fs.lookup(etc, "hostname").unwrap_err().downcast().unwrap(),
+            ErrorCode::NoEntry
+        );
+
+        let usr = fs.lookup(fs.root(), "usr").unwrap();
+        let share = fs.lookup(usr, "share").unwrap();
+        let doc = fs.lookup(share, "doc").unwrap();
+        let readme = fs.lookup(doc, "README").unwrap();
+        assert_eq!(read_all(&fs, readme), b"hello");
+
+        let a = fs.lookup(fs.root(), "a").unwrap();
+        let long = fs.lookup(a, "long").unwrap();
+        let name = fs.lookup(long, "name").unwrap();
+        assert_eq!(read_all(&fs, name), b"long");
+
+        let names = fs
+            .read_dir(fs.root())
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a", "etc", "usr"]);
+    }
+
+    #[test]
+    fn from_tar_long_names() {
+        let mut archive = Vec::new();
+
+        // ustar splits long names into a prefix and a name.
+        let start = archive.len();
+        tar_entry(&mut archive, "file", b'0', b"ustar");
+        archive[start + 345..][..9].copy_from_slice(b"some/deep");
+
+        // GNU tar stores long names in a preceding `L` entry, truncating the
+        // name in the header itself.
+        let dir = "d".repeat(150);
+        let file = format!("{dir}/{}", "f".repeat(150));
+        tar_entry(
+            &mut archive,
+            "././@LongLink",
+            b'L',
+            format!("{dir}\0").as_bytes(),
+        );
+        tar_entry(&mut archive, &dir[..100], b'5', &[]);
+        tar_entry(
+            &mut archive,
+            "././@LongLink",
+            b'L',
+            format!("{file}\0").as_bytes(),
+        );
+        tar_entry(&mut archive, &file[..100], b'0', b"gnu");
+
+        // The long name only applies to the next entry.
+        tar_entry(&mut archive, "short", b'0', b"short");
+
+        let fs = MemoryFilesystem::from_tar(archive).unwrap();
+        let root = fs.root();
+        let some = fs.lookup(root, "some").unwrap();
+        let deep = fs.lookup(some, "deep").unwrap();
+        let file_id = fs.lookup(deep, "file").unwrap();
+        assert_eq!(read_all(&fs, file_id), b"ustar");
+
+        let d = fs.lookup(root, &dir).unwrap();
+        let f = fs.lookup(d, &"f".repeat(150)).unwrap();
+        assert_eq!(read_all(&fs, f), b"gnu");
+
+        let short = fs.lookup(root, "short").unwrap();
+        assert_eq!(read_all(&fs, short), b"short");
+
+        let names = fs
+            .read_dir(root)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, [dir.as_str(), "short", "some"]);
+    }
+
+    #[test]
+    fn from_tar_stays_within_root() {
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "/etc/passwd", b'0', b"root");
+        tar_entry(&mut archive, "./a/./b", b'0', b"b");
+        let fs = MemoryFilesystem::from_tar(archive).unwrap();
+        let etc = fs.lookup(fs.root(), "etc").unwrap();
+        let passwd = fs.lookup(etc, "passwd").unwrap();
+        assert_eq!(read_all(&fs, passwd), b"root");
+        let a = fs.lookup(fs.root(), "a").unwrap();
+        assert_eq!(read_all(&fs, fs.lookup(a, "b").unwrap()), b"b");
+
+        // Archives without an end-of-archive marker, or which are empty, are
+        // accepted.
+        let fs = MemoryFilesystem::from_tar(Vec::new()).unwrap();
+        assert!(fs.read_dir(fs.root()).unwrap().is_empty());
+        let fs = MemoryFilesystem::from_tar(vec![0; 100]).unwrap();
+        assert!(fs.read_dir(fs.root()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn from_tar_rejects_malformed() {
+        #[track_caller]
+        fn assert_rejected(archive: Vec<u8>, message: &str) {
+            let err = match MemoryFilesystem::from_tar(archive) {
+                Ok(_) => panic!("expected an error containing `{message}`"),
+                Err(e) => e.to_string(),
+            };
+            assert!(err.contains(message), "`{err}` doesn't contain `{message}`");
+        }
+
+        for name in ["../escape", "a/../../escape", "a/.."] {
+            let mut archive = Vec::new();
+            tar_entry(&mut archive, name, b'0', b"x");
+            assert_rejected(archive, "contains `..`");
+        }
+
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "file", b'0', &[0; 1000]);
+        archive.truncate(600);
+        assert_rejected(archive, "truncated tar entry");
+
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "././@LongLink", b'L', &[b'x'; 1000]);
+        archive.truncate(1000);
+        assert_rejected(archive, "truncated tar entry");
+
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "file", b'0', b"x");
+        archive[124..135].copy_from_slice(b"0000000009x");
+        assert_rejected(archive, "invalid number in tar header");
+
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "file", b'0', b"x");
+        archive[0] = 0xff;
+        assert_rejected(archive, "not UTF-8");
+
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "./", b'0', b"x");
+        assert_rejected(archive, "empty file name");
+
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "file", b'0', b"x");
+        tar_entry(&mut archive, "file/child", b'0', b"x");
+        assert_rejected(archive, "beneath a file");
+
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "dir/", b'5', &[]);
+        tar_entry(&mut archive, "dir", b'0', b"x");
+        assert!(MemoryFilesystem::from_tar(archive).is_err());
+    }
+
+    #[test]
+    fn from_zip() {
+        let text = b"compressible ".repeat(100);
+        let archive = zip_archive(&[
+            ("etc/", b"", 0, 0o40755),
+            ("etc/hosts", b"127.0.0.1 localhost\n", 0, 0o100644),
+            ("usr/share/doc/README", &text, 8, 0o100644),
+            ("etc/hostname", b"hosts", 0, 0o120777),
+            ("empty", b"", 8, 0o100644),
+        ]);
+
+        let fs = MemoryFilesystem::from_zip(archive).unwrap();
+        let etc = fs.lookup(fs.root(), "etc").unwrap();
+        let hosts = fs.lookup(etc, "hosts").unwrap();
+        assert_eq!(read_all(&fs, hosts), b"127.0.0.1 localhost\n");
+        assert_eq!(
+            fs.stat(hosts)
+                .unwrap()
+                .data_modification_timestamp
+                .unwrap()
+                .seconds,
+            946_782_246
+        );
+        assert_eq!(
+            fs.lookup(etc, "hostname").unwrap_err().downcast().unwrap(),
+            ErrorCode::NoEntry
+        );
+
+        let usr = fs.lookup(fs.root(), "usr").unwrap();
+        let share = fs.lookup(usr, "share").unwrap();
+        let doc = fs.lookup(share, "doc").unwrap();
+        let readme = fs.lookup(doc, "README").unwrap();
+        assert_eq!(read_all(&fs, readme), text);
+        let empty = fs.lookup(fs.root(), "empty").unwrap();
+        assert_eq!(read_all(&fs, empty), b"");
+
+        let names = fs
+            .read_dir(fs.root())
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["empty", "etc", "usr"]);
+
+        // A comment may follow the end of central directory record.
+        let mut archive = zip_archive(&[("file", b"x", 0, 0o100644)]);
+        let len = archive.len();
+        archive[len - 2..].copy_from_slice(&4u16.to_le_bytes());
+        archive.extend_from_slice(b"PK\x05\x06");
+        let fs = MemoryFilesystem::from_zip(archive).unwrap();
+        let file = fs.lookup(fs.root(), "file").unwrap();
+        assert_eq!(read_all(&fs, file), b"x");
+    }
+
+    #[test]
+    fn from_zip_rejects_malformed() {
+        #[track_caller]
+        fn assert_rejected(archive: Vec<u8>, message: &str) {
+            let err = match MemoryFilesystem::from_zip(archive) {
+                Ok(_) => panic!("expected an error containing `{message}`"),
+                Err(e) => e.to_string(),
+            };
+            assert!(err.contains(message), "`{err}` doesn't contain `{message}`");
+        }
+
+        assert_rejected(Vec::new(), "no end of central directory");
+        assert_rejected(
+            b"not a zip archive".repeat(10),
+            "no end of central directory",
+        );
+
+        for name in ["../escape", "a/../../escape"] {
+            assert_rejected(zip_archive(&[(name, b"x", 0, 0o100644)]), "contains `..`");
+        }
+
+        let archive = zip_archive(&[("file", b"x", 0, 0o100644)]);
+        let mut truncated = archive.clone();
+        truncated.drain(10..20);
+        assert_rejected(truncated, "invalid zip central directory");
+
+        let mut corrupt = archive.clone();
+        corrupt[30 + 4] ^= 0xff;
+        assert_rejected(corrupt, "zip entry `file` is corrupt");
+
+        // The flags of the entry in the central directory.
+        let mut encrypted = archive.clone();
+        encrypted[archive.len() - 22 - 50 + 8] |= 1;
+        assert_rejected(encrypted, "encrypted");
+
+        let archive = zip_archive(&[("file", b"x", 12, 0o100644)]);
+        assert_rejected(archive, "unsupported compression method 12");
+
+        let mut archive = zip_archive(&[("file", &[0; 100], 8, 0o100644)]);
+        archive[34] ^= 0xff;
+        assert_rejected(archive, "zip entry `file`");
+    }
+
+    #[test]
+    fn copy_on_write() {
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "file", b'0', b"original");
+        let fs = MemoryFilesystem::from_tar(archive).unwrap();
+        let file = fs.lookup(fs.root(), "file").unwrap();
+
+        assert_eq!(fs.write(file, 4, b"-modified").unwrap(), 9);
+        assert_eq!(read_all(&fs, file), b"orig-modified");
+        fs.set_size(file, 2).unwrap();
+        assert_eq!(read_all(&fs, file), b"or");
+        assert_eq!(fs.write(file, 4, b"!").unwrap(), 1);
+        assert_eq!(read_all(&fs, file), b"or\0\0!");
+    }
+
+    #[test]
+    fn rename_and_remove() {
+        let fs = MemoryFilesystem::new();
+        let root = fs.root();
+        fs.create_dir(root, "a").unwrap();
+        let a = fs.lookup(root, "a").unwrap();
+        let file = fs.create_file(a, "file", true).unwrap();
+        assert_eq!(
+            fs.create_file(a, "file", true)
+                .unwrap_err()
+                .downcast()
+                .unwrap(),
+            ErrorCode::Exist
+        );
+        assert_eq!(fs.create_file(a, "file", false).unwrap(), file);
+
+        assert_eq!(
+            fs.rename(root, "a", a, "b")
+                .unwrap_err()
+                .downcast()
+                .unwrap(),
+            ErrorCode::Invalid
+        );
+        assert_eq!(
+            fs.remove_dir(root, "a").unwrap_err().downcast().unwrap(),
+            ErrorCode::NotEmpty
+        );
+        fs.rename(a, "file", root, "moved").unwrap();
+        assert_eq!(fs.lookup(root, "moved").unwrap(), file);
+        fs.remove_dir(root, "a").unwrap();
+        fs.remove_file(root, "moved").unwrap();
+        assert!(fs.read_dir(root).unwrap().is_empty());
+    }
+}
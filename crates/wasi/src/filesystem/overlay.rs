@@ -0,0 +1,559 @@
+use crate::bindings::clocks::wall_clock::Datetime;
+use crate::bindings::filesystem::types::{DescriptorStat, DirectoryEntry, ErrorCode};
+use crate::{FsResult, MemoryFilesystem, VirtualFilesystem};
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// A [`VirtualFilesystem`] which layers a private, writable, in-memory
+/// filesystem on top of a shared read-only base filesystem.
+///
+/// Reads are served from the base filesystem until an entry is modified, at
+/// which point it is copied into the upper layer. Entries deleted from the
+/// base are hidden rather than removed, so the base filesystem is never
+/// written to and may be shared between any number of overlays.
+///
+/// As with Linux's overlayfs, directories which exist in the base filesystem
+/// cannot be renamed; attempting to do so returns
+/// [`ErrorCode::CrossDevice`].
+pub struct OverlayFilesystem {
+    base: Arc<dyn VirtualFilesystem>,
+    upper: MemoryFilesystem,
+    state: Mutex<State>,
+}
+
+struct State {
+    nodes: HashMap<u64, Node>,
+    by_base: HashMap<u64, u64>,
+    by_upper: HashMap<u64, u64>,
+    /// Entries of the base filesystem which have been removed, keyed by the
+    /// overlay directory they were in.
+    whiteouts: HashSet<(u64, String)>,
+    next_id: u64,
+}
+
+#[derive(Clone)]
+struct Node {
+    base: Option<u64>,
+    upper: Option<u64>,
+    /// The directory containing this node and its name there, used to copy
+    /// the node into the upper layer.
+    parent: Option<(u64, String)>,
+    is_dir: bool,
+}
+
+const ROOT: u64 = 1;
+
+impl OverlayFilesystem {
+    /// Creates a new overlay on top of `base`, with an empty upper layer.
+    pub fn new(base: Arc<dyn VirtualFilesystem>) -> OverlayFilesystem {
+        let upper = MemoryFilesystem::new();
+        let root = Node {
+            base: Some(base.root()),
+            upper: Some(upper.root()),
+            parent: None,
+            is_dir: true,
+        };
+        let state = State {
+            nodes: HashMap::from([(ROOT, root)]),
+            by_base: HashMap::from([(base.root(), ROOT)]),
+            by_upper: HashMap::from([(upper.root(), ROOT)]),
+            whiteouts: HashSet::new(),
+            next_id: ROOT + 1,
+        };
+        OverlayFilesystem {
+            base,
+            upper,
+            state: Mutex::new(state),
+        }
+    }
+
+    fn lookup_locked(&self, state: &mut State, dir: u64, name: &str) -> FsResult<u64> {
+        let d = state.node(dir)?.clone();
+        if !d.is_dir {
+            return Err(ErrorCode::NotDirectory.into());
+        }
+        if let Some(u) = d.upper {
+            if let Some(u) = optional(self.upper.lookup(u, name))? {
+                return Ok(state.by_upper[&u]);
+            }
+        }
+        if state.whiteouts.contains(&(dir, name.to_string())) {
+            return Err(ErrorCode::NoEntry.into());
+        }
+        let b = match d.base {
+            Some(b) => self.base.lookup(b, name)?,
+            None => return Err(ErrorCode::NoEntry.into()),
+        };
+        if let Some(id) = state.by_base.get(&b) {
+            return Ok(*id);
+        }
+        let is_dir = is_dir(&self.base.stat(b)?);
+        Ok(state.insert(
+            Node {
+                base: Some(b),
+                upper: None,
+                parent: Some((dir, name.to_string())),
+                is_dir,
+            },
+            None,
+        ))
+    }
+
+    /// Whether the base filesystem has an entry called `name` in `dir`.
+    fn in_base(&self, state: &State, dir: u64, name: &str) -> FsResult<bool> {
+        match state.node(dir)?.base {
+            Some(b) => Ok(optional(self.base.lookup(b, name))?.is_some()),
+            None => Ok(false),
+        }
+    }
+
+    /// Copies `id` into the upper layer, along with its parent directories,
+    /// returning its node in the upper layer.
+    fn copy_up(&self, state: &mut State, id: u64) -> FsResult<u64> {
+        let node = state.node(id)?.clone();
+        if let Some(u) = node.upper {
+            return Ok(u);
+        }
+        let (parent, name) = node
+            .parent
+            .clone()
+            .expect("the root is always in the upper layer");
+        let b = node.base.expect("nodes are in at least one layer");
+        let parent_upper = self.copy_up(state, parent)?;
+        let stat = self.base.stat(b)?;
+        let u = if node.is_dir {
+            self.upper.create_dir(parent_upper, &name)?;
+            self.upper.lookup(parent_upper, &name)?
+        } else {
+            let u = self.upper.create_file(parent_upper, &name, true)?;
+            let mut offset = 0;
+            while offset < stat.size {
+                let chunk = self.base.read(b, offset, stat.size - offset)?;
+                if chunk.is_empty() {
+                    break;
+                }
+                self.upper.write(u, offset, &chunk)?;
+                offset += chunk.len() as u64;
+            }
+            u
+        };
+        self.upper.set_times(
+            u,
+            stat.data_access_timestamp,
+            stat.data_modification_timestamp,
+        )?;
+        state.nodes.get_mut(&id).unwrap().upper = Some(u);
+        state.by_upper.insert(u, id);
+        Ok(u)
+    }
+
+    /// Removes the entry `name`, which is the node `id`, from `dir`.
+    fn remove_entry(&self, state: &mut State, dir: u64, name: &str, id: u64) -> FsResult<()> {
+        let node = state.node(id)?.clone();
+        if let Some(u) = node.upper {
+            let dir_upper = state.node(dir)?.upper.unwrap();
+            if node.is_dir {
+                self.upper.remove_dir(dir_upper, name)?;
+            } else {
+                self.upper.remove_file(dir_upper, name)?;
+            }
+            state.by_upper.remove(&u);
+        }
+        if self.in_base(state, dir, name)? {
+            state.whiteouts.insert((dir, name.to_string()));
+        }
+        if let Some(b) = node.base {
+            state.by_base.remove(&b);
+        }
+        state.whiteouts.retain(|(d, _)| *d != id);
+        state.nodes.remove(&id);
+        Ok(())
+    }
+}
+
+impl State {
+    fn node(&self, id: u64) -> Result<&Node, ErrorCode> {
+        self.nodes.get(&id).ok_or(ErrorCode::BadDescriptor)
+    }
+
+    fn insert(&mut self, node: Node, upper: Option<u64>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        if let Some(b) = node.base {
+            self.by_base.insert(b, id);
+        }
+        if let Some(u) = upper {
+            self.by_upper.insert(u, id);
+        }
+        self.nodes.insert(id, node);
+        id
+    }
+}
+
+fn is_dir(stat: &DescriptorStat) -> bool {
+    stat.type_ == crate::bindings::filesystem::types::DescriptorType::Directory
+}
+
+/// Converts a `NoEntry` error into `None`.
+fn optional(result: FsResult<u64>) -> FsResult<Option<u64>> {
+    match result {
+        Ok(id) => Ok(Some(id)),
+        Err(e) => match e.downcast_ref() {
+            Some(ErrorCode::NoEntry) => Ok(None),
+            _ => Err(e),
+        },
+    }
+}
+
+impl VirtualFilesystem for OverlayFilesystem {
+    fn root(&self) -> u64 {
+        ROOT
+    }
+
+    fn lookup(&self, dir: u64, name: &str) -> FsResult<u64> {
+        let mut state = self.state.lock().unwrap();
+        self.lookup_locked(&mut state, dir, name)
+    }
+
+    fn stat(&self, node: u64) -> FsResult<DescriptorStat> {
+        let state = self.state.lock().unwrap();
+        let node = state.node(node)?;
+        match (node.upper, node.base) {
+            (Some(u), _) => self.upper.stat(u),
+            (None, Some(b)) => self.base.stat(b),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    fn read_dir(&self, dir: u64) -> FsResult<Vec<DirectoryEntry>> {
+        let state = self.state.lock().unwrap();
+        let node = state.node(dir)?;
+        if !node.is_dir {
+            return Err(ErrorCode::NotDirectory.into());
+        }
+        let mut entries = BTreeMap::new();
+        if let Some(b) = node.base {
+            for entry in self.base.read_dir(b)? {
+                if !state.whiteouts.contains(&(dir, entry.name.clone())) {
+                    entries.insert(entry.name.clone(), entry);
+                }
+            }
+        }
+        if let Some(u) = node.upper {
+            for entry in self.upper.read_dir(u)? {
+                entries.insert(entry.name.clone(), entry);
+            }
+        }
+        Ok(entries.into_values().collect())
+    }
+
+    fn read(&self, file: u64, offset: u64, len: u64) -> FsResult<Bytes> {
+        let state = self.state.lock().unwrap();
+        let node = state.node(file)?;
+        match (node.upper, node.base) {
+            (Some(u), _) => self.upper.read(u, offset, len),
+            (None, Some(b)) => self.base.read(b, offset, len),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    fn write(&self, file: u64, offset: u64, buf: &[u8]) -> FsResult<u64> {
+        let mut state = self.state.lock().unwrap();
+        let u = self.copy_up(&mut state, file)?;
+        self.upper.write(u, offset, buf)
+    }
+
+    fn set_size(&self, file: u64, size: u64) -> FsResult<()> {
+        let mut state = self.state.lock().unwrap();
+        let u = self.copy_up(&mut state, file)?;
+        self.upper.set_size(u, size)
+    }
+
+    fn set_times(&self, node: u64, atim: Option<Datetime>, mtim: Option<Datetime>) -> FsResult<()> {
+        let mut state = self.state.lock().unwrap();
+        let u = self.copy_up(&mut state, node)?;
+        self.upper.set_times(u, atim, mtim)
+    }
+
+    fn create_file(&self, dir: u64, name: &str, exclusive: bool) -> FsResult<u64> {
+        let mut state = self.state.lock().unwrap();
+        match optional(self.lookup_locked(&mut state, dir, name))? {
+            Some(_) if exclusive => return Err(ErrorCode::Exist.into()),
+            Some(id) if state.node(id)?.is_dir => return Err(ErrorCode::IsDirectory.into()),
+            Some(id) => return Ok(id),
+            None => {}
+        }
+        let dir_upper = self.copy_up(&mut state, dir)?;
+        let u = self.upper.create_file(dir_upper, name, true)?;
+        let node = Node {
+            base: None,
+            upper: Some(u),
+            parent: Some((dir, name.to_string())),
+            is_dir: false,
+        };
+        Ok(state.insert(node, Some(u)))
+    }
+
+    fn create_dir(&self, dir: u64, name: &str) -> FsResult<()> {
+        let mut state = self.state.lock().unwrap();
+        if optional(self.lookup_locked(&mut state, dir, name))?.is_some() {
+            return Err(ErrorCode::Exist.into());
+        }
+        let dir_upper = self.copy_up(&mut state, dir)?;
+        self.upper.create_dir(dir_upper, name)?;
+        let u = self.upper.lookup(dir_upper, name)?;
+        let node = Node {
+            base: None,
+            upper: Some(u),
+            parent: Some((dir, name.to_string())),
+            is_dir: true,
+        };
+        state.insert(node, Some(u));
+        Ok(())
+    }
+
+    fn remove_file(&self, dir: u64, name: &str) -> FsResult<()> {
+        let mut state = self.state.lock().unwrap();
+        let id = self.lookup_locked(&mut state, dir, name)?;
+        if state.node(id)?.is_dir {
+            return Err(ErrorCode::IsDirectory.into());
+        }
+        self.remove_entry(&mut state, dir, name, id)
+    }
+
+    fn remove_dir(&self, dir: u64, name: &str) -> FsResult<()> {
+        let id = {
+            let mut state = self.state.lock().unwrap();
+            self.lookup_locked(&mut state, dir, name)?
+        };
+        if !self.read_dir(id)?.is_empty() {
+            return Err(ErrorCode::NotEmpty.into());
+        }
+        let mut state = self.state.lock().unwrap();
+        self.remove_entry(&mut state, dir, name, id)
+    }
+
+    fn rename(&self, old_dir: u64, old_name: &str, new_dir: u64, new_name: &str) -> FsResult<()> {
+        let id = {
+            let mut state = self.state.lock().unwrap();
+            let id = self.lookup_locked(&mut state, old_dir, old_name)?;
+            let node = state.node(id)?;
+            if node.is_dir && node.base.is_some() {
+                return Err(ErrorCode::CrossDevice.into());
+            }
+            id
+        };
+        let existing = {
+            let mut state = self.state.lock().unwrap();
+            optional(self.lookup_locked(&mut state, new_dir, new_name))?
+        };
+        if let Some(existing) = existing {
+            if existing == id {
+                return Ok(());
+            }
+            let (is_dir, existing_is_dir) = {
+                let state = self.state.lock().unwrap();
+                (state.node(id)?.is_dir, state.node(existing)?.is_dir)
+            };
+            match (is_dir, existing_is_dir) {
+                (false, true) => return Err(ErrorCode::IsDirectory.into()),
+                (true, false) => return Err(ErrorCode::NotDirectory.into()),
+                (true, true) if !self.read_dir(existing)?.is_empty() => {
+                    return Err(ErrorCode::NotEmpty.into())
+                }
+                _ => {}
+            }
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(existing) = existing {
+            self.remove_entry(&mut state, new_dir, new_name, existing)?;
+        }
+        self.copy_up(&mut state, id)?;
+        let old_upper = state.node(old_dir)?.upper.unwrap();
+        let new_upper = self.copy_up(&mut state, new_dir)?;
+        self.upper
+            .rename(old_upper, old_name, new_upper, new_name)?;
+        if self.in_base(&state, old_dir, old_name)? {
+            state.whiteouts.insert((old_dir, old_name.to_string()));
+        }
+        let node = state.nodes.get_mut(&id).unwrap();
+        node.parent = Some((new_dir, new_name.to_string()));
+        if let Some(b) = node.base.take() {
+            state.by_base.remove(&b);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn base() -> Arc<MemoryFilesystem> {
+        let fs = MemoryFilesystem::new();
+        let root = fs.root();
+        fs.create_dir(root, "dir").unwrap();
+        let dir = fs.lookup(root, "dir").unwrap();
+        let file = fs.create_file(dir, "file", true).unwrap();
+        fs.write(file, 0, b"base contents").unwrap();
+        fs.create_file(root, "other", true).unwrap();
+        Arc::new(fs)
+    }
+
+    fn names(fs: &dyn VirtualFilesystem, dir: u64) -> Vec<String> {
+        fs.read_dir(dir)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect()
+    }
+
+    fn error(result: FsResult<impl std::fmt::Debug>) -> ErrorCode {
+        result.unwrap_err().downcast().unwrap()
+    }
+
+    #[test]
+    fn copy_up_leaves_base_untouched() {
+        let base = base();
+        let overlay = OverlayFilesystem::new(base.clone());
+        let dir = overlay.lookup(overlay.root(), "dir").unwrap();
+        let file = overlay.lookup(dir, "file").unwrap();
+        assert_eq!(&overlay.read(file, 0, 4).unwrap()[..], b"base");
+
+        overlay.write(file, 0, b"BASE").unwrap();
+        assert_eq!(&overlay.read(file, 0, 100).unwrap()[..], b"BASE contents");
+        assert_eq!(overlay.lookup(dir, "file").unwrap(), file);
+
+        let base_dir = base.lookup(base.root(), "dir").unwrap();
+        let base_file = base.lookup(base_dir, "file").unwrap();
+        assert_eq!(&base.read(base_file, 0, 100).unwrap()[..], b"base contents");
+
+        // A second overlay on the same base sees none of the changes.
+        let other = OverlayFilesystem::new(base);
+        let dir = other.lookup(other.root(), "dir").unwrap();
+        let file = other.lookup(dir, "file").unwrap();
+        assert_eq!(&other.read(file, 0, 100).unwrap()[..], b"base contents");
+    }
+
+    #[test]
+    fn whiteouts() {
+        let overlay = OverlayFilesystem::new(base());
+        let root = overlay.root();
+        assert_eq!(names(&overlay, root), ["dir", "other"]);
+
+        overlay.remove_file(root, "other").unwrap();
+        assert_eq!(error(overlay.lookup(root, "other")), ErrorCode::NoEntry);
+        assert_eq!(names(&overlay, root), ["dir"]);
+
+        let file = overlay.create_file(root, "other", true).unwrap();
+        assert_eq!(overlay.stat(file).unwrap().size, 0);
+        assert_eq!(names(&overlay, root), ["dir", "other"]);
+
+        assert_eq!(error(overlay.remove_dir(root, "dir")), ErrorCode::NotEmpty);
+        let dir = overlay.lookup(root, "dir").unwrap();
+        overlay.remove_file(dir, "file").unwrap();
+        assert!(names(&overlay, dir).is_empty());
+        overlay.remove_dir(root, "dir").unwrap();
+        assert_eq!(names(&overlay, root), ["other"]);
+    }
+
+    #[test]
+    fn rename() {
+        let overlay = OverlayFilesystem::new(base());
+        let root = overlay.root();
+        let dir = overlay.lookup(root, "dir").unwrap();
+        assert_eq!(
+            error(overlay.rename(root, "dir", root, "renamed")),
+            ErrorCode::CrossDevice
+        );
+
+        let file = overlay.lookup(dir, "file").unwrap();
+        overlay.rename(dir, "file", root, "moved").unwrap();
+        assert_eq!(overlay.lookup(root, "moved").unwrap(), file);
+        assert_eq!(error(overlay.lookup(dir, "file")), ErrorCode::NoEntry);
+        assert_eq!(&overlay.read(file, 0, 4).unwrap()[..], b"base");
+
+        overlay.create_dir(root, "new").unwrap();
+        overlay.rename(root, "new", dir, "nested").unwrap();
+        assert_eq!(names(&overlay, dir), ["nested"]);
+    }
+
+    #[test]
+    fn copy_up_parents() {
+        let base = base();
+        let overlay = OverlayFilesystem::new(base.clone());
+        let root = overlay.root();
+        let dir = overlay.lookup(root, "dir").unwrap();
+
+        // Creating an entry in a base directory copies the directory up, but
+        // not its other entries.
+        overlay.create_file(dir, "new", true).unwrap();
+        overlay.create_dir(dir, "sub").unwrap();
+        assert_eq!(names(&overlay, dir), ["file", "new", "sub"]);
+        let base_dir = base.lookup(base.root(), "dir").unwrap();
+        assert_eq!(names(&*base, base_dir), ["file"]);
+        let upper_dir = overlay.upper.lookup(overlay.upper.root(), "dir").unwrap();
+        assert_eq!(names(&overlay.upper, upper_dir), ["new", "sub"]);
+
+        // Truncating and setting times copy files up with their contents.
+        let file = overlay.lookup(dir, "file").unwrap();
+        overlay.set_size(file, 4).unwrap();
+        assert_eq!(&overlay.read(file, 0, 100).unwrap()[..], b"base");
+        let other = overlay.lookup(root, "other").unwrap();
+        let mtim = Datetime {
+            seconds: 42,
+            nanoseconds: 0,
+        };
+        overlay.set_times(other, None, Some(mtim)).unwrap();
+        let seconds = |stat: DescriptorStat| stat.data_modification_timestamp.unwrap().seconds;
+        assert_eq!(seconds(overlay.stat(other).unwrap()), 42);
+        let base_file = base.lookup(base_dir, "file").unwrap();
+        assert_eq!(base.stat(base_file).unwrap().size, 13);
+        let base_other = base.lookup(base.root(), "other").unwrap();
+        assert_ne!(seconds(base.stat(base_other).unwrap()), 42);
+    }
+
+    #[test]
+    fn whiteouts_hide_base_contents() {
+        let base = base();
+        let overlay = OverlayFilesystem::new(base.clone());
+        let root = overlay.root();
+        let dir = overlay.lookup(root, "dir").unwrap();
+
+        // A base directory which is removed and recreated is empty.
+        overlay.remove_file(dir, "file").unwrap();
+        overlay.remove_dir(root, "dir").unwrap();
+        assert_eq!(error(overlay.lookup(dir, "file")), ErrorCode::BadDescriptor);
+        overlay.create_dir(root, "dir").unwrap();
+        let dir = overlay.lookup(root, "dir").unwrap();
+        assert!(names(&overlay, dir).is_empty());
+        assert_eq!(error(overlay.lookup(dir, "file")), ErrorCode::NoEntry);
+
+        // Renaming over a base file replaces it, and removing the replacement
+        // doesn't bring the base file back.
+        let overlay = OverlayFilesystem::new(base.clone());
+        let dir = overlay.lookup(root, "dir").unwrap();
+        let new = overlay.create_file(root, "new", true).unwrap();
+        overlay.write(new, 0, b"replacement").unwrap();
+        overlay.rename(root, "new", dir, "file").unwrap();
+        assert_eq!(overlay.lookup(dir, "file").unwrap(), new);
+        assert_eq!(&overlay.read(new, 0, 100).unwrap()[..], b"replacement");
+        assert_eq!(names(&overlay, root), ["dir", "other"]);
+        overlay.remove_file(dir, "file").unwrap();
+        assert_eq!(error(overlay.lookup(dir, "file")), ErrorCode::NoEntry);
+        assert!(names(&overlay, dir).is_empty());
+
+        // Renaming a base file away leaves a whiteout behind.
+        overlay.rename(root, "other", dir, "other").unwrap();
+        assert_eq!(error(overlay.lookup(root, "other")), ErrorCode::NoEntry);
+        assert_eq!(names(&overlay, root), ["dir"]);
+        assert_eq!(names(&overlay, dir), ["other"]);
+
+        // The base is unchanged throughout.
+        assert_eq!(names(&*base, base.root()), ["dir", "other"]);
+        let base_dir = base.lookup(base.root(), "dir").unwrap();
+        assert_eq!(names(&*base, base_dir), ["file"]);
+    }
+}
@@ -0,0 +1,863 @@
+//! Support for preopening host-implemented virtual filesystems.
+
+use crate::bindings::clocks::wall_clock::Datetime;
+use crate::bindings::filesystem::types::{
+    self, DescriptorFlags, DescriptorStat, DescriptorType, DirectoryEntry, ErrorCode, OpenFlags,
+};
+use crate::filesystem::{DirPerms, FilePerms, OpenMode};
+use crate::Subscribe;
+use crate::{FsError, FsResult, HostInputStream, HostOutputStream, StreamError, StreamResult};
+use bytes::Bytes;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// A filesystem implemented by the embedder rather than by the host operating
+/// system.
+///
+/// Virtual filesystems are made available to WebAssembly with
+/// [`WasiCtxBuilder::preopened_virtual_dir`](crate::WasiCtxBuilder::preopened_virtual_dir),
+/// and are accessible through both the WASIp2 `wasi:filesystem` interfaces and
+/// WASIp1.
+///
+/// Files and directories are identified by a `u64` node number chosen by the
+/// implementation. A node's number must remain stable for as long as the node
+/// exists, and is reported to the guest as its inode number.
+///
+/// Paths are resolved by Wasmtime, so every `name` passed to these methods is
+/// a single, non-empty path component which is neither `.` nor `..`. Access
+/// permissions are enforced by Wasmtime according to the [`DirPerms`] and
+/// [`FilePerms`] the directory was preopened with, before any method here is
+/// called. Symbolic and hard links are not supported.
+///
+/// These methods are called synchronously on the thread executing
+/// WebAssembly, so implementations should not block for long periods of time.
+///
+/// All mutating methods default to returning [`ErrorCode::ReadOnly`], so
+/// read-only filesystems need only implement the required methods.
+pub trait VirtualFilesystem: Send + Sync + 'static {
+    /// Returns the node number of the root directory.
+    fn root(&self) -> u64;
+
+    /// Looks up the entry called `name` in the directory `dir`.
+    ///
+    /// Returns [`ErrorCode::NoEntry`] if there is no such entry, and
+    /// [`ErrorCode::NotDirectory`] if `dir` is not a directory.
+    fn lookup(&self, dir: u64, name: &str) -> FsResult<u64>;
+
+    /// Returns the metadata of `node`.
+    fn stat(&self, node: u64) -> FsResult<DescriptorStat>;
+
+    /// Returns the entries of the directory `dir`, excluding `.` and `..`.
+    fn read_dir(&self, dir: u64) -> FsResult<Vec<DirectoryEntry>>;
+
+    /// Reads up to `len` bytes from `file` starting at `offset`.
+    ///
+    /// Returns an empty buffer at or beyond the end of the file.
+    fn read(&self, file: u64, offset: u64, len: u64) -> FsResult<Bytes>;
+
+    /// Writes `buf` into `file` at `offset`, extending the file if necessary,
+    /// and returns the number of bytes written.
+    fn write(&self, file: u64, offset: u64, buf: &[u8]) -> FsResult<u64> {
+        let _ = (file, offset, buf);
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    /// Truncates or extends `file` to `size` bytes.
+    fn set_size(&self, file: u64, size: u64) -> FsResult<()> {
+        let _ = (file, size);
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    /// Sets the access and modification timestamps of `node`, leaving those
+    /// that are `None` unchanged.
+    fn set_times(&self, node: u64, atim: Option<Datetime>, mtim: Option<Datetime>) -> FsResult<()> {
+        let _ = (node, atim, mtim);
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    /// Creates a regular file called `name` in `dir` and returns its node.
+    ///
+    /// If the entry already exists this returns [`ErrorCode::Exist`] when
+    /// `exclusive` is set, [`ErrorCode::IsDirectory`] if it is a directory, and
+    /// the existing file otherwise.
+    fn create_file(&self, dir: u64, name: &str, exclusive: bool) -> FsResult<u64> {
+        let _ = (dir, name, exclusive);
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    /// Creates an empty directory called `name` in `dir`.
+    fn create_dir(&self, dir: u64, name: &str) -> FsResult<()> {
+        let _ = (dir, name);
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    /// Removes the regular file called `name` from `dir`.
+    fn remove_file(&self, dir: u64, name: &str) -> FsResult<()> {
+        let _ = (dir, name);
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    /// Removes the empty directory called `name` from `dir`.
+    fn remove_dir(&self, dir: u64, name: &str) -> FsResult<()> {
+        let _ = (dir, name);
+        Err(ErrorCode::ReadOnly.into())
+    }
+
+    /// Moves the entry `old_name` in `old_dir` to `new_name` in `new_dir`,
+    /// replacing any existing file or empty directory there.
+    fn rename(&self, old_dir: u64, old_name: &str, new_dir: u64, new_name: &str) -> FsResult<()> {
+        let _ = (old_dir, old_name, new_dir, new_name);
+        Err(ErrorCode::ReadOnly.into())
+    }
+}
+
+/// Returns the current time as a [`Datetime`], for use in timestamps.
+pub(crate) fn now() -> Datetime {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    Datetime {
+        seconds: now.as_secs(),
+        nanoseconds: now.subsec_nanos(),
+    }
+}
+
+/// An open file or directory within a [`VirtualFilesystem`].
+#[derive(Clone)]
+pub struct VirtualDescriptor {
+    fs: Arc<dyn VirtualFilesystem>,
+    node: u64,
+    is_dir: bool,
+    /// Permissions to enforce on this directory and any directories opened
+    /// under it. Unused for files.
+    perms: DirPerms,
+    /// Permissions to enforce on this file, or any files opened under this
+    /// directory.
+    file_perms: FilePerms,
+    open_mode: OpenMode,
+}
+
+impl VirtualDescriptor {
+    pub(crate) fn new_root(
+        fs: Arc<dyn VirtualFilesystem>,
+        perms: DirPerms,
+        file_perms: FilePerms,
+        open_mode: OpenMode,
+    ) -> Self {
+        let node = fs.root();
+        VirtualDescriptor {
+            fs,
+            node,
+            is_dir: true,
+            perms,
+            file_perms,
+            open_mode,
+        }
+    }
+
+    pub(crate) fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// Returns a value identifying the underlying node, for comparing whether
+    /// two descriptors refer to the same object.
+    pub(crate) fn identity(&self) -> (usize, u64) {
+        (Arc::as_ptr(&self.fs).cast::<u8>() as usize, self.node)
+    }
+
+    fn file(&self) -> Result<(), ErrorCode> {
+        if self.is_dir {
+            Err(ErrorCode::BadDescriptor)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn dir(&self) -> Result<(), ErrorCode> {
+        if self.is_dir {
+            Ok(())
+        } else {
+            Err(ErrorCode::NotDirectory)
+        }
+    }
+
+    fn require_dir_perms(&self, perms: DirPerms) -> Result<(), ErrorCode> {
+        self.dir()?;
+        if self.perms.contains(perms) {
+            Ok(())
+        } else {
+            Err(ErrorCode::NotPermitted)
+        }
+    }
+
+    fn require_file_perms(&self, perms: FilePerms) -> Result<(), ErrorCode> {
+        self.file()?;
+        if !self.file_perms.contains(perms) {
+            return Err(ErrorCode::NotPermitted);
+        }
+        // Host files have their open mode enforced by the operating system,
+        // so mirror its behavior here.
+        if !self.open_mode.contains(open_mode(perms)) {
+            return Err(ErrorCode::BadDescriptor);
+        }
+        Ok(())
+    }
+
+    /// Resolves `path` relative to this directory, without allowing it to
+    /// escape this directory.
+    fn resolve(&self, path: &str) -> FsResult<u64> {
+        if path.is_empty() {
+            return Err(ErrorCode::NoEntry.into());
+        }
+        if path.starts_with('/') {
+            return Err(ErrorCode::NotPermitted.into());
+        }
+        let mut stack = vec![self.node];
+        for component in path.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    if !self.is_dir_node(*stack.last().unwrap())? {
+                        return Err(ErrorCode::NotDirectory.into());
+                    }
+                    stack.pop();
+                    if stack.is_empty() {
+                        return Err(ErrorCode::NotPermitted.into());
+                    }
+                }
+                name => {
+                    let next = self.fs.lookup(*stack.last().unwrap(), name)?;
+                    stack.push(next);
+                }
+            }
+        }
+        Ok(*stack.last().unwrap())
+    }
+
+    /// Resolves all but the last component of `path`, returning the containing
+    /// directory and the final component's name.
+    fn resolve_parent<'a>(&self, path: &'a str) -> FsResult<(u64, &'a str)> {
+        if path.starts_with('/') {
+            return Err(ErrorCode::NotPermitted.into());
+        }
+        let path = path.trim_end_matches('/');
+        let (parent, name) = match path.rsplit_once('/') {
+            Some((parent, name)) => (self.resolve(parent)?, name),
+            None => (self.node, path),
+        };
+        match name {
+            "" | "." | ".." => Err(ErrorCode::Invalid.into()),
+            name => Ok((parent, name)),
+        }
+    }
+
+    fn is_dir_node(&self, node: u64) -> FsResult<bool> {
+        Ok(self.fs.stat(node)?.type_ == DescriptorType::Directory)
+    }
+
+    pub(crate) fn get_flags(&self) -> FsResult<DescriptorFlags> {
+        let mut flags = DescriptorFlags::empty();
+        if self.open_mode.contains(OpenMode::READ) {
+            flags |= DescriptorFlags::READ;
+        }
+        if self.open_mode.contains(OpenMode::WRITE) {
+            flags |= if self.is_dir {
+                DescriptorFlags::MUTATE_DIRECTORY
+            } else {
+                DescriptorFlags::WRITE
+            };
+        }
+        Ok(flags)
+    }
+
+    pub(crate) fn get_type(&self) -> FsResult<DescriptorType> {
+        Ok(self.stat()?.type_)
+    }
+
+    pub(crate) fn set_size(&self, size: u64) -> FsResult<()> {
+        self.require_file_perms(FilePerms::WRITE)?;
+        self.fs.set_size(self.node, size)
+    }
+
+    pub(crate) fn set_times(
+        &self,
+        atim: types::NewTimestamp,
+        mtim: types::NewTimestamp,
+    ) -> FsResult<()> {
+        if self.is_dir {
+            self.require_dir_perms(DirPerms::MUTATE)?;
+        } else {
+            self.require_file_perms(FilePerms::WRITE)?;
+        }
+        self.fs
+            .set_times(self.node, timestamp(atim), timestamp(mtim))
+    }
+
+    pub(crate) fn read(&self, len: u64, offset: u64) -> FsResult<(Vec<u8>, bool)> {
+        self.require_file_perms(FilePerms::READ)?;
+        let buf = self.fs.read(self.node, offset, len)?;
+        let eof = buf.is_empty();
+        Ok((buf.to_vec(), eof))
+    }
+
+    pub(crate) fn write(&self, buf: &[u8], offset: u64) -> FsResult<u64> {
+        self.require_file_perms(FilePerms::WRITE)?;
+        self.fs.write(self.node, offset, buf)
+    }
+
+    pub(crate) fn read_directory(&self) -> FsResult<Vec<DirectoryEntry>> {
+        self.require_dir_perms(DirPerms::READ)?;
+        self.fs.read_dir(self.node)
+    }
+
+    pub(crate) fn create_directory_at(&self, path: &str) -> FsResult<()> {
+        self.require_dir_perms(DirPerms::MUTATE)?;
+        let (parent, name) = self.resolve_parent(path)?;
+        self.fs.create_dir(parent, name)
+    }
+
+    pub(crate) fn stat(&self) -> FsResult<DescriptorStat> {
+        self.fs.stat(self.node)
+    }
+
+    pub(crate) fn stat_at(&self, path: &str) -> FsResult<DescriptorStat> {
+        self.require_dir_perms(DirPerms::READ)?;
+        self.fs.stat(self.resolve(path)?)
+    }
+
+    pub(crate) fn set_times_at(
+        &self,
+        path: &str,
+        atim: types::NewTimestamp,
+        mtim: types::NewTimestamp,
+    ) -> FsResult<()> {
+        self.require_dir_perms(DirPerms::MUTATE)?;
+        let node = self.resolve(path)?;
+        self.fs.set_times(node, timestamp(atim), timestamp(mtim))
+    }
+
+    pub(crate) fn open_at(
+        &self,
+        path: &str,
+        oflags: OpenFlags,
+        flags: DescriptorFlags,
+    ) -> FsResult<VirtualDescriptor> {
+        self.require_dir_perms(DirPerms::READ)?;
+
+        let create = oflags.contains(OpenFlags::CREATE);
+        let truncate = oflags.contains(OpenFlags::TRUNCATE);
+        if !self.perms.contains(DirPerms::MUTATE)
+            && (create || truncate || flags.contains(DescriptorFlags::WRITE))
+        {
+            return Err(ErrorCode::NotPermitted.into());
+        }
+        if flags.intersects(
+            DescriptorFlags::FILE_INTEGRITY_SYNC
+                | DescriptorFlags::DATA_INTEGRITY_SYNC
+                | DescriptorFlags::REQUESTED_WRITE_SYNC,
+        ) {
+            return Err(ErrorCode::Unsupported.into());
+        }
+        if oflags.contains(OpenFlags::DIRECTORY)
+            && (create || truncate || oflags.contains(OpenFlags::EXCLUSIVE))
+        {
+            return Err(ErrorCode::Invalid.into());
+        }
+
+        let mut open_mode = OpenMode::empty();
+        if create || truncate || flags.contains(DescriptorFlags::WRITE) {
+            open_mode |= OpenMode::WRITE;
+        }
+        if flags.contains(DescriptorFlags::READ) || !flags.contains(DescriptorFlags::WRITE) {
+            open_mode |= OpenMode::READ;
+        }
+        if open_mode.contains(OpenMode::WRITE) && !self.file_perms.contains(FilePerms::WRITE) {
+            return Err(ErrorCode::NotPermitted.into());
+        }
+
+        let node = if create {
+            let (parent, name) = self.resolve_parent(path)?;
+            self.fs
+                .create_file(parent, name, oflags.contains(OpenFlags::EXCLUSIVE))?
+        } else {
+            self.resolve(path)?
+        };
+        let is_dir = self.is_dir_node(node)?;
+        if is_dir && open_mode.contains(OpenMode::WRITE) {
+            return Err(ErrorCode::IsDirectory.into());
+        }
+        if !is_dir && oflags.contains(OpenFlags::DIRECTORY) {
+            return Err(ErrorCode::NotDirectory.into());
+        }
+        if !is_dir && truncate {
+            self.fs.set_size(node, 0)?;
+        }
+
+        Ok(VirtualDescriptor {
+            fs: self.fs.clone(),
+            node,
+            is_dir,
+            perms: self.perms,
+            file_perms: self.file_perms,
+            open_mode,
+        })
+    }
+
+    pub(crate) fn readlink_at(&self, path: &str) -> FsResult<String> {
+        self.require_dir_perms(DirPerms::READ)?;
+        // Virtual filesystems have no symbolic links, so the only question
+        // is whether `path` exists at all.
+        self.resolve(path)?;
+        Err(ErrorCode::Invalid.into())
+    }
+
+    pub(crate) fn remove_directory_at(&self, path: &str) -> FsResult<()> {
+        self.require_dir_perms(DirPerms::MUTATE)?;
+        let (parent, name) = self.resolve_parent(path)?;
+        self.fs.remove_dir(parent, name)
+    }
+
+    pub(crate) fn rename_at(
+        &self,
+        old_path: &str,
+        new_dir: &VirtualDescriptor,
+        new_path: &str,
+    ) -> FsResult<()> {
+        self.require_dir_perms(DirPerms::MUTATE)?;
+        new_dir.require_dir_perms(DirPerms::MUTATE)?;
+        if !Arc::ptr_eq(&self.fs, &new_dir.fs) {
+            return Err(ErrorCode::CrossDevice.into());
+        }
+        let (old_parent, old_name) = self.resolve_parent(old_path)?;
+        let (new_parent, new_name) = new_dir.resolve_parent(new_path)?;
+        self.fs.rename(old_parent, old_name, new_parent, new_name)
+    }
+
+    pub(crate) fn symlink_at(&self) -> FsResult<()> {
+        self.require_dir_perms(DirPerms::MUTATE)?;
+        Err(ErrorCode::Unsupported.into())
+    }
+
+    pub(crate) fn unlink_file_at(&self, path: &str) -> FsResult<()> {
+        self.require_dir_perms(DirPerms::MUTATE)?;
+        let (parent, name) = self.resolve_parent(path)?;
+        self.fs.remove_file(parent, name)
+    }
+
+    pub(crate) fn read_via_stream(&self, offset: u64) -> FsResult<VirtualInputStream> {
+        self.file()?;
+        if !self.file_perms.contains(FilePerms::READ) || !self.open_mode.contains(OpenMode::READ) {
+            return Err(ErrorCode::BadDescriptor.into());
+        }
+        Ok(VirtualInputStream {
+            file: self.clone(),
+            position: offset,
+        })
+    }
+
+    pub(crate) fn write_via_stream(&self, offset: Option<u64>) -> FsResult<VirtualOutputStream> {
+        self.file()?;
+        if !self.file_perms.contains(FilePerms::WRITE) || !self.open_mode.contains(OpenMode::WRITE)
+        {
+            return Err(ErrorCode::BadDescriptor.into());
+        }
+        Ok(VirtualOutputStream {
+            file: self.clone(),
+            position: offset,
+        })
+    }
+
+    pub(crate) fn metadata_hash(&self) -> types::MetadataHashValue {
+        metadata_hash(self.identity())
+    }
+
+    pub(crate) fn metadata_hash_at(&self, path: &str) -> FsResult<types::MetadataHashValue> {
+        self.dir()?;
+        let node = self.resolve(path)?;
+        Ok(metadata_hash((self.identity().0, node)))
+    }
+}
+
+fn open_mode(perms: FilePerms) -> OpenMode {
+    let mut mode = OpenMode::empty();
+    if perms.contains(FilePerms::READ) {
+        mode |= OpenMode::READ;
+    }
+    if perms.contains(FilePerms::WRITE) {
+        mode |= OpenMode::WRITE;
+    }
+    mode
+}
+
+fn timestamp(t: types::NewTimestamp) -> Option<Datetime> {
+    match t {
+        types::NewTimestamp::NoChange => None,
+        types::NewTimestamp::Now => Some(now()),
+        types::NewTimestamp::Timestamp(t) => Some(t),
+    }
+}
+
+fn metadata_hash((fs, node): (usize, u64)) -> types::MetadataHashValue {
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hasher.write_usize(fs);
+    hasher.write_u64(node);
+    let lower = hasher.finish();
+    // See `calculate_metadata_hash` for the cap-std backend.
+    let upper = lower ^ 4614256656552045848u64;
+    types::MetadataHashValue { lower, upper }
+}
+
+/// Convert a filesystem error into a stream error, keeping traps as traps.
+fn stream_error(err: FsError) -> StreamError {
+    match err.downcast() {
+        Ok(code) => StreamError::LastOperationFailed(code.into()),
+        Err(trap) => StreamError::Trap(trap),
+    }
+}
+
+// Virtual filesystems are synchronous, so streams over them are always ready.
+
+pub(crate) struct VirtualInputStream {
+    file: VirtualDescriptor,
+    position: u64,
+}
+
+#[async_trait::async_trait]
+impl HostInputStream for VirtualInputStream {
+    fn read(&mut self, size: usize) -> StreamResult<Bytes> {
+        if size == 0 {
+            return Ok(Bytes::new());
+        }
+        let buf = self
+            .file
+            .fs
+            .read(self.file.node, self.position, size as u64)
+            .map_err(stream_error)?;
+        if buf.is_empty() {
+            return Err(StreamError::Closed);
+        }
+        self.position += buf.len() as u64;
+        Ok(buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscribe for VirtualInputStream {
+    async fn ready(&mut self) {}
+}
+
+pub(crate) struct VirtualOutputStream {
+    file: VirtualDescriptor,
+    /// The position to write at, or `None` to append.
+    position: Option<u64>,
+}
+
+// Matches the capacity used for host files.
+const VIRTUAL_WRITE_CAPACITY: usize = 1024 * 1024;
+
+#[async_trait::async_trait]
+impl HostOutputStream for VirtualOutputStream {
+    fn write(&mut self, buf: Bytes) -> StreamResult<()> {
+        let fs = &self.file.fs;
+        let offset = match self.position {
+            Some(position) => position,
+            None => fs.stat(self.file.node).map_err(stream_error)?.size,
+        };
+        let n = fs
+            .write(self.file.node, offset, &buf)
+            .map_err(stream_error)?;
+        if let Some(position) = &mut self.position {
+            *position += n;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> StreamResult<()> {
+        Ok(())
+    }
+
+    fn check_write(&mut self) -> StreamResult<usize> {
+        Ok(VIRTUAL_WRITE_CAPACITY)
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscribe for VirtualOutputStream {
+    async fn ready(&mut self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MemoryFilesystem;
+
+    fn fixture(dir_perms: DirPerms, file_perms: FilePerms) -> VirtualDescriptor {
+        let fs = MemoryFilesystem::new();
+        let root = fs.root();
+        fs.create_dir(root, "dir").unwrap();
+        let dir = fs.lookup(root, "dir").unwrap();
+        let file = fs.create_file(dir, "file", true).unwrap();
+        fs.write(file, 0, b"hello").unwrap();
+        VirtualDescriptor::new_root(
+            Arc::new(fs),
+            dir_perms,
+            file_perms,
+            OpenMode::READ | OpenMode::WRITE,
+        )
+    }
+
+    fn error<T>(result: FsResult<T>) -> ErrorCode {
+        match result {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => e.downcast().unwrap(),
+        }
+    }
+
+    #[test]
+    fn resolve_paths() {
+        let root = fixture(DirPerms::all(), FilePerms::all());
+        let read = DescriptorFlags::READ;
+        let file = root.open_at("dir/file", OpenFlags::empty(), read).unwrap();
+        assert_eq!(file.read(100, 0).unwrap(), (b"hello".to_vec(), false));
+        assert_eq!(file.read(100, 5).unwrap(), (Vec::new(), true));
+
+        let same = root
+            .open_at("./dir/../dir//file", OpenFlags::empty(), read)
+            .unwrap();
+        assert_eq!(same.identity(), file.identity());
+
+        let dir = root.open_at("dir", OpenFlags::DIRECTORY, read).unwrap();
+        assert!(dir.is_dir());
+        assert_eq!(error(dir.stat_at("../dir")), ErrorCode::NotPermitted);
+        assert_eq!(error(root.stat_at("/dir")), ErrorCode::NotPermitted);
+        assert_eq!(error(root.stat_at("missing")), ErrorCode::NoEntry);
+        assert_eq!(error(root.stat_at("dir/file/x")), ErrorCode::NotDirectory);
+        assert_eq!(
+            error(root.open_at("dir/file", OpenFlags::DIRECTORY, read)),
+            ErrorCode::NotDirectory
+        );
+        assert_eq!(error(root.readlink_at("dir/file")), ErrorCode::Invalid);
+    }
+
+    #[test]
+    fn create_and_write() {
+        let root = fixture(DirPerms::all(), FilePerms::all());
+        let file = root
+            .open_at(
+                "dir/new",
+                OpenFlags::CREATE | OpenFlags::EXCLUSIVE,
+                DescriptorFlags::READ | DescriptorFlags::WRITE,
+            )
+            .unwrap();
+        assert_eq!(file.write(b"data", 2).unwrap(), 4);
+        assert_eq!(file.stat().unwrap().size, 6);
+        assert_eq!(
+            error(root.open_at(
+                "dir/new",
+                OpenFlags::CREATE | OpenFlags::EXCLUSIVE,
+                DescriptorFlags::WRITE
+            )),
+            ErrorCode::Exist
+        );
+
+        let truncated = root
+            .open_at("dir/new", OpenFlags::TRUNCATE, DescriptorFlags::WRITE)
+            .unwrap();
+        assert_eq!(truncated.stat().unwrap().size, 0);
+        assert_eq!(error(truncated.read(10, 0)), ErrorCode::BadDescriptor);
+
+        root.rename_at("dir/new", &root, "renamed").unwrap();
+        let names = root
+            .read_directory()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["dir", "renamed"]);
+        root.unlink_file_at("renamed").unwrap();
+        assert_eq!(error(root.remove_directory_at("dir")), ErrorCode::NotEmpty);
+    }
+
+    #[test]
+    fn permissions() {
+        let root = fixture(DirPerms::READ, FilePerms::READ);
+        assert_eq!(
+            error(root.open_at("dir/file", OpenFlags::empty(), DescriptorFlags::WRITE)),
+            ErrorCode::NotPermitted
+        );
+        assert_eq!(
+            error(root.open_at("new", OpenFlags::CREATE, DescriptorFlags::READ)),
+            ErrorCode::NotPermitted
+        );
+        assert_eq!(
+            error(root.create_directory_at("new")),
+            ErrorCode::NotPermitted
+        );
+        assert_eq!(
+            error(root.unlink_file_at("dir/file")),
+            ErrorCode::NotPermitted
+        );
+
+        let file = root
+            .open_at("dir/file", OpenFlags::empty(), DescriptorFlags::READ)
+            .unwrap();
+        assert_eq!(error(file.write(b"x", 0)), ErrorCode::NotPermitted);
+        assert_eq!(error(file.set_size(0)), ErrorCode::NotPermitted);
+        assert!(file.write_via_stream(Some(0)).is_err());
+
+        let other = fixture(DirPerms::all(), FilePerms::all());
+        assert_eq!(
+            error(other.rename_at("dir", &root, "dir2")),
+            ErrorCode::NotPermitted
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        let root = fixture(DirPerms::all(), FilePerms::all());
+        let read = DescriptorFlags::READ;
+        for path in [
+            "..",
+            "./..",
+            "../dir",
+            "dir/../..",
+            "dir//..//..",
+            "dir/../../dir/file",
+            "/",
+            "/dir/file",
+        ] {
+            assert_eq!(error(root.stat_at(path)), ErrorCode::NotPermitted, "{path}");
+            assert_eq!(
+                error(root.open_at(path, OpenFlags::empty(), read)),
+                ErrorCode::NotPermitted,
+                "{path}"
+            );
+        }
+
+        // Nothing can be created, removed, or moved outside of the preopen.
+        for path in ["../new", "dir/../../new", "/new"] {
+            assert_eq!(
+                error(root.open_at(path, OpenFlags::CREATE, DescriptorFlags::WRITE)),
+                ErrorCode::NotPermitted,
+                "{path}"
+            );
+            assert_eq!(
+                error(root.create_directory_at(path)),
+                ErrorCode::NotPermitted,
+                "{path}"
+            );
+            assert_eq!(
+                error(root.rename_at("dir/file", &root, path)),
+                ErrorCode::NotPermitted,
+                "{path}"
+            );
+        }
+        assert_eq!(
+            error(root.unlink_file_at("../dir/file")),
+            ErrorCode::NotPermitted
+        );
+        assert_eq!(
+            error(root.remove_directory_at("/dir")),
+            ErrorCode::NotPermitted
+        );
+
+        // A directory opened beneath the preopen can't reach its parent either.
+        let dir = root.open_at("dir", OpenFlags::DIRECTORY, read).unwrap();
+        assert_eq!(error(dir.stat_at("..")), ErrorCode::NotPermitted);
+        assert_eq!(
+            error(dir.open_at("../dir/file", OpenFlags::empty(), read)),
+            ErrorCode::NotPermitted
+        );
+        assert_eq!(error(dir.stat_at("file/../..")), ErrorCode::NotDirectory);
+
+        // Files can't be traversed through with `..`.
+        assert_eq!(
+            error(root.stat_at("dir/file/../file")),
+            ErrorCode::NotDirectory
+        );
+
+        // None of the attempts above changed anything.
+        let names = |d: &VirtualDescriptor| {
+            d.read_directory()
+                .unwrap()
+                .into_iter()
+                .map(|e| e.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&root), ["dir"]);
+        assert_eq!(names(&dir), ["file"]);
+    }
+
+    #[test]
+    fn permissions_are_inherited() {
+        // Directory and file permissions apply to everything opened beneath a
+        // preopen, however it is reached.
+        let root = fixture(DirPerms::READ, FilePerms::READ);
+        let dir = root
+            .open_at("dir", OpenFlags::DIRECTORY, DescriptorFlags::READ)
+            .unwrap();
+        assert_eq!(
+            error(dir.create_directory_at("new")),
+            ErrorCode::NotPermitted
+        );
+        assert_eq!(error(dir.unlink_file_at("file")), ErrorCode::NotPermitted);
+        assert_eq!(
+            error(dir.set_times_at("file", types::NewTimestamp::Now, types::NewTimestamp::Now)),
+            ErrorCode::NotPermitted
+        );
+        assert_eq!(
+            error(dir.open_at("file", OpenFlags::TRUNCATE, DescriptorFlags::READ)),
+            ErrorCode::NotPermitted
+        );
+        let file = dir
+            .open_at("file", OpenFlags::empty(), DescriptorFlags::READ)
+            .unwrap();
+        assert_eq!(file.read(5, 0).unwrap().0, b"hello");
+        assert_eq!(error(file.write(b"x", 0)), ErrorCode::NotPermitted);
+
+        // Directories may be mutated without granting write access to files.
+        let root = fixture(DirPerms::all(), FilePerms::READ);
+        root.create_directory_at("new").unwrap();
+        assert_eq!(
+            error(root.open_at("dir/file", OpenFlags::empty(), DescriptorFlags::WRITE)),
+            ErrorCode::NotPermitted
+        );
+        assert_eq!(
+            error(root.open_at("created", OpenFlags::CREATE, DescriptorFlags::READ)),
+            ErrorCode::NotPermitted
+        );
+        root.unlink_file_at("dir/file").unwrap();
+
+        // Directory contents can't be listed or opened without `DirPerms::READ`.
+        let root = fixture(DirPerms::MUTATE, FilePerms::all());
+        assert_eq!(error(root.read_directory()), ErrorCode::NotPermitted);
+        assert_eq!(error(root.stat_at("dir")), ErrorCode::NotPermitted);
+        assert_eq!(
+            error(root.open_at("dir/file", OpenFlags::empty(), DescriptorFlags::READ)),
+            ErrorCode::NotPermitted
+        );
+        root.create_directory_at("new").unwrap();
+
+        // Files are limited to the mode they were opened with, and reading
+        // requires `FilePerms::READ`.
+        let root = fixture(DirPerms::all(), FilePerms::WRITE);
+        let file = root
+            .open_at("dir/file", OpenFlags::empty(), DescriptorFlags::WRITE)
+            .unwrap();
+        assert_eq!(file.write(b"J", 0).unwrap(), 1);
+        assert_eq!(error(file.read(5, 0)), ErrorCode::NotPermitted);
+        assert!(file.read_via_stream(0).is_err());
+
+        let root = fixture(DirPerms::all(), FilePerms::all());
+        let file = root
+            .open_at("dir/file", OpenFlags::empty(), DescriptorFlags::WRITE)
+            .unwrap();
+        assert_eq!(error(file.read(5, 0)), ErrorCode::BadDescriptor);
+        let file = root
+            .open_at("dir/file", OpenFlags::empty(), DescriptorFlags::READ)
+            .unwrap();
+        assert_eq!(error(file.write(b"x", 0)), ErrorCode::BadDescriptor);
+        assert!(file.write_via_stream(None).is_err());
+    }
+}
@@ -24,7 +24,7 @@ where
         for (dir, name) in self.ctx().preopens.clone() {
             let fd = self
                 .table()
-                .push(Descriptor::Dir(dir))
+                .push(dir)
                 .with_context(|| format!("failed to push preopen {name}"))?;
             results.push((fd, name));
         }
@@ -47,12 +47,17 @@ where
     ) -> anyhow::Result<Option<ErrorCode>> {
         let err = self.table().get(&err)?;
 
-        // Currently `err` always comes from the stream implementation which
-        // uses standard reads/writes so only check for `std::io::Error` here.
+        // Currently `err` always comes from the stream implementation, which
+        // uses standard reads/writes for host files.
         if let Some(err) = err.downcast_ref::<std::io::Error>() {
             return Ok(Some(ErrorCode::from(err)));
         }
 
+        // Streams over virtual filesystems report their errors directly.
+        if let Some(code) = err.downcast_ref::<ErrorCode>() {
+            return Ok(Some(*code));
+        }
+
         Ok(None)
     }
 }
@@ -81,7 +86,11 @@ where
             Advice::NoReuse => A::NoReuse,
         };
 
-        let f = self.table().get(&fd)?.file()?;
+        let f = match self.table().get(&fd)? {
+            // Advice is purely a hint, so it is ignored for virtual files.
+            Descriptor::Virtual(v) if !v.is_dir() => return Ok(()),
+            d => d.file()?,
+        };
         f.run_blocking(move |f| f.advise(offset, len, advice))
             .await?;
         Ok(())
@@ -111,6 +120,8 @@ where
                 d.run_blocking(|d| Ok(d.open(std::path::Component::CurDir)?.sync_data()?))
                     .await
             }
+            // Virtual filesystems have no separate persistent storage to sync.
+            Descriptor::Virtual(_) => Ok(()),
         }
     }
 
@@ -159,6 +170,7 @@ where
                 }
                 Ok(flags)
             }
+            Descriptor::Virtual(v) => v.get_flags(),
         }
     }
 
@@ -174,6 +186,7 @@ where
                 Ok(descriptortype_from(meta.file_type()))
             }
             Descriptor::Dir(_) => Ok(types::DescriptorType::Directory),
+            Descriptor::Virtual(v) => v.get_type(),
         }
     }
 
@@ -182,7 +195,10 @@ where
         fd: Resource<types::Descriptor>,
         size: types::Filesize,
     ) -> FsResult<()> {
-        let f = self.table().get(&fd)?.file()?;
+        let f = match self.table().get(&fd)? {
+            Descriptor::Virtual(v) => return v.set_size(size),
+            d => d.file()?,
+        };
         if !f.perms.contains(FilePerms::WRITE) {
             Err(ErrorCode::NotPermitted)?;
         }
@@ -218,6 +234,7 @@ where
                 d.run_blocking(|d| d.set_times(atim, mtim)).await?;
                 Ok(())
             }
+            Descriptor::Virtual(v) => v.set_times(atim, mtim),
         }
     }
 
@@ -232,7 +249,10 @@ where
 
         let table = self.table();
 
-        let f = table.get(&fd)?.file()?;
+        let f = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.read(len, offset),
+            d => d.file()?,
+        };
         if !f.perms.contains(FilePerms::READ) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -269,7 +289,10 @@ where
         use system_interface::fs::FileIoExt;
 
         let table = self.table();
-        let f = table.get(&fd)?.file()?;
+        let f = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.write(&buf, offset),
+            d => d.file()?,
+        };
         if !f.perms.contains(FilePerms::WRITE) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -286,7 +309,13 @@ where
         fd: Resource<types::Descriptor>,
     ) -> FsResult<Resource<types::DirectoryEntryStream>> {
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => {
+                let entries = v.read_directory()?;
+                return Ok(table.push(ReaddirIterator::new(entries.into_iter().map(Ok)))?);
+            }
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::READ) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -370,6 +399,7 @@ where
                 d.run_blocking(|d| Ok(d.open(std::path::Component::CurDir)?.sync_all()?))
                     .await
             }
+            Descriptor::Virtual(_) => Ok(()),
         }
     }
 
@@ -379,7 +409,10 @@ where
         path: String,
     ) -> FsResult<()> {
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.create_directory_at(&path),
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -400,6 +433,7 @@ where
                 let meta = d.run_blocking(|d| d.dir_metadata()).await?;
                 Ok(descriptorstat_from(meta))
             }
+            Descriptor::Virtual(v) => v.stat(),
         }
     }
 
@@ -410,7 +444,11 @@ where
         path: String,
     ) -> FsResult<types::DescriptorStat> {
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            // Virtual filesystems have no symlinks, so `path_flags` is moot.
+            Descriptor::Virtual(v) => return v.stat_at(&path),
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::READ) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -434,7 +472,10 @@ where
         use cap_fs_ext::DirExt;
 
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.set_times_at(&path, atim, mtim),
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -472,7 +513,11 @@ where
         new_path: String,
     ) -> FsResult<()> {
         let table = self.table();
-        let old_dir = table.get(&fd)?.dir()?;
+        let old_dir = match table.get(&fd)? {
+            // Virtual filesystems do not support hard links.
+            Descriptor::Virtual(v) if v.is_dir() => return Err(ErrorCode::Unsupported.into()),
+            d => d.dir()?,
+        };
         if !old_dir.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -504,7 +549,13 @@ where
 
         let allow_blocking_current_thread = self.ctx().allow_blocking_current_thread;
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => {
+                let opened = v.open_at(&path, oflags, flags)?;
+                return Ok(table.push(Descriptor::Virtual(opened))?);
+            }
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::READ) {
             Err(ErrorCode::NotPermitted)?;
         }
@@ -652,7 +703,10 @@ where
         path: String,
     ) -> FsResult<String> {
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.readlink_at(&path),
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::READ) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -669,7 +723,10 @@ where
         path: String,
     ) -> FsResult<()> {
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.remove_directory_at(&path),
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -684,7 +741,16 @@ where
         new_path: String,
     ) -> FsResult<()> {
         let table = self.table();
-        let old_dir = table.get(&fd)?.dir()?;
+        let old_dir = match table.get(&fd)? {
+            Descriptor::Virtual(v) => {
+                let v = v.clone();
+                return match table.get(&new_fd)? {
+                    Descriptor::Virtual(new_dir) => v.rename_at(&old_path, new_dir, &new_path),
+                    _ => Err(ErrorCode::CrossDevice.into()),
+                };
+            }
+            d => d.dir()?,
+        };
         if !old_dir.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -709,7 +775,10 @@ where
         use cap_fs_ext::DirExt;
 
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.symlink_at(),
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -725,7 +794,10 @@ where
         use cap_fs_ext::DirExt;
 
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.unlink_file_at(&path),
+            d => d.dir()?,
+        };
         if !d.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted.into());
         }
@@ -739,7 +811,13 @@ where
         offset: types::Filesize,
     ) -> FsResult<Resource<InputStream>> {
         // Trap if fd lookup fails:
-        let f = self.table().get(&fd)?.file()?;
+        let f = match self.table().get(&fd)? {
+            Descriptor::Virtual(v) => {
+                let reader: InputStream = Box::new(v.read_via_stream(offset)?);
+                return Ok(self.table().push(reader)?);
+            }
+            d => d.file()?,
+        };
 
         if !f.perms.contains(FilePerms::READ) {
             Err(types::ErrorCode::BadDescriptor)?;
@@ -760,7 +838,13 @@ where
         offset: types::Filesize,
     ) -> FsResult<Resource<OutputStream>> {
         // Trap if fd lookup fails:
-        let f = self.table().get(&fd)?.file()?;
+        let f = match self.table().get(&fd)? {
+            Descriptor::Virtual(v) => {
+                let writer: OutputStream = Box::new(v.write_via_stream(Some(offset))?);
+                return Ok(self.table().push(writer)?);
+            }
+            d => d.file()?,
+        };
 
         if !f.perms.contains(FilePerms::WRITE) {
             Err(types::ErrorCode::BadDescriptor)?;
@@ -781,7 +865,13 @@ where
         fd: Resource<types::Descriptor>,
     ) -> FsResult<Resource<OutputStream>> {
         // Trap if fd lookup fails:
-        let f = self.table().get(&fd)?.file()?;
+        let f = match self.table().get(&fd)? {
+            Descriptor::Virtual(v) => {
+                let appender: OutputStream = Box::new(v.write_via_stream(None)?);
+                return Ok(self.table().push(appender)?);
+            }
+            d => d.file()?,
+        };
 
         if !f.perms.contains(FilePerms::WRITE) {
             Err(types::ErrorCode::BadDescriptor)?;
@@ -803,6 +893,11 @@ where
         b: Resource<types::Descriptor>,
     ) -> anyhow::Result<bool> {
         use cap_fs_ext::MetadataExt;
+        let virtual_a = self.table().get(&a)?.virtual_identity();
+        let virtual_b = self.table().get(&b)?.virtual_identity();
+        if virtual_a.is_some() || virtual_b.is_some() {
+            return Ok(virtual_a == virtual_b);
+        }
         let descriptor_a = self.table().get(&a)?;
         let meta_a = get_descriptor_metadata(descriptor_a).await?;
         let descriptor_b = self.table().get(&b)?;
@@ -829,6 +924,9 @@ where
         fd: Resource<types::Descriptor>,
     ) -> FsResult<types::MetadataHashValue> {
         let descriptor_a = self.table().get(&fd)?;
+        if let Descriptor::Virtual(v) = descriptor_a {
+            return Ok(v.metadata_hash());
+        }
         let meta = get_descriptor_metadata(descriptor_a).await?;
         Ok(calculate_metadata_hash(&meta))
     }
@@ -839,7 +937,10 @@ where
         path: String,
     ) -> FsResult<types::MetadataHashValue> {
         let table = self.table();
-        let d = table.get(&fd)?.dir()?;
+        let d = match table.get(&fd)? {
+            Descriptor::Virtual(v) => return v.metadata_hash_at(&path),
+            d => d.dir()?,
+        };
         // No permissions check on metadata: if dir opened, allowed to stat it
         let meta = d
             .run_blocking(move |d| {
@@ -884,6 +985,7 @@ async fn get_descriptor_metadata(fd: &types::Descriptor) -> FsResult<cap_std::fs
             // No permissions check on metadata: if opened, allowed to stat it
             Ok(d.run_blocking(|d| d.dir_metadata()).await?)
         }
+        Descriptor::Virtual(_) => Err(ErrorCode::Unsupported.into()),
     }
 }
 
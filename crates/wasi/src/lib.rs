@@ -209,7 +209,10 @@ mod write_stream;
 pub use self::clocks::{HostMonotonicClock, HostWallClock};
 pub use self::ctx::{WasiCtx, WasiCtxBuilder, WasiImpl, WasiView};
 pub use self::error::{I32Exit, TrappableError};
-pub use self::filesystem::{DirPerms, FileInputStream, FilePerms, FsError, FsResult};
+pub use self::filesystem::{
+    DirPerms, FileInputStream, FilePerms, FsError, FsResult, MemoryFilesystem, OverlayFilesystem,
+    VirtualFilesystem,
+};
 pub use self::network::{Network, SocketAddrUse, SocketError, SocketResult};
 pub use self::poll::{subscribe, ClosureFuture, MakeFuture, Pollable, PollableFuture, Subscribe};
 pub use self::random::{thread_rng, Deterministic};
@@ -594,6 +594,11 @@ impl WasiP1Ctx {
                 let pos = position.load(Ordering::Relaxed);
                 let append = *append;
                 drop(t);
+                if let crate::filesystem::Descriptor::Virtual(_) = self.table().get(&fd)? {
+                    return self
+                        .fd_write_virtual(memory, fd, ciovs, write, append, position)
+                        .await;
+                }
                 let f = self.table().get(&fd)?.file()?;
                 let buf = first_non_empty_ciovec(memory, ciovs)?;
 
@@ -656,6 +661,32 @@ impl WasiP1Ctx {
             _ => Err(types::Errno::Badf.into()),
         }
     }
+
+    /// Implementation of `fd_write_impl` for files in a virtual filesystem,
+    /// which are written through the preview2 implementation.
+    async fn fd_write_virtual(
+        &mut self,
+        memory: &mut GuestMemory<'_>,
+        fd: Resource<filesystem::Descriptor>,
+        ciovs: types::CiovecArray,
+        write: FdWrite,
+        append: bool,
+        position: Arc<AtomicU64>,
+    ) -> Result<types::Size, types::Error> {
+        let buf = first_non_empty_ciovec(memory, ciovs)?;
+        let buf = memory.to_vec(buf)?;
+        let offset = match (append, write) {
+            (true, _) => self.as_wasi_impl().stat(fd.borrowed()).await?.size,
+            (false, FdWrite::At(pos)) => pos,
+            (false, FdWrite::AtCur) => position.load(Ordering::Relaxed),
+        };
+        let nwritten = self.as_wasi_impl().write(fd, buf, offset).await?;
+        if let FdWrite::AtCur = write {
+            let pos = offset.checked_add(nwritten).ok_or(types::Errno::Overflow)?;
+            position.store(pos, Ordering::Relaxed);
+        }
+        Ok(nwritten.try_into()?)
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -1637,6 +1668,17 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for WasiP1Ctx {
                 let position = position.clone();
                 drop(t);
                 let pos = position.load(Ordering::Relaxed);
+                if let crate::filesystem::Descriptor::Virtual(_) = self.table().get(&fd)? {
+                    let iov = first_non_empty_iovec(memory, iovs)?;
+                    let (buf, _) = self.as_wasi_impl().read(fd, iov.len().into(), pos).await?;
+                    let iov = iov.get_range(0..u32::try_from(buf.len())?).unwrap();
+                    memory.copy_from_slice(&buf, iov)?;
+                    let pos = pos
+                        .checked_add(buf.len().try_into()?)
+                        .ok_or(types::Errno::Overflow)?;
+                    position.store(pos, Ordering::Relaxed);
+                    return Ok(buf.len().try_into()?);
+                }
                 let file = self.table().get(&fd)?.file()?;
                 let iov = first_non_empty_iovec(memory, iovs)?;
                 let bytes_read = match (file.as_blocking_file(), memory.as_slice_mut(iov)?) {
@@ -2150,12 +2192,18 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for WasiP1Ctx {
                 fd,
                 preopen_path: None,
             },
-            crate::filesystem::Descriptor::File(_) => Descriptor::File(File {
+            crate::filesystem::Descriptor::Virtual(v) if v.is_dir() => Descriptor::Directory {
                 fd,
-                position: Default::default(),
-                append: fdflags.contains(types::Fdflags::APPEND),
-                blocking_mode: BlockingMode::from_fdflags(&fdflags),
-            }),
+                preopen_path: None,
+            },
+            crate::filesystem::Descriptor::File(_) | crate::filesystem::Descriptor::Virtual(_) => {
+                Descriptor::File(File {
+                    fd,
+                    position: Default::default(),
+                    append: fdflags.contains(types::Fdflags::APPEND),
+                    blocking_mode: BlockingMode::from_fdflags(&fdflags),
+                })
+            }
         };
         let fd = t.descriptors.push(desc)?;
         Ok(fd.into())
@@ -1,8 +1,10 @@
 use super::*;
 use std::path::Path;
+use std::sync::Arc;
 use test_programs_artifacts::*;
-use wasmtime::{Linker, Module};
+use wasmtime::{Linker, Module, Store};
 use wasmtime_wasi::preview1::add_to_linker_async;
+use wasmtime_wasi::{MemoryFilesystem, VirtualFilesystem};
 
 async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
     let path = Path::new(path);
@@ -25,6 +27,198 @@ async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
     Ok(())
 }
 
+// Drives WASIp1 calls against a virtual preopen directly, through a module
+// which wraps the functions it imports.
+#[test_log::test]
+fn preview1_virtual_preopen() -> Result<()> {
+    const PATH: usize = 1024;
+    const IOV: usize = 0;
+    const RESULT: usize = 16;
+    const BUF: usize = 2048;
+    const RIGHTS_READ: i64 = 1 << 1;
+    const RIGHTS_WRITE: i64 = 1 << 6;
+    const OFLAGS_CREAT: i32 = 1 << 0;
+    const OFLAGS_EXCL: i32 = 1 << 2;
+
+    let engine = test_programs_artifacts::engine(|_| {});
+    let mut linker = Linker::<Ctx>::new(&engine);
+    wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |t| &mut t.wasi)?;
+    let module = Module::new(
+        &engine,
+        wat::parse_str(
+            r#"
+                (module
+                    (import "wasi_snapshot_preview1" "path_open"
+                        (func $path_open
+                            (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+                    (import "wasi_snapshot_preview1" "fd_read"
+                        (func $fd_read (param i32 i32 i32 i32) (result i32)))
+                    (import "wasi_snapshot_preview1" "fd_write"
+                        (func $fd_write (param i32 i32 i32 i32) (result i32)))
+                    (func (export "path_open")
+                        (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)
+                        (call $path_open
+                            (local.get 0) (local.get 1) (local.get 2) (local.get 3)
+                            (local.get 4) (local.get 5) (local.get 6) (local.get 7)
+                            (local.get 8)))
+                    (func (export "fd_read") (param i32 i32 i32 i32) (result i32)
+                        (call $fd_read
+                            (local.get 0) (local.get 1) (local.get 2) (local.get 3)))
+                    (func (export "fd_write") (param i32 i32 i32 i32) (result i32)
+                        (call $fd_write
+                            (local.get 0) (local.get 1) (local.get 2) (local.get 3)))
+                    (memory (export "memory") 1)
+                )
+            "#,
+        )?,
+    )?;
+
+    let fs = Arc::new(MemoryFilesystem::new());
+    let greeting = fs.create_file(fs.root(), "greeting", true).unwrap();
+    fs.write(greeting, 0, b"hello").unwrap();
+    let contents = |node| fs.read(node, 0, u64::MAX).unwrap().to_vec();
+
+    let (mut store, _td) = store(&engine, "preview1_virtual_preopen", |builder| {
+        builder
+            .preopened_virtual_dir(fs.clone(), "/virtual", DirPerms::all(), FilePerms::all())
+            .preopened_virtual_dir(fs.clone(), "/read-only", DirPerms::READ, FilePerms::READ);
+    })?;
+    // The workspace is preopened first, as fd 3.
+    let (virtual_dir, read_only_dir) = (4, 5);
+
+    let instance = linker.instantiate(&mut store, &module)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    let path_open = instance.get_typed_func::<(i32, i32, i32, i32, i32, i64, i64, i32, i32), i32>(
+        &mut store,
+        "path_open",
+    )?;
+    let fd_read = instance.get_typed_func::<(i32, i32, i32, i32), i32>(&mut store, "fd_read")?;
+    let fd_write = instance.get_typed_func::<(i32, i32, i32, i32), i32>(&mut store, "fd_write")?;
+
+    let result = |store: &mut Store<Ctx>| {
+        let mut buf = [0; 4];
+        memory.read(&*store, RESULT, &mut buf).unwrap();
+        u32::from_le_bytes(buf)
+    };
+    let open = |store: &mut Store<Ctx>, dir: i32, path: &str, oflags: i32, rights: i64| {
+        memory.write(&mut *store, PATH, path.as_bytes()).unwrap();
+        let errno = path_open
+            .call(
+                &mut *store,
+                (
+                    dir,
+                    0,
+                    PATH as i32,
+                    path.len() as i32,
+                    oflags,
+                    rights,
+                    0,
+                    0,
+                    RESULT as i32,
+                ),
+            )
+            .unwrap();
+        match errno {
+            0 => Ok(result(store) as i32),
+            errno => Err(errno),
+        }
+    };
+    let set_iov = |store: &mut Store<Ctx>, len: usize| {
+        let mut iov = [0; 8];
+        iov[..4].copy_from_slice(&(BUF as u32).to_le_bytes());
+        iov[4..].copy_from_slice(&(len as u32).to_le_bytes());
+        memory.write(&mut *store, IOV, &iov).unwrap();
+    };
+    let read = |store: &mut Store<Ctx>, fd: i32| {
+        set_iov(store, 64);
+        let errno = fd_read
+            .call(&mut *store, (fd, IOV as i32, 1, RESULT as i32))
+            .unwrap();
+        if errno != 0 {
+            return Err(errno);
+        }
+        let n = result(store) as usize;
+        Ok(memory.data(&*store)[BUF..][..n].to_vec())
+    };
+    let write = |store: &mut Store<Ctx>, fd: i32, data: &[u8]| {
+        memory.write(&mut *store, BUF, data).unwrap();
+        set_iov(store, data.len());
+        let errno = fd_write
+            .call(&mut *store, (fd, IOV as i32, 1, RESULT as i32))
+            .unwrap();
+        match errno {
+            0 => Ok(result(store)),
+            errno => Err(errno),
+        }
+    };
+
+    // Reads and writes go through the file's position, and are visible in
+    // the filesystem.
+    let fd = open(
+        &mut store,
+        virtual_dir,
+        "greeting",
+        0,
+        RIGHTS_READ | RIGHTS_WRITE,
+    )
+    .unwrap();
+    assert_eq!(read(&mut store, fd).unwrap(), b"hello");
+    assert_eq!(read(&mut store, fd).unwrap(), b"");
+    assert_eq!(write(&mut store, fd, b", world").unwrap(), 7);
+    assert_eq!(contents(greeting), b"hello, world");
+
+    let fd = open(
+        &mut store,
+        virtual_dir,
+        "new",
+        OFLAGS_CREAT | OFLAGS_EXCL,
+        RIGHTS_WRITE,
+    )
+    .unwrap();
+    assert_eq!(write(&mut store, fd, b"data").unwrap(), 4);
+    assert_eq!(write(&mut store, fd, b"more").unwrap(), 4);
+    assert_eq!(contents(fs.lookup(fs.root(), "new").unwrap()), b"datamore");
+    assert!(read(&mut store, fd).is_err());
+    assert!(open(
+        &mut store,
+        virtual_dir,
+        "new",
+        OFLAGS_CREAT | OFLAGS_EXCL,
+        RIGHTS_WRITE
+    )
+    .is_err());
+
+    // Paths can't escape the preopen.
+    for path in ["../greeting", "/greeting", "./../greeting"] {
+        assert!(open(&mut store, virtual_dir, path, 0, RIGHTS_READ).is_err());
+    }
+
+    // The same filesystem preopened read-only can be read but not written.
+    let fd = open(&mut store, read_only_dir, "greeting", 0, RIGHTS_READ).unwrap();
+    assert_eq!(read(&mut store, fd).unwrap(), b"hello, world");
+    assert!(write(&mut store, fd, b"!").is_err());
+    assert!(open(&mut store, read_only_dir, "greeting", 0, RIGHTS_WRITE).is_err());
+    assert!(open(
+        &mut store,
+        read_only_dir,
+        "other",
+        OFLAGS_CREAT,
+        RIGHTS_READ
+    )
+    .is_err());
+    assert_eq!(contents(greeting), b"hello, world");
+    assert_eq!(
+        fs.read_dir(fs.root())
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<_>>(),
+        ["greeting", "new"]
+    );
+
+    Ok(())
+}
+
 foreach_preview1!(assert_test_exists);
 
 // Below here is mechanical: there should be one test for every binary in
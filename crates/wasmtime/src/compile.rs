@@ -47,6 +47,8 @@ pub use self::code_builder::{CodeBuilder, CodeHint, HashedEngineCompileEnv};
 
 #[cfg(feature = "runtime")]
 mod runtime;
+#[cfg(all(feature = "runtime", feature = "cranelift", feature = "winch"))]
+pub(crate) use self::runtime::compile_optimized_module;
 
 /// Converts an input binary-encoded WebAssembly module to compilation
 /// artifacts and type information.
@@ -64,6 +66,29 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     engine: &Engine,
     wasm: &[u8],
     dwarf_package: Option<&[u8]>,
+) -> Result<(T, Option<(CompiledModuleInfo, ModuleTypes)>)> {
+    build_module_artifacts(engine, engine.compiler(), wasm, dwarf_package)
+}
+
+/// Same as [`build_artifacts`] except that the module is compiled with the
+/// optimizing compiler of an engine using `Strategy::Tiered`, producing the
+/// optimized code that hot functions are switched over to.
+#[cfg(all(feature = "runtime", feature = "cranelift", feature = "winch"))]
+pub(crate) fn build_optimized_artifacts<T: FinishedObject>(
+    engine: &Engine,
+    wasm: &[u8],
+) -> Result<(T, Option<(CompiledModuleInfo, ModuleTypes)>)> {
+    let compiler = engine
+        .optimizing_compiler()
+        .ok_or_else(|| anyhow!("engine is not configured for tiered compilation"))?;
+    build_module_artifacts(engine, compiler, wasm, None)
+}
+
+fn build_module_artifacts<T: FinishedObject>(
+    engine: &Engine,
+    compiler: &dyn Compiler,
+    wasm: &[u8],
+    dwarf_package: Option<&[u8]>,
 ) -> Result<(T, Option<(CompiledModuleInfo, ModuleTypes)>)> {
     let tunables = engine.tunables();
 
@@ -80,14 +105,13 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
         .context("failed to parse WebAssembly module")?;
     let functions = mem::take(&mut translation.function_body_inputs);
 
-    let compile_inputs =
-        CompileInputs::for_module(engine.compiler().triple(), &types, &translation, functions);
-    let unlinked_compile_outputs = compile_inputs.compile(engine)?;
+    let compile_inputs = CompileInputs::for_module(&types, &translation, functions);
+    let unlinked_compile_outputs = compile_inputs.compile(engine, compiler)?;
     let (compiled_funcs, function_indices) = unlinked_compile_outputs.pre_link();
 
     // Emplace all compiled functions into the object file with any other
     // sections associated with code as well.
-    let mut object = engine.compiler().object(ObjectKind::Module)?;
+    let mut object = compiler.object(ObjectKind::Module)?;
     // Insert `Engine` and type-level information into the compiled
     // artifact so if this module is deserialized later it contains all
     // information necessary.
@@ -104,6 +128,7 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
         &types,
         object,
         engine,
+        compiler,
         compiled_funcs,
         std::iter::once(translation).collect(),
         dwarf_package,
@@ -155,7 +180,7 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
             (i, &*translation, functions)
         }),
     );
-    let unlinked_compile_outputs = compile_inputs.compile(&engine)?;
+    let unlinked_compile_outputs = compile_inputs.compile(&engine, compiler)?;
 
     let (compiled_funcs, function_indices) = unlinked_compile_outputs.pre_link();
 
@@ -167,6 +192,7 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
         types.module_types_builder(),
         object,
         engine,
+        compiler,
         compiled_funcs,
         module_translations,
         None, // TODO: Support dwarf packages for components.
@@ -324,9 +350,6 @@ struct CompileOutput {
 /// The collection of things we need to compile for a Wasm module or component.
 #[derive(Default)]
 struct CompileInputs<'a> {
-    // Whether or not we need to compile wasm-to-native trampolines.
-    need_wasm_to_array_trampolines: bool,
-
     inputs: Vec<CompileInput<'a>>,
 }
 
@@ -337,18 +360,11 @@ impl<'a> CompileInputs<'a> {
 
     /// Create the `CompileInputs` for a core Wasm module.
     fn for_module(
-        triple: &target_lexicon::Triple,
         types: &'a ModuleTypesBuilder,
         translation: &'a ModuleTranslation<'a>,
         functions: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'a>>,
     ) -> Self {
-        let mut ret = CompileInputs {
-            need_wasm_to_array_trampolines: !matches!(
-                triple.architecture,
-                target_lexicon::Architecture::Pulley32 | target_lexicon::Architecture::Pulley64
-            ),
-            inputs: vec![],
-        };
+        let mut ret = CompileInputs { inputs: vec![] };
 
         let module_index = StaticModuleIndex::from_u32(0);
         ret.collect_inputs_in_translations(types, [(module_index, translation, functions)]);
@@ -370,14 +386,7 @@ impl<'a> CompileInputs<'a> {
             ),
         >,
     ) -> Self {
-        let triple = engine.compiler().triple();
-        let mut ret = CompileInputs {
-            need_wasm_to_array_trampolines: !matches!(
-                triple.architecture,
-                target_lexicon::Architecture::Pulley32 | target_lexicon::Architecture::Pulley64
-            ),
-            inputs: vec![],
-        };
+        let mut ret = CompileInputs { inputs: vec![] };
 
         ret.collect_inputs_in_translations(types.module_types_builder(), module_translations);
         let tunables = engine.tunables();
@@ -518,36 +527,31 @@ impl<'a> CompileInputs<'a> {
             }
         }
 
-        if self.need_wasm_to_array_trampolines {
-            let mut trampoline_types_seen = HashSet::new();
-            for (_func_type_index, trampoline_type_index) in types.trampoline_types() {
-                let is_new = trampoline_types_seen.insert(trampoline_type_index);
-                if !is_new {
-                    continue;
-                }
-                let trampoline_func_ty = types[trampoline_type_index].unwrap_func();
-                self.push_input(move |compiler| {
-                    let trampoline =
-                        compiler.compile_wasm_to_array_trampoline(trampoline_func_ty)?;
-                    Ok(CompileOutput {
-                        key: CompileKey::wasm_to_array_trampoline(trampoline_type_index),
-                        symbol: format!(
-                            "signatures[{}]::wasm_to_array_trampoline",
-                            trampoline_type_index.as_u32()
-                        ),
-                        function: CompiledFunction::Function(trampoline),
-                        info: None,
-                    })
-                });
+        let mut trampoline_types_seen = HashSet::new();
+        for (_func_type_index, trampoline_type_index) in types.trampoline_types() {
+            let is_new = trampoline_types_seen.insert(trampoline_type_index);
+            if !is_new {
+                continue;
             }
+            let trampoline_func_ty = types[trampoline_type_index].unwrap_func();
+            self.push_input(move |compiler| {
+                let trampoline = compiler.compile_wasm_to_array_trampoline(trampoline_func_ty)?;
+                Ok(CompileOutput {
+                    key: CompileKey::wasm_to_array_trampoline(trampoline_type_index),
+                    symbol: format!(
+                        "signatures[{}]::wasm_to_array_trampoline",
+                        trampoline_type_index.as_u32()
+                    ),
+                    function: CompiledFunction::Function(trampoline),
+                    info: None,
+                })
+            });
         }
     }
 
     /// Compile these `CompileInput`s (maybe in parallel) and return the
     /// resulting `UnlinkedCompileOutput`s.
-    fn compile(self, engine: &Engine) -> Result<UnlinkedCompileOutputs> {
-        let compiler = engine.compiler();
-
+    fn compile(self, engine: &Engine, compiler: &dyn Compiler) -> Result<UnlinkedCompileOutputs> {
         // Compile each individual input in parallel.
         let mut raw_outputs = engine.run_maybe_parallel(self.inputs, |f| f(compiler))?;
 
@@ -555,7 +559,7 @@ impl<'a> CompileInputs<'a> {
         // wasmtime-builtin functions are necessary. If so those need to be
         // collected and then those trampolines additionally need to be
         // compiled.
-        compile_required_builtins(engine, &mut raw_outputs)?;
+        compile_required_builtins(engine, compiler, &mut raw_outputs)?;
 
         // Bucket the outputs by kind.
         let mut outputs: BTreeMap<u32, Vec<CompileOutput>> = BTreeMap::new();
@@ -567,8 +571,11 @@ impl<'a> CompileInputs<'a> {
     }
 }
 
-fn compile_required_builtins(engine: &Engine, raw_outputs: &mut Vec<CompileOutput>) -> Result<()> {
-    let compiler = engine.compiler();
+fn compile_required_builtins(
+    engine: &Engine,
+    compiler: &dyn Compiler,
+    raw_outputs: &mut Vec<CompileOutput>,
+) -> Result<()> {
     let mut builtins = HashSet::new();
     let mut new_inputs: Vec<CompileInput<'_>> = Vec::new();
 
@@ -684,6 +691,7 @@ impl FunctionIndices {
         types: &ModuleTypesBuilder,
         mut obj: object::write::Object<'static>,
         engine: &'a Engine,
+        compiler: &dyn Compiler,
         compiled_funcs: Vec<(String, Box<dyn Any + Send>)>,
         translations: PrimaryMap<StaticModuleIndex, ModuleTranslation<'_>>,
         dwarf_package_bytes: Option<&[u8]>,
@@ -693,7 +701,6 @@ impl FunctionIndices {
         // The result is a vector parallel to `compiled_funcs` where
         // `symbol_ids_and_locs[i]` is the symbol ID and function location of
         // `compiled_funcs[i]`.
-        let compiler = engine.compiler();
         let tunables = engine.tunables();
         let symbol_ids_and_locs = compiler.append_code(
             &mut obj,
@@ -720,6 +727,9 @@ impl FunctionIndices {
                 RelocationTarget::HostLibcall(_) => {
                     unreachable!("relocation is resolved at runtime, not compile time");
                 }
+                RelocationTarget::PulleyHostcall(_) => {
+                    unreachable!("relocation is resolved when appending the function");
+                }
             },
         )?;
 
@@ -831,29 +841,23 @@ impl FunctionIndices {
                         })
                         .collect();
 
-                let wasm_to_array_trampolines = match engine.compiler().triple().architecture {
-                    target_lexicon::Architecture::Pulley32
-                    | target_lexicon::Architecture::Pulley64 => vec![],
-                    _ => {
-                        let unique_and_sorted_trampoline_sigs = translation
-                            .module
-                            .types
-                            .iter()
-                            .map(|(_, ty)| *ty)
-                            .filter(|idx| types[*idx].is_func())
-                            .map(|idx| types.trampoline_type(idx))
-                            .collect::<BTreeSet<_>>();
-                        unique_and_sorted_trampoline_sigs
-                            .iter()
-                            .map(|idx| {
-                                let trampoline = types.trampoline_type(*idx);
-                                let key = CompileKey::wasm_to_array_trampoline(trampoline);
-                                let compiled = wasm_to_array_trampolines[&key];
-                                (*idx, symbol_ids_and_locs[compiled.unwrap_function()].1)
-                            })
-                            .collect()
-                    }
-                };
+                let unique_and_sorted_trampoline_sigs = translation
+                    .module
+                    .types
+                    .iter()
+                    .map(|(_, ty)| *ty)
+                    .filter(|idx| types[*idx].is_func())
+                    .map(|idx| types.trampoline_type(idx))
+                    .collect::<BTreeSet<_>>();
+                let wasm_to_array_trampolines = unique_and_sorted_trampoline_sigs
+                    .iter()
+                    .map(|idx| {
+                        let trampoline = types.trampoline_type(*idx);
+                        let key = CompileKey::wasm_to_array_trampoline(trampoline);
+                        let compiled = wasm_to_array_trampolines[&key];
+                        (*idx, symbol_ids_and_locs[compiled.unwrap_function()].1)
+                    })
+                    .collect();
 
                 obj.append(translation, funcs, wasm_to_array_trampolines)
             })
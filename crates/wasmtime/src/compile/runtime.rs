@@ -37,7 +37,7 @@ impl<'a> CodeBuilder<'a> {
                         |(engine, wasm, dwarf_package, build_artifacts)| -> Result<_> {
                             let (mmap, info) =
                                 (build_artifacts.0)(engine.0, wasm, dwarf_package.as_deref())?;
-                            let code = publish_mmap(mmap.0)?;
+                            let code = publish_mmap(engine.0, mmap.0)?;
                             Ok((code, info))
                         },
                         // Implementation of how to serialize artifacts
@@ -62,7 +62,7 @@ impl<'a> CodeBuilder<'a> {
         {
             let (mmap, info_and_types) =
                 build_artifacts(self.engine, &wasm, dwarf_package.as_deref())?;
-            let code = publish_mmap(mmap.0)?;
+            let code = publish_mmap(self.engine, mmap.0)?;
             return Ok((code, info_and_types));
         }
 
@@ -80,7 +80,12 @@ impl<'a> CodeBuilder<'a> {
     /// enabled and turned on in [`Config`](crate::Config).
     pub fn compile_module(&self) -> Result<Module> {
         let (code, info_and_types) = self.compile_cached(super::build_artifacts)?;
-        Module::from_parts(self.engine, code, info_and_types)
+        let module = Module::from_parts(self.engine, code, info_and_types)?;
+        #[cfg(all(feature = "cranelift", feature = "winch"))]
+        if self.engine.optimizing_compiler().is_some() {
+            module.enable_tier_up(self.get_wasm()?);
+        }
+        Ok(module)
     }
 
     /// Same as [`CodeBuilder::compile_module`] except that it compiles a
@@ -92,8 +97,26 @@ impl<'a> CodeBuilder<'a> {
     }
 }
 
-fn publish_mmap(mmap: MmapVec) -> Result<Arc<CodeMemory>> {
+/// Compiles the optimizing tier of a module compiled with
+/// [`Strategy::Tiered`](crate::Strategy::Tiered) from its original `wasm`.
+///
+/// The optimized code lives in the same engine, and so shares its type
+/// registry, as the baseline module. It's never cached since it's only ever
+/// produced at runtime for modules that are already loaded.
+#[cfg(all(feature = "cranelift", feature = "winch"))]
+pub(crate) fn compile_optimized_module(engine: &Engine, wasm: &[u8]) -> Result<Module> {
+    let (mmap, info_and_types) = super::build_optimized_artifacts::<MmapVecWrapper>(engine, wasm)?;
+    let code = publish_mmap(engine, mmap.0)?;
+    let module = Module::from_parts(engine, code, info_and_types)?;
+    module.mark_optimized_tier();
+    Ok(module)
+}
+
+fn publish_mmap(engine: &Engine, mmap: MmapVec) -> Result<Arc<CodeMemory>> {
     let mut code = CodeMemory::new(mmap)?;
+    if engine.is_pulley() {
+        code.set_pulley();
+    }
     code.publish()?;
     Ok(Arc::new(code))
 }
@@ -31,6 +31,8 @@ use wasmtime_fiber::RuntimeFiberStackCreator;
 
 #[cfg(feature = "pooling-allocator")]
 pub use crate::runtime::vm::MpkEnabled;
+#[cfg(feature = "cache")]
+pub use wasmtime_cache::{CacheStorage, DirectoryStorage};
 #[cfg(all(feature = "incremental-cache", feature = "cranelift"))]
 pub use wasmtime_environ::CacheStore;
 
@@ -151,6 +153,7 @@ pub struct Config {
     pub(crate) coredump_on_trap: bool,
     pub(crate) macos_use_mach_ports: bool,
     pub(crate) detect_host_feature: Option<fn(&str) -> Option<bool>>,
+    pub(crate) tier_up_threshold: u32,
 }
 
 /// User-provided configuration for the compiler.
@@ -256,6 +259,7 @@ impl Config {
             detect_host_feature: Some(detect_host_feature),
             #[cfg(not(feature = "std"))]
             detect_host_feature: None,
+            tier_up_threshold: 10_000,
         };
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
@@ -410,6 +414,28 @@ impl Config {
         self
     }
 
+    /// Configures whether compiled code is instrumented for in-process guest
+    /// debugging.
+    ///
+    /// When enabled, a check is emitted before every WebAssembly instruction
+    /// which, while a [`Store`](crate::Store) has breakpoints set or is
+    /// single-stepping, pauses execution and invokes the store's debug
+    /// handler. This allows an embedder to implement a debugger for guest
+    /// code without a native debugger attached. See
+    /// [`Store::debug_handler`](crate::Store::debug_handler),
+    /// [`Store::add_breakpoint`](crate::Store::add_breakpoint), and
+    /// [`Store::single_step`](crate::Store::single_step).
+    ///
+    /// The instrumentation adds overhead to every instruction even when no
+    /// breakpoints are set, so this is intended for debugging only.
+    ///
+    /// By default this option is `false`.
+    /// **Note** Enabling this option is not compatible with the Winch compiler.
+    pub fn guest_debug(&mut self, enable: bool) -> &mut Self {
+        self.tunables.guest_debug = Some(enable);
+        self
+    }
+
     /// Configures whether [`WasmBacktrace`] will be present in the context of
     /// errors returned from Wasmtime.
     ///
@@ -1040,6 +1066,20 @@ impl Config {
         self
     }
 
+    /// Configures how many calls and loop iterations a function compiled with
+    /// [`Strategy::Tiered`] performs before it's recompiled with Cranelift.
+    ///
+    /// Each instance counts down from this threshold for each of its
+    /// functions. When a function's counter reaches zero the module is
+    /// recompiled with Cranelift on a background thread, if it isn't already,
+    /// and once that finishes the function's optimized code is swapped in.
+    ///
+    /// The default value for this is 10,000.
+    pub fn tier_up_threshold(&mut self, threshold: u32) -> &mut Self {
+        self.tier_up_threshold = threshold;
+        self
+    }
+
     /// Configures which garbage collector will be used for Wasm modules.
     ///
     /// This method can be used to configure which garbage collector
@@ -1131,6 +1171,25 @@ impl Config {
         self
     }
 
+    /// Configures whether Cranelift inlines calls to small functions.
+    ///
+    /// When enabled, direct calls to functions defined in the same core wasm
+    /// module are replaced with the callee's body if it is small enough. This
+    /// includes calls between the functions of the adapter modules generated
+    /// for components. Calls to imported functions and indirect calls are
+    /// never inlined.
+    ///
+    /// Traps and backtraces in inlined code are attributed to the call site
+    /// in the caller. This option has no effect when
+    /// [`Config::debug_info`] is enabled.
+    ///
+    /// The default value for this is `false`.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn cranelift_inlining(&mut self, enable: bool) -> &mut Self {
+        self.tunables.inlining = Some(enable);
+        self
+    }
+
     /// Controls whether proof-carrying code (PCC) is used to validate
     /// lowering of Wasm sandbox checks.
     ///
@@ -1209,7 +1268,9 @@ impl Config {
     /// due to I/O errors, misconfiguration, syntax errors, etc. For expected
     /// syntax in the configuration file see the [documentation online][docs].
     ///
-    /// By default cache configuration is not enabled or loaded.
+    /// By default cache configuration is not enabled or loaded. A storage set
+    /// with [`Config::cache_storage`] is kept unless the loaded configuration
+    /// disables caching.
     ///
     /// This method is only available when the `cache` feature of this crate is
     /// enabled.
@@ -1222,10 +1283,51 @@ impl Config {
     /// [docs]: https://bytecodealliance.github.io/wasmtime/cli-cache.html
     #[cfg(feature = "cache")]
     pub fn cache_config_load(&mut self, path: impl AsRef<Path>) -> Result<&mut Self> {
-        self.cache_config = CacheConfig::from_file(Some(path.as_ref()))?;
+        self.replace_cache_config(CacheConfig::from_file(Some(path.as_ref()))?);
         Ok(self)
     }
 
+    /// Stores the compilation cache's entries in `storage`.
+    ///
+    /// By default cached compilation artifacts are stored in the cache
+    /// directory configured with [`Config::cache_config_load`]. This method
+    /// instead stores them in `storage`, which embedders can implement to
+    /// share compiled artifacts between processes or machines, for example
+    /// with [`DirectoryStorage`] on a network file system or with a blob
+    /// server. Entries read from `storage` are checked for integrity and
+    /// recompiled if they were corrupted.
+    ///
+    /// If caching isn't enabled yet then this enables it with default
+    /// settings. Otherwise the compression settings of the loaded cache
+    /// configuration are kept, while settings which only apply to the cache
+    /// directory, such as its cleanup, have no effect on `storage`.
+    ///
+    /// The storage is kept when cache configuration is loaded afterwards with
+    /// [`Config::cache_config_load`] or [`Config::cache_config_load_default`],
+    /// unless the loaded configuration disables caching, and is removed by
+    /// [`Config::disable_cache`].
+    ///
+    /// This method is only available when the `cache` feature of this crate is
+    /// enabled.
+    #[cfg(feature = "cache")]
+    pub fn cache_storage(&mut self, storage: Arc<dyn CacheStorage>) -> &mut Self {
+        self.cache_config.set_storage(storage);
+        self
+    }
+
+    /// Replaces the cache configuration with `cache_config`, keeping the
+    /// storage set with [`Config::cache_storage`] if caching stays enabled.
+    #[cfg(feature = "cache")]
+    fn replace_cache_config(&mut self, cache_config: CacheConfig) {
+        let storage = self.cache_config.storage().cloned();
+        self.cache_config = cache_config;
+        if let Some(storage) = storage {
+            if self.cache_config.enabled() {
+                self.cache_config.set_storage(storage);
+            }
+        }
+    }
+
     /// Disable caching.
     ///
     /// Every call to [`Module::new(my_wasm)`][crate::Module::new] will
@@ -1250,7 +1352,9 @@ impl Config {
     /// Unix at `$HOME/.config/wasmtime/config.toml` and is typically created
     /// with the `wasmtime config new` command.
     ///
-    /// By default cache configuration is not enabled or loaded.
+    /// By default cache configuration is not enabled or loaded. A storage set
+    /// with [`Config::cache_storage`] is kept unless the loaded configuration
+    /// disables caching.
     ///
     /// This method is only available when the `cache` feature of this crate is
     /// enabled.
@@ -1265,7 +1369,7 @@ impl Config {
     /// [docs]: https://bytecodealliance.github.io/wasmtime/cli-cache.html
     #[cfg(feature = "cache")]
     pub fn cache_config_load_default(&mut self) -> Result<&mut Self> {
-        self.cache_config = CacheConfig::from_file(None)?;
+        self.replace_cache_config(CacheConfig::from_file(None)?);
         Ok(self)
     }
 
@@ -1875,22 +1979,18 @@ impl Config {
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         match self.compiler_config.strategy {
             None | Some(Strategy::Cranelift) => WasmFeatures::empty(),
-            Some(Strategy::Winch) => {
+            // Tiered compilation compiles everything with Winch first, so it
+            // has the same limitations.
+            Some(Strategy::Winch) | Some(Strategy::Tiered) => {
                 let mut unsupported = WasmFeatures::GC
                     | WasmFeatures::FUNCTION_REFERENCES
                     | WasmFeatures::THREADS
-                    | WasmFeatures::RELAXED_SIMD
                     | WasmFeatures::TAIL_CALL
                     | WasmFeatures::GC_TYPES;
                 match self.compiler_target().architecture {
                     target_lexicon::Architecture::Aarch64(_) => {
                         // no support for simd on aarch64
-                        unsupported |= WasmFeatures::SIMD;
-
-                        // things like multi-table are technically supported on
-                        // winch on aarch64 but this helps gate most spec tests
-                        // by default which otherwise currently cause panics.
-                        unsupported |= WasmFeatures::REFERENCE_TYPES;
+                        unsupported |= WasmFeatures::SIMD | WasmFeatures::RELAXED_SIMD;
                     }
 
                     // Winch doesn't support other non-x64 architectures at this
@@ -2036,10 +2136,14 @@ impl Config {
 
         self.tunables.configure(&mut tunables);
 
-        // If we're going to compile with winch, we must use the winch calling convention.
+        // If we're going to compile with winch, we must use the winch calling
+        // convention. This includes Cranelift code for the optimizing tier of
+        // tiered compilation, which is called from Winch code.
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
-            tunables.winch_callable = self.compiler_config.strategy == Some(Strategy::Winch);
+            let strategy = self.compiler_config.strategy;
+            tunables.winch_callable = matches!(strategy, Some(Strategy::Winch | Strategy::Tiered));
+            tunables.tiered_compilation = strategy == Some(Strategy::Tiered);
         }
 
         tunables.collector = if features.gc_types() {
@@ -2049,6 +2153,7 @@ impl Config {
                 Some(match self.collector.try_not_auto()? {
                     Collector::DeferredReferenceCounting => EnvCollector::DeferredReferenceCounting,
                     Collector::Null => EnvCollector::Null,
+                    Collector::Copying => EnvCollector::Copying,
                     Collector::Auto => unreachable!(),
                 })
             }
@@ -2109,7 +2214,7 @@ impl Config {
 
         #[cfg(feature = "gc")]
         #[cfg_attr(
-            not(any(feature = "gc-null", feature = "gc-drc")),
+            not(any(feature = "gc-null", feature = "gc-drc", feature = "gc-copying")),
             allow(unused_variables, unreachable_code)
         )]
         {
@@ -2128,6 +2233,13 @@ impl Config {
                 #[cfg(not(feature = "gc-null"))]
                 Collector::Null => unreachable!(),
 
+                #[cfg(feature = "gc-copying")]
+                Collector::Copying => {
+                    Arc::new(crate::runtime::vm::CopyingCollector::default()) as Arc<dyn GcRuntime>
+                }
+                #[cfg(not(feature = "gc-copying"))]
+                Collector::Copying => unreachable!(),
+
                 Collector::Auto => unreachable!(),
             }))
         }
@@ -2160,14 +2272,16 @@ impl Config {
             Some(Strategy::Winch) => wasmtime_winch::builder(target)?,
             #[cfg(not(feature = "winch"))]
             Some(Strategy::Winch) => bail!("winch support not compiled in"),
+            #[cfg(all(feature = "cranelift", feature = "winch"))]
+            Some(Strategy::Tiered) => wasmtime_winch::builder(target)?,
+            #[cfg(not(all(feature = "cranelift", feature = "winch")))]
+            Some(Strategy::Tiered) => {
+                bail!("tiered compilation requires both cranelift and winch support")
+            }
 
             None | Some(Strategy::Auto) => unreachable!(),
         };
 
-        if let Some(path) = &self.compiler_config.clif_dir {
-            compiler.clif_dir(path)?;
-        }
-
         // If probestack is enabled for a target, Wasmtime will always use the
         // inline strategy which doesn't require us to define a `__probestack`
         // function or similar.
@@ -2254,6 +2368,39 @@ impl Config {
             bail!("cannot disable the simd proposal but enable the relaxed simd proposal");
         }
 
+        self.apply_compiler_config(&mut *compiler, tunables)?;
+
+        Ok((self, compiler.build()?))
+    }
+
+    /// Builds the Cranelift compiler which recompiles hot functions for an
+    /// engine using [`Strategy::Tiered`].
+    ///
+    /// This must be called on the configuration returned by
+    /// [`Config::build_compiler`] so that both compilers are configured the
+    /// same way.
+    #[cfg(all(feature = "runtime", feature = "cranelift", feature = "winch"))]
+    pub(crate) fn build_optimizing_compiler(
+        &self,
+        tunables: &Tunables,
+    ) -> Result<Box<dyn wasmtime_environ::Compiler>> {
+        let mut compiler = wasmtime_cranelift::builder(self.compiler_config.target.clone())?;
+        self.apply_compiler_config(&mut *compiler, tunables)?;
+        compiler.build()
+    }
+
+    /// Applies the compiler settings and flags of this configuration to
+    /// `compiler`.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    fn apply_compiler_config(
+        &self,
+        compiler: &mut dyn wasmtime_environ::CompilerBuilder,
+        tunables: &Tunables,
+    ) -> Result<()> {
+        if let Some(path) = &self.compiler_config.clif_dir {
+            compiler.clif_dir(path)?;
+        }
+
         // Apply compiler settings and flags
         for (k, v) in self.compiler_config.settings.iter() {
             compiler.set(k, v)?;
@@ -2269,8 +2416,7 @@ impl Config {
 
         compiler.set_tunables(tunables.clone())?;
         compiler.wmemcheck(self.compiler_config.wmemcheck);
-
-        Ok((self, compiler.build()?))
+        Ok(())
     }
 
     /// Internal setting for whether adapter modules for components will have
@@ -2456,6 +2602,23 @@ pub enum Strategy {
     /// A baseline compiler for WebAssembly, currently under active development and not ready for
     /// production applications.
     Winch,
+
+    /// Compiles modules with Winch for fast startup and recompiles them with
+    /// Cranelift in the background once their functions become hot.
+    ///
+    /// Winch code counts function calls and loop iterations, see
+    /// [`Config::tier_up_threshold`]. Once the Cranelift code is available,
+    /// hot functions are switched over to it for subsequent calls, both from
+    /// within their instance and through function references, such as calls
+    /// from the host, `call_indirect`, and `call_ref`. Calls already running
+    /// finish in the code they started in, and calls to functions imported
+    /// from another instance use the code the import was linked to. Modules
+    /// loaded from precompiled artifacts are not recompiled.
+    ///
+    /// This has the same limitations on supported WebAssembly proposals as
+    /// [`Strategy::Winch`], and requires both the `cranelift` and `winch`
+    /// features.
+    Tiered,
 }
 
 impl Strategy {
@@ -2486,6 +2649,7 @@ impl Strategy {
 /// |-----------------------------|----------------------|-------------|----------------|----------------------|----------------------|
 /// | `DeferredReferenceCounting` | Yes, but not cycles  | 🙂         | 🙁             | 😐                   | 😐                  |
 /// | `Null`                      | No                   | 🙂         | 🙂             | 🙂                   | 🙂                  |
+/// | `Copying`                   | Yes                  | 🙁         | 🙂             | 🙂                   | 🙁                  |
 ///
 /// [^1]: Whether or not the collector is capable of collecting garbage and cyclic garbage.
 ///
@@ -2550,6 +2714,19 @@ pub enum Collector {
     /// collectors, as this collector imposes as close to zero throughput and
     /// latency overhead as possible.
     Null,
+
+    /// The semi-space copying collector.
+    ///
+    /// A tracing collector that divides the GC heap into two halves and bump
+    /// allocates objects in one of them. When that half is full, every object
+    /// reachable from the GC roots is copied into the other half and the halves
+    /// swap roles. Unreachable objects, including cycles, are reclaimed without
+    /// ever being visited, so the cost of a collection is proportional to the
+    /// amount of live data rather than the size of the heap.
+    ///
+    /// Collections pause the Wasm program until they complete, and only half
+    /// of the GC heap is available for objects at any given time.
+    Copying,
 }
 
 impl Default for Collector {
@@ -2564,6 +2741,8 @@ impl Collector {
             Collector::Auto => {
                 if cfg!(feature = "gc-drc") {
                     Some(Collector::DeferredReferenceCounting)
+                } else if cfg!(feature = "gc-copying") {
+                    Some(Collector::Copying)
                 } else if cfg!(feature = "gc-null") {
                     Some(Collector::Null)
                 } else {
@@ -2593,12 +2772,20 @@ impl Collector {
                  the `gc-null` feature was not enabled at compile time",
             ),
 
+            #[cfg(feature = "gc-copying")]
+            Some(c @ Collector::Copying) => Ok(c),
+            #[cfg(not(feature = "gc-copying"))]
+            Some(Collector::Copying) => bail!(
+                "cannot create an engine using the copying collector because \
+                 the `gc-copying` feature was not enabled at compile time",
+            ),
+
             Some(Collector::Auto) => unreachable!(),
 
             None => bail!(
                 "cannot create an engine with GC support when none of the \
                  collectors are available; enable one of the following \
-                 features: `gc-drc`, `gc-null`",
+                 features: `gc-drc`, `gc-null`, `gc-copying`",
             ),
         }
     }
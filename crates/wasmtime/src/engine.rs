@@ -62,6 +62,10 @@ struct EngineInner {
     #[cfg(feature = "runtime")]
     epoch: AtomicU64,
 
+    /// The compiler used to recompile hot functions with `Strategy::Tiered`.
+    #[cfg(all(feature = "runtime", feature = "cranelift", feature = "winch"))]
+    optimizing_compiler: Option<Box<dyn wasmtime_environ::Compiler>>,
+
     /// One-time check of whether the compiler's settings, if present, are
     /// compatible with the native host.
     #[cfg(any(feature = "cranelift", feature = "winch"))]
@@ -105,6 +109,13 @@ impl Engine {
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         let (config, compiler) = config.build_compiler(&tunables, features)?;
 
+        #[cfg(all(feature = "runtime", feature = "cranelift", feature = "winch"))]
+        let optimizing_compiler = if tunables.tiered_compilation {
+            Some(config.build_optimizing_compiler(&tunables)?)
+        } else {
+            None
+        };
+
         Ok(Engine {
             inner: Arc::new(EngineInner {
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
@@ -119,6 +130,8 @@ impl Engine {
                 signatures: TypeRegistry::new(),
                 #[cfg(feature = "runtime")]
                 epoch: AtomicU64::new(0),
+                #[cfg(all(feature = "runtime", feature = "cranelift", feature = "winch"))]
+                optimizing_compiler,
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
                 compatible_with_native_host: OnceLock::new(),
                 config,
@@ -139,6 +152,13 @@ impl Engine {
         self.inner.features
     }
 
+    /// Returns the compiler used to recompile hot functions when this engine
+    /// uses `Strategy::Tiered`.
+    #[cfg(all(feature = "runtime", feature = "cranelift", feature = "winch"))]
+    pub(crate) fn optimizing_compiler(&self) -> Option<&dyn wasmtime_environ::Compiler> {
+        self.inner.optimizing_compiler.as_deref()
+    }
+
     pub(crate) fn run_maybe_parallel<
         A: Send,
         B: Send,
@@ -231,6 +251,15 @@ impl Engine {
         return target_lexicon::Triple::host();
     }
 
+    /// Returns whether this engine produces Pulley bytecode, which is
+    /// executed by an interpreter, rather than native machine code.
+    pub(crate) fn is_pulley(&self) -> bool {
+        matches!(
+            self.target().architecture,
+            target_lexicon::Architecture::Pulley32 | target_lexicon::Architecture::Pulley64
+        )
+    }
+
     /// Verify that this engine's configuration is compatible with loading
     /// modules onto the native host platform.
     ///
@@ -258,9 +287,19 @@ impl Engine {
         {
             let compiler = self.compiler();
 
-            // Check to see that the config's target matches the host
+            // Check to see that the config's target matches the host. Pulley
+            // bytecode can be interpreted on any host so long as pointers are
+            // the same size.
             let target = compiler.triple();
-            if *target != target_lexicon::Triple::host() {
+            let host = target_lexicon::Triple::host();
+            if self.is_pulley() {
+                if target.pointer_width() != host.pointer_width() {
+                    return Err(format!(
+                        "target '{target}' specified in the configuration does not match \
+                         the pointer width of the host"
+                    ));
+                }
+            } else if *target != host {
                 return Err(format!(
                     "target '{target}' specified in the configuration does not match the host"
                 ));
@@ -270,8 +309,13 @@ impl Engine {
             for (key, value) in compiler.flags().iter() {
                 self.check_compatible_with_shared_flag(key, value)?;
             }
-            for (key, value) in compiler.isa_flags().iter() {
-                self.check_compatible_with_isa_flag(key, value)?;
+
+            // Pulley's ISA flags describe the bytecode rather than features
+            // of the host CPU, so there's nothing to detect for them.
+            if !self.is_pulley() {
+                for (key, value) in compiler.isa_flags().iter() {
+                    self.check_compatible_with_isa_flag(key, value)?;
+                }
             }
         }
         Ok(())
@@ -358,6 +402,8 @@ impl Engine {
             | "stack_switch_model" // wasmtime doesn't use stack switching right now
             | "opt_level" // opt level doesn't change semantics
             | "enable_alias_analysis" // alias analysis-based opts don't change semantics
+            | "enable_strength_reduction" // loop opts don't change semantics
+            | "enable_loop_unrolling"
             | "probestack_size_log2" // probestack above asserted disabled
             | "regalloc" // shouldn't change semantics
             | "enable_incremental_compilation_cache_checks" // shouldn't change semantics
@@ -584,6 +630,16 @@ impl Engine {
         crate::runtime::vm::tls_eager_initialize();
     }
 
+    /// Returns a handle to metrics about the slots in use by this engine's
+    /// pooling allocator.
+    ///
+    /// Returns `None` if this engine wasn't configured to use
+    /// [`InstanceAllocationStrategy::Pooling`](crate::InstanceAllocationStrategy::Pooling).
+    #[cfg(feature = "pooling-allocator")]
+    pub fn pooling_allocator_metrics(&self) -> Option<crate::PoolingAllocatorMetrics> {
+        crate::runtime::vm::PoolingAllocatorMetrics::new(self)
+    }
+
     pub(crate) fn allocator(&self) -> &dyn crate::runtime::vm::InstanceAllocator {
         self.inner.allocator.as_ref()
     }
@@ -711,6 +767,9 @@ impl Engine {
     ) -> Result<Arc<crate::CodeMemory>> {
         serialization::check_compatible(self, &mmap, expected)?;
         let mut code = crate::CodeMemory::new(mmap)?;
+        if self.is_pulley() {
+            code.set_pulley();
+        }
         code.publish()?;
         Ok(Arc::new(code))
     }
@@ -326,6 +326,10 @@ impl Metadata<'_> {
     }
 
     fn check_isa_flags(&mut self, engine: &Engine) -> Result<()> {
+        // Pulley bytecode doesn't depend on any host CPU features.
+        if engine.is_pulley() {
+            return Ok(());
+        }
         for (name, val) in self.isa_flags.iter() {
             engine
                 .check_compatible_with_isa_flag(name, val)
@@ -370,11 +374,13 @@ impl Metadata<'_> {
             parse_wasm_debuginfo,
             consume_fuel,
             epoch_interruption,
+            guest_debug,
             memory_may_move,
             guard_before_linear_memory,
             table_lazy_init,
             relaxed_simd_deterministic,
             winch_callable,
+            tiered_compilation,
             signals_based_traps,
             // This doesn't affect compilation, it's just a runtime setting.
             memory_reservation_for_growth: _,
@@ -387,6 +393,9 @@ impl Metadata<'_> {
 
             // Just a debugging aid, doesn't affect functionality at all.
             debug_adapter_modules: _,
+
+            // An optimization which doesn't change the ABI of compiled code.
+            inlining: _,
         } = self.tunables;
 
         Self::check_collector(collector, other.collector)?;
@@ -416,6 +425,7 @@ impl Metadata<'_> {
             other.epoch_interruption,
             "epoch interruption",
         )?;
+        Self::check_bool(guest_debug, other.guest_debug, "guest debugging")?;
         Self::check_bool(memory_may_move, other.memory_may_move, "memory may move")?;
         Self::check_bool(
             guard_before_linear_memory,
@@ -433,6 +443,11 @@ impl Metadata<'_> {
             other.winch_callable,
             "Winch calling convention",
         )?;
+        Self::check_bool(
+            tiered_compilation,
+            other.tiered_compilation,
+            "tiered compilation",
+        )?;
         Self::check_bool(
             signals_based_traps,
             other.signals_based_traps,
@@ -799,6 +814,85 @@ Caused by:
         );
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn cache_shared_through_storage() -> Result<()> {
+        use crate::DirectoryStorage;
+
+        let td = TempDir::new()?;
+        let storage = std::sync::Arc::new(DirectoryStorage::new(td.path()));
+        let mut cfg = Config::new();
+        cfg.cache_storage(storage.clone());
+        let engine = Engine::new(&cfg)?;
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(engine.config().cache_config.cache_hits(), 0);
+        assert_eq!(engine.config().cache_config.cache_misses(), 1);
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(engine.config().cache_config.cache_hits(), 1);
+        assert_eq!(engine.config().cache_config.cache_misses(), 1);
+
+        // A separate engine sharing the storage, e.g. in another process,
+        // finds the same entry.
+        let mut cfg = Config::new();
+        cfg.cache_storage(storage);
+        let engine = Engine::new(&cfg)?;
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(engine.config().cache_config.cache_hits(), 1);
+        assert_eq!(engine.config().cache_config.cache_misses(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn cache_storage_kept_across_config_load() -> Result<()> {
+        use crate::DirectoryStorage;
+
+        let td = TempDir::new()?;
+        let storage = std::sync::Arc::new(DirectoryStorage::new(td.path().join("storage")));
+        let write_config = |enabled: bool| -> Result<std::path::PathBuf> {
+            let path = td.path().join(format!("config-{enabled}.toml"));
+            std::fs::write(
+                &path,
+                &format!(
+                    "
+                        [cache]
+                        enabled = {enabled}
+                        directory = '{}'
+                    ",
+                    td.path().join("cache").display()
+                ),
+            )?;
+            Ok(path)
+        };
+
+        // Loading a configuration after setting the storage keeps the storage.
+        let mut cfg = Config::new();
+        cfg.cache_storage(storage.clone())
+            .cache_config_load(write_config(true)?)?;
+        assert!(cfg.cache_config.storage().is_some());
+        let engine = Engine::new(&cfg)?;
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(engine.config().cache_config.cache_misses(), 1);
+
+        // So the entry went to the storage, where another engine finds it.
+        let mut cfg = Config::new();
+        cfg.cache_storage(storage.clone());
+        let engine = Engine::new(&cfg)?;
+        Module::new(&engine, "(module (func))")?;
+        assert_eq!(engine.config().cache_config.cache_hits(), 1);
+        assert_eq!(engine.config().cache_config.cache_misses(), 0);
+
+        // Loading a configuration which disables caching drops the storage.
+        let mut cfg = Config::new();
+        cfg.cache_storage(storage)
+            .cache_config_load(write_config(false)?)?;
+        assert!(!cfg.cache_config.enabled());
+        assert!(cfg.cache_config.storage().is_none());
+
+        Ok(())
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn cache_accounts_for_opt_level() -> Result<()> {
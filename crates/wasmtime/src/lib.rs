@@ -266,6 +266,11 @@
 //!   with the same overhead as the `call-hook` feature where entries/exits into
 //!   WebAssembly will have more overhead than before.
 //!
+//! * `wave` - Disabled by default, this implements the traits of the
+//!   [`wasm-wave`](https://docs.rs/wasm-wave) crate for component values and
+//!   types, re-exported as `wasmtime::component::wasm_wave`, to parse and print
+//!   them in the WebAssembly Value Encoding.
+//!
 //! More crate features can be found in the [manifest] of Wasmtime itself for
 //! seeing what can be enabled and disabled.
 //!
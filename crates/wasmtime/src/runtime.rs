@@ -32,6 +32,7 @@ pub(crate) mod func;
 pub(crate) mod code;
 pub(crate) mod code_memory;
 pub(crate) mod debug;
+pub(crate) mod debugger;
 pub(crate) mod externals;
 pub(crate) mod gc;
 pub(crate) mod instance;
@@ -41,6 +42,7 @@ pub(crate) mod linker;
 pub(crate) mod memory;
 pub(crate) mod module;
 pub(crate) mod resources;
+pub(crate) mod snapshot;
 pub(crate) mod store;
 pub(crate) mod trampoline;
 pub(crate) mod trap;
@@ -67,6 +69,7 @@ cfg_if::cfg_if! {
 }
 
 pub use code_memory::CodeMemory;
+pub use debugger::{DebugAction, DebugFrame};
 pub use externals::*;
 pub use func::*;
 pub use gc::*;
@@ -77,6 +80,7 @@ pub use linker::*;
 pub use memory::*;
 pub use module::{Module, ModuleExport};
 pub use resources::*;
+pub use snapshot::InstanceSnapshot;
 #[cfg(all(feature = "async", feature = "call-hook"))]
 pub use store::CallHookHandler;
 pub use store::{
@@ -90,7 +94,7 @@ pub use values::*;
 pub(crate) use uninhabited::*;
 
 #[cfg(feature = "pooling-allocator")]
-pub use vm::PoolConcurrencyLimitError;
+pub use vm::{PoolConcurrencyLimitError, PoolingAllocatorMetrics};
 
 #[cfg(feature = "profiling")]
 mod profiling;
@@ -20,6 +20,7 @@ pub struct CodeMemory {
     debug_registration: Option<crate::runtime::vm::GdbJitImageRegistration>,
     published: bool,
     enable_branch_protection: bool,
+    is_pulley: bool,
     #[cfg(feature = "debug-builtins")]
     has_native_debug_info: bool,
 
@@ -139,6 +140,7 @@ impl CodeMemory {
             #[cfg(feature = "debug-builtins")]
             debug_registration: None,
             published: false,
+            is_pulley: false,
             enable_branch_protection: enable_branch_protection
                 .ok_or_else(|| anyhow!("missing `{}` section", obj::ELF_WASM_BTI))?,
             #[cfg(feature = "debug-builtins")]
@@ -155,6 +157,15 @@ impl CodeMemory {
         })
     }
 
+    /// Marks this image as containing Pulley bytecode.
+    ///
+    /// Bytecode is interpreted rather than executed so `publish` leaves it
+    /// readonly and doesn't register any native unwinding information for
+    /// it.
+    pub(crate) fn set_pulley(&mut self) {
+        self.is_pulley = true;
+    }
+
     /// Returns a reference to the underlying `MmapVec` this memory owns.
     #[inline]
     pub fn mmap(&self) -> &MmapVec {
@@ -168,6 +179,14 @@ impl CodeMemory {
         &self.mmap[self.text.clone()]
     }
 
+    /// Returns the offsets, relative to the start of the text section, of the
+    /// pointer-sized relocations which are filled in with host addresses when
+    /// this image is published.
+    #[inline]
+    pub fn relocation_offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.relocations.iter().map(|(offset, _)| *offset)
+    }
+
     /// Returns the contents of the `ELF_WASMTIME_DWARF` section.
     #[inline]
     pub fn wasm_dwarf(&self) -> &[u8] {
@@ -255,6 +274,10 @@ impl CodeMemory {
             // otherwise written to the image at any point either.
             self.mmap.make_readonly(0..self.mmap.len())?;
 
+            if self.is_pulley {
+                return Ok(());
+            }
+
             let text = self.text();
 
             // Clear the newly allocated code from cache if the processor requires it
@@ -377,6 +377,11 @@ impl Component {
         code_memory: Arc<CodeMemory>,
         artifacts: Option<ComponentArtifacts>,
     ) -> Result<Component> {
+        // Component trampolines call native host functions directly which
+        // Pulley bytecode can't do yet.
+        if engine.is_pulley() {
+            bail!("components are not yet supported when executing Pulley bytecode");
+        }
         let ComponentArtifacts {
             ty,
             info,
@@ -444,6 +449,12 @@ impl Component {
         &self.inner.static_modules[idx]
     }
 
+    /// Returns all core modules defined within this component.
+    #[cfg(feature = "profiling")]
+    pub(crate) fn static_modules(&self) -> impl ExactSizeIterator<Item = &Module> + '_ {
+        self.inner.static_modules.values()
+    }
+
     #[inline]
     pub(crate) fn types(&self) -> &Arc<ComponentTypes> {
         self.inner.component_types()
@@ -36,6 +36,7 @@ union ParamsAndResults<Params: Copy, Return: Copy> {
 /// [`wasmtime::Func`](crate::Func) it's possible to call functions either
 /// synchronously or asynchronously and either typed or untyped.
 #[derive(Copy, Clone, Debug)]
+#[repr(transparent)] // here for the C API
 pub struct Func(Stored<FuncData>);
 
 #[doc(hidden)]
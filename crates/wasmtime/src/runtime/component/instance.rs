@@ -1,7 +1,8 @@
 use crate::component::func::HostFunc;
 use crate::component::matching::InstanceType;
 use crate::component::{
-    Component, ComponentExportIndex, ComponentNamedList, Func, Lift, Lower, ResourceType, TypedFunc,
+    Component, ComponentExportIndex, ComponentNamedList, Func, InstanceSnapshot, Lift, Lower,
+    ResourceType, TypedFunc,
 };
 use crate::instance::OwnedImports;
 use crate::linker::DefinitionType;
@@ -31,6 +32,7 @@ use wasmtime_environ::{EntityIndex, EntityType, Global, PrimaryMap, WasmValType}
 /// [`wasmtime::Instance`](crate::Instance) except that it represents an
 /// instantiated component instead of an instantiated module.
 #[derive(Copy, Clone)]
+#[repr(transparent)] // here for the C API
 pub struct Instance(pub(crate) Stored<Option<Box<InstanceData>>>);
 
 pub(crate) struct InstanceData {
@@ -294,6 +296,56 @@ impl Instance {
         self._get_export(store.as_context_mut().0, instance, name)
     }
 
+    /// Captures a snapshot of the state of this instance.
+    ///
+    /// This is the component model equivalent of
+    /// [`wasmtime::Instance::snapshot`](crate::Instance::snapshot) and the
+    /// returned [`InstanceSnapshot`] records the state of every core wasm
+    /// instance within this component instance. It can later be restored into
+    /// a new instance of the same component with
+    /// [`InstancePre::instantiate_snapshot`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this instance contains state which cannot be
+    /// snapshotted. See the documentation of [`InstanceSnapshot`] for more
+    /// details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    pub fn snapshot(&self, mut store: impl AsContextMut) -> Result<InstanceSnapshot> {
+        let mut store = store.as_context_mut();
+        let data = store.0[self.0].as_ref().unwrap();
+        let state = data.instance();
+        if state.has_resource_handles() {
+            bail!("cannot snapshot a component instance which holds resource handles");
+        }
+        let env_component = data.component.env_component();
+        for i in 0..env_component.num_runtime_component_instances {
+            let flags = state.instance_flags(RuntimeComponentInstanceIndex::from_u32(i));
+            // SAFETY: the flags are owned by `state`, which is alive for the
+            // duration of this function.
+            //
+            // Note that `may_enter` is cleared while the instance is being
+            // called, until `post_return` has run, and permanently once the
+            // instance has trapped.
+            if unsafe { !flags.may_enter() } {
+                bail!(
+                    "cannot snapshot a component instance which is being called, \
+                     has a pending `post_return`, or has trapped"
+                );
+            }
+        }
+
+        let instances = data.instances.values().copied().collect::<Vec<_>>();
+        let snapshots = instances
+            .iter()
+            .map(|instance| instance.snapshot(&mut store))
+            .collect::<Result<_>>()?;
+        Ok(InstanceSnapshot::new(snapshots))
+    }
+
     fn _get_export(
         &self,
         store: &StoreOpaque,
@@ -457,6 +509,9 @@ struct Instantiator<'a> {
     data: InstanceData,
     core_imports: OwnedImports,
     imports: &'a PrimaryMap<RuntimeImportIndex, RuntimeImport>,
+    /// Snapshot to restore the state of each core wasm instance from, instead
+    /// of running their start functions.
+    snapshot: Option<&'a InstanceSnapshot>,
 }
 
 pub(crate) enum RuntimeImport {
@@ -490,6 +545,7 @@ impl<'a> Instantiator<'a> {
         component: &'a Component,
         store: &mut StoreOpaque,
         imports: &'a Arc<PrimaryMap<RuntimeImportIndex, RuntimeImport>>,
+        snapshot: Option<&'a InstanceSnapshot>,
     ) -> Instantiator<'a> {
         let env_component = component.env_component();
         store.modules_mut().register_component(component);
@@ -498,6 +554,7 @@ impl<'a> Instantiator<'a> {
         Instantiator {
             component,
             imports,
+            snapshot,
             core_imports: OwnedImports::empty(),
             data: InstanceData {
                 instances: PrimaryMap::with_capacity(env_component.num_runtime_instances as usize),
@@ -549,6 +606,9 @@ impl<'a> Instantiator<'a> {
         for initializer in env_component.initializers.iter() {
             match initializer {
                 GlobalInitializer::InstantiateModule(m) => {
+                    let snapshot = self
+                        .snapshot
+                        .map(|s| &s.instances()[self.data.instances.len()]);
                     let module;
                     let imports = match m {
                         // Since upvars are statically know we know that the
@@ -585,10 +645,21 @@ impl<'a> Instantiator<'a> {
                     //
                     // Also note we are calling new_started_impl because we have
                     // already checked for asyncness and are running on a fiber
-                    // if required.
-
-                    let i = unsafe {
-                        crate::Instance::new_started_impl(store, module, imports.as_ref())?
+                    // if required. When restoring from a snapshot the start
+                    // function is skipped as its effects are captured by the
+                    // snapshot.
+                    let i = match snapshot {
+                        Some(snapshot) => unsafe {
+                            crate::Instance::new_restored_impl(
+                                store,
+                                module,
+                                imports.as_ref(),
+                                snapshot,
+                            )?
+                        },
+                        None => unsafe {
+                            crate::Instance::new_started_impl(store, module, imports.as_ref())?
+                        },
                     };
                     self.data.instances.push(i);
                 }
@@ -801,7 +872,7 @@ impl<T> InstancePre<T> {
             !store.as_context().async_support(),
             "must use async instantiation when async support is enabled"
         );
-        self.instantiate_impl(store)
+        self.instantiate_impl(store, None)
     }
     /// Performs the instantiation process into the store specified.
     ///
@@ -821,16 +892,84 @@ impl<T> InstancePre<T> {
             store.0.async_support(),
             "must use sync instantiation when async support is disabled"
         );
-        store.on_fiber(|store| self.instantiate_impl(store)).await?
+        store
+            .on_fiber(|store| self.instantiate_impl(store, None))
+            .await?
     }
 
-    fn instantiate_impl(&self, mut store: impl AsContextMut<Data = T>) -> Result<Instance> {
+    /// Instantiates this component within the provided `store`, restoring its
+    /// state from `snapshot` instead of running the `start` functions of its
+    /// core wasm modules.
+    ///
+    /// This is the component model equivalent of
+    /// [`wasmtime::InstancePre::instantiate_snapshot`](crate::InstancePre::instantiate_snapshot).
+    /// The `snapshot` must have been created with [`Instance::snapshot`] from
+    /// an instance of the same component that this [`InstancePre`]
+    /// instantiates, although that instance may have lived in a different
+    /// [`Store`](crate::Store).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if instantiation fails or if `snapshot` does not
+    /// describe an instance of this component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` has async support enabled.
+    pub fn instantiate_snapshot(
+        &self,
+        store: impl AsContextMut<Data = T>,
+        snapshot: &InstanceSnapshot,
+    ) -> Result<Instance> {
+        assert!(
+            !store.as_context().async_support(),
+            "must use async instantiation when async support is enabled"
+        );
+        self.instantiate_impl(store, Some(snapshot))
+    }
+
+    /// Same as [`InstancePre::instantiate_snapshot`], but for use with
+    /// [asynchronous stores](crate::Config::async_support).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not have async support enabled.
+    #[cfg(feature = "async")]
+    pub async fn instantiate_snapshot_async(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        snapshot: &InstanceSnapshot,
+    ) -> Result<Instance>
+    where
+        T: Send,
+    {
         let mut store = store.as_context_mut();
+        assert!(
+            store.0.async_support(),
+            "must use sync instantiation when async support is disabled"
+        );
+        store
+            .on_fiber(|store| self.instantiate_impl(store, Some(snapshot)))
+            .await?
+    }
+
+    fn instantiate_impl(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        snapshot: Option<&InstanceSnapshot>,
+    ) -> Result<Instance> {
+        let mut store = store.as_context_mut();
+        if let Some(snapshot) = snapshot {
+            let expected = self.component.env_component().num_runtime_instances;
+            if snapshot.instances().len() != expected as usize {
+                bail!("snapshot was not taken from an instance of this component");
+            }
+        }
         store
             .engine()
             .allocator()
             .increment_component_instance_count()?;
-        let mut instantiator = Instantiator::new(&self.component, store.0, &self.imports);
+        let mut instantiator = Instantiator::new(&self.component, store.0, &self.imports, snapshot);
         instantiator.run(&mut store).map_err(|e| {
             store
                 .engine()
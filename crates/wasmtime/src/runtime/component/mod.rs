@@ -107,10 +107,13 @@ mod linker;
 mod matching;
 mod resource_table;
 mod resources;
+mod snapshot;
 mod storage;
 mod store;
 pub mod types;
 mod values;
+#[cfg(feature = "wave")]
+mod wave;
 pub use self::component::{Component, ComponentExportIndex};
 pub use self::func::{
     ComponentNamedList, ComponentType, Func, Lift, Lower, TypedFunc, WasmList, WasmStr,
@@ -119,8 +122,11 @@ pub use self::instance::{Instance, InstanceExportLookup, InstancePre};
 pub use self::linker::{Linker, LinkerInstance};
 pub use self::resource_table::{ResourceTable, ResourceTableError};
 pub use self::resources::{Resource, ResourceAny};
+pub use self::snapshot::InstanceSnapshot;
 pub use self::types::{ResourceType, Type};
 pub use self::values::Val;
+#[cfg(feature = "wave")]
+pub use wasm_wave;
 
 pub(crate) use self::resources::HostResourceData;
 
@@ -56,6 +56,22 @@ impl ResourceType {
         }
     }
 
+    /// Creates a new host resource type which is identified by the `payload`
+    /// integer rather than a Rust type.
+    ///
+    /// This is intended for embeddings, such as the C API, where host
+    /// resources can't be described with a static Rust type. Two types
+    /// created with this function are the same if their `payload` is the same,
+    /// and they are never equal to a type created with [`ResourceType::host`].
+    ///
+    /// Values of this type are created with
+    /// [`ResourceAny::try_from_host_dynamic`].
+    pub fn host_dynamic(payload: u32) -> ResourceType {
+        ResourceType {
+            kind: ResourceTypeKind::HostDynamic(payload),
+        }
+    }
+
     pub(crate) fn guest(
         store: StoreId,
         instance: &ComponentInstance,
@@ -83,6 +99,7 @@ impl ResourceType {
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum ResourceTypeKind {
     Host(TypeId),
+    HostDynamic(u32),
     Guest {
         store: StoreId,
         // For now this is the `*mut ComponentInstance` pointer within the store
@@ -936,6 +953,68 @@ impl ResourceAny {
         })
     }
 
+    /// Creates a new host resource of the type [`ResourceType::host_dynamic`]
+    /// with the given `payload`.
+    ///
+    /// This is the dynamically-typed equivalent of creating a `Resource<T>`
+    /// with [`Resource::new_own`] or [`Resource::new_borrow`] and converting
+    /// it with [`ResourceAny::try_from_resource`]. The `rep` is the
+    /// host-defined representation of the resource and `owned` indicates
+    /// whether an owned or a borrowed handle is created.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host resource table of `store` is full.
+    pub fn try_from_host_dynamic(
+        mut store: impl AsContextMut,
+        payload: u32,
+        rep: u32,
+        owned: bool,
+    ) -> Result<Self> {
+        let store = store.as_context_mut();
+        let mut tables = HostResourceTables::new_host(store.0);
+        let idx = if owned {
+            tables.host_resource_lower_own(rep, None, None)?
+        } else {
+            tables.host_resource_lower_borrow(rep)?
+        };
+        Ok(Self {
+            idx,
+            ty: ResourceType::host_dynamic(payload),
+            owned,
+        })
+    }
+
+    /// Converts this resource into the host-defined representation it was
+    /// created with, the inverse of [`ResourceAny::try_from_host_dynamic`].
+    ///
+    /// Like [`Resource::try_from_resource_any`] this consumes the handle: an
+    /// owned handle is removed from the store and a borrowed handle has its
+    /// borrow released.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this resource's type is not
+    /// `ResourceType::host_dynamic(payload)` or if the handle is no longer
+    /// valid.
+    pub fn try_into_host_dynamic(self, mut store: impl AsContextMut, payload: u32) -> Result<u32> {
+        let store = store.as_context_mut();
+        let mut tables = HostResourceTables::new_host(store.0);
+        let ResourceAny { idx, ty, owned } = self;
+        ensure!(
+            ty == ResourceType::host_dynamic(payload),
+            "resource type mismatch"
+        );
+        if owned {
+            tables.host_resource_lift_own(idx)
+        } else {
+            let rep = tables.host_resource_lift_borrow(idx)?;
+            let res = tables.host_resource_drop(idx)?;
+            assert!(res.is_none());
+            Ok(rep)
+        }
+    }
+
     /// See [`Resource::try_from_resource_any`]
     pub fn try_into_resource<T: 'static>(self, store: impl AsContextMut) -> Result<Resource<T>> {
         Resource::try_from_resource_any(self, store)
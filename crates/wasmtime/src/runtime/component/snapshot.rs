@@ -0,0 +1,75 @@
+use crate::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+
+/// The version of the encoding produced by [`InstanceSnapshot::serialize`].
+///
+/// This must be bumped whenever the structure of `InstanceSnapshot` changes.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// A snapshot of the state of a component [`Instance`](super::Instance) which
+/// can be restored into a new instance of the same component.
+///
+/// This is the component model equivalent of
+/// [`wasmtime::InstanceSnapshot`](crate::InstanceSnapshot). A snapshot is
+/// created with [`Instance::snapshot`](super::Instance::snapshot) and records
+/// the state of each core wasm instance that the component instantiated. It's
+/// restored with
+/// [`InstancePre::instantiate_snapshot`](super::InstancePre::instantiate_snapshot)
+/// which instantiates the component without running the `start` functions of
+/// its core modules and then restores the state of each of them.
+///
+/// In addition to the restrictions documented on
+/// [`wasmtime::InstanceSnapshot`](crate::InstanceSnapshot), a component
+/// instance cannot be snapshotted while it holds any resource handles, while
+/// it's being called or has a pending
+/// [`TypedFunc::post_return`](super::TypedFunc::post_return), or after it has
+/// trapped.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InstanceSnapshot {
+    version: u32,
+    /// Snapshots of each core wasm instance, in the order that they were
+    /// instantiated.
+    instances: Vec<crate::InstanceSnapshot>,
+}
+
+impl InstanceSnapshot {
+    pub(crate) fn new(instances: Vec<crate::InstanceSnapshot>) -> InstanceSnapshot {
+        InstanceSnapshot {
+            version: SNAPSHOT_VERSION,
+            instances,
+        }
+    }
+
+    /// Returns the snapshots of each core wasm instance of the component.
+    pub(crate) fn instances(&self) -> &[crate::InstanceSnapshot] {
+        &self.instances
+    }
+
+    /// Serializes this snapshot into a blob of bytes.
+    ///
+    /// The returned bytes can be turned back into a snapshot with
+    /// [`InstanceSnapshot::deserialize`]. The encoding is not stable across
+    /// versions of Wasmtime.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        postcard::to_allocvec(self).err2anyhow()
+    }
+
+    /// Deserializes a snapshot previously created with
+    /// [`InstanceSnapshot::serialize`].
+    ///
+    /// Note that this only validates the encoding of the snapshot itself, and
+    /// it's only when the snapshot is restored with
+    /// [`InstancePre::instantiate_snapshot`](super::InstancePre::instantiate_snapshot)
+    /// that it's checked against the component being instantiated.
+    pub fn deserialize(bytes: &[u8]) -> Result<InstanceSnapshot> {
+        let snapshot: InstanceSnapshot = postcard::from_bytes(bytes).err2anyhow()?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            bail!(
+                "snapshot was created with an incompatible version of Wasmtime \
+                 (version {}, expected {SNAPSHOT_VERSION})",
+                snapshot.version
+            );
+        }
+        Ok(snapshot)
+    }
+}
@@ -0,0 +1,435 @@
+//! Implementations of the `wasm-wave` traits for component types and values,
+//! which enable parsing and printing [`Val`]s in the WebAssembly Value
+//! Encoding.
+
+use crate::component::types::{self, Type};
+use crate::component::Val;
+use crate::prelude::*;
+use alloc::borrow::Cow;
+use wasm_wave::wasm::{
+    ensure_type_kind, WasmFunc, WasmType, WasmTypeKind, WasmValue, WasmValueError,
+};
+
+impl WasmType for Type {
+    fn kind(&self) -> WasmTypeKind {
+        match self {
+            Type::Bool => WasmTypeKind::Bool,
+            Type::S8 => WasmTypeKind::S8,
+            Type::U8 => WasmTypeKind::U8,
+            Type::S16 => WasmTypeKind::S16,
+            Type::U16 => WasmTypeKind::U16,
+            Type::S32 => WasmTypeKind::S32,
+            Type::U32 => WasmTypeKind::U32,
+            Type::S64 => WasmTypeKind::S64,
+            Type::U64 => WasmTypeKind::U64,
+            Type::Float32 => WasmTypeKind::Float32,
+            Type::Float64 => WasmTypeKind::Float64,
+            Type::Char => WasmTypeKind::Char,
+            Type::String => WasmTypeKind::String,
+            Type::List(_) => WasmTypeKind::List,
+            Type::Record(_) => WasmTypeKind::Record,
+            Type::Tuple(_) => WasmTypeKind::Tuple,
+            Type::Variant(_) => WasmTypeKind::Variant,
+            Type::Enum(_) => WasmTypeKind::Enum,
+            Type::Option(_) => WasmTypeKind::Option,
+            Type::Result(_) => WasmTypeKind::Result,
+            Type::Flags(_) => WasmTypeKind::Flags,
+            // Resources have no textual representation.
+            Type::Own(_) | Type::Borrow(_) => WasmTypeKind::Unsupported,
+        }
+    }
+
+    fn list_element_type(&self) -> Option<Self> {
+        match self {
+            Type::List(list) => Some(list.ty()),
+            _ => None,
+        }
+    }
+
+    fn record_fields(&self) -> Box<dyn Iterator<Item = (Cow<'_, str>, Self)> + '_> {
+        match self {
+            Type::Record(record) => {
+                Box::new(record.fields().map(|f| (Cow::Borrowed(f.name), f.ty)))
+            }
+            _ => Box::new(core::iter::empty()),
+        }
+    }
+
+    fn tuple_element_types(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        match self {
+            Type::Tuple(tuple) => Box::new(tuple.types()),
+            _ => Box::new(core::iter::empty()),
+        }
+    }
+
+    fn variant_cases(&self) -> Box<dyn Iterator<Item = (Cow<'_, str>, Option<Self>)> + '_> {
+        match self {
+            Type::Variant(variant) => {
+                Box::new(variant.cases().map(|c| (Cow::Borrowed(c.name), c.ty)))
+            }
+            _ => Box::new(core::iter::empty()),
+        }
+    }
+
+    fn enum_cases(&self) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        match self {
+            Type::Enum(enum_) => Box::new(enum_.names().map(Cow::Borrowed)),
+            _ => Box::new(core::iter::empty()),
+        }
+    }
+
+    fn option_some_type(&self) -> Option<Self> {
+        match self {
+            Type::Option(option) => Some(option.ty()),
+            _ => None,
+        }
+    }
+
+    fn result_types(&self) -> Option<(Option<Self>, Option<Self>)> {
+        match self {
+            Type::Result(result) => Some((result.ok(), result.err())),
+            _ => None,
+        }
+    }
+
+    fn flags_names(&self) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        match self {
+            Type::Flags(flags) => Box::new(flags.names().map(Cow::Borrowed)),
+            _ => Box::new(core::iter::empty()),
+        }
+    }
+}
+
+impl WasmFunc for types::ComponentFunc {
+    type Type = Type;
+
+    fn params(&self) -> Box<dyn Iterator<Item = Self::Type> + '_> {
+        Box::new(types::ComponentFunc::params(self))
+    }
+
+    fn results(&self) -> Box<dyn Iterator<Item = Self::Type> + '_> {
+        Box::new(types::ComponentFunc::results(self))
+    }
+}
+
+/// `Val`s don't carry their type, so values built by the `make_*` methods
+/// are only checked against their type as far as their kind goes, and for
+/// the names of cases and fields. The rest is checked when they're passed to
+/// a component.
+impl WasmValue for Val {
+    type Type = Type;
+
+    fn kind(&self) -> WasmTypeKind {
+        match self {
+            Val::Bool(_) => WasmTypeKind::Bool,
+            Val::S8(_) => WasmTypeKind::S8,
+            Val::U8(_) => WasmTypeKind::U8,
+            Val::S16(_) => WasmTypeKind::S16,
+            Val::U16(_) => WasmTypeKind::U16,
+            Val::S32(_) => WasmTypeKind::S32,
+            Val::U32(_) => WasmTypeKind::U32,
+            Val::S64(_) => WasmTypeKind::S64,
+            Val::U64(_) => WasmTypeKind::U64,
+            Val::Float32(_) => WasmTypeKind::Float32,
+            Val::Float64(_) => WasmTypeKind::Float64,
+            Val::Char(_) => WasmTypeKind::Char,
+            Val::String(_) => WasmTypeKind::String,
+            Val::List(_) => WasmTypeKind::List,
+            Val::Record(_) => WasmTypeKind::Record,
+            Val::Tuple(_) => WasmTypeKind::Tuple,
+            Val::Variant(..) => WasmTypeKind::Variant,
+            Val::Enum(_) => WasmTypeKind::Enum,
+            Val::Option(_) => WasmTypeKind::Option,
+            Val::Result(_) => WasmTypeKind::Result,
+            Val::Flags(_) => WasmTypeKind::Flags,
+            Val::Resource(_) => WasmTypeKind::Unsupported,
+        }
+    }
+
+    fn make_bool(val: bool) -> Self {
+        Val::Bool(val)
+    }
+    fn make_s8(val: i8) -> Self {
+        Val::S8(val)
+    }
+    fn make_s16(val: i16) -> Self {
+        Val::S16(val)
+    }
+    fn make_s32(val: i32) -> Self {
+        Val::S32(val)
+    }
+    fn make_s64(val: i64) -> Self {
+        Val::S64(val)
+    }
+    fn make_u8(val: u8) -> Self {
+        Val::U8(val)
+    }
+    fn make_u16(val: u16) -> Self {
+        Val::U16(val)
+    }
+    fn make_u32(val: u32) -> Self {
+        Val::U32(val)
+    }
+    fn make_u64(val: u64) -> Self {
+        Val::U64(val)
+    }
+    fn make_float32(val: f32) -> Self {
+        Val::Float32(val)
+    }
+    fn make_float64(val: f64) -> Self {
+        Val::Float64(val)
+    }
+    fn make_char(val: char) -> Self {
+        Val::Char(val)
+    }
+    fn make_string(val: Cow<str>) -> Self {
+        Val::String(val.into())
+    }
+
+    fn make_list(
+        ty: &Self::Type,
+        vals: impl IntoIterator<Item = Self>,
+    ) -> Result<Self, WasmValueError> {
+        ensure_type_kind(ty, WasmTypeKind::List)?;
+        Ok(Val::List(vals.into_iter().collect()))
+    }
+
+    fn make_record<'a>(
+        ty: &Self::Type,
+        fields: impl IntoIterator<Item = (&'a str, Self)>,
+    ) -> Result<Self, WasmValueError> {
+        ensure_type_kind(ty, WasmTypeKind::Record)?;
+        // Fields may be written in any order, but `Val::Record` lists them in
+        // declaration order.
+        let mut vals: Vec<(&str, Self)> = fields.into_iter().collect();
+        let mut fields = Vec::with_capacity(vals.len());
+        for (name, _) in ty.record_fields() {
+            let i = vals
+                .iter()
+                .position(|(n, _)| *n == name)
+                .ok_or_else(|| WasmValueError::MissingField(name.to_string()))?;
+            let (_, val) = vals.swap_remove(i);
+            fields.push((name.into_owned(), val));
+        }
+        if let Some((name, _)) = vals.first() {
+            return Err(WasmValueError::UnknownField(name.to_string()));
+        }
+        Ok(Val::Record(fields))
+    }
+
+    fn make_tuple(
+        ty: &Self::Type,
+        vals: impl IntoIterator<Item = Self>,
+    ) -> Result<Self, WasmValueError> {
+        ensure_type_kind(ty, WasmTypeKind::Tuple)?;
+        let vals: Vec<Self> = vals.into_iter().collect();
+        let want = ty.tuple_element_types().count();
+        if vals.len() != want {
+            return Err(WasmValueError::WrongNumberOfTupleValues {
+                want,
+                got: vals.len(),
+            });
+        }
+        Ok(Val::Tuple(vals))
+    }
+
+    fn make_variant(
+        ty: &Self::Type,
+        case: &str,
+        val: Option<Self>,
+    ) -> Result<Self, WasmValueError> {
+        ensure_type_kind(ty, WasmTypeKind::Variant)?;
+        let payload = ty
+            .variant_cases()
+            .find(|(name, _)| name == case)
+            .ok_or_else(|| WasmValueError::UnknownCase(case.to_string()))?
+            .1;
+        match (payload, &val) {
+            (Some(_), None) => return Err(WasmValueError::MissingPayload(case.to_string())),
+            (None, Some(_)) => return Err(WasmValueError::UnexpectedPayload(case.to_string())),
+            _ => {}
+        }
+        Ok(Val::Variant(case.to_string(), val.map(Box::new)))
+    }
+
+    fn make_enum(ty: &Self::Type, case: &str) -> Result<Self, WasmValueError> {
+        ensure_type_kind(ty, WasmTypeKind::Enum)?;
+        if !ty.enum_cases().any(|name| name == case) {
+            return Err(WasmValueError::UnknownCase(case.to_string()));
+        }
+        Ok(Val::Enum(case.to_string()))
+    }
+
+    fn make_option(ty: &Self::Type, val: Option<Self>) -> Result<Self, WasmValueError> {
+        ensure_type_kind(ty, WasmTypeKind::Option)?;
+        Ok(Val::Option(val.map(Box::new)))
+    }
+
+    fn make_result(
+        ty: &Self::Type,
+        val: Result<Option<Self>, Option<Self>>,
+    ) -> Result<Self, WasmValueError> {
+        ensure_type_kind(ty, WasmTypeKind::Result)?;
+        Ok(Val::Result(match val {
+            Ok(val) => Ok(val.map(Box::new)),
+            Err(val) => Err(val.map(Box::new)),
+        }))
+    }
+
+    fn make_flags<'a>(
+        ty: &Self::Type,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, WasmValueError> {
+        ensure_type_kind(ty, WasmTypeKind::Flags)?;
+        let names: Vec<&str> = names.into_iter().collect();
+        if let Some(unknown) = names
+            .iter()
+            .find(|name| !ty.flags_names().any(|n| n == **name))
+        {
+            return Err(WasmValueError::UnknownCase(unknown.to_string()));
+        }
+        // Like record fields, flags are kept in declaration order.
+        Ok(Val::Flags(
+            ty.flags_names()
+                .filter(|name| names.contains(&&**name))
+                .map(Cow::into_owned)
+                .collect(),
+        ))
+    }
+
+    fn unwrap_bool(&self) -> bool {
+        match self {
+            Val::Bool(val) => *val,
+            _ => unwrap_mismatch("bool", self),
+        }
+    }
+    fn unwrap_s8(&self) -> i8 {
+        match self {
+            Val::S8(val) => *val,
+            _ => unwrap_mismatch("s8", self),
+        }
+    }
+    fn unwrap_s16(&self) -> i16 {
+        match self {
+            Val::S16(val) => *val,
+            _ => unwrap_mismatch("s16", self),
+        }
+    }
+    fn unwrap_s32(&self) -> i32 {
+        match self {
+            Val::S32(val) => *val,
+            _ => unwrap_mismatch("s32", self),
+        }
+    }
+    fn unwrap_s64(&self) -> i64 {
+        match self {
+            Val::S64(val) => *val,
+            _ => unwrap_mismatch("s64", self),
+        }
+    }
+    fn unwrap_u8(&self) -> u8 {
+        match self {
+            Val::U8(val) => *val,
+            _ => unwrap_mismatch("u8", self),
+        }
+    }
+    fn unwrap_u16(&self) -> u16 {
+        match self {
+            Val::U16(val) => *val,
+            _ => unwrap_mismatch("u16", self),
+        }
+    }
+    fn unwrap_u32(&self) -> u32 {
+        match self {
+            Val::U32(val) => *val,
+            _ => unwrap_mismatch("u32", self),
+        }
+    }
+    fn unwrap_u64(&self) -> u64 {
+        match self {
+            Val::U64(val) => *val,
+            _ => unwrap_mismatch("u64", self),
+        }
+    }
+    fn unwrap_float32(&self) -> f32 {
+        match self {
+            Val::Float32(val) => *val,
+            _ => unwrap_mismatch("float32", self),
+        }
+    }
+    fn unwrap_float64(&self) -> f64 {
+        match self {
+            Val::Float64(val) => *val,
+            _ => unwrap_mismatch("float64", self),
+        }
+    }
+    fn unwrap_char(&self) -> char {
+        match self {
+            Val::Char(val) => *val,
+            _ => unwrap_mismatch("char", self),
+        }
+    }
+    fn unwrap_string(&self) -> Cow<'_, str> {
+        match self {
+            Val::String(val) => Cow::Borrowed(val),
+            _ => unwrap_mismatch("string", self),
+        }
+    }
+    fn unwrap_list(&self) -> Box<dyn Iterator<Item = Cow<'_, Self>> + '_> {
+        match self {
+            Val::List(vals) => Box::new(vals.iter().map(Cow::Borrowed)),
+            _ => unwrap_mismatch("list", self),
+        }
+    }
+    fn unwrap_record(&self) -> Box<dyn Iterator<Item = (Cow<'_, str>, Cow<'_, Self>)> + '_> {
+        match self {
+            Val::Record(fields) => Box::new(
+                fields
+                    .iter()
+                    .map(|(name, val)| (Cow::Borrowed(&**name), Cow::Borrowed(val))),
+            ),
+            _ => unwrap_mismatch("record", self),
+        }
+    }
+    fn unwrap_tuple(&self) -> Box<dyn Iterator<Item = Cow<'_, Self>> + '_> {
+        match self {
+            Val::Tuple(vals) => Box::new(vals.iter().map(Cow::Borrowed)),
+            _ => unwrap_mismatch("tuple", self),
+        }
+    }
+    fn unwrap_variant(&self) -> (Cow<'_, str>, Option<Cow<'_, Self>>) {
+        match self {
+            Val::Variant(case, val) => (Cow::Borrowed(case), val.as_deref().map(Cow::Borrowed)),
+            _ => unwrap_mismatch("variant", self),
+        }
+    }
+    fn unwrap_enum(&self) -> Cow<'_, str> {
+        match self {
+            Val::Enum(case) => Cow::Borrowed(case),
+            _ => unwrap_mismatch("enum", self),
+        }
+    }
+    fn unwrap_option(&self) -> Option<Cow<'_, Self>> {
+        match self {
+            Val::Option(val) => val.as_deref().map(Cow::Borrowed),
+            _ => unwrap_mismatch("option", self),
+        }
+    }
+    fn unwrap_result(&self) -> Result<Option<Cow<'_, Self>>, Option<Cow<'_, Self>>> {
+        match self {
+            Val::Result(Ok(val)) => Ok(val.as_deref().map(Cow::Borrowed)),
+            Val::Result(Err(val)) => Err(val.as_deref().map(Cow::Borrowed)),
+            _ => unwrap_mismatch("result", self),
+        }
+    }
+    fn unwrap_flags(&self) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        match self {
+            Val::Flags(names) => Box::new(names.iter().map(|name| Cow::Borrowed(&**name))),
+            _ => unwrap_mismatch("flags", self),
+        }
+    }
+}
+
+fn unwrap_mismatch(kind: &str, val: &Val) -> ! {
+    panic!("called unwrap_{kind} on a {} value", WasmValue::kind(val))
+}
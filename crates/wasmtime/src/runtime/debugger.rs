@@ -0,0 +1,236 @@
+//! In-process debugging of guest WebAssembly.
+//!
+//! When [`Config::guest_debug`](crate::Config::guest_debug) is enabled, code
+//! is compiled with a hook before every Wasm instruction. While a store has
+//! breakpoints set or is single-stepping, that hook spills the current
+//! function's locals and operand stack and calls into the store's debug
+//! handler, which can inspect the paused frame and decide how to resume.
+
+use crate::hash_set::HashSet;
+use crate::prelude::*;
+use crate::runtime::vm::{CompiledModuleId, VMRuntimeLimits, ValRaw};
+use crate::store::{AutoAssertNoGc, StoreOpaque};
+use crate::{AsContextMut, Global, Instance, Module, Val, ValType};
+use core::ptr;
+use wasmtime_environ::{DebugSlotKind, GlobalIndex, DEBUG_SLOT_SIZE, DEBUG_SLOT_VALUE_OFFSET};
+
+/// What a debug handler wants to happen after it returns.
+///
+/// This is returned from the handler configured with
+/// [`Store::debug_handler`](crate::Store::debug_handler).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugAction {
+    /// Resume execution until the next breakpoint is hit.
+    Continue,
+    /// Execute a single instruction and then pause again.
+    ///
+    /// This steps into calls: if the next instruction is a call to another
+    /// Wasm function, execution pauses before the first instruction of the
+    /// callee.
+    Step,
+    /// Execute a single instruction, running any calls it makes to
+    /// completion, and then pause again.
+    ///
+    /// Execution pauses at the next instruction in the paused frame or, if
+    /// the paused function returns first, in one of its callers.
+    StepOver,
+    /// Run until the paused function returns, and then pause in its caller.
+    ///
+    /// If the paused frame is the outermost Wasm frame, this behaves like
+    /// [`DebugAction::Continue`].
+    StepOut,
+}
+
+/// A paused WebAssembly frame, as seen by a debug handler.
+///
+/// Execution is paused just before the instruction at
+/// [`DebugFrame::wasm_offset`] executes.
+pub struct DebugFrame {
+    instance: Instance,
+    module: Module,
+    func_index: u32,
+    wasm_offset: u32,
+    locals: Vec<Val>,
+    stack: Vec<Val>,
+}
+
+impl DebugFrame {
+    /// The instance that the paused function belongs to.
+    pub fn instance(&self) -> Instance {
+        self.instance
+    }
+
+    /// The module that the paused function is defined in.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// The index of the paused function within its module's function index
+    /// space.
+    pub fn func_index(&self) -> u32 {
+        self.func_index
+    }
+
+    /// The byte offset, within the module's original Wasm binary, of the
+    /// instruction that is about to execute.
+    ///
+    /// This is the same offset that breakpoints are set with.
+    pub fn wasm_offset(&self) -> u32 {
+        self.wasm_offset
+    }
+
+    /// The values of the function's locals, including its parameters, by
+    /// local index.
+    pub fn locals(&self) -> &[Val] {
+        &self.locals
+    }
+
+    /// The values on the function's operand stack, from bottom to top.
+    ///
+    /// This includes the values of all enclosing blocks, not just the
+    /// innermost one.
+    pub fn stack(&self) -> &[Val] {
+        &self.stack
+    }
+
+    /// Get the global at `index` in the paused instance's global index space,
+    /// including imported globals.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this frame's instance.
+    pub fn global(&self, mut store: impl AsContextMut, index: u32) -> Option<Global> {
+        let store = store.as_context_mut().0;
+        let index = GlobalIndex::from_u32(index);
+        if !self.module.env_module().globals.is_valid(index) {
+            return None;
+        }
+        let id = self.instance.id(store);
+        let export = store.instance_mut(id).get_exported_global(index);
+        Some(unsafe { Global::from_wasmtime_global(export, store) })
+    }
+}
+
+/// The frame state passed from compiled code to the `debug_hook` libcall.
+pub struct RawDebugFrame<'a> {
+    pub instance: Instance,
+    pub module: &'a Module,
+    pub func_index: u32,
+    pub wasm_offset: u32,
+    pub slots: *const u8,
+    pub num_locals: u32,
+    pub num_stack: u32,
+}
+
+impl RawDebugFrame<'_> {
+    /// Decode this frame's spilled values into a `DebugFrame`.
+    ///
+    /// Any GC references are rooted in the store's current LIFO scope.
+    ///
+    /// # Safety
+    ///
+    /// `slots` must point to `num_locals + num_stack` debug slots written by
+    /// code compiled for this store's engine.
+    pub unsafe fn decode(&self, store: &mut StoreOpaque) -> DebugFrame {
+        let mut store = AutoAssertNoGc::new(store);
+        let num_locals = usize::try_from(self.num_locals).unwrap();
+        let num_stack = usize::try_from(self.num_stack).unwrap();
+        let mut values = (0..num_locals + num_stack)
+            .map(|i| self.decode_slot(&mut store, i))
+            .collect::<Vec<_>>();
+        let stack = values.split_off(num_locals);
+        DebugFrame {
+            instance: self.instance,
+            module: self.module.clone(),
+            func_index: self.func_index,
+            wasm_offset: self.wasm_offset,
+            locals: values,
+            stack,
+        }
+    }
+
+    unsafe fn decode_slot(&self, store: &mut AutoAssertNoGc<'_>, index: usize) -> Val {
+        let slot = self
+            .slots
+            .add(index * usize::try_from(DEBUG_SLOT_SIZE).unwrap());
+        let tag = ptr::read(slot.cast::<u32>());
+        let value = slot.add(usize::try_from(DEBUG_SLOT_VALUE_OFFSET).unwrap());
+        let kind = DebugSlotKind::from_u32(tag).expect("invalid debug slot tag");
+        let (raw, ty) = match kind {
+            DebugSlotKind::I32 => (ValRaw::i32(ptr::read(value.cast())), ValType::I32),
+            DebugSlotKind::I64 => (ValRaw::i64(ptr::read(value.cast())), ValType::I64),
+            DebugSlotKind::F32 => (ValRaw::f32(ptr::read(value.cast())), ValType::F32),
+            DebugSlotKind::F64 => (ValRaw::f64(ptr::read(value.cast())), ValType::F64),
+            DebugSlotKind::V128 => (ValRaw::v128(ptr::read(value.cast())), ValType::V128),
+            DebugSlotKind::FuncRef => (ValRaw::funcref(ptr::read(value.cast())), ValType::FUNCREF),
+            DebugSlotKind::ExternRef => (
+                ValRaw::externref(ptr::read(value.cast())),
+                ValType::EXTERNREF,
+            ),
+            DebugSlotKind::AnyRef => (ValRaw::anyref(ptr::read(value.cast())), ValType::ANYREF),
+        };
+        Val::_from_raw(store, raw, &ty)
+    }
+}
+
+/// Per-store guest-debugging state.
+#[derive(Default)]
+pub(crate) struct DebugState {
+    breakpoints: HashSet<(CompiledModuleId, u32)>,
+    single_step: bool,
+    /// While single-stepping, only pause in frames whose depth, counted in
+    /// Wasm frames on the stack, is at most this.
+    step_max_depth: Option<usize>,
+}
+
+impl DebugState {
+    pub fn add_breakpoint(&mut self, module: &Module, wasm_offset: u32) {
+        self.breakpoints.insert((module.id(), wasm_offset));
+    }
+
+    pub fn remove_breakpoint(&mut self, module: &Module, wasm_offset: u32) -> bool {
+        self.breakpoints.remove(&(module.id(), wasm_offset))
+    }
+
+    pub fn set_single_step(&mut self, enable: bool) {
+        self.single_step = enable;
+        self.step_max_depth = None;
+    }
+
+    /// Pause before the next instruction executed in a frame at most
+    /// `max_depth` deep, or before any instruction if `max_depth` is `None`.
+    pub fn step(&mut self, max_depth: Option<usize>) {
+        self.single_step = true;
+        self.step_max_depth = max_depth;
+    }
+
+    /// Whether execution should pause before the instruction at
+    /// `wasm_offset` in `module`.
+    ///
+    /// `depth` computes the depth of the current frame, and is only called
+    /// when stepping over or out of a frame.
+    pub fn should_pause(
+        &self,
+        module: &Module,
+        wasm_offset: u32,
+        depth: impl FnOnce() -> usize,
+    ) -> bool {
+        if self.breakpoints.contains(&(module.id(), wasm_offset)) {
+            return true;
+        }
+        self.single_step && self.step_max_depth.map_or(true, |max| depth() <= max)
+    }
+
+    /// Update the flag that instrumented code checks before calling the
+    /// `debug_hook` libcall.
+    pub fn update_runtime_limits(&self, limits: &VMRuntimeLimits) {
+        let active = self.single_step || !self.breakpoints.is_empty();
+        // Safety: the flag is only read by Wasm running in this store, and
+        // updating it requires exclusive access to the store.
+        unsafe {
+            *limits.debug_hook_active.get() = u32::from(active);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::prelude::*;
 use crate::runtime::vm::{
-    ExportFunction, SendSyncPtr, StoreBox, VMArrayCallHostFuncContext, VMContext, VMFuncRef,
-    VMFunctionImport, VMOpaqueContext,
+    ExportFunction, InterpreterRef, SendSyncPtr, StoreBox, VMArrayCallHostFuncContext, VMContext,
+    VMFuncRef, VMFunctionImport, VMOpaqueContext,
 };
 use crate::runtime::Uninhabited;
 use crate::store::{AutoAssertNoGc, StoreData, StoreOpaque, Stored};
@@ -900,6 +900,28 @@ impl Func {
         self.load_ty(&store.as_context().0)
     }
 
+    /// Returns whether calls to this function run code compiled by the
+    /// optimizing tier of [`Strategy::Tiered`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this function.
+    ///
+    /// [`Strategy::Tiered`]: crate::Strategy::Tiered
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    pub fn is_tiered_up(&self, store: impl AsContext) -> bool {
+        let store = store.as_context().0;
+        assert!(self.comes_from_same_store(store));
+        let func_ref = store.store_data()[self.0].export().func_ref;
+        let Some(wasm_call) = (unsafe { func_ref.as_ref().wasm_call }) else {
+            return false;
+        };
+        store
+            .modules()
+            .lookup_module_by_pc(wasm_call.as_ptr() as usize)
+            .is_some_and(|module| module.is_optimized_tier())
+    }
+
     /// Forcibly loads the type of this function from the `Engine`.
     ///
     /// Note that this is a somewhat expensive method since it requires taking a
@@ -1076,11 +1098,10 @@ impl Func {
         params_and_returns: *mut ValRaw,
         params_and_returns_capacity: usize,
     ) -> Result<()> {
-        invoke_wasm_and_catch_traps(store, |caller| {
-            let func_ref = func_ref.as_ref();
-            (func_ref.array_call)(
-                func_ref.vmctx,
-                caller.cast::<VMOpaqueContext>(),
+        invoke_wasm_and_catch_traps(store, |caller, vm| {
+            func_ref.as_ref().array_call(
+                vm,
+                VMOpaqueContext::from_vmcontext(caller),
                 params_and_returns,
                 params_and_returns_capacity,
             )
@@ -1601,7 +1622,7 @@ impl Func {
 /// can pass to the called wasm function, if desired.
 pub(crate) fn invoke_wasm_and_catch_traps<T>(
     store: &mut StoreContextMut<'_, T>,
-    closure: impl FnMut(*mut VMContext),
+    mut closure: impl FnMut(*mut VMContext, Option<InterpreterRef<'_>>),
 ) -> Result<()> {
     unsafe {
         let exit = enter_wasm(store);
@@ -1610,14 +1631,27 @@ pub(crate) fn invoke_wasm_and_catch_traps<T>(
             exit_wasm(store, exit);
             return Err(trap);
         }
+        let signal_handler = store.0.signal_handler();
+        let capture_backtrace = store.0.engine().config().wasm_backtrace;
+        let capture_coredump = store.0.engine().config().coredump_on_trap;
+        let async_guard_range = store.0.async_guard_range();
+        let caller = store.0.default_caller();
+        let vm = store.0.interpreter();
+
+        // A trap unwinds past any Pulley frames on the interpreter's stack
+        // without running their epilogues, so restore its state afterwards.
+        let saved_regs = vm.map(|vm| vm.save_regs());
         let result = crate::runtime::vm::catch_traps(
-            store.0.signal_handler(),
-            store.0.engine().config().wasm_backtrace,
-            store.0.engine().config().coredump_on_trap,
-            store.0.async_guard_range(),
-            store.0.default_caller(),
-            closure,
+            signal_handler,
+            capture_backtrace,
+            capture_coredump,
+            async_guard_range,
+            caller,
+            |caller| closure(caller, vm),
         );
+        if let (Some(vm), Some(saved_regs)) = (vm, saved_regs) {
+            vm.restore_regs(saved_regs);
+        }
         exit_wasm(store, exit);
         store.0.call_hook(CallHook::ReturningFromWasm)?;
         result.map_err(|t| crate::trap::from_runtime_box(store.0, t))
@@ -1659,6 +1693,24 @@ fn enter_wasm<T>(store: &mut StoreContextMut<'_, T>) -> Option<usize> {
         return None;
     }
 
+    // Pulley bytecode runs on the interpreter's own stack rather than the
+    // native one, so the limit is relative to that stack instead. The
+    // interpreter's stack is also never larger than `max_wasm_stack`.
+    let max_wasm_stack = store.engine().config().max_wasm_stack;
+    if let Some(vm) = store.0.interpreter() {
+        let wasm_stack_limit = vm
+            .stack_pointer()
+            .saturating_sub(max_wasm_stack)
+            .max(vm.stack_start());
+        let prev_stack = unsafe {
+            mem::replace(
+                &mut *store.0.runtime_limits().stack_limit.get(),
+                wasm_stack_limit,
+            )
+        };
+        return Some(prev_stack);
+    }
+
     let stack_pointer = crate::runtime::vm::get_stack_pointer();
 
     // Determine the stack pointer where, after which, any wasm code will
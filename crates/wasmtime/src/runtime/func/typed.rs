@@ -211,11 +211,10 @@ where
         // the memory go away, so the size matters here for performance.
         let mut captures = (func, storage);
 
-        let result = invoke_wasm_and_catch_traps(store, |caller| {
+        let result = invoke_wasm_and_catch_traps(store, |caller, vm| {
             let (func_ref, storage) = &mut captures;
-            let func_ref = func_ref.as_ref();
-            (func_ref.array_call)(
-                func_ref.vmctx,
+            func_ref.as_ref().array_call(
+                vm,
                 VMOpaqueContext::from_vmcontext(caller),
                 (storage as *mut Storage<_, _>) as *mut ValRaw,
                 mem::size_of_val::<Storage<_, _>>(storage) / mem::size_of::<ValRaw>(),
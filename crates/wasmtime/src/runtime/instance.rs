@@ -7,8 +7,8 @@ use crate::runtime::vm::{
 use crate::store::{InstanceId, StoreOpaque, Stored};
 use crate::types::matching;
 use crate::{
-    AsContextMut, Engine, Export, Extern, Func, Global, Memory, Module, ModuleExport, SharedMemory,
-    StoreContext, StoreContextMut, Table, TypedFunc,
+    AsContextMut, Engine, Export, Extern, Func, Global, InstanceSnapshot, Memory, Module,
+    ModuleExport, SharedMemory, StoreContext, StoreContextMut, Table, TypedFunc,
 };
 use alloc::sync::Arc;
 use core::ptr::NonNull;
@@ -179,6 +179,21 @@ impl Instance {
         Ok(owned_imports)
     }
 
+    /// Internal function to create an instance whose state is restored from
+    /// `snapshot` instead of by running its start function.
+    ///
+    /// This has the same safety requirements as `new_started_impl`.
+    pub(crate) unsafe fn new_restored_impl<T>(
+        store: &mut StoreContextMut<'_, T>,
+        module: &Module,
+        imports: Imports<'_>,
+        snapshot: &InstanceSnapshot,
+    ) -> Result<Instance> {
+        let (instance, _start) = Instance::new_raw(store.0, module, imports)?;
+        snapshot.restore(store, &instance)?;
+        Ok(instance)
+    }
+
     /// Internal function to create an instance and run the start function.
     ///
     /// This function's unsafety is the same as `Instance::new_raw`.
@@ -361,10 +376,9 @@ impl Instance {
         let f = instance.get_exported_func(start);
         let caller_vmctx = instance.vmctx();
         unsafe {
-            super::func::invoke_wasm_and_catch_traps(store, |_default_caller| {
-                let func = f.func_ref.as_ref().array_call;
-                func(
-                    f.func_ref.as_ref().vmctx,
+            super::func::invoke_wasm_and_catch_traps(store, |_default_caller, vm| {
+                f.func_ref.as_ref().array_call(
+                    vm,
                     VMOpaqueContext::from_vmcontext(caller_vmctx),
                     [].as_mut_ptr(),
                     0,
@@ -595,7 +609,27 @@ impl Instance {
         self.get_export(store, name)?.into_global()
     }
 
-    #[cfg(feature = "component-model")]
+    /// Captures a snapshot of the state of this instance.
+    ///
+    /// The returned [`InstanceSnapshot`] records the contents of the memories,
+    /// tables, and mutable globals defined by this instance. It can later be
+    /// restored into a new instance of the same module with
+    /// [`InstancePre::instantiate_snapshot`], skipping the need to re-run any
+    /// initialization that was performed prior to the snapshot being taken.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this instance contains state which cannot be
+    /// snapshotted. See the documentation of [`InstanceSnapshot`] for more
+    /// details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    pub fn snapshot(&self, mut store: impl AsContextMut) -> Result<InstanceSnapshot> {
+        InstanceSnapshot::capture(store.as_context_mut(), self)
+    }
+
     pub(crate) fn id(&self, store: &StoreOpaque) -> InstanceId {
         store[self.0].id
     }
@@ -620,6 +654,26 @@ impl Instance {
             .map(|(i, g)| (i, unsafe { Global::from_wasmtime_global(g, store) }))
     }
 
+    /// Get all tables within this instance.
+    ///
+    /// Returns both import and defined tables.
+    ///
+    /// Returns both exported and non-exported tables.
+    ///
+    /// Gives access to the full tables space.
+    pub(crate) fn all_tables<'a>(
+        &'a self,
+        store: &'a mut StoreOpaque,
+    ) -> impl ExactSizeIterator<Item = (TableIndex, Table)> + 'a {
+        let data = &store[self.0];
+        let instance = store.instance_mut(data.id);
+        instance
+            .all_tables()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|(i, t)| (i, unsafe { Table::from_wasmtime_table(t, store) }))
+    }
+
     /// Get all memories within this instance.
     ///
     /// Returns both import and defined memories.
@@ -906,6 +960,93 @@ impl<T> InstancePre<T> {
         // in match the module we're instantiating.
         unsafe { Instance::new_started_async(&mut store, &self.module, imports.as_ref()).await }
     }
+
+    /// Instantiates this instance within the provided `store`, restoring its
+    /// state from `snapshot` instead of running its `start` function.
+    ///
+    /// The `snapshot` must have been created with [`Instance::snapshot`] from
+    /// an instance of the same module that this [`InstancePre`] instantiates,
+    /// although that instance may have lived in a different [`Store`]. The
+    /// imports closed over by this [`InstancePre`] are expected to be
+    /// equivalent to the ones the snapshotted instance was created with, as
+    /// imported state is not part of snapshots.
+    ///
+    /// Note that the module's `start` function is not run as the effects of
+    /// running it are expected to already be reflected in the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if instantiation fails or if `snapshot` does not
+    /// describe an instance of this module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any import closed over by this [`InstancePre`] isn't owned by
+    /// `store`, or if `store` has async support enabled.
+    ///
+    /// [`Store`]: crate::Store
+    pub fn instantiate_snapshot(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        snapshot: &InstanceSnapshot,
+    ) -> Result<Instance> {
+        let mut store = store.as_context_mut();
+        assert!(
+            !store.0.async_support(),
+            "must use async instantiation when async support is enabled",
+        );
+        let imports = pre_instantiate_raw(
+            &mut store.0,
+            &self.module,
+            &self.items,
+            self.host_funcs,
+            &self.func_refs,
+        )?;
+
+        // See `instantiate` for notes on this unsafety.
+        unsafe { Instance::new_restored_impl(&mut store, &self.module, imports.as_ref(), snapshot) }
+    }
+
+    /// Same as [`InstancePre::instantiate_snapshot`], but for use with
+    /// [asynchronous stores](crate::Config::async_support).
+    ///
+    /// # Panics
+    ///
+    /// Panics if any import closed over by this [`InstancePre`] isn't owned by
+    /// `store`, or if `store` does not have async support enabled.
+    #[cfg(feature = "async")]
+    pub async fn instantiate_snapshot_async(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        snapshot: &InstanceSnapshot,
+    ) -> Result<Instance>
+    where
+        T: Send,
+    {
+        let mut store = store.as_context_mut();
+        assert!(
+            store.0.async_support(),
+            "must use sync instantiation when async support is disabled",
+        );
+        let imports = pre_instantiate_raw(
+            &mut store.0,
+            &self.module,
+            &self.items,
+            self.host_funcs,
+            &self.func_refs,
+        )?;
+
+        // Note that, like other instantiation, this is done on a fiber since
+        // an async resource limiter may need to yield.
+        store
+            .on_fiber(|store| {
+                // See `instantiate` for notes on this unsafety.
+                unsafe {
+                    Instance::new_restored_impl(store, &self.module, imports.as_ref(), snapshot)
+                }
+            })
+            .await?
+    }
 }
 
 /// Helper function shared between
@@ -22,10 +22,14 @@ use wasmtime_environ::{
     VMSharedTypeIndex,
 };
 mod registry;
+#[cfg(all(feature = "cranelift", feature = "winch"))]
+mod tier_up;
 
 pub use registry::{
     lookup_code, register_code, unregister_code, ModuleRegistry, RegisteredModuleId,
 };
+#[cfg(all(feature = "cranelift", feature = "winch"))]
+pub(crate) use tier_up::{TierUp, TierUpStatus};
 
 /// A compiled WebAssembly module, ready to be instantiated.
 ///
@@ -43,11 +47,13 @@ pub use registry::{
 /// call to [`Module::deserialize`] will quickly load the module to execute and
 /// does not need to compile any code, representing a more AOT-style use case.
 ///
-/// Currently a `Module` does not implement any form of tiering or dynamic
-/// optimization of compiled code. Creation of a `Module` via [`Module::new`] or
-/// related APIs will perform the entire compilation step synchronously. When
-/// finished no further compilation will happen at runtime or later during
-/// execution of WebAssembly instances for example.
+/// Creation of a `Module` via [`Module::new`] or related APIs will perform the
+/// entire compilation step synchronously. When finished no further compilation
+/// will happen at runtime or later during execution of WebAssembly instances,
+/// unless the module was compiled with [`Strategy::Tiered`], in which case hot
+/// functions are recompiled with Cranelift in the background.
+///
+/// [`Strategy::Tiered`]: crate::Strategy::Tiered
 ///
 /// Compilation of WebAssembly by default goes through Cranelift and is
 /// recommended to be done once-per-module. The same WebAssembly binary need not
@@ -157,6 +163,26 @@ struct ModuleInner {
 
     /// Runtime offset information for `VMContext`.
     offsets: VMOffsets<HostPtr>,
+
+    /// Lazily-computed fingerprint of this module's compiled artifacts, used
+    /// to check that snapshots are restored into the module they were taken
+    /// from.
+    fingerprint: OnceLock<u64>,
+
+    /// Which tier of `Strategy::Tiered` this module's code belongs to, if it
+    /// was compiled with that strategy.
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    tier: std::sync::OnceLock<Tier>,
+}
+
+/// The tier of a module compiled with `Strategy::Tiered`.
+#[cfg(all(feature = "cranelift", feature = "winch"))]
+enum Tier {
+    /// Baseline code compiled by Winch, which can tier up.
+    Baseline(TierUp),
+    /// Optimized code compiled by Cranelift, which hot functions of the
+    /// baseline module are switched over to.
+    Optimized,
 }
 
 impl fmt::Debug for Module {
@@ -489,6 +515,9 @@ impl Module {
                 module,
                 serializable,
                 offsets,
+                fingerprint: OnceLock::new(),
+                #[cfg(all(feature = "cranelift", feature = "winch"))]
+                tier: std::sync::OnceLock::new(),
             }),
         })
     }
@@ -1021,6 +1050,66 @@ impl Module {
         &self.inner.offsets
     }
 
+    /// Returns a fingerprint of this module's compiled artifacts which is
+    /// stable across processes, see [`crate::snapshot::fingerprint`].
+    pub(crate) fn fingerprint(&self) -> u64 {
+        *self
+            .inner
+            .fingerprint
+            .get_or_init(|| crate::snapshot::fingerprint(self))
+    }
+
+    /// Waits for this module's optimizing tier to finish compiling in the
+    /// background.
+    ///
+    /// Modules compiled with [`Strategy::Tiered`] start compiling their
+    /// optimizing tier once one of their functions has become hot. This blocks
+    /// the current thread until that compilation, if it has started, has
+    /// finished, and returns whether the optimizing tier is ready. Hot
+    /// functions switch over to it the next time their tier-up counter runs
+    /// out, which can be observed with [`Func::is_tiered_up`].
+    ///
+    /// Returns `false` right away if this module wasn't compiled with
+    /// [`Strategy::Tiered`] or none of its functions has become hot yet.
+    ///
+    /// [`Strategy::Tiered`]: crate::Strategy::Tiered
+    /// [`Func::is_tiered_up`]: crate::Func::is_tiered_up
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    pub fn wait_for_tier_up(&self) -> bool {
+        self.tier_up().is_some_and(|tier_up| tier_up.wait())
+    }
+
+    /// Enables tiering up this module, which was compiled from `wasm`, to code
+    /// compiled by its engine's optimizing compiler.
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn enable_tier_up(&self, wasm: &[u8]) {
+        let tier_up = TierUp::new(self.engine(), wasm);
+        let _ = self.inner.tier.set(Tier::Baseline(tier_up));
+    }
+
+    /// Marks this module as the optimizing tier of another module.
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn mark_optimized_tier(&self) {
+        let _ = self.inner.tier.set(Tier::Optimized);
+    }
+
+    /// Returns this module's tier-up state, if it's the baseline tier of a
+    /// module compiled with `Strategy::Tiered`.
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn tier_up(&self) -> Option<&TierUp> {
+        match self.inner.tier.get()? {
+            Tier::Baseline(tier_up) => Some(tier_up),
+            Tier::Optimized => None,
+        }
+    }
+
+    /// Returns whether this module contains the optimized code of a module
+    /// compiled with `Strategy::Tiered`.
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn is_optimized_tier(&self) -> bool {
+        matches!(self.inner.tier.get(), Some(Tier::Optimized))
+    }
+
     /// Return the address, in memory, of the trampoline that allows Wasm to
     /// call a array function of the given signature.
     pub(crate) fn wasm_to_array_trampoline(
@@ -0,0 +1,126 @@
+//! Background recompilation of modules compiled with [`Strategy::Tiered`].
+//!
+//! Modules compiled with the tiered strategy are first compiled with Winch and
+//! count how often each of their functions is called or loops. When one of
+//! those counters runs out the module is recompiled with Cranelift on a
+//! background thread, and once that's finished instances switch their hot
+//! functions over to the optimized code.
+//!
+//! [`Strategy::Tiered`]: crate::Strategy::Tiered
+
+use crate::prelude::*;
+use crate::{Engine, Module};
+use alloc::sync::Arc;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+/// Tier-up state of a module compiled with [`Strategy::Tiered`].
+///
+/// [`Strategy::Tiered`]: crate::Strategy::Tiered
+pub(crate) struct TierUp {
+    inner: Arc<TierUpInner>,
+}
+
+struct TierUpInner {
+    /// The engine of the baseline module, whose optimizing compiler compiles
+    /// the optimizing tier.
+    engine: Engine,
+    /// The original WebAssembly binary, recompiled by the optimizing tier.
+    wasm: Box<[u8]>,
+    state: Mutex<State>,
+    /// Notified when the background compilation of the optimizing tier has
+    /// finished.
+    finished: Condvar,
+}
+
+enum State {
+    /// No function of the module has become hot yet.
+    Baseline,
+    /// The optimizing tier is being compiled on a background thread.
+    Compiling,
+    /// The optimizing tier is ready.
+    Optimized(Module),
+    /// Compiling the optimizing tier failed, so the module stays on the
+    /// baseline tier.
+    Failed,
+}
+
+/// The result of [`TierUp::hot`].
+pub(crate) enum TierUpStatus {
+    /// The optimizing tier is being compiled, so the function should check
+    /// back later.
+    Compiling,
+    /// The optimizing tier is ready and contains the given module's code.
+    Ready(Module),
+    /// The optimizing tier will never be available.
+    Unavailable,
+}
+
+impl TierUp {
+    /// Creates the tier-up state for a module compiled from `wasm` within
+    /// `engine`.
+    pub(crate) fn new(engine: &Engine, wasm: &[u8]) -> TierUp {
+        TierUp {
+            inner: Arc::new(TierUpInner {
+                engine: engine.clone(),
+                wasm: wasm.into(),
+                state: Mutex::new(State::Baseline),
+                finished: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Called when a function of this module has become hot.
+    ///
+    /// Starts compiling the optimizing tier in the background if that hasn't
+    /// happened yet.
+    pub(crate) fn hot(&self) -> TierUpStatus {
+        let mut state = self.inner.state.lock().unwrap();
+        match &*state {
+            State::Baseline => {}
+            State::Compiling => return TierUpStatus::Compiling,
+            State::Optimized(module) => return TierUpStatus::Ready(module.clone()),
+            State::Failed => return TierUpStatus::Unavailable,
+        }
+
+        let inner = self.inner.clone();
+        let spawned = thread::Builder::new()
+            .name("wasmtime-tier-up".to_string())
+            .spawn(move || {
+                let state =
+                    match crate::compile::compile_optimized_module(&inner.engine, &inner.wasm) {
+                        Ok(module) => State::Optimized(module),
+                        Err(e) => {
+                            log::warn!("failed to compile the optimizing tier: {e:?}");
+                            State::Failed
+                        }
+                    };
+                *inner.state.lock().unwrap() = state;
+                inner.finished.notify_all();
+            });
+        match spawned {
+            Ok(_) => {
+                *state = State::Compiling;
+                TierUpStatus::Compiling
+            }
+            Err(e) => {
+                log::warn!("failed to spawn the optimizing tier's compilation thread: {e}");
+                *state = State::Failed;
+                TierUpStatus::Unavailable
+            }
+        }
+    }
+
+    /// Blocks until the background compilation of the optimizing tier has
+    /// finished, if it has been started, returning whether the optimizing
+    /// tier is ready.
+    pub(crate) fn wait(&self) -> bool {
+        let state = self.inner.state.lock().unwrap();
+        let state = self
+            .inner
+            .finished
+            .wait_while(state, |state| matches!(state, State::Compiling))
+            .unwrap();
+        matches!(*state, State::Optimized(_))
+    }
+}
@@ -4,18 +4,21 @@ use crate::runtime::vm::Backtrace;
 use crate::{AsContext, CallHook, Module};
 use fxprof_processed_profile::debugid::DebugId;
 use fxprof_processed_profile::{
-    CategoryHandle, Frame, FrameFlags, FrameInfo, LibraryInfo, MarkerLocation, MarkerSchema,
-    MarkerTiming, Profile, ProfilerMarker, ReferenceTimestamp, Symbol, SymbolTable, Timestamp,
+    CategoryHandle, Frame, FrameFlags, FrameInfo, LibraryInfo, MarkerDynamicField,
+    MarkerFieldFormat, MarkerLocation, MarkerSchema, MarkerSchemaField, MarkerTiming, Profile,
+    ProfilerMarker, ReferenceTimestamp, Symbol, SymbolTable, Timestamp,
 };
+use std::collections::HashMap;
 use std::ops::Range;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use wasmtime_environ::demangle_function_name_or_index;
 
+mod pprof;
+
 // TODO: collect more data
 // - On non-Windows, measure thread-local CPU usage between events with
 //   rustix::time::clock_gettime(ClockId::ThreadCPUTime)
-// - Report which wasm module, and maybe instance, each frame came from
 
 /// Collects basic profiling data for a single WebAssembly guest.
 ///
@@ -30,6 +33,10 @@ use wasmtime_environ::demangle_function_name_or_index;
 /// way to do that is to call it from a callback registered with
 /// [`Store::epoch_deadline_callback()`](crate::Store::epoch_deadline_callback).
 ///
+/// Once the guest is done the profile can be written out either in the
+/// [Firefox processed profile format](GuestProfiler::finish) or in the
+/// [pprof format](GuestProfiler::finish_pprof).
+///
 /// # Accuracy
 ///
 /// The data collection granularity is limited by the mechanism you use to
@@ -38,7 +45,11 @@ use wasmtime_environ::demangle_function_name_or_index;
 /// If you use epoch interruption, then samples will only be collected at
 /// function entry points and loop headers. This introduces some bias to the
 /// results. In addition, samples will only be taken at times when WebAssembly
-/// functions are running, not during host-calls.
+/// functions are running, not during host-calls. Time spent in host-calls can
+/// still be attributed by calling [`GuestProfiler::call_hook`] or
+/// [`GuestProfiler::call_hook_named`] on every transition between guest and
+/// host, which records synthetic samples for the host function when it
+/// returns.
 ///
 /// It is technically possible to use fuel interruption instead. That
 /// introduces worse bias since samples occur after a certain number of
@@ -76,9 +87,60 @@ pub struct GuestProfiler {
     process: fxprof_processed_profile::ProcessHandle,
     thread: fxprof_processed_profile::ThreadHandle,
     start: Instant,
+    start_time: SystemTime,
+    interval: Duration,
+    host_call: Option<HostCall>,
+    /// Time spent in host calls which hasn't been accounted for by a sample
+    /// yet.
+    host_time: Duration,
+    /// Sample counts and durations aggregated by stack, used for the pprof
+    /// output.
+    stacks: HashMap<Vec<StackFrame>, StackCounts>,
+}
+
+#[derive(Debug)]
+struct ModuleInfo {
+    /// Absolute address range covered by this module's functions.
+    range: Range<usize>,
+    lib: fxprof_processed_profile::LibraryHandle,
+    name: String,
+    /// Functions of this module, sorted by their offset from `range.start`.
+    symbols: Vec<ModuleSymbol>,
+}
+
+#[derive(Debug)]
+struct ModuleSymbol {
+    offset: u32,
+    len: u32,
+    name: String,
+}
+
+type Modules = Vec<ModuleInfo>;
+
+#[derive(Debug)]
+struct HostCall {
+    name: Arc<str>,
+    start: Instant,
+}
+
+/// A single frame of a recorded stack.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum StackFrame {
+    /// A wasm frame at `offset` from the start of `modules[module]`.
+    Wasm { module: usize, offset: u32 },
+    /// A host function called by the guest.
+    Host(Arc<str>),
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct StackCounts {
+    samples: i64,
+    wall_nanos: i64,
+    cpu_nanos: i64,
 }
 
-type Modules = Vec<(Range<usize>, fxprof_processed_profile::LibraryHandle)>;
+/// The name of host frames recorded by [`GuestProfiler::call_hook`].
+const DEFAULT_HOST_NAME: &str = "<host>";
 
 impl GuestProfiler {
     /// Begin profiling a new guest. When this function is called, the current
@@ -96,23 +158,23 @@ impl GuestProfiler {
     /// host code or functions from other modules will be omitted. See the
     /// "Security" section of the [`GuestProfiler`] documentation for guidance
     /// on what modules should not be included in this list.
-    pub fn new(module_name: &str, interval: Duration, modules: Vec<(String, Module)>) -> Self {
+    pub fn new(
+        module_name: &str,
+        interval: Duration,
+        modules: impl IntoIterator<Item = (String, Module)>,
+    ) -> Self {
         let zero = ReferenceTimestamp::from_millis_since_unix_epoch(0.0);
         let mut profile = Profile::new(module_name, zero, interval.into());
 
         let mut modules: Vec<_> = modules
             .into_iter()
-            .filter_map(|(name, module)| {
-                let compiled = module.compiled_module();
-                let text = compiled.text().as_ptr_range();
-                let address_range = text.start as usize..text.end as usize;
-                module_symbols(name, compiled).map(|lib| (address_range, profile.add_lib(lib)))
-            })
+            .filter_map(|(name, module)| module_info(&mut profile, name, module.compiled_module()))
             .collect();
 
-        modules.sort_unstable_by_key(|(range, _)| range.start);
+        modules.sort_unstable_by_key(|m| m.range.start);
 
-        profile.set_reference_timestamp(std::time::SystemTime::now().into());
+        let start_time = SystemTime::now();
+        profile.set_reference_timestamp(start_time.into());
         let process = profile.add_process(module_name, 0, Timestamp::from_nanos_since_reference(0));
         let thread = profile.add_thread(process, 0, Timestamp::from_nanos_since_reference(0), true);
         let start = Instant::now();
@@ -122,59 +184,180 @@ impl GuestProfiler {
             process,
             thread,
             start,
+            start_time,
+            interval,
+            host_call: None,
+            host_time: Duration::ZERO,
+            stacks: HashMap::new(),
         }
     }
 
+    /// Begin profiling a new component. This is the same as
+    /// [`GuestProfiler::new`] except that all core modules within `component`
+    /// are included in the profile.
+    ///
+    /// Core modules are named after their name section, if any, and otherwise
+    /// by their index within the component. Modules in `extra_modules` are
+    /// included as well, for example modules which were instantiated by the
+    /// host and linked into the component.
+    #[cfg(feature = "component-model")]
+    pub fn new_component(
+        component_name: &str,
+        interval: Duration,
+        component: crate::component::Component,
+        extra_modules: impl IntoIterator<Item = (String, Module)>,
+    ) -> Self {
+        let modules = component
+            .static_modules()
+            .enumerate()
+            .map(|(i, module)| {
+                let name = match module.name() {
+                    Some(name) => format!("{component_name}/{name}"),
+                    None => format!("{component_name}/module{i}"),
+                };
+                (name, module.clone())
+            })
+            .collect::<Vec<_>>();
+        Self::new(
+            component_name,
+            interval,
+            modules.into_iter().chain(extra_modules),
+        )
+    }
+
     /// Add a sample to the profile. This function collects a backtrace from
     /// any stack frames for allowed modules on the current stack. It should
     /// typically be called from a callback registered using
     /// [`Store::epoch_deadline_callback()`](crate::Store::epoch_deadline_callback).
     ///
+    /// If this is called during a host call that was reported through
+    /// [`GuestProfiler::call_hook`], a frame for the host function is recorded
+    /// on top of the guest's stack.
+    ///
     /// The `delta` parameter is the amount of CPU time that was used by this
     /// guest since the previous sample. It is allowed to pass `Duration::ZERO`
     /// here if recording CPU usage information is not needed.
     pub fn sample(&mut self, store: impl AsContext, delta: Duration) {
-        let now = Timestamp::from_nanos_since_reference(
-            self.start.elapsed().as_nanos().try_into().unwrap(),
-        );
         let backtrace = Backtrace::new(store.as_context().0.vmruntime_limits());
-        let frames = lookup_frames(&self.modules, &backtrace);
-        self.profile
-            .add_sample(self.thread, now, frames, delta.into(), 1);
+        let mut stack = lookup_frames(&self.modules, &backtrace);
+        if let Some(call) = &self.host_call {
+            stack.push(StackFrame::Host(call.name.clone()));
+        }
+        self.add_sample(stack, delta, 1);
     }
 
     /// Add a marker for transitions between guest and host to the profile.
     /// This function should typically be called from a callback registered
     /// using [`Store::call_hook()`](crate::Store::call_hook), and the `kind`
     /// parameter should be the value of the same type passed into that hook.
+    ///
+    /// Time spent in the host is attributed to a frame named `<host>`. Use
+    /// [`GuestProfiler::call_hook_named`] to give host functions a name.
     pub fn call_hook(&mut self, store: impl AsContext, kind: CallHook) {
-        let now = Timestamp::from_nanos_since_reference(
-            self.start.elapsed().as_nanos().try_into().unwrap(),
-        );
+        self.call_hook_named(store, kind, DEFAULT_HOST_NAME);
+    }
+
+    /// Same as [`GuestProfiler::call_hook`], but records `name` as the name of
+    /// the host function being called.
+    ///
+    /// The `name` is only used for [`CallHook::CallingHost`]. When the host
+    /// function returns, its running time is recorded in the profile as
+    /// samples with a frame of this name on top of the guest's stack, at the
+    /// rate of one sample per `interval` spent in host calls.
+    pub fn call_hook_named(&mut self, store: impl AsContext, kind: CallHook, name: &str) {
+        let now = self.now();
         match kind {
             CallHook::CallingWasm | CallHook::ReturningFromWasm => {}
             CallHook::CallingHost => {
                 let backtrace = Backtrace::new(store.as_context().0.vmruntime_limits());
-                let frames = lookup_frames(&self.modules, &backtrace);
+                let stack = lookup_frames(&self.modules, &backtrace);
+                let frames = self.frame_infos(&stack);
                 self.profile.add_marker_with_stack(
                     self.thread,
                     "hostcall",
-                    CallMarker,
+                    CallMarker { name: name.into() },
                     MarkerTiming::IntervalStart(now),
-                    frames,
+                    frames.into_iter(),
                 );
+                self.host_call = Some(HostCall {
+                    name: name.into(),
+                    start: Instant::now(),
+                });
             }
             CallHook::ReturningFromHost => {
+                let Some(call) = self.host_call.take() else {
+                    return;
+                };
                 self.profile.add_marker(
                     self.thread,
                     "hostcall",
-                    CallMarker,
+                    CallMarker {
+                        name: call.name.to_string(),
+                    },
                     MarkerTiming::IntervalEnd(now),
                 );
+
+                // Record one sample per `interval` spent in the host, carrying
+                // over the remainder to the next host call.
+                self.host_time += call.start.elapsed();
+                if self.interval.is_zero() {
+                    return;
+                }
+                let weight = self.host_time.as_nanos() / self.interval.as_nanos();
+                if weight == 0 {
+                    return;
+                }
+                let weight = u32::try_from(weight).unwrap_or(u32::MAX);
+                self.host_time = self.host_time.saturating_sub(self.interval * weight);
+                let backtrace = Backtrace::new(store.as_context().0.vmruntime_limits());
+                let mut stack = lookup_frames(&self.modules, &backtrace);
+                stack.push(StackFrame::Host(call.name));
+                self.add_sample(stack, Duration::ZERO, weight);
             }
         }
     }
 
+    fn now(&self) -> Timestamp {
+        Timestamp::from_nanos_since_reference(self.start.elapsed().as_nanos().try_into().unwrap())
+    }
+
+    fn add_sample(&mut self, stack: Vec<StackFrame>, delta: Duration, weight: u32) {
+        let now = self.now();
+        let frames = self.frame_infos(&stack);
+        self.profile.add_sample(
+            self.thread,
+            now,
+            frames.into_iter(),
+            delta.into(),
+            i32::try_from(weight).unwrap_or(i32::MAX),
+        );
+
+        let counts = self.stacks.entry(stack).or_default();
+        let wall = self.interval.as_nanos() * u128::from(weight);
+        counts.samples += i64::from(weight);
+        counts.wall_nanos += i64::try_from(wall).unwrap_or(i64::MAX);
+        counts.cpu_nanos += i64::try_from(delta.as_nanos()).unwrap_or(i64::MAX);
+    }
+
+    fn frame_infos(&mut self, stack: &[StackFrame]) -> Vec<FrameInfo> {
+        stack
+            .iter()
+            .map(|frame| {
+                let frame = match frame {
+                    StackFrame::Wasm { module, offset } => {
+                        Frame::RelativeAddressFromReturnAddress(self.modules[*module].lib, *offset)
+                    }
+                    StackFrame::Host(name) => Frame::Label(self.profile.intern_string(name)),
+                };
+                FrameInfo {
+                    frame,
+                    category_pair: CategoryHandle::OTHER.into(),
+                    flags: FrameFlags::empty(),
+                }
+            })
+            .collect()
+    }
+
     /// When the guest finishes running, call this function to write the
     /// profile to the given `output`. The output is a JSON-formatted object in
     /// the [Firefox "processed profile format"][fmt]. Files in this format may
@@ -182,19 +365,39 @@ impl GuestProfiler {
     ///
     /// [fmt]: https://github.com/firefox-devtools/profiler/blob/main/docs-developer/processed-profile-format.md
     pub fn finish(mut self, output: impl std::io::Write) -> Result<()> {
-        let now = Timestamp::from_nanos_since_reference(
-            self.start.elapsed().as_nanos().try_into().unwrap(),
-        );
+        let now = self.now();
         self.profile.set_thread_end_time(self.thread, now);
         self.profile.set_process_end_time(self.process, now);
 
         serde_json::to_writer(output, &self.profile)?;
         Ok(())
     }
+
+    /// When the guest finishes running, call this function to write the
+    /// profile to the given `output` in the [pprof format][fmt].
+    ///
+    /// The output is an uncompressed protobuf-encoded `Profile` message, which
+    /// is accepted by `go tool pprof` and most continuous profiling services.
+    /// Each stack records the number of samples, the wall-clock time those
+    /// samples represent (the number of samples times the sampling interval),
+    /// and the CPU time passed to [`GuestProfiler::sample`].
+    ///
+    /// [fmt]: https://github.com/google/pprof/blob/main/proto/profile.proto
+    pub fn finish_pprof(self, mut output: impl std::io::Write) -> Result<()> {
+        let bytes = pprof::encode(&self);
+        output.write_all(&bytes)?;
+        Ok(())
+    }
 }
 
-fn module_symbols(name: String, compiled: &CompiledModule) -> Option<LibraryInfo> {
-    let symbols = Vec::from_iter(compiled.finished_functions().map(|(defined_idx, _)| {
+fn module_info(
+    profile: &mut Profile,
+    name: String,
+    compiled: &CompiledModule,
+) -> Option<ModuleInfo> {
+    // Modules within a component share a single text section, so only the
+    // part of it which contains this module's functions is attributed to it.
+    let mut symbols = Vec::from_iter(compiled.finished_functions().map(|(defined_idx, _)| {
         let loc = compiled.func_loc(defined_idx);
         let func_idx = compiled.module().func_index(defined_idx);
         let mut name = String::new();
@@ -204,57 +407,91 @@ fn module_symbols(name: String, compiled: &CompiledModule) -> Option<LibraryInfo
             defined_idx.as_u32() as usize,
         )
         .unwrap();
-        Symbol {
-            address: loc.start,
-            size: Some(loc.length),
+        ModuleSymbol {
+            offset: loc.start,
+            len: loc.length,
             name,
         }
     }));
-    if symbols.is_empty() {
-        return None;
+    let base = symbols.iter().map(|s| s.offset).min()?;
+    let end = symbols.iter().map(|s| s.offset + s.len).max()?;
+    for symbol in symbols.iter_mut() {
+        symbol.offset -= base;
     }
+    symbols.sort_unstable_by_key(|s| s.offset);
 
-    Some(LibraryInfo {
-        name,
+    let text = compiled.text().as_ptr() as usize;
+    let range = text + base as usize..text + end as usize;
+
+    let lib = profile.add_lib(LibraryInfo {
+        name: name.clone(),
         debug_name: String::new(),
         path: String::new(),
         debug_path: String::new(),
         debug_id: DebugId::nil(),
         code_id: None,
         arch: None,
-        symbol_table: Some(Arc::new(SymbolTable::new(symbols))),
+        symbol_table: Some(Arc::new(SymbolTable::new(
+            symbols
+                .iter()
+                .map(|s| Symbol {
+                    address: s.offset,
+                    size: Some(s.len),
+                    name: s.name.clone(),
+                })
+                .collect(),
+        ))),
+    });
+
+    Some(ModuleInfo {
+        range,
+        lib,
+        name,
+        symbols,
     })
 }
 
-fn lookup_frames<'a>(
-    modules: &'a Modules,
-    backtrace: &'a Backtrace,
-) -> impl Iterator<Item = FrameInfo> + 'a {
+/// Returns the frames of `backtrace` which belong to one of `modules`, oldest
+/// first.
+fn lookup_frames(modules: &Modules, backtrace: &Backtrace) -> Vec<StackFrame> {
     backtrace
         .frames()
         // Samply needs to see the oldest frame first, but we list the newest
         // first, so iterate in reverse.
         .rev()
         .filter_map(|frame| {
-            // Find the first module whose start address includes this PC.
-            let module_idx = modules.partition_point(|(range, _)| range.start > frame.pc());
-            if let Some((range, lib)) = modules.get(module_idx) {
-                if range.contains(&frame.pc()) {
-                    return Some(FrameInfo {
-                        frame: Frame::RelativeAddressFromReturnAddress(
-                            *lib,
-                            u32::try_from(frame.pc() - range.start).unwrap(),
-                        ),
-                        category_pair: CategoryHandle::OTHER.into(),
-                        flags: FrameFlags::empty(),
-                    });
-                }
+            // Find the last module whose start address is at or before this PC.
+            let module_idx = modules.partition_point(|m| m.range.start <= frame.pc());
+            let module_idx = module_idx.checked_sub(1)?;
+            let module = &modules[module_idx];
+            if module.range.contains(&frame.pc()) {
+                return Some(StackFrame::Wasm {
+                    module: module_idx,
+                    offset: u32::try_from(frame.pc() - module.range.start).unwrap(),
+                });
             }
             None
         })
+        .collect()
 }
 
-struct CallMarker;
+impl ModuleInfo {
+    /// Returns the index into `symbols` of the function containing `offset`.
+    fn symbol_index(&self, offset: u32) -> Option<usize> {
+        let idx = self.symbols.partition_point(|s| s.offset <= offset);
+        let idx = idx.checked_sub(1)?;
+        let symbol = &self.symbols[idx];
+        if offset < symbol.offset + symbol.len {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+}
+
+struct CallMarker {
+    name: String,
+}
 
 impl ProfilerMarker for CallMarker {
     const MARKER_TYPE_NAME: &'static str = "hostcall";
@@ -267,14 +504,19 @@ impl ProfilerMarker for CallMarker {
                 MarkerLocation::MarkerTable,
                 MarkerLocation::TimelineOverview,
             ],
-            chart_label: None,
-            tooltip_label: None,
-            table_label: None,
-            fields: vec![],
+            chart_label: Some("{marker.data.name}"),
+            tooltip_label: Some("host call: {marker.data.name}"),
+            table_label: Some("{marker.data.name}"),
+            fields: vec![MarkerSchemaField::Dynamic(MarkerDynamicField {
+                key: "name",
+                label: "Function",
+                format: MarkerFieldFormat::String,
+                searchable: true,
+            })],
         }
     }
 
     fn json_marker_data(&self) -> serde_json::Value {
-        serde_json::json!({ "type": Self::MARKER_TYPE_NAME })
+        serde_json::json!({ "type": Self::MARKER_TYPE_NAME, "name": self.name })
     }
 }
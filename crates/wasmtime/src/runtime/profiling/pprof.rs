@@ -0,0 +1,280 @@
+//! Encoding of [`GuestProfiler`] data in the pprof format.
+//!
+//! This writes the protobuf messages defined in [`profile.proto`] by hand
+//! rather than depending on a protobuf library, since only a handful of
+//! fields are needed.
+//!
+//! [`profile.proto`]: https://github.com/google/pprof/blob/main/proto/profile.proto
+
+use super::{GuestProfiler, StackFrame};
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::time::UNIX_EPOCH;
+
+/// Field numbers of the `Profile` message.
+mod profile {
+    pub const SAMPLE_TYPE: u32 = 1;
+    pub const SAMPLE: u32 = 2;
+    pub const MAPPING: u32 = 3;
+    pub const LOCATION: u32 = 4;
+    pub const FUNCTION: u32 = 5;
+    pub const STRING_TABLE: u32 = 6;
+    pub const TIME_NANOS: u32 = 9;
+    pub const DURATION_NANOS: u32 = 10;
+    pub const PERIOD_TYPE: u32 = 11;
+    pub const PERIOD: u32 = 12;
+}
+
+/// Field numbers of the `ValueType` message.
+mod value_type {
+    pub const TYPE: u32 = 1;
+    pub const UNIT: u32 = 2;
+}
+
+/// Field numbers of the `Sample` message.
+mod sample {
+    pub const LOCATION_ID: u32 = 1;
+    pub const VALUE: u32 = 2;
+}
+
+/// Field numbers of the `Mapping` message.
+mod mapping {
+    pub const ID: u32 = 1;
+    pub const MEMORY_LIMIT: u32 = 3;
+    pub const FILENAME: u32 = 5;
+    pub const HAS_FUNCTIONS: u32 = 7;
+}
+
+/// Field numbers of the `Location` and `Line` messages.
+mod location {
+    pub const ID: u32 = 1;
+    pub const MAPPING_ID: u32 = 2;
+    pub const ADDRESS: u32 = 3;
+    pub const LINE: u32 = 4;
+    pub const LINE_FUNCTION_ID: u32 = 1;
+}
+
+/// Field numbers of the `Function` message.
+mod function {
+    pub const ID: u32 = 1;
+    pub const NAME: u32 = 2;
+    pub const SYSTEM_NAME: u32 = 3;
+    pub const FILENAME: u32 = 4;
+}
+
+const WIRE_VARINT: u32 = 0;
+const WIRE_LEN: u32 = 2;
+
+/// A minimal protobuf message writer.
+#[derive(Default)]
+struct Message {
+    buf: Vec<u8>,
+}
+
+impl Message {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value & 0x7f) as u8 | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(u8::try_from(value).unwrap());
+    }
+
+    fn key(&mut self, field: u32, wire_type: u32) {
+        self.varint(u64::from(field << 3 | wire_type));
+    }
+
+    /// Writes an `int64`, `uint64` or `bool` field, omitting default values.
+    fn uint(&mut self, field: u32, value: u64) {
+        if value != 0 {
+            self.key(field, WIRE_VARINT);
+            self.varint(value);
+        }
+    }
+
+    fn int(&mut self, field: u32, value: i64) {
+        self.uint(field, value as u64);
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) {
+        self.key(field, WIRE_LEN);
+        self.varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn message(&mut self, field: u32, message: Message) {
+        self.bytes(field, &message.buf);
+    }
+
+    fn packed(&mut self, field: u32, values: impl IntoIterator<Item = u64>) {
+        let mut packed = Message::default();
+        for value in values {
+            packed.varint(value);
+        }
+        self.bytes(field, &packed.buf);
+    }
+}
+
+/// The `string_table` of a profile, whose first entry must be empty.
+struct Strings {
+    table: Vec<String>,
+    indices: HashMap<String, i64>,
+}
+
+impl Strings {
+    fn new() -> Self {
+        Strings {
+            table: vec![String::new()],
+            indices: HashMap::from([(String::new(), 0)]),
+        }
+    }
+
+    fn intern(&mut self, s: &str) -> i64 {
+        if let Some(idx) = self.indices.get(s) {
+            return *idx;
+        }
+        let idx = self.table.len() as i64;
+        self.table.push(s.to_string());
+        self.indices.insert(s.to_string(), idx);
+        idx
+    }
+}
+
+fn value_type(strings: &mut Strings, ty: &str, unit: &str) -> Message {
+    let mut msg = Message::default();
+    msg.int(value_type::TYPE, strings.intern(ty));
+    msg.int(value_type::UNIT, strings.intern(unit));
+    msg
+}
+
+/// Key identifying a `Function` in the profile: either a function of a module
+/// or a host function.
+#[derive(PartialEq, Eq, Hash)]
+enum FunctionKey<'a> {
+    Wasm { module: usize, symbol: usize },
+    Host(&'a str),
+}
+
+pub(super) fn encode(profiler: &GuestProfiler) -> Vec<u8> {
+    let mut out = Message::default();
+    let mut strings = Strings::new();
+
+    for (ty, unit) in [
+        ("samples", "count"),
+        ("wall", "nanoseconds"),
+        ("cpu", "nanoseconds"),
+    ] {
+        let msg = value_type(&mut strings, ty, unit);
+        out.message(profile::SAMPLE_TYPE, msg);
+    }
+
+    // Each module gets a mapping whose id is its index plus one, since zero
+    // means "no mapping".
+    for (i, module) in profiler.modules.iter().enumerate() {
+        let mut msg = Message::default();
+        msg.uint(mapping::ID, i as u64 + 1);
+        msg.uint(
+            mapping::MEMORY_LIMIT,
+            (module.range.end - module.range.start) as u64,
+        );
+        msg.int(mapping::FILENAME, strings.intern(&module.name));
+        msg.uint(mapping::HAS_FUNCTIONS, 1);
+        out.message(profile::MAPPING, msg);
+    }
+
+    let mut functions = HashMap::new();
+    let mut locations = HashMap::new();
+
+    // Sort the stacks so the output is deterministic.
+    let mut stacks = profiler.stacks.iter().collect::<Vec<_>>();
+    stacks.sort_by(|a, b| b.1.samples.cmp(&a.1.samples).then(a.0.cmp(b.0)));
+
+    for (stack, counts) in stacks {
+        let mut location_ids = Vec::with_capacity(stack.len());
+        // pprof lists the leaf frame first.
+        for frame in stack.iter().rev() {
+            if let Some(id) = locations.get(frame) {
+                location_ids.push(*id);
+                continue;
+            }
+            let location_id = locations.len() as u64 + 1;
+            let mut loc = Message::default();
+            loc.uint(location::ID, location_id);
+
+            let function = match frame {
+                StackFrame::Wasm { module, offset } => {
+                    loc.uint(location::MAPPING_ID, *module as u64 + 1);
+                    loc.uint(location::ADDRESS, u64::from(*offset));
+                    profiler.modules[*module]
+                        .symbol_index(*offset)
+                        .map(|symbol| FunctionKey::Wasm {
+                            module: *module,
+                            symbol,
+                        })
+                }
+                StackFrame::Host(name) => Some(FunctionKey::Host(name)),
+            };
+
+            if let Some(key) = function {
+                let next_id = functions.len() as u64 + 1;
+                let function_id = *functions.entry(key).or_insert_with_key(|key| {
+                    let (name, filename) = match key {
+                        FunctionKey::Wasm { module, symbol } => {
+                            let module = &profiler.modules[*module];
+                            (&module.symbols[*symbol].name[..], &module.name[..])
+                        }
+                        FunctionKey::Host(name) => (*name, ""),
+                    };
+                    let mut func = Message::default();
+                    func.uint(function::ID, next_id);
+                    func.int(function::NAME, strings.intern(name));
+                    func.int(function::SYSTEM_NAME, strings.intern(name));
+                    func.int(function::FILENAME, strings.intern(filename));
+                    out.message(profile::FUNCTION, func);
+                    next_id
+                });
+                let mut line = Message::default();
+                line.uint(location::LINE_FUNCTION_ID, function_id);
+                loc.message(location::LINE, line);
+            }
+
+            out.message(profile::LOCATION, loc);
+            locations.insert(frame, location_id);
+            location_ids.push(location_id);
+        }
+
+        let mut msg = Message::default();
+        msg.packed(sample::LOCATION_ID, location_ids);
+        msg.packed(
+            sample::VALUE,
+            [counts.samples, counts.wall_nanos, counts.cpu_nanos].map(|v| v as u64),
+        );
+        out.message(profile::SAMPLE, msg);
+    }
+
+    let period_type = value_type(&mut strings, "wall", "nanoseconds");
+
+    for s in strings.table.iter() {
+        out.bytes(profile::STRING_TABLE, s.as_bytes());
+    }
+
+    let time_nanos = profiler
+        .start_time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    out.int(
+        profile::TIME_NANOS,
+        i64::try_from(time_nanos).unwrap_or(i64::MAX),
+    );
+    out.int(
+        profile::DURATION_NANOS,
+        i64::try_from(profiler.start.elapsed().as_nanos()).unwrap_or(i64::MAX),
+    );
+    out.message(profile::PERIOD_TYPE, period_type);
+    out.int(
+        profile::PERIOD,
+        i64::try_from(profiler.interval.as_nanos()).unwrap_or(i64::MAX),
+    );
+
+    out.buf
+}
@@ -0,0 +1,457 @@
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::store::{InstanceId, StoreOpaque};
+use crate::{Func, Instance, Module, Mutability, Ref, StoreContextMut, Val, V128};
+use core::hash::Hasher;
+use serde_derive::{Deserialize, Serialize};
+use wasmtime_environ::{DataIndex, ElemIndex, FuncIndex};
+
+/// The version of the encoding produced by [`InstanceSnapshot::serialize`].
+///
+/// This must be bumped whenever the structure of `InstanceSnapshot` changes.
+const SNAPSHOT_VERSION: u32 = 2;
+
+/// Size of the chunks that linear memories are split into when snapshotting.
+///
+/// Each chunk has leading and trailing runs of zeroes trimmed and all-zero
+/// chunks are omitted entirely, which keeps snapshots of mostly-empty memories
+/// small.
+const CHUNK_SIZE: usize = 4096;
+
+/// A snapshot of the state of an [`Instance`] which can be restored into a new
+/// instance of the same module.
+///
+/// Snapshots are created with [`Instance::snapshot`] and capture the contents
+/// of the linear memories, tables, and mutable globals that an instance
+/// defines, as well as which of its passive data and element segments have
+/// been dropped. A snapshot can be turned into a blob of bytes with
+/// [`InstanceSnapshot::serialize`] and later rehydrated into a fresh instance,
+/// possibly within a different [`Store`](crate::Store), with
+/// [`InstancePre::instantiate_snapshot`](crate::InstancePre::instantiate_snapshot).
+///
+/// The primary use case for snapshots is skipping expensive guest
+/// initialization: an instance is initialized once, snapshotted, and then all
+/// subsequent instances are created directly from the snapshot. Note that
+/// restoring a snapshot does not run the module's `start` function.
+///
+/// Only state owned by the instance itself is captured. Imported memories,
+/// tables, and globals are not part of the snapshot and are expected to be
+/// supplied again, through the same imports, when the snapshot is restored.
+/// Additionally the following state cannot be captured, and attempting to
+/// snapshot an instance which contains it will return an error:
+///
+/// * Shared linear memories.
+/// * Non-null `externref`, `anyref`, or other GC references.
+/// * Function references which do not refer to a function of the instance
+///   being snapshotted, for example a host function inserted into a table
+///   with [`Table::set`](crate::Table::set).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InstanceSnapshot {
+    version: u32,
+    /// The fingerprint of the module that the snapshotted instance is an
+    /// instance of, see [`fingerprint`].
+    module: u64,
+    memories: Vec<MemorySnapshot>,
+    tables: Vec<TableSnapshot>,
+    /// One entry per defined global, `None` for immutable globals as their
+    /// value is fully determined by instantiation.
+    globals: Vec<Option<SnapshotVal>>,
+    dropped_elements: Vec<u32>,
+    dropped_data: Vec<u32>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct MemorySnapshot {
+    /// The size of the memory, in pages.
+    pages: u64,
+    /// The non-zero contents of the memory.
+    chunks: Vec<MemoryChunk>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct MemoryChunk {
+    offset: u64,
+    bytes: Vec<u8>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct TableSnapshot {
+    /// The size of the table, in elements.
+    size: u64,
+    /// All non-null elements of the table along with the index of the function
+    /// that they refer to.
+    elements: Vec<(u64, u32)>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum SnapshotVal {
+    I32(i32),
+    I64(i64),
+    F32(u32),
+    F64(u64),
+    V128(u128),
+    NullRef,
+    FuncRef(u32),
+}
+
+impl InstanceSnapshot {
+    pub(crate) fn capture<T>(
+        mut store: StoreContextMut<'_, T>,
+        instance: &Instance,
+    ) -> Result<InstanceSnapshot> {
+        let id = instance.id(&store.0);
+        let module = runtime_module(store.0, id);
+        let env_module = module.compiled_module().module().clone();
+
+        // Build a reverse map from the address of each of this instance's
+        // `VMFuncRef`s to the index of the function it refers to. This is how
+        // function references found in tables and globals are encoded in the
+        // snapshot.
+        let func_refs = {
+            let handle = store.0.instance_mut(id).instance_mut();
+            env_module
+                .functions
+                .iter()
+                .filter(|(_, f)| f.is_escaping())
+                .filter_map(|(index, _)| Some((handle.get_func_ref(index)? as usize, index)))
+                .collect::<HashMap<_, _>>()
+        };
+        let func_index = |store: &mut StoreContextMut<'_, T>, func: Func| -> Result<u32> {
+            let func_ref = func.vm_func_ref(store.0).as_ptr() as usize;
+            match func_refs.get(&func_ref) {
+                Some(index) => Ok(index.as_u32()),
+                None => bail!("function reference does not refer to a function of this instance"),
+            }
+        };
+
+        let mut memories = Vec::new();
+        for (index, memory) in instance
+            .all_memories(&mut store.0)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter(|(index, _)| env_module.defined_memory_index(*index).is_some())
+        {
+            if memory.ty(&store).is_shared() {
+                bail!(
+                    "cannot snapshot memory {}: shared memories are not supported",
+                    index.as_u32()
+                );
+            }
+            let mut chunks = Vec::new();
+            for (i, chunk) in memory.data(&store).chunks(CHUNK_SIZE).enumerate() {
+                if let Some(start) = chunk.iter().position(|byte| *byte != 0) {
+                    let end = chunk.iter().rposition(|byte| *byte != 0).unwrap() + 1;
+                    chunks.push(MemoryChunk {
+                        offset: u64::try_from(i * CHUNK_SIZE + start).unwrap(),
+                        bytes: chunk[start..end].to_vec(),
+                    });
+                }
+            }
+            memories.push(MemorySnapshot {
+                pages: memory.size(&store),
+                chunks,
+            });
+        }
+
+        let mut tables = Vec::new();
+        for (index, table) in instance
+            .all_tables(&mut store.0)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter(|(index, _)| env_module.defined_table_index(*index).is_some())
+        {
+            let size = table.size(&store);
+            let mut elements = Vec::new();
+            for i in 0..size {
+                match table.get(&mut store, i) {
+                    Some(Ref::Func(Some(func))) => {
+                        let func = func_index(&mut store, func).with_context(|| {
+                            format!("cannot snapshot element {i} of table {}", index.as_u32())
+                        })?;
+                        elements.push((i, func));
+                    }
+                    Some(r) if r.is_null() => {}
+                    Some(_) => bail!(
+                        "cannot snapshot element {i} of table {}: GC references are not supported",
+                        index.as_u32()
+                    ),
+                    None => unreachable!("index is within the table's bounds"),
+                }
+            }
+            tables.push(TableSnapshot { size, elements });
+        }
+
+        let mut globals = Vec::new();
+        for (index, global) in instance
+            .all_globals(&mut store.0)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter(|(index, _)| env_module.defined_global_index(*index).is_some())
+        {
+            if matches!(global.ty(&store).mutability(), Mutability::Const) {
+                globals.push(None);
+                continue;
+            }
+            let val = match global.get(&mut store) {
+                Val::I32(x) => SnapshotVal::I32(x),
+                Val::I64(x) => SnapshotVal::I64(x),
+                Val::F32(x) => SnapshotVal::F32(x),
+                Val::F64(x) => SnapshotVal::F64(x),
+                Val::V128(x) => SnapshotVal::V128(x.as_u128()),
+                Val::FuncRef(None) | Val::ExternRef(None) | Val::AnyRef(None) => {
+                    SnapshotVal::NullRef
+                }
+                Val::FuncRef(Some(func)) => {
+                    let func = func_index(&mut store, func)
+                        .with_context(|| format!("cannot snapshot global {}", index.as_u32()))?;
+                    SnapshotVal::FuncRef(func)
+                }
+                Val::ExternRef(Some(_)) | Val::AnyRef(Some(_)) => bail!(
+                    "cannot snapshot global {}: GC references are not supported",
+                    index.as_u32()
+                ),
+            };
+            globals.push(Some(val));
+        }
+
+        let handle = store.0.instance(id).instance();
+        let dropped_elements = env_module
+            .passive_elements_map
+            .keys()
+            .filter(|index| handle.elem_dropped(**index))
+            .map(|index| index.as_u32())
+            .collect();
+        let dropped_data = env_module
+            .passive_data_map
+            .keys()
+            .filter(|index| handle.data_dropped(**index))
+            .map(|index| index.as_u32())
+            .collect();
+
+        Ok(InstanceSnapshot {
+            version: SNAPSHOT_VERSION,
+            module: module.fingerprint(),
+            memories,
+            tables,
+            globals,
+            dropped_elements,
+            dropped_data,
+        })
+    }
+
+    /// Overwrites the state of the freshly-created `instance` with the state
+    /// recorded in this snapshot.
+    pub(crate) fn restore<T>(
+        &self,
+        store: &mut StoreContextMut<'_, T>,
+        instance: &Instance,
+    ) -> Result<()> {
+        let id = instance.id(&store.0);
+        let module = runtime_module(store.0, id);
+        if module.fingerprint() != self.module {
+            bail!("snapshot was not taken from an instance of this module");
+        }
+        let env_module = module.compiled_module().module().clone();
+
+        let memories = instance
+            .all_memories(&mut store.0)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter(|(index, _)| env_module.defined_memory_index(*index).is_some())
+            .map(|(_, memory)| memory)
+            .collect::<Vec<_>>();
+        let tables = instance
+            .all_tables(&mut store.0)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter(|(index, _)| env_module.defined_table_index(*index).is_some())
+            .map(|(_, table)| table)
+            .collect::<Vec<_>>();
+        let globals = instance
+            .all_globals(&mut store.0)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter(|(index, _)| env_module.defined_global_index(*index).is_some())
+            .map(|(_, global)| global)
+            .collect::<Vec<_>>();
+        if memories.len() != self.memories.len()
+            || tables.len() != self.tables.len()
+            || globals.len() != self.globals.len()
+        {
+            bail!("snapshot was not taken from an instance of this module");
+        }
+
+        let func = |store: &mut StoreContextMut<'_, T>, index: u32| -> Result<Func> {
+            let index = FuncIndex::from_u32(index);
+            match env_module.functions.get(index) {
+                Some(f) if f.is_escaping() => {}
+                _ => bail!(
+                    "snapshot refers to invalid function index {}",
+                    index.as_u32()
+                ),
+            }
+            let func_ref = store
+                .0
+                .instance_mut(id)
+                .instance_mut()
+                .get_func_ref(index)
+                .unwrap();
+            // SAFETY: the `VMFuncRef` is owned by `instance` which lives within
+            // `store`.
+            Ok(unsafe { Func::from_vm_func_ref(store.0, func_ref).unwrap() })
+        };
+
+        for (memory, snapshot) in memories.iter().zip(&self.memories) {
+            let current = memory.size(&*store);
+            if snapshot.pages < current {
+                bail!("snapshot memory is smaller than the module's minimum size");
+            }
+            memory.grow(&mut *store, snapshot.pages - current)?;
+            let data = memory.data_mut(&mut *store);
+            data.fill(0);
+            for chunk in snapshot.chunks.iter() {
+                let start = usize::try_from(chunk.offset)
+                    .ok()
+                    .filter(|start| {
+                        start
+                            .checked_add(chunk.bytes.len())
+                            .is_some_and(|end| end <= data.len())
+                    })
+                    .ok_or_else(|| anyhow!("snapshot memory contents are out of bounds"))?;
+                data[start..][..chunk.bytes.len()].copy_from_slice(&chunk.bytes);
+            }
+        }
+
+        for (table, snapshot) in tables.iter().zip(&self.tables) {
+            let null = Ref::null(table.ty(&*store).element().heap_type());
+            let current = table.size(&*store);
+            if snapshot.size < current {
+                bail!("snapshot table is smaller than the module's minimum size");
+            }
+            table.grow(&mut *store, snapshot.size - current, null.clone())?;
+            table.fill(&mut *store, 0, null, snapshot.size)?;
+            for (i, index) in snapshot.elements.iter() {
+                let f = func(store, *index)?;
+                table.set(&mut *store, *i, f.into())?;
+            }
+        }
+
+        for (global, snapshot) in globals.iter().zip(&self.globals) {
+            let val = match snapshot {
+                Some(SnapshotVal::I32(x)) => Val::I32(*x),
+                Some(SnapshotVal::I64(x)) => Val::I64(*x),
+                Some(SnapshotVal::F32(x)) => Val::F32(*x),
+                Some(SnapshotVal::F64(x)) => Val::F64(*x),
+                Some(SnapshotVal::V128(x)) => Val::V128(V128::from(*x)),
+                Some(SnapshotVal::NullRef) => match global.ty(&*store).content().as_ref() {
+                    Some(r) => Val::null_ref(r.heap_type()),
+                    None => bail!("snapshot global has the wrong type"),
+                },
+                Some(SnapshotVal::FuncRef(index)) => Val::FuncRef(Some(func(store, *index)?)),
+                None => continue,
+            };
+            global.set(&mut *store, val)?;
+        }
+
+        let handle = store.0.instance_mut(id).instance_mut();
+        for index in self.dropped_elements.iter() {
+            handle.elem_drop(ElemIndex::from_u32(*index));
+        }
+        for index in self.dropped_data.iter() {
+            handle.data_drop(DataIndex::from_u32(*index));
+        }
+
+        Ok(())
+    }
+
+    /// Serializes this snapshot into a blob of bytes.
+    ///
+    /// The returned bytes can be turned back into a snapshot with
+    /// [`InstanceSnapshot::deserialize`]. The encoding is not stable across
+    /// versions of Wasmtime.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        postcard::to_allocvec(self).err2anyhow()
+    }
+
+    /// Deserializes a snapshot previously created with
+    /// [`InstanceSnapshot::serialize`].
+    ///
+    /// Note that this only validates the encoding of the snapshot itself, and
+    /// it's only when the snapshot is restored with
+    /// [`InstancePre::instantiate_snapshot`](crate::InstancePre::instantiate_snapshot)
+    /// that it's checked against the module being instantiated.
+    pub fn deserialize(bytes: &[u8]) -> Result<InstanceSnapshot> {
+        let snapshot: InstanceSnapshot = postcard::from_bytes(bytes).err2anyhow()?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            bail!(
+                "snapshot was created with an incompatible version of Wasmtime \
+                 (version {}, expected {SNAPSHOT_VERSION})",
+                snapshot.version
+            );
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Returns the module that the instance `id` is an instance of.
+///
+/// Note that this is looked up through the instance itself, rather than the
+/// store's module registry, as the registry can't distinguish between the
+/// modules of a component which all share the same code.
+fn runtime_module(store: &StoreOpaque, id: InstanceId) -> Module {
+    store
+        .instance(id)
+        .instance()
+        .runtime_module()
+        .expect("instances of modules always have a runtime module")
+        .clone()
+}
+
+/// Computes a fingerprint of the compiled artifacts of `module`.
+///
+/// This covers the module's metadata, its data segments, and its compiled
+/// code. The locations of relocations in the code are skipped as they're
+/// patched with host addresses which differ between processes, which keeps the
+/// fingerprint stable when a module is serialized and deserialized elsewhere.
+pub(crate) fn fingerprint(module: &Module) -> u64 {
+    let code = module.compiled_module().code_memory();
+    let text = code.text();
+    let mut relocations = code.relocation_offsets().collect::<Vec<_>>();
+    relocations.sort_unstable();
+
+    let mut hasher = Fnv1a::default();
+    let mut pos = 0;
+    for offset in relocations {
+        hasher.write(&text[pos..offset.max(pos)]);
+        pos = offset.max(pos) + core::mem::size_of::<usize>();
+    }
+    hasher.write(&text[pos.min(text.len())..]);
+    hasher.write(code.wasm_data());
+    let metadata = postcard::to_allocvec(module.env_module().as_ref())
+        .expect("module metadata is always serializable");
+    hasher.write(&metadata);
+    hasher.finish()
+}
+
+/// The 64-bit FNV-1a hash, used for fingerprints as unlike `DefaultHasher`
+/// it's available without `std`.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -76,6 +76,7 @@
 //! contents of `StoreOpaque`. This is an invariant that we, as the authors of
 //! `wasmtime`, must uphold for the public interface to be safe.
 
+use crate::debugger::{DebugAction, DebugFrame, DebugState, RawDebugFrame};
 use crate::hash_set::HashSet;
 use crate::instance::InstanceData;
 use crate::linker::Definition;
@@ -84,8 +85,8 @@ use crate::prelude::*;
 use crate::runtime::vm::mpk::{self, ProtectionKey, ProtectionMask};
 use crate::runtime::vm::{
     Backtrace, ExportGlobal, GcRootsList, GcStore, InstanceAllocationRequest, InstanceAllocator,
-    InstanceHandle, ModuleRuntimeInfo, OnDemandInstanceAllocator, SignalHandler, StoreBox,
-    StorePtr, VMContext, VMFuncRef, VMGcRef, VMRuntimeLimits, WasmFault,
+    InstanceHandle, Interpreter, InterpreterRef, ModuleRuntimeInfo, OnDemandInstanceAllocator,
+    SignalHandler, StoreBox, StorePtr, VMContext, VMFuncRef, VMGcRef, VMRuntimeLimits, WasmFault,
 };
 use crate::trampoline::VMHostGlobalContext;
 use crate::type_registry::RegisteredType;
@@ -222,6 +223,9 @@ pub struct StoreInner<T> {
     call_hook: Option<CallHookInner<T>>,
     epoch_deadline_behavior:
         Option<Box<dyn FnMut(StoreContextMut<T>) -> Result<UpdateDeadline> + Send + Sync>>,
+    debug_handler: Option<
+        Box<dyn FnMut(StoreContextMut<'_, T>, &DebugFrame) -> Result<DebugAction> + Send + Sync>,
+    >,
     // for comments about `ManuallyDrop`, see `Store::into_data`
     data: ManuallyDrop<T>,
 }
@@ -341,6 +345,7 @@ pub struct StoreOpaque {
     // until the reserve is empty.
     fuel_reserve: u64,
     fuel_yield_interval: Option<NonZeroU64>,
+    debug: DebugState,
     /// Indexed data within this `Store`, used to store information about
     /// globals, functions, memories, etc.
     ///
@@ -372,6 +377,10 @@ pub struct StoreOpaque {
     /// `store_data` above, where the function pointers are stored.
     rooted_host_funcs: ManuallyDrop<Vec<Arc<[Definition]>>>,
 
+    /// The Pulley interpreter which executes all wasm in this store, if the
+    /// engine targets Pulley.
+    interpreter: Option<Interpreter>,
+
     /// Keep track of what protection key is being used during allocation so
     /// that the right memory pages can be enabled when entering WebAssembly
     /// guest code.
@@ -559,11 +568,17 @@ impl<T> Store<T> {
                 },
                 fuel_reserve: 0,
                 fuel_yield_interval: None,
+                debug: DebugState::default(),
                 store_data: ManuallyDrop::new(StoreData::new()),
                 default_caller: InstanceHandle::null(),
                 hostcall_val_storage: Vec::new(),
                 wasm_val_raw_storage: Vec::new(),
                 rooted_host_funcs: ManuallyDrop::new(Vec::new()),
+                interpreter: if engine.is_pulley() {
+                    Some(Interpreter::new(engine.config().max_wasm_stack))
+                } else {
+                    None
+                },
                 pkey,
                 #[cfg(feature = "component-model")]
                 component_host_table: Default::default(),
@@ -575,6 +590,7 @@ impl<T> Store<T> {
             limiter: None,
             call_hook: None,
             epoch_deadline_behavior: None,
+            debug_handler: None,
             data: ManuallyDrop::new(data),
         });
 
@@ -1048,6 +1064,67 @@ impl<T> Store<T> {
     pub fn epoch_deadline_async_yield_and_update(&mut self, delta: u64) {
         self.inner.epoch_deadline_async_yield_and_update(delta);
     }
+
+    /// Configures the handler that is invoked whenever guest execution pauses
+    /// at a breakpoint or after a single step.
+    ///
+    /// The handler is given the paused [`DebugFrame`], whose locals, operand
+    /// stack, and globals may be inspected, and returns a [`DebugAction`]
+    /// describing how to resume. Returning an error from the handler will
+    /// terminate execution with a trap.
+    ///
+    /// Execution only pauses in code compiled with
+    /// [`Config::guest_debug`](crate::Config::guest_debug) enabled. See
+    /// [`Store::add_breakpoint`] and [`Store::single_step`] for how to pause
+    /// execution.
+    pub fn debug_handler(
+        &mut self,
+        handler: impl FnMut(StoreContextMut<'_, T>, &DebugFrame) -> Result<DebugAction>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.inner.debug_handler = Some(Box::new(handler));
+    }
+
+    /// Sets a breakpoint before the instruction at `wasm_offset` in `module`.
+    ///
+    /// The offset is a byte offset within the module's original Wasm binary,
+    /// and must be the offset of an instruction within a function body to
+    /// ever be hit. When execution in any instance of `module` in this store
+    /// reaches the breakpoint, the handler configured with
+    /// [`Store::debug_handler`] is invoked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this store's engine was not configured with
+    /// [`Config::guest_debug`](crate::Config::guest_debug), or if `module`
+    /// belongs to a different engine.
+    pub fn add_breakpoint(&mut self, module: &Module, wasm_offset: u32) -> Result<()> {
+        self.inner.add_breakpoint(module, wasm_offset)
+    }
+
+    /// Removes a breakpoint previously set with [`Store::add_breakpoint`].
+    ///
+    /// Returns whether the breakpoint was set.
+    pub fn remove_breakpoint(&mut self, module: &Module, wasm_offset: u32) -> bool {
+        self.inner.remove_breakpoint(module, wasm_offset)
+    }
+
+    /// Configures whether execution pauses before the next instruction
+    /// executed in this store, regardless of breakpoints.
+    ///
+    /// Single-stepping is cleared each time execution pauses, and is re-enabled
+    /// when the debug handler returns [`DebugAction::Step`],
+    /// [`DebugAction::StepOver`], or [`DebugAction::StepOut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this store's engine was not configured with
+    /// [`Config::guest_debug`](crate::Config::guest_debug).
+    pub fn single_step(&mut self, enable: bool) -> Result<()> {
+        self.inner.single_step(enable)
+    }
 }
 
 impl<'a, T> StoreContext<'a, T> {
@@ -1162,6 +1239,27 @@ impl<'a, T> StoreContextMut<'a, T> {
     pub fn epoch_deadline_async_yield_and_update(&mut self, delta: u64) {
         self.0.epoch_deadline_async_yield_and_update(delta);
     }
+
+    /// Sets a breakpoint before the instruction at `wasm_offset` in `module`.
+    ///
+    /// For more information see [`Store::add_breakpoint`].
+    pub fn add_breakpoint(&mut self, module: &Module, wasm_offset: u32) -> Result<()> {
+        self.0.add_breakpoint(module, wasm_offset)
+    }
+
+    /// Removes a breakpoint previously set with [`Store::add_breakpoint`].
+    ///
+    /// For more information see [`Store::remove_breakpoint`].
+    pub fn remove_breakpoint(&mut self, module: &Module, wasm_offset: u32) -> bool {
+        self.0.remove_breakpoint(module, wasm_offset)
+    }
+
+    /// Configures whether execution pauses before the next instruction.
+    ///
+    /// For more information see [`Store::single_step`].
+    pub fn single_step(&mut self, enable: bool) -> Result<()> {
+        self.0.single_step(enable)
+    }
 }
 
 impl<T> StoreInner<T> {
@@ -1544,7 +1642,7 @@ impl StoreOpaque {
             );
             let (index, heap) = engine
                 .allocator()
-                .allocate_gc_heap(&**engine.gc_runtime()?)?;
+                .allocate_gc_heap(engine, &**engine.gc_runtime()?)?;
             Ok(GcStore::new(index, heap))
         }
 
@@ -1612,6 +1710,36 @@ impl StoreOpaque {
         self.gc_roots.exit_lifo_scope(self.gc_store.as_mut(), scope);
     }
 
+    fn ensure_guest_debug(&self) -> Result<()> {
+        if !self.engine.tunables().guest_debug {
+            bail!("guest debugging is not enabled; see `Config::guest_debug`");
+        }
+        Ok(())
+    }
+
+    fn add_breakpoint(&mut self, module: &Module, wasm_offset: u32) -> Result<()> {
+        self.ensure_guest_debug()?;
+        if !Engine::same(&self.engine, module.engine()) {
+            bail!("cross-`Engine` breakpoints are not supported");
+        }
+        self.debug.add_breakpoint(module, wasm_offset);
+        self.debug.update_runtime_limits(&self.runtime_limits);
+        Ok(())
+    }
+
+    fn remove_breakpoint(&mut self, module: &Module, wasm_offset: u32) -> bool {
+        let removed = self.debug.remove_breakpoint(module, wasm_offset);
+        self.debug.update_runtime_limits(&self.runtime_limits);
+        removed
+    }
+
+    fn single_step(&mut self, enable: bool) -> Result<()> {
+        self.ensure_guest_debug()?;
+        self.debug.set_single_step(enable);
+        self.debug.update_runtime_limits(&self.runtime_limits);
+        Ok(())
+    }
+
     #[cfg(feature = "gc")]
     pub fn gc(&mut self) {
         // If the GC heap hasn't been initialized, there is nothing to collect.
@@ -1909,6 +2037,22 @@ impl StoreOpaque {
         &self.runtime_limits as *const VMRuntimeLimits as *mut VMRuntimeLimits
     }
 
+    /// The number of Wasm frames currently on the stack.
+    fn wasm_stack_depth(&self) -> usize {
+        let mut depth = 0;
+        Backtrace::trace(self.vmruntime_limits().cast_const(), |_| {
+            depth += 1;
+            core::ops::ControlFlow::Continue(())
+        });
+        depth
+    }
+
+    /// Returns a handle to this store's Pulley interpreter, if its engine
+    /// targets Pulley.
+    pub(crate) fn interpreter(&mut self) -> Option<InterpreterRef<'_>> {
+        self.interpreter.as_mut().map(|i| i.as_interpreter_ref())
+    }
+
     #[inline]
     pub fn default_caller(&self) -> *mut VMContext {
         self.default_caller.vmctx()
@@ -2626,6 +2770,55 @@ unsafe impl<T> crate::runtime::vm::VMStore for StoreInner<T> {
         delta_result
     }
 
+    unsafe fn debug_hook(&mut self, frame: RawDebugFrame<'_>) -> Result<()> {
+        if !self
+            .debug
+            .should_pause(frame.module, frame.wasm_offset, || self.wasm_stack_depth())
+        {
+            return Ok(());
+        }
+
+        // Execution pauses at most once per step.
+        self.debug.set_single_step(false);
+
+        // Temporarily take the handler to avoid mutably borrowing multiple
+        // times.
+        let result = match self.debug_handler.take() {
+            None => Ok(DebugAction::Continue),
+            Some(mut handler) => {
+                let result = {
+                    #[cfg(feature = "gc")]
+                    let mut scope = RootScope::new(&mut *self);
+                    #[cfg(feature = "gc")]
+                    let store = scope.as_context_mut();
+                    #[cfg(not(feature = "gc"))]
+                    let store = StoreContextMut(&mut *self);
+                    let frame = frame.decode(store.0);
+                    handler(store, &frame)
+                };
+                self.debug_handler = Some(handler);
+                result
+            }
+        };
+
+        match result? {
+            DebugAction::Continue => {}
+            DebugAction::Step => self.debug.step(None),
+            DebugAction::StepOver => {
+                let depth = self.wasm_stack_depth();
+                self.debug.step(Some(depth));
+            }
+            DebugAction::StepOut => {
+                let depth = self.wasm_stack_depth();
+                if depth > 1 {
+                    self.debug.step(Some(depth - 1));
+                }
+            }
+        }
+        self.debug.update_runtime_limits(&self.runtime_limits);
+        Ok(())
+    }
+
     #[cfg(feature = "gc")]
     fn maybe_async_gc(&mut self, root: Option<VMGcRef>) -> Result<Option<VMGcRef>> {
         let mut scope = RootScope::new(self);
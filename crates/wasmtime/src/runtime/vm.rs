@@ -28,6 +28,12 @@ mod export;
 mod gc;
 mod imports;
 mod instance;
+#[cfg(feature = "pulley")]
+#[path = "vm/interpreter.rs"]
+mod interpreter;
+#[cfg(not(feature = "pulley"))]
+#[path = "vm/interpreter_disabled.rs"]
+mod interpreter;
 mod memory;
 mod mmap;
 mod mmap_vec;
@@ -62,9 +68,10 @@ pub use crate::runtime::vm::instance::{
 };
 #[cfg(feature = "pooling-allocator")]
 pub use crate::runtime::vm::instance::{
-    InstanceLimits, PoolConcurrencyLimitError, PoolingInstanceAllocator,
+    InstanceLimits, PoolConcurrencyLimitError, PoolingAllocatorMetrics, PoolingInstanceAllocator,
     PoolingInstanceAllocatorConfig,
 };
+pub use crate::runtime::vm::interpreter::*;
 pub use crate::runtime::vm::memory::{Memory, RuntimeLinearMemory, RuntimeMemoryCreator};
 pub use crate::runtime::vm::mmap::Mmap;
 pub use crate::runtime::vm::mmap_vec::MmapVec;
@@ -146,6 +153,15 @@ pub unsafe trait VMStore {
     /// completely semantically transparent. Returns the new deadline.
     fn new_epoch(&mut self) -> Result<u64, Error>;
 
+    /// Callback invoked by code instrumented for guest debugging before each
+    /// instruction, while breakpoints are set or single-stepping is enabled.
+    ///
+    /// # Safety
+    ///
+    /// The `frame`'s debug slots must have been written by compiled code
+    /// running in this store.
+    unsafe fn debug_hook(&mut self, frame: crate::debugger::RawDebugFrame<'_>) -> Result<()>;
+
     /// Callback invoked whenever an instance needs to trigger a GC.
     ///
     /// Optionally given a GC reference that is rooted for the collection, and
@@ -561,6 +561,14 @@ impl ComponentInstance {
         }
     }
 
+    /// Returns whether any of this component's resource tables contain
+    /// handles.
+    pub fn has_resource_handles(&self) -> bool {
+        self.component_resource_tables
+            .values()
+            .any(|table| !table.is_empty())
+    }
+
     /// Returns the runtime state of resources associated with this component.
     #[inline]
     pub fn component_resource_tables(
@@ -291,6 +291,13 @@ impl ResourceTables<'_> {
 }
 
 impl ResourceTable {
+    /// Returns whether this table contains no handles.
+    pub fn is_empty(&self) -> bool {
+        self.slots
+            .iter()
+            .all(|slot| matches!(slot, Slot::Free { .. }))
+    }
+
     fn insert(&mut self, new: Slot) -> Result<u32> {
         let next = self.next as usize;
         if next == self.slots.len() {
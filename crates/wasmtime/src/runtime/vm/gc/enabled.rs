@@ -21,6 +21,11 @@ mod null;
 #[cfg(feature = "gc-null")]
 pub use null::*;
 
+#[cfg(feature = "gc-copying")]
+mod copying;
+#[cfg(feature = "gc-copying")]
+pub use copying::*;
+
 use crate::runtime::vm::GcRuntime;
 
 /// The default GC heap capacity: 512KiB.
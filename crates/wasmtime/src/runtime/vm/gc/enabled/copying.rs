@@ -0,0 +1,720 @@
+//! The semi-space copying collector.
+//!
+//! The copying collector splits its GC heap into two equally-sized
+//! semi-spaces. Objects are bump allocated in the active semi-space until it is
+//! exhausted, at which point a collection is performed: every object reachable
+//! from the GC roots is evacuated into the other semi-space, the GC roots and
+//! all references between live objects are updated to point to the objects'
+//! new locations, and the two semi-spaces swap roles. Unreachable objects,
+//! including cycles of unreachable objects, are never copied and are therefore
+//! reclaimed all at once.
+//!
+//! Evacuation uses Cheney's algorithm: the region of to-space between the
+//! "scan" and "next" fingers acts as the work queue of objects whose outgoing
+//! edges have yet to be forwarded, so no additional memory is required to
+//! trace the heap. When an object is evacuated, its from-space header is
+//! overwritten with a forwarding reference to its new location, so that other
+//! references to the same object are forwarded to the same copy.
+//!
+//! Because objects move, compiled Wasm code must include every live GC
+//! reference in its stack maps, and runtime code must only hold onto GC
+//! references via GC roots across any operation that may trigger a collection.
+//! The collector requires no read or write barriers.
+//!
+//! Every object is aligned to `OBJECT_ALIGN` bytes, which guarantees that the
+//! evacuated copies of the live objects in one semi-space always fit in the
+//! other semi-space, regardless of the order in which they are evacuated.
+
+use super::*;
+use crate::{
+    hash_map::HashMap,
+    prelude::*,
+    vm::{
+        ExternRefHostDataId, ExternRefHostDataTable, GarbageCollection, GcHeap, GcHeapObject,
+        GcProgress, GcRootsIter, Mmap, TypedGcRef, VMGcHeader, VMGcRef,
+    },
+    Engine, EngineWeak, GcHeapOutOfMemory,
+};
+use core::{
+    alloc::Layout,
+    any::Any,
+    cell::UnsafeCell,
+    num::{NonZeroU32, NonZeroUsize},
+    ops::Range,
+    ptr,
+};
+use wasmtime_environ::{
+    copying::{CopyingTypeLayouts, OBJECT_ALIGN},
+    GcArrayLayout, GcLayout, GcStructLayout, GcTypeLayouts, VMGcKind, VMSharedTypeIndex,
+    WasmCompositeInnerType, WasmStorageType,
+};
+
+/// The semi-space copying collector.
+#[derive(Default)]
+pub struct CopyingCollector {
+    layouts: CopyingTypeLayouts,
+}
+
+unsafe impl GcRuntime for CopyingCollector {
+    fn layouts(&self) -> &dyn GcTypeLayouts {
+        &self.layouts
+    }
+
+    fn new_gc_heap(&self, engine: &Engine) -> Result<Box<dyn GcHeap>> {
+        let heap = CopyingHeap::new(engine)?;
+        Ok(Box::new(heap) as _)
+    }
+}
+
+/// A GC heap for the copying collector.
+struct CopyingHeap {
+    /// The engine that this heap's objects' types are registered within.
+    ///
+    /// This is a weak reference to avoid a cycle between the engine, its
+    /// pooling allocator, and the pooling allocator's GC heaps.
+    engine: EngineWeak,
+
+    /// The number of active no-gc scopes at the current moment.
+    no_gc_count: usize,
+
+    /// The semi-space that new objects are allocated within.
+    active: Range<u32>,
+
+    /// The semi-space that live objects are evacuated into during the next
+    /// collection.
+    idle: Range<u32>,
+
+    /// Bump-allocation finger within `self.active`.
+    ///
+    /// Always a multiple of `OBJECT_ALIGN`.
+    next: u32,
+
+    /// Cached tracing information for each type of object that has been
+    /// traced in this heap.
+    ///
+    /// Every type with objects in this heap is kept registered by the owning
+    /// store for the store's whole lifetime, so type indices are stable until
+    /// this heap is `reset`.
+    trace_infos: HashMap<VMSharedTypeIndex, TraceInfo>,
+
+    /// The offsets of GC reference fields within structs, indexed by
+    /// `TraceInfo::Struct` ranges.
+    struct_gc_ref_offsets: Vec<u32>,
+
+    /// The actual GC heap.
+    heap: Mmap,
+}
+
+/// How to find the outgoing GC edges of objects of a particular type.
+#[derive(Clone)]
+enum TraceInfo {
+    /// A struct type whose GC reference fields are at the offsets in
+    /// `CopyingHeap::struct_gc_ref_offsets[gc_ref_offsets]`.
+    Struct { gc_ref_offsets: Range<usize> },
+
+    /// An array type.
+    Array {
+        /// The offset of the first element in the array.
+        base_size: u32,
+        /// Whether the array's elements are GC references.
+        elems_are_gc_refs: bool,
+    },
+}
+
+/// The common header for all arrays in the copying collector.
+#[repr(C)]
+struct VMCopyingArrayHeader {
+    header: VMGcHeader,
+    length: u32,
+}
+
+unsafe impl GcHeapObject for VMCopyingArrayHeader {
+    #[inline]
+    fn is(header: &VMGcHeader) -> bool {
+        header.kind() == VMGcKind::ArrayRef
+    }
+}
+
+impl VMCopyingArrayHeader {
+    fn typed_ref<'a>(
+        gc_heap: &CopyingHeap,
+        array: &'a VMArrayRef,
+    ) -> &'a TypedGcRef<VMCopyingArrayHeader> {
+        let gc_ref = array.as_gc_ref();
+        debug_assert!(gc_ref.is_typed::<VMCopyingArrayHeader>(gc_heap));
+        gc_ref.as_typed_unchecked()
+    }
+}
+
+/// The representation of an `externref` in the copying collector.
+#[repr(C)]
+struct VMCopyingExternRef {
+    header: VMGcHeader,
+    host_data: ExternRefHostDataId,
+}
+
+unsafe impl GcHeapObject for VMCopyingExternRef {
+    #[inline]
+    fn is(header: &VMGcHeader) -> bool {
+        header.kind() == VMGcKind::ExternRef
+    }
+}
+
+impl VMCopyingExternRef {
+    /// Convert a generic `externref` to a typed reference to our concrete
+    /// `externref` type.
+    fn typed_ref<'a>(
+        gc_heap: &CopyingHeap,
+        externref: &'a VMExternRef,
+    ) -> &'a TypedGcRef<VMCopyingExternRef> {
+        let gc_ref = externref.as_gc_ref();
+        debug_assert!(gc_ref.is_typed::<VMCopyingExternRef>(gc_heap));
+        gc_ref.as_typed_unchecked()
+    }
+}
+
+fn oom() -> Error {
+    GcHeapOutOfMemory::new(()).into_anyhow()
+}
+
+fn is_gc_ref(ty: &WasmStorageType) -> bool {
+    matches!(ty, WasmStorageType::Val(v) if v.is_vmgcref_type())
+}
+
+impl CopyingHeap {
+    /// Construct a new, default heap for the copying collector.
+    fn new(engine: &Engine) -> Result<Self> {
+        Self::with_capacity(engine, super::DEFAULT_GC_HEAP_CAPACITY)
+    }
+
+    /// Create a new copying heap with the given capacity.
+    ///
+    /// Note that only half of the capacity is usable for objects at any given
+    /// time.
+    fn with_capacity(engine: &Engine, capacity: usize) -> Result<Self> {
+        let heap = Mmap::with_at_least(capacity)?;
+
+        // Skip the first `OBJECT_ALIGN` bytes, since index zero is the null
+        // reference, and split the rest of the heap into two equally-sized,
+        // aligned semi-spaces.
+        let heap_len = u32::try_from(heap.len()).unwrap_or(u32::MAX);
+        let semi_space_size = ((heap_len - OBJECT_ALIGN) / 2) & !(OBJECT_ALIGN - 1);
+        let active = OBJECT_ALIGN..OBJECT_ALIGN + semi_space_size;
+        let idle = active.end..active.end + semi_space_size;
+
+        Ok(Self {
+            engine: engine.weak(),
+            no_gc_count: 0,
+            next: active.start,
+            active,
+            idle,
+            trace_infos: HashMap::new(),
+            struct_gc_ref_offsets: Vec::new(),
+            heap,
+        })
+    }
+
+    fn alloc(&mut self, mut header: VMGcHeader, layout: Layout) -> Result<Option<VMGcRef>> {
+        debug_assert!(layout.size() >= core::mem::size_of::<VMGcHeader>());
+        debug_assert!(layout.align() >= core::mem::align_of::<VMGcHeader>());
+        debug_assert!(layout.align() <= usize::try_from(OBJECT_ALIGN).unwrap());
+
+        // Make sure that the requested allocation's size fits in the GC
+        // header's unused bits.
+        let size = match u32::try_from(layout.size()).ok().and_then(|size| {
+            if VMGcKind::value_fits_in_unused_bits(size) {
+                Some(size)
+            } else {
+                None
+            }
+        }) {
+            Some(size) => size,
+            None => return Err(crate::Trap::AllocationTooLarge.into_anyhow()),
+        };
+
+        // If the object can never fit in a semi-space, then no amount of
+        // collecting garbage will help.
+        if size > self.active.end - self.active.start {
+            return Err(oom());
+        }
+
+        // Check whether the allocation fits in the active semi-space's
+        // remaining capacity. If not, the caller should collect garbage and
+        // then try again.
+        let start = self.next;
+        let end_of_object = match start.checked_add(size) {
+            Some(end) if end <= self.active.end => end,
+            _ => return Ok(None),
+        };
+
+        // Update the bump pointer, write the header, and return the GC ref.
+        self.next = end_of_object.next_multiple_of(OBJECT_ALIGN);
+
+        let gc_ref = VMGcRef::from_heap_index(NonZeroU32::new(start).unwrap()).unwrap();
+
+        debug_assert_eq!(header.reserved_u27(), 0);
+        header.set_reserved_u27(size);
+        *self.header_mut(&gc_ref) = header;
+
+        Ok(Some(gc_ref))
+    }
+
+    /// Get the forwarding reference for an already-evacuated from-space
+    /// object, if any.
+    ///
+    /// Evacuated objects have their size bits cleared (no live object has size
+    /// zero) and their type index replaced with the heap index of their new
+    /// location.
+    fn forwarding_ref(&self, gc_ref: &VMGcRef) -> Option<VMGcRef> {
+        let header = self.header(gc_ref);
+        if header.reserved_u27() != 0 {
+            return None;
+        }
+        let new_index = header.ty().expect("forwarded objects have a new index");
+        Some(VMGcRef::from_raw_u32(new_index.bits()).expect("forwarding ref is non-null"))
+    }
+
+    /// Overwrite the from-space object `old`'s header with a forwarding
+    /// reference to `new`.
+    fn set_forwarding_ref(&mut self, old: &VMGcRef, new: &VMGcRef) {
+        let kind = self.header(old).kind();
+        let new_index = VMSharedTypeIndex::new(new.as_raw_u32());
+        *self.header_mut(old) = VMGcHeader::from_kind_and_index(kind, new_index);
+    }
+
+    /// Get the tracing information for objects of the given type.
+    fn trace_info(&mut self, ty: VMSharedTypeIndex) -> TraceInfo {
+        if let Some(info) = self.trace_infos.get(&ty) {
+            return info.clone();
+        }
+
+        let engine = self
+            .engine
+            .upgrade()
+            .expect("engine should outlive its GC heaps");
+        let sub_ty = engine
+            .signatures()
+            .borrow(ty)
+            .expect("GC object types should be registered while objects exist");
+        let layout = engine
+            .signatures()
+            .layout(ty)
+            .expect("GC object types should have GC layouts");
+
+        let info = match (&sub_ty.composite_type.inner, layout) {
+            (WasmCompositeInnerType::Struct(s), GcLayout::Struct(l)) => {
+                let start = self.struct_gc_ref_offsets.len();
+                self.struct_gc_ref_offsets.extend(
+                    s.fields
+                        .iter()
+                        .zip(l.fields.iter())
+                        .filter(|(field, _)| is_gc_ref(&field.element_type))
+                        .map(|(_, offset)| *offset),
+                );
+                let end = self.struct_gc_ref_offsets.len();
+                TraceInfo::Struct {
+                    gc_ref_offsets: start..end,
+                }
+            }
+            (WasmCompositeInnerType::Array(a), GcLayout::Array(l)) => TraceInfo::Array {
+                base_size: l.base_size,
+                elems_are_gc_refs: is_gc_ref(&a.0.element_type),
+            },
+            _ => unreachable!("GC object types are either structs or arrays"),
+        };
+
+        self.trace_infos.insert(ty, info.clone());
+        info
+    }
+
+    fn read_u32(&self, index: usize) -> u32 {
+        let bytes = &self.heap_bytes()[index..][..4];
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    fn write_u32(&mut self, index: usize, value: u32) {
+        self.heap_slice_mut()[index..][..4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// A shared view of the heap's bytes.
+    ///
+    /// Only used during collection, when Wasm cannot be concurrently mutating
+    /// the heap.
+    fn heap_bytes(&self) -> &[u8] {
+        let ptr = self.heap.as_ptr();
+        let len = self.heap.len();
+        unsafe { core::slice::from_raw_parts(ptr, len) }
+    }
+
+    /// Evacuate the given from-space object into to-space, if it hasn't been
+    /// already, and return its new location.
+    fn evacuate(&mut self, gc_ref: &VMGcRef) -> VMGcRef {
+        debug_assert!(!gc_ref.is_i31());
+
+        // References that already point into to-space have already been
+        // forwarded (for example, a root that was reported more than once).
+        if self.active.contains(&gc_ref.as_heap_index().unwrap().get()) {
+            return gc_ref.unchecked_copy();
+        }
+
+        if let Some(new_ref) = self.forwarding_ref(gc_ref) {
+            return new_ref;
+        }
+
+        let size = self.object_size(gc_ref);
+        let size_u32 = u32::try_from(size).unwrap();
+
+        let new_index = self.next;
+        let end_of_object = new_index.checked_add(size_u32).unwrap();
+        assert!(
+            end_of_object <= self.active.end,
+            "live objects should always fit in to-space"
+        );
+        self.next = end_of_object.next_multiple_of(OBJECT_ALIGN);
+
+        let old_index = usize::try_from(gc_ref.as_heap_index().unwrap().get()).unwrap();
+        let new_index_usize = usize::try_from(new_index).unwrap();
+        self.heap_slice_mut()
+            .copy_within(old_index..old_index + size, new_index_usize);
+
+        let new_ref = VMGcRef::from_heap_index(NonZeroU32::new(new_index).unwrap()).unwrap();
+        log::trace!("evacuated {gc_ref:#p} -> {new_ref:#p}");
+        self.set_forwarding_ref(gc_ref, &new_ref);
+        new_ref
+    }
+
+    /// Forward the GC reference stored at the given heap index, evacuating its
+    /// referent if necessary.
+    fn forward_field(&mut self, index: usize) {
+        let raw = self.read_u32(index);
+        let gc_ref = match VMGcRef::from_raw_u32(raw) {
+            Some(r) if !r.is_i31() => r,
+            _ => return,
+        };
+        let new_ref = self.evacuate(&gc_ref);
+        self.write_u32(index, new_ref.as_raw_u32());
+    }
+
+    /// Forward all of the outgoing edges of the given to-space object.
+    fn scan(&mut self, gc_ref: &VMGcRef) {
+        // Objects without a concrete type, i.e. `externref`s, do not have any
+        // outgoing edges.
+        let ty = match self.header(gc_ref).ty() {
+            Some(ty) => ty,
+            None => return,
+        };
+
+        let object = usize::try_from(gc_ref.as_heap_index().unwrap().get()).unwrap();
+        match self.trace_info(ty) {
+            TraceInfo::Struct { gc_ref_offsets } => {
+                for i in gc_ref_offsets {
+                    let offset = usize::try_from(self.struct_gc_ref_offsets[i]).unwrap();
+                    self.forward_field(object + offset);
+                }
+            }
+            TraceInfo::Array {
+                base_size,
+                elems_are_gc_refs,
+            } => {
+                if !elems_are_gc_refs {
+                    return;
+                }
+                let len = self
+                    .index::<VMCopyingArrayHeader>(gc_ref.as_typed_unchecked())
+                    .length;
+                let elems = object + usize::try_from(base_size).unwrap();
+                for i in 0..usize::try_from(len).unwrap() {
+                    self.forward_field(elems + i * core::mem::size_of::<u32>());
+                }
+            }
+        }
+    }
+
+    /// Perform a full collection.
+    fn collect(
+        &mut self,
+        roots: &mut GcRootsIter<'_>,
+        host_data_table: &mut ExternRefHostDataTable,
+    ) {
+        // Flip the semi-spaces.
+        let from_space = self.active.start..self.next;
+        core::mem::swap(&mut self.active, &mut self.idle);
+        self.next = self.active.start;
+
+        // Evacuate the objects directly referenced by roots.
+        for mut root in roots {
+            let gc_ref = root.get();
+            if gc_ref.is_i31() {
+                continue;
+            }
+            let new_ref = self.evacuate(&gc_ref);
+            root.set(new_ref);
+        }
+
+        // Scan the evacuated objects, evacuating everything they reference in
+        // turn, until we've caught up with the allocation finger.
+        let mut scan = self.active.start;
+        while scan < self.next {
+            let gc_ref = VMGcRef::from_heap_index(NonZeroU32::new(scan).unwrap()).unwrap();
+            self.scan(&gc_ref);
+            let size = u32::try_from(self.object_size(&gc_ref)).unwrap();
+            scan = (scan + size).next_multiple_of(OBJECT_ALIGN);
+        }
+
+        self.sweep_from_space(from_space, host_data_table);
+    }
+
+    /// Reclaim the host data of every `externref` that was not evacuated out
+    /// of from-space, and then zero from-space so that it is ready for reuse.
+    fn sweep_from_space(
+        &mut self,
+        from_space: Range<u32>,
+        host_data_table: &mut ExternRefHostDataTable,
+    ) {
+        let mut index = from_space.start;
+        while index < from_space.end {
+            let gc_ref = VMGcRef::from_heap_index(NonZeroU32::new(index).unwrap()).unwrap();
+            let size = match self.forwarding_ref(&gc_ref) {
+                Some(new_ref) => self.object_size(&new_ref),
+                None => {
+                    if self.header(&gc_ref).kind() == VMGcKind::ExternRef {
+                        let host_data = self
+                            .index::<VMCopyingExternRef>(gc_ref.as_typed_unchecked())
+                            .host_data;
+                        log::trace!("reclaiming {gc_ref:#p}'s host data");
+                        host_data_table.dealloc(host_data);
+                    }
+                    self.object_size(&gc_ref)
+                }
+            };
+            let size = u32::try_from(size).unwrap();
+            index = (index + size).next_multiple_of(OBJECT_ALIGN);
+        }
+
+        let from_space =
+            usize::try_from(from_space.start).unwrap()..usize::try_from(from_space.end).unwrap();
+        self.heap_slice_mut()[from_space].fill(0);
+    }
+}
+
+unsafe impl GcHeap for CopyingHeap {
+    fn as_any(&self) -> &dyn Any {
+        self as _
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self as _
+    }
+
+    fn enter_no_gc_scope(&mut self) {
+        self.no_gc_count += 1;
+    }
+
+    fn exit_no_gc_scope(&mut self) {
+        self.no_gc_count -= 1;
+    }
+
+    fn heap_slice(&self) -> &[UnsafeCell<u8>] {
+        let ptr = self.heap.as_ptr().cast();
+        let len = self.heap.len();
+        unsafe { core::slice::from_raw_parts(ptr, len) }
+    }
+
+    fn heap_slice_mut(&mut self) -> &mut [u8] {
+        let ptr = self.heap.as_mut_ptr();
+        let len = self.heap.len();
+        unsafe { core::slice::from_raw_parts_mut(ptr, len) }
+    }
+
+    fn clone_gc_ref(&mut self, gc_ref: &VMGcRef) -> VMGcRef {
+        gc_ref.unchecked_copy()
+    }
+
+    fn write_gc_ref(
+        &mut self,
+        _host_data_table: &mut ExternRefHostDataTable,
+        destination: &mut Option<VMGcRef>,
+        source: Option<&VMGcRef>,
+    ) {
+        *destination = source.map(|s| s.unchecked_copy());
+    }
+
+    fn expose_gc_ref_to_wasm(&mut self, _gc_ref: VMGcRef) {
+        // Don't need to do anything special here: references held by Wasm are
+        // found via stack maps during collection.
+    }
+
+    fn need_gc_before_entering_wasm(&self, _num_gc_refs: NonZeroUsize) -> bool {
+        // Never need to GC before entering Wasm.
+        false
+    }
+
+    fn alloc_externref(&mut self, host_data: ExternRefHostDataId) -> Result<Option<VMExternRef>> {
+        let gc_ref =
+            match self.alloc(VMGcHeader::externref(), Layout::new::<VMCopyingExternRef>())? {
+                None => return Ok(None),
+                Some(gc_ref) => gc_ref,
+            };
+        self.index_mut::<VMCopyingExternRef>(gc_ref.as_typed_unchecked())
+            .host_data = host_data;
+        Ok(Some(gc_ref.into_externref_unchecked()))
+    }
+
+    fn externref_host_data(&self, externref: &VMExternRef) -> ExternRefHostDataId {
+        let typed_ref = VMCopyingExternRef::typed_ref(self, externref);
+        self.index(typed_ref).host_data
+    }
+
+    fn object_size(&self, gc_ref: &VMGcRef) -> usize {
+        let size = self.header(gc_ref).reserved_u27();
+        usize::try_from(size).unwrap()
+    }
+
+    fn header(&self, gc_ref: &VMGcRef) -> &VMGcHeader {
+        self.index(gc_ref.as_typed_unchecked())
+    }
+
+    fn header_mut(&mut self, gc_ref: &VMGcRef) -> &mut VMGcHeader {
+        self.index_mut(gc_ref.as_typed_unchecked())
+    }
+
+    fn alloc_raw(&mut self, header: VMGcHeader, layout: Layout) -> Result<Option<VMGcRef>> {
+        self.alloc(header, layout)
+    }
+
+    fn alloc_uninit_struct(
+        &mut self,
+        ty: VMSharedTypeIndex,
+        layout: &GcStructLayout,
+    ) -> Result<Option<VMStructRef>> {
+        let gc_ref = match self.alloc(
+            VMGcHeader::from_kind_and_index(VMGcKind::StructRef, ty),
+            layout.layout(),
+        )? {
+            None => return Ok(None),
+            Some(gc_ref) => gc_ref,
+        };
+        Ok(Some(gc_ref.into_structref_unchecked()))
+    }
+
+    fn dealloc_uninit_struct(&mut self, _struct_ref: VMStructRef) {
+        // The object is unreachable, so it will not be evacuated at the next
+        // collection.
+    }
+
+    fn alloc_uninit_array(
+        &mut self,
+        ty: VMSharedTypeIndex,
+        length: u32,
+        layout: &GcArrayLayout,
+    ) -> Result<Option<VMArrayRef>> {
+        let gc_ref = match self.alloc(
+            VMGcHeader::from_kind_and_index(VMGcKind::ArrayRef, ty),
+            layout.layout(length),
+        )? {
+            None => return Ok(None),
+            Some(gc_ref) => gc_ref,
+        };
+        self.index_mut::<VMCopyingArrayHeader>(gc_ref.as_typed_unchecked())
+            .length = length;
+        Ok(Some(gc_ref.into_arrayref_unchecked()))
+    }
+
+    fn dealloc_uninit_array(&mut self, _array_ref: VMArrayRef) {
+        // The object is unreachable, so it will not be evacuated at the next
+        // collection.
+    }
+
+    fn array_len(&self, arrayref: &VMArrayRef) -> u32 {
+        let arrayref = VMCopyingArrayHeader::typed_ref(self, arrayref);
+        self.index(arrayref).length
+    }
+
+    fn gc<'a>(
+        &'a mut self,
+        roots: GcRootsIter<'a>,
+        host_data_table: &'a mut ExternRefHostDataTable,
+    ) -> Box<dyn GarbageCollection<'a> + 'a> {
+        assert_eq!(self.no_gc_count, 0, "Cannot GC inside a no-GC scope!");
+        Box::new(CopyingCollection {
+            roots,
+            host_data_table,
+            heap: self,
+            done: false,
+        })
+    }
+
+    unsafe fn vmctx_gc_heap_data(&self) -> *mut u8 {
+        // Compiled code allocates via libcalls and doesn't need any additional
+        // data from the heap.
+        ptr::null_mut()
+    }
+
+    #[cfg(feature = "pooling-allocator")]
+    fn reset(&mut self) {
+        // Zero the objects left behind by the previous store, so that the next
+        // store's newly-allocated objects are zero-initialized.
+        let used = usize::try_from(self.active.start).unwrap()..usize::try_from(self.next).unwrap();
+        self.heap_slice_mut()[used].fill(0);
+
+        let CopyingHeap {
+            engine: _,
+            no_gc_count,
+            active,
+            idle: _,
+            next,
+            trace_infos,
+            struct_gc_ref_offsets,
+            heap: _,
+        } = self;
+
+        *next = active.start;
+        *no_gc_count = 0;
+        trace_infos.clear();
+        struct_gc_ref_offsets.clear();
+    }
+}
+
+struct CopyingCollection<'a> {
+    roots: GcRootsIter<'a>,
+    host_data_table: &'a mut ExternRefHostDataTable,
+    heap: &'a mut CopyingHeap,
+    done: bool,
+}
+
+impl<'a> GarbageCollection<'a> for CopyingCollection<'a> {
+    fn collect_increment(&mut self) -> GcProgress {
+        if !self.done {
+            log::trace!("Begin copying collection");
+            self.heap.collect(&mut self.roots, self.host_data_table);
+            log::trace!("End copying collection");
+            self.done = true;
+        }
+        GcProgress::Complete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vm_gc_copying_header_size_align() {
+        assert_eq!(
+            (wasmtime_environ::copying::HEADER_SIZE as usize),
+            core::mem::size_of::<VMGcHeader>()
+        );
+        assert_eq!(
+            (wasmtime_environ::copying::HEADER_ALIGN as usize),
+            core::mem::align_of::<VMGcHeader>()
+        );
+    }
+
+    #[test]
+    fn vm_copying_array_header_length_offset() {
+        assert_eq!(
+            wasmtime_environ::copying::ARRAY_LENGTH_OFFSET,
+            u32::try_from(core::mem::offset_of!(VMCopyingArrayHeader, length)).unwrap(),
+        );
+    }
+}
@@ -78,7 +78,7 @@ unsafe impl GcRuntime for DrcCollector {
         &self.layouts
     }
 
-    fn new_gc_heap(&self) -> Result<Box<dyn GcHeap>> {
+    fn new_gc_heap(&self, _engine: &crate::Engine) -> Result<Box<dyn GcHeap>> {
         let heap = DrcHeap::new()?;
         Ok(Box::new(heap) as _)
     }
@@ -1028,6 +1028,7 @@ mod tests {
             num_owned_memories: 0,
             num_defined_globals: 0,
             num_escaped_funcs: 0,
+            num_tier_up_counters: 0,
         });
 
         assert_eq!(
@@ -1056,6 +1057,7 @@ mod tests {
             num_owned_memories: 0,
             num_defined_globals: 0,
             num_escaped_funcs: 0,
+            num_tier_up_counters: 0,
         });
         assert_eq!(
             offsets.vm_gc_ref_activation_table_next() as usize,
@@ -1083,6 +1085,7 @@ mod tests {
             num_owned_memories: 0,
             num_defined_globals: 0,
             num_escaped_funcs: 0,
+            num_tier_up_counters: 0,
         });
         assert_eq!(
             offsets.vm_gc_ref_activation_table_end() as usize,
@@ -35,7 +35,7 @@ unsafe impl GcRuntime for NullCollector {
         &self.layouts
     }
 
-    fn new_gc_heap(&self) -> Result<Box<dyn GcHeap>> {
+    fn new_gc_heap(&self, _engine: &crate::Engine) -> Result<Box<dyn GcHeap>> {
         let heap = NullHeap::new()?;
         Ok(Box::new(heap) as _)
     }
@@ -35,8 +35,8 @@ pub unsafe trait GcRuntime: 'static + Send + Sync {
     /// Get this collector's GC type layouts.
     fn layouts(&self) -> &dyn GcTypeLayouts;
 
-    /// Construct a new GC heap.
-    fn new_gc_heap(&self) -> Result<Box<dyn GcHeap>>;
+    /// Construct a new GC heap for use with stores belonging to `engine`.
+    fn new_gc_heap(&self, engine: &crate::Engine) -> Result<Box<dyn GcHeap>>;
 }
 
 /// A heap that manages garbage-collected objects.
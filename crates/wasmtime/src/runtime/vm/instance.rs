@@ -9,7 +9,7 @@ use crate::runtime::vm::table::{Table, TableElement, TableElementType};
 use crate::runtime::vm::vmcontext::{
     VMBuiltinFunctionsArray, VMContext, VMFuncRef, VMFunctionImport, VMGlobalDefinition,
     VMGlobalImport, VMMemoryDefinition, VMMemoryImport, VMOpaqueContext, VMRuntimeLimits,
-    VMTableDefinition, VMTableImport,
+    VMTableDefinition, VMTableImport, VMWasmCallFunction,
 };
 use crate::runtime::vm::{
     ExportFunction, ExportGlobal, ExportMemory, ExportTable, GcStore, Imports, ModuleRuntimeInfo,
@@ -26,11 +26,11 @@ use core::sync::atomic::AtomicU64;
 use core::{mem, ptr};
 use sptr::Strict;
 use wasmtime_environ::{
-    packed_option::ReservedValue, DataIndex, DefinedGlobalIndex, DefinedMemoryIndex,
-    DefinedTableIndex, ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex, GlobalIndex,
-    HostPtr, MemoryIndex, Module, ModuleInternedTypeIndex, PrimaryMap, PtrSize, TableIndex,
-    TableInitialValue, TableSegmentElements, Trap, VMOffsets, VMSharedTypeIndex, WasmHeapTopType,
-    VMCONTEXT_MAGIC,
+    packed_option::ReservedValue, DataIndex, DefinedFuncIndex, DefinedGlobalIndex,
+    DefinedMemoryIndex, DefinedTableIndex, ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex,
+    GlobalIndex, HostPtr, MemoryIndex, Module, ModuleInternedTypeIndex, PrimaryMap, PtrSize,
+    TableIndex, TableInitialValue, TableSegmentElements, Trap, VMOffsets, VMSharedTypeIndex,
+    WasmHeapTopType, VMCONTEXT_MAGIC,
 };
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::Wmemcheck;
@@ -224,6 +224,14 @@ pub struct Instance {
     /// If the index is present in the set, the segment has been dropped.
     dropped_data: EntitySet<DataIndex>,
 
+    /// The module containing optimized code for this instance's functions, if
+    /// any function in this instance has been tiered up.
+    optimized_tier: Option<ModuleRuntimeInfo>,
+
+    /// Stores the defined functions in this instantiation which have been
+    /// switched over to the code in `optimized_tier`.
+    tiered_up_funcs: EntitySet<DefinedFuncIndex>,
+
     /// Hosts can store arbitrary per-instance information here.
     ///
     /// Most of the time from Wasmtime this is `Box::new(())`, a noop
@@ -321,6 +329,8 @@ impl Instance {
                 tables,
                 dropped_elements,
                 dropped_data,
+                optimized_tier: None,
+                tiered_up_funcs: EntitySet::new(),
                 host_state: req.host_state,
                 vmctx_self_reference: SendSyncPtr::new(NonNull::new(ptr.add(1).cast()).unwrap()),
                 vmctx: VMContext {
@@ -848,12 +858,15 @@ impl Instance {
         };
 
         let func_ref = if let Some(def_index) = self.env_module().defined_func_index(index) {
+            let runtime_info = match &self.optimized_tier {
+                Some(optimized) if self.tiered_up_funcs.contains(def_index) => optimized,
+                _ => &self.runtime_info,
+            };
             VMFuncRef {
-                array_call: self
-                    .runtime_info
+                array_call: runtime_info
                     .array_to_wasm_trampoline(def_index)
                     .expect("should have array-to-Wasm trampoline for escaping function"),
-                wasm_call: Some(self.runtime_info.function(def_index)),
+                wasm_call: Some(runtime_info.function(def_index)),
                 vmctx: VMOpaqueContext::from_vmcontext(self.vmctx()),
                 type_index,
             }
@@ -1069,6 +1082,11 @@ impl Instance {
         // dropping a non-passive segment is a no-op (not a trap).
     }
 
+    /// Returns whether the given element segment has been dropped.
+    pub(crate) fn elem_dropped(&self, elem_index: ElemIndex) -> bool {
+        self.dropped_elements.contains(elem_index)
+    }
+
     /// Get a locally-defined memory.
     pub fn get_defined_memory(&mut self, index: DefinedMemoryIndex) -> *mut Memory {
         ptr::addr_of_mut!(self.memories[index].1)
@@ -1221,6 +1239,81 @@ impl Instance {
         // dropping a non-passive segment is a no-op (not a trap).
     }
 
+    /// Returns whether the given data segment has been dropped.
+    pub(crate) fn data_dropped(&self, data_index: DataIndex) -> bool {
+        self.dropped_data.contains(data_index)
+    }
+
+    /// Handles the tier-up counter of the defined function `index` reaching
+    /// zero.
+    ///
+    /// If the optimizing tier of this instance's module is ready then the
+    /// function is switched over to it and its counter is disabled. Otherwise
+    /// compilation of the optimizing tier is started, if it isn't already
+    /// running, and the counter is reset.
+    pub(crate) fn tier_up(&mut self, store: &mut StoreOpaque, index: DefinedFuncIndex) {
+        let counter = self.try_tier_up(store, index);
+        unsafe {
+            let offset = self.offsets().vmctx_tier_up_counter(index);
+            *self.vmctx_plus_offset_mut::<u32>(offset) = counter;
+        }
+    }
+
+    /// Attempts to switch the defined function `index` over to the optimizing
+    /// tier, returning the new value of its tier-up counter.
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    fn try_tier_up(&mut self, store: &mut StoreOpaque, index: DefinedFuncIndex) -> u32 {
+        use crate::module::TierUpStatus;
+
+        let status = match self.runtime_module().and_then(|m| m.tier_up()) {
+            Some(tier_up) => tier_up.hot(),
+            None => TierUpStatus::Unavailable,
+        };
+        let optimized = match status {
+            TierUpStatus::Compiling => return self.tier_up_threshold(),
+            TierUpStatus::Unavailable => return u32::MAX,
+            TierUpStatus::Ready(optimized) => optimized,
+        };
+
+        if self.optimized_tier.is_none() {
+            // Register the optimized code with the store so that traps and
+            // stack walks within it are attributed to this module.
+            store.modules_mut().register_module(&optimized);
+            self.optimized_tier = Some(ModuleRuntimeInfo::Module(optimized));
+        }
+        self.tiered_up_funcs.insert(index);
+
+        // Patch the code pointer that direct calls from Winch code within this
+        // instance go through.
+        let code = self.optimized_tier.as_ref().unwrap().function(index);
+        unsafe {
+            let offset = self.offsets().vmctx_tier_up_wasm_call(index);
+            *self.vmctx_plus_offset_mut::<*mut VMWasmCallFunction>(offset) = code.as_ptr();
+        }
+
+        // Refresh this function's `VMFuncRef`, if it has one, so that calls
+        // through tables, `call_ref`, and the embedder API use the optimized
+        // code from now on.
+        let func_index = self.env_module().func_index(index);
+        if self.env_module().functions[func_index].is_escaping() {
+            self.get_func_ref(func_index);
+        }
+        u32::MAX
+    }
+
+    #[cfg(not(all(feature = "cranelift", feature = "winch")))]
+    fn try_tier_up(&mut self, _store: &mut StoreOpaque, _index: DefinedFuncIndex) -> u32 {
+        u32::MAX
+    }
+
+    /// Returns the initial value of tier-up counters for this instance.
+    fn tier_up_threshold(&self) -> u32 {
+        match &self.runtime_info {
+            ModuleRuntimeInfo::Module(m) => m.engine().config().tier_up_threshold.max(1),
+            ModuleRuntimeInfo::Bare(_) => u32::MAX,
+        }
+    }
+
     /// Get a table by index regardless of whether it is locally-defined
     /// or an imported, foreign table. Ensure that the given range of
     /// elements in the table is lazily initialized.  We define this
@@ -1424,6 +1517,27 @@ impl Instance {
         for (index, _init) in module.global_initializers.iter() {
             ptr::write(self.global_ptr(index), VMGlobalDefinition::new());
         }
+
+        // Initialize the tier-up counters, if any, with the engine's
+        // configured threshold, and point direct calls at the baseline code of
+        // each function.
+        if offsets.num_tier_up_counters > 0 {
+            let threshold = self.tier_up_threshold();
+            let mut ptr = self.vmctx_plus_offset_mut::<u32>(offsets.vmctx_tier_up_counters_begin());
+            for _ in 0..offsets.num_tier_up_counters {
+                ptr::write(ptr, threshold);
+                ptr = ptr.add(1);
+            }
+
+            let mut ptr = self.vmctx_plus_offset_mut::<*mut VMWasmCallFunction>(
+                offsets.vmctx_tier_up_wasm_calls_begin(),
+            );
+            for i in 0..offsets.num_tier_up_counters {
+                let index = DefinedFuncIndex::from_u32(i);
+                ptr::write(ptr, self.runtime_info.function(index).as_ptr());
+                ptr = ptr.add(1);
+            }
+        }
     }
 
     fn wasm_fault(&self, addr: usize) -> Option<WasmFault> {
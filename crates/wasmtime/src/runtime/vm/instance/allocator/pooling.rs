@@ -21,6 +21,7 @@
 mod decommit_queue;
 mod index_allocator;
 mod memory_pool;
+mod metrics;
 mod table_pool;
 
 #[cfg(feature = "gc")]
@@ -40,6 +41,8 @@ cfg_if::cfg_if! {
     }
 }
 
+pub use self::metrics::PoolingAllocatorMetrics;
+
 use self::decommit_queue::DecommitQueue;
 use self::memory_pool::MemoryPool;
 use self::table_pool::TablePool;
@@ -680,12 +683,17 @@ unsafe impl InstanceAllocatorImpl for PoolingInstanceAllocator {
         mpk::allow(ProtectionMask::all());
     }
 
+    fn as_pooling(&self) -> Option<&PoolingInstanceAllocator> {
+        Some(self)
+    }
+
     #[cfg(feature = "gc")]
     fn allocate_gc_heap(
         &self,
+        engine: &crate::Engine,
         gc_runtime: &dyn GcRuntime,
     ) -> Result<(GcHeapAllocationIndex, Box<dyn GcHeap>)> {
-        self.gc_heaps.allocate(gc_runtime)
+        self.gc_heaps.allocate(engine, gc_runtime)
     }
 
     #[cfg(feature = "gc")]
@@ -47,6 +47,7 @@ impl GcHeapPool {
     /// Allocate a single table for the given instance allocation request.
     pub fn allocate(
         &self,
+        engine: &crate::Engine,
         gc_runtime: &dyn GcRuntime,
     ) -> Result<(GcHeapAllocationIndex, Box<dyn GcHeap>)> {
         let allocation_index = self
@@ -69,7 +70,7 @@ impl GcHeapPool {
             Some(heap) => heap,
             // Otherwise, we haven't forced this slot's lazily allocated heap
             // yet. So do that now.
-            None => gc_runtime.new_gc_heap()?,
+            None => gc_runtime.new_gc_heap(engine)?,
         };
 
         Ok((allocation_index, heap))
@@ -40,6 +40,10 @@ impl SimpleIndexAllocator {
         self.0.alloc(None)
     }
 
+    pub fn num_used(&self) -> usize {
+        self.0.num_used()
+    }
+
     pub(crate) fn free(&self, index: SlotId) {
         self.0.free(index);
     }
@@ -185,6 +189,16 @@ impl ModuleAffinityIndexAllocator {
             .any(|s| matches!(s, SlotState::Used(_)))
     }
 
+    /// How many slots are in use right now?
+    pub fn num_used(&self) -> usize {
+        let inner = self.0.lock().unwrap();
+        inner
+            .slot_state
+            .iter()
+            .filter(|s| matches!(s, SlotState::Used(_)))
+            .count()
+    }
+
     /// Allocate a new index from this allocator optionally using `id` as an
     /// affinity request if the allocation strategy supports it.
     ///
@@ -300,6 +300,11 @@ impl MemoryPool {
         self.stripes.iter().all(|s| s.allocator.is_empty())
     }
 
+    /// How many slots are in use right now?
+    pub fn num_used(&self) -> usize {
+        self.stripes.iter().map(|s| s.allocator.num_used()).sum()
+    }
+
     /// Allocate a single memory for the given instance allocation request.
     pub fn allocate(
         &self,
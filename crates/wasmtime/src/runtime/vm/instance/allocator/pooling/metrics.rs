@@ -0,0 +1,52 @@
+use super::PoolingInstanceAllocator;
+use crate::Engine;
+use core::sync::atomic::Ordering;
+
+/// Metrics about the current usage of the slots in a pooling allocator.
+///
+/// This is created with [`Engine::pooling_allocator_metrics`] and reports the
+/// state of the engine's pool each time one of its methods is called. Note
+/// that slots which are in the process of being decommitted for reuse are
+/// still counted as in use.
+#[derive(Clone)]
+pub struct PoolingAllocatorMetrics {
+    engine: Engine,
+}
+
+impl PoolingAllocatorMetrics {
+    pub(crate) fn new(engine: &Engine) -> Option<Self> {
+        engine.allocator().as_pooling()?;
+        Some(PoolingAllocatorMetrics {
+            engine: engine.clone(),
+        })
+    }
+
+    fn allocator(&self) -> &PoolingInstanceAllocator {
+        self.engine
+            .allocator()
+            .as_pooling()
+            .expect("engine's allocator is always the pooling allocator")
+    }
+
+    /// Returns the number of core (module) instances currently allocated.
+    pub fn core_instances(&self) -> u64 {
+        self.allocator().live_core_instances.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of component instances currently allocated.
+    pub fn component_instances(&self) -> u64 {
+        self.allocator()
+            .live_component_instances
+            .load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of linear memory slots currently in use.
+    pub fn memories(&self) -> usize {
+        self.allocator().memories.num_used()
+    }
+
+    /// Returns the number of table slots currently in use.
+    pub fn tables(&self) -> usize {
+        self.allocator().tables.num_used()
+    }
+}
@@ -100,6 +100,11 @@ impl TablePool {
         self.index_allocator.is_empty()
     }
 
+    /// How many slots are in use right now?
+    pub fn num_used(&self) -> usize {
+        self.index_allocator.num_used()
+    }
+
     /// Get the base pointer of the given table allocation.
     fn get(&self, table_index: TableAllocationIndex) -> *mut u8 {
         assert!(table_index.index() < self.max_total_tables);
@@ -0,0 +1,230 @@
+//! Execution of Pulley bytecode with the interpreter from the
+//! `pulley-interpreter` crate.
+//!
+//! Each store whose engine targets Pulley owns an [`Interpreter`] and all wasm
+//! in that store runs on the interpreter's stack. Calls from bytecode out to
+//! the host, such as host functions and libcalls, are compiled to
+//! `call_indirect_host` instructions which suspend the interpreter and are
+//! dispatched here based on their [`HostCall`] index.
+
+use crate::prelude::*;
+use crate::runtime::vm::{VMArrayCallFunction, VMContext, VMOpaqueContext, ValRaw};
+use core::marker;
+use core::mem;
+use core::ptr::NonNull;
+use pulley_interpreter::interp::{DoneReason, Vm, XRegVal};
+use pulley_interpreter::regs::Reg;
+use pulley_interpreter::{FReg, XReg};
+use wasmtime_environ::{BuiltinFunctionIndex, HostCall};
+
+/// The Pulley interpreter state of a store.
+pub struct Interpreter {
+    pulley: Box<Vm>,
+}
+
+impl Interpreter {
+    /// Creates a new interpreter with a stack of `stack_size` bytes.
+    pub fn new(stack_size: usize) -> Interpreter {
+        Interpreter {
+            pulley: Box::new(Vm::with_stack(vec![0; stack_size])),
+        }
+    }
+
+    /// Returns a handle to this interpreter which can be used to invoke
+    /// bytecode.
+    pub fn as_interpreter_ref(&mut self) -> InterpreterRef<'_> {
+        InterpreterRef {
+            vm: NonNull::from(&mut *self.pulley),
+            _marker: marker::PhantomData,
+        }
+    }
+}
+
+/// A copyable handle to an [`Interpreter`].
+///
+/// This is passed into closures which run while the owning store is otherwise
+/// borrowed, and re-entrant calls from the host back into wasm use the same
+/// interpreter through it.
+#[derive(Copy, Clone)]
+pub struct InterpreterRef<'a> {
+    vm: NonNull<Vm>,
+    _marker: marker::PhantomData<&'a mut Interpreter>,
+}
+
+/// The special registers of an interpreter, as saved by
+/// [`InterpreterRef::save_regs`].
+pub struct SavedRegs {
+    sp: XRegVal,
+    fp: XRegVal,
+    lr: XRegVal,
+}
+
+impl InterpreterRef<'_> {
+    /// Returns the current stack pointer of the interpreter.
+    pub fn stack_pointer(self) -> usize {
+        unsafe { self.vm.as_ref().state()[XReg::sp].get_ptr::<u8>() as usize }
+    }
+
+    /// Returns the lowest address of the interpreter's stack.
+    pub fn stack_start(self) -> usize {
+        unsafe { self.vm.as_ref().state().stack_range().start as usize }
+    }
+
+    /// Saves the stack pointer, frame pointer and link register.
+    ///
+    /// Traps unwind past bytecode frames without executing their epilogues,
+    /// so these must be restored with [`InterpreterRef::restore_regs`] once
+    /// a call into wasm has finished.
+    pub fn save_regs(self) -> SavedRegs {
+        let state = unsafe { self.vm.as_ref().state() };
+        SavedRegs {
+            sp: state[XReg::sp],
+            fp: state[XReg::fp],
+            lr: state[XReg::lr],
+        }
+    }
+
+    /// Restores registers saved by [`InterpreterRef::save_regs`].
+    pub fn restore_regs(self, saved: SavedRegs) {
+        let state = unsafe { &mut *self.vm.as_ptr() }.state_mut();
+        state[XReg::sp] = saved.sp;
+        state[XReg::fp] = saved.fp;
+        state[XReg::lr] = saved.lr;
+    }
+
+    /// Invokes the `bytecode` of an array-call function with the interpreter.
+    ///
+    /// Traps raised by the bytecode are delivered in the same way as traps
+    /// from native code, so this must be called within `catch_traps`.
+    ///
+    /// # Unsafety
+    ///
+    /// `bytecode` must be the `array_call` entry point of a function compiled
+    /// to Pulley bytecode, and the remaining arguments must be valid for it.
+    pub unsafe fn call(
+        self,
+        mut bytecode: NonNull<u8>,
+        callee: *mut VMOpaqueContext,
+        caller: *mut VMOpaqueContext,
+        args_and_results: *mut ValRaw,
+        args_and_results_len: usize,
+    ) {
+        let vm = self.vm.as_ptr();
+        (*vm).call_start(&[
+            callee.into(),
+            caller.into(),
+            args_and_results.into(),
+            XRegVal::new_u64(args_and_results_len as u64).into(),
+        ]);
+        loop {
+            match (*vm).call_run(bytecode) {
+                DoneReason::ReturnToHost(()) => return,
+                DoneReason::Trap(pc) => {
+                    let fp = (*vm).state()[XReg::fp].get_ptr::<u8>() as usize;
+                    crate::runtime::vm::raise_interpreter_trap(pc.as_ptr() as usize, fp)
+                }
+                DoneReason::CallIndirectHost { id, resume } => {
+                    self.call_indirect_host(id);
+                    bytecode = resume;
+                }
+            }
+        }
+    }
+
+    /// Performs the host call `id` requested by a `call_indirect_host`
+    /// instruction.
+    ///
+    /// The native address of the callee is in `x0` followed by its arguments
+    /// in the order of the Pulley calling convention, and results are written
+    /// back into `x0` or `f0`.
+    unsafe fn call_indirect_host(self, id: u8) {
+        let vm = self.vm.as_ptr();
+        match HostCall::from_index(u32::from(id)) {
+            Some(HostCall::ArrayCall) => {
+                // Read all arguments up front since the callee may re-enter
+                // the interpreter and clobber these registers.
+                let state = (*vm).state();
+                let func = state[XReg::x0].get_ptr::<u8>();
+                let callee = state[XReg::x1].get_ptr::<VMOpaqueContext>();
+                let caller = state[XReg::x2].get_ptr::<VMOpaqueContext>();
+                let args = state[XReg::x3].get_ptr::<ValRaw>();
+                let len = usize::try_from(state[XReg::x4].get_u64()).unwrap();
+                let func = mem::transmute::<*mut u8, VMArrayCallFunction>(func);
+                func(callee, caller, args, len);
+            }
+            Some(HostCall::Builtin(index)) => call_builtin(vm, index),
+            None => unreachable!("invalid host call {id}"),
+        }
+    }
+}
+
+macro_rules! call_builtin {
+    (
+        $(
+            $( #[cfg($attr:meta)] )?
+            $name:ident( vmctx: vmctx $(, $pname:ident: $param:ident )* ) $( -> $result:ident )?;
+        )*
+    ) => {
+        /// Invokes the builtin `index` with its arguments read from the
+        /// registers of `vm`.
+        ///
+        /// `x0` holds the native address of the builtin, which isn't needed
+        /// here since the builtin is called directly.
+        #[allow(unused_mut, unused_variables)]
+        unsafe fn call_builtin(vm: *mut Vm, index: BuiltinFunctionIndex) {
+            $(
+                $( #[cfg($attr)] )?
+                if index == BuiltinFunctionIndex::$name() {
+                    let mut x = 2_u8..;
+                    let mut f = 0_u8..;
+                    let vmctx = (*vm).state()[XReg::x1].get_ptr::<VMContext>();
+                    $(
+                        let $pname = call_builtin!(@get vm x f $param);
+                    )*
+                    call_builtin!(
+                        @call vm
+                        [crate::runtime::vm::libcalls::raw::$name(vmctx $(, $pname)*)]
+                        $($result)?
+                    );
+                    return;
+                }
+            )*
+            unreachable!("unknown builtin {index:?}")
+        }
+    };
+
+    (@get $vm:ident $x:ident $f:ident i32) => (
+        (*$vm).state()[call_builtin!(@x $x)].get_u32()
+    );
+    (@get $vm:ident $x:ident $f:ident i64) => (
+        (*$vm).state()[call_builtin!(@x $x)].get_u64()
+    );
+    (@get $vm:ident $x:ident $f:ident u8) => (
+        ((*$vm).state()[call_builtin!(@x $x)].get_u32() & 0xff) as u8
+    );
+    (@get $vm:ident $x:ident $f:ident reference) => (
+        (*$vm).state()[call_builtin!(@x $x)].get_u32()
+    );
+    (@get $vm:ident $x:ident $f:ident pointer) => (
+        (*$vm).state()[call_builtin!(@x $x)].get_ptr::<u8>()
+    );
+    (@get $vm:ident $x:ident $f:ident f64) => (
+        (*$vm).state()[FReg::new($f.next().unwrap()).unwrap()].get_f64()
+    );
+    (@x $x:ident) => (XReg::new($x.next().unwrap()).unwrap());
+
+    (@call $vm:ident [$($call:tt)*]) => {
+        $($call)*;
+    };
+    (@call $vm:ident [$($call:tt)*] $result:ident) => {
+        let ret = $($call)*;
+        call_builtin!(@set $vm ret $result);
+    };
+
+    (@set $vm:ident $ret:ident i32) => ((*$vm).state_mut()[XReg::x0].set_u32($ret));
+    (@set $vm:ident $ret:ident i64) => ((*$vm).state_mut()[XReg::x0].set_u64($ret));
+    (@set $vm:ident $ret:ident reference) => ((*$vm).state_mut()[XReg::x0].set_u32($ret));
+    (@set $vm:ident $ret:ident pointer) => ((*$vm).state_mut()[XReg::x0].set_ptr($ret));
+}
+
+wasmtime_environ::foreach_builtin_function!(call_builtin);
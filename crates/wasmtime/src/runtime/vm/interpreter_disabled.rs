@@ -0,0 +1,62 @@
+//! Dummy interpreter types for when the `pulley` cargo feature is disabled.
+//!
+//! Engines can't target Pulley without this feature so none of these types
+//! are ever constructed.
+
+#![allow(missing_docs)]
+
+use crate::runtime::vm::{VMOpaqueContext, ValRaw};
+use crate::runtime::Uninhabited;
+use core::marker;
+use core::ptr::NonNull;
+
+pub struct Interpreter {
+    empty: Uninhabited,
+}
+
+impl Interpreter {
+    pub fn new(_stack_size: usize) -> Interpreter {
+        unreachable!()
+    }
+
+    pub fn as_interpreter_ref(&mut self) -> InterpreterRef<'_> {
+        match self.empty {}
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct InterpreterRef<'a> {
+    empty: Uninhabited,
+    _marker: marker::PhantomData<&'a mut Interpreter>,
+}
+
+pub enum SavedRegs {}
+
+impl InterpreterRef<'_> {
+    pub fn stack_pointer(self) -> usize {
+        match self.empty {}
+    }
+
+    pub fn stack_start(self) -> usize {
+        match self.empty {}
+    }
+
+    pub fn save_regs(self) -> SavedRegs {
+        match self.empty {}
+    }
+
+    pub fn restore_regs(self, saved: SavedRegs) {
+        match saved {}
+    }
+
+    pub unsafe fn call(
+        self,
+        _bytecode: NonNull<u8>,
+        _callee: *mut VMOpaqueContext,
+        _caller: *mut VMOpaqueContext,
+        _args_and_results: *mut ValRaw,
+        _args_and_results_len: usize,
+    ) {
+        match self.empty {}
+    }
+}
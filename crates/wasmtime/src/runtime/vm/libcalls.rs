@@ -61,7 +61,9 @@ use crate::runtime::vm::{Instance, TrapReason, VMGcRef, VMStore};
 #[cfg(feature = "threads")]
 use core::time::Duration;
 use wasmtime_environ::Unsigned;
-use wasmtime_environ::{DataIndex, ElemIndex, FuncIndex, MemoryIndex, TableIndex, Trap};
+use wasmtime_environ::{
+    DataIndex, DefinedFuncIndex, ElemIndex, FuncIndex, MemoryIndex, TableIndex, Trap,
+};
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::AccessError::{
     DoubleMalloc, InvalidFree, InvalidRead, InvalidWrite, OutOfBounds,
@@ -487,7 +489,7 @@ unsafe fn gc(store: &mut dyn VMStore, _instance: &mut Instance, gc_ref: u32) ->
 /// Allocate a raw, unininitialized GC object for Wasm code.
 ///
 /// The Wasm code is responsible for initializing the object.
-#[cfg(feature = "gc-drc")]
+#[cfg(any(feature = "gc-drc", feature = "gc-copying"))]
 unsafe fn gc_alloc_raw(
     store: &mut dyn VMStore,
     instance: &mut Instance,
@@ -1108,6 +1110,40 @@ fn new_epoch(store: &mut dyn VMStore, _instance: &mut Instance) -> Result<u64> {
     store.new_epoch()
 }
 
+// Hook for when a function's tier-up counter reaches zero.
+fn tier_up(store: &mut dyn VMStore, instance: &mut Instance, func: u32) {
+    let func = DefinedFuncIndex::from_u32(func);
+    instance.tier_up(store.store_opaque_mut(), func);
+}
+
+// Hook for guest debugging, invoked before each instruction while the store
+// has breakpoints set or is single-stepping.
+unsafe fn debug_hook(
+    store: &mut dyn VMStore,
+    instance: &mut Instance,
+    func: u32,
+    offset: u32,
+    slots: *mut u8,
+    num_locals: u32,
+    num_stack: u32,
+) -> Result<()> {
+    let Some(module) = instance.runtime_module() else {
+        return Ok(());
+    };
+    let Some(handle) = instance.host_state().downcast_ref::<crate::Instance>() else {
+        return Ok(());
+    };
+    store.debug_hook(crate::debugger::RawDebugFrame {
+        instance: *handle,
+        module,
+        func_index: func,
+        wasm_offset: offset,
+        slots: slots.cast_const(),
+        num_locals,
+        num_stack,
+    })
+}
+
 // Hook for validating malloc using wmemcheck_state.
 #[cfg(feature = "wmemcheck")]
 unsafe fn check_malloc(
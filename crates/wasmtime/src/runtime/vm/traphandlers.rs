@@ -98,6 +98,24 @@ pub unsafe fn raise_trap(reason: TrapReason) -> ! {
     tls::with(|info| info.unwrap().unwind_with(UnwindReason::Trap(reason)))
 }
 
+/// Raises the trap of a `trap` instruction executed by the Pulley
+/// interpreter at `pc`, where `fp` is the interpreter's frame pointer.
+///
+/// # Safety
+///
+/// Same as [`raise_trap`].
+#[cfg(feature = "pulley")]
+pub(crate) unsafe fn raise_interpreter_trap(pc: usize, fp: usize) -> ! {
+    tls::with(|info| {
+        let info = info.unwrap();
+        let trap = lookup_code(pc)
+            .and_then(|(code, text_offset)| code.lookup_trap_code(text_offset))
+            .expect("interpreter trapped outside of wasm code");
+        info.set_jit_trap(TrapRegisters { pc, fp }, None, trap);
+        traphandlers::wasmtime_longjmp(info.take_jmp_buf())
+    })
+}
+
 /// Raises a user-defined trap immediately.
 ///
 /// This function performs as-if a wasm trap was just executed, only the trap
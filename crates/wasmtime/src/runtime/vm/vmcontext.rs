@@ -5,7 +5,7 @@ mod vm_host_func_context;
 
 pub use self::vm_host_func_context::VMArrayCallHostFuncContext;
 use crate::prelude::*;
-use crate::runtime::vm::{GcStore, VMGcRef};
+use crate::runtime::vm::{GcStore, InterpreterRef, VMGcRef};
 use crate::store::StoreOpaque;
 use core::cell::UnsafeCell;
 use core::ffi::c_void;
@@ -690,6 +690,43 @@ pub struct VMFuncRef {
 unsafe impl Send for VMFuncRef {}
 unsafe impl Sync for VMFuncRef {}
 
+impl VMFuncRef {
+    /// Invokes the `array_call` field of this `VMFuncRef` with the supplied
+    /// arguments.
+    ///
+    /// The callee vmctx is `self.vmctx` and `args_and_results` must be large
+    /// enough to both load all arguments from and store all results to.
+    ///
+    /// Core wasm functions are invoked with `pulley` if it's provided, since
+    /// their code is then Pulley bytecode. Host functions are always native
+    /// code and are called directly.
+    ///
+    /// # Unsafety
+    ///
+    /// This method is unsafe because it can be called with any pointers. They
+    /// must all be valid for this wasm function call to proceed. Additionally
+    /// `pulley` must be the interpreter of the store this function belongs
+    /// to.
+    pub unsafe fn array_call(
+        &self,
+        pulley: Option<InterpreterRef<'_>>,
+        caller: *mut VMOpaqueContext,
+        args_and_results: *mut ValRaw,
+        args_and_results_len: usize,
+    ) {
+        match pulley {
+            Some(vm) if (*self.vmctx).magic == VMCONTEXT_MAGIC => vm.call(
+                NonNull::new(self.array_call as *mut u8).unwrap(),
+                self.vmctx,
+                caller,
+                args_and_results,
+                args_and_results_len,
+            ),
+            _ => (self.array_call)(self.vmctx, caller, args_and_results, args_and_results_len),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_vm_func_ref {
     use super::VMFuncRef;
@@ -835,6 +872,14 @@ pub struct VMRuntimeLimits {
     /// Used to find the end of a contiguous sequence of Wasm frames when
     /// walking the stack.
     pub last_wasm_entry_fp: UnsafeCell<usize>,
+
+    /// Whether code instrumented for guest debugging should call the
+    /// `debug_hook` libcall before each instruction.
+    ///
+    /// This is nonzero while the store has breakpoints set or is
+    /// single-stepping, and is only read by code compiled with
+    /// `Config::guest_debug` enabled.
+    pub debug_hook_active: UnsafeCell<u32>,
 }
 
 // The `VMRuntimeLimits` type is a pod-type with no destructor, and we don't
@@ -853,6 +898,7 @@ impl Default for VMRuntimeLimits {
             last_wasm_exit_fp: UnsafeCell::new(0),
             last_wasm_exit_pc: UnsafeCell::new(0),
             last_wasm_entry_fp: UnsafeCell::new(0),
+            debug_hook_active: UnsafeCell::new(0),
         }
     }
 }
@@ -891,6 +937,10 @@ mod test_vmruntime_limits {
             offset_of!(VMRuntimeLimits, last_wasm_entry_fp),
             usize::from(offsets.ptr.vmruntime_limits_last_wasm_entry_fp())
         );
+        assert_eq!(
+            offset_of!(VMRuntimeLimits, debug_hook_active),
+            usize::from(offsets.ptr.vmruntime_limits_debug_hook_active())
+        );
     }
 }
 
@@ -89,6 +89,7 @@ fn spec_test_config(test: &Path) -> TestConfig {
             ret.reference_types = Some(true);
         }
         Some("relaxed-simd") => {
+            ret.simd = Some(true);
             ret.relaxed_simd = Some(true);
         }
         Some("memory64") => {
@@ -123,6 +124,16 @@ fn spec_test_config(test: &Path) -> TestConfig {
         None => ret.reference_types = Some(true),
     }
 
+    // The SIMD proposal was merged into the spec, so its tests are named
+    // `simd_*.wast` rather than living in a proposal directory.
+    if test
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("simd_"))
+    {
+        ret.simd = Some(true);
+    }
+
     ret
 }
 
@@ -173,6 +184,9 @@ pub struct TestConfig {
     pub threads: Option<bool>,
     pub gc: Option<bool>,
     pub function_references: Option<bool>,
+    /// Whether the test uses `v128`. SIMD is always enabled, so this doesn't
+    /// change the configuration the test runs with.
+    pub simd: Option<bool>,
     pub relaxed_simd: Option<bool>,
     pub reference_types: Option<bool>,
     pub tail_call: Option<bool>,
@@ -204,6 +218,7 @@ pub enum Collector {
     Auto,
     Null,
     DeferredReferenceCounting,
+    Copying,
 }
 
 impl WastTest {
@@ -224,8 +239,18 @@ impl WastTest {
     /// Returns whether this test should fail under the specified extra
     /// configuration.
     pub fn should_fail(&self, config: &WastConfig) -> bool {
-        // Winch only supports x86_64 at this time.
-        if config.compiler == Compiler::Winch && !cfg!(target_arch = "x86_64") {
+        // Winch only supports x86_64 and aarch64 at this time.
+        if config.compiler == Compiler::Winch
+            && !cfg!(any(target_arch = "x86_64", target_arch = "aarch64"))
+        {
+            return true;
+        }
+
+        // Winch doesn't support SIMD on aarch64 yet.
+        if config.compiler == Compiler::Winch
+            && cfg!(target_arch = "aarch64")
+            && (self.config.simd == Some(true) || self.config.relaxed_simd == Some(true))
+        {
             return true;
         }
 
@@ -237,7 +262,6 @@ impl WastTest {
                 || self.config.tail_call == Some(true)
                 || self.config.function_references == Some(true)
                 || self.config.gc == Some(true)
-                || self.config.relaxed_simd == Some(true)
             {
                 return true;
             }
@@ -276,76 +300,8 @@ impl WastTest {
                 "spec_testsuite/table_size.wast",
                 "spec_testsuite/unreached-invalid.wast",
                 "spec_testsuite/call_indirect.wast",
-                // simd-related failures
-                "annotations/simd_lane.wast",
-                "memory64/simd.wast",
-                "misc_testsuite/int-to-float-splat.wast",
-                "misc_testsuite/issue6562.wast",
-                "misc_testsuite/simd/almost-extmul.wast",
+                // Winch doesn't implement NaN canonicalization.
                 "misc_testsuite/simd/canonicalize-nan.wast",
-                "misc_testsuite/simd/cvt-from-uint.wast",
-                "misc_testsuite/simd/issue4807.wast",
-                "misc_testsuite/simd/issue6725-no-egraph-panic.wast",
-                "misc_testsuite/simd/issue_3327_bnot_lowering.wast",
-                "misc_testsuite/simd/load_splat_out_of_bounds.wast",
-                "misc_testsuite/simd/replace-lane-preserve.wast",
-                "misc_testsuite/simd/spillslot-size-fuzzbug.wast",
-                "misc_testsuite/simd/unaligned-load.wast",
-                "multi-memory/simd_memory-multi.wast",
-                "spec_testsuite/simd_align.wast",
-                "spec_testsuite/simd_bit_shift.wast",
-                "spec_testsuite/simd_bitwise.wast",
-                "spec_testsuite/simd_boolean.wast",
-                "spec_testsuite/simd_const.wast",
-                "spec_testsuite/simd_conversions.wast",
-                "spec_testsuite/simd_f32x4.wast",
-                "spec_testsuite/simd_f32x4_arith.wast",
-                "spec_testsuite/simd_f32x4_cmp.wast",
-                "spec_testsuite/simd_f32x4_pmin_pmax.wast",
-                "spec_testsuite/simd_f32x4_rounding.wast",
-                "spec_testsuite/simd_f64x2.wast",
-                "spec_testsuite/simd_f64x2_arith.wast",
-                "spec_testsuite/simd_f64x2_cmp.wast",
-                "spec_testsuite/simd_f64x2_pmin_pmax.wast",
-                "spec_testsuite/simd_f64x2_rounding.wast",
-                "spec_testsuite/simd_i16x8_arith.wast",
-                "spec_testsuite/simd_i16x8_arith2.wast",
-                "spec_testsuite/simd_i16x8_cmp.wast",
-                "spec_testsuite/simd_i16x8_extadd_pairwise_i8x16.wast",
-                "spec_testsuite/simd_i16x8_extmul_i8x16.wast",
-                "spec_testsuite/simd_i16x8_q15mulr_sat_s.wast",
-                "spec_testsuite/simd_i16x8_sat_arith.wast",
-                "spec_testsuite/simd_i32x4_arith.wast",
-                "spec_testsuite/simd_i32x4_arith2.wast",
-                "spec_testsuite/simd_i32x4_cmp.wast",
-                "spec_testsuite/simd_i32x4_dot_i16x8.wast",
-                "spec_testsuite/simd_i32x4_extadd_pairwise_i16x8.wast",
-                "spec_testsuite/simd_i32x4_extmul_i16x8.wast",
-                "spec_testsuite/simd_i32x4_trunc_sat_f32x4.wast",
-                "spec_testsuite/simd_i32x4_trunc_sat_f64x2.wast",
-                "spec_testsuite/simd_i64x2_arith.wast",
-                "spec_testsuite/simd_i64x2_arith2.wast",
-                "spec_testsuite/simd_i64x2_cmp.wast",
-                "spec_testsuite/simd_i64x2_extmul_i32x4.wast",
-                "spec_testsuite/simd_i8x16_arith.wast",
-                "spec_testsuite/simd_i8x16_arith2.wast",
-                "spec_testsuite/simd_i8x16_cmp.wast",
-                "spec_testsuite/simd_i8x16_sat_arith.wast",
-                "spec_testsuite/simd_int_to_int_extend.wast",
-                "spec_testsuite/simd_lane.wast",
-                "spec_testsuite/simd_load.wast",
-                "spec_testsuite/simd_load16_lane.wast",
-                "spec_testsuite/simd_load32_lane.wast",
-                "spec_testsuite/simd_load64_lane.wast",
-                "spec_testsuite/simd_load8_lane.wast",
-                "spec_testsuite/simd_load_extend.wast",
-                "spec_testsuite/simd_load_splat.wast",
-                "spec_testsuite/simd_load_zero.wast",
-                "spec_testsuite/simd_splat.wast",
-                "spec_testsuite/simd_store16_lane.wast",
-                "spec_testsuite/simd_store32_lane.wast",
-                "spec_testsuite/simd_store64_lane.wast",
-                "spec_testsuite/simd_store8_lane.wast",
             ];
 
             if unsupported.iter().any(|part| self.path.ends_with(part)) {
@@ -73,6 +73,10 @@ impl CompilerBuilder for Builder {
             bail!("Winch does not currently support generating native debug information");
         }
 
+        if tunables.guest_debug {
+            bail!("Winch does not currently support guest debugging");
+        }
+
         self.tunables = Some(tunables.clone());
         self.cranelift.set_tunables(tunables)?;
         Ok(())
@@ -92,6 +92,7 @@ impl wasmtime_environ::Compiler for Compiler {
         data: FunctionBodyData<'_>,
         types: &ModuleTypesBuilder,
     ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        let def_index = index;
         let index = translation.module.func_index(index);
         let sig = translation.module.functions[index].signature;
         let ty = types[sig].unwrap_func();
@@ -110,6 +111,7 @@ impl wasmtime_environ::Compiler for Compiler {
                 &mut context.builtins,
                 &mut validator,
                 &self.tunables,
+                def_index,
             )
             .map_err(|e| CompileError::Codegen(format!("{e:?}")));
         self.save_context(context, validator.into_allocations());
@@ -0,0 +1,240 @@
+/*
+Example of instantiating a WebAssembly component, defining host functions and
+resources for its imports, and invoking its exported function.
+
+You can compile and run this example on Linux with:
+
+   cargo build --release -p wasmtime-c-api
+   cc examples/component.c \
+       -I crates/c-api/include \
+       target/release/libwasmtime.a \
+       -lpthread -ldl -lm \
+       -o component
+   ./component
+
+Note that on Windows and macOS the command will be similar, but you'll need
+to tweak the `-lpthread` and such annotations as well as the name of the
+`libwasmtime.a` file on Windows.
+
+You can also build using cmake:
+
+mkdir build && cd build && cmake .. && cmake --build . --target wasmtime-component
+*/
+
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <wasm.h>
+#include <wasmtime.h>
+
+// Identifier of our host-defined `counter` resource type.
+#define COUNTER_TYPE 1
+
+static const char *component_wat =
+    "(component\n"
+    "  (import \"host:demo/math\" (instance $math\n"
+    "    (export \"double\" (func (param \"x\" u32) (result u32)))\n"
+    "  ))\n"
+    "  (import \"counter\" (type $counter (sub resource)))\n"
+    "  (import \"counter-value\"\n"
+    "    (func $counter-value (param \"c\" (borrow $counter)) (result u32)))\n"
+    "\n"
+    "  (core func $double (canon lower (func $math \"double\")))\n"
+    "  (core func $value (canon lower (func $counter-value)))\n"
+    "  (core func $drop (canon resource.drop $counter))\n"
+    "\n"
+    "  (core module $m\n"
+    "    (import \"host\" \"double\" (func $double (param i32) (result i32)))\n"
+    "    (import \"host\" \"value\" (func $value (param i32) (result i32)))\n"
+    "    (import \"host\" \"drop\" (func $drop (param i32)))\n"
+    "    (func (export \"run\") (param i32 i32) (result i32)\n"
+    "      (local $ret i32)\n"
+    "      (local.set $ret\n"
+    "        (i32.add\n"
+    "          (call $double (local.get 0))\n"
+    "          (call $value (local.get 1))))\n"
+    "      (call $drop (local.get 1))\n"
+    "      (local.get $ret))\n"
+    "  )\n"
+    "  (core instance $i (instantiate $m\n"
+    "    (with \"host\" (instance\n"
+    "      (export \"double\" (func $double))\n"
+    "      (export \"value\" (func $value))\n"
+    "      (export \"drop\" (func $drop))\n"
+    "    ))\n"
+    "  ))\n"
+    "\n"
+    "  (func (export \"run\")\n"
+    "    (param \"x\" u32) (param \"c\" (borrow $counter)) (result u32)\n"
+    "    (canon lift (core func $i \"run\")))\n"
+    ")\n";
+
+static void exit_with_error(const char *message, wasmtime_error_t *error);
+
+static wasmtime_error_t *double_callback(void *env, wasmtime_context_t *context,
+                                         const wasmtime_component_val_t *args,
+                                         size_t nargs,
+                                         wasmtime_component_val_t *results,
+                                         size_t nresults) {
+  assert(nargs == 1 && nresults == 1);
+  assert(args[0].kind == WASMTIME_COMPONENT_U32);
+  printf("> double(%u)\n", args[0].of.u32);
+  results[0].kind = WASMTIME_COMPONENT_U32;
+  results[0].of.u32 = args[0].of.u32 * 2;
+  return NULL;
+}
+
+static wasmtime_error_t *
+counter_value_callback(void *env, wasmtime_context_t *context,
+                       const wasmtime_component_val_t *args, size_t nargs,
+                       wasmtime_component_val_t *results, size_t nresults) {
+  assert(nargs == 1 && nresults == 1);
+  assert(args[0].kind == WASMTIME_COMPONENT_RESOURCE);
+  assert(!wasmtime_component_resource_any_owned(args[0].of.resource));
+
+  // Convert the borrowed handle back into the representation the host chose
+  // when creating the resource, which also ends the borrow.
+  uint32_t rep = 0;
+  wasmtime_error_t *error = wasmtime_component_resource_any_to_host(
+      context, args[0].of.resource, COUNTER_TYPE, &rep);
+  if (error != NULL)
+    return error;
+  printf("> counter-value(rep = %u)\n", rep);
+  results[0].kind = WASMTIME_COMPONENT_U32;
+  results[0].of.u32 = rep;
+  return NULL;
+}
+
+static wasmtime_error_t *counter_destructor(void *env,
+                                            wasmtime_context_t *context,
+                                            uint32_t rep) {
+  printf("> destroying counter %u\n", rep);
+  return NULL;
+}
+
+int main() {
+  // Set up our engine and store like we would for a core wasm module. The
+  // component model is enabled by default.
+  printf("Initializing...\n");
+  wasm_engine_t *engine = wasm_engine_new();
+  assert(engine != NULL);
+  wasmtime_store_t *store = wasmtime_store_new(engine, NULL, NULL);
+  assert(store != NULL);
+  wasmtime_context_t *context = wasmtime_store_context(store);
+
+  // Parse the text format of our component into a binary and compile it.
+  printf("Compiling component...\n");
+  wasm_byte_vec_t wasm;
+  wasmtime_error_t *error =
+      wasmtime_wat2wasm(component_wat, strlen(component_wat), &wasm);
+  if (error != NULL)
+    exit_with_error("failed to parse wat", error);
+  wasmtime_component_t *component = NULL;
+  error = wasmtime_component_new(engine, (uint8_t *)wasm.data, wasm.size,
+                                 &component);
+  wasm_byte_vec_delete(&wasm);
+  if (error != NULL)
+    exit_with_error("failed to compile component", error);
+
+  // Define all of the component's imports in a linker. Each nested
+  // `wasmtime_component_linker_instance_t` borrows its parent, so it's deleted
+  // before its parent is used again.
+  printf("Defining imports...\n");
+  wasmtime_component_linker_t *linker = wasmtime_component_linker_new(engine);
+  wasmtime_component_linker_instance_t *root =
+      wasmtime_component_linker_root(linker);
+
+  wasmtime_component_linker_instance_t *math = NULL;
+  error = wasmtime_component_linker_instance_add_instance(
+      root, "host:demo/math", strlen("host:demo/math"), &math);
+  if (error != NULL)
+    exit_with_error("failed to define instance", error);
+  error = wasmtime_component_linker_instance_add_func(
+      math, "double", strlen("double"), double_callback, NULL, NULL);
+  if (error != NULL)
+    exit_with_error("failed to define function", error);
+  wasmtime_component_linker_instance_delete(math);
+
+  wasmtime_component_resource_type_t *counter_ty =
+      wasmtime_component_resource_type_new_host(COUNTER_TYPE);
+  error = wasmtime_component_linker_instance_add_resource(
+      root, "counter", strlen("counter"), counter_ty, counter_destructor, NULL,
+      NULL);
+  wasmtime_component_resource_type_delete(counter_ty);
+  if (error != NULL)
+    exit_with_error("failed to define resource", error);
+  error = wasmtime_component_linker_instance_add_func(
+      root, "counter-value", strlen("counter-value"), counter_value_callback,
+      NULL, NULL);
+  if (error != NULL)
+    exit_with_error("failed to define function", error);
+  wasmtime_component_linker_instance_delete(root);
+
+  printf("Instantiating component...\n");
+  wasmtime_component_instance_t instance;
+  error = wasmtime_component_linker_instantiate(linker, context, component,
+                                                &instance);
+  if (error != NULL)
+    exit_with_error("failed to instantiate", error);
+
+  // Look up the `run` export, first on the component and then the instance.
+  printf("Extracting export...\n");
+  wasmtime_component_export_index_t *run_index =
+      wasmtime_component_get_export_index(component, NULL, "run",
+                                          strlen("run"));
+  assert(run_index != NULL);
+  wasmtime_component_func_t run;
+  bool ok =
+      wasmtime_component_instance_get_func(&instance, context, run_index, &run);
+  assert(ok);
+  wasmtime_component_export_index_delete(run_index);
+
+  // Create a host-owned counter which is lent to the guest for the call.
+  wasmtime_component_resource_any_t *counter = NULL;
+  error = wasmtime_component_resource_any_new_host(context, COUNTER_TYPE, 42,
+                                                   true, &counter);
+  if (error != NULL)
+    exit_with_error("failed to create resource", error);
+
+  printf("Calling export...\n");
+  wasmtime_component_val_t args[2];
+  args[0].kind = WASMTIME_COMPONENT_U32;
+  args[0].of.u32 = 10;
+  args[1].kind = WASMTIME_COMPONENT_RESOURCE;
+  args[1].of.resource = counter;
+  wasmtime_component_val_t result;
+  error = wasmtime_component_func_call(&run, context, args, 2, &result, 1);
+  if (error != NULL)
+    exit_with_error("failed to call function", error);
+  assert(result.kind == WASMTIME_COMPONENT_U32);
+  printf("> run(10, counter) = %u\n", result.of.u32);
+  assert(result.of.u32 == 10 * 2 + 42);
+  wasmtime_component_val_delete(&result);
+
+  // The counter was only lent to the guest, so it's still owned by the host.
+  // Destroy it within the store and then free the handle itself.
+  error = wasmtime_component_resource_any_drop(context, counter);
+  if (error != NULL)
+    exit_with_error("failed to drop resource", error);
+  wasmtime_component_val_delete(&args[1]);
+
+  // Clean up after ourselves at this point
+  printf("All finished!\n");
+
+  wasmtime_component_linker_delete(linker);
+  wasmtime_component_delete(component);
+  wasmtime_store_delete(store);
+  wasm_engine_delete(engine);
+  return 0;
+}
+
+static void exit_with_error(const char *message, wasmtime_error_t *error) {
+  fprintf(stderr, "error: %s\n", message);
+  wasm_byte_vec_t error_message;
+  wasmtime_error_message(error, &error_message);
+  wasmtime_error_delete(error);
+  fprintf(stderr, "%.*s\n", (int)error_message.size, error_message.data);
+  wasm_byte_vec_delete(&error_message);
+  exit(1);
+}
@@ -1,5 +1,5 @@
 use pulley_interpreter::{
-    interp::Vm,
+    interp::{DoneReason, Vm},
     op::{self, ExtendedOp, Op},
     *,
 };
@@ -30,8 +30,9 @@ pub fn interp(ops: Vec<Op>) {
         let args = &[];
         let rets = &[];
         match vm.call(NonNull::from(&encoded[0]), args, rets.into_iter().copied()) {
-            Ok(rets) => assert_eq!(rets.count(), 0),
-            Err(pc) => {
+            DoneReason::ReturnToHost(rets) => assert_eq!(rets.count(), 0),
+            DoneReason::CallIndirectHost { .. } => unreachable!(),
+            DoneReason::Trap(pc) => {
                 let pc = pc.as_ptr() as usize;
 
                 let start = &encoded[0] as *const u8 as usize;
@@ -42,15 +43,34 @@ pub fn interp(ops: Vec<Op>) {
                 );
 
                 let index = pc - start;
-                assert_eq!(encoded[index], Opcode::ExtendedOp as u8);
-                let [a, b] = (ExtendedOpcode::Trap as u16).to_le_bytes();
-                assert_eq!(encoded[index + 1], a);
-                assert_eq!(encoded[index + 2], b);
+                let opcode = Opcode::new(encoded[index]).unwrap();
+                if is_division(opcode) {
+                    // Division by zero traps at the division itself.
+                } else {
+                    assert_eq!(opcode, Opcode::ExtendedOp);
+                    let [a, b] = (ExtendedOpcode::Trap as u16).to_le_bytes();
+                    assert_eq!(encoded[index + 1], a);
+                    assert_eq!(encoded[index + 2], b);
+                }
             }
         };
     }
 }
 
+fn is_division(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::XDiv32S
+            | Opcode::XDiv32U
+            | Opcode::XDiv64S
+            | Opcode::XDiv64U
+            | Opcode::XRem32S
+            | Opcode::XRem32U
+            | Opcode::XRem64S
+            | Opcode::XRem64U
+    )
+}
+
 fn op_is_safe_for_fuzzing(op: &Op) -> bool {
     match op {
         Op::Ret(_) => true,
@@ -69,6 +89,7 @@ fn op_is_safe_for_fuzzing(op: &Op) -> bool {
         Op::BrIfXulteq64(_) => false,
         Op::BrIfXslt64(_) => false,
         Op::BrIfXslteq64(_) => false,
+        Op::BrTable32(_) => false,
         Op::Xmov(op::Xmov { dst, .. }) => !dst.is_special(),
         Op::Fmov(_) => true,
         Op::Vmov(_) => true,
@@ -91,12 +112,23 @@ fn op_is_safe_for_fuzzing(op: &Op) -> bool {
         Op::Store32SOffset64(_) => false,
         Op::Store64Offset8(_) => false,
         Op::Store64Offset64(_) => false,
+        Op::Load8UOffset64(_) => false,
+        Op::Load8SOffset64(_) => false,
+        Op::Load16UOffset64(_) => false,
+        Op::Load16SOffset64(_) => false,
+        Op::Fload32Offset64(_) => false,
+        Op::Fload64Offset64(_) => false,
+        Op::Store8Offset64(_) => false,
+        Op::Store16Offset64(_) => false,
+        Op::Fstore32Offset64(_) => false,
+        Op::Fstore64Offset64(_) => false,
         Op::BitcastIntFromFloat32(op::BitcastIntFromFloat32 { dst, .. }) => !dst.is_special(),
         Op::BitcastIntFromFloat64(op::BitcastIntFromFloat64 { dst, .. }) => !dst.is_special(),
         Op::BitcastFloatFromInt32(_) => true,
         Op::BitcastFloatFromInt64(_) => true,
         Op::ExtendedOp(op) => extended_op_is_safe_for_fuzzing(op),
         Op::Call(_) => false,
+        Op::CallIndirect(_) => false,
         Op::Xadd32(Xadd32 { operands, .. })
         | Op::Xadd64(Xadd64 { operands, .. })
         | Op::Xeq64(Xeq64 { operands, .. })
@@ -110,7 +142,103 @@ fn op_is_safe_for_fuzzing(op: &Op) -> bool {
         | Op::Xslt32(Xslt32 { operands, .. })
         | Op::Xslteq32(Xslteq32 { operands, .. })
         | Op::Xult32(Xult32 { operands, .. })
+        | Op::Xsub32(Xsub32 { operands, .. })
+        | Op::Xsub64(Xsub64 { operands, .. })
+        | Op::Xmul32(Xmul32 { operands, .. })
+        | Op::Xmul64(Xmul64 { operands, .. })
+        | Op::XDiv32S(XDiv32S { operands, .. })
+        | Op::XDiv64S(XDiv64S { operands, .. })
+        | Op::XDiv32U(XDiv32U { operands, .. })
+        | Op::XDiv64U(XDiv64U { operands, .. })
+        | Op::XRem32S(XRem32S { operands, .. })
+        | Op::XRem64S(XRem64S { operands, .. })
+        | Op::XRem32U(XRem32U { operands, .. })
+        | Op::XRem64U(XRem64U { operands, .. })
+        | Op::XBand32(XBand32 { operands, .. })
+        | Op::XBand64(XBand64 { operands, .. })
+        | Op::XBor32(XBor32 { operands, .. })
+        | Op::XBor64(XBor64 { operands, .. })
+        | Op::XBxor32(XBxor32 { operands, .. })
+        | Op::XBxor64(XBxor64 { operands, .. })
+        | Op::Xshl32(Xshl32 { operands, .. })
+        | Op::Xshr32S(Xshr32S { operands, .. })
+        | Op::Xshr32U(Xshr32U { operands, .. })
+        | Op::Xshl64(Xshl64 { operands, .. })
+        | Op::Xshr64S(Xshr64S { operands, .. })
+        | Op::Xshr64U(Xshr64U { operands, .. })
+        | Op::Xrotl32(Xrotl32 { operands, .. })
+        | Op::Xrotr32(Xrotr32 { operands, .. })
+        | Op::Xrotl64(Xrotl64 { operands, .. })
+        | Op::Xrotr64(Xrotr64 { operands, .. })
         | Op::Xulteq32(Xulteq32 { operands, .. }) => !operands.dst.is_special(),
+        Op::Xclz32(Xclz32 { dst, .. })
+        | Op::Xclz64(Xclz64 { dst, .. })
+        | Op::Xctz32(Xctz32 { dst, .. })
+        | Op::Xctz64(Xctz64 { dst, .. })
+        | Op::Xpopcnt32(Xpopcnt32 { dst, .. })
+        | Op::Xpopcnt64(Xpopcnt64 { dst, .. })
+        | Op::Zext8(Zext8 { dst, .. })
+        | Op::Zext16(Zext16 { dst, .. })
+        | Op::Zext32(Zext32 { dst, .. })
+        | Op::Sext8(Sext8 { dst, .. })
+        | Op::Sext16(Sext16 { dst, .. })
+        | Op::Sext32(Sext32 { dst, .. })
+        | Op::XSelect(XSelect { dst, .. })
+        | Op::Feq32(Feq32 { dst, .. })
+        | Op::Feq64(Feq64 { dst, .. })
+        | Op::Fneq32(Fneq32 { dst, .. })
+        | Op::Fneq64(Fneq64 { dst, .. })
+        | Op::Flt32(Flt32 { dst, .. })
+        | Op::Flt64(Flt64 { dst, .. })
+        | Op::Flteq32(Flteq32 { dst, .. })
+        | Op::Flteq64(Flteq64 { dst, .. })
+        | Op::X32FromF32SSat(X32FromF32SSat { dst, .. })
+        | Op::X32FromF32USat(X32FromF32USat { dst, .. })
+        | Op::X32FromF64SSat(X32FromF64SSat { dst, .. })
+        | Op::X32FromF64USat(X32FromF64USat { dst, .. })
+        | Op::X64FromF32SSat(X64FromF32SSat { dst, .. })
+        | Op::X64FromF32USat(X64FromF32USat { dst, .. })
+        | Op::X64FromF64SSat(X64FromF64SSat { dst, .. })
+        | Op::X64FromF64USat(X64FromF64USat { dst, .. }) => !dst.is_special(),
+        Op::FSelect(_)
+        | Op::Fadd32(_)
+        | Op::Fadd64(_)
+        | Op::Fsub32(_)
+        | Op::Fsub64(_)
+        | Op::Fmul32(_)
+        | Op::Fmul64(_)
+        | Op::Fdiv32(_)
+        | Op::Fdiv64(_)
+        | Op::Fmin32(_)
+        | Op::Fmin64(_)
+        | Op::Fmax32(_)
+        | Op::Fmax64(_)
+        | Op::Fcopysign32(_)
+        | Op::Fcopysign64(_)
+        | Op::Fneg32(_)
+        | Op::Fneg64(_)
+        | Op::Fabs32(_)
+        | Op::Fabs64(_)
+        | Op::Fsqrt32(_)
+        | Op::Fsqrt64(_)
+        | Op::Fceil32(_)
+        | Op::Fceil64(_)
+        | Op::Ffloor32(_)
+        | Op::Ffloor64(_)
+        | Op::Ftrunc32(_)
+        | Op::Ftrunc64(_)
+        | Op::Fnearest32(_)
+        | Op::Fnearest64(_)
+        | Op::F32FromF64(_)
+        | Op::F64FromF32(_)
+        | Op::F32FromX32S(_)
+        | Op::F32FromX32U(_)
+        | Op::F32FromX64S(_)
+        | Op::F32FromX64U(_)
+        | Op::F64FromX32S(_)
+        | Op::F64FromX32U(_)
+        | Op::F64FromX64S(_)
+        | Op::F64FromX64U(_) => true,
         Op::PushFrame(_) | Op::PopFrame(_) => false,
         Op::XPush32(_) | Op::XPush64(_) => false,
         Op::XPop32(_) | Op::XPop64(_) => false,
@@ -124,5 +252,7 @@ fn extended_op_is_safe_for_fuzzing(op: &ExtendedOp) -> bool {
         ExtendedOp::Trap(_) => true,
         ExtendedOp::Nop(_) => true,
         ExtendedOp::GetSp(GetSp { dst, .. }) => !dst.is_special(),
+        ExtendedOp::GetFp(GetFp { dst, .. }) => !dst.is_special(),
+        ExtendedOp::CallIndirectHost(_) => false,
     }
 }
@@ -67,20 +67,42 @@ impl Vm {
     ///
     /// The given `rets` must match the function's actual return types.
     ///
-    /// Returns either the resulting values, or the PC at which a trap was
-    /// raised.
+    /// Returns either the resulting values, the PC at which a trap was
+    /// raised, or a request to perform a host call. In the last case the host
+    /// should perform the call and then continue execution with
+    /// [`Vm::call_run`] at the `resume` PC, followed by [`Vm::call_end`] once
+    /// that returns to the host.
     pub unsafe fn call<'a>(
         &'a mut self,
         func: NonNull<u8>,
         args: &[Val],
         rets: impl IntoIterator<Item = RegType> + 'a,
-    ) -> Result<impl Iterator<Item = Val> + 'a, NonNull<u8>> {
+    ) -> DoneReason<impl Iterator<Item = Val> + 'a> {
+        self.call_start(args);
+
+        match self.call_run(func) {
+            DoneReason::ReturnToHost(()) => DoneReason::ReturnToHost(self.call_end(rets)),
+            DoneReason::Trap(pc) => DoneReason::Trap(pc),
+            DoneReason::CallIndirectHost { id, resume } => {
+                DoneReason::CallIndirectHost { id, resume }
+            }
+        }
+    }
+
+    /// Peforms the initial part of [`Vm::call`] in setting up the `args`
+    /// provided in registers and setting `lr` so that `ret` returns to the
+    /// host.
+    ///
+    /// Note that this clobbers `lr` and the argument registers, so callers
+    /// re-entering the interpreter while a call is already in progress must
+    /// save and restore whatever state they need themselves.
+    pub fn call_start(&mut self, args: &[Val]) {
         // NB: make sure this method stays in sync with
         // `PulleyMachineDeps::compute_arg_locs`!
 
-        let mut x_args = (0..16).map(|x| XReg::new_unchecked(x));
-        let mut f_args = (0..16).map(|f| FReg::new_unchecked(f));
-        let mut v_args = (0..16).map(|v| VReg::new_unchecked(v));
+        let mut x_args = (0..16).map(|x| unsafe { XReg::new_unchecked(x) });
+        let mut f_args = (0..16).map(|f| unsafe { FReg::new_unchecked(f) });
+        let mut v_args = (0..16).map(|v| unsafe { VReg::new_unchecked(v) });
 
         for arg in args {
             match arg {
@@ -99,13 +121,40 @@ impl Vm {
             }
         }
 
-        self.run(func)?;
+        self.state[XReg::lr] = XRegVal::HOST_RETURN_ADDR;
+    }
 
-        let mut x_rets = (0..16).map(|x| XReg::new_unchecked(x));
-        let mut f_rets = (0..16).map(|f| FReg::new_unchecked(f));
-        let mut v_rets = (0..16).map(|v| VReg::new_unchecked(v));
+    /// Executes bytecode starting at `pc` until it returns to the host, traps,
+    /// or requests a host call.
+    ///
+    /// This is used both to start a call after [`Vm::call_start`] and to
+    /// resume execution after the host has finished a call requested through
+    /// [`DoneReason::CallIndirectHost`].
+    ///
+    /// # Unsafety
+    ///
+    /// `pc` must point to valid Pulley bytecode.
+    pub unsafe fn call_run(&mut self, pc: NonNull<u8>) -> DoneReason<()> {
+        let mut bytecode = UnsafeBytecodeStream::new(pc);
+        match interp_loop::interpreter_loop(self, &mut bytecode) {
+            Done::ReturnToHost => self.return_to_host(),
+            Done::Trap(pc) => self.trap(pc),
+            Done::CallIndirectHost { id, resume } => self.call_indirect_host(id, resume),
+        }
+    }
+
+    /// Reads the return values of a function which has returned to the host.
+    ///
+    /// The given `rets` must match the function's actual return types.
+    pub fn call_end<'a>(
+        &'a mut self,
+        rets: impl IntoIterator<Item = RegType> + 'a,
+    ) -> impl Iterator<Item = Val> + 'a {
+        let mut x_rets = (0..16).map(|x| unsafe { XReg::new_unchecked(x) });
+        let mut f_rets = (0..16).map(|f| unsafe { FReg::new_unchecked(f) });
+        let mut v_rets = (0..16).map(|v| unsafe { VReg::new_unchecked(v) });
 
-        Ok(rets.into_iter().map(move |ty| match ty {
+        rets.into_iter().map(move |ty| match ty {
             RegType::XReg => match x_rets.next() {
                 Some(reg) => Val::XReg(self.state[reg]),
                 None => todo!("stack slots"),
@@ -118,43 +167,52 @@ impl Vm {
                 Some(reg) => Val::VReg(self.state[reg]),
                 None => todo!("stack slots"),
             },
-        }))
-    }
-
-    unsafe fn run(&mut self, pc: NonNull<u8>) -> Result<(), NonNull<u8>> {
-        let mut bytecode = UnsafeBytecodeStream::new(pc);
-        match interp_loop::interpreter_loop(self, &mut bytecode) {
-            Done::ReturnToHost => self.return_to_host(),
-            Done::Trap(pc) => self.trap(pc),
-            Done::HostCall => self.host_call(),
-        }
+        })
     }
 
     #[cold]
     #[inline(never)]
-    fn return_to_host(&self) -> Result<(), NonNull<u8>> {
-        Ok(())
+    fn return_to_host(&self) -> DoneReason<()> {
+        DoneReason::ReturnToHost(())
     }
 
     #[cold]
     #[inline(never)]
-    fn trap(&self, pc: NonNull<u8>) -> Result<(), NonNull<u8>> {
-        // We are given the VM's PC upon having executed a trap instruction,
-        // which is actually pointing to the next instruction after the
-        // trap. Back the PC up to point exactly at the trap.
-        let trap_pc = unsafe {
-            NonNull::new_unchecked(pc.as_ptr().byte_sub(ExtendedOpcode::ENCODED_SIZE_OF_TRAP))
-        };
-        Err(trap_pc)
+    fn trap(&self, pc: NonNull<u8>) -> DoneReason<()> {
+        DoneReason::Trap(pc)
     }
 
     #[cold]
     #[inline(never)]
-    fn host_call(&self) -> Result<(), NonNull<u8>> {
-        todo!()
+    fn call_indirect_host(&self, id: u8, resume: NonNull<u8>) -> DoneReason<()> {
+        DoneReason::CallIndirectHost { id, resume }
     }
 }
 
+/// The reason that [`Vm::call`] or [`Vm::call_run`] stopped executing
+/// bytecode.
+#[derive(Debug)]
+pub enum DoneReason<T> {
+    /// The function returned to the host normally.
+    ReturnToHost(T),
+
+    /// The instruction at the given PC trapped: either a `trap` instruction or
+    /// an instruction such as a division by zero.
+    Trap(NonNull<u8>),
+
+    /// A `call_indirect_host` instruction was executed.
+    ///
+    /// The host is expected to perform the call identified by `id`, reading
+    /// arguments from and writing results to this VM's registers, and then
+    /// continue execution at `resume` with [`Vm::call_run`].
+    CallIndirectHost {
+        /// The embedder-defined identifier of the host call.
+        id: u8,
+        /// The PC of the instruction after the `call_indirect_host`.
+        resume: NonNull<u8>,
+    },
+}
+
 /// The type of a register in the Pulley machine state.
 #[derive(Clone, Copy, Debug)]
 pub enum RegType {
@@ -567,20 +625,31 @@ impl MachineState {
         state
     }
 
-    /// `*sp = val; sp += size_of::<T>()`
+    /// `sp -= size_of::<T>(); *sp = val`
+    ///
+    /// The stack grows downwards, starting from the end of the stack
+    /// allocation.
     fn push<T>(&mut self, val: T) {
-        let sp = self[XReg::sp].get_ptr::<T>();
+        let sp = self[XReg::sp].get_ptr::<T>().wrapping_sub(1);
         unsafe { sp.write_unaligned(val) }
-        self[XReg::sp].set_ptr(sp.wrapping_add(1));
+        self[XReg::sp].set_ptr(sp);
     }
 
-    /// `ret = *sp; sp -= size_of::<T>()`
+    /// `ret = *sp; sp += size_of::<T>()`
     fn pop<T>(&mut self) -> T {
         let sp = self[XReg::sp].get_ptr::<T>();
         let val = unsafe { sp.read_unaligned() };
-        self[XReg::sp].set_ptr(sp.wrapping_sub(1));
+        self[XReg::sp].set_ptr(sp.wrapping_add(1));
         val
     }
+
+    /// Returns the range of addresses covered by this machine's stack.
+    ///
+    /// The stack pointer starts at the end of this range and grows towards
+    /// its start.
+    pub fn stack_range(&self) -> core::ops::Range<*const u8> {
+        self.stack.as_ptr_range()
+    }
 }
 
 /// The reason the interpreter loop terminated.
@@ -590,9 +659,11 @@ enum Done {
     /// how the loop normally ends.
     ReturnToHost,
 
-    /// A `trap` instruction was executed at the given PC.
+    /// The instruction at the given PC trapped: either a `trap` instruction or
+    /// an instruction such as a division by zero.
     Trap(NonNull<u8>),
 
-    #[allow(dead_code)]
-    HostCall,
+    /// A `call_indirect_host` instruction was executed; `resume` is the PC to
+    /// continue at once the host call is done.
+    CallIndirectHost { id: u8, resume: NonNull<u8> },
 }
@@ -130,6 +130,65 @@ fn pc_rel_jump(pc: &mut UnsafeBytecodeStream, offset: PcRelOffset, inst_size: is
     *pc = unsafe { pc.offset(offset - inst_size) };
 }
 
+/// Get the PC of the instruction of `inst_size` bytes that was just decoded
+/// from `pc`, for reporting a trap raised by that instruction.
+#[inline]
+fn trapping_pc(pc: &UnsafeBytecodeStream, inst_size: isize) -> NonNull<u8> {
+    unsafe { pc.offset(-inst_size).as_ptr() }
+}
+
+/// Wasm's `fmin` for `f32`: NaN-propagating and ordering `-0.0` before `0.0`.
+fn wasm_fmin32(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        f32::NAN
+    } else if a == b {
+        f32::from_bits(a.to_bits() | b.to_bits())
+    } else if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Wasm's `fmin` for `f64`: NaN-propagating and ordering `-0.0` before `0.0`.
+fn wasm_fmin64(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == b {
+        f64::from_bits(a.to_bits() | b.to_bits())
+    } else if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Wasm's `fmax` for `f32`: NaN-propagating and ordering `-0.0` before `0.0`.
+fn wasm_fmax32(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        f32::NAN
+    } else if a == b {
+        f32::from_bits(a.to_bits() & b.to_bits())
+    } else if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Wasm's `fmax` for `f64`: NaN-propagating and ordering `-0.0` before `0.0`.
+fn wasm_fmax64(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == b {
+        f64::from_bits(a.to_bits() & b.to_bits())
+    } else if a > b {
+        a
+    } else {
+        b
+    }
+}
+
 define_opcode_handlers! {
     fn ret(state: &mut MachineState, pc: &mut UnsafeBytecodeStream) {
         if state[XReg::lr] == XRegVal::HOST_RETURN_ADDR {
@@ -148,6 +207,14 @@ define_opcode_handlers! {
         ControlFlow::Continue(())
     }
 
+    fn call_indirect(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, reg: XReg) {
+        let return_addr = pc.as_ptr();
+        let target = state[reg].get_ptr::<u8>();
+        state[XReg::lr].set_ptr(return_addr.as_ptr());
+        *pc = unsafe { UnsafeBytecodeStream::new(NonNull::new_unchecked(target)) };
+        ControlFlow::Continue(())
+    }
+
     fn jump(_state: &mut MachineState, pc: &mut UnsafeBytecodeStream, offset: PcRelOffset) {
         pc_rel_jump(pc, offset, 5);
         ControlFlow::Continue(())
@@ -277,6 +344,15 @@ define_opcode_handlers! {
         ControlFlow::Continue(())
     }
 
+    fn br_table32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, idx: XReg, amt: u32) {
+        // Each table entry is a 5-byte `jump` instruction and the last entry
+        // is the default.
+        let idx = state[idx].get_u32().min(amt - 1);
+        let idx = isize::try_from(idx).unwrap();
+        *pc = unsafe { pc.offset(idx * 5) };
+        ControlFlow::Continue(())
+    }
+
     fn xmov(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
         let val = state[src];
         state[dst] = val;
@@ -329,6 +405,306 @@ define_opcode_handlers! {
         ControlFlow::Continue(())
     }
 
+    fn xsub32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a.wrapping_sub(b));
+        ControlFlow::Continue(())
+    }
+
+    fn xsub64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a.wrapping_sub(b));
+        ControlFlow::Continue(())
+    }
+
+    fn xmul32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a.wrapping_mul(b));
+        ControlFlow::Continue(())
+    }
+
+    fn xmul64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a.wrapping_mul(b));
+        ControlFlow::Continue(())
+    }
+
+    fn xdiv32_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_i32();
+        let b = state[operands.src2].get_i32();
+        if b == 0 {
+            ControlFlow::Break(Done::Trap(trapping_pc(pc, 3)))
+        } else {
+            state[operands.dst].set_i32(a.wrapping_div(b));
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn xdiv32_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        if b == 0 {
+            ControlFlow::Break(Done::Trap(trapping_pc(pc, 3)))
+        } else {
+            state[operands.dst].set_u32(a.wrapping_div(b));
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn xdiv64_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_i64();
+        let b = state[operands.src2].get_i64();
+        if b == 0 {
+            ControlFlow::Break(Done::Trap(trapping_pc(pc, 3)))
+        } else {
+            state[operands.dst].set_i64(a.wrapping_div(b));
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn xdiv64_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        if b == 0 {
+            ControlFlow::Break(Done::Trap(trapping_pc(pc, 3)))
+        } else {
+            state[operands.dst].set_u64(a.wrapping_div(b));
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn xrem32_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_i32();
+        let b = state[operands.src2].get_i32();
+        if b == 0 {
+            ControlFlow::Break(Done::Trap(trapping_pc(pc, 3)))
+        } else {
+            state[operands.dst].set_i32(a.wrapping_rem(b));
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn xrem32_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        if b == 0 {
+            ControlFlow::Break(Done::Trap(trapping_pc(pc, 3)))
+        } else {
+            state[operands.dst].set_u32(a.wrapping_rem(b));
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn xrem64_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_i64();
+        let b = state[operands.src2].get_i64();
+        if b == 0 {
+            ControlFlow::Break(Done::Trap(trapping_pc(pc, 3)))
+        } else {
+            state[operands.dst].set_i64(a.wrapping_rem(b));
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn xrem64_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        if b == 0 {
+            ControlFlow::Break(Done::Trap(trapping_pc(pc, 3)))
+        } else {
+            state[operands.dst].set_u64(a.wrapping_rem(b));
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn xband32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a & b);
+        ControlFlow::Continue(())
+    }
+
+    fn xband64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a & b);
+        ControlFlow::Continue(())
+    }
+
+    fn xbor32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a | b);
+        ControlFlow::Continue(())
+    }
+
+    fn xbor64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a | b);
+        ControlFlow::Continue(())
+    }
+
+    fn xbxor32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a ^ b);
+        ControlFlow::Continue(())
+    }
+
+    fn xbxor64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a ^ b);
+        ControlFlow::Continue(())
+    }
+
+    fn xshl32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a.wrapping_shl(b));
+        ControlFlow::Continue(())
+    }
+
+    fn xshr32_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_i32();
+        let b = state[operands.src2].get_i32();
+        state[operands.dst].set_i32(a.wrapping_shr(b as u32));
+        ControlFlow::Continue(())
+    }
+
+    fn xshr32_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a.wrapping_shr(b));
+        ControlFlow::Continue(())
+    }
+
+    fn xshl64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a.wrapping_shl(b as u32));
+        ControlFlow::Continue(())
+    }
+
+    fn xshr64_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_i64();
+        let b = state[operands.src2].get_i64();
+        state[operands.dst].set_i64(a.wrapping_shr(b as u32));
+        ControlFlow::Continue(())
+    }
+
+    fn xshr64_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a.wrapping_shr(b as u32));
+        ControlFlow::Continue(())
+    }
+
+    fn xrotl32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a.rotate_left(b));
+        ControlFlow::Continue(())
+    }
+
+    fn xrotr32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u32();
+        let b = state[operands.src2].get_u32();
+        state[operands.dst].set_u32(a.rotate_right(b));
+        ControlFlow::Continue(())
+    }
+
+    fn xrotl64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a.rotate_left(b as u32));
+        ControlFlow::Continue(())
+    }
+
+    fn xrotr64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
+        let a = state[operands.src1].get_u64();
+        let b = state[operands.src2].get_u64();
+        state[operands.dst].set_u64(a.rotate_right(b as u32));
+        ControlFlow::Continue(())
+    }
+
+    fn xclz32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u32();
+        state[dst].set_u32(a.leading_zeros());
+        ControlFlow::Continue(())
+    }
+
+    fn xclz64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u64();
+        state[dst].set_u64(a.leading_zeros().into());
+        ControlFlow::Continue(())
+    }
+
+    fn xctz32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u32();
+        state[dst].set_u32(a.trailing_zeros());
+        ControlFlow::Continue(())
+    }
+
+    fn xctz64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u64();
+        state[dst].set_u64(a.trailing_zeros().into());
+        ControlFlow::Continue(())
+    }
+
+    fn xpopcnt32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u32();
+        state[dst].set_u32(a.count_ones());
+        ControlFlow::Continue(())
+    }
+
+    fn xpopcnt64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u64();
+        state[dst].set_u64(a.count_ones().into());
+        ControlFlow::Continue(())
+    }
+
+    fn zext8(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u64();
+        state[dst].set_u64(u64::from(a as u8));
+        ControlFlow::Continue(())
+    }
+
+    fn zext16(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u64();
+        state[dst].set_u64(u64::from(a as u16));
+        ControlFlow::Continue(())
+    }
+
+    fn zext32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_u64();
+        state[dst].set_u64(u64::from(a as u32));
+        ControlFlow::Continue(())
+    }
+
+    fn sext8(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_i64();
+        state[dst].set_i64(i64::from(a as i8));
+        ControlFlow::Continue(())
+    }
+
+    fn sext16(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_i64();
+        state[dst].set_i64(i64::from(a as i16));
+        ControlFlow::Continue(())
+    }
+
+    fn sext32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: XReg) {
+        let a = state[src].get_i64();
+        state[dst].set_i64(i64::from(a as i32));
+        ControlFlow::Continue(())
+    }
+
     fn xeq64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<XReg>) {
         let a = state[operands.src1].get_u64();
         let b = state[operands.src2].get_u64();
@@ -413,6 +789,26 @@ define_opcode_handlers! {
         ControlFlow::Continue(())
     }
 
+    fn xselect(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, cond: XReg, if_nonzero: XReg, if_zero: XReg) {
+        let val = if state[cond].get_u32() != 0 {
+            state[if_nonzero]
+        } else {
+            state[if_zero]
+        };
+        state[dst] = val;
+        ControlFlow::Continue(())
+    }
+
+    fn fselect(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, cond: XReg, if_nonzero: FReg, if_zero: FReg) {
+        let val = if state[cond].get_u32() != 0 {
+            state[if_nonzero]
+        } else {
+            state[if_zero]
+        };
+        state[dst] = val;
+        ControlFlow::Continue(())
+    }
+
     fn load32_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, ptr: XReg) {
         let ptr = state[ptr].get_ptr::<u32>();
         let val = unsafe { ptr::read_unaligned(ptr) };
@@ -562,6 +958,116 @@ define_opcode_handlers! {
         ControlFlow::Continue(())
     }
 
+    fn load8_u_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, ptr: XReg, offset: i64) {
+        let val = unsafe {
+            state[ptr]
+                .get_ptr::<u8>()
+                .byte_offset(offset as isize)
+                .read_unaligned()
+        };
+        state[dst].set_u64(u64::from(val));
+        ControlFlow::Continue(())
+    }
+
+    fn load8_s_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, ptr: XReg, offset: i64) {
+        let val = unsafe {
+            state[ptr]
+                .get_ptr::<i8>()
+                .byte_offset(offset as isize)
+                .read_unaligned()
+        };
+        state[dst].set_i64(i64::from(val));
+        ControlFlow::Continue(())
+    }
+
+    fn load16_u_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, ptr: XReg, offset: i64) {
+        let val = unsafe {
+            state[ptr]
+                .get_ptr::<u16>()
+                .byte_offset(offset as isize)
+                .read_unaligned()
+        };
+        state[dst].set_u64(u64::from(val));
+        ControlFlow::Continue(())
+    }
+
+    fn load16_s_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, ptr: XReg, offset: i64) {
+        let val = unsafe {
+            state[ptr]
+                .get_ptr::<i16>()
+                .byte_offset(offset as isize)
+                .read_unaligned()
+        };
+        state[dst].set_i64(i64::from(val));
+        ControlFlow::Continue(())
+    }
+
+    fn fload32_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, ptr: XReg, offset: i64) {
+        let val = unsafe {
+            state[ptr]
+                .get_ptr::<u32>()
+                .byte_offset(offset as isize)
+                .read_unaligned()
+        };
+        state[dst].set_f32(f32::from_bits(val));
+        ControlFlow::Continue(())
+    }
+
+    fn fload64_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, ptr: XReg, offset: i64) {
+        let val = unsafe {
+            state[ptr]
+                .get_ptr::<u64>()
+                .byte_offset(offset as isize)
+                .read_unaligned()
+        };
+        state[dst].set_f64(f64::from_bits(val));
+        ControlFlow::Continue(())
+    }
+
+    fn store8_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, ptr: XReg, offset: i64, src: XReg) {
+        let val = state[src].get_u32() as u8;
+        unsafe {
+            state[ptr]
+                .get_ptr::<u8>()
+                .byte_offset(offset as isize)
+                .write_unaligned(val);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn store16_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, ptr: XReg, offset: i64, src: XReg) {
+        let val = state[src].get_u32() as u16;
+        unsafe {
+            state[ptr]
+                .get_ptr::<u16>()
+                .byte_offset(offset as isize)
+                .write_unaligned(val);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn fstore32_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, ptr: XReg, offset: i64, src: FReg) {
+        let val = state[src].get_f32().to_bits();
+        unsafe {
+            state[ptr]
+                .get_ptr::<u32>()
+                .byte_offset(offset as isize)
+                .write_unaligned(val);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn fstore64_offset64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, ptr: XReg, offset: i64, src: FReg) {
+        let val = state[src].get_f64().to_bits();
+        unsafe {
+            state[ptr]
+                .get_ptr::<u64>()
+                .byte_offset(offset as isize)
+                .write_unaligned(val);
+        }
+        ControlFlow::Continue(())
+    }
+
     fn xpush32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, src: XReg) {
         state.push(state[src].get_u32());
         ControlFlow::Continue(())
@@ -654,16 +1160,385 @@ define_opcode_handlers! {
         ControlFlow::Continue(())
     }
 
+    fn fadd32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f32();
+        let b = state[operands.src2].get_f32();
+        state[operands.dst].set_f32(a + b);
+        ControlFlow::Continue(())
+    }
+
+    fn fadd64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f64();
+        let b = state[operands.src2].get_f64();
+        state[operands.dst].set_f64(a + b);
+        ControlFlow::Continue(())
+    }
+
+    fn fsub32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f32();
+        let b = state[operands.src2].get_f32();
+        state[operands.dst].set_f32(a - b);
+        ControlFlow::Continue(())
+    }
+
+    fn fsub64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f64();
+        let b = state[operands.src2].get_f64();
+        state[operands.dst].set_f64(a - b);
+        ControlFlow::Continue(())
+    }
+
+    fn fmul32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f32();
+        let b = state[operands.src2].get_f32();
+        state[operands.dst].set_f32(a * b);
+        ControlFlow::Continue(())
+    }
+
+    fn fmul64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f64();
+        let b = state[operands.src2].get_f64();
+        state[operands.dst].set_f64(a * b);
+        ControlFlow::Continue(())
+    }
+
+    fn fdiv32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f32();
+        let b = state[operands.src2].get_f32();
+        state[operands.dst].set_f32(a / b);
+        ControlFlow::Continue(())
+    }
+
+    fn fdiv64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f64();
+        let b = state[operands.src2].get_f64();
+        state[operands.dst].set_f64(a / b);
+        ControlFlow::Continue(())
+    }
+
+    fn fmin32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f32();
+        let b = state[operands.src2].get_f32();
+        state[operands.dst].set_f32(wasm_fmin32(a, b));
+        ControlFlow::Continue(())
+    }
+
+    fn fmin64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f64();
+        let b = state[operands.src2].get_f64();
+        state[operands.dst].set_f64(wasm_fmin64(a, b));
+        ControlFlow::Continue(())
+    }
+
+    fn fmax32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f32();
+        let b = state[operands.src2].get_f32();
+        state[operands.dst].set_f32(wasm_fmax32(a, b));
+        ControlFlow::Continue(())
+    }
+
+    fn fmax64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f64();
+        let b = state[operands.src2].get_f64();
+        state[operands.dst].set_f64(wasm_fmax64(a, b));
+        ControlFlow::Continue(())
+    }
+
+    fn fcopysign32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f32();
+        let b = state[operands.src2].get_f32();
+        state[operands.dst].set_f32(libm::copysignf(a, b));
+        ControlFlow::Continue(())
+    }
+
+    fn fcopysign64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, operands: BinaryOperands<FReg>) {
+        let a = state[operands.src1].get_f64();
+        let b = state[operands.src2].get_f64();
+        state[operands.dst].set_f64(libm::copysign(a, b));
+        ControlFlow::Continue(())
+    }
+
+    fn fneg32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f32();
+        state[dst].set_f32(-a);
+        ControlFlow::Continue(())
+    }
+
+    fn fneg64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f64();
+        state[dst].set_f64(-a);
+        ControlFlow::Continue(())
+    }
+
+    fn fabs32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f32();
+        state[dst].set_f32(libm::fabsf(a));
+        ControlFlow::Continue(())
+    }
+
+    fn fabs64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f64();
+        state[dst].set_f64(libm::fabs(a));
+        ControlFlow::Continue(())
+    }
+
+    fn fsqrt32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f32();
+        state[dst].set_f32(libm::sqrtf(a));
+        ControlFlow::Continue(())
+    }
+
+    fn fsqrt64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f64();
+        state[dst].set_f64(libm::sqrt(a));
+        ControlFlow::Continue(())
+    }
+
+    fn fceil32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f32();
+        state[dst].set_f32(libm::ceilf(a));
+        ControlFlow::Continue(())
+    }
+
+    fn fceil64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f64();
+        state[dst].set_f64(libm::ceil(a));
+        ControlFlow::Continue(())
+    }
+
+    fn ffloor32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f32();
+        state[dst].set_f32(libm::floorf(a));
+        ControlFlow::Continue(())
+    }
+
+    fn ffloor64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f64();
+        state[dst].set_f64(libm::floor(a));
+        ControlFlow::Continue(())
+    }
+
+    fn ftrunc32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f32();
+        state[dst].set_f32(libm::truncf(a));
+        ControlFlow::Continue(())
+    }
+
+    fn ftrunc64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f64();
+        state[dst].set_f64(libm::trunc(a));
+        ControlFlow::Continue(())
+    }
+
+    fn fnearest32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f32();
+        state[dst].set_f32(libm::rintf(a));
+        ControlFlow::Continue(())
+    }
+
+    fn fnearest64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f64();
+        state[dst].set_f64(libm::rint(a));
+        ControlFlow::Continue(())
+    }
+
+    fn feq32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src1: FReg, src2: FReg) {
+        let a = state[src1].get_f32();
+        let b = state[src2].get_f32();
+        state[dst].set_u64(u64::from(a == b));
+        ControlFlow::Continue(())
+    }
+
+    fn feq64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src1: FReg, src2: FReg) {
+        let a = state[src1].get_f64();
+        let b = state[src2].get_f64();
+        state[dst].set_u64(u64::from(a == b));
+        ControlFlow::Continue(())
+    }
+
+    fn fneq32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src1: FReg, src2: FReg) {
+        let a = state[src1].get_f32();
+        let b = state[src2].get_f32();
+        state[dst].set_u64(u64::from(a != b));
+        ControlFlow::Continue(())
+    }
+
+    fn fneq64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src1: FReg, src2: FReg) {
+        let a = state[src1].get_f64();
+        let b = state[src2].get_f64();
+        state[dst].set_u64(u64::from(a != b));
+        ControlFlow::Continue(())
+    }
+
+    fn flt32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src1: FReg, src2: FReg) {
+        let a = state[src1].get_f32();
+        let b = state[src2].get_f32();
+        state[dst].set_u64(u64::from(a < b));
+        ControlFlow::Continue(())
+    }
+
+    fn flt64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src1: FReg, src2: FReg) {
+        let a = state[src1].get_f64();
+        let b = state[src2].get_f64();
+        state[dst].set_u64(u64::from(a < b));
+        ControlFlow::Continue(())
+    }
+
+    fn flteq32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src1: FReg, src2: FReg) {
+        let a = state[src1].get_f32();
+        let b = state[src2].get_f32();
+        state[dst].set_u64(u64::from(a <= b));
+        ControlFlow::Continue(())
+    }
+
+    fn flteq64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src1: FReg, src2: FReg) {
+        let a = state[src1].get_f64();
+        let b = state[src2].get_f64();
+        state[dst].set_u64(u64::from(a <= b));
+        ControlFlow::Continue(())
+    }
+
+    fn f32_from_f64(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f64();
+        state[dst].set_f32(a as f32);
+        ControlFlow::Continue(())
+    }
+
+    fn f64_from_f32(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: FReg) {
+        let a = state[src].get_f32();
+        state[dst].set_f64(a.into());
+        ControlFlow::Continue(())
+    }
+
+    fn f32_from_x32_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: XReg) {
+        let a = state[src].get_i32();
+        state[dst].set_f32(a as f32);
+        ControlFlow::Continue(())
+    }
+
+    fn f32_from_x32_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: XReg) {
+        let a = state[src].get_u32();
+        state[dst].set_f32(a as f32);
+        ControlFlow::Continue(())
+    }
+
+    fn f32_from_x64_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: XReg) {
+        let a = state[src].get_i64();
+        state[dst].set_f32(a as f32);
+        ControlFlow::Continue(())
+    }
+
+    fn f32_from_x64_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: XReg) {
+        let a = state[src].get_u64();
+        state[dst].set_f32(a as f32);
+        ControlFlow::Continue(())
+    }
+
+    fn f64_from_x32_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: XReg) {
+        let a = state[src].get_i32();
+        state[dst].set_f64(a as f64);
+        ControlFlow::Continue(())
+    }
+
+    fn f64_from_x32_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: XReg) {
+        let a = state[src].get_u32();
+        state[dst].set_f64(a as f64);
+        ControlFlow::Continue(())
+    }
+
+    fn f64_from_x64_s(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: XReg) {
+        let a = state[src].get_i64();
+        state[dst].set_f64(a as f64);
+        ControlFlow::Continue(())
+    }
+
+    fn f64_from_x64_u(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: FReg, src: XReg) {
+        let a = state[src].get_u64();
+        state[dst].set_f64(a as f64);
+        ControlFlow::Continue(())
+    }
+
+    fn x32_from_f32_s_sat(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: FReg) {
+        // Rust's float-to-int `as` casts saturate and map NaN to zero.
+        let a = state[src].get_f32();
+        state[dst].set_i32(a as i32);
+        ControlFlow::Continue(())
+    }
+
+    fn x32_from_f32_u_sat(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: FReg) {
+        // Rust's float-to-int `as` casts saturate and map NaN to zero.
+        let a = state[src].get_f32();
+        state[dst].set_u32(a as u32);
+        ControlFlow::Continue(())
+    }
+
+    fn x32_from_f64_s_sat(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: FReg) {
+        // Rust's float-to-int `as` casts saturate and map NaN to zero.
+        let a = state[src].get_f64();
+        state[dst].set_i32(a as i32);
+        ControlFlow::Continue(())
+    }
+
+    fn x32_from_f64_u_sat(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: FReg) {
+        // Rust's float-to-int `as` casts saturate and map NaN to zero.
+        let a = state[src].get_f64();
+        state[dst].set_u32(a as u32);
+        ControlFlow::Continue(())
+    }
+
+    fn x64_from_f32_s_sat(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: FReg) {
+        // Rust's float-to-int `as` casts saturate and map NaN to zero.
+        let a = state[src].get_f32();
+        state[dst].set_i64(a as i64);
+        ControlFlow::Continue(())
+    }
+
+    fn x64_from_f32_u_sat(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: FReg) {
+        // Rust's float-to-int `as` casts saturate and map NaN to zero.
+        let a = state[src].get_f32();
+        state[dst].set_u64(a as u64);
+        ControlFlow::Continue(())
+    }
+
+    fn x64_from_f64_s_sat(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: FReg) {
+        // Rust's float-to-int `as` casts saturate and map NaN to zero.
+        let a = state[src].get_f64();
+        state[dst].set_i64(a as i64);
+        ControlFlow::Continue(())
+    }
+
+    fn x64_from_f64_u_sat(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, dst: XReg, src: FReg) {
+        // Rust's float-to-int `as` casts saturate and map NaN to zero.
+        let a = state[src].get_f64();
+        state[dst].set_u64(a as u64);
+        ControlFlow::Continue(())
+    }
+
     fn extended(state: &mut MachineState, pc: &mut UnsafeBytecodeStream, opcode: ExtendedOpcode) {
         match opcode {
             ExtendedOpcode::Nop => ControlFlow::Continue(()),
-            ExtendedOpcode::Trap => ControlFlow::Break(Done::Trap(pc.as_ptr())),
+            ExtendedOpcode::Trap => {
+                let inst_size = ExtendedOpcode::ENCODED_SIZE_OF_TRAP as isize;
+                ControlFlow::Break(Done::Trap(trapping_pc(pc, inst_size)))
+            }
             ExtendedOpcode::GetSp => {
                 let (dst,) = crate::decode::unwrap_uninhabited(crate::decode::operands::get_sp(pc));
                 let sp = state[XReg::sp].get_u64();
                 state[dst].set_u64(sp);
                 ControlFlow::Continue(())
             }
+            ExtendedOpcode::GetFp => {
+                let (dst,) = crate::decode::unwrap_uninhabited(crate::decode::operands::get_fp(pc));
+                let fp = state[XReg::fp].get_u64();
+                state[dst].set_u64(fp);
+                ControlFlow::Continue(())
+            }
+            ExtendedOpcode::CallIndirectHost => {
+                let (id,) = crate::decode::unwrap_uninhabited(
+                    crate::decode::operands::call_indirect_host(pc),
+                );
+                ControlFlow::Break(Done::CallIndirectHost { id, resume: pc.as_ptr() })
+            }
         }
     }
 }
@@ -60,6 +60,15 @@ macro_rules! for_each_op {
             /// Branch if unsigned `a <= b`.
             br_if_xulteq64 = BrIfXulteq64 { a: XReg, b: XReg, offset: PcRelOffset };
 
+            /// Branch to the `low32(idx)`th of the `amt` instructions that
+            /// follow this one, or to the last of them if `low32(idx)` is out
+            /// of bounds.
+            ///
+            /// Each of the instructions that follow is expected to be a
+            /// `jump`, so this implements a jump table whose last entry is
+            /// the default target.
+            br_table32 = BrTable32 { idx: XReg, amt: u32 };
+
             /// Move between `x` registers.
             xmov = Xmov { dst: XReg, src: XReg };
             /// Move between `f` registers.
@@ -84,6 +93,113 @@ macro_rules! for_each_op {
             /// 64-bit wrapping addition: `dst = src1 + src2`.
             xadd64 = Xadd64 { operands: BinaryOperands<XReg> };
 
+            /// 32-bit wrapping subtraction: `low32(dst) = low32(src1) - low32(src2)`.
+            ///
+            /// The upper 32-bits of `dst` are unmodified.
+            xsub32 = Xsub32 { operands: BinaryOperands<XReg> };
+            /// 64-bit wrapping subtraction: `dst = src1 - src2`.
+            xsub64 = Xsub64 { operands: BinaryOperands<XReg> };
+
+            /// 32-bit wrapping multiplication: `low32(dst) = low32(src1) * low32(src2)`.
+            ///
+            /// The upper 32-bits of `dst` are unmodified.
+            xmul32 = Xmul32 { operands: BinaryOperands<XReg> };
+            /// 64-bit wrapping multiplication: `dst = src1 * src2`.
+            xmul64 = Xmul64 { operands: BinaryOperands<XReg> };
+
+            /// 32-bit signed division: `low32(dst) = low32(src1) / low32(src2)`.
+            ///
+            /// Traps if `low32(src2)` is zero. Overflow wraps.
+            xdiv32_s = XDiv32S { operands: BinaryOperands<XReg> };
+            /// 64-bit signed division: `dst = src1 / src2`.
+            ///
+            /// Traps if `src2` is zero. Overflow wraps.
+            xdiv64_s = XDiv64S { operands: BinaryOperands<XReg> };
+            /// 32-bit unsigned division: `low32(dst) = low32(src1) / low32(src2)`.
+            ///
+            /// Traps if `low32(src2)` is zero.
+            xdiv32_u = XDiv32U { operands: BinaryOperands<XReg> };
+            /// 64-bit unsigned division: `dst = src1 / src2`.
+            ///
+            /// Traps if `src2` is zero.
+            xdiv64_u = XDiv64U { operands: BinaryOperands<XReg> };
+            /// 32-bit signed remainder: `low32(dst) = low32(src1) % low32(src2)`.
+            ///
+            /// Traps if `low32(src2)` is zero.
+            xrem32_s = XRem32S { operands: BinaryOperands<XReg> };
+            /// 64-bit signed remainder: `dst = src1 % src2`.
+            ///
+            /// Traps if `src2` is zero.
+            xrem64_s = XRem64S { operands: BinaryOperands<XReg> };
+            /// 32-bit unsigned remainder: `low32(dst) = low32(src1) % low32(src2)`.
+            ///
+            /// Traps if `low32(src2)` is zero.
+            xrem32_u = XRem32U { operands: BinaryOperands<XReg> };
+            /// 64-bit unsigned remainder: `dst = src1 % src2`.
+            ///
+            /// Traps if `src2` is zero.
+            xrem64_u = XRem64U { operands: BinaryOperands<XReg> };
+
+            /// `low32(dst) = low32(src1) & low32(src2)`
+            xband32 = XBand32 { operands: BinaryOperands<XReg> };
+            /// `dst = src1 & src2`
+            xband64 = XBand64 { operands: BinaryOperands<XReg> };
+            /// `low32(dst) = low32(src1) | low32(src2)`
+            xbor32 = XBor32 { operands: BinaryOperands<XReg> };
+            /// `dst = src1 | src2`
+            xbor64 = XBor64 { operands: BinaryOperands<XReg> };
+            /// `low32(dst) = low32(src1) ^ low32(src2)`
+            xbxor32 = XBxor32 { operands: BinaryOperands<XReg> };
+            /// `dst = src1 ^ src2`
+            xbxor64 = XBxor64 { operands: BinaryOperands<XReg> };
+
+            /// `low32(dst) = low32(src1) << (low32(src2) % 32)`
+            xshl32 = Xshl32 { operands: BinaryOperands<XReg> };
+            /// `low32(dst) = low32(src1) >> (low32(src2) % 32)` (signed)
+            xshr32_s = Xshr32S { operands: BinaryOperands<XReg> };
+            /// `low32(dst) = low32(src1) >> (low32(src2) % 32)` (unsigned)
+            xshr32_u = Xshr32U { operands: BinaryOperands<XReg> };
+            /// `dst = src1 << (src2 % 64)`
+            xshl64 = Xshl64 { operands: BinaryOperands<XReg> };
+            /// `dst = src1 >> (src2 % 64)` (signed)
+            xshr64_s = Xshr64S { operands: BinaryOperands<XReg> };
+            /// `dst = src1 >> (src2 % 64)` (unsigned)
+            xshr64_u = Xshr64U { operands: BinaryOperands<XReg> };
+            /// `low32(dst) = rotate_left(low32(src1), low32(src2) % 32)`
+            xrotl32 = Xrotl32 { operands: BinaryOperands<XReg> };
+            /// `low32(dst) = rotate_right(low32(src1), low32(src2) % 32)`
+            xrotr32 = Xrotr32 { operands: BinaryOperands<XReg> };
+            /// `dst = rotate_left(src1, src2 % 64)`
+            xrotl64 = Xrotl64 { operands: BinaryOperands<XReg> };
+            /// `dst = rotate_right(src1, src2 % 64)`
+            xrotr64 = Xrotr64 { operands: BinaryOperands<XReg> };
+
+            /// `low32(dst) = leading_zeros(low32(src))`
+            xclz32 = Xclz32 { dst: XReg, src: XReg };
+            /// `dst = leading_zeros(src)`
+            xclz64 = Xclz64 { dst: XReg, src: XReg };
+            /// `low32(dst) = trailing_zeros(low32(src))`
+            xctz32 = Xctz32 { dst: XReg, src: XReg };
+            /// `dst = trailing_zeros(src)`
+            xctz64 = Xctz64 { dst: XReg, src: XReg };
+            /// `low32(dst) = count_ones(low32(src))`
+            xpopcnt32 = Xpopcnt32 { dst: XReg, src: XReg };
+            /// `dst = count_ones(src)`
+            xpopcnt64 = Xpopcnt64 { dst: XReg, src: XReg };
+
+            /// `dst = zero_extend(low8(src))`
+            zext8 = Zext8 { dst: XReg, src: XReg };
+            /// `dst = zero_extend(low16(src))`
+            zext16 = Zext16 { dst: XReg, src: XReg };
+            /// `dst = zero_extend(low32(src))`
+            zext32 = Zext32 { dst: XReg, src: XReg };
+            /// `dst = sign_extend(low8(src))`
+            sext8 = Sext8 { dst: XReg, src: XReg };
+            /// `dst = sign_extend(low16(src))`
+            sext16 = Sext16 { dst: XReg, src: XReg };
+            /// `dst = sign_extend(low32(src))`
+            sext32 = Sext32 { dst: XReg, src: XReg };
+
             /// 64-bit equality.
             xeq64 = Xeq64 { operands: BinaryOperands<XReg> };
             /// 64-bit inequality.
@@ -109,6 +225,11 @@ macro_rules! for_each_op {
             /// 32-bit unsigned less-than-equal.
             xulteq32 = Xulteq32 { operands: BinaryOperands<XReg> };
 
+            /// `dst = if cond != 0 { if_nonzero } else { if_zero }`
+            xselect = XSelect { dst: XReg, cond: XReg, if_nonzero: XReg, if_zero: XReg };
+            /// `dst = if cond != 0 { if_nonzero } else { if_zero }`
+            fselect = FSelect { dst: FReg, cond: XReg, if_nonzero: FReg, if_zero: FReg };
+
             /// `dst = zero_extend(load32(ptr))`
             load32_u = Load32U { dst: XReg, ptr: XReg };
             /// `dst = sign_extend(load32(ptr))`
@@ -145,6 +266,28 @@ macro_rules! for_each_op {
             /// `*(ptr + sign_extend(offset64)) = src`
             store64_offset64 = Store64Offset64 { ptr: XReg, offset: i64, src: XReg };
 
+            /// `dst = zero_extend(load8(ptr + offset64))`
+            load8_u_offset64 = Load8UOffset64 { dst: XReg, ptr: XReg, offset: i64 };
+            /// `dst = sign_extend(load8(ptr + offset64))`
+            load8_s_offset64 = Load8SOffset64 { dst: XReg, ptr: XReg, offset: i64 };
+            /// `dst = zero_extend(load16(ptr + offset64))`
+            load16_u_offset64 = Load16UOffset64 { dst: XReg, ptr: XReg, offset: i64 };
+            /// `dst = sign_extend(load16(ptr + offset64))`
+            load16_s_offset64 = Load16SOffset64 { dst: XReg, ptr: XReg, offset: i64 };
+            /// `low32(dst) = load32(ptr + offset64)`
+            fload32_offset64 = Fload32Offset64 { dst: FReg, ptr: XReg, offset: i64 };
+            /// `dst = load64(ptr + offset64)`
+            fload64_offset64 = Fload64Offset64 { dst: FReg, ptr: XReg, offset: i64 };
+
+            /// `*(ptr + sign_extend(offset64)) = low8(src)`
+            store8_offset64 = Store8Offset64 { ptr: XReg, offset: i64, src: XReg };
+            /// `*(ptr + sign_extend(offset64)) = low16(src)`
+            store16_offset64 = Store16Offset64 { ptr: XReg, offset: i64, src: XReg };
+            /// `*(ptr + sign_extend(offset64)) = low32(src)`
+            fstore32_offset64 = Fstore32Offset64 { ptr: XReg, offset: i64, src: FReg };
+            /// `*(ptr + sign_extend(offset64)) = src`
+            fstore64_offset64 = Fstore64Offset64 { ptr: XReg, offset: i64, src: FReg };
+
             /// `push lr; push fp; fp = sp`
             push_frame = PushFrame ;
             /// `sp = fp; pop fp; pop lr`
@@ -176,6 +319,138 @@ macro_rules! for_each_op {
             bitcast_float_from_int_32 = BitcastFloatFromInt32 { dst: FReg, src: XReg };
             /// `dst = bitcast src as f64`
             bitcast_float_from_int_64 = BitcastFloatFromInt64 { dst: FReg, src: XReg };
+
+            /// `low32(dst) = low32(src1) + low32(src2)`
+            fadd32 = Fadd32 { operands: BinaryOperands<FReg> };
+            /// `dst = src1 + src2`
+            fadd64 = Fadd64 { operands: BinaryOperands<FReg> };
+            /// `low32(dst) = low32(src1) - low32(src2)`
+            fsub32 = Fsub32 { operands: BinaryOperands<FReg> };
+            /// `dst = src1 - src2`
+            fsub64 = Fsub64 { operands: BinaryOperands<FReg> };
+            /// `low32(dst) = low32(src1) * low32(src2)`
+            fmul32 = Fmul32 { operands: BinaryOperands<FReg> };
+            /// `dst = src1 * src2`
+            fmul64 = Fmul64 { operands: BinaryOperands<FReg> };
+            /// `low32(dst) = low32(src1) / low32(src2)`
+            fdiv32 = Fdiv32 { operands: BinaryOperands<FReg> };
+            /// `dst = src1 / src2`
+            fdiv64 = Fdiv64 { operands: BinaryOperands<FReg> };
+            /// `low32(dst) = min(low32(src1), low32(src2))`, propagating NaNs
+            /// and ordering `-0.0` before `0.0`.
+            fmin32 = Fmin32 { operands: BinaryOperands<FReg> };
+            /// `dst = min(src1, src2)`, propagating NaNs and ordering `-0.0`
+            /// before `0.0`.
+            fmin64 = Fmin64 { operands: BinaryOperands<FReg> };
+            /// `low32(dst) = max(low32(src1), low32(src2))`, propagating NaNs
+            /// and ordering `-0.0` before `0.0`.
+            fmax32 = Fmax32 { operands: BinaryOperands<FReg> };
+            /// `dst = max(src1, src2)`, propagating NaNs and ordering `-0.0`
+            /// before `0.0`.
+            fmax64 = Fmax64 { operands: BinaryOperands<FReg> };
+            /// `low32(dst) = copysign(low32(src1), low32(src2))`
+            fcopysign32 = Fcopysign32 { operands: BinaryOperands<FReg> };
+            /// `dst = copysign(src1, src2)`
+            fcopysign64 = Fcopysign64 { operands: BinaryOperands<FReg> };
+
+            /// `low32(dst) = -low32(src)`
+            fneg32 = Fneg32 { dst: FReg, src: FReg };
+            /// `dst = -src`
+            fneg64 = Fneg64 { dst: FReg, src: FReg };
+            /// `low32(dst) = |low32(src)|`
+            fabs32 = Fabs32 { dst: FReg, src: FReg };
+            /// `dst = |src|`
+            fabs64 = Fabs64 { dst: FReg, src: FReg };
+            /// `low32(dst) = sqrt(low32(src))`
+            fsqrt32 = Fsqrt32 { dst: FReg, src: FReg };
+            /// `dst = sqrt(src)`
+            fsqrt64 = Fsqrt64 { dst: FReg, src: FReg };
+            /// `low32(dst) = ceil(low32(src))`
+            fceil32 = Fceil32 { dst: FReg, src: FReg };
+            /// `dst = ceil(src)`
+            fceil64 = Fceil64 { dst: FReg, src: FReg };
+            /// `low32(dst) = floor(low32(src))`
+            ffloor32 = Ffloor32 { dst: FReg, src: FReg };
+            /// `dst = floor(src)`
+            ffloor64 = Ffloor64 { dst: FReg, src: FReg };
+            /// `low32(dst) = trunc(low32(src))`
+            ftrunc32 = Ftrunc32 { dst: FReg, src: FReg };
+            /// `dst = trunc(src)`
+            ftrunc64 = Ftrunc64 { dst: FReg, src: FReg };
+            /// `low32(dst) = low32(src)` rounded to the nearest integer, with
+            /// ties to even.
+            fnearest32 = Fnearest32 { dst: FReg, src: FReg };
+            /// `dst = src` rounded to the nearest integer, with ties to even.
+            fnearest64 = Fnearest64 { dst: FReg, src: FReg };
+
+            /// `dst = low32(src1) == low32(src2)`
+            feq32 = Feq32 { dst: XReg, src1: FReg, src2: FReg };
+            /// `dst = src1 == src2`
+            feq64 = Feq64 { dst: XReg, src1: FReg, src2: FReg };
+            /// `dst = low32(src1) != low32(src2)`, which is true if either is
+            /// NaN.
+            fneq32 = Fneq32 { dst: XReg, src1: FReg, src2: FReg };
+            /// `dst = src1 != src2`, which is true if either is NaN.
+            fneq64 = Fneq64 { dst: XReg, src1: FReg, src2: FReg };
+            /// `dst = low32(src1) < low32(src2)`
+            flt32 = Flt32 { dst: XReg, src1: FReg, src2: FReg };
+            /// `dst = src1 < src2`
+            flt64 = Flt64 { dst: XReg, src1: FReg, src2: FReg };
+            /// `dst = low32(src1) <= low32(src2)`
+            flteq32 = Flteq32 { dst: XReg, src1: FReg, src2: FReg };
+            /// `dst = src1 <= src2`
+            flteq64 = Flteq64 { dst: XReg, src1: FReg, src2: FReg };
+
+            /// `low32(dst) = demote(src)`
+            f32_from_f64 = F32FromF64 { dst: FReg, src: FReg };
+            /// `dst = promote(low32(src))`
+            f64_from_f32 = F64FromF32 { dst: FReg, src: FReg };
+
+            /// `low32(dst) = low32(src)` converted from a signed integer.
+            f32_from_x32_s = F32FromX32S { dst: FReg, src: XReg };
+            /// `low32(dst) = low32(src)` converted from an unsigned integer.
+            f32_from_x32_u = F32FromX32U { dst: FReg, src: XReg };
+            /// `low32(dst) = src` converted from a signed integer.
+            f32_from_x64_s = F32FromX64S { dst: FReg, src: XReg };
+            /// `low32(dst) = src` converted from an unsigned integer.
+            f32_from_x64_u = F32FromX64U { dst: FReg, src: XReg };
+            /// `dst = low32(src)` converted from a signed integer.
+            f64_from_x32_s = F64FromX32S { dst: FReg, src: XReg };
+            /// `dst = low32(src)` converted from an unsigned integer.
+            f64_from_x32_u = F64FromX32U { dst: FReg, src: XReg };
+            /// `dst = src` converted from a signed integer.
+            f64_from_x64_s = F64FromX64S { dst: FReg, src: XReg };
+            /// `dst = src` converted from an unsigned integer.
+            f64_from_x64_u = F64FromX64U { dst: FReg, src: XReg };
+
+            /// `low32(dst) = low32(src)` truncated to a signed integer,
+            /// saturating out-of-range values and converting NaN to zero.
+            x32_from_f32_s_sat = X32FromF32SSat { dst: XReg, src: FReg };
+            /// `low32(dst) = low32(src)` truncated to an unsigned integer,
+            /// saturating out-of-range values and converting NaN to zero.
+            x32_from_f32_u_sat = X32FromF32USat { dst: XReg, src: FReg };
+            /// `low32(dst) = src` truncated to a signed integer, saturating
+            /// out-of-range values and converting NaN to zero.
+            x32_from_f64_s_sat = X32FromF64SSat { dst: XReg, src: FReg };
+            /// `low32(dst) = src` truncated to an unsigned integer, saturating
+            /// out-of-range values and converting NaN to zero.
+            x32_from_f64_u_sat = X32FromF64USat { dst: XReg, src: FReg };
+            /// `dst = low32(src)` truncated to a signed integer, saturating
+            /// out-of-range values and converting NaN to zero.
+            x64_from_f32_s_sat = X64FromF32SSat { dst: XReg, src: FReg };
+            /// `dst = low32(src)` truncated to an unsigned integer, saturating
+            /// out-of-range values and converting NaN to zero.
+            x64_from_f32_u_sat = X64FromF32USat { dst: XReg, src: FReg };
+            /// `dst = src` truncated to a signed integer, saturating
+            /// out-of-range values and converting NaN to zero.
+            x64_from_f64_s_sat = X64FromF64SSat { dst: XReg, src: FReg };
+            /// `dst = src` truncated to an unsigned integer, saturating
+            /// out-of-range values and converting NaN to zero.
+            x64_from_f64_u_sat = X64FromF64USat { dst: XReg, src: FReg };
+
+            /// Transfer control to the PC in `reg` and set `lr` to the PC just
+            /// after this instruction.
+            call_indirect = CallIndirect { reg: XReg };
         }
     };
 }
@@ -192,6 +467,19 @@ macro_rules! for_each_extended_op {
 
             /// Copy the special `sp` stack pointer register into an `x` register.
             get_sp = GetSp { dst: XReg };
+
+            /// Copy the special `fp` frame pointer register into an `x`
+            /// register.
+            get_fp = GetFp { dst: XReg };
+
+            /// Stop executing bytecode and return control to the host so that
+            /// it can perform the call identified by `id`.
+            ///
+            /// Arguments and results are passed in registers according to the
+            /// usual calling convention. What `id` means is up to the embedder;
+            /// the interpreter resumes at the next instruction once the host
+            /// has finished the call.
+            call_indirect_host = CallIndirectHost { id: u8 };
         }
     };
 }
@@ -1,7 +1,10 @@
 //! Interpreter tests.
 
-use interp::Val;
-use pulley_interpreter::{interp::Vm, *};
+use interp::{RegType, Val};
+use pulley_interpreter::{
+    interp::{DoneReason, Vm},
+    *,
+};
 use std::{cell::UnsafeCell, fmt::Debug, ptr::NonNull};
 
 fn encoded(ops: &[Op]) -> Vec<u8> {
@@ -16,8 +19,11 @@ fn encoded(ops: &[Op]) -> Vec<u8> {
 unsafe fn run(vm: &mut Vm, ops: &[Op]) -> Result<(), NonNull<u8>> {
     let _ = env_logger::try_init();
     let ops = encoded(ops);
-    let _ = vm.call(NonNull::from(&ops[..]).cast(), &[], [])?;
-    Ok(())
+    match vm.call(NonNull::from(&ops[..]).cast(), &[], []) {
+        DoneReason::ReturnToHost(_) => Ok(()),
+        DoneReason::Trap(pc) => Err(pc),
+        DoneReason::CallIndirectHost { .. } => panic!("unexpected host call"),
+    }
 }
 
 unsafe fn assert_one<R0, R1, V>(
@@ -169,6 +175,79 @@ fn xadd64() {
     }
 }
 
+#[test]
+fn xsub32() {
+    for (expected, a, b) in [
+        (22u64 | 0x1234567800000000, 32u64, 10u64),
+        (u32::MAX as u64 | 0x1234567800000000, 0, 1),
+    ] {
+        unsafe {
+            assert_one(
+                [(x(0), 0x1234567812345678), (x(1), a), (x(2), b)],
+                Xsub32 {
+                    operands: BinaryOperands {
+                        dst: x(0),
+                        src1: x(1),
+                        src2: x(2),
+                    },
+                },
+                x(0),
+                expected,
+            );
+        }
+    }
+}
+
+#[test]
+fn xdiv64_s() {
+    for (expected, a, b) in [
+        (3u64, 10u64, 3u64),
+        (-5i64 as u64, 10, -2i64 as u64),
+        (i64::MIN as u64, i64::MIN as u64, -1i64 as u64),
+    ] {
+        unsafe {
+            assert_one(
+                [(x(0), 0x1234567812345678), (x(1), a), (x(2), b)],
+                XDiv64S {
+                    operands: BinaryOperands {
+                        dst: x(0),
+                        src1: x(1),
+                        src2: x(2),
+                    },
+                },
+                x(0),
+                expected,
+            );
+        }
+    }
+}
+
+#[test]
+fn xdiv_by_zero_traps() {
+    let mut vm = Vm::new();
+    let ops = encoded(&[
+        Op::Xconst8(Xconst8 { dst: x(0), imm: 1 }),
+        Op::Xconst8(Xconst8 { dst: x(1), imm: 0 }),
+        Op::XDiv32U(XDiv32U {
+            operands: BinaryOperands {
+                dst: x(0),
+                src1: x(0),
+                src2: x(1),
+            },
+        }),
+        Op::Ret(Ret {}),
+    ]);
+
+    unsafe {
+        match vm.call(NonNull::from(&ops[..]).cast(), &[], []) {
+            // The trapping PC is that of the division itself.
+            DoneReason::Trap(pc) => assert_eq!(pc.as_ptr().offset_from(ops.as_ptr()), 6),
+            _ => panic!("expected a trap"),
+        }
+    }
+    assert_eq!(vm.state()[x(0)].get_u32(), 1);
+}
+
 #[test]
 fn xeq64() {
     for (expected, a, b) in [
@@ -1214,3 +1293,190 @@ fn trap() {
     // `dst` should not have been written to the second time.
     assert_eq!(vm.state()[dst].get_u32(), 1);
 }
+
+#[test]
+fn call_indirect_host() {
+    let _ = env_logger::try_init();
+    let mut vm = Vm::new();
+    let ops = encoded(&[
+        Op::Xconst8(Xconst8 { dst: x(0), imm: 10 }),
+        Op::ExtendedOp(ExtendedOp::CallIndirectHost(CallIndirectHost { id: 42 })),
+        Op::Xadd64(Xadd64 {
+            operands: BinaryOperands {
+                dst: x(0),
+                src1: x(0),
+                src2: x(0),
+            },
+        }),
+        Op::Ret(Ret {}),
+    ]);
+
+    unsafe {
+        let resume = match vm.call_run(NonNull::from(&ops[..]).cast()) {
+            DoneReason::CallIndirectHost { id, resume } => {
+                assert_eq!(id, 42);
+                resume
+            }
+            other => panic!("expected a host call, got {other:?}"),
+        };
+
+        // Perform the "host call", which increments its argument.
+        assert_eq!(vm.state()[x(0)].get_u64(), 10);
+        vm.state_mut()[x(0)].set_u64(11);
+
+        match vm.call_run(resume) {
+            DoneReason::ReturnToHost(()) => {}
+            other => panic!("expected a return, got {other:?}"),
+        }
+    }
+
+    assert_eq!(vm.state()[x(0)].get_u64(), 22);
+}
+
+#[test]
+fn call_indirect() {
+    let _ = env_logger::try_init();
+    let callee = encoded(&[Op::Xconst8(Xconst8 { dst: x(0), imm: 7 }), Op::Ret(Ret {})]);
+    let caller = encoded(&[
+        Op::PushFrame(PushFrame {}),
+        Op::CallIndirect(CallIndirect { reg: x(1) }),
+        Op::PopFrame(PopFrame {}),
+        Op::Ret(Ret {}),
+    ]);
+
+    let mut vm = Vm::new();
+    unsafe {
+        let args = [Val::from(0_u64), Val::from(callee.as_ptr().cast_mut())];
+        match vm.call(NonNull::from(&caller[..]).cast(), &args, [RegType::XReg]) {
+            DoneReason::ReturnToHost(mut rets) => match rets.next() {
+                Some(Val::XReg(v)) => assert_eq!(v.get_u64(), 7),
+                other => panic!("unexpected return value {other:?}"),
+            },
+            DoneReason::Trap(_) => panic!("unexpected trap"),
+            DoneReason::CallIndirectHost { .. } => panic!("unexpected host call"),
+        };
+    }
+}
+
+#[test]
+fn push_pop_stay_in_bounds() {
+    let mut vm = Vm::with_stack(vec![0; 64]);
+    let stack = vm.state().stack_range();
+    unsafe {
+        run(
+            &mut vm,
+            &[
+                Op::Xconst8(Xconst8 { dst: x(0), imm: 1 }),
+                Op::Xconst8(Xconst8 { dst: x(1), imm: 2 }),
+                Op::XPush64(XPush64 { src: x(0) }),
+                Op::XPush32(XPush32 { src: x(1) }),
+                Op::ExtendedOp(ExtendedOp::GetSp(GetSp { dst: x(2) })),
+                Op::XPop32(XPop32 { dst: x(3) }),
+                Op::XPop64(XPop64 { dst: x(4) }),
+                Op::Ret(Ret {}),
+            ],
+        )
+        .unwrap();
+    }
+
+    // The stack grows down from the end of its allocation.
+    let sp = vm.state()[x(2)].get_u64() as usize;
+    assert_eq!(sp, stack.end as usize - 12);
+    assert!(stack.start as usize <= sp);
+    assert_eq!(vm.state()[x(3)].get_u64(), 2);
+    assert_eq!(vm.state()[x(4)].get_u64(), 1);
+    assert_eq!(vm.state()[XReg::sp].get_u64(), stack.end as usize as u64);
+}
+
+#[test]
+fn br_table32() {
+    for (idx, expected) in [(0u64, 10u64), (1, 11), (2, 12), (3, 12), (u64::MAX, 12)] {
+        let mut vm = Vm::new();
+        vm.state_mut()[x(1)].set_u64(idx);
+        unsafe {
+            run(
+                &mut vm,
+                &[
+                    Op::BrTable32(BrTable32 { idx: x(1), amt: 3 }),
+                    Op::Jump(Jump { offset: 15.into() }),
+                    Op::Jump(Jump { offset: 14.into() }),
+                    Op::Jump(Jump { offset: 13.into() }),
+                    Op::Xconst8(Xconst8 { dst: x(0), imm: 10 }),
+                    Op::Ret(Ret {}),
+                    Op::Xconst8(Xconst8 { dst: x(0), imm: 11 }),
+                    Op::Ret(Ret {}),
+                    Op::Xconst8(Xconst8 { dst: x(0), imm: 12 }),
+                    Op::Ret(Ret {}),
+                ],
+            )
+            .unwrap();
+        }
+        assert_eq!(vm.state()[x(0)].get_u64(), expected, "idx = {idx}");
+    }
+}
+
+#[test]
+fn fmin64() {
+    for (expected, a, b) in [
+        (1.0f64.to_bits(), 1.0f64, 2.0f64),
+        ((-0.0f64).to_bits(), 0.0, -0.0),
+        ((-0.0f64).to_bits(), -0.0, 0.0),
+    ] {
+        unsafe {
+            assert_one(
+                [(f(1), a), (f(2), b)],
+                Fmin64 {
+                    operands: BinaryOperands {
+                        dst: f(0),
+                        src1: f(1),
+                        src2: f(2),
+                    },
+                },
+                f(0),
+                expected,
+            );
+        }
+    }
+}
+
+#[test]
+fn fnearest64() {
+    for (expected, a) in [(2.0f64, 2.5f64), (4.0, 3.5), (-2.0, -2.5), (1.0, 0.75)] {
+        unsafe {
+            assert_one(
+                [(f(1), a)],
+                Fnearest64 {
+                    dst: f(0),
+                    src: f(1),
+                },
+                f(0),
+                expected.to_bits(),
+            );
+        }
+    }
+}
+
+#[test]
+fn x32_from_f64_s_sat() {
+    for (expected, a) in [
+        (0x1234_5678_0000_0007u64, 7.9f64),
+        (0x1234_5678_8000_0000, -1e10),
+        (0x1234_5678_7fff_ffff, 1e10),
+        (0x1234_5678_0000_0000, f64::NAN),
+    ] {
+        unsafe {
+            assert_one(
+                [
+                    (AnyReg::from(f(1)), Val::from(a)),
+                    (AnyReg::from(x(0)), Val::from(0x1234567812345678u64)),
+                ],
+                X32FromF64SSat {
+                    dst: x(0),
+                    src: f(1),
+                },
+                x(0),
+                expected,
+            );
+        }
+    }
+}
@@ -66,6 +66,10 @@ enum Subcommand {
     #[cfg(feature = "compile")]
     Compile(wasmtime_cli::commands::CompileCommand),
 
+    /// Runs a Debug Adapter Protocol server for debugging WebAssembly modules.
+    #[cfg(feature = "debug-adapter")]
+    DebugAdapter(wasmtime_cli::commands::DebugAdapterCommand),
+
     /// Explore the compilation of a WebAssembly module to native code.
     #[cfg(feature = "explore")]
     Explore(wasmtime_cli::commands::ExploreCommand),
@@ -105,6 +109,9 @@ impl Wasmtime {
             #[cfg(feature = "compile")]
             Subcommand::Compile(c) => c.execute(),
 
+            #[cfg(feature = "debug-adapter")]
+            Subcommand::DebugAdapter(c) => c.execute(),
+
             #[cfg(feature = "explore")]
             Subcommand::Explore(c) => c.execute(),
 
@@ -30,6 +30,11 @@ mod compile;
 #[cfg(feature = "compile")]
 pub use self::compile::*;
 
+#[cfg(feature = "debug-adapter")]
+mod debug_adapter;
+#[cfg(feature = "debug-adapter")]
+pub use self::debug_adapter::*;
+
 #[cfg(feature = "cranelift")]
 mod settings;
 #[cfg(feature = "cranelift")]
@@ -0,0 +1,513 @@
+//! The module that implements the `wasmtime debug-adapter` command.
+//!
+//! This is a minimal [Debug Adapter Protocol] server built on top of
+//! Wasmtime's in-process guest debugging API. It speaks DAP over stdin and
+//! stdout so that an IDE can launch it as a debug adapter for a core
+//! WebAssembly module using WASI preview1.
+//!
+//! Wasm has no notion of source lines without DWARF, so breakpoints are set
+//! with `setInstructionBreakpoints`, where each instruction reference is a
+//! byte offset in the module's original Wasm binary. The paused frame exposes
+//! three scopes: the function's locals, its operand stack, and the instance's
+//! globals.
+//!
+//! [Debug Adapter Protocol]: https://microsoft.github.io/debug-adapter-protocol/
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use wasmtime::{
+    AsContextMut, DebugAction, DebugFrame, Engine, Linker, Module, Store, StoreContextMut, Val,
+};
+use wasmtime_cli_flags::CommonOptions;
+use wasmtime_wasi::pipe::MemoryOutputPipe;
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{I32Exit, WasiCtxBuilder};
+
+/// The only thread reported to the client; Wasm execution is single-threaded.
+const THREAD_ID: u64 = 1;
+
+/// Variable references for the scopes of the paused frame.
+const LOCALS_REF: u64 = 1;
+const STACK_REF: u64 = 2;
+const GLOBALS_REF: u64 = 3;
+
+/// Run a Debug Adapter Protocol server for WebAssembly modules over stdio.
+#[derive(Parser, PartialEq)]
+pub struct DebugAdapterCommand {
+    #[command(flatten)]
+    common: CommonOptions,
+}
+
+impl DebugAdapterCommand {
+    /// Executes the command.
+    pub fn execute(mut self) -> Result<()> {
+        self.common.init_logging()?;
+
+        let mut config = self.common.config(None, None)?;
+        config.guest_debug(true);
+        // Used to interrupt the program when the client disconnects.
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+
+        let output = Output::new(Box::new(std::io::stdout()));
+        let mut session = Session {
+            engine,
+            output,
+            launch: None,
+            shared: Arc::new(Shared::default()),
+            resume: None,
+            thread: None,
+        };
+        let mut input = BufReader::new(std::io::stdin().lock());
+        while let Some(request) = read_message(&mut input)? {
+            if !session.handle(&request)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The program that a `launch` request asked to debug.
+struct Launch {
+    module: Module,
+    args: Vec<String>,
+    invoke: Option<String>,
+    stop_on_entry: bool,
+}
+
+/// State shared between the protocol thread and the thread running Wasm.
+#[derive(Default)]
+struct Shared {
+    /// The breakpoint offsets requested by the client.
+    breakpoints: Mutex<BTreeSet<u32>>,
+    /// The frame that execution is currently paused in, if any.
+    paused: Mutex<Option<PausedFrame>>,
+}
+
+/// A snapshot of a paused frame, formatted for display.
+struct PausedFrame {
+    func_index: u32,
+    wasm_offset: u32,
+    locals: Vec<(String, String)>,
+    stack: Vec<(String, String)>,
+    globals: Vec<(String, String)>,
+}
+
+struct Session {
+    engine: Engine,
+    output: Output,
+    launch: Option<Launch>,
+    shared: Arc<Shared>,
+    /// Sends resume actions to the Wasm thread once it has been started.
+    resume: Option<Sender<DebugAction>>,
+    /// The thread running Wasm, once it has been started.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Session {
+    /// Handle a single request, returning `false` once the session is over.
+    fn handle(&mut self, request: &Value) -> Result<bool> {
+        let command = request["command"].as_str().unwrap_or_default();
+        let args = &request["arguments"];
+        let body = match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsInstructionBreakpoints": true,
+            })),
+            "launch" => self.launch(args),
+            "setBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "setExceptionBreakpoints" => Ok(json!({})),
+            "setInstructionBreakpoints" => self.set_instruction_breakpoints(args),
+            "configurationDone" => self.start(),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "stackTrace" => Ok(self.stack_trace()),
+            "scopes" => Ok(self.scopes()),
+            "variables" => Ok(self.variables(args)),
+            "continue" => self
+                .resume(DebugAction::Continue)
+                .map(|()| json!({ "allThreadsContinued": true })),
+            "next" => self.resume(DebugAction::StepOver).map(|()| json!({})),
+            "stepIn" => self.resume(DebugAction::Step).map(|()| json!({})),
+            "stepOut" => self.resume(DebugAction::StepOut).map(|()| json!({})),
+            "disconnect" | "terminate" => {
+                self.stop();
+                self.output.respond(request, Ok(json!({})))?;
+                return Ok(false);
+            }
+            _ => Err(anyhow::anyhow!("unsupported request `{command}`")),
+        };
+        self.output.respond(request, body)?;
+        Ok(true)
+    }
+
+    fn launch(&mut self, args: &Value) -> Result<Value> {
+        let program = args["program"]
+            .as_str()
+            .context("`launch` requires a `program`")?;
+        let module = Module::from_file(&self.engine, program)
+            .with_context(|| format!("failed to load `{program}`"))?;
+        let mut argv = vec![program.to_string()];
+        if let Some(rest) = args["args"].as_array() {
+            argv.extend(rest.iter().filter_map(|a| a.as_str()).map(String::from));
+        }
+        self.launch = Some(Launch {
+            module,
+            args: argv,
+            invoke: args["invoke"].as_str().map(String::from),
+            stop_on_entry: args["stopOnEntry"].as_bool().unwrap_or(false),
+        });
+        self.output.event("initialized", json!({}))?;
+        Ok(json!({}))
+    }
+
+    fn set_instruction_breakpoints(&mut self, args: &Value) -> Result<Value> {
+        let mut offsets = BTreeSet::new();
+        let mut verified = Vec::new();
+        for bp in args["breakpoints"].as_array().into_iter().flatten() {
+            let offset = bp["instructionReference"]
+                .as_str()
+                .and_then(parse_offset)
+                .and_then(|base| {
+                    let delta = bp["offset"].as_i64().unwrap_or(0);
+                    u32::try_from(i64::from(base) + delta).ok()
+                });
+            if let Some(offset) = offset {
+                offsets.insert(offset);
+            }
+            verified.push(json!({
+                "verified": offset.is_some(),
+                "instructionReference": offset.map(|o| format!("{o:#x}")),
+            }));
+        }
+        // Breakpoints are applied to the store when the program starts and
+        // every time it pauses.
+        *self.shared.breakpoints.lock().unwrap() = offsets;
+        Ok(json!({ "breakpoints": verified }))
+    }
+
+    fn start(&mut self) -> Result<Value> {
+        let Some(launch) = self.launch.take() else {
+            bail!("`configurationDone` received before `launch`");
+        };
+        let (tx, rx) = channel();
+        self.resume = Some(tx);
+        let engine = self.engine.clone();
+        let output = self.output.clone();
+        let shared = self.shared.clone();
+        self.thread = Some(thread::spawn(move || {
+            let stdout = MemoryOutputPipe::new(usize::MAX);
+            let exit_code = match run(&engine, &launch, &output, &shared, rx, stdout.clone()) {
+                Ok(()) => 0,
+                Err(e) => match e.downcast_ref::<I32Exit>() {
+                    Some(exit) => exit.0,
+                    None => {
+                        let _ = output.event(
+                            "output",
+                            json!({ "category": "stderr", "output": format!("Error: {e:?}\n") }),
+                        );
+                        1
+                    }
+                },
+            };
+            let captured = String::from_utf8_lossy(&stdout.contents()).into_owned();
+            if !captured.is_empty() {
+                let _ = output.event(
+                    "output",
+                    json!({ "category": "stdout", "output": captured }),
+                );
+            }
+            let _ = output.event("exited", json!({ "exitCode": exit_code }));
+            let _ = output.event("terminated", json!({}));
+        }));
+        Ok(json!({}))
+    }
+
+    /// Stop the program, if it has been started, and wait for it to exit.
+    fn stop(&mut self) {
+        // A paused program fails to receive its next action and traps, and a
+        // running one is interrupted at its next epoch check.
+        self.resume = None;
+        self.engine.increment_epoch();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    fn resume(&mut self, action: DebugAction) -> Result<()> {
+        if self.shared.paused.lock().unwrap().take().is_none() {
+            bail!("the program is not paused");
+        }
+        self.resume
+            .as_ref()
+            .context("the program has not been started")?
+            .send(action)
+            .context("the program has already exited")?;
+        Ok(())
+    }
+
+    fn stack_trace(&self) -> Value {
+        let paused = self.shared.paused.lock().unwrap();
+        let frames = paused.iter().map(|frame| {
+            json!({
+                "id": 0,
+                "name": format!("func[{}]", frame.func_index),
+                "line": 0,
+                "column": 0,
+                "instructionPointerReference": format!("{:#x}", frame.wasm_offset),
+            })
+        });
+        let frames = frames.collect::<Vec<_>>();
+        json!({ "stackFrames": frames, "totalFrames": frames.len() })
+    }
+
+    fn scopes(&self) -> Value {
+        json!({
+            "scopes": [
+                { "name": "Locals", "variablesReference": LOCALS_REF, "expensive": false },
+                { "name": "Operand Stack", "variablesReference": STACK_REF, "expensive": false },
+                { "name": "Globals", "variablesReference": GLOBALS_REF, "expensive": false },
+            ]
+        })
+    }
+
+    fn variables(&self, args: &Value) -> Value {
+        let paused = self.shared.paused.lock().unwrap();
+        let vars = paused
+            .as_ref()
+            .map(|frame| match args["variablesReference"].as_u64() {
+                Some(LOCALS_REF) => &frame.locals[..],
+                Some(STACK_REF) => &frame.stack[..],
+                Some(GLOBALS_REF) => &frame.globals[..],
+                _ => &[][..],
+            });
+        let vars = vars
+            .unwrap_or_default()
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "value": value, "variablesReference": 0 }))
+            .collect::<Vec<_>>();
+        json!({ "variables": vars })
+    }
+}
+
+/// Instantiate and run the launched program on the current thread.
+fn run(
+    engine: &Engine,
+    launch: &Launch,
+    output: &Output,
+    shared: &Arc<Shared>,
+    resume: Receiver<DebugAction>,
+    stdout: MemoryOutputPipe,
+) -> Result<()> {
+    let wasi = WasiCtxBuilder::new()
+        .args(&launch.args)
+        .stdout(stdout)
+        .inherit_stderr()
+        .build_p1();
+    let mut store = Store::new(engine, wasi);
+    store.set_epoch_deadline(1);
+    let mut linker = Linker::<WasiP1Ctx>::new(engine);
+    preview1::add_to_linker_sync(&mut linker, |t| t)?;
+
+    let mut applied = BTreeSet::new();
+    apply_breakpoints(store.as_context_mut(), &launch.module, shared, &mut applied)?;
+    store.single_step(launch.stop_on_entry)?;
+
+    let output = output.clone();
+    let shared2 = shared.clone();
+    let resume = Mutex::new(resume);
+    let mut entry = launch.stop_on_entry;
+    store.debug_handler(move |mut store, frame| {
+        shared2
+            .paused
+            .lock()
+            .unwrap()
+            .replace(snapshot(&mut store, frame));
+        let reason = if std::mem::take(&mut entry) {
+            "entry"
+        } else if shared2
+            .breakpoints
+            .lock()
+            .unwrap()
+            .contains(&frame.wasm_offset())
+        {
+            "breakpoint"
+        } else {
+            "step"
+        };
+        output.event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        )?;
+        let action = resume
+            .lock()
+            .unwrap()
+            .recv()
+            .context("debugger disconnected")?;
+        apply_breakpoints(store, frame.module(), &shared2, &mut applied)?;
+        Ok(action)
+    });
+
+    let instance = linker.instantiate(&mut store, &launch.module)?;
+    match &launch.invoke {
+        Some(name) => {
+            let func = instance
+                .get_func(&mut store, name)
+                .with_context(|| format!("no function named `{name}` was exported"))?;
+            let ty = func.ty(&store);
+            if ty.params().len() > 0 {
+                bail!("cannot invoke `{name}`: functions with parameters are not supported");
+            }
+            let mut results = vec![Val::I32(0); ty.results().len()];
+            func.call(&mut store, &[], &mut results)?;
+        }
+        None => {
+            if let Some(func) = instance.get_func(&mut store, "_start") {
+                func.typed::<(), ()>(&store)?.call(&mut store, ())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Synchronize the store's breakpoints with those requested by the client.
+///
+/// `applied` tracks the offsets that are currently set in the store.
+fn apply_breakpoints(
+    mut store: StoreContextMut<'_, WasiP1Ctx>,
+    module: &Module,
+    shared: &Shared,
+    applied: &mut BTreeSet<u32>,
+) -> Result<()> {
+    let requested = shared.breakpoints.lock().unwrap().clone();
+    for offset in applied.difference(&requested) {
+        store.remove_breakpoint(module, *offset);
+    }
+    for offset in requested.difference(applied) {
+        store.add_breakpoint(module, *offset)?;
+    }
+    *applied = requested;
+    Ok(())
+}
+
+/// Capture the paused frame's values for display while the Wasm thread
+/// waits to be resumed.
+fn snapshot(store: &mut StoreContextMut<'_, WasiP1Ctx>, frame: &DebugFrame) -> PausedFrame {
+    let named = |vals: &[Val]| {
+        vals.iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), format_val(v)))
+            .collect()
+    };
+    let mut globals = Vec::new();
+    for index in 0.. {
+        let Some(global) = frame.global(&mut *store, index) else {
+            break;
+        };
+        let value = global.get(&mut *store);
+        globals.push((index.to_string(), format_val(&value)));
+    }
+    PausedFrame {
+        func_index: frame.func_index(),
+        wasm_offset: frame.wasm_offset(),
+        locals: named(frame.locals()),
+        stack: named(frame.stack()),
+        globals,
+    }
+}
+
+fn format_val(val: &Val) -> String {
+    match val {
+        Val::I32(i) => format!("{i}"),
+        Val::I64(i) => format!("{i}"),
+        Val::F32(f) => format!("{}", f32::from_bits(*f)),
+        Val::F64(f) => format!("{}", f64::from_bits(*f)),
+        Val::V128(i) => format!("{:#034x}", i.as_u128()),
+        Val::ExternRef(None) => "<null externref>".to_string(),
+        Val::ExternRef(Some(_)) => "<externref>".to_string(),
+        Val::FuncRef(None) => "<null funcref>".to_string(),
+        Val::FuncRef(Some(_)) => "<funcref>".to_string(),
+        Val::AnyRef(None) => "<null anyref>".to_string(),
+        Val::AnyRef(Some(_)) => "<anyref>".to_string(),
+    }
+}
+
+/// Parse an instruction reference, either in hex with a `0x` prefix or in
+/// decimal.
+fn parse_offset(s: &str) -> Option<u32> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Read a single DAP message, returning `None` at the end of input.
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(len) = line.strip_prefix("Content-Length:") {
+            content_length = Some(len.trim().parse::<usize>()?);
+        }
+    }
+    let len = content_length.context("DAP message is missing a `Content-Length` header")?;
+    let mut body = vec![0; len];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+/// The sending half of the protocol, shared with the Wasm thread.
+#[derive(Clone)]
+struct Output {
+    inner: Arc<Mutex<(Box<dyn Write + Send>, u64)>>,
+}
+
+impl Output {
+    fn new(writer: Box<dyn Write + Send>) -> Output {
+        Output {
+            inner: Arc::new(Mutex::new((writer, 1))),
+        }
+    }
+
+    fn respond(&self, request: &Value, body: Result<Value>) -> Result<()> {
+        let mut msg = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": body.is_ok(),
+        });
+        match body {
+            Ok(body) => msg["body"] = body,
+            Err(e) => msg["message"] = json!(format!("{e:#}")),
+        }
+        self.send(msg)
+    }
+
+    fn event(&self, event: &str, body: Value) -> Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    fn send(&self, mut msg: Value) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let (writer, seq) = &mut *inner;
+        msg["seq"] = json!(*seq);
+        *seq += 1;
+        let body = serde_json::to_string(&msg)?;
+        write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -23,12 +23,15 @@ use wasmtime_wasi_nn::wit::WasiNnView;
 #[cfg(feature = "wasi-threads")]
 use wasmtime_wasi_threads::WasiThreadsCtx;
 
+#[cfg(feature = "component-model")]
+use wasmtime::component::wasm_wave::{untyped::UntypedFuncCall, wasm::DisplayValue};
+
 #[cfg(feature = "wasi-config")]
-use wasmtime_wasi_config::{WasiConfig, WasiConfigVariables};
+use wasmtime_wasi_config::{ConfigProvider, WasiConfig};
 #[cfg(feature = "wasi-http")]
 use wasmtime_wasi_http::WasiHttpCtx;
 #[cfg(feature = "wasi-keyvalue")]
-use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder};
+use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx};
 
 fn parse_preloads(s: &str) -> Result<(String, PathBuf)> {
     let parts: Vec<&str> = s.splitn(2, '=').collect();
@@ -46,6 +49,12 @@ pub struct RunCommand {
     pub run: RunCommon,
 
     /// The name of the function to run
+    ///
+    /// For components this is a call of an exported function with its
+    /// arguments in the WebAssembly Value Encoding (WAVE), such as
+    /// `--invoke 'pkg:iface/iface#func(1, "abc", {x: 2})'`. Functions of
+    /// exported instances are named by the instance's name and the function's
+    /// separated by `#`. The results are printed in WAVE as well.
     #[arg(long, value_name = "FUNCTION")]
     pub invoke: Option<String>,
 
@@ -70,8 +79,9 @@ pub struct RunCommand {
     /// The WebAssembly module to run and arguments to pass to it.
     ///
     /// Arguments passed to the wasm module will be configured as WASI CLI
-    /// arguments unless the `--invoke` CLI argument is passed in which case
-    /// arguments will be interpreted as arguments to the function specified.
+    /// arguments unless the `--invoke` CLI argument is passed for a core
+    /// module in which case arguments will be interpreted as arguments to the
+    /// function specified.
     #[arg(value_name = "WASM", trailing_var_arg = true, required = true)]
     pub module_and_args: Vec<OsString>,
 }
@@ -279,14 +289,15 @@ impl RunCommand {
     fn setup_epoch_handler(
         &self,
         store: &mut Store<Host>,
+        main: &RunTarget,
         modules: Vec<(String, Module)>,
     ) -> Result<Box<dyn FnOnce(&mut Store<Host>)>> {
         if let Some(Profile::Guest { path, interval }) = &self.run.profile {
             #[cfg(feature = "profiling")]
-            return Ok(self.setup_guest_profiler(store, modules, path, *interval));
+            return Ok(self.setup_guest_profiler(store, main, modules, path, *interval));
             #[cfg(not(feature = "profiling"))]
             {
-                let _ = (modules, path, interval);
+                let _ = (main, modules, path, interval);
                 bail!("support for profiling disabled at compile time");
             }
         }
@@ -307,6 +318,7 @@ impl RunCommand {
     fn setup_guest_profiler(
         &self,
         store: &mut Store<Host>,
+        main: &RunTarget,
         modules: Vec<(String, Module)>,
         path: &str,
         interval: std::time::Duration,
@@ -314,8 +326,14 @@ impl RunCommand {
         use wasmtime::{AsContext, GuestProfiler, StoreContext, StoreContextMut, UpdateDeadline};
 
         let module_name = self.module_and_args[0].to_str().unwrap_or("<main module>");
-        store.data_mut().guest_profiler =
-            Some(Arc::new(GuestProfiler::new(module_name, interval, modules)));
+        let profiler = match main {
+            RunTarget::Core(_) => GuestProfiler::new(module_name, interval, modules),
+            #[cfg(feature = "component-model")]
+            RunTarget::Component(component) => {
+                GuestProfiler::new_component(module_name, interval, component.clone(), modules)
+            }
+        };
+        store.data_mut().guest_profiler = Some(Arc::new(profiler));
 
         fn sample(
             mut store: StoreContextMut<Host>,
@@ -364,18 +382,30 @@ impl RunCommand {
         });
 
         let path = path.to_string();
+        let pprof = Profile::is_pprof_path(&path);
         return Box::new(move |store| {
             let profiler = Arc::try_unwrap(store.data_mut().guest_profiler.take().unwrap())
                 .expect("profiling doesn't support threads yet");
             if let Err(e) = std::fs::File::create(&path)
                 .map_err(anyhow::Error::new)
-                .and_then(|output| profiler.finish(std::io::BufWriter::new(output)))
+                .and_then(|output| {
+                    let output = std::io::BufWriter::new(output);
+                    if pprof {
+                        profiler.finish_pprof(output)
+                    } else {
+                        profiler.finish(output)
+                    }
+                })
             {
                 eprintln!("failed writing profile at {path}: {e:#}");
             } else {
                 eprintln!();
                 eprintln!("Profile written to: {path}");
-                eprintln!("View this profile at https://profiler.firefox.com/.");
+                if pprof {
+                    eprintln!("View this profile with `go tool pprof {path}`.");
+                } else {
+                    eprintln!("View this profile at https://profiler.firefox.com/.");
+                }
             }
         });
     }
@@ -411,7 +441,7 @@ impl RunCommand {
             }
         }
 
-        let finish_epoch_handler = self.setup_epoch_handler(store, modules)?;
+        let finish_epoch_handler = self.setup_epoch_handler(store, module, modules)?;
 
         let result = match linker {
             CliLinker::Core(linker) => {
@@ -453,31 +483,31 @@ impl RunCommand {
             }
             #[cfg(feature = "component-model")]
             CliLinker::Component(linker) => {
-                if self.invoke.is_some() {
-                    bail!("using `--invoke` with components is not supported");
-                }
-
                 let component = module.unwrap_component();
 
-                let command = wasmtime_wasi::bindings::Command::instantiate_async(
-                    &mut *store,
-                    component,
-                    linker,
-                )
-                .await?;
-                let result = command
-                    .wasi_cli_run()
-                    .call_run(&mut *store)
-                    .await
-                    .context("failed to invoke `run` function")
-                    .map_err(|e| self.handle_core_dump(&mut *store, e));
-
-                // Translate the `Result<(),()>` produced by wasm into a feigned
-                // explicit exit here with status 1 if `Err(())` is returned.
-                result.and_then(|wasm_result| match wasm_result {
-                    Ok(()) => Ok(()),
-                    Err(()) => Err(wasmtime_wasi::I32Exit(1).into()),
-                })
+                if let Some(call) = &self.invoke {
+                    self.invoke_component(store, component, linker, call).await
+                } else {
+                    let command = wasmtime_wasi::bindings::Command::instantiate_async(
+                        &mut *store,
+                        component,
+                        linker,
+                    )
+                    .await?;
+                    let result = command
+                        .wasi_cli_run()
+                        .call_run(&mut *store)
+                        .await
+                        .context("failed to invoke `run` function")
+                        .map_err(|e| self.handle_core_dump(&mut *store, e));
+
+                    // Translate the `Result<(),()>` produced by wasm into a feigned
+                    // explicit exit here with status 1 if `Err(())` is returned.
+                    result.and_then(|wasm_result| match wasm_result {
+                        Ok(()) => Ok(()),
+                        Err(()) => Err(wasmtime_wasi::I32Exit(1).into()),
+                    })
+                }
             }
         };
         finish_epoch_handler(store);
@@ -565,6 +595,74 @@ impl RunCommand {
         Ok(())
     }
 
+    #[cfg(feature = "component-model")]
+    async fn invoke_component(
+        &self,
+        store: &mut Store<Host>,
+        component: &wasmtime::component::Component,
+        linker: &mut wasmtime::component::Linker<Host>,
+        call: &str,
+    ) -> Result<()> {
+        // WAVE function names are plain labels, so split off the name of an
+        // exported instance before parsing the call.
+        let (instance_name, call) = match call.find('(').map(|paren| &call[..paren]) {
+            Some(name) => match name.rfind('#') {
+                Some(hash) => (Some(&call[..hash]), &call[hash + 1..]),
+                None => (None, call),
+            },
+            None => (None, call),
+        };
+        let call =
+            UntypedFuncCall::parse(call).map_err(|e| anyhow!("failed to parse `{call}`: {e}"))?;
+        let name = match instance_name {
+            Some(instance_name) => format!("{instance_name}#{}", call.name()),
+            None => call.name().to_string(),
+        };
+
+        let instance = linker
+            .instantiate_async(&mut *store, component)
+            .await
+            .context(format!(
+                "failed to instantiate {:?}",
+                self.module_and_args[0]
+            ))?;
+
+        // `pkg:iface/iface#func` is the function `func` of the exported
+        // instance `pkg:iface/iface`.
+        let export = match instance_name {
+            Some(instance_name) => {
+                let instance_export = instance
+                    .get_export(&mut *store, None, instance_name)
+                    .ok_or_else(|| anyhow!("no instance export named `{instance_name}` found"))?;
+                instance.get_export(&mut *store, Some(&instance_export), call.name())
+            }
+            None => instance.get_export(&mut *store, None, call.name()),
+        };
+        let func = export
+            .and_then(|export| instance.get_func(&mut *store, &export))
+            .ok_or_else(|| anyhow!("no func export named `{name}` found"))?;
+
+        let params = func.params(&*store);
+        let args = call
+            .to_wasm_params(&params)
+            .map_err(|e| anyhow!("failed to parse arguments for `{name}`: {e}"))?;
+        let mut results = vec![wasmtime::component::Val::Bool(false); func.results(&*store).len()];
+        let invoke_res = func
+            .call_async(&mut *store, &args, &mut results)
+            .await
+            .with_context(|| format!("failed to invoke `{name}`"));
+        if let Err(err) = invoke_res {
+            return Err(self.handle_core_dump(&mut *store, err));
+        }
+        func.post_return_async(&mut *store).await?;
+
+        for result in &results {
+            println!("{}", DisplayValue(result));
+        }
+
+        Ok(())
+    }
+
     #[cfg(feature = "coredump")]
     fn handle_core_dump(&self, store: &mut Store<Host>, err: Error) -> Error {
         let coredump_path = match &self.run.common.debug.coredump {
@@ -707,19 +805,10 @@ impl RunCommand {
                         bail!("Cannot enable wasi-config for core wasm modules");
                     }
                     CliLinker::Component(linker) => {
-                        let vars = WasiConfigVariables::from_iter(
-                            self.run
-                                .common
-                                .wasi
-                                .config_var
-                                .iter()
-                                .map(|v| (v.key.clone(), v.value.clone())),
-                        );
-
                         wasmtime_wasi_config::add_to_linker(linker, |h| {
-                            WasiConfig::new(Arc::get_mut(h.wasi_config.as_mut().unwrap()).unwrap())
+                            WasiConfig::new(&**h.wasi_config.as_ref().unwrap())
                         })?;
-                        store.data_mut().wasi_config = Some(Arc::new(vars));
+                        store.data_mut().wasi_config = Some(self.run.wasi_config_provider()?);
                     }
                 }
             }
@@ -737,16 +826,7 @@ impl RunCommand {
                         bail!("Cannot enable wasi-keyvalue for core wasm modules");
                     }
                     CliLinker::Component(linker) => {
-                        let ctx = WasiKeyValueCtxBuilder::new()
-                            .in_memory_data(
-                                self.run
-                                    .common
-                                    .wasi
-                                    .keyvalue_in_memory_data
-                                    .iter()
-                                    .map(|v| (v.key.clone(), v.value.clone())),
-                            )
-                            .build();
+                        let ctx = self.run.wasi_keyvalue_ctx()?;
 
                         wasmtime_wasi_keyvalue::add_to_linker(linker, |h| {
                             let preview2_ctx =
@@ -910,7 +990,7 @@ struct Host {
     guest_profiler: Option<Arc<wasmtime::GuestProfiler>>,
 
     #[cfg(feature = "wasi-config")]
-    wasi_config: Option<Arc<WasiConfigVariables>>,
+    wasi_config: Option<Arc<dyn ConfigProvider>>,
     #[cfg(feature = "wasi-keyvalue")]
     wasi_keyvalue: Option<Arc<WasiKeyValueCtx>>,
 }
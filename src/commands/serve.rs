@@ -1,7 +1,12 @@
 use crate::common::{Profile, RunCommon, RunTarget};
 use anyhow::{anyhow, bail, Result};
 use clap::Parser;
+use http_body_util::BodyExt;
+use std::future::Future;
 use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::{
     path::PathBuf,
     sync::{
@@ -9,8 +14,13 @@ use std::{
         Arc,
     },
 };
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+#[cfg(not(any(target_arch = "riscv64", target_arch = "s390x")))]
+use tokio_rustls::TlsAcceptor;
 use wasmtime::component::Linker;
-use wasmtime::{Config, Engine, Memory, MemoryType, Store, StoreLimits};
+use wasmtime::{Config, Engine, Memory, MemoryType, ResourceLimiter, Store, StoreLimits};
+use wasmtime_cli_flags::opt::WasmtimeOptionValue;
 use wasmtime_wasi::{StreamError, StreamResult, WasiCtx, WasiCtxBuilder, WasiView};
 use wasmtime_wasi_http::bindings::http::types::Scheme;
 use wasmtime_wasi_http::bindings::ProxyPre;
@@ -18,24 +28,28 @@ use wasmtime_wasi_http::io::TokioIo;
 use wasmtime_wasi_http::{body::HyperOutgoingBody, WasiHttpCtx, WasiHttpView};
 
 #[cfg(feature = "wasi-config")]
-use wasmtime_wasi_config::{WasiConfig, WasiConfigVariables};
+use wasmtime_wasi_config::{ConfigProvider, WasiConfig};
 #[cfg(feature = "wasi-keyvalue")]
-use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder};
+use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx};
 #[cfg(feature = "wasi-nn")]
 use wasmtime_wasi_nn::wit::WasiNnCtx;
 
+mod metrics;
+
+use self::metrics::Metrics;
+
 struct Host {
     table: wasmtime::component::ResourceTable,
     ctx: WasiCtx,
     http: WasiHttpCtx,
 
-    limits: StoreLimits,
+    limits: HostLimits,
 
     #[cfg(feature = "wasi-nn")]
     nn: Option<WasiNnCtx>,
 
     #[cfg(feature = "wasi-config")]
-    wasi_config: Option<WasiConfigVariables>,
+    wasi_config: Option<Arc<dyn ConfigProvider>>,
 
     #[cfg(feature = "wasi-keyvalue")]
     wasi_keyvalue: Option<WasiKeyValueCtx>,
@@ -61,6 +75,60 @@ impl WasiHttpView for Host {
     }
 }
 
+/// The resource limits for a request's store, which additionally track the
+/// high-water mark of the linear memory used by the request.
+#[derive(Default)]
+struct HostLimits {
+    limits: StoreLimits,
+    memory_high_water: usize,
+}
+
+impl ResourceLimiter for HostLimits {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool> {
+        let allowed = self.limits.memory_growing(current, desired, maximum)?;
+        if allowed {
+            // Memories are never shrunk, so the total size of all memories
+            // only grows over the lifetime of the store.
+            self.memory_high_water += desired - current;
+        }
+        Ok(allowed)
+    }
+
+    fn memory_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        self.limits.memory_grow_failed(error)
+    }
+
+    fn table_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool> {
+        self.limits.table_growing(current, desired, maximum)
+    }
+
+    fn table_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        self.limits.table_grow_failed(error)
+    }
+
+    fn instances(&self) -> usize {
+        self.limits.instances()
+    }
+
+    fn tables(&self) -> usize {
+        self.limits.tables()
+    }
+
+    fn memories(&self) -> usize {
+        self.limits.memories()
+    }
+}
+
 const DEFAULT_ADDR: std::net::SocketAddr = std::net::SocketAddr::new(
     std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0)),
     8080,
@@ -76,11 +144,65 @@ pub struct ServeCommand {
     #[arg(long = "addr", value_name = "SOCKADDR", default_value_t = DEFAULT_ADDR )]
     addr: SocketAddr,
 
+    /// Path to a PEM-encoded certificate chain to serve HTTPS with.
+    ///
+    /// Requires `--tls-key`. When TLS is enabled both HTTP/1.1 and HTTP/2 are
+    /// offered to clients and negotiated with ALPN.
+    #[arg(long = "tls-cert", value_name = "PATH", requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// Path to the PEM-encoded private key for the certificate in `--tls-cert`.
+    #[arg(long = "tls-key", value_name = "PATH", requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Maximum number of pending connections in the listening socket's
+    /// backlog.
+    #[arg(long = "backlog", value_name = "N", default_value_t = 100)]
+    backlog: u32,
+
+    /// Maximum number of requests which are handled concurrently.
+    ///
+    /// Requests received while this many are already in flight wait for one of
+    /// them to finish, subject to `--max-queued-requests`.
+    #[arg(long = "max-concurrent-requests", value_name = "N")]
+    max_concurrent_requests: Option<usize>,
+
+    /// Maximum number of requests which may wait for `--max-concurrent-requests`
+    /// to allow them to be handled.
+    ///
+    /// Requests received while the queue is full are immediately rejected with
+    /// a 503 Service Unavailable response. By default the queue is unbounded.
+    #[arg(
+        long = "max-queued-requests",
+        value_name = "N",
+        requires = "max_concurrent_requests"
+    )]
+    max_queued_requests: Option<usize>,
+
+    /// Wall-clock time limit for handling each request, such as `30s` or
+    /// `500ms`.
+    ///
+    /// Unlike `-W timeout`, which interrupts wasm execution, this also covers
+    /// time the guest spends waiting on I/O. Requests which haven't produced a
+    /// response by the deadline receive a 504 Gateway Timeout response, and
+    /// the guest's handler is cancelled once the deadline passes regardless.
+    #[arg(long = "request-timeout", value_name = "DURATION", value_parser = parse_duration)]
+    request_timeout: Option<Duration>,
+
+    /// Socket address to serve metrics about requests and the pooling
+    /// allocator on, in the Prometheus text format at `/metrics`.
+    #[arg(long = "metrics-addr", value_name = "SOCKADDR")]
+    metrics_addr: Option<SocketAddr>,
+
     /// The WebAssembly component to run.
     #[arg(value_name = "WASM", required = true)]
     component: PathBuf,
 }
 
+fn parse_duration(s: &str) -> Result<Duration> {
+    Duration::parse(Some(s))
+}
+
 impl ServeCommand {
     /// Start a server to run the given wasi-http proxy component
     pub fn execute(mut self) -> Result<()> {
@@ -117,19 +239,7 @@ impl ServeCommand {
             .enable_io()
             .build()?;
 
-        runtime.block_on(async move {
-            tokio::select! {
-                _ = tokio::signal::ctrl_c() => {
-                    Ok::<_, anyhow::Error>(())
-                }
-
-                res = self.serve() => {
-                    res
-                }
-            }
-        })?;
-
-        Ok(())
+        runtime.block_on(self.serve())
     }
 
     fn new_store(&self, engine: &Engine, req_id: u64) -> Result<Store<Host>> {
@@ -148,12 +258,13 @@ impl ServeCommand {
             Output::Stderr,
         ));
 
+        #[allow(unused_mut)]
         let mut host = Host {
             table: wasmtime::component::ResourceTable::new(),
             ctx: builder.build(),
             http: WasiHttpCtx::new(),
 
-            limits: StoreLimits::default(),
+            limits: HostLimits::default(),
 
             #[cfg(feature = "wasi-nn")]
             nn: None,
@@ -179,45 +290,13 @@ impl ServeCommand {
             }
         }
 
-        if self.run.common.wasi.config == Some(true) {
-            #[cfg(feature = "wasi-config")]
-            {
-                let vars = WasiConfigVariables::from_iter(
-                    self.run
-                        .common
-                        .wasi
-                        .config_var
-                        .iter()
-                        .map(|v| (v.key.clone(), v.value.clone())),
-                );
-                host.wasi_config.replace(vars);
-            }
-        }
-
-        if self.run.common.wasi.keyvalue == Some(true) {
-            #[cfg(feature = "wasi-keyvalue")]
-            {
-                let ctx = WasiKeyValueCtxBuilder::new()
-                    .in_memory_data(
-                        self.run
-                            .common
-                            .wasi
-                            .keyvalue_in_memory_data
-                            .iter()
-                            .map(|v| (v.key.clone(), v.value.clone())),
-                    )
-                    .build();
-                host.wasi_keyvalue.replace(ctx);
-            }
-        }
-
         let mut store = Store::new(engine, host);
 
         if self.run.common.wasm.timeout.is_some() {
             store.set_epoch_deadline(u64::from(EPOCH_PRECISION) + 1);
         }
 
-        store.data_mut().limits = self.run.store_limits();
+        store.data_mut().limits.limits = self.run.store_limits();
         store.limiter(|t| &mut t.limits);
 
         // If fuel has been configured, we want to add the configured
@@ -283,7 +362,7 @@ impl ServeCommand {
             #[cfg(feature = "wasi-config")]
             {
                 wasmtime_wasi_config::add_to_linker(linker, |h| {
-                    WasiConfig::from(h.wasi_config.as_ref().unwrap())
+                    WasiConfig::new(&**h.wasi_config.as_ref().unwrap())
                 })?;
             }
         }
@@ -313,8 +392,6 @@ impl ServeCommand {
     }
 
     async fn serve(mut self) -> Result<()> {
-        use hyper::server::conn::http1;
-
         let mut config = self
             .run
             .common
@@ -350,7 +427,23 @@ impl ServeCommand {
         let instance = linker.instantiate_pre(&component)?;
         let instance = ProxyPre::new(instance)?;
 
-        let socket = match &self.addr {
+        let addr = self.addr;
+        let timeout = self.run.common.wasm.timeout;
+        let backlog = self.backlog;
+        let tls = self.tls_acceptor()?;
+
+        // Every task serving a connection or running a guest holds onto a
+        // receiver of this channel, which is used to notify them of a pending
+        // shutdown. Shutdown is complete once all receivers are dropped.
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let metrics_addr = self.metrics_addr;
+        let handler = ProxyHandler::new(self, engine.clone(), instance, shutdown_rx)?;
+
+        if let Some(addr) = metrics_addr {
+            metrics::serve(addr, handler.0.metrics.clone()).await?;
+        }
+
+        let socket = match &addr {
             SocketAddr::V4(_) => tokio::net::TcpSocket::new_v4()?,
             SocketAddr::V6(_) => tokio::net::TcpSocket::new_v6()?,
         };
@@ -363,12 +456,17 @@ impl ServeCommand {
         // this is conditionally set based on the platform (and deviates from
         // Tokio's default from always-on).
         socket.set_reuseaddr(!cfg!(windows))?;
-        socket.bind(self.addr)?;
-        let listener = socket.listen(100)?;
+        socket.bind(addr)?;
+        let listener = socket.listen(backlog)?;
+        let shutdown = shutdown_signal()?;
 
-        eprintln!("Serving HTTP on http://{}/", listener.local_addr()?);
+        if tls.is_some() {
+            eprintln!("Serving HTTPS on https://{}/", listener.local_addr()?);
+        } else {
+            eprintln!("Serving HTTP on http://{}/", listener.local_addr()?);
+        }
 
-        let _epoch_thread = if let Some(timeout) = self.run.common.wasm.timeout {
+        let _epoch_thread = if let Some(timeout) = timeout {
             Some(EpochThread::spawn(
                 timeout / EPOCH_PRECISION,
                 engine.clone(),
@@ -377,27 +475,282 @@ impl ServeCommand {
             None
         };
 
-        log::info!("Listening on {}", self.addr);
-
-        let handler = ProxyHandler::new(self, engine, instance);
+        log::info!("Listening on {addr}");
 
+        tokio::pin!(shutdown);
         loop {
-            let (stream, _) = listener.accept().await?;
-            let stream = TokioIo::new(stream);
+            let (stream, _) = tokio::select! {
+                res = listener.accept() => res?,
+                _ = &mut shutdown => break,
+            };
             let h = handler.clone();
-            tokio::task::spawn(async {
-                if let Err(e) = http1::Builder::new()
-                    .keep_alive(true)
-                    .serve_connection(
-                        stream,
-                        hyper::service::service_fn(move |req| handle_request(h.clone(), req)),
-                    )
-                    .await
-                {
+            let tls = tls.clone();
+            tokio::task::spawn(async move {
+                if let Err(e) = serve_connection(stream, tls, h).await {
                     eprintln!("error: {e:?}");
                 }
             });
         }
+
+        // Stop accepting new connections and ask all existing ones to close
+        // once their in-flight requests are complete, then wait for that to
+        // happen. Another signal skips waiting.
+        drop(listener);
+        drop(handler);
+        log::info!("Shutting down, waiting for in-flight requests to complete");
+        let force = shutdown_signal()?;
+        shutdown_tx.send_replace(true);
+        tokio::select! {
+            _ = shutdown_tx.closed() => {}
+            _ = force => {
+                log::warn!("Shutting down without waiting for in-flight requests");
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the certificate and key configured with `--tls-cert` and
+    /// `--tls-key`, if any, into an acceptor for incoming connections.
+    fn tls_acceptor(&self) -> Result<Option<TlsAcceptor>> {
+        let (cert, key) = match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => (cert, key),
+            (None, None) => return Ok(None),
+            _ => bail!("`--tls-cert` and `--tls-key` must be specified together"),
+        };
+
+        #[cfg(any(target_arch = "riscv64", target_arch = "s390x"))]
+        {
+            let _ = (cert, key);
+            bail!("TLS is not supported on this architecture");
+        }
+
+        #[cfg(not(any(target_arch = "riscv64", target_arch = "s390x")))]
+        {
+            use anyhow::Context as _;
+            use std::io::BufReader;
+
+            let open = |path: &std::path::Path| {
+                std::fs::File::open(path)
+                    .map(BufReader::new)
+                    .with_context(|| format!("failed to read `{}`", path.display()))
+            };
+
+            let certs = rustls_pemfile::certs(&mut open(cert)?)
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("invalid PEM file `{}`", cert.display()))?;
+            if certs.is_empty() {
+                bail!("no certificates found in `{}`", cert.display());
+            }
+
+            let key = rustls_pemfile::private_key(&mut open(key)?)
+                .with_context(|| format!("invalid PEM file `{}`", key.display()))?
+                .ok_or_else(|| anyhow!("no private key found in `{}`", key.display()))?;
+
+            let mut config = rustls::ServerConfig::builder()
+                .with_no_client_auth()
+                .with_single_cert(certs, key)
+                .context("invalid TLS certificate or key")?;
+            config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+            Ok(Some(TlsAcceptor::from(Arc::new(config))))
+        }
+    }
+}
+
+/// Registers handlers for the signals which trigger a graceful shutdown of the
+/// server, returning a future which resolves when one is received.
+fn shutdown_signal() -> Result<impl Future<Output = ()>> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut terminate = signal(SignalKind::terminate())?;
+        Ok(async move {
+            tokio::select! {
+                _ = interrupt.recv() => {}
+                _ = terminate.recv() => {}
+            }
+        })
+    }
+
+    #[cfg(not(unix))]
+    {
+        Ok(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+    }
+}
+
+/// Stand-in for the acceptor on platforms where TLS isn't supported, which can
+/// never be constructed.
+#[cfg(any(target_arch = "riscv64", target_arch = "s390x"))]
+#[derive(Clone)]
+enum TlsAcceptor {}
+
+/// The connection preface sent by HTTP/2 clients, which is used to detect
+/// clients speaking HTTP/2 with prior knowledge (h2c) on plaintext
+/// connections.
+const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// Serves all requests made on the connection `stream`, performing a TLS
+/// handshake first if `tls` is provided.
+async fn serve_connection(
+    stream: tokio::net::TcpStream,
+    tls: Option<TlsAcceptor>,
+    handler: ProxyHandler,
+) -> Result<()> {
+    #[cfg(not(any(target_arch = "riscv64", target_arch = "s390x")))]
+    if let Some(tls) = tls {
+        let mut shutdown = handler.0.shutdown.clone();
+        let stream = tokio::select! {
+            res = tls.accept(stream) => res?,
+            _ = shutdown.wait_for(|s| *s) => return Ok(()),
+        };
+        let http2 = match stream.get_ref().1.alpn_protocol() {
+            Some(b"h2") => Some(true),
+            Some(_) => Some(false),
+            None => None,
+        };
+        return serve_http(stream, http2, handler).await;
+    }
+    #[cfg(any(target_arch = "riscv64", target_arch = "s390x"))]
+    if let Some(tls) = tls {
+        match tls {}
+    }
+
+    serve_http(stream, None, handler).await
+}
+
+/// Serves HTTP over `stream`, using HTTP/2 if `http2` is `Some(true)` and
+/// HTTP/1.1 if it's `Some(false)`. If the protocol wasn't negotiated
+/// up front then HTTP/2 is only used if the client starts by sending the
+/// HTTP/2 connection preface.
+async fn serve_http<T>(mut stream: T, http2: Option<bool>, handler: ProxyHandler) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let mut shutdown = handler.0.shutdown.clone();
+    let (http2, prefix) = match http2 {
+        Some(http2) => (http2, Vec::new()),
+        None => {
+            let prefix = tokio::select! {
+                res = read_http2_preface(&mut stream) => res?,
+                _ = shutdown.wait_for(|s| *s) => return Ok(()),
+            };
+            (prefix == HTTP2_PREFACE, prefix)
+        }
+    };
+
+    let stream = TokioIo::new(Rewind {
+        prefix,
+        pos: 0,
+        inner: stream,
+    });
+    let service = hyper::service::service_fn(move |req| handle_request(handler.clone(), req));
+    if http2 {
+        let conn = hyper::server::conn::http2::Builder::new(TokioExecutor)
+            .serve_connection(stream, service);
+        tokio::pin!(conn);
+        tokio::select! {
+            res = conn.as_mut() => return Ok(res?),
+            _ = shutdown.wait_for(|s| *s) => conn.as_mut().graceful_shutdown(),
+        }
+        conn.await?;
+    } else {
+        let conn = hyper::server::conn::http1::Builder::new()
+            .keep_alive(true)
+            .serve_connection(stream, service);
+        tokio::pin!(conn);
+        tokio::select! {
+            res = conn.as_mut() => return Ok(res?),
+            _ = shutdown.wait_for(|s| *s) => conn.as_mut().graceful_shutdown(),
+        }
+        conn.await?;
+    }
+    Ok(())
+}
+
+/// Reads from `stream` for as long as its contents match the HTTP/2 connection
+/// preface, returning the bytes read.
+async fn read_http2_preface<T: AsyncRead + Unpin>(stream: &mut T) -> std::io::Result<Vec<u8>> {
+    let mut prefix = Vec::with_capacity(HTTP2_PREFACE.len());
+    while prefix.len() < HTTP2_PREFACE.len() && HTTP2_PREFACE.starts_with(&prefix) {
+        let mut buf = [0; HTTP2_PREFACE.len()];
+        let n = stream.read(&mut buf[prefix.len()..]).await?;
+        if n == 0 {
+            break;
+        }
+        prefix.extend_from_slice(&buf[prefix.len()..][..n]);
+    }
+    Ok(prefix)
+}
+
+/// A stream which yields the bytes in `prefix` before those of `inner`, used
+/// to put back the bytes read while detecting the protocol of a connection.
+struct Rewind<T> {
+    prefix: Vec<u8>,
+    pos: usize,
+    inner: T,
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Rewind<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        if this.pos < this.prefix.len() {
+            let n = buf.remaining().min(this.prefix.len() - this.pos);
+            buf.put_slice(&this.prefix[this.pos..][..n]);
+            this.pos += n;
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut this.inner).poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Rewind<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Executor used by HTTP/2 connections to spawn the tasks for each stream.
+#[derive(Clone, Copy)]
+struct TokioExecutor;
+
+impl<F> hyper::rt::Executor<F> for TokioExecutor
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, fut: F) {
+        tokio::task::spawn(fut);
     }
 }
 
@@ -444,36 +797,137 @@ struct ProxyHandlerInner {
     engine: Engine,
     instance_pre: ProxyPre<Host>,
     next_id: AtomicU64,
+
+    /// Permits for handling requests when `--max-concurrent-requests` is set.
+    request_permits: Option<Arc<Semaphore>>,
+
+    /// Slots for requests waiting on `request_permits`, when
+    /// `--max-queued-requests` is set.
+    queue_slots: Option<Semaphore>,
+
+    /// Set to `true` when the server is shutting down.
+    shutdown: watch::Receiver<bool>,
+
+    metrics: Arc<Metrics>,
+
+    /// The key-value store is shared between all requests, so it is created
+    /// once up front rather than in `ServeCommand::new_store`.
+    #[cfg(feature = "wasi-keyvalue")]
+    wasi_keyvalue: Option<WasiKeyValueCtx>,
+
+    /// Likewise the config provider is shared so that a config file is only
+    /// reloaded when it changes, not parsed again for every request.
+    #[cfg(feature = "wasi-config")]
+    wasi_config: Option<Arc<dyn ConfigProvider>>,
 }
 
 impl ProxyHandlerInner {
     fn next_req_id(&self) -> u64 {
         self.next_id.fetch_add(1, Ordering::Relaxed)
     }
+
+    /// Waits for capacity to handle another request with respect to
+    /// `--max-concurrent-requests`.
+    ///
+    /// Returns `Err` if the request should be rejected because the queue of
+    /// waiting requests is already full, and `Ok(None)` if there's no limit.
+    async fn acquire_request_permit(&self) -> Result<Option<OwnedSemaphorePermit>, ()> {
+        let permits = match &self.request_permits {
+            Some(permits) => permits,
+            None => return Ok(None),
+        };
+        if let Ok(permit) = permits.clone().try_acquire_owned() {
+            return Ok(Some(permit));
+        }
+        let _slot = match &self.queue_slots {
+            Some(slots) => Some(slots.try_acquire().map_err(|_| ())?),
+            None => None,
+        };
+        // The semaphore is never closed so acquiring can't fail.
+        Ok(Some(permits.clone().acquire_owned().await.unwrap()))
+    }
+
+    fn new_store(&self, req_id: u64) -> Result<Store<Host>> {
+        #[allow(unused_mut)]
+        let mut store = self.cmd.new_store(&self.engine, req_id)?;
+        #[cfg(feature = "wasi-keyvalue")]
+        {
+            store.data_mut().wasi_keyvalue = self.wasi_keyvalue.clone();
+        }
+        #[cfg(feature = "wasi-config")]
+        {
+            store.data_mut().wasi_config = self.wasi_config.clone();
+        }
+        Ok(store)
+    }
 }
 
 #[derive(Clone)]
 struct ProxyHandler(Arc<ProxyHandlerInner>);
 
 impl ProxyHandler {
-    fn new(cmd: ServeCommand, engine: Engine, instance_pre: ProxyPre<Host>) -> Self {
-        Self(Arc::new(ProxyHandlerInner {
+    fn new(
+        cmd: ServeCommand,
+        engine: Engine,
+        instance_pre: ProxyPre<Host>,
+        shutdown: watch::Receiver<bool>,
+    ) -> Result<Self> {
+        #[cfg(feature = "wasi-keyvalue")]
+        let wasi_keyvalue = match cmd.run.common.wasi.keyvalue {
+            Some(true) => Some(cmd.run.wasi_keyvalue_ctx()?),
+            _ => None,
+        };
+        #[cfg(feature = "wasi-config")]
+        let wasi_config = match cmd.run.common.wasi.config {
+            Some(true) => Some(cmd.run.wasi_config_provider()?),
+            _ => None,
+        };
+        let request_permits = cmd
+            .max_concurrent_requests
+            .map(|n| Arc::new(Semaphore::new(n)));
+        let queue_slots = cmd.max_queued_requests.map(Semaphore::new);
+        let metrics = Arc::new(Metrics::new(&engine));
+        Ok(Self(Arc::new(ProxyHandlerInner {
             cmd,
             engine,
             instance_pre,
             next_id: AtomicU64::from(0),
-        }))
+            request_permits,
+            queue_slots,
+            shutdown,
+            metrics,
+            #[cfg(feature = "wasi-keyvalue")]
+            wasi_keyvalue,
+            #[cfg(feature = "wasi-config")]
+            wasi_config,
+        })))
     }
 }
 
 type Request = hyper::Request<hyper::body::Incoming>;
 
 async fn handle_request(
+    handler: ProxyHandler,
+    req: Request,
+) -> Result<hyper::Response<HyperOutgoingBody>> {
+    let start = Instant::now();
+    let metrics = handler.0.metrics.clone();
+    let result = proxy_request(handler, req).await;
+    metrics.record_request(result.as_ref().ok().map(|r| r.status()), start.elapsed());
+    result
+}
+
+async fn proxy_request(
     ProxyHandler(inner): ProxyHandler,
     req: Request,
 ) -> Result<hyper::Response<HyperOutgoingBody>> {
     let (sender, receiver) = tokio::sync::oneshot::channel();
 
+    let deadline = inner
+        .cmd
+        .request_timeout
+        .map(|timeout| tokio::time::Instant::now() + timeout);
+
     let req_id = inner.next_req_id();
 
     log::info!(
@@ -482,18 +936,49 @@ async fn handle_request(
         req.uri()
     );
 
-    let mut store = inner.cmd.new_store(&inner.engine, req_id)?;
+    let permit = match with_deadline(deadline, inner.acquire_request_permit()).await {
+        Ok(Ok(permit)) => permit,
+        Ok(Err(())) => {
+            log::warn!("Request {req_id} rejected: too many requests are queued");
+            return Ok(status_response(http::StatusCode::SERVICE_UNAVAILABLE));
+        }
+        Err(_) => {
+            log::warn!("Request {req_id} timed out waiting to be handled");
+            return Ok(status_response(http::StatusCode::GATEWAY_TIMEOUT));
+        }
+    };
+
+    let mut store = inner.new_store(req_id)?;
 
-    let req = store.data_mut().new_incoming_request(Scheme::Http, req)?;
+    let scheme = if inner.cmd.tls_cert.is_some() {
+        Scheme::Https
+    } else {
+        Scheme::Http
+    };
+    let req = store.data_mut().new_incoming_request(scheme, req)?;
     let out = store.data_mut().new_response_outparam(sender)?;
+    let instantiate_start = Instant::now();
     let proxy = inner.instance_pre.instantiate_async(&mut store).await?;
-
+    inner
+        .metrics
+        .record_instantiation(instantiate_start.elapsed());
+
+    // Keep the server from shutting down, and the request counted against
+    // `--max-concurrent-requests`, until the guest is done.
+    let shutdown = inner.shutdown.clone();
+    let metrics = inner.metrics.clone();
     let task = tokio::task::spawn(async move {
-        if let Err(e) = proxy
+        let _shutdown = shutdown;
+        let _permit = permit;
+        let handle = proxy
             .wasi_http_incoming_handler()
-            .call_handle(store, req, out)
+            .call_handle(&mut store, req, out);
+        let result = with_deadline(deadline, handle)
             .await
-        {
+            .unwrap_or_else(|elapsed| Err(elapsed.into()));
+        metrics.record_memory(store.data().limits.memory_high_water);
+        if let Err(e) = result {
+            metrics.record_error(&e);
             log::error!("[{req_id}] :: {:#?}", e);
             return Err(e);
         }
@@ -501,7 +986,15 @@ async fn handle_request(
         Ok(())
     });
 
-    match receiver.await {
+    let result = match with_deadline(deadline, receiver).await {
+        Ok(result) => result,
+        Err(_) => {
+            log::warn!("Request {req_id} timed out");
+            return Ok(status_response(http::StatusCode::GATEWAY_TIMEOUT));
+        }
+    };
+
+    match result {
         Ok(Ok(resp)) => Ok(resp),
         Ok(Err(e)) => Err(e.into()),
         Err(_) => {
@@ -516,11 +1009,37 @@ async fn handle_request(
                 Ok(r) => r.expect_err("if the receiver has an error, the task must have failed"),
                 Err(e) => e.into(),
             };
+            if e.is::<tokio::time::error::Elapsed>() {
+                log::warn!("Request {req_id} timed out");
+                return Ok(status_response(http::StatusCode::GATEWAY_TIMEOUT));
+            }
             bail!("guest never invoked `response-outparam::set` method: {e:?}")
         }
     }
 }
 
+/// Runs `future` to completion, or until `deadline` if one is provided.
+async fn with_deadline<F: Future>(
+    deadline: Option<tokio::time::Instant>,
+    future: F,
+) -> Result<F::Output, tokio::time::error::Elapsed> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, future).await,
+        None => Ok(future.await),
+    }
+}
+
+/// Creates an empty response with the given `status`, used for requests which
+/// the server fails on behalf of the guest.
+fn status_response(status: http::StatusCode) -> hyper::Response<HyperOutgoingBody> {
+    let body = http_body_util::Empty::<bytes::Bytes>::new()
+        .map_err(|never| match never {})
+        .boxed();
+    let mut response = hyper::Response::new(body);
+    *response.status_mut() = status;
+    response
+}
+
 #[derive(Clone)]
 enum Output {
     Stdout,
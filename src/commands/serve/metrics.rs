@@ -0,0 +1,333 @@
+//! Metrics collected by `wasmtime serve` and the `--metrics-addr` listener
+//! which reports them in the Prometheus text exposition format.
+
+use anyhow::Result;
+use bytes::Bytes;
+use http_body_util::Full;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wasmtime::{Engine, Trap};
+use wasmtime_wasi_http::io::TokioIo;
+
+/// Buckets, in seconds, for the time taken to respond to a request.
+const REQUEST_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Buckets, in seconds, for the time taken to instantiate the component.
+const INSTANTIATION_DURATION_BUCKETS: &[f64] = &[
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1,
+];
+
+/// Buckets, in bytes, for the linear memory used by a request.
+const MEMORY_BUCKETS: &[f64] = &[
+    65536.0,
+    262144.0,
+    1048576.0,
+    4194304.0,
+    16777216.0,
+    67108864.0,
+    268435456.0,
+    1073741824.0,
+    4294967296.0,
+];
+
+/// Metrics shared by all requests handled by a server.
+pub struct Metrics {
+    /// Time taken to respond to requests, keyed by the response's status code
+    /// or `error` if no response could be produced.
+    requests: Mutex<BTreeMap<String, Histogram>>,
+    instantiations: Mutex<Histogram>,
+    memory: Mutex<Histogram>,
+    /// Number of requests which trapped, keyed by the kind of trap.
+    traps: Mutex<BTreeMap<String, u64>>,
+    #[cfg(feature = "pooling-allocator")]
+    pooling: Option<wasmtime::PoolingAllocatorMetrics>,
+}
+
+impl Metrics {
+    #[cfg_attr(not(feature = "pooling-allocator"), allow(unused_variables))]
+    pub fn new(engine: &Engine) -> Metrics {
+        Metrics {
+            requests: Mutex::new(BTreeMap::new()),
+            instantiations: Mutex::new(Histogram::new(INSTANTIATION_DURATION_BUCKETS)),
+            memory: Mutex::new(Histogram::new(MEMORY_BUCKETS)),
+            traps: Mutex::new(BTreeMap::new()),
+            #[cfg(feature = "pooling-allocator")]
+            pooling: engine.pooling_allocator_metrics(),
+        }
+    }
+
+    /// Records that a request was responded to with `status` after `duration`,
+    /// where `None` means that no response could be produced.
+    pub fn record_request(&self, status: Option<http::StatusCode>, duration: Duration) {
+        let status = match status {
+            Some(status) => status.as_str().to_string(),
+            None => "error".to_string(),
+        };
+        self.requests
+            .lock()
+            .unwrap()
+            .entry(status)
+            .or_insert_with(|| Histogram::new(REQUEST_DURATION_BUCKETS))
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Records that instantiating the component for a request took `duration`.
+    pub fn record_instantiation(&self, duration: Duration) {
+        self.instantiations
+            .lock()
+            .unwrap()
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Records the high-water mark of the linear memory used by a request once
+    /// it's complete.
+    pub fn record_memory(&self, bytes: usize) {
+        self.memory.lock().unwrap().observe(bytes as f64);
+    }
+
+    /// Records that handling a request failed with `error`, counting it if it
+    /// was caused by a trap.
+    pub fn record_error(&self, error: &anyhow::Error) {
+        if let Some(trap) = error.downcast_ref::<Trap>() {
+            *self
+                .traps
+                .lock()
+                .unwrap()
+                .entry(format!("{trap:?}"))
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let requests = self.requests.lock().unwrap();
+        header(
+            &mut out,
+            "wasmtime_serve_requests_total",
+            "counter",
+            "Number of requests responded to, by status code.",
+        );
+        for (status, histogram) in requests.iter() {
+            writeln!(
+                out,
+                "wasmtime_serve_requests_total{{status=\"{status}\"}} {}",
+                histogram.count
+            )
+            .unwrap();
+        }
+        header(
+            &mut out,
+            "wasmtime_serve_request_duration_seconds",
+            "histogram",
+            "Time taken to respond to requests, by status code.",
+        );
+        for (status, histogram) in requests.iter() {
+            histogram.render(
+                &mut out,
+                "wasmtime_serve_request_duration_seconds",
+                &format!("status=\"{status}\""),
+            );
+        }
+        drop(requests);
+
+        header(
+            &mut out,
+            "wasmtime_serve_instantiation_duration_seconds",
+            "histogram",
+            "Time taken to instantiate the component for each request.",
+        );
+        self.instantiations.lock().unwrap().render(
+            &mut out,
+            "wasmtime_serve_instantiation_duration_seconds",
+            "",
+        );
+
+        header(
+            &mut out,
+            "wasmtime_serve_request_memory_high_water_bytes",
+            "histogram",
+            "Maximum size of the linear memory used by each request.",
+        );
+        self.memory.lock().unwrap().render(
+            &mut out,
+            "wasmtime_serve_request_memory_high_water_bytes",
+            "",
+        );
+
+        let traps = self.traps.lock().unwrap();
+        header(
+            &mut out,
+            "wasmtime_serve_traps_total",
+            "counter",
+            "Number of requests which trapped, by kind of trap.",
+        );
+        for (kind, count) in traps.iter() {
+            writeln!(out, "wasmtime_serve_traps_total{{kind=\"{kind}\"}} {count}").unwrap();
+        }
+        header(
+            &mut out,
+            "wasmtime_serve_interrupts_total",
+            "counter",
+            "Number of requests interrupted by running out of fuel or by reaching \
+             their epoch deadline.",
+        );
+        for (kind, trap) in [("fuel", Trap::OutOfFuel), ("epoch", Trap::Interrupt)] {
+            let count = traps.get(&format!("{trap:?}")).copied().unwrap_or(0);
+            writeln!(
+                out,
+                "wasmtime_serve_interrupts_total{{kind=\"{kind}\"}} {count}"
+            )
+            .unwrap();
+        }
+        drop(traps);
+
+        #[cfg(feature = "pooling-allocator")]
+        if let Some(pooling) = &self.pooling {
+            header(
+                &mut out,
+                "wasmtime_pooling_allocator_slots_in_use",
+                "gauge",
+                "Number of slots in use in the pooling allocator, by kind of slot.",
+            );
+            for (kind, count) in [
+                ("core_instances", pooling.core_instances()),
+                ("component_instances", pooling.component_instances()),
+                ("memories", pooling.memories() as u64),
+                ("tables", pooling.tables() as u64),
+            ] {
+                writeln!(
+                    out,
+                    "wasmtime_pooling_allocator_slots_in_use{{kind=\"{kind}\"}} {count}"
+                )
+                .unwrap();
+            }
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, ty: &str, help: &str) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} {ty}").unwrap();
+}
+
+/// A Prometheus histogram with fixed bucket boundaries.
+struct Histogram {
+    bounds: &'static [f64],
+    /// Number of observations in each bucket, not cumulative.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Histogram {
+        Histogram {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        if let Some(i) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.counts[i] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Renders this histogram's series for the metric `name`, with `labels`
+    /// added to each sample.
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count;
+            writeln!(
+                out,
+                "{name}_bucket{{{labels}{sep}le=\"{bound}\"}} {cumulative}"
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "{name}_bucket{{{labels}{sep}le=\"+Inf\"}} {}",
+            self.count
+        )
+        .unwrap();
+        if labels.is_empty() {
+            writeln!(out, "{name}_sum {}", self.sum).unwrap();
+            writeln!(out, "{name}_count {}", self.count).unwrap();
+        } else {
+            writeln!(out, "{name}_sum{{{labels}}} {}", self.sum).unwrap();
+            writeln!(out, "{name}_count{{{labels}}} {}", self.count).unwrap();
+        }
+    }
+}
+
+/// Serves `metrics` over HTTP/1.1 at `/metrics` on the listener bound to
+/// `addr`, until the process exits.
+pub async fn serve(addr: SocketAddr, metrics: Arc<Metrics>) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    log::info!(
+        "Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+
+    tokio::task::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    // Errors here are typically transient resource exhaustion
+                    // (e.g. `EMFILE`), so back off briefly rather than
+                    // spinning on the listener.
+                    log::warn!("failed to accept metrics connection: {e}");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            let metrics = metrics.clone();
+            tokio::task::spawn(async move {
+                let service = hyper::service::service_fn(move |req| {
+                    let response = render_response(&metrics, &req);
+                    async move { Ok::<_, Infallible>(response) }
+                });
+                if let Err(e) = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    log::warn!("error serving metrics: {e:?}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn render_response<B>(metrics: &Metrics, req: &hyper::Request<B>) -> hyper::Response<Full<Bytes>> {
+    let mut response = hyper::Response::new(Full::default());
+    if req.uri().path() != "/metrics" {
+        *response.status_mut() = http::StatusCode::NOT_FOUND;
+    } else if req.method() != http::Method::GET {
+        *response.status_mut() = http::StatusCode::METHOD_NOT_ALLOWED;
+    } else {
+        *response.body_mut() = Full::new(Bytes::from(metrics.render()));
+        response.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static("text/plain; version=0.0.4"),
+        );
+    }
+    response
+}
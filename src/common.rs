@@ -66,7 +66,9 @@ pub struct RunCommon {
     ///
     /// where `path` is where to write the profile and `interval` is the
     /// duration between samples. When used with `--wasm-timeout` the timeout
-    /// will be rounded up to the nearest multiple of this interval.
+    /// will be rounded up to the nearest multiple of this interval. If `path`
+    /// ends in `.pb` or `.pprof` the profile is written in the pprof format
+    /// instead, which can be viewed with `go tool pprof`.
     #[arg(
         long,
         value_name = "STRATEGY",
@@ -334,6 +336,62 @@ impl RunCommon {
         Ok(listeners)
     }
 
+    /// Creates the context for the WASI key-value API from the `-S keyvalue-*`
+    /// options.
+    #[cfg(feature = "wasi-keyvalue")]
+    pub fn wasi_keyvalue_ctx(&self) -> Result<wasmtime_wasi_keyvalue::WasiKeyValueCtx> {
+        let wasi = &self.common.wasi;
+        let builder = wasmtime_wasi_keyvalue::WasiKeyValueCtxBuilder::new();
+        let builder = match &wasi.keyvalue_file {
+            Some(path) => {
+                if !wasi.keyvalue_in_memory_data.is_empty() {
+                    bail!("`-S keyvalue-in-memory-data` cannot be used with `-S keyvalue-file`");
+                }
+                let store = wasmtime_wasi_keyvalue::FileStore::open(path)?;
+                builder.store(std::sync::Arc::new(store))
+            }
+            None => builder.in_memory_data(
+                wasi.keyvalue_in_memory_data
+                    .iter()
+                    .map(|v| (v.key.clone(), v.value.clone())),
+            ),
+        };
+        Ok(builder.build())
+    }
+
+    /// Creates the provider for the WASI config API from the `-S config-*`
+    /// options.
+    #[cfg(feature = "wasi-config")]
+    pub fn wasi_config_provider(
+        &self,
+    ) -> Result<std::sync::Arc<dyn wasmtime_wasi_config::ConfigProvider>> {
+        use std::sync::Arc;
+        use wasmtime_wasi_config::{
+            EnvConfigProvider, FileConfigProvider, LayeredConfigProvider, WasiConfigVariables,
+        };
+
+        let wasi = &self.common.wasi;
+        let vars = WasiConfigVariables::from_iter(
+            wasi.config_var
+                .iter()
+                .map(|v| (v.key.clone(), v.value.clone())),
+        );
+        if wasi.config_file.is_none() && wasi.config_env_prefix.is_none() {
+            return Ok(Arc::new(vars));
+        }
+        let mut layered = LayeredConfigProvider::new();
+        if let Some(path) = &wasi.config_file {
+            let file = Arc::new(FileConfigProvider::open(path)?);
+            file.watch(std::time::Duration::from_secs(1));
+            layered.push(file);
+        }
+        if let Some(prefix) = &wasi.config_env_prefix {
+            layered.push(Arc::new(EnvConfigProvider::new(prefix.clone())));
+        }
+        layered.push(Arc::new(vars));
+        Ok(Arc::new(layered))
+    }
+
     pub fn compute_wasi_features(&self) -> LinkOptions {
         let mut options = LinkOptions::default();
         options.cli_exit_with_code(self.common.wasi.cli_exit_with_code.unwrap_or(false));
@@ -371,4 +429,11 @@ impl Profile {
             _ => bail!("unknown profiling strategy: {s}"),
         }
     }
+
+    /// Returns whether a guest profile written to `path` should use the pprof
+    /// format rather than the Firefox profiler's JSON format.
+    #[cfg(feature = "profiling")]
+    pub fn is_pprof_path(path: &str) -> bool {
+        path.ends_with(".pb") || path.ends_with(".pprof")
+    }
 }
@@ -847,6 +847,37 @@ fn run_basic_component() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn invoke_component() -> Result<()> {
+    let path = "tests/all/cli_tests/component-invoke.wat";
+    let invoke = |call: &str| run_wasmtime(&["run", "-Ccache=n", "--invoke", call, path]);
+
+    assert_eq!(invoke("add(1, 2)")?, "3\n");
+    assert_eq!(invoke(r#"len("h\u{e9}llo")"#)?, "6\n");
+    assert_eq!(invoke("local:demo/math#sum((3, 4))")?, "7\n");
+    assert_eq!(invoke("local:demo/math#maybe(5)")?, "some(5)\n");
+    assert_eq!(invoke("local:demo/math#maybe(0)")?, "none\n");
+
+    let output = run_wasmtime_for_output(&["run", "-Ccache=n", "--invoke", "add(1)", path], None)?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("missing required param(s)"),
+        "bad stderr: {stderr}"
+    );
+
+    let output = run_wasmtime_for_output(&["run", "-Ccache=n", "--invoke", "nope()", path], None)?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("no func export named `nope` found"),
+        "bad stderr: {stderr}"
+    );
+
+    Ok(())
+}
+
 #[test]
 #[cfg_attr(not(feature = "component-model"), ignore)]
 fn run_precompiled_component() -> Result<()> {
@@ -1107,6 +1138,214 @@ fn increase_stack_size() -> Result<()> {
     Ok(())
 }
 
+/// A client for a `wasmtime debug-adapter` child process.
+struct DebugAdapterClient {
+    child: std::process::Child,
+    stdin: Option<std::process::ChildStdin>,
+    stdout: std::io::BufReader<std::process::ChildStdout>,
+    seq: u64,
+}
+
+impl DebugAdapterClient {
+    fn spawn() -> Result<DebugAdapterClient> {
+        let mut child = get_wasmtime_command()?
+            .arg("debug-adapter")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        Ok(DebugAdapterClient {
+            stdin: child.stdin.take(),
+            stdout: std::io::BufReader::new(child.stdout.take().unwrap()),
+            child,
+            seq: 0,
+        })
+    }
+
+    fn send(&mut self, command: &str, arguments: serde_json::Value) -> Result<()> {
+        self.seq += 1;
+        let body = serde_json::json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        let stdin = self.stdin.as_mut().unwrap();
+        write!(stdin, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        stdin.flush()?;
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<serde_json::Value> {
+        use std::io::{BufRead, Read};
+
+        let mut len = None;
+        loop {
+            let mut line = String::new();
+            if self.stdout.read_line(&mut line)? == 0 {
+                bail!("debug adapter exited unexpectedly");
+            }
+            match line.trim_end() {
+                "" => break,
+                line => {
+                    if let Some(n) = line.strip_prefix("Content-Length:") {
+                        len = Some(n.trim().parse::<usize>()?);
+                    }
+                }
+            }
+        }
+        let mut body = vec![0; len.unwrap()];
+        self.stdout.read_exact(&mut body)?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Reads messages until one is the response to `command`, asserting that
+    /// any responses skipped along the way were successful.
+    fn expect_response(&mut self, command: &str) -> Result<serde_json::Value> {
+        self.expect(|msg| msg["type"] == "response" && msg["command"] == command)
+    }
+
+    /// Reads messages until one is an `event`, asserting that any responses
+    /// skipped along the way were successful.
+    fn expect_event(&mut self, event: &str) -> Result<serde_json::Value> {
+        self.expect(|msg| msg["type"] == "event" && msg["event"] == event)
+    }
+
+    fn expect(&mut self, pred: impl Fn(&serde_json::Value) -> bool) -> Result<serde_json::Value> {
+        loop {
+            let msg = self.recv()?;
+            if pred(&msg) {
+                return Ok(msg);
+            }
+            if msg["type"] == "response" {
+                assert_eq!(msg["success"], true, "request failed: {msg}");
+            }
+        }
+    }
+
+    /// Launches `wasm`, invoking its `run` export.
+    fn launch(&mut self, wasm: &[u8], stop_on_entry: bool) -> Result<NamedTempFile> {
+        let mut module = tempfile::Builder::new().suffix(".wasm").tempfile()?;
+        module.write_all(wasm)?;
+        self.send("initialize", serde_json::json!({ "adapterID": "wasmtime" }))?;
+        let msg = self.expect_response("initialize")?;
+        assert_eq!(msg["body"]["supportsInstructionBreakpoints"], true);
+        self.send(
+            "launch",
+            serde_json::json!({
+                "program": module.path().to_str().unwrap(),
+                "invoke": "run",
+                "stopOnEntry": stop_on_entry,
+            }),
+        )?;
+        self.expect_event("initialized")?;
+        Ok(module)
+    }
+
+    /// Disconnects and waits for the adapter to exit successfully.
+    fn disconnect(mut self) -> Result<()> {
+        self.send("disconnect", serde_json::json!({}))?;
+        self.expect_response("disconnect")?;
+        drop(self.stdin.take());
+        assert!(self.child.wait()?.success());
+        Ok(())
+    }
+}
+
+/// Drives `wasmtime debug-adapter` through a scripted session which stops at a
+/// breakpoint, inspects the paused frame, and continues to completion.
+#[test]
+fn debug_adapter_breakpoint() -> Result<()> {
+    use serde_json::json;
+
+    let wasm = wat::parse_str(
+        r#"
+            (module
+                (func (export "run")
+                    i32.const 5
+                    i32.const 6
+                    i32.add
+                    drop)
+            )
+        "#,
+    )?;
+    let mut offset = None;
+    for payload in wasmparser::Parser::new(0).parse_all(&wasm) {
+        if let wasmparser::Payload::CodeSectionEntry(body) = payload? {
+            for op in body.get_operators_reader()?.into_iter_with_offsets() {
+                if let (wasmparser::Operator::I32Add, pos) = op? {
+                    offset = Some(pos);
+                }
+            }
+        }
+    }
+    let offset = offset.unwrap();
+
+    let mut dap = DebugAdapterClient::spawn()?;
+    let _module = dap.launch(&wasm, false)?;
+    dap.send(
+        "setInstructionBreakpoints",
+        json!({ "breakpoints": [{ "instructionReference": format!("{offset:#x}") }] }),
+    )?;
+    let msg = dap.expect_response("setInstructionBreakpoints")?;
+    assert_eq!(msg["body"]["breakpoints"][0]["verified"], true);
+    dap.send("configurationDone", json!({}))?;
+
+    let msg = dap.expect_event("stopped")?;
+    assert_eq!(msg["body"]["reason"], "breakpoint");
+
+    dap.send("stackTrace", json!({ "threadId": 1 }))?;
+    let msg = dap.expect_response("stackTrace")?;
+    let frame = &msg["body"]["stackFrames"][0];
+    assert_eq!(frame["name"], "func[0]");
+    assert_eq!(frame["instructionPointerReference"], format!("{offset:#x}"));
+    dap.send("variables", json!({ "variablesReference": 2 }))?;
+    let msg = dap.expect_response("variables")?;
+    let stack = msg["body"]["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["value"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(stack, ["5", "6"]);
+
+    dap.send("continue", json!({ "threadId": 1 }))?;
+    let msg = dap.expect_event("exited")?;
+    assert_eq!(msg["body"]["exitCode"], 0);
+    dap.expect_event("terminated")?;
+
+    dap.disconnect()
+}
+
+/// Disconnecting from `wasmtime debug-adapter` interrupts a program that never
+/// pauses, and one that is paused.
+#[test]
+fn debug_adapter_disconnect() -> Result<()> {
+    use serde_json::json;
+
+    let wasm = wat::parse_str(
+        r#"
+            (module
+                (func (export "run")
+                    (loop br 0))
+            )
+        "#,
+    )?;
+
+    let mut dap = DebugAdapterClient::spawn()?;
+    let _module = dap.launch(&wasm, false)?;
+    dap.send("configurationDone", json!({}))?;
+    dap.expect_response("configurationDone")?;
+    dap.disconnect()?;
+
+    let mut dap = DebugAdapterClient::spawn()?;
+    let _module = dap.launch(&wasm, true)?;
+    dap.send("configurationDone", json!({}))?;
+    let msg = dap.expect_event("stopped")?;
+    assert_eq!(msg["body"]["reason"], "entry");
+    dap.disconnect()
+}
+
 mod test_programs {
     use super::{get_wasmtime_command, run_wasmtime};
     use anyhow::{bail, Context, Result};
@@ -1116,6 +1355,7 @@ mod test_programs {
     use std::net::SocketAddr;
     use std::process::{Child, Command, Stdio};
     use test_programs_artifacts::*;
+    use tokio::io::{AsyncRead, AsyncWrite};
     use tokio::net::TcpStream;
 
     macro_rules! assert_test_exists {
@@ -1916,6 +2156,346 @@ stderr [1] :: after empty
         Ok(())
     }
 
+    #[tokio::test]
+    async fn cli_serve_http2_prior_knowledge() -> Result<()> {
+        let server = WasmtimeServe::new(CLI_SERVE_ECHO_ENV_COMPONENT, |cmd| {
+            cmd.arg("--env=FOO=bar");
+            cmd.arg("-Scli");
+        })?;
+
+        // Clients which know up front that the server speaks HTTP/2 (h2c)
+        // are served over HTTP/2 on the plaintext port ...
+        let tcp = TcpStream::connect(&server.addr).await?;
+        let resp = send_http2_request(
+            tcp,
+            hyper::Request::builder()
+                .uri("http://localhost/")
+                .header("env", "FOO")
+                .body(String::new())
+                .context("failed to make request")?,
+        )
+        .await?;
+        assert!(resp.status().is_success());
+        assert_eq!(resp.version(), hyper::Version::HTTP_2);
+        assert_eq!(
+            resp.headers().get("env"),
+            Some(&HeaderValue::from_static("bar"))
+        );
+
+        // ... while HTTP/1.1 continues to work on the same port.
+        let resp = server
+            .send_request(
+                hyper::Request::builder()
+                    .uri("http://localhost/")
+                    .header("env", "FOO")
+                    .body(String::new())
+                    .context("failed to make request")?,
+            )
+            .await?;
+        assert!(resp.status().is_success());
+        assert_eq!(resp.version(), hyper::Version::HTTP_11);
+        assert_eq!(
+            resp.headers().get("env"),
+            Some(&HeaderValue::from_static("bar"))
+        );
+
+        server.finish()?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(not(any(target_arch = "riscv64", target_arch = "s390x")))]
+    async fn cli_serve_tls() -> Result<()> {
+        use rustls::pki_types::ServerName;
+        use std::sync::Arc;
+
+        // Generate a self-signed certificate for `localhost`.
+        let dir = tempfile::tempdir()?;
+        let cert = dir.path().join("cert.pem");
+        let key = dir.path().join("key.pem");
+        let rcgen::CertifiedKey {
+            cert: certificate,
+            key_pair,
+        } = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        std::fs::write(&cert, certificate.pem())?;
+        std::fs::write(&key, key_pair.serialize_pem())?;
+
+        let server = WasmtimeServe::new(CLI_SERVE_ECHO_ENV_COMPONENT, |cmd| {
+            cmd.arg("--env=FOO=bar");
+            cmd.arg("-Scli");
+            cmd.arg("--tls-cert").arg(&cert);
+            cmd.arg("--tls-key").arg(&key);
+        })?;
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(certificate.der().clone())?;
+
+        // Both HTTP/2 and HTTP/1.1 are negotiated with ALPN.
+        for (alpn, version) in [
+            (&b"h2"[..], hyper::Version::HTTP_2),
+            (&b"http/1.1"[..], hyper::Version::HTTP_11),
+        ] {
+            let mut config = rustls::ClientConfig::builder()
+                .with_root_certificates(roots.clone())
+                .with_no_client_auth();
+            config.alpn_protocols = vec![alpn.to_vec()];
+            let connector = tokio_rustls::TlsConnector::from(Arc::new(config));
+            let tcp = TcpStream::connect(&server.addr).await?;
+            let tls = connector
+                .connect(ServerName::try_from("localhost")?, tcp)
+                .await
+                .context("failed TLS handshake")?;
+            assert_eq!(tls.get_ref().1.alpn_protocol(), Some(alpn));
+
+            let req = hyper::Request::builder()
+                .uri("https://localhost/")
+                .header("env", "FOO")
+                .body(String::new())
+                .context("failed to make request")?;
+            let resp = if version == hyper::Version::HTTP_2 {
+                send_http2_request(tls, req).await?
+            } else {
+                send_http1_request(tls, req).await?
+            };
+            assert!(resp.status().is_success());
+            assert_eq!(resp.version(), version);
+            assert_eq!(
+                resp.headers().get("env"),
+                Some(&HeaderValue::from_static("bar"))
+            );
+        }
+
+        server.finish()?;
+        Ok(())
+    }
+
+    fn sleep_request(ms: u64) -> Result<http::Request<String>> {
+        hyper::Request::builder()
+            .uri("http://localhost/")
+            .header("sleep-ms", ms.to_string())
+            .body(String::new())
+            .context("failed to make request")
+    }
+
+    /// A request which `CLI_SERVE_SLEEP_COMPONENT` answers with response
+    /// headers right away, and with the end of the body after `ms`
+    /// milliseconds.
+    fn body_sleep_request(ms: u64) -> Result<http::Request<String>> {
+        hyper::Request::builder()
+            .uri("http://localhost/")
+            .header("body-sleep-ms", ms.to_string())
+            .body(String::new())
+            .context("failed to make request")
+    }
+
+    #[tokio::test]
+    async fn cli_serve_sleep() -> Result<()> {
+        let server = WasmtimeServe::new(CLI_SERVE_SLEEP_COMPONENT, |cmd| {
+            cmd.arg("-Scli");
+            cmd.arg("--request-timeout=500ms");
+        })?;
+
+        let resp = server.send_request(sleep_request(0)?).await?;
+        assert!(resp.status().is_success());
+
+        // Requests which take too long are answered by the server itself.
+        let resp = server.send_request(sleep_request(10_000)?).await?;
+        assert_eq!(resp.status(), hyper::StatusCode::GATEWAY_TIMEOUT);
+
+        server.finish()?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cli_serve_max_concurrent_requests() -> Result<()> {
+        let server = WasmtimeServe::new(CLI_SERVE_SLEEP_COMPONENT, |cmd| {
+            cmd.arg("-Scli");
+            cmd.arg("--max-concurrent-requests=1");
+            cmd.arg("--max-queued-requests=0");
+        })?;
+
+        // While one request is in flight there's no room for another. The
+        // response headers are only sent once the guest is handling the
+        // request.
+        let (mut send, conn_task) = server.start_requests().await?;
+        let slow = send.send_request(body_sleep_request(1000)?).await?;
+        let fast = server.send_request(sleep_request(0)?).await?;
+        assert_eq!(fast.status(), hyper::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(slow.status().is_success());
+        slow.into_body().collect().await?;
+        drop(send);
+        conn_task.await??;
+
+        // Once it's done requests are handled again.
+        let resp = server.send_request(sleep_request(0)?).await?;
+        assert!(resp.status().is_success());
+
+        server.finish()?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn cli_serve_graceful_shutdown() -> Result<()> {
+        let mut server = WasmtimeServe::new(CLI_SERVE_SLEEP_COMPONENT, |cmd| {
+            cmd.arg("-Scli");
+        })?;
+        let pid = server.child.as_ref().unwrap().id();
+
+        // Ask the server to shut down while a request is in flight, which
+        // should still complete successfully. The response headers are only
+        // sent once the guest is handling the request.
+        let (mut send, conn_task) = server.start_requests().await?;
+        let slow = send.send_request(body_sleep_request(1000)?).await?;
+        unsafe {
+            assert_eq!(libc::kill(pid as libc::pid_t, libc::SIGTERM), 0);
+        }
+        assert!(slow.status().is_success());
+        slow.into_body().collect().await?;
+        drop(send);
+        conn_task.await??;
+
+        // And afterwards the server exits on its own.
+        let status = server.child.as_mut().unwrap().wait()?;
+        assert!(status.success());
+
+        server.finish()?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cli_serve_metrics() -> Result<()> {
+        // Find a free port for metrics to be served on.
+        let metrics_addr = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        let server = WasmtimeServe::new(CLI_SERVE_SLEEP_COMPONENT, |cmd| {
+            cmd.arg("-Scli");
+            cmd.arg(format!("--metrics-addr={metrics_addr}"));
+            cmd.arg("--max-concurrent-requests=1");
+            cmd.arg("--max-queued-requests=0");
+        })?;
+
+        let (mut send, conn_task) = server.start_requests().await?;
+        let slow = send.send_request(body_sleep_request(500)?).await?;
+        let fast = server.send_request(sleep_request(0)?).await?;
+        assert_eq!(fast.status(), hyper::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(slow.status().is_success());
+        slow.into_body().collect().await?;
+        drop(send);
+        conn_task.await??;
+
+        let resp = send_http1_request(
+            TcpStream::connect(&metrics_addr).await?,
+            hyper::Request::builder()
+                .uri("/metrics")
+                .header("Host", "localhost")
+                .body(String::new())
+                .context("failed to make request")?,
+        )
+        .await?;
+        assert!(resp.status().is_success());
+        let metrics = resp.body();
+        for line in [
+            "wasmtime_serve_requests_total{status=\"200\"} 1",
+            "wasmtime_serve_requests_total{status=\"503\"} 1",
+            "wasmtime_serve_request_duration_seconds_count{status=\"200\"} 1",
+            "wasmtime_serve_instantiation_duration_seconds_count 1",
+            "wasmtime_serve_interrupts_total{kind=\"fuel\"} 0",
+            "wasmtime_serve_interrupts_total{kind=\"epoch\"} 0",
+        ] {
+            assert!(
+                metrics.lines().any(|l| l == line),
+                "missing `{line}` in:\n{metrics}"
+            );
+        }
+        assert!(metrics.contains("wasmtime_serve_request_memory_high_water_bytes_bucket"));
+
+        let resp = send_http1_request(
+            TcpStream::connect(&metrics_addr).await?,
+            hyper::Request::builder()
+                .uri("/")
+                .header("Host", "localhost")
+                .body(String::new())
+                .context("failed to make request")?,
+        )
+        .await?;
+        assert_eq!(resp.status(), hyper::StatusCode::NOT_FOUND);
+
+        server.finish()?;
+        Ok(())
+    }
+
+    /// Sends `req` over a new HTTP/1.1 connection on `stream`.
+    async fn send_http1_request<T>(
+        stream: T,
+        req: http::Request<String>,
+    ) -> Result<http::Response<String>>
+    where
+        T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut send, conn) =
+            hyper::client::conn::http1::handshake(wasmtime_wasi_http::io::TokioIo::new(stream))
+                .await
+                .context("failed http handshake")?;
+        let conn_task = tokio::task::spawn(conn);
+        let response = send
+            .send_request(req)
+            .await
+            .context("error sending request")?;
+        drop(send);
+        let response = collect_response(response).await?;
+        conn_task.await??;
+        Ok(response)
+    }
+
+    /// Sends `req` over a new HTTP/2 connection on `stream`.
+    async fn send_http2_request<T>(
+        stream: T,
+        req: http::Request<String>,
+    ) -> Result<http::Response<String>>
+    where
+        T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut send, conn) = hyper::client::conn::http2::handshake(
+            TokioExecutor,
+            wasmtime_wasi_http::io::TokioIo::new(stream),
+        )
+        .await
+        .context("failed http handshake")?;
+        let conn_task = tokio::task::spawn(conn);
+        let response = send
+            .send_request(req)
+            .await
+            .context("error sending request")?;
+        drop(send);
+        let response = collect_response(response).await?;
+        conn_task.await??;
+        Ok(response)
+    }
+
+    async fn collect_response(
+        response: http::Response<hyper::body::Incoming>,
+    ) -> Result<http::Response<String>> {
+        let (parts, body) = response.into_parts();
+        let body = body.collect().await.context("failed to read body")?;
+        assert!(body.trailers().is_none());
+        let body = std::str::from_utf8(&body.to_bytes())?.to_string();
+        Ok(http::Response::from_parts(parts, body))
+    }
+
+    /// Executor used by HTTP/2 client connections to spawn background tasks.
+    #[derive(Clone, Copy)]
+    struct TokioExecutor;
+
+    impl<F> hyper::rt::Executor<F> for TokioExecutor
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        fn execute(&self, fut: F) {
+            tokio::task::spawn(fut);
+        }
+    }
+
     #[test]
     fn cli_argv0() -> Result<()> {
         run_wasmtime(&["run", "--argv0=a", CLI_ARGV0, "a"])?;
@@ -1957,6 +2537,54 @@ stderr [1] :: after empty
         Ok(())
     }
 
+    #[test]
+    fn cli_config_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "hello = \"world\"\n")?;
+        let file = format!("-Sconfig-file={}", path.display());
+        run_wasmtime(&["run", "-Sconfig", &file, CONFIG_GET_COMPONENT])?;
+
+        // `config-var` takes precedence over the file.
+        std::fs::write(&path, "hello = \"there\"\n")?;
+        run_wasmtime(&[
+            "run",
+            "-Sconfig",
+            &file,
+            "-Sconfig-var=hello=world",
+            CONFIG_GET_COMPONENT,
+        ])?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cli_serve_config_file_reload() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"hello": "world"}"#)?;
+        let server = WasmtimeServe::new(CLI_SERVE_CONFIG_COMPONENT, |cmd| {
+            cmd.arg("-Scli");
+            cmd.arg("-Sconfig");
+            cmd.arg(format!("-Sconfig-file={}", path.display()));
+        })?;
+
+        for expected in ["world", "updated world"] {
+            std::fs::write(&path, format!(r#"{{"hello": "{expected}"}}"#))?;
+            let resp = server
+                .send_request(
+                    hyper::Request::builder()
+                        .uri("http://localhost/")
+                        .body(String::new())
+                        .context("failed to make request")?,
+                )
+                .await?;
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.body(), expected);
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn cli_serve_keyvalue() -> Result<()> {
         let server = WasmtimeServe::new(CLI_SERVE_KEYVALUE_COMPONENT, |cmd| {
@@ -1989,6 +2617,36 @@ stderr [1] :: after empty
         ])?;
         Ok(())
     }
+
+    #[test]
+    fn cli_keyvalue_file() -> Result<()> {
+        use wasmtime_wasi_keyvalue::{FileStore, KeyValueStore};
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("kv.log");
+        FileStore::open(&path)?
+            .open("")?
+            .set("atomics_key", b"5".to_vec())?;
+
+        let file = format!("-Skeyvalue-file={}", path.display());
+        run_wasmtime(&["run", "-Skeyvalue", &file, KEYVALUE_MAIN_COMPONENT])?;
+
+        // The guest's changes should have been persisted.
+        let bucket = FileStore::open(&path)?.open("")?;
+        assert_eq!(bucket.get("atomics_key")?, Some(b"6".to_vec()));
+        assert!(!bucket.exists("hello")?);
+
+        let err = run_wasmtime(&[
+            "run",
+            "-Skeyvalue",
+            &file,
+            "-Skeyvalue-in-memory-data=a=b",
+            KEYVALUE_MAIN_COMPONENT,
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("cannot be used with"), "{err:?}");
+        Ok(())
+    }
 }
 
 #[test]
@@ -0,0 +1,31 @@
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (func (export "add") (param i32 i32) (result i32)
+      (i32.add (local.get 0) (local.get 1)))
+    (func (export "maybe") (param i32) (result i32)
+      (i32.store8 (i32.const 8) (i32.ne (local.get 0) (i32.const 0)))
+      (i32.store (i32.const 12) (local.get 0))
+      i32.const 8)
+    (func (export "len") (param i32 i32) (result i32)
+      local.get 1)
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      i32.const 100)
+  )
+  (core instance $i (instantiate $m))
+
+  (func $add (param "a" u32) (param "b" u32) (result u32)
+    (canon lift (core func $i "add")))
+  (func $len (param "s" string) (result u32)
+    (canon lift (core func $i "len") (memory $i "memory") (realloc (func $i "realloc"))))
+  (func $sum (param "p" (tuple u32 u32)) (result u32)
+    (canon lift (core func $i "add")))
+  (func $maybe (param "x" u32) (result (option u32))
+    (canon lift (core func $i "maybe") (memory $i "memory")))
+
+  (export "add" (func $add))
+  (export "len" (func $len))
+  (instance (export "local:demo/math")
+    (export "sum" (func $sum))
+    (export "maybe" (func $maybe)))
+)
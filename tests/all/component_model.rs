@@ -18,7 +18,9 @@ mod macros;
 mod nested;
 mod post_return;
 mod resources;
+mod snapshot;
 mod strings;
+mod wave;
 
 #[test]
 #[cfg_attr(miri, ignore)]
@@ -1528,3 +1528,73 @@ fn resource_any_to_typed_handles_borrow() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn host_dynamic_resources() -> Result<()> {
+    let engine = super::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "t" (type $t (sub resource)))
+
+                (import "f" (func $f (param "a" (borrow $t))))
+
+                (core func $f (canon lower (func $f)))
+
+                (core module $m
+                    (import "" "f" (func $f (param i32)))
+
+                    (func (export "f") (param i32)
+                        (call $f (local.get 0))
+                    )
+                )
+                (core instance $i (instantiate $m
+                    (with "" (instance
+                        (export "f" (func $f))
+                    ))
+                ))
+
+                (func (export "f") (param "a" (own $t))
+                    (canon lift (core func $i "f")))
+                (export "t" (type $t))
+            )
+        "#,
+    )?;
+
+    assert!(ResourceType::host_dynamic(1) == ResourceType::host_dynamic(1));
+    assert!(ResourceType::host_dynamic(1) != ResourceType::host_dynamic(2));
+    assert!(ResourceType::host_dynamic(0) != ResourceType::host::<()>());
+
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .resource("t", ResourceType::host_dynamic(1), |_, _| Ok(()))?;
+    linker.root().func_new("f", |mut cx, args, _results| {
+        let Val::Resource(r) = &args[0] else {
+            panic!("expected a resource");
+        };
+        assert!(!r.owned());
+        assert_eq!(r.ty(), ResourceType::host_dynamic(1));
+        assert!(r.try_into_host_dynamic(&mut cx, 2).is_err());
+        assert_eq!(r.try_into_host_dynamic(&mut cx, 1)?, 100);
+        Ok(())
+    })?;
+    let i = linker.instantiate(&mut store, &c)?;
+    assert_eq!(
+        i.get_resource(&mut store, "t"),
+        Some(ResourceType::host_dynamic(1))
+    );
+    let f = i.get_func(&mut store, "f").unwrap();
+
+    let resource = ResourceAny::try_from_host_dynamic(&mut store, 1, 100, true)?;
+    f.call(&mut store, &[Val::Resource(resource)], &mut [])?;
+    f.post_return(&mut store)?;
+
+    // Host-owned handles can be turned back into their representation.
+    let resource = ResourceAny::try_from_host_dynamic(&mut store, 1, 7, true)?;
+    assert!(resource.owned());
+    assert_eq!(resource.try_into_host_dynamic(&mut store, 1)?, 7);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use wasmtime::component::*;
+use wasmtime::Store;
+
+const COUNTER: &str = r#"
+    (component
+        (core module $m
+            (global $g (mut i32) (i32.const 0))
+            (memory (export "memory") 1)
+            (func $start
+                (global.set $g (i32.add (global.get $g) (i32.const 100))))
+            (start $start)
+            (func (export "bump")
+                (global.set $g (i32.add (global.get $g) (i32.const 1))))
+            (func (export "get") (result i32) global.get $g)
+        )
+        (core instance $i (instantiate $m))
+
+        (core module $reader
+            (import "" "memory" (memory 1))
+            (func (export "write") (param i32)
+                (i32.store (i32.const 0) (local.get 0)))
+            (func (export "read") (result i32)
+                (i32.load (i32.const 0)))
+        )
+        (core instance $r (instantiate $reader
+            (with "" (instance (export "memory" (memory $i "memory"))))
+        ))
+
+        (func (export "bump") (canon lift (core func $i "bump")))
+        (func (export "get") (result u32) (canon lift (core func $i "get")))
+        (func (export "write") (param "x" u32) (canon lift (core func $r "write")))
+        (func (export "read") (result u32) (canon lift (core func $r "read")))
+    )
+"#;
+
+#[test]
+fn snapshot_restores_core_instances() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, COUNTER)?;
+    let pre = Linker::new(&engine).instantiate_pre(&component)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = pre.instantiate(&mut store)?;
+    let bump = instance.get_typed_func::<(), ()>(&mut store, "bump")?;
+    bump.call(&mut store, ())?;
+    bump.post_return(&mut store)?;
+    let write = instance.get_typed_func::<(u32,), ()>(&mut store, "write")?;
+    write.call(&mut store, (42,))?;
+    write.post_return(&mut store)?;
+
+    let snapshot = instance.snapshot(&mut store)?.serialize()?;
+    let snapshot = InstanceSnapshot::deserialize(&snapshot)?;
+
+    // The start function isn't run again so the counter is 101 and not 201.
+    let mut store = Store::new(&engine, ());
+    let instance = pre.instantiate_snapshot(&mut store, &snapshot)?;
+    let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+    assert_eq!(get.call(&mut store, ())?, (101,));
+    get.post_return(&mut store)?;
+    let read = instance.get_typed_func::<(), (u32,)>(&mut store, "read")?;
+    assert_eq!(read.call(&mut store, ())?, (42,));
+    read.post_return(&mut store)?;
+    Ok(())
+}
+
+#[test]
+fn snapshot_rejects_pending_post_return() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, COUNTER)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+
+    let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+    get.call(&mut store, ())?;
+    assert!(instance.snapshot(&mut store).is_err());
+    get.post_return(&mut store)?;
+    assert!(instance.snapshot(&mut store).is_ok());
+    Ok(())
+}
+
+#[test]
+fn snapshot_rejects_other_component() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, COUNTER)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let snapshot = instance.snapshot(&mut store)?;
+
+    // A component with the same number of core instances whose modules differ.
+    let other = Component::new(&engine, COUNTER.replace("i32.const 100", "i32.const 200"))?;
+    let pre = Linker::new(&engine).instantiate_pre(&other)?;
+    assert!(pre.instantiate_snapshot(&mut store, &snapshot).is_err());
+
+    let empty = Component::new(&engine, "(component)")?;
+    let pre = Linker::new(&engine).instantiate_pre(&empty)?;
+    assert!(pre.instantiate_snapshot(&mut store, &snapshot).is_err());
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use super::REALLOC_AND_FREE;
+use wasmtime::component::wasm_wave::{self, wasm::DisplayValue};
+use wasmtime::component::{Component, Linker, Type, Val};
+use wasmtime::{Engine, Result, Store};
+
+#[test]
+fn parse_and_display() -> Result<()> {
+    let engine = Engine::default();
+    let component = Component::new(
+        &engine,
+        format!(
+            r#"
+            (component
+                (type $rec (record (field "b" u8) (field "a" string)))
+                (export $rec' "rec" (type $rec))
+                (type $var (variant (case "empty") (case "full" $rec')))
+                (export $var' "var" (type $var))
+                (type $flags (flags "x" "y" "z"))
+                (export $flags' "flags" (type $flags))
+                (type $args (tuple
+                    $var'
+                    $flags'
+                    (list (option (result u32 (error char))))
+                ))
+                (export $args' "args" (type $args))
+
+                (core module $m
+                    (memory (export "memory") 1)
+                    (func (export "f") (param i32 i32 i32 i32 i32 i32 i32))
+
+                    {REALLOC_AND_FREE}
+                )
+                (core instance $i (instantiate $m))
+                (func (export "f") (param "x" $args')
+                    (canon lift
+                        (core func $i "f")
+                        (memory $i "memory")
+                        (realloc (func $i "realloc"))
+                    )
+                )
+            )
+        "#
+        ),
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "f").unwrap();
+    let ty = match &func.params(&store)[..] {
+        [ty @ Type::Tuple(_)] => ty.clone(),
+        params => panic!("unexpected params {params:?}"),
+    };
+
+    let val: Val = wasm_wave::from_str(
+        &ty,
+        r#"(full({a: "hi", b: 7}), {z, x}, [none, some(ok(3)), some(err('e'))])"#,
+    )?;
+    let record = Val::Record(vec![
+        ("b".to_string(), Val::U8(7)),
+        ("a".to_string(), Val::String("hi".to_string())),
+    ]);
+    assert_eq!(
+        val,
+        Val::Tuple(vec![
+            Val::Variant("full".to_string(), Some(Box::new(record))),
+            Val::Flags(vec!["x".to_string(), "z".to_string()]),
+            Val::List(vec![
+                Val::Option(None),
+                Val::Option(Some(Box::new(Val::Result(Ok(Some(Box::new(Val::U32(3)))))))),
+                Val::Option(Some(Box::new(Val::Result(Err(Some(Box::new(Val::Char(
+                    'e'
+                )))))))),
+            ]),
+        ])
+    );
+    assert_eq!(
+        DisplayValue(&val).to_string(),
+        r#"(full({b: 7, a: "hi"}), {x, z}, [none, some(ok(3)), some(err('e'))])"#
+    );
+
+    assert!(wasm_wave::from_str::<Val>(&ty, "(other, {}, [])").is_err());
+    assert!(wasm_wave::from_str::<Val>(&ty, "(empty, {w}, [])").is_err());
+    assert!(wasm_wave::from_str::<Val>(&ty, "(full({a: \"\"}), {}, [])").is_err());
+    Ok(())
+}
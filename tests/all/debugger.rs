@@ -0,0 +1,279 @@
+#![cfg(not(miri))]
+
+use std::sync::{Arc, Mutex};
+use wasmtime::*;
+
+const WAT: &str = r#"
+    (module
+        (global $g (mut i32) (i32.const 100))
+        (func (export "add") (param i32 i32) (result i32)
+            (local i64)
+            local.get 0
+            local.get 1
+            i32.add
+            global.get $g
+            i32.add)
+        (func (export "call_add") (result i32)
+            i32.const 1
+            i32.const 2
+            call 0)
+    )
+"#;
+
+/// A snapshot of a paused frame.
+#[derive(Debug, Clone)]
+struct Pause {
+    func_index: u32,
+    wasm_offset: u32,
+    locals: Vec<i64>,
+    stack: Vec<i64>,
+}
+
+fn debug_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.guest_debug(true);
+    Engine::new(&config)
+}
+
+fn ints(vals: &[Val]) -> Vec<i64> {
+    vals.iter()
+        .map(|v| match v {
+            Val::I32(i) => i64::from(*i),
+            Val::I64(i) => *i,
+            other => panic!("unexpected value {other:?}"),
+        })
+        .collect()
+}
+
+/// Record every pause in `store`, resuming with `action`.
+fn record_pauses(store: &mut Store<()>, action: DebugAction) -> Arc<Mutex<Vec<Pause>>> {
+    let pauses = Arc::new(Mutex::new(Vec::new()));
+    let recorded = pauses.clone();
+    store.debug_handler(move |_store, frame| {
+        recorded.lock().unwrap().push(Pause {
+            func_index: frame.func_index(),
+            wasm_offset: frame.wasm_offset(),
+            locals: ints(frame.locals()),
+            stack: ints(frame.stack()),
+        });
+        Ok(action)
+    });
+    pauses
+}
+
+#[test]
+fn single_step_through_function() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let pauses = record_pauses(&mut store, DebugAction::Step);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+
+    store.single_step(true)?;
+    assert_eq!(add.call(&mut store, (3, 4))?, 107);
+
+    let pauses = pauses.lock().unwrap();
+    let stacks = pauses.iter().map(|p| p.stack.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        stacks,
+        [
+            vec![],
+            vec![3],
+            vec![3, 4],
+            vec![7],
+            vec![7, 100],
+            vec![107],
+        ]
+    );
+    for pause in pauses.iter() {
+        assert_eq!(pause.func_index, 0);
+        assert_eq!(pause.locals, [3, 4, 0]);
+    }
+    let offsets = pauses.iter().map(|p| p.wasm_offset).collect::<Vec<_>>();
+    assert!(offsets.windows(2).all(|w| w[0] < w[1]), "{offsets:?}");
+    Ok(())
+}
+
+#[test]
+fn step_into_call() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let pauses = record_pauses(&mut store, DebugAction::Step);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let call_add = instance.get_typed_func::<(), i32>(&mut store, "call_add")?;
+
+    store.single_step(true)?;
+    assert_eq!(call_add.call(&mut store, ())?, 103);
+
+    let pauses = pauses.lock().unwrap();
+    let funcs = pauses.iter().map(|p| p.func_index).collect::<Vec<_>>();
+    assert_eq!(funcs[..4], [1, 1, 1, 0]);
+    assert_eq!(pauses[3].locals, [1, 2, 0]);
+    assert_eq!(*funcs.last().unwrap(), 1);
+    Ok(())
+}
+
+#[test]
+fn step_over_call() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let pauses = record_pauses(&mut store, DebugAction::StepOver);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let call_add = instance.get_typed_func::<(), i32>(&mut store, "call_add")?;
+
+    store.single_step(true)?;
+    assert_eq!(call_add.call(&mut store, ())?, 103);
+
+    let pauses = pauses.lock().unwrap();
+    let stacks = pauses.iter().map(|p| p.stack.clone()).collect::<Vec<_>>();
+    assert_eq!(stacks, [vec![], vec![1], vec![1, 2], vec![103]]);
+    assert!(pauses.iter().all(|p| p.func_index == 1));
+    Ok(())
+}
+
+#[test]
+fn step_out_of_call() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let pauses = Arc::new(Mutex::new(Vec::new()));
+    let recorded = pauses.clone();
+    store.debug_handler(move |_store, frame| {
+        recorded
+            .lock()
+            .unwrap()
+            .push((frame.func_index(), ints(frame.stack())));
+        // Step into `add`, and then out of it again.
+        Ok(if frame.func_index() == 0 {
+            DebugAction::StepOut
+        } else {
+            DebugAction::Step
+        })
+    });
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let call_add = instance.get_typed_func::<(), i32>(&mut store, "call_add")?;
+
+    store.single_step(true)?;
+    assert_eq!(call_add.call(&mut store, ())?, 103);
+
+    let pauses = pauses.lock().unwrap();
+    assert_eq!(
+        *pauses,
+        [
+            (1, vec![]),
+            (1, vec![1]),
+            (1, vec![1, 2]),
+            (0, vec![]),
+            (1, vec![103]),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn step_out_of_outermost_frame_continues() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let pauses = record_pauses(&mut store, DebugAction::StepOut);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let call_add = instance.get_typed_func::<(), i32>(&mut store, "call_add")?;
+
+    store.single_step(true)?;
+    assert_eq!(call_add.call(&mut store, ())?, 103);
+    assert_eq!(pauses.lock().unwrap().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn breakpoint_hit_and_continue() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, WAT)?;
+
+    // Discover the offset of the `i32.add` in `add` by single-stepping.
+    let offset = {
+        let mut store = Store::new(&engine, ());
+        let pauses = record_pauses(&mut store, DebugAction::Step);
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+        store.single_step(true)?;
+        add.call(&mut store, (1, 1))?;
+        let pauses = pauses.lock().unwrap();
+        pauses[2].wasm_offset
+    };
+
+    let mut store = Store::new(&engine, ());
+    let pauses = record_pauses(&mut store, DebugAction::Continue);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    store.add_breakpoint(&module, offset)?;
+
+    assert_eq!(add.call(&mut store, (5, 6))?, 111);
+    assert_eq!(add.call(&mut store, (7, 8))?, 115);
+    {
+        let pauses = pauses.lock().unwrap();
+        assert_eq!(pauses.len(), 2);
+        assert_eq!(pauses[0].wasm_offset, offset);
+        assert_eq!(pauses[0].stack, [5, 6]);
+        assert_eq!(pauses[1].stack, [7, 8]);
+    }
+
+    assert!(store.remove_breakpoint(&module, offset));
+    assert!(!store.remove_breakpoint(&module, offset));
+    add.call(&mut store, (1, 2))?;
+    assert_eq!(pauses.lock().unwrap().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn inspect_and_modify_globals() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    store.debug_handler(|mut store, frame| {
+        let global = frame.global(&mut store, 0).unwrap();
+        assert_eq!(global.get(&mut store).unwrap_i32(), 100);
+        global.set(&mut store, Val::I32(1000))?;
+        assert!(frame.global(&mut store, 1).is_none());
+        Ok(DebugAction::Continue)
+    });
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+
+    store.single_step(true)?;
+    assert_eq!(add.call(&mut store, (1, 2))?, 1003);
+    Ok(())
+}
+
+#[test]
+fn handler_error_traps() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    store.debug_handler(|_store, _frame| anyhow::bail!("stop here"));
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+
+    store.single_step(true)?;
+    let err = add.call(&mut store, (1, 2)).unwrap_err();
+    assert!(format!("{err:?}").contains("stop here"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn requires_guest_debug() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+
+    let err = store.add_breakpoint(&module, 0).unwrap_err();
+    assert!(
+        err.to_string().contains("guest debugging is not enabled"),
+        "{err}"
+    );
+    assert!(store.single_step(true).is_err());
+    Ok(())
+}
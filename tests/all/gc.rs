@@ -882,3 +882,138 @@ fn table_copy_doesnt_leak() -> Result<()> {
     assert!(flag.load(SeqCst));
     Ok(())
 }
+
+fn copying_collector_config() -> Config {
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_gc(true);
+    config.collector(Collector::Copying);
+    config
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn copying_collector_reclaims_cycles() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let engine = Engine::new(&copying_collector_config())?;
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $node (struct (field (mut (ref null $node)))))
+                (func (export "run") (param i32)
+                    (local $a (ref null $node))
+                    (local $b (ref null $node))
+                    (loop $loop
+                        (local.set $a (struct.new $node (ref.null $node)))
+                        (local.set $b (struct.new $node (local.get $a)))
+                        (struct.set $node 0 (local.get $a) (local.get $b))
+                        (br_if $loop (local.tee 0 (i32.sub (local.get 0) (i32.const 1))))
+                    )
+                )
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, ()>(&mut store, "run")?;
+
+    // Allocate many more cycles than fit in the GC heap at once.
+    run.call(&mut store, 100_000)?;
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn copying_collector_preserves_live_objects() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let test_engine = |engine: &Engine| -> Result<()> {
+        let mut store = Store::new(engine, ());
+        let module = Module::new(
+            engine,
+            r#"
+                (module
+                    (type $node (struct (field i32) (field (ref null $node))))
+                    (type $bytes (array (mut i8)))
+                    (global $list (mut (ref null $node)) (ref.null $node))
+                    (func (export "build") (param $n i32)
+                        (loop $loop
+                            (global.set $list
+                                (struct.new $node (local.get $n) (global.get $list)))
+                            (drop (struct.new $node (i32.const -1) (ref.null $node)))
+                            (drop (array.new $bytes (i32.const 0) (i32.const 64)))
+                            (br_if $loop
+                                (local.tee $n (i32.sub (local.get $n) (i32.const 1))))
+                        )
+                    )
+                    (func (export "sum") (result i32)
+                        (local $sum i32)
+                        (local $cur (ref null $node))
+                        (local.set $cur (global.get $list))
+                        (block $done
+                            (loop $loop
+                                (br_if $done (ref.is_null (local.get $cur)))
+                                (local.set $sum
+                                    (i32.add (local.get $sum)
+                                             (struct.get $node 0 (local.get $cur))))
+                                (local.set $cur (struct.get $node 1 (local.get $cur)))
+                                (br $loop)
+                            )
+                        )
+                        (local.get $sum)
+                    )
+                )
+            "#,
+        )?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let build = instance.get_typed_func::<i32, ()>(&mut store, "build")?;
+        let sum = instance.get_typed_func::<(), i32>(&mut store, "sum")?;
+
+        build.call(&mut store, 10_000)?;
+        store.gc();
+        assert_eq!(sum.call(&mut store, ())?, 10_000 * 10_001 / 2);
+        Ok(())
+    };
+
+    test_engine(&Engine::new(&copying_collector_config())?)?;
+
+    if !skip_pooling_allocator_tests() {
+        let mut config = copying_collector_config();
+        config.allocation_strategy(InstanceAllocationStrategy::pooling());
+        test_engine(&Engine::new(&config)?)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn copying_collector_moves_rooted_externrefs() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let engine = Engine::new(&copying_collector_config())?;
+    let mut store = Store::new(&engine, ());
+    let dropped = Arc::new(AtomicBool::new(false));
+
+    {
+        let mut scope = RootScope::new(&mut store);
+        ExternRef::new(&mut scope, SetFlagOnDrop(dropped.clone()))?;
+    }
+    let live = ExternRef::new_manually_rooted(&mut store, 42_u32)?;
+
+    store.gc();
+    assert!(
+        dropped.load(SeqCst),
+        "unrooted externref's host data dropped"
+    );
+
+    let data = live
+        .data(&store)?
+        .and_then(|data| data.downcast_ref::<u32>())
+        .copied();
+    assert_eq!(data, Some(42));
+
+    live.unroot(&mut store);
+    Ok(())
+}
@@ -0,0 +1,136 @@
+#![cfg(not(miri))]
+
+use std::time::Duration;
+use wasmtime::component::{self, Component};
+use wasmtime::*;
+
+const INTERVAL: Duration = Duration::from_millis(1);
+
+fn with_profiler(
+    mut store: impl AsContextMut<Data = Option<GuestProfiler>>,
+    f: impl FnOnce(&mut GuestProfiler, StoreContext<'_, Option<GuestProfiler>>),
+) {
+    let mut store = store.as_context_mut();
+    let mut profiler = store.data_mut().take().unwrap();
+    f(&mut profiler, store.as_context());
+    *store.data_mut() = Some(profiler);
+}
+
+fn run_profiled() -> Result<GuestProfiler> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "host" "sleep" (func $sleep))
+                (func $guest_entry (export "run")
+                    call $sleep)
+            )
+        "#,
+    )?;
+    let profiler = GuestProfiler::new("test", INTERVAL, [("test".to_string(), module.clone())]);
+    let mut store = Store::new(&engine, Some(profiler));
+    store.call_hook(|store, kind| {
+        with_profiler(store, |profiler, store| {
+            profiler.call_hook_named(store, kind, "host_sleep")
+        });
+        Ok(())
+    });
+
+    let sleep = Func::wrap(
+        &mut store,
+        |mut caller: Caller<'_, Option<GuestProfiler>>| {
+            // A sample taken during a host call includes the host frame.
+            with_profiler(&mut caller, |profiler, store| {
+                profiler.sample(store, Duration::ZERO)
+            });
+            std::thread::sleep(INTERVAL * 5);
+        },
+    );
+    let instance = Instance::new(&mut store, &module, &[sleep.into()])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    run.call(&mut store, ())?;
+
+    Ok(store.data_mut().take().unwrap())
+}
+
+#[test]
+fn firefox_output() -> Result<()> {
+    let mut output = Vec::new();
+    run_profiled()?.finish(&mut output)?;
+    let output = String::from_utf8(output)?;
+    assert!(output.contains("guest_entry"), "{output}");
+    assert!(output.contains("host_sleep"), "{output}");
+    let _: serde_json::Value = serde_json::from_str(&output)?;
+    Ok(())
+}
+
+#[test]
+fn pprof_output() -> Result<()> {
+    let mut output = Vec::new();
+    run_profiled()?.finish_pprof(&mut output)?;
+
+    // Strings are stored verbatim in the profile's string table.
+    let contains = |s: &str| output.windows(s.len()).any(|w| w == s.as_bytes());
+    for s in [
+        "samples",
+        "wall",
+        "nanoseconds",
+        "guest_entry",
+        "host_sleep",
+    ] {
+        assert!(contains(s), "missing {s:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn component_modules() -> Result<()> {
+    let engine = Engine::default();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "sleep" (func $sleep))
+                (core func $sleep_lowered (canon lower (func $sleep)))
+                (core module $inner
+                    (import "host" "sleep" (func $sleep))
+                    (func $guest_entry (export "run")
+                        call $sleep)
+                )
+                (core instance $i (instantiate $inner
+                    (with "host" (instance (export "sleep" (func $sleep_lowered))))
+                ))
+                (func (export "run") (canon lift (core func $i "run")))
+            )
+        "#,
+    )?;
+    let profiler = GuestProfiler::new_component(
+        "my-component",
+        INTERVAL,
+        component.clone(),
+        Vec::<(String, Module)>::new(),
+    );
+    let mut store = Store::new(&engine, Some(profiler));
+
+    // Modules only show up in the profile once a sample references them.
+    let mut linker = component::Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap("sleep", |mut store: StoreContextMut<'_, _>, (): ()| {
+            with_profiler(&mut store, |profiler, store| {
+                profiler.sample(store, Duration::ZERO)
+            });
+            Ok(())
+        })?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    run.call(&mut store, ())?;
+
+    let mut output = Vec::new();
+    store.data_mut().take().unwrap().finish(&mut output)?;
+    let output = String::from_utf8(output)?;
+    assert!(output.contains("my-component/"), "{output}");
+    assert!(output.contains("guest_entry"), "{output}");
+    Ok(())
+}
@@ -8,6 +8,7 @@ mod code_too_large;
 mod component_model;
 mod coredump;
 mod debug;
+mod debugger;
 mod defaults;
 mod epoch_interruption;
 mod externals;
@@ -16,6 +17,7 @@ mod func;
 mod funcref;
 mod gc;
 mod globals;
+mod guest_profiler;
 mod host_funcs;
 mod i31ref;
 mod iloop;
@@ -33,13 +35,16 @@ mod name;
 mod noextern;
 mod piped_tests;
 mod pooling_allocator;
+mod pulley;
 mod relocs;
+mod snapshot;
 mod stack_creator;
 mod stack_overflow;
 mod store;
 mod structs;
 mod table;
 mod threads;
+mod tiered;
 mod traps;
 mod types;
 mod wait_notify;
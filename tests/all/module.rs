@@ -219,6 +219,84 @@ fn missing_sse_and_floats_still_works() -> Result<()> {
     Ok(())
 }
 
+// Winch lowers most SIMD instructions to SSSE3, SSE4.1 or SSE4.2
+// instructions. Without them, lane accesses fall back to SSE2 and everything
+// else is rejected at compile time.
+#[test]
+#[cfg_attr(any(not(target_arch = "x86_64"), miri), ignore)]
+fn winch_simd_without_sse41() -> Result<()> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Winch);
+    unsafe {
+        config.cranelift_flag_set("has_ssse3", "false");
+        config.cranelift_flag_set("has_sse41", "false");
+    }
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "i8x16") (param i32 i32) (result i32 i32)
+                    (local v128)
+                    (local.set 2
+                        (i8x16.replace_lane 5 (i8x16.splat (local.get 0)) (local.get 1)))
+                    (i8x16.extract_lane_s 5 (local.get 2))
+                    (i8x16.extract_lane_u 4 (local.get 2)))
+                (func (export "i32x4") (param i32 i32) (result i32 i32)
+                    (local v128)
+                    (local.set 2
+                        (i32x4.replace_lane 3 (i32x4.splat (local.get 0)) (local.get 1)))
+                    (i32x4.extract_lane 3 (local.get 2))
+                    (i32x4.extract_lane 2 (local.get 2)))
+                (func (export "i64x2") (param i64 i64) (result i64 i64)
+                    (local v128)
+                    (local.set 2
+                        (i64x2.replace_lane 1 (i64x2.splat (local.get 0)) (local.get 1)))
+                    (i64x2.extract_lane 1 (local.get 2))
+                    (i64x2.extract_lane 0 (local.get 2)))
+                (func (export "f32x4") (param f32 f32) (result f32 f32)
+                    (local v128)
+                    (local.set 2
+                        (f32x4.replace_lane 2 (f32x4.splat (local.get 0)) (local.get 1)))
+                    (f32x4.extract_lane 2 (local.get 2))
+                    (f32x4.extract_lane 1 (local.get 2)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    let i8x16 = instance.get_typed_func::<(i32, i32), (i32, i32)>(&mut store, "i8x16")?;
+    assert_eq!(i8x16.call(&mut store, (0x1ff, 0x80))?, (-128, 0xff));
+    assert_eq!(i8x16.call(&mut store, (0x7f, 0x101))?, (1, 0x7f));
+    let i32x4 = instance.get_typed_func::<(i32, i32), (i32, i32)>(&mut store, "i32x4")?;
+    assert_eq!(
+        i32x4.call(&mut store, (0x1234_5678, -2))?,
+        (-2, 0x1234_5678)
+    );
+    let i64x2 = instance.get_typed_func::<(i64, i64), (i64, i64)>(&mut store, "i64x2")?;
+    assert_eq!(i64x2.call(&mut store, (i64::MIN, 3))?, (3, i64::MIN));
+    let f32x4 = instance.get_typed_func::<(f32, f32), (f32, f32)>(&mut store, "f32x4")?;
+    assert_eq!(f32x4.call(&mut store, (1.5, -2.25))?, (-2.25, 1.5));
+
+    let err = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (param v128 v128) (result v128)
+                    (i8x16.swizzle (local.get 0) (local.get 1)))
+            )
+        "#,
+    )
+    .unwrap_err();
+    let err = format!("{err:?}");
+    assert!(
+        err.contains("unsupported instruction in Winch: I8x16Swizzle"),
+        "bad error: {err}"
+    );
+    Ok(())
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn large_add_chain_no_stack_overflow() -> Result<()> {
@@ -636,19 +636,19 @@ fn instance_too_large() -> Result<()> {
     let engine = Engine::new(&config)?;
     let expected = if cfg!(feature = "wmemcheck") {
         "\
-        instance allocation for this module requires 336 bytes which exceeds the \
+        instance allocation for this module requires 368 bytes which exceeds the \
 configured maximum of 16 bytes; breakdown of allocation requirement:
 
- * 71.43% - 240 bytes - instance state management
- * 26.19% - 88 bytes - static vmctx data
+ * 73.91% - 272 bytes - instance state management
+ * 23.91% - 88 bytes - static vmctx data
 "
     } else {
         "\
-        instance allocation for this module requires 240 bytes which exceeds the \
+        instance allocation for this module requires 272 bytes which exceeds the \
 configured maximum of 16 bytes; breakdown of allocation requirement:
 
- * 60.00% - 144 bytes - instance state management
- * 36.67% - 88 bytes - static vmctx data
+ * 64.71% - 176 bytes - instance state management
+ * 32.35% - 88 bytes - static vmctx data
 "
     };
     match Module::new(&engine, "(module)") {
@@ -664,19 +664,19 @@ configured maximum of 16 bytes; breakdown of allocation requirement:
 
     let expected = if cfg!(feature = "wmemcheck") {
         "\
-instance allocation for this module requires 1936 bytes which exceeds the \
+instance allocation for this module requires 1968 bytes which exceeds the \
 configured maximum of 16 bytes; breakdown of allocation requirement:
 
- * 12.40% - 240 bytes - instance state management
- * 82.64% - 1600 bytes - defined globals
+ * 13.82% - 272 bytes - instance state management
+ * 81.30% - 1600 bytes - defined globals
 "
     } else {
         "\
-instance allocation for this module requires 1840 bytes which exceeds the \
+instance allocation for this module requires 1872 bytes which exceeds the \
 configured maximum of 16 bytes; breakdown of allocation requirement:
 
- * 7.83% - 144 bytes - instance state management
- * 86.96% - 1600 bytes - defined globals
+ * 9.40% - 176 bytes - instance state management
+ * 85.47% - 1600 bytes - defined globals
 "
     };
     match Module::new(&engine, &lots_of_globals) {
@@ -1351,3 +1351,36 @@ fn instantiate_non_page_aligned_sizes() -> Result<()> {
     Instance::new(&mut store, &module, &[])?;
     Ok(())
 }
+
+#[test]
+fn pooling_allocator_metrics() -> Result<()> {
+    let engine = Engine::default();
+    assert!(engine.pooling_allocator_metrics().is_none());
+
+    let mut config = Config::new();
+    config.allocation_strategy(crate::small_pool_config());
+    config.memory_guard_size(0);
+    config.memory_reservation(1 << 16);
+    let engine = Engine::new(&config)?;
+    let metrics = engine.pooling_allocator_metrics().unwrap();
+    let module = Module::new(&engine, r#"(module (memory 1) (table 10 funcref))"#)?;
+
+    assert_eq!(metrics.core_instances(), 0);
+    assert_eq!(metrics.component_instances(), 0);
+    assert_eq!(metrics.memories(), 0);
+    assert_eq!(metrics.tables(), 0);
+
+    let mut store = Store::new(&engine, ());
+    Instance::new(&mut store, &module, &[])?;
+    assert_eq!(metrics.core_instances(), 1);
+    assert_eq!(metrics.memories(), 1);
+    assert_eq!(metrics.tables(), 1);
+
+    // Everything is returned to the pool when the store is dropped.
+    drop(store);
+    assert_eq!(metrics.core_instances(), 0);
+    assert_eq!(metrics.memories(), 0);
+    assert_eq!(metrics.tables(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,481 @@
+#![cfg(not(miri))]
+
+use wasmtime::*;
+
+fn pulley_config() -> Result<Config> {
+    let mut config = Config::new();
+    let target = if cfg!(target_pointer_width = "64") {
+        "pulley64"
+    } else {
+        "pulley32"
+    };
+    config.target(target)?;
+    Ok(config)
+}
+
+fn pulley_engine() -> Result<Engine> {
+    Engine::new(&pulley_config()?)
+}
+
+#[test]
+fn call_export() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "add") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    assert_eq!(add.call(&mut store, (1, 2))?, 3);
+    assert_eq!(add.call(&mut store, (i32::MAX, 1))?, i32::MIN);
+    Ok(())
+}
+
+#[test]
+fn trap() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "run") unreachable)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    for _ in 0..2 {
+        let trap = run.call(&mut store, ()).unwrap_err();
+        assert_eq!(trap.downcast::<Trap>()?, Trap::UnreachableCodeReached);
+    }
+    Ok(())
+}
+
+#[test]
+fn host_call() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "host" "double" (func $double (param i32) (result i32)))
+                (func (export "run") (param i32) (result i32)
+                    local.get 0
+                    call $double
+                    call $double)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, 0);
+    let double = Func::wrap(&mut store, |mut caller: Caller<'_, i32>, x: i32| {
+        *caller.data_mut() += 1;
+        x * 2
+    });
+    let instance = Instance::new(&mut store, &module, &[double.into()])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 3)?, 12);
+    assert_eq!(*store.data(), 2);
+    Ok(())
+}
+
+#[test]
+fn components_rejected() -> Result<()> {
+    let engine = pulley_engine()?;
+    let err = match component::Component::new(&engine, "(component)") {
+        Ok(_) => panic!("components should be rejected"),
+        Err(e) => e,
+    };
+    assert!(err.to_string().contains("Pulley"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn loops() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "sum") (param $n i32) (result i32)
+                    (local $acc i32)
+                    (loop $l
+                        (local.set $acc (i32.add (local.get $acc) (local.get $n)))
+                        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+                        (br_if $l (i32.gt_s (local.get $n) (i32.const 0))))
+                    local.get $acc)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let sum = instance.get_typed_func::<i32, i32>(&mut store, "sum")?;
+    assert_eq!(sum.call(&mut store, 1)?, 1);
+    assert_eq!(sum.call(&mut store, 10)?, 55);
+    assert_eq!(sum.call(&mut store, 1000)?, 500500);
+    Ok(())
+}
+
+#[test]
+fn br_table() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "run") (param i32) (result i32)
+                    (block $default
+                        (block $b2
+                            (block $b1
+                                (block $b0
+                                    (br_table $b0 $b1 $b2 $default (local.get 0)))
+                                (return (i32.const 10)))
+                            (return (i32.const 11)))
+                        (return (i32.const 12)))
+                    i32.const 13)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 0)?, 10);
+    assert_eq!(run.call(&mut store, 1)?, 11);
+    assert_eq!(run.call(&mut store, 2)?, 12);
+    assert_eq!(run.call(&mut store, 3)?, 13);
+    assert_eq!(run.call(&mut store, -1)?, 13);
+    Ok(())
+}
+
+#[test]
+fn memory_access() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "store") (param i32 i32)
+                    (i32.store (local.get 0) (local.get 1)))
+                (func (export "load") (param i32) (result i32)
+                    (i32.load (local.get 0)))
+                (func (export "load8_s") (param i32) (result i32)
+                    (i32.load8_s (local.get 0)))
+                (func (export "load16_u") (param i32) (result i32)
+                    (i32.load16_u (local.get 0)))
+                (func (export "f64_roundtrip") (param i32 f64) (result f64)
+                    (f64.store (local.get 0) (local.get 1))
+                    (f64.load (local.get 0)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let store_fn = instance.get_typed_func::<(i32, i32), ()>(&mut store, "store")?;
+    let load = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+    let load8_s = instance.get_typed_func::<i32, i32>(&mut store, "load8_s")?;
+    let load16_u = instance.get_typed_func::<i32, i32>(&mut store, "load16_u")?;
+    let f64_roundtrip = instance.get_typed_func::<(i32, f64), f64>(&mut store, "f64_roundtrip")?;
+
+    store_fn.call(&mut store, (8, 0x1234_80ff))?;
+    assert_eq!(load.call(&mut store, 8)?, 0x1234_80ff);
+    assert_eq!(load8_s.call(&mut store, 8)?, -1);
+    assert_eq!(load16_u.call(&mut store, 8)?, 0x80ff);
+    assert_eq!(f64_roundtrip.call(&mut store, (16, 1.5))?, 1.5);
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(memory.data(&store)[8..12], [0xff, 0x80, 0x34, 0x12]);
+
+    let trap = load.call(&mut store, 65536).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::MemoryOutOfBounds);
+    Ok(())
+}
+
+#[test]
+fn memory_grow() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory 1 3)
+                (func (export "size") (result i32)
+                    memory.size)
+                (func (export "grow") (param i32) (result i32)
+                    (memory.grow (local.get 0)))
+                (func (export "store") (param i32 i32)
+                    (i32.store (local.get 0) (local.get 1)))
+                (func (export "load") (param i32) (result i32)
+                    (i32.load (local.get 0)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let size = instance.get_typed_func::<(), i32>(&mut store, "size")?;
+    let grow = instance.get_typed_func::<i32, i32>(&mut store, "grow")?;
+    let store_fn = instance.get_typed_func::<(i32, i32), ()>(&mut store, "store")?;
+    let load = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+
+    assert_eq!(size.call(&mut store, ())?, 1);
+    assert!(store_fn.call(&mut store, (65536, 7)).is_err());
+    assert_eq!(grow.call(&mut store, 1)?, 1);
+    assert_eq!(size.call(&mut store, ())?, 2);
+    store_fn.call(&mut store, (65536, 7))?;
+    assert_eq!(load.call(&mut store, 65536)?, 7);
+    assert_eq!(grow.call(&mut store, 2)?, -1);
+    assert_eq!(size.call(&mut store, ())?, 2);
+    Ok(())
+}
+
+#[test]
+fn call_indirect() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $i2i (func (param i32) (result i32)))
+                (table 3 funcref)
+                (elem (i32.const 0) $double $negate $nullary)
+                (func $double (param i32) (result i32)
+                    (i32.mul (local.get 0) (i32.const 2)))
+                (func $negate (param i32) (result i32)
+                    (i32.sub (i32.const 0) (local.get 0)))
+                (func $nullary (result i32)
+                    i32.const 0)
+                (func (export "run") (param i32 i32) (result i32)
+                    (call_indirect (type $i2i) (local.get 1) (local.get 0)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(i32, i32), i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, (0, 21))?, 42);
+    assert_eq!(run.call(&mut store, (1, 5))?, -5);
+
+    let trap = run.call(&mut store, (2, 5)).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::BadSignature);
+    let trap = run.call(&mut store, (3, 5)).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::TableOutOfBounds);
+    Ok(())
+}
+
+#[test]
+fn integer_division_traps() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "div_s") (param i32 i32) (result i32)
+                    (i32.div_s (local.get 0) (local.get 1)))
+                (func (export "rem_u") (param i64 i64) (result i64)
+                    (i64.rem_u (local.get 0) (local.get 1)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let div_s = instance.get_typed_func::<(i32, i32), i32>(&mut store, "div_s")?;
+    let rem_u = instance.get_typed_func::<(i64, i64), i64>(&mut store, "rem_u")?;
+    assert_eq!(div_s.call(&mut store, (-7, 2))?, -3);
+    assert_eq!(rem_u.call(&mut store, (-1, 10))?, 5);
+
+    let trap = div_s.call(&mut store, (1, 0)).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::IntegerDivisionByZero);
+    let trap = div_s.call(&mut store, (i32::MIN, -1)).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::IntegerOverflow);
+    let trap = rem_u.call(&mut store, (1, 0)).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::IntegerDivisionByZero);
+    Ok(())
+}
+
+#[test]
+fn floats() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "nearest") (param f64) (result f64)
+                    (f64.nearest (local.get 0)))
+                (func (export "hypot") (param f32 f32) (result f32)
+                    (f32.sqrt (f32.add (f32.mul (local.get 0) (local.get 0))
+                                       (f32.mul (local.get 1) (local.get 1)))))
+                (func (export "trunc_s") (param f64) (result i32)
+                    (i32.trunc_f64_s (local.get 0)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let nearest = instance.get_typed_func::<f64, f64>(&mut store, "nearest")?;
+    let hypot = instance.get_typed_func::<(f32, f32), f32>(&mut store, "hypot")?;
+    let trunc_s = instance.get_typed_func::<f64, i32>(&mut store, "trunc_s")?;
+    assert_eq!(nearest.call(&mut store, 2.5)?, 2.0);
+    assert_eq!(nearest.call(&mut store, 3.5)?, 4.0);
+    assert_eq!(
+        nearest.call(&mut store, -0.4)?.to_bits(),
+        (-0.0f64).to_bits()
+    );
+    assert_eq!(hypot.call(&mut store, (3.0, 4.0))?, 5.0);
+    assert_eq!(trunc_s.call(&mut store, -3.9)?, -3);
+
+    let trap = trunc_s.call(&mut store, f64::NAN).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::BadConversionToInteger);
+    let trap = trunc_s.call(&mut store, 3e9).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::IntegerOverflow);
+    Ok(())
+}
+
+#[test]
+fn fuel_exhaustion() -> Result<()> {
+    let mut config = pulley_config()?;
+    config.consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "spin")
+                    (loop $l (br $l)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    store.set_fuel(10_000)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let spin = instance.get_typed_func::<(), ()>(&mut store, "spin")?;
+    let trap = spin.call(&mut store, ()).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::OutOfFuel);
+    assert_eq!(store.get_fuel()?, 0);
+    Ok(())
+}
+
+#[test]
+fn epoch_interruption() -> Result<()> {
+    let mut config = pulley_config()?;
+    config.epoch_interruption(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "spin")
+                    (loop $l (br $l)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    store.set_epoch_deadline(1);
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let spin = instance.get_typed_func::<(), ()>(&mut store, "spin")?;
+    engine.increment_epoch();
+    let trap = spin.call(&mut store, ()).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::Interrupt);
+    Ok(())
+}
+
+#[test]
+fn stack_overflow() -> Result<()> {
+    let engine = pulley_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $recurse (export "recurse") (param i64) (result i64)
+                    (i64.add (local.get 0) (call $recurse (local.get 0))))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let recurse = instance.get_typed_func::<i64, i64>(&mut store, "recurse")?;
+    for _ in 0..2 {
+        let trap = recurse.call(&mut store, 1).unwrap_err();
+        assert_eq!(trap.downcast::<Trap>()?, Trap::StackOverflow);
+    }
+    Ok(())
+}
+
+#[test]
+fn stack_arguments() -> Result<()> {
+    // Enough parameters that most are passed on the stack, with the caller's
+    // `vmctx` spilled across the call.
+    const N: usize = 40;
+    let params = vec!["i32"; N].join(" ");
+    let sum = (1..N)
+        .map(|i| format!("(local.get {i}) i32.add"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let args = (0..N)
+        .map(|i| format!("(i32.const {i})"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let wat = format!(
+        r#"
+            (module
+                (global $g (mut i32) (i32.const 0))
+                (func $sum (param {params}) (result i32)
+                    (local.get 0) {sum})
+                (func (export "run") (result i32)
+                    {args} (call $sum)
+                    (global.set $g)
+                    (global.get $g))
+            )
+        "#
+    );
+    let engine = pulley_engine()?;
+    let module = Module::new(&engine, &wat)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, ())?, (N * (N - 1) / 2) as i32);
+    Ok(())
+}
+
+#[test]
+fn unsupported_instructions_are_errors() -> Result<()> {
+    let mut config = pulley_config()?;
+    config.wasm_tail_call(true);
+    config.wasm_threads(true);
+    let engine = Engine::new(&config)?;
+    for wat in [
+        r#"
+            (module
+                (func $f (result i32)
+                    i32.const 1)
+                (func (export "run") (result i32)
+                    return_call $f)
+            )
+        "#,
+        r#"
+            (module
+                (func (export "run") (param v128 v128) (result v128)
+                    (i32x4.add (local.get 0) (local.get 1)))
+            )
+        "#,
+        r#"
+            (module
+                (memory 1 1 shared)
+                (func (export "run") (param i32) (result i32)
+                    (i32.atomic.rmw.add (local.get 0) (i32.const 1)))
+            )
+        "#,
+    ] {
+        let err = Module::new(&engine, wat).unwrap_err();
+        let err = format!("{err:?}");
+        assert!(err.contains("Pulley does not support"), "{err}");
+    }
+    Ok(())
+}
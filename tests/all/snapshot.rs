@@ -0,0 +1,174 @@
+use wasmtime::*;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn snapshot_restores_memory_globals_and_tables() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let module = Module::new(
+        store.engine(),
+        r#"
+            (module
+                (memory (export "memory") 1 10)
+                (global $counter (export "counter") (mut i32) (i32.const 0))
+                (table $table 1 funcref)
+                (func $a (result i32) i32.const 100)
+                (func $b (result i32) i32.const 200)
+                (elem declare func $b)
+                (func (export "init")
+                    (memory.grow (i32.const 1))
+                    drop
+                    (i32.store (i32.const 65540) (i32.const 42))
+                    (global.set $counter (i32.const 7))
+                    (table.grow $table (ref.func $b) (i32.const 1))
+                    drop)
+                (func (export "call") (param i32) (result i32)
+                    (call_indirect $table (result i32) (local.get 0)))
+                (elem (table $table) (i32.const 0) func $a)
+            )
+        "#,
+    )?;
+    let linker = Linker::new(store.engine());
+    let pre = linker.instantiate_pre(&module)?;
+
+    let instance = pre.instantiate(&mut store)?;
+    instance
+        .get_typed_func::<(), ()>(&mut store, "init")?
+        .call(&mut store, ())?;
+    let snapshot = instance.snapshot(&mut store)?.serialize()?;
+    let snapshot = InstanceSnapshot::deserialize(&snapshot)?;
+
+    let mut store = Store::<()>::new(module.engine(), ());
+    let instance = pre.instantiate_snapshot(&mut store, &snapshot)?;
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(memory.size(&store), 2);
+    assert_eq!(memory.data(&store)[65540], 42);
+
+    let counter = instance.get_global(&mut store, "counter").unwrap();
+    assert_eq!(counter.get(&mut store).unwrap_i32(), 7);
+
+    let call = instance.get_typed_func::<i32, i32>(&mut store, "call")?;
+    assert_eq!(call.call(&mut store, 0)?, 100);
+    assert_eq!(call.call(&mut store, 1)?, 200);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn snapshot_skips_start_function() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let module = Module::new(
+        store.engine(),
+        r#"
+            (module
+                (global $g (export "g") (mut i32) (i32.const 0))
+                (func $start
+                    (global.set $g (i32.add (global.get $g) (i32.const 1))))
+                (start $start)
+            )
+        "#,
+    )?;
+    let linker = Linker::new(store.engine());
+    let pre = linker.instantiate_pre(&module)?;
+
+    let instance = pre.instantiate(&mut store)?;
+    let g = instance.get_global(&mut store, "g").unwrap();
+    g.set(&mut store, Val::I32(10))?;
+    let snapshot = instance.snapshot(&mut store)?;
+
+    let instance = pre.instantiate_snapshot(&mut store, &snapshot)?;
+    let g = instance.get_global(&mut store, "g").unwrap();
+    assert_eq!(g.get(&mut store).unwrap_i32(), 10);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn snapshot_rejects_foreign_funcref() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let module = Module::new(
+        store.engine(),
+        r#"
+            (module
+                (table (export "table") 1 funcref)
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let table = instance.get_table(&mut store, "table").unwrap();
+    let host = Func::wrap(&mut store, || {});
+    table.set(&mut store, 0, host.into())?;
+    assert!(instance.snapshot(&mut store).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn snapshot_rejects_other_module() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let a = Module::new(store.engine(), r#"(module (memory 1))"#)?;
+    let b = Module::new(store.engine(), r#"(module)"#)?;
+    let instance = Instance::new(&mut store, &a, &[])?;
+    let snapshot = instance.snapshot(&mut store)?;
+
+    let pre = Linker::new(store.engine()).instantiate_pre(&b)?;
+    assert!(pre.instantiate_snapshot(&mut store, &snapshot).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn snapshot_rejects_module_with_same_shape() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let a = Module::new(
+        store.engine(),
+        r#"(module (memory 1) (func (export "f") (result i32) i32.const 1))"#,
+    )?;
+    let b = Module::new(
+        store.engine(),
+        r#"(module (memory 1) (func (export "f") (result i32) i32.const 2))"#,
+    )?;
+    let instance = Instance::new(&mut store, &a, &[])?;
+    let snapshot = instance.snapshot(&mut store)?;
+
+    let pre = Linker::new(store.engine()).instantiate_pre(&b)?;
+    let err = pre
+        .instantiate_snapshot(&mut store, &snapshot)
+        .err()
+        .unwrap();
+    assert!(
+        err.to_string()
+            .contains("not taken from an instance of this module"),
+        "bad error: {err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn snapshot_restores_into_deserialized_module() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let module = Module::new(
+        store.engine(),
+        r#"
+            (module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "hello")
+                (func (export "f") (param f32) (result f32)
+                    (f32.floor (local.get 0)))
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    memory.data_mut(&mut store)[0] = b'j';
+    let snapshot = instance.snapshot(&mut store)?;
+
+    let module = unsafe { Module::deserialize(store.engine(), &module.serialize()?)? };
+    let pre = Linker::new(store.engine()).instantiate_pre(&module)?;
+    let instance = pre.instantiate_snapshot(&mut store, &snapshot)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(&memory.data(&store)[..5], b"jello");
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use wasmtime::*;
+
+const WAT: &str = r#"
+    (module
+        (type $t (func (param i32) (result i32)))
+        (table 1 funcref)
+        (elem (i32.const 0) $sum)
+
+        (func $sum (export "sum") (param $n i32) (result i32)
+            (local $acc i32)
+            (block $done
+                (loop $loop
+                    (br_if $done (i32.eqz (local.get $n)))
+                    (local.set $acc (i32.add (local.get $acc) (local.get $n)))
+                    (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+                    (br $loop)))
+            (local.get $acc))
+
+        (func (export "sum_indirect") (param i32) (result i32)
+            (call_indirect (type $t) (local.get 0) (i32.const 0)))
+
+        (func $div (export "div") (param i32 i32) (result i32)
+            (i32.div_u (local.get 0) (local.get 1)))
+
+        (func (export "div_direct") (param i32 i32) (result i32)
+            (call $div (local.get 0) (local.get 1)))
+    )
+"#;
+
+const THRESHOLD: u32 = 10;
+
+fn tiered_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Tiered);
+    config.tier_up_threshold(THRESHOLD);
+    Engine::new(&config)
+}
+
+/// Calls `f` until the tier-up counters of the functions it calls run out,
+/// waits for `module`'s optimizing tier to be compiled in the background, and
+/// then calls `f` until the counters run out again so that the functions
+/// switch over to the optimized code.
+fn tier_up(module: &Module, mut f: impl FnMut() -> Result<()>) -> Result<()> {
+    for _ in 0..THRESHOLD {
+        f()?;
+    }
+    assert!(module.wait_for_tier_up());
+    for _ in 0..THRESHOLD {
+        f()?;
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn results_are_stable_across_tier_up() -> Result<()> {
+    if !cfg!(target_arch = "x86_64") {
+        return Ok(());
+    }
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let sum = instance.get_typed_func::<i32, i32>(&mut store, "sum")?;
+    let sum_indirect = instance.get_typed_func::<i32, i32>(&mut store, "sum_indirect")?;
+    assert!(!sum.func().is_tiered_up(&store));
+    assert!(!sum_indirect.func().is_tiered_up(&store));
+
+    tier_up(&module, || {
+        assert_eq!(sum.call(&mut store, 100)?, 5050);
+        assert_eq!(sum_indirect.call(&mut store, 100)?, 5050);
+        Ok(())
+    })?;
+    assert!(sum.func().is_tiered_up(&store));
+    assert!(sum_indirect.func().is_tiered_up(&store));
+    assert_eq!(sum.call(&mut store, 100)?, 5050);
+    assert_eq!(sum_indirect.call(&mut store, 100)?, 5050);
+
+    // A second instance starts out on the baseline tier again but shares the
+    // module's optimizing tier.
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let sum = instance.get_typed_func::<i32, i32>(&mut store, "sum")?;
+    assert!(!sum.func().is_tiered_up(&store));
+    assert_eq!(sum.call(&mut store, 1000)?, 500500);
+    assert!(sum.func().is_tiered_up(&store));
+    assert_eq!(sum.call(&mut store, 1000)?, 500500);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn traps_after_tier_up() -> Result<()> {
+    if !cfg!(target_arch = "x86_64") {
+        return Ok(());
+    }
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let div = instance.get_typed_func::<(i32, i32), i32>(&mut store, "div")?;
+
+    tier_up(&module, || {
+        assert_eq!(div.call(&mut store, (10, 2))?, 5);
+        Ok(())
+    })?;
+    assert!(div.func().is_tiered_up(&store));
+
+    let err = div.call(&mut store, (1, 0)).unwrap_err();
+    assert_eq!(
+        err.downcast::<Trap>()?,
+        Trap::IntegerDivisionByZero,
+        "traps in tiered-up code are reported as wasm traps"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn hot_loops_tier_up() -> Result<()> {
+    if !cfg!(target_arch = "x86_64") {
+        return Ok(());
+    }
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let sum = instance.get_typed_func::<i32, i32>(&mut store, "sum")?;
+
+    // A single call whose loop runs for longer than the threshold is enough
+    // to start compiling the optimizing tier, and a second one to switch over.
+    assert_eq!(sum.call(&mut store, 100)?, 5050);
+    assert!(module.wait_for_tier_up());
+    assert_eq!(sum.call(&mut store, 100)?, 5050);
+    assert!(sum.func().is_tiered_up(&store));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn direct_calls_after_tier_up() -> Result<()> {
+    if !cfg!(target_arch = "x86_64") {
+        return Ok(());
+    }
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let div = instance.get_typed_func::<(i32, i32), i32>(&mut store, "div")?;
+    let div_direct = instance.get_typed_func::<(i32, i32), i32>(&mut store, "div_direct")?;
+
+    tier_up(&module, || {
+        assert_eq!(div.call(&mut store, (10, 2))?, 5);
+        Ok(())
+    })?;
+    assert!(div.func().is_tiered_up(&store));
+    assert!(!div_direct.func().is_tiered_up(&store));
+
+    // `div_direct` is still baseline code, but its direct call to `div` goes
+    // to the optimized code.
+    let err = div_direct.call(&mut store, (1, 0)).unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    let frames = trace.frames();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].func_index(), 2);
+    assert_ne!(frames[0].module().image_range(), module.image_range());
+    assert_eq!(frames[1].func_index(), 3);
+    assert_eq!(frames[1].module().image_range(), module.image_range());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn no_tier_up_without_tiered_strategy() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let sum = instance.get_typed_func::<i32, i32>(&mut store, "sum")?;
+    for _ in 0..THRESHOLD {
+        assert_eq!(sum.call(&mut store, 100)?, 5050);
+    }
+    assert!(!module.wait_for_tier_up());
+    assert!(!sum.func().is_tiered_up(&store));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tiered_requires_winch_compatible_config() -> Result<()> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Tiered);
+    config.table_lazy_init(false);
+    assert!(Engine::new(&config).is_err());
+    Ok(())
+}
@@ -17,8 +17,8 @@
 ;;     gv3 = vmctx
 ;;     sig0 = (i64 vmctx, i32 uext, i32 uext, i32 uext, i32 uext) -> i32 system_v
 ;;     sig1 = (i64 vmctx, i64) -> i32 uext system_v
-;;     fn0 = colocated u1:27 sig0
-;;     fn1 = colocated u1:28 sig1
+;;     fn0 = colocated u1:29 sig0
+;;     fn1 = colocated u1:30 sig1
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64, v2: i64):
@@ -0,0 +1,110 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = ["-Cinlining"]
+
+;; With inlining enabled, calls to small functions defined in the same module
+;; are replaced with the callee's body, while calls to imports are kept.
+(module
+  (import "" "imported" (func $imported (param i32) (result i32)))
+  (memory 1)
+
+  (func $add_one (param i32) (result i32)
+    local.get 0
+    i32.const 1
+    i32.add)
+
+  (func $load (param i32) (result i32)
+    local.get 0
+    i32.load offset=4)
+
+  (func (export "f") (param i32) (result i32)
+    local.get 0
+    call $add_one
+    call $load
+    call $imported)
+)
+;; function u0:1(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @003b                               jump block1
+;;
+;;                                 block1:
+;; @0038                               v4 = iconst.i32 1
+;; @003a                               v5 = iadd.i32 v2, v4  ; v4 = 1
+;; @003b                               return v5
+;; }
+;;
+;; function u0:2(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+128
+;;     gv5 = load.i64 notrap aligned readonly checked gv3+120
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @0040                               v5 = load.i64 notrap aligned readonly checked v0+120
+;; @0040                               v4 = uextend.i64 v2
+;; @0040                               v6 = iadd v5, v4
+;; @0040                               v7 = iconst.i64 4
+;; @0040                               v8 = iadd v6, v7  ; v7 = 4
+;; @0040                               v9 = load.i32 little heap v8
+;; @0043                               jump block1
+;;
+;;                                 block1:
+;; @0043                               return v9
+;; }
+;;
+;; function u0:3(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1
+;;     gv3 = vmctx
+;;     sig0 = (i64 vmctx, i64, i32) -> i32 tail
+;;     sig1 = (i64 vmctx, i64, i32) -> i32 tail
+;;     sig2 = (i64 vmctx, i64, i32) -> i32 tail
+;;     fn0 = colocated u0:1 sig0
+;;     fn1 = colocated u0:2 sig1
+;;     fn2 = u0:0 sig2
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @0048                               jump block3
+;;
+;;                                 block3:
+;; @0048                               jump block4
+;;
+;;                                 block4:
+;; @0048                               jump block2
+;;
+;;                                 block2:
+;; @004a                               jump block6
+;;
+;;                                 block6:
+;; @004a                               v23 = load.i64 notrap aligned readonly checked v0+120
+;; @0048                               v15 = iconst.i32 1
+;; @0048                               v16 = iadd.i32 v2, v15  ; v15 = 1
+;; @004a                               v22 = uextend.i64 v16
+;; @004a                               v24 = iadd v23, v22
+;; @004a                               v25 = iconst.i64 4
+;; @004a                               v26 = iadd v24, v25  ; v25 = 4
+;; @004a                               v27 = load.i32 little heap v26
+;; @004a                               jump block7
+;;
+;;                                 block7:
+;; @004a                               jump block5
+;;
+;;                                 block5:
+;; @004c                               v7 = load.i64 notrap aligned readonly v0+88
+;; @004c                               v8 = load.i64 notrap aligned readonly v0+104
+;; @004c                               v9 = call_indirect sig2, v7(v8, v0, v27)
+;; @004e                               jump block1
+;;
+;;                                 block1:
+;; @004e                               return v9
+;; }
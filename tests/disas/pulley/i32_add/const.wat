@@ -11,13 +11,21 @@
 
 ;; wasm[0]::function[0]:
 ;;       xconst8 spilltmp0, -16
-;;       xadd32 sp, sp, spilltmp0
+;;       xadd64 sp, sp, spilltmp0
 ;;       store64_offset8 sp, 8, lr
 ;;       store64 sp, fp
 ;;       xmov fp, sp
-;;       xconst8 x0, 30
+;;       load64_offset8 x7, x0, 8
+;;       load64 x7, x7
+;;       get_sp x8
+;;       xult64 x7, x8, x7
+;;       br_if x7, 0x17    // target = 0x35
+;;   24: xconst8 x0, 30
 ;;       load64_offset8 lr, sp, 8
 ;;       load64 fp, sp
 ;;       xconst8 spilltmp0, 16
-;;       xadd32 sp, sp, spilltmp0
+;;       xadd64 sp, sp, spilltmp0
 ;;       ret
+;;   35: xconst8 x1, 0
+;;   38: call 0x8b    // target = 0xc3
+;;   3d: trap
@@ -23,7 +23,7 @@
 ;;     gv4 = load.i64 notrap aligned gv3+88
 ;;     gv5 = load.i64 notrap aligned gv3+96
 ;;     sig0 = (i64 vmctx, i32) -> i32 system_v
-;;     fn0 = colocated u1:26 sig0
+;;     fn0 = colocated u1:28 sig0
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64):
@@ -115,7 +115,7 @@
 ;;     gv4 = load.i64 notrap aligned gv3+88
 ;;     gv5 = load.i64 notrap aligned gv3+96
 ;;     sig0 = (i64 vmctx, i32) -> i32 system_v
-;;     fn0 = colocated u1:26 sig0
+;;     fn0 = colocated u1:28 sig0
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64, v2: i32):
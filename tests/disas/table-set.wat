@@ -24,7 +24,7 @@
 ;;     gv4 = load.i64 notrap aligned gv3+88
 ;;     gv5 = load.i64 notrap aligned gv3+96
 ;;     sig0 = (i64 vmctx, i32 uext) system_v
-;;     fn0 = colocated u1:25 sig0
+;;     fn0 = colocated u1:27 sig0
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64, v2: i32):
@@ -128,7 +128,7 @@
 ;;     gv4 = load.i64 notrap aligned gv3+88
 ;;     gv5 = load.i64 notrap aligned gv3+96
 ;;     sig0 = (i64 vmctx, i32 uext) system_v
-;;     fn0 = colocated u1:25 sig0
+;;     fn0 = colocated u1:27 sig0
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
@@ -9,7 +9,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x10
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x4c
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x10
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #8]
@@ -18,3 +25,4 @@
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;;   4c: .byte   0x1f, 0xc1, 0x00, 0x00
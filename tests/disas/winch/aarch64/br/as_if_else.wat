@@ -14,7 +14,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x18
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x74
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #0x10]
@@ -23,13 +30,14 @@
 ;;       stur    w3, [x28]
 ;;       ldur    w0, [x28, #4]
 ;;       tst     w0, w0
-;;       b.eq    #0x40
-;;       b       #0x38
-;;   38: ldur    w0, [x28]
-;;       b       #0x48
-;;   40: mov     x16, #4
+;;       b.eq    #0x5c
+;;       b       #0x54
+;;   54: ldur    w0, [x28]
+;;       b       #0x64
+;;   5c: mov     x16, #4
 ;;       mov     w0, w16
 ;;       add     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;;   74: .byte   0x1f, 0xc1, 0x00, 0x00
@@ -9,7 +9,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x10
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x68
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x10
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #8]
@@ -19,9 +26,10 @@
 ;;       mov     x16, #1
 ;;       mov     w0, w16
 ;;       tst     w1, w1
-;;       b.ne    #0x3c
-;;       b       #0x3c
-;;   3c: add     sp, sp, #0x10
+;;       b.ne    #0x58
+;;       b       #0x58
+;;   58: add     sp, sp, #0x10
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;;   68: .byte   0x1f, 0xc1, 0x00, 0x00
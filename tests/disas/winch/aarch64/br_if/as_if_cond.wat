@@ -15,7 +15,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x18
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x88
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #0x10]
@@ -25,17 +32,18 @@
 ;;       mov     x16, #1
 ;;       mov     w0, w16
 ;;       tst     w1, w1
-;;       b.ne    #0x5c
-;;       b       #0x3c
-;;   3c: tst     w0, w0
-;;       b.eq    #0x54
-;;       b       #0x48
-;;   48: mov     x16, #2
+;;       b.ne    #0x78
+;;       b       #0x58
+;;   58: tst     w0, w0
+;;       b.eq    #0x70
+;;       b       #0x64
+;;   64: mov     x16, #2
 ;;       mov     w0, w16
-;;       b       #0x5c
-;;   54: mov     x16, #3
+;;       b       #0x78
+;;   70: mov     x16, #3
 ;;       mov     w0, w16
 ;;       add     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;;   88: .byte   0x1f, 0xc1, 0x00, 0x00
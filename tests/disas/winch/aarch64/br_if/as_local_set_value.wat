@@ -13,7 +13,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x18
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x80
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #0x10]
@@ -26,12 +33,13 @@
 ;;       mov     x16, #0x11
 ;;       mov     w0, w16
 ;;       tst     w1, w1
-;;       b.ne    #0x54
-;;       b       #0x48
-;;   48: stur    w0, [x28, #4]
+;;       b.ne    #0x70
+;;       b       #0x64
+;;   64: stur    w0, [x28, #4]
 ;;       orr     x16, xzr, #0xffffffff
 ;;       mov     w0, w16
 ;;       add     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;;   80: .byte   0x1f, 0xc1, 0x00, 0x00
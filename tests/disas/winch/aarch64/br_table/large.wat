@@ -742,7 +742,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x18
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x18128
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #0x10]
@@ -750,15 +757,15 @@
 ;;       stur    w2, [x28, #4]
 ;;       ldur    w0, [x28, #4]
 ;;       mov     x16, #0x6027
-;;       cmp     x0, x16, uxtx
-;;       b.hs    #0x180f4
-;;   34: csel    x1, xzr, x0, hs
+;;       cmp     w0, w16, uxtx
+;;       b.hs    #0x18110
+;;   50: csel    x1, xzr, x0, hs
 ;;       csdb
-;;       adr     x16, #0x4c
+;;       adr     x16, #0x68
 ;;       ldrsw   x1, [x16, w1, uxtw #2]
 ;;       add     x16, x16, x1
 ;;       br      x16
-;;   4c: .byte   0x9c, 0x80, 0x01, 0x00
+;;   68: .byte   0x9c, 0x80, 0x01, 0x00
 ;;       .byte   0xa8, 0x80, 0x01, 0x00
 ;;       .byte   0x9c, 0x80, 0x01, 0x00
 ;;       .byte   0xa8, 0x80, 0x01, 0x00
@@ -25375,10 +25382,11 @@
 ;;       .byte   0x9c, 0x80, 0x01, 0x00
 ;;       mov     x16, #0
 ;;       mov     w0, w16
-;;       b       #0x180fc
-;; 180f4: mov     x16, #1
+;;       b       #0x18118
+;; 18110: mov     x16, #1
 ;;       mov     w0, w16
 ;;       add     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;; 18128: .byte   0x1f, 0xc1, 0x00, 0x00
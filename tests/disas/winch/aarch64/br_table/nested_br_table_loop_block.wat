@@ -22,37 +22,44 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x18
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0xc0
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x18
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #0x10]
 ;;       stur    x1, [x28, #8]
 ;;       stur    w2, [x28, #4]
 ;;       ldur    w0, [x28, #4]
-;;       cmp     x0, #2
-;;       b.hs    #0x54
-;;   30: csel    x1, xzr, x0, hs
+;;       cmp     w0, #2
+;;       b.hs    #0x70
+;;   4c: csel    x1, xzr, x0, hs
 ;;       csdb
-;;       adr     x16, #0x48
+;;       adr     x16, #0x64
 ;;       ldrsw   x1, [x16, w1, uxtw #2]
 ;;       add     x16, x16, x1
 ;;       br      x16
-;;   48: .byte   0xdc, 0xff, 0xff, 0xff
+;;   64: .byte   0xdc, 0xff, 0xff, 0xff
 ;;       .byte   0x0c, 0x00, 0x00, 0x00
-;;       b       #0x24
-;;   54: mov     x16, #0
+;;       b       #0x40
+;;   70: mov     x16, #0
 ;;       mov     w0, w16
 ;;       stur    w0, [x28, #4]
 ;;       ldur    w0, [x28, #4]
-;;       cmp     x0, #2
-;;       b.hs    #0x60
-;;   6c: csel    x1, xzr, x0, hs
+;;       cmp     w0, #2
+;;       b.hs    #0x7c
+;;   88: csel    x1, xzr, x0, hs
 ;;       csdb
-;;       adr     x16, #0x84
+;;       adr     x16, #0xa0
 ;;       ldrsw   x1, [x16, w1, uxtw #2]
 ;;       add     x16, x16, x1
 ;;       br      x16
-;;   84: .byte   0x08, 0x00, 0x00, 0x00
+;;   a0: .byte   0x08, 0x00, 0x00, 0x00
 ;;       .byte   0xdc, 0xff, 0xff, 0xff
 ;;       mov     x16, #3
 ;;       mov     w0, w16
@@ -60,3 +67,4 @@
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;;   c0: .byte   0x1f, 0xc1, 0x00, 0x00
@@ -21,7 +21,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x20
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x9c
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x20
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #0x18]
@@ -50,3 +57,4 @@
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;;   9c: .byte   0x1f, 0xc1, 0x00, 0x00
@@ -23,7 +23,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x1
+;;       ldur    x16, [x1, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x28
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x150
+;;   28: mov     x9, x1
 ;;       sub     sp, sp, #0x20
 ;;       mov     x28, sp
 ;;       stur    x1, [x28, #0x18]
@@ -33,49 +40,49 @@
 ;;       ldur    w0, [x28, #0xc]
 ;;       ldur    x1, [x9, #0x68]
 ;;       mov     w2, w0
-;;       add     x2, x2, #4
-;;       b.hs    #0x134
-;;   3c: cmp     x2, x1, uxtx
-;;       b.hi    #0x138
-;;   44: ldur    x3, [x9, #0x60]
+;;       adds    x2, x2, #4
+;;       b.hs    #0x154
+;;   58: cmp     x2, x1, uxtx
+;;       b.hi    #0x158
+;;   60: ldur    x3, [x9, #0x60]
 ;;       add     x3, x3, x0, uxtx
 ;;       mov     x16, #0
 ;;       mov     x4, x16
 ;;       cmp     x2, x1, uxtx
-;;       csel    x3, x4, x4, hi
+;;       csel    x3, x4, x3, hi
 ;;       ldur    w0, [x3]
 ;;       ldur    w1, [x28, #0xc]
 ;;       ldur    x2, [x9, #0x68]
 ;;       mov     w3, w1
-;;       add     x3, x3, #8
-;;       b.hs    #0x13c
-;;   74: cmp     x3, x2, uxtx
-;;       b.hi    #0x140
-;;   7c: ldur    x4, [x9, #0x60]
+;;       adds    x3, x3, #8
+;;       b.hs    #0x15c
+;;   90: cmp     x3, x2, uxtx
+;;       b.hi    #0x160
+;;   98: ldur    x4, [x9, #0x60]
 ;;       add     x4, x4, x1, uxtx
 ;;       add     x4, x4, #4
 ;;       mov     x16, #0
 ;;       mov     x5, x16
 ;;       cmp     x3, x2, uxtx
-;;       csel    x4, x5, x5, hi
+;;       csel    x4, x5, x4, hi
 ;;       ldur    w1, [x4]
 ;;       ldur    w2, [x28, #0xc]
 ;;       ldur    x3, [x9, #0x68]
 ;;       mov     w4, w2
 ;;       mov     w16, #3
 ;;       movk    w16, #0x10, lsl #16
-;;       add     x4, x4, x16, uxtx
-;;       b.hs    #0x144
-;;   b8: cmp     x4, x3, uxtx
-;;       b.hi    #0x148
-;;   c0: ldur    x5, [x9, #0x60]
+;;       adds    x4, x4, x16, uxtx
+;;       b.hs    #0x164
+;;   d4: cmp     x4, x3, uxtx
+;;       b.hi    #0x168
+;;   dc: ldur    x5, [x9, #0x60]
 ;;       add     x5, x5, x2, uxtx
 ;;       orr     x16, xzr, #0xfffff
 ;;       add     x5, x5, x16, uxtx
 ;;       mov     x16, #0
 ;;       mov     x6, x16
 ;;       cmp     x4, x3, uxtx
-;;       csel    x5, x6, x6, hi
+;;       csel    x5, x6, x5, hi
 ;;       ldur    w2, [x5]
 ;;       sub     sp, sp, #4
 ;;       mov     x28, sp
@@ -97,9 +104,10 @@
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
-;;  134: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  138: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  13c: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  140: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  144: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  148: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  150: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  154: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  158: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  15c: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  160: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  164: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  168: .byte   0x1f, 0xc1, 0x00, 0x00
@@ -13,7 +13,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x1
+;;       ldur    x16, [x1, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x34
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0xc8
+;;   28: mov     x9, x1
 ;;       sub     sp, sp, #0x28
 ;;       mov     x28, sp
 ;;       stur    x1, [x28, #0x20]
@@ -53,3 +60,4 @@
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
+;;   c8: .byte   0x1f, 0xc1, 0x00, 0x00
@@ -23,7 +23,14 @@
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
 ;;       mov     x28, sp
-;;       mov     x9, x0
+;;       ldur    x16, [x0, #8]
+;;       ldur    x16, [x16]
+;;       mov     x17, #0x20
+;;       movk    x17, #0, lsl #16
+;;       add     x16, x16, x17
+;;       cmp     sp, x16
+;;       b.lo    #0x124
+;;   28: mov     x9, x0
 ;;       sub     sp, sp, #0x20
 ;;       mov     x28, sp
 ;;       stur    x0, [x28, #0x18]
@@ -36,32 +43,32 @@
 ;;       ldur    w1, [x28, #0xc]
 ;;       ldur    x2, [x9, #0x68]
 ;;       mov     w3, w1
-;;       add     x3, x3, #4
-;;       b.hs    #0x108
-;;   48: cmp     x3, x2, uxtx
-;;       b.hi    #0x10c
-;;   50: ldur    x4, [x9, #0x60]
+;;       adds    x3, x3, #4
+;;       b.hs    #0x128
+;;   64: cmp     x3, x2, uxtx
+;;       b.hi    #0x12c
+;;   6c: ldur    x4, [x9, #0x60]
 ;;       add     x4, x4, x1, uxtx
 ;;       mov     x16, #0
 ;;       mov     x5, x16
 ;;       cmp     x3, x2, uxtx
-;;       csel    x4, x5, x5, hi
+;;       csel    x4, x5, x4, hi
 ;;       stur    w0, [x4]
 ;;       ldur    w0, [x28, #4]
 ;;       ldur    w1, [x28, #0xc]
 ;;       ldur    x2, [x9, #0x68]
 ;;       mov     w3, w1
-;;       add     x3, x3, #8
-;;       b.hs    #0x110
-;;   84: cmp     x3, x2, uxtx
-;;       b.hi    #0x114
-;;   8c: ldur    x4, [x9, #0x60]
+;;       adds    x3, x3, #8
+;;       b.hs    #0x130
+;;   a0: cmp     x3, x2, uxtx
+;;       b.hi    #0x134
+;;   a8: ldur    x4, [x9, #0x60]
 ;;       add     x4, x4, x1, uxtx
 ;;       add     x4, x4, #4
 ;;       mov     x16, #0
 ;;       mov     x5, x16
 ;;       cmp     x3, x2, uxtx
-;;       csel    x4, x5, x5, hi
+;;       csel    x4, x5, x4, hi
 ;;       stur    w0, [x4]
 ;;       ldur    w0, [x28]
 ;;       ldur    w1, [x28, #0xc]
@@ -69,26 +76,27 @@
 ;;       mov     w3, w1
 ;;       mov     w16, #3
 ;;       movk    w16, #0x10, lsl #16
-;;       add     x3, x3, x16, uxtx
-;;       b.hs    #0x118
-;;   cc: cmp     x3, x2, uxtx
-;;       b.hi    #0x11c
-;;   d4: ldur    x4, [x9, #0x60]
+;;       adds    x3, x3, x16, uxtx
+;;       b.hs    #0x138
+;;   e8: cmp     x3, x2, uxtx
+;;       b.hi    #0x13c
+;;   f0: ldur    x4, [x9, #0x60]
 ;;       add     x4, x4, x1, uxtx
 ;;       orr     x16, xzr, #0xfffff
 ;;       add     x4, x4, x16, uxtx
 ;;       mov     x16, #0
 ;;       mov     x5, x16
 ;;       cmp     x3, x2, uxtx
-;;       csel    x4, x5, x5, hi
+;;       csel    x4, x5, x4, hi
 ;;       stur    w0, [x4]
 ;;       add     sp, sp, #0x20
 ;;       mov     x28, sp
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
-;;  108: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  10c: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  110: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  114: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  118: .byte   0x1f, 0xc1, 0x00, 0x00
-;;  11c: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  124: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  128: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  12c: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  130: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  134: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  138: .byte   0x1f, 0xc1, 0x00, 0x00
+;;  13c: .byte   0x1f, 0xc1, 0x00, 0x00
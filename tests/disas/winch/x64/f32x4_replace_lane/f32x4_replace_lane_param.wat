@@ -0,0 +1,35 @@
+;;! target = "x86_64"
+;;! test = "winch"
+
+(module
+    (func (param v128 f32) (result v128)
+        (local.get 0)
+        (local.get 1)
+        (f32x4.replace_lane 2)
+    )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    (%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x63
+;;   1b: movq    %rdi, %r14
+;;       subq    $0x30, %rsp
+;;       movq    %rdi, 0x28(%rsp)
+;;       movq    %rsi, 0x20(%rsp)
+;;       movdqu  %xmm0, 0x10(%rsp)
+;;       movss   %xmm1, 0xc(%rsp)
+;;       movss   0xc(%rsp), %xmm0
+;;       movdqu  0x10(%rsp), %xmm1
+;;       movd    %xmm0, %r11d
+;;       pinsrw  $4, %r11d, %xmm1
+;;       shrl    $0x10, %r11d
+;;       pinsrw  $5, %r11d, %xmm1
+;;       movdqa  %xmm1, %xmm0
+;;       addq    $0x30, %rsp
+;;       popq    %rbp
+;;       retq
+;;   63: ud2
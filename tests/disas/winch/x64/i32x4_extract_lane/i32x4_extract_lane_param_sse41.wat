@@ -0,0 +1,29 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = ["-Ccranelift-has_sse41"]
+
+(module
+    (func (param v128) (result i32)
+        (local.get 0)
+        (i32x4.extract_lane 2)
+    )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    (%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x42
+;;   1b: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movdqu  %xmm0, (%rsp)
+;;       movdqu  (%rsp), %xmm0
+;;       pextrd  $2, %xmm0, %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   42: ud2
@@ -0,0 +1,32 @@
+;;! target = "x86_64"
+;;! test = "winch"
+;;! flags = ["-Ccranelift-has_sse41"]
+
+(module
+    (func (param v128 i32) (result v128)
+        (local.get 0)
+        (local.get 1)
+        (i32x4.replace_lane 3)
+    )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    (%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x4c
+;;   1b: movq    %rdi, %r14
+;;       subq    $0x30, %rsp
+;;       movq    %rdi, 0x28(%rsp)
+;;       movq    %rsi, 0x20(%rsp)
+;;       movdqu  %xmm0, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       movdqu  0x10(%rsp), %xmm0
+;;       pinsrd  $3, %eax, %xmm0
+;;       addq    $0x30, %rsp
+;;       popq    %rbp
+;;       retq
+;;   4c: ud2
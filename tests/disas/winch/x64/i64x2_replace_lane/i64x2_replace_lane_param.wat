@@ -0,0 +1,32 @@
+;;! target = "x86_64"
+;;! test = "winch"
+
+(module
+    (func (param v128 i64) (result v128)
+        (local.get 0)
+        (local.get 1)
+        (i64x2.replace_lane 1)
+    )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    (%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x51
+;;   1b: movq    %rdi, %r14
+;;       subq    $0x30, %rsp
+;;       movq    %rdi, 0x28(%rsp)
+;;       movq    %rsi, 0x20(%rsp)
+;;       movdqu  %xmm0, 0x10(%rsp)
+;;       movq    %rdx, 8(%rsp)
+;;       movq    8(%rsp), %rax
+;;       movdqu  0x10(%rsp), %xmm0
+;;       movq    %rax, %xmm15
+;;       movlhps %xmm15, %xmm0
+;;       addq    $0x30, %rsp
+;;       popq    %rbp
+;;       retq
+;;   51: ud2
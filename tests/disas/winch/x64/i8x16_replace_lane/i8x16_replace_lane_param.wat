@@ -0,0 +1,36 @@
+;;! target = "x86_64"
+;;! test = "winch"
+
+(module
+    (func (param v128 i32) (result v128)
+        (local.get 0)
+        (local.get 1)
+        (i8x16.replace_lane 5)
+    )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    (%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x65
+;;   1b: movq    %rdi, %r14
+;;       subq    $0x30, %rsp
+;;       movq    %rdi, 0x28(%rsp)
+;;       movq    %rsi, 0x20(%rsp)
+;;       movdqu  %xmm0, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       movdqu  0x10(%rsp), %xmm0
+;;       pextrw  $2, %xmm0, %r11d
+;;       andl    $0xff, %eax
+;;       andl    $0xff, %r11d
+;;       shll    $8, %eax
+;;       orl     %eax, %r11d
+;;       pinsrw  $2, %r11d, %xmm0
+;;       addq    $0x30, %rsp
+;;       popq    %rbp
+;;       retq
+;;   65: ud2
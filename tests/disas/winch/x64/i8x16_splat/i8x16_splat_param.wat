@@ -0,0 +1,31 @@
+;;! target = "x86_64"
+;;! test = "winch"
+
+(module
+    (func (param i32) (result v128)
+        (local.get 0)
+        (i8x16.splat)
+    )
+)
+;; wasm[0]::function[0]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    (%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x4c
+;;   1b: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0xc(%rsp), %eax
+;;       movd    %eax, %xmm0
+;;       punpcklbw %xmm0, %xmm0
+;;       pshuflw $0, %xmm0, %xmm0
+;;       pshufd  $0, %xmm0, %xmm0
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   4c: ud2
@@ -0,0 +1,94 @@
+;;! target = "x86_64"
+;;! test = "compile"
+;;! flags = ["-Ccompiler=tiered"]
+
+(module
+  (func $callee (param i32) (result i32)
+    (local.get 0))
+
+  (func (export "caller") (param i32) (result i32)
+    (loop $loop
+      (br_if $loop (i32.eqz (call $callee (local.get 0)))))
+    (local.get 0))
+)
+;; wasm[0]::function[0]::callee:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    (%r11), %r11
+;;       addq    $0x20, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x66
+;;   1b: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0x90(%r14), %eax
+;;       subl    $1, %eax
+;;       movl    %eax, 0x90(%r14)
+;;       cmpl    $0, %eax
+;;       jne     0x5c
+;;   4a: movq    %r14, %rdi
+;;       movl    $0, %esi
+;;       callq   0x24a
+;;       movq    0x18(%rsp), %r14
+;;       movl    0xc(%rsp), %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;   66: ud2
+;;
+;; wasm[0]::function[1]:
+;;       pushq   %rbp
+;;       movq    %rsp, %rbp
+;;       movq    8(%rdi), %r11
+;;       movq    (%r11), %r11
+;;       addq    $0x30, %r11
+;;       cmpq    %rsp, %r11
+;;       ja      0x147
+;;   8b: movq    %rdi, %r14
+;;       subq    $0x20, %rsp
+;;       movq    %rdi, 0x18(%rsp)
+;;       movq    %rsi, 0x10(%rsp)
+;;       movl    %edx, 0xc(%rsp)
+;;       movl    0x94(%r14), %eax
+;;       subl    $1, %eax
+;;       movl    %eax, 0x94(%r14)
+;;       cmpl    $0, %eax
+;;       jne     0xcc
+;;   ba: movq    %r14, %rdi
+;;       movl    $1, %esi
+;;       callq   0x24a
+;;       movq    0x18(%rsp), %r14
+;;       movl    0x94(%r14), %eax
+;;       subl    $1, %eax
+;;       movl    %eax, 0x94(%r14)
+;;       cmpl    $0, %eax
+;;       jne     0xf8
+;;   e6: movq    %r14, %rdi
+;;       movl    $1, %esi
+;;       callq   0x24a
+;;       movq    0x18(%rsp), %r14
+;;       movq    0x80(%r14), %rcx
+;;       movl    0xc(%rsp), %r11d
+;;       subq    $4, %rsp
+;;       movl    %r11d, (%rsp)
+;;       subq    $0xc, %rsp
+;;       movq    %r14, %rdi
+;;       movq    %r14, %rsi
+;;       movl    0xc(%rsp), %edx
+;;       callq   *%rcx
+;;       addq    $0xc, %rsp
+;;       addq    $4, %rsp
+;;       movq    0x18(%rsp), %r14
+;;       cmpl    $0, %eax
+;;       movl    $0, %eax
+;;       sete    %al
+;;       testl   %eax, %eax
+;;       jne     0xcc
+;;  13d: movl    0xc(%rsp), %eax
+;;       addq    $0x20, %rsp
+;;       popq    %rbp
+;;       retq
+;;  147: ud2
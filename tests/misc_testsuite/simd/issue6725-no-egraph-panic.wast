@@ -1,3 +1,5 @@
+;;! simd = true
+
 (module
   (func (param v128) (result v128)
     (i8x16.eq (local.get 0) (local.get 0))
@@ -1,3 +1,5 @@
+;;! simd = true
+
 ;; v128.const normal parameter (e.g. (i8x16, i16x8 i32x4, f32x4))
 
 (module (func (v128.const i8x16  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF  0xFF) drop))
@@ -1,3 +1,5 @@
+;;! simd = true
+
 ;; v128.load operater with normal argument (e.g. (i8x16, i16x8 i32x4))
 
 (module
@@ -26,7 +26,11 @@ fn main() {
         for compiler in [Compiler::Cranelift, Compiler::Winch] {
             for pooling in [true, false] {
                 let collectors: &[_] = if !pooling && test_uses_gc_types {
-                    &[Collector::DeferredReferenceCounting, Collector::Null]
+                    &[
+                        Collector::DeferredReferenceCounting,
+                        Collector::Null,
+                        Collector::Copying,
+                    ]
                 } else {
                     &[Collector::Auto]
                 };
@@ -77,24 +81,7 @@ fn main() {
 // function which actually executes the `wast` test suite given the `strategy`
 // to compile it.
 fn run_wast(test: &WastTest, config: WastConfig) -> anyhow::Result<()> {
-    let mut test_config = test.config.clone();
-
-    // FIXME: this is a bit of a hack to get Winch working here for now. Winch
-    // passes some tests on aarch64 so returning `true` from `should_fail`
-    // doesn't work. Winch doesn't pass many tests though as it either panics or
-    // segfaults as AArch64 support isn't finished yet. That means that we can't
-    // have, for example, an allow-list of tests that should pass and assume
-    // everything else fails. In lieu of all of this we feign all tests as
-    // requiring references types which Wasmtime understands that Winch doesn't
-    // support on aarch64 which means that all tests fail quickly in config
-    // validation.
-    //
-    // Ideally the aarch64 backend for Winch would return a normal error on
-    // unsupported opcodes and not segfault, meaning that this would not be
-    // needed.
-    if cfg!(target_arch = "aarch64") && test_config.reference_types.is_none() {
-        test_config.reference_types = Some(true);
-    }
+    let test_config = test.config.clone();
 
     // Determine whether this test is expected to fail or pass. Regardless the
     // test is executed and the result of the execution is asserted to match
@@ -162,6 +149,7 @@ fn run_wast(test: &WastTest, config: WastConfig) -> anyhow::Result<()> {
             Collector::Auto => wasmtime::Collector::Auto,
             Collector::Null => wasmtime::Collector::Null,
             Collector::DeferredReferenceCounting => wasmtime::Collector::DeferredReferenceCounting,
+            Collector::Copying => wasmtime::Collector::Copying,
         })
         .cranelift_nan_canonicalization(nan_canonicalization);
 
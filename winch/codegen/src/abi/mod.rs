@@ -48,6 +48,7 @@
 use crate::codegen::ptr_type_from_ptr_size;
 use crate::isa::{reg::Reg, CallingConvention};
 use crate::masm::SPOffset;
+use anyhow::Result;
 use smallvec::SmallVec;
 use std::collections::HashSet;
 use std::ops::{Add, BitAnd, Not, Sub};
@@ -86,7 +87,7 @@ pub(crate) use scratch;
 pub(crate) use vmctx;
 
 /// Constructs an [ABISig] using Winch's ABI.
-pub(crate) fn wasm_sig<A: ABI>(ty: &WasmFuncType) -> ABISig {
+pub(crate) fn wasm_sig<A: ABI>(ty: &WasmFuncType) -> Result<ABISig> {
     // 6 is used semi-arbitrarily here, we can modify as we see fit.
     let mut params: SmallVec<[WasmValType; 6]> = SmallVec::new();
     params.extend_from_slice(&vmctx_types::<A>());
@@ -116,19 +117,24 @@ pub(crate) trait ABI {
     /// Construct the ABI-specific signature from a WebAssembly
     /// function type.
     #[cfg(test)]
-    fn sig(wasm_sig: &WasmFuncType, call_conv: &CallingConvention) -> ABISig {
+    fn sig(wasm_sig: &WasmFuncType, call_conv: &CallingConvention) -> Result<ABISig> {
         Self::sig_from(wasm_sig.params(), wasm_sig.returns(), call_conv)
     }
 
     /// Construct an ABI signature from WasmType params and returns.
+    ///
+    /// Fails with [`CodeGenError::UnsupportedWasmType`] if any of the types
+    /// isn't supported.
+    ///
+    /// [`CodeGenError::UnsupportedWasmType`]: crate::codegen::CodeGenError::UnsupportedWasmType
     fn sig_from(
         params: &[WasmValType],
         returns: &[WasmValType],
         call_conv: &CallingConvention,
-    ) -> ABISig;
+    ) -> Result<ABISig>;
 
     /// Construct [`ABIResults`] from a slice of [`WasmType`].
-    fn abi_results(returns: &[WasmValType], call_conv: &CallingConvention) -> ABIResults;
+    fn abi_results(returns: &[WasmValType], call_conv: &CallingConvention) -> Result<ABIResults>;
 
     /// Returns the number of bits in a word.
     fn word_bits() -> u8;
@@ -148,8 +154,9 @@ pub(crate) trait ABI {
     /// The size, in bytes, of each stack slot used for stack parameter passing.
     fn stack_slot_size() -> u8;
 
-    /// Returns the size in bytes of the given [`WasmType`].
-    fn sizeof(ty: &WasmValType) -> u8;
+    /// Returns the size in bytes of the given [`WasmType`], failing if the
+    /// type isn't supported.
+    fn sizeof(ty: &WasmValType) -> Result<u8>;
 
     /// The target pointer size represented as [WasmValType].
     fn ptr_type() -> WasmValType {
@@ -330,23 +337,27 @@ impl ABIResults {
     /// representation, according to the calling convention. In the case of
     /// results, one result is stored in registers and the rest at particular
     /// offsets in the stack.
-    pub fn from<F>(returns: &[WasmValType], call_conv: &CallingConvention, mut map: F) -> Self
+    pub fn from<F>(
+        returns: &[WasmValType],
+        call_conv: &CallingConvention,
+        mut map: F,
+    ) -> Result<Self>
     where
-        F: FnMut(&WasmValType, u32) -> (ABIOperand, u32),
+        F: FnMut(&WasmValType, u32) -> Result<(ABIOperand, u32)>,
     {
         if returns.len() == 0 {
-            return Self::default();
+            return Ok(Self::default());
         }
 
         type FoldTuple = (SmallVec<[ABIOperand; 6]>, HashSet<Reg>, u32);
 
-        let fold_impl = |(mut operands, mut regs, stack_bytes): FoldTuple, arg| {
-            let (operand, bytes) = map(arg, stack_bytes);
+        let fold_impl = |(mut operands, mut regs, stack_bytes): FoldTuple, arg| -> Result<_> {
+            let (operand, bytes) = map(arg, stack_bytes)?;
             if operand.is_reg() {
                 regs.insert(operand.unwrap_reg());
             }
             operands.push(operand);
-            (operands, regs, bytes)
+            Ok((operands, regs, bytes))
         };
 
         // When dealing with multiple results, Winch's calling convention stores the
@@ -361,11 +372,11 @@ impl ABIResults {
             returns
                 .iter()
                 .rev()
-                .fold((SmallVec::new(), HashSet::with_capacity(1), 0), fold_impl)
+                .try_fold((SmallVec::new(), HashSet::with_capacity(1), 0), fold_impl)?
         } else {
             returns
                 .iter()
-                .fold((SmallVec::new(), HashSet::with_capacity(1), 0), fold_impl)
+                .try_fold((SmallVec::new(), HashSet::with_capacity(1), 0), fold_impl)?
         };
 
         // Similar to above, we reverse the result of the operands calculation
@@ -374,11 +385,11 @@ impl ABIResults {
             operands.reverse();
         }
 
-        Self::new(ABIOperands {
+        Ok(Self::new(ABIOperands {
             inner: operands,
             regs,
             bytes,
-        })
+        }))
     }
 
     /// Create a new [`ABIResults`] from [`ABIOperands`].
@@ -479,12 +490,12 @@ impl ABIParams {
         initial_bytes: u32,
         needs_stack_results: bool,
         mut map: F,
-    ) -> Self
+    ) -> Result<Self>
     where
-        F: FnMut(&WasmValType, u32) -> (ABIOperand, u32),
+        F: FnMut(&WasmValType, u32) -> Result<(ABIOperand, u32)>,
     {
         if params.len() == 0 && !needs_stack_results {
-            return Self::with_bytes(initial_bytes);
+            return Ok(Self::with_bytes(initial_bytes));
         }
 
         let register_capacity = params.len().min(6);
@@ -495,7 +506,7 @@ impl ABIParams {
         let ptr_type = ptr_type_from_ptr_size(<A as ABI>::word_bytes());
         // Handle stack results by specifying an extra, implicit first argument.
         let stack_results = if needs_stack_results {
-            let (operand, bytes) = map(&ptr_type, stack_bytes);
+            let (operand, bytes) = map(&ptr_type, stack_bytes)?;
             if operand.is_reg() {
                 regs.insert(operand.unwrap_reg());
             }
@@ -506,7 +517,7 @@ impl ABIParams {
         };
 
         for arg in params.iter() {
-            let (operand, bytes) = map(arg, stack_bytes);
+            let (operand, bytes) = map(arg, stack_bytes)?;
             if operand.is_reg() {
                 regs.insert(operand.unwrap_reg());
             }
@@ -520,14 +531,14 @@ impl ABIParams {
             operands.push(operand);
         }
 
-        Self {
+        Ok(Self {
             operands: ABIOperands {
                 inner: operands,
                 regs,
                 bytes: stack_bytes,
             },
             has_retptr: needs_stack_results,
-        }
+        })
     }
 
     /// Creates new [`ABIParams`], with the specified amount of stack bytes.
@@ -147,12 +147,19 @@ macro_rules! declare_function_sig {
                 self.pointer()
             }
 
+            /// Builds the signature of a builtin function, which only uses
+            /// integer, float and pointer types, all supported by every ABI.
+            fn sig_from<A: ABI>(&self, params: &[WasmValType], results: &[WasmValType]) -> ABISig {
+                A::sig_from(params, results, &self.call_conv)
+                    .expect("builtin function signatures only use supported types")
+            }
+
             fn over_f64<A: ABI>(&self) -> ABISig {
-                A::sig_from(&[self.f64()], &[self.f64()], &self.call_conv)
+                self.sig_from::<A>(&[self.f64()], &[self.f64()])
             }
 
             fn over_f32<A: ABI>(&self) -> ABISig {
-                A::sig_from(&[self.f64()], &[self.f64()], &self.call_conv)
+                self.sig_from::<A>(&[self.f64()], &[self.f64()])
             }
 
             pub(crate) fn ceil_f32<A: ABI>(&mut self) -> BuiltinFunction {
@@ -249,7 +256,7 @@ macro_rules! declare_function_sig {
                     if self.$name.is_none() {
                         let params = vec![ $(self.$param() ),* ];
                         let result = vec![ $(self.$result() )?];
-                        let sig = A::sig_from(&params, &result, &self.call_conv);
+                        let sig = self.sig_from::<A>(&params, &result);
                         let index = BuiltinFunctionIndex::$name();
                         let inner = Arc::new(BuiltinFunctionInner { sig, ty: BuiltinType::builtin(index) });
                         self.$name = Some(BuiltinFunction {
@@ -68,7 +68,7 @@ use crate::{
     stack::Val,
     FuncEnv,
 };
-use wasmtime_environ::{FuncIndex, PtrSize, VMOffsets};
+use wasmtime_environ::{DefinedFuncIndex, FuncIndex, PtrSize, VMOffsets};
 
 /// All the information needed to emit a function call.
 #[derive(Copy, Clone)]
@@ -82,6 +82,9 @@ impl FnCall {
     /// 4. Creates the stack space needed for the return area.
     /// 5. Emits the call.
     /// 6. Cleans up the stack space.
+    ///
+    /// The signature of the [`Callee`] must have been resolved with
+    /// [`FuncEnv::callee_sig`], unless it's a builtin function.
     pub fn emit<M: MacroAssembler>(
         env: &mut FuncEnv<M::Ptr>,
         masm: &mut M,
@@ -90,7 +93,7 @@ impl FnCall {
     ) {
         let (kind, callee_context) = Self::lower(env, context.vmoffsets, &callee, context, masm);
 
-        let sig = env.callee_sig::<M::ABI>(&callee);
+        let sig = env.resolved_callee_sig(&callee);
         context.spill(masm);
         let ret_area = Self::make_ret_area(&sig, masm);
         let arg_stack_space = sig.params_stack_size();
@@ -136,11 +139,20 @@ impl FnCall {
         match callee {
             Callee::Builtin(b) => Self::lower_builtin(env, b),
             Callee::FuncRef(_) => {
-                Self::lower_funcref(env.callee_sig::<M::ABI>(callee), ptr, context, masm)
+                Self::lower_funcref(env.resolved_callee_sig(callee), ptr, context, masm)
+            }
+            // Modules compiled for tiered compilation call their own
+            // functions through the table of tier-up function pointers in the
+            // `VMContext` rather than directly, so that the runtime can switch
+            // callees over to their optimized code.
+            Callee::Local(i) if vmoffsets.num_tier_up_counters > 0 => {
+                let def_index = env.translation.module.defined_func_index(*i).unwrap();
+                let sig = env.resolved_callee_sig(callee);
+                Self::lower_tiered_local(def_index, sig, context, masm, vmoffsets)
             }
             Callee::Local(i) => Self::lower_local(env, *i),
             Callee::Import(i) => {
-                let sig = env.callee_sig::<M::ABI>(callee);
+                let sig = env.resolved_callee_sig(callee);
                 Self::lower_import(*i, sig, context, masm, vmoffsets)
             }
         }
@@ -172,6 +184,27 @@ impl FnCall {
         )
     }
 
+    /// Lowers a local function of a module compiled for tiered compilation by
+    /// loading its current code from the `VMContext` to the next available
+    /// register.
+    fn lower_tiered_local<M: MacroAssembler, P: PtrSize>(
+        def_index: DefinedFuncIndex,
+        sig: &ABISig,
+        context: &mut CodeGenContext,
+        masm: &mut M,
+        vmoffsets: &VMOffsets<P>,
+    ) -> (CalleeKind, ContextArgs) {
+        let callee =
+            context.without::<Reg, M, _>(&sig.regs, masm, |context, masm| context.any_gpr(masm));
+        let callee_addr = masm.address_at_vmctx(vmoffsets.vmctx_tier_up_wasm_call(def_index));
+        masm.load_ptr(callee_addr, writable!(callee));
+
+        (
+            CalleeKind::indirect(callee),
+            ContextArgs::pinned_callee_and_caller_vmctx(),
+        )
+    }
+
     /// Lowers a function import by loading its address to the next available
     /// register.
     fn lower_import<M: MacroAssembler, P: PtrSize>(
@@ -253,6 +253,26 @@ impl<'a> CodeGenContext<'a> {
         self.stack.push(dst.into());
     }
 
+    /// Prepares arguments for emitting a ternary operation.
+    ///
+    /// The operands are passed to `emit` in the order in which they were
+    /// pushed to the value stack. The `emit` function returns the `TypedReg`
+    /// to put on the value stack; the registers of the second and third
+    /// operands are freed after emission.
+    pub fn ternop<F, M>(&mut self, masm: &mut M, mut emit: F)
+    where
+        F: FnMut(&mut M, Reg, Reg, Reg) -> TypedReg,
+        M: MacroAssembler,
+    {
+        let c = self.pop_to_reg(masm, None);
+        let b = self.pop_to_reg(masm, None);
+        let a = self.pop_to_reg(masm, None);
+        let dst = emit(masm, a.reg, b.reg, c.reg);
+        self.free_reg(b);
+        self.free_reg(c);
+        self.stack.push(dst.into());
+    }
+
     /// Prepares arguments for emitting an f32 or f64 comparison operation.
     pub fn float_cmp_op<F, M>(&mut self, masm: &mut M, size: OperandSize, mut emit: F)
     where
@@ -389,7 +409,7 @@ impl<'a> CodeGenContext<'a> {
             WasmValType::I64 => OperandSize::S64,
             WasmValType::F32 => OperandSize::S32,
             WasmValType::F64 => OperandSize::S64,
-            WasmValType::V128 => unreachable!(),
+            WasmValType::V128 => OperandSize::S128,
             WasmValType::Ref(_) => unreachable!(),
         };
 
@@ -14,6 +14,7 @@ use crate::{
     stack::Val,
     CallingConvention,
 };
+use anyhow::Result;
 use cranelift_codegen::MachLabel;
 use wasmtime_environ::{WasmFuncType, WasmValType};
 
@@ -35,90 +36,67 @@ pub(crate) enum BlockType {
 pub(crate) struct BlockSig {
     /// The type of the block.
     pub ty: BlockType,
-    /// ABI representation of the results of the block.
-    results: Option<ABIResults>,
+    /// ABI representation of the results of the block. Unused for
+    /// [`BlockType::ABISig`], which holds its own results.
+    results: ABIResults,
     /// ABI representation of the params of the block interpreted as results.
-    params: Option<ABIResults>,
+    params: ABIResults,
 }
 
 impl BlockSig {
-    /// Create a new [BlockSig].
-    pub fn new(ty: BlockType) -> Self {
-        Self {
+    /// Create a new [BlockSig], failing if the block's params or results
+    /// aren't supported by the ABI.
+    pub fn new<A: ABI>(ty: BlockType) -> Result<Self> {
+        let (results, params) = match &ty {
+            BlockType::Void => (
+                A::abi_results(&[], &CallingConvention::Default)?,
+                A::abi_results(&[], &CallingConvention::Default)?,
+            ),
+            BlockType::Single(ty) => (
+                A::abi_results(&[*ty], &CallingConvention::Default)?,
+                A::abi_results(&[], &CallingConvention::Default)?,
+            ),
+            BlockType::Func(f) => (
+                A::abi_results(f.returns(), &CallingConvention::Default)?,
+                A::abi_results(f.params(), &CallingConvention::Default)?,
+            ),
+            BlockType::ABISig(_) => unreachable!(),
+        };
+        Ok(Self {
             ty,
-            results: None,
-            params: None,
-        }
+            results,
+            params,
+        })
     }
 
     /// Create a new [BlockSig] from an [ABISig].
     pub fn from_sig(sig: ABISig) -> Self {
         Self {
             ty: BlockType::sig(sig),
-            results: None,
-            params: None,
+            results: Default::default(),
+            params: Default::default(),
         }
     }
 
     /// Return the ABI representation of the results of the block.
-    /// This method will lazily initialize the results if not present.
-    pub fn results<M>(&mut self) -> &mut ABIResults
-    where
-        M: MacroAssembler,
-    {
-        if self.ty.is_sig() {
-            return match &mut self.ty {
-                BlockType::ABISig(sig) => &mut sig.results,
-                _ => unreachable!(),
-            };
+    pub fn results(&mut self) -> &mut ABIResults {
+        match &mut self.ty {
+            BlockType::ABISig(sig) => &mut sig.results,
+            _ => &mut self.results,
         }
-
-        if self.results.is_some() {
-            return self.results.as_mut().unwrap();
-        }
-
-        let results = match &self.ty {
-            BlockType::Void => <M::ABI as ABI>::abi_results(&[], &CallingConvention::Default),
-            BlockType::Single(ty) => {
-                <M::ABI as ABI>::abi_results(&[*ty], &CallingConvention::Default)
-            }
-            BlockType::Func(f) => {
-                <M::ABI as ABI>::abi_results(f.returns(), &CallingConvention::Default)
-            }
-            BlockType::ABISig(_) => unreachable!(),
-        };
-
-        self.results = Some(results);
-        self.results.as_mut().unwrap()
     }
 
-    /// Construct an ABI result representation of the params of the block.
+    /// Return the ABI representation of the params of the block interpreted
+    /// as results.
     /// This is needed for loops and for handling cases in which params flow as
     /// the block's results, i.e. in the presence of an empty then or else.
-    pub fn params<M>(&mut self) -> &mut ABIResults
-    where
-        M: MacroAssembler,
-    {
-        if self.params.is_some() {
-            return self.params.as_mut().unwrap();
-        }
-
-        let params_as_results = match &self.ty {
-            BlockType::Void | BlockType::Single(_) => {
-                <M::ABI as ABI>::abi_results(&[], &CallingConvention::Default)
-            }
-            BlockType::Func(f) => {
-                <M::ABI as ABI>::abi_results(f.params(), &CallingConvention::Default)
-            }
-            // Once we have created a block type from a known signature, we
-            // can't modify its meaning. This should only be used for the
-            // function body block, in which case there's no need for treating
-            // params as results.
-            BlockType::ABISig(_) => unreachable!(),
-        };
-
-        self.params = Some(params_as_results);
-        self.params.as_mut().unwrap()
+    pub fn params(&mut self) -> &mut ABIResults {
+        // Once we have created a block type from a known signature, we
+        // can't modify its meaning. This should only be used for the
+        // function body block, in which case there's no need for treating
+        // params as results.
+        assert!(!self.ty.is_sig());
+        &mut self.params
     }
 
     /// Returns the signature param count.
@@ -299,9 +277,9 @@ impl ControlStackFrame {
         self.calculate_stack_state(context, masm);
         // If the block has stack results, immediately resolve the return area
         // base.
-        if self.results::<M>().on_stack() {
+        if self.results().on_stack() {
             let results_base = self.stack_state().target_offset;
-            self.results::<M>().set_ret_area(RetArea::sp(results_base));
+            self.results().set_ret_area(RetArea::sp(results_base));
         }
 
         if self.is_if() || self.is_loop() {
@@ -319,13 +297,13 @@ impl ControlStackFrame {
             //   )
             //)
             let base_offset = self.stack_state().base_offset;
-            if self.params::<M>().on_stack() {
-                let offset = base_offset.as_u32() + self.params::<M>().size();
-                self.params::<M>()
+            if self.params().on_stack() {
+                let offset = base_offset.as_u32() + self.params().size();
+                self.params()
                     .set_ret_area(RetArea::sp(SPOffset::from_u32(offset)));
             }
             Self::top_abi_results_impl(
-                self.params::<M>(),
+                self.params(),
                 context,
                 masm,
                 |params: &ABIResults, _, _| params.ret_area().copied(),
@@ -352,7 +330,7 @@ impl ControlStackFrame {
         };
         let return_count = sig.return_count();
         debug_assert!(context.stack.len() >= param_count);
-        let results_size = self.results::<M>().size();
+        let results_size = self.results().size();
 
         // Save any live registers and locals.
         context.spill(masm);
@@ -430,10 +408,9 @@ impl ControlStackFrame {
                 // Because in the case of Self::If, Self::init, will top the
                 // branch params, we exclude any result registers from being
                 // used as the branch test.
-                let top =
-                    context.without::<_, _, _>(self.params::<M>().regs(), masm, |cx, masm| {
-                        cx.pop_to_reg(masm, None)
-                    });
+                let top = context.without::<_, _, _>(self.params().regs(), masm, |cx, masm| {
+                    cx.pop_to_reg(masm, None)
+                });
                 self.init(masm, context);
                 masm.branch(
                     IntCmpKind::Eq,
@@ -489,8 +466,8 @@ impl ControlStackFrame {
                 // resets the stack pointer so that it matches the expectations
                 // of the else branch: the stack pointer is expected to be at
                 // the base stack pointer, plus the params stack size in bytes.
-                let params_size = sig.params::<M>().size();
-                context.push_abi_results::<M, _>(sig.params::<M>(), masm, |params, _, _| {
+                let params_size = sig.params().size();
+                context.push_abi_results::<M, _>(sig.params(), masm, |params, _, _| {
                     params.ret_area().copied()
                 });
                 masm.reset_stack_pointer(SPOffset::from_u32(
@@ -584,27 +561,21 @@ impl ControlStackFrame {
 
     /// Returns [`crate::abi::ABIResults`] of the control stack frame
     /// block.
-    pub fn results<M>(&mut self) -> &mut ABIResults
-    where
-        M: MacroAssembler,
-    {
+    pub fn results(&mut self) -> &mut ABIResults {
         use ControlStackFrame::*;
 
         match self {
-            If { sig, .. } | Else { sig, .. } | Block { sig, .. } => sig.results::<M>(),
-            Loop { sig, .. } => sig.params::<M>(),
+            If { sig, .. } | Else { sig, .. } | Block { sig, .. } => sig.results(),
+            Loop { sig, .. } => sig.params(),
         }
     }
 
     /// Returns the block params interpreted as [crate::abi::ABIResults].
-    pub fn params<M>(&mut self) -> &mut ABIResults
-    where
-        M: MacroAssembler,
-    {
+    pub fn params(&mut self) -> &mut ABIResults {
         use ControlStackFrame::*;
         match self {
             If { sig, .. } | Else { sig, .. } | Block { sig, .. } | Loop { sig, .. } => {
-                sig.params::<M>()
+                sig.params()
             }
         }
     }
@@ -634,7 +605,7 @@ impl ControlStackFrame {
         M: MacroAssembler,
         F: FnMut(&ABIResults, &mut CodeGenContext, &mut M) -> Option<RetArea>,
     {
-        Self::pop_abi_results_impl(self.results::<M>(), context, masm, calculate_ret_area)
+        Self::pop_abi_results_impl(self.results(), context, masm, calculate_ret_area)
     }
 
     /// Shared implementation for poppping the ABI results.
@@ -694,7 +665,7 @@ impl ControlStackFrame {
     where
         M: MacroAssembler,
     {
-        context.push_abi_results(self.results::<M>(), masm, |results, _, _| {
+        context.push_abi_results(self.results(), masm, |results, _, _| {
             results.ret_area().copied()
         })
     }
@@ -712,7 +683,7 @@ impl ControlStackFrame {
         M: MacroAssembler,
         F: FnMut(&ABIResults, &mut CodeGenContext, &mut M) -> Option<RetArea>,
     {
-        Self::top_abi_results_impl::<M, _>(self.results::<M>(), context, masm, calculate_ret_area)
+        Self::top_abi_results_impl::<M, _>(self.results(), context, masm, calculate_ret_area)
     }
 
     /// Internal implementation of [Self::top_abi_results].
@@ -3,6 +3,7 @@ use crate::{
     codegen::{control, BlockSig, BuiltinFunction, BuiltinFunctions, OperandSize},
     isa::TargetIsa,
 };
+use anyhow::Result;
 use cranelift_codegen::ir::{UserExternalName, UserExternalNameRef};
 use std::collections::{
     hash_map::Entry::{Occupied, Vacant},
@@ -178,18 +179,18 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
     }
 
     /// Converts a [wasmparser::BlockType] into a [BlockSig].
-    pub(crate) fn resolve_block_sig(&self, ty: BlockType) -> BlockSig {
+    pub(crate) fn resolve_block_sig<A: ABI>(&self, ty: BlockType) -> Result<BlockSig> {
         use BlockType::*;
         match ty {
-            Empty => BlockSig::new(control::BlockType::void()),
+            Empty => BlockSig::new::<A>(control::BlockType::void()),
             Type(ty) => {
                 let ty = TypeConverter::new(self.translation, self.types).convert_valtype(ty);
-                BlockSig::new(control::BlockType::single(ty))
+                BlockSig::new::<A>(control::BlockType::single(ty))
             }
             FuncType(idx) => {
                 let sig_index = self.translation.module.types[TypeIndex::from_u32(idx)];
                 let sig = self.types[sig_index].unwrap_func();
-                BlockSig::new(control::BlockType::func(sig.clone()))
+                BlockSig::new::<A>(control::BlockType::func(sig.clone()))
             }
         }
     }
@@ -302,31 +303,46 @@ impl<'a, 'translation, 'data, P: PtrSize> FuncEnv<'a, 'translation, 'data, P> {
         self.table_access_spectre_mitigation
     }
 
-    pub(crate) fn callee_sig<'b, A>(&'b mut self, callee: &'b Callee) -> &'b ABISig
+    /// Resolves the ABI signature of `callee`, failing if its type isn't
+    /// supported.
+    ///
+    /// The signature of a callee other than a builtin function must be
+    /// resolved with this method before the call is emitted with
+    /// [`crate::codegen::FnCall::emit`].
+    pub(crate) fn callee_sig<'b, A>(&'b mut self, callee: &'b Callee) -> Result<&'b ABISig>
     where
         A: ABI,
     {
         match callee {
             Callee::Local(idx) | Callee::Import(idx) => {
-                let types = self.translation.get_types();
-                let types = types.as_ref();
-                let ty = types[types.core_function_at(idx.as_u32())].unwrap_func();
-                let val = || {
+                if !self.resolved_callees.contains_key(idx) {
+                    let types = self.translation.get_types();
+                    let types = types.as_ref();
+                    let ty = types[types.core_function_at(idx.as_u32())].unwrap_func();
                     let converter = TypeConverter::new(self.translation, self.types);
                     let ty = converter.convert_func_type(&ty);
-                    wasm_sig::<A>(&ty)
-                };
-                self.resolved_callees.entry(*idx).or_insert_with(val)
+                    self.resolved_callees.insert(*idx, wasm_sig::<A>(&ty)?);
+                }
+                Ok(&self.resolved_callees[idx])
             }
             Callee::FuncRef(idx) => {
-                let val = || {
+                if !self.resolved_sigs.contains_key(idx) {
                     let sig_index = self.translation.module.types[*idx];
                     let ty = self.types[sig_index].unwrap_func();
-                    let sig = wasm_sig::<A>(ty);
-                    sig
-                };
-                self.resolved_sigs.entry(*idx).or_insert_with(val)
+                    self.resolved_sigs.insert(*idx, wasm_sig::<A>(ty)?);
+                }
+                Ok(&self.resolved_sigs[idx])
             }
+            Callee::Builtin(b) => Ok(b.sig()),
+        }
+    }
+
+    /// Returns the ABI signature of `callee`, which must have been resolved
+    /// with [`FuncEnv::callee_sig`] unless it's a builtin function.
+    pub(crate) fn resolved_callee_sig<'b>(&'b self, callee: &'b Callee) -> &'b ABISig {
+        match callee {
+            Callee::Local(idx) | Callee::Import(idx) => &self.resolved_callees[idx],
+            Callee::FuncRef(idx) => &self.resolved_sigs[idx],
             Callee::Builtin(b) => b.sig(),
         }
     }
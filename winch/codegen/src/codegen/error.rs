@@ -0,0 +1,27 @@
+use std::fmt;
+
+/// Errors raised while generating code for a function that Winch can't
+/// compile.
+#[derive(Debug)]
+pub(crate) enum CodeGenError {
+    /// A WebAssembly value type that Winch doesn't support, for example a
+    /// reference to a heap type other than `func` or `extern`.
+    UnsupportedWasmType,
+}
+
+impl CodeGenError {
+    /// Returns the error for an unsupported WebAssembly value type.
+    pub(crate) fn unsupported_wasm_type() -> Self {
+        Self::UnsupportedWasmType
+    }
+}
+
+impl fmt::Display for CodeGenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedWasmType => write!(f, "unsupported Wasm type in Winch"),
+        }
+    }
+}
+
+impl std::error::Error for CodeGenError {}
@@ -18,8 +18,8 @@ use wasmparser::{
 };
 use wasmtime_cranelift::{TRAP_BAD_SIGNATURE, TRAP_TABLE_OUT_OF_BOUNDS};
 use wasmtime_environ::{
-    GlobalIndex, MemoryIndex, PtrSize, TableIndex, Tunables, TypeIndex, WasmHeapType, WasmValType,
-    FUNCREF_MASK,
+    DefinedFuncIndex, GlobalIndex, MemoryIndex, PtrSize, TableIndex, Tunables, TypeIndex,
+    WasmHeapType, WasmValType, FUNCREF_MASK,
 };
 
 mod context;
@@ -32,6 +32,8 @@ mod control;
 pub(crate) use control::*;
 mod builtin;
 pub use builtin::*;
+mod error;
+pub(crate) use error::*;
 pub(crate) mod bounds;
 
 use bounds::{Bounds, ImmOffset, Index};
@@ -77,11 +79,18 @@ where
     /// was found.
     pub found_unsupported_instruction: Option<&'static str>,
 
+    /// Error found while translating the current instruction, such as an
+    /// unsupported type in its signature.
+    pub found_error: Option<anyhow::Error>,
+
     /// Compilation settings for code generation.
     pub tunables: &'a Tunables,
 
     /// Local counter to track fuel consumption.
     pub fuel_consumed: i64,
+
+    /// The index of the function being compiled.
+    pub func_index: DefinedFuncIndex,
 }
 
 impl<'a, 'translation, 'data, M> CodeGen<'a, 'translation, 'data, M>
@@ -94,6 +103,7 @@ where
         context: CodeGenContext<'a>,
         env: FuncEnv<'a, 'translation, 'data, M::Ptr>,
         sig: ABISig,
+        func_index: DefinedFuncIndex,
     ) -> Self {
         Self {
             sig,
@@ -104,8 +114,10 @@ where
             source_location: Default::default(),
             control_frames: Default::default(),
             found_unsupported_instruction: None,
+            found_error: None,
             // Empty functions should consume at least 1 fuel unit.
             fuel_consumed: 1,
+            func_index,
         }
     }
 
@@ -122,6 +134,18 @@ where
         Ok(())
     }
 
+    /// Returns the value of `result`, or records its error to be reported once
+    /// the current instruction has been visited.
+    pub fn record_error<T>(&mut self, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(val) => Some(val),
+            Err(e) => {
+                self.found_error = Some(e);
+                None
+            }
+        }
+    }
+
     /// Derives a [RelSourceLoc] from a [SourceLoc].
     pub fn source_loc_from(&mut self, loc: SourceLoc) -> RelSourceLoc {
         if self.source_location.base.is_none() && !loc.is_default() {
@@ -242,6 +266,12 @@ where
             }
         });
 
+        // The tier-up check calls into the runtime, so it must happen after
+        // the register arguments have been saved.
+        if self.tunables.tiered_compilation {
+            self.emit_tier_up_check();
+        }
+
         while !body.eof() {
             let offset = body.original_position();
             body.visit_operator(&mut ValidateThenVisit(
@@ -253,6 +283,9 @@ where
             if let Some(insn) = self.found_unsupported_instruction {
                 anyhow::bail!("unsupported instruction in Winch: {insn}")
             }
+            if let Some(e) = self.found_error.take() {
+                return Err(e);
+            }
         }
         validator.finish(body.original_position())?;
         return Ok(());
@@ -266,6 +299,9 @@ where
                         self.0.$visit($($($arg.clone()),*)?)?;
                         let op = Operator::$op $({ $($arg: $arg.clone()),* })?;
                         if self.1.visit(&op) {
+                            if !self.1.supported(stringify!($proposal), &op, stringify!($op)) {
+                                return Ok(U::Output::default());
+                            }
                             self.1.before_visit_op(&op, self.2);
                             let res = Ok(self.1.$visit($($($arg),*)?));
                             self.1.after_visit_op();
@@ -303,6 +339,11 @@ where
             ///   visited in order to keep the control stack frames balanced and
             ///   to determine if the reachability state must be restored.
             fn visit(&self, op: &Operator) -> bool;
+
+            /// Returns `true` if the target can compile the operator from
+            /// the given proposal, recording `name` as an unsupported
+            /// instruction otherwise.
+            fn supported(&mut self, proposal: &str, op: &Operator, name: &'static str) -> bool;
         }
 
         impl<'a, 'translation, 'data, M: MacroAssembler> VisitorHooks
@@ -312,6 +353,17 @@ where
                 self.context.reachable || visit_op_when_unreachable(op)
             }
 
+            fn supported(&mut self, proposal: &str, op: &Operator, name: &'static str) -> bool {
+                let supported = match proposal {
+                    "simd" | "relaxed_simd" => self.masm.supports_v128_op(op),
+                    _ => true,
+                };
+                if !supported {
+                    self.found_unsupported_instruction = Some(name);
+                }
+                supported
+            }
+
             fn before_visit_op(&mut self, operator: &Operator, offset: usize) {
                 // Handle source location mapping.
                 self.source_location_before_visit_op(offset);
@@ -953,6 +1005,57 @@ where
         self.context.free_reg(fuel_var);
     }
 
+    /// Emits a series of instructions that decrement the tier-up counter of
+    /// the function being compiled, notifying the runtime through the
+    /// `tier_up` builtin once the counter reaches zero.
+    pub fn emit_tier_up_check(&mut self) {
+        let offset = self.env.vmoffsets.vmctx_tier_up_counter(self.func_index);
+        let counter = self.context.any_gpr(self.masm);
+        let continuation = self.masm.get_label();
+
+        self.masm.load(
+            self.masm.address_at_vmctx(offset),
+            writable!(counter),
+            OperandSize::S32,
+        );
+        self.masm.sub(
+            writable!(counter),
+            counter,
+            RegImm::i32(1),
+            OperandSize::S32,
+        );
+        self.masm.store(
+            counter.into(),
+            self.masm.address_at_vmctx(offset),
+            OperandSize::S32,
+        );
+
+        // Spill locals and registers to avoid conflicts at the tier-up
+        // control flow merge.
+        self.context.spill(self.masm);
+        self.masm.branch(
+            IntCmpKind::Ne,
+            counter,
+            RegImm::i32(0),
+            continuation,
+            OperandSize::S32,
+        );
+        self.context.free_reg(counter);
+
+        // Hot function branch.
+        self.context
+            .stack
+            .extend([self.func_index.as_u32().try_into().unwrap()]);
+        let tier_up = self.env.builtins.tier_up::<M::ABI, M::Ptr>();
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(tier_up),
+        );
+        self.masm.bind(continuation);
+    }
+
     /// Increments the fuel consumed in `VMRuntimeLimits` by flushing
     /// `self.fuel_consumed` to memory.
     fn emit_fuel_increment(&mut self) {
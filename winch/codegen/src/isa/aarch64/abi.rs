@@ -1,8 +1,10 @@
 use super::regs;
 use crate::abi::{align_to, ABIOperand, ABIParams, ABIResults, ABISig, ParamsOrReturns, ABI};
+use crate::codegen::CodeGenError;
 use crate::isa::{reg::Reg, CallingConvention};
 use crate::RegIndexEnv;
-use wasmtime_environ::{WasmHeapType, WasmRefType, WasmValType};
+use anyhow::Result;
+use wasmtime_environ::{WasmHeapType, WasmValType};
 
 #[derive(Default)]
 pub(crate) struct Aarch64ABI;
@@ -29,14 +31,14 @@ impl ABI for Aarch64ABI {
         params: &[WasmValType],
         returns: &[WasmValType],
         call_conv: &CallingConvention,
-    ) -> ABISig {
+    ) -> Result<ABISig> {
         assert!(call_conv.is_apple_aarch64() || call_conv.is_default());
         // The first element tracks the general purpose register index, capped at 7 (x0-x7).
         // The second element tracks the floating point register index, capped at 7 (v0-v7).
         // Follows
         // https://github.com/ARM-software/abi-aa/blob/2021Q1/aapcs64/aapcs64.rst#64parameter-passing
         let mut params_index_env = RegIndexEnv::with_limits_per_class(8, 8);
-        let results = Self::abi_results(returns, call_conv);
+        let results = Self::abi_results(returns, call_conv)?;
         let params =
             ABIParams::from::<_, Self>(params, 0, results.on_stack(), |ty, stack_offset| {
                 Self::to_abi_operand(
@@ -46,12 +48,12 @@ impl ABI for Aarch64ABI {
                     call_conv,
                     ParamsOrReturns::Params,
                 )
-            });
+            })?;
 
-        ABISig::new(params, results)
+        Ok(ABISig::new(params, results))
     }
 
-    fn abi_results(returns: &[WasmValType], call_conv: &CallingConvention) -> ABIResults {
+    fn abi_results(returns: &[WasmValType], call_conv: &CallingConvention) -> Result<ABIResults> {
         assert!(call_conv.is_apple_aarch64() || call_conv.is_default());
         // Use absolute count for results given that for Winch's
         // default CallingConvention only one register is used for results
@@ -73,14 +75,8 @@ impl ABI for Aarch64ABI {
 
     fn scratch_for(ty: &WasmValType) -> Reg {
         match ty {
-            WasmValType::I32
-            | WasmValType::I64
-            | WasmValType::Ref(WasmRefType {
-                heap_type: WasmHeapType::Func,
-                ..
-            }) => regs::scratch(),
+            WasmValType::I32 | WasmValType::I64 | WasmValType::Ref(_) => regs::scratch(),
             WasmValType::F32 | WasmValType::F64 | WasmValType::V128 => regs::float_scratch(),
-            _ => unimplemented!(),
         }
     }
 
@@ -92,16 +88,16 @@ impl ABI for Aarch64ABI {
         Self::word_bytes()
     }
 
-    fn sizeof(ty: &WasmValType) -> u8 {
-        match ty {
+    fn sizeof(ty: &WasmValType) -> Result<u8> {
+        Ok(match ty {
             WasmValType::Ref(rt) => match rt.heap_type {
-                WasmHeapType::Func => Self::word_bytes(),
-                ht => unimplemented!("Support for WasmHeapType: {ht}"),
+                WasmHeapType::Func | WasmHeapType::Extern => Self::word_bytes(),
+                _ => return Err(CodeGenError::unsupported_wasm_type().into()),
             },
             WasmValType::F64 | WasmValType::I64 => Self::word_bytes(),
             WasmValType::F32 | WasmValType::I32 => Self::word_bytes() / 2,
-            ty => unimplemented!("Support for WasmType: {ty}"),
-        }
+            WasmValType::V128 => Self::word_bytes() * 2,
+        })
     }
 }
 
@@ -112,20 +108,20 @@ impl Aarch64ABI {
         index_env: &mut RegIndexEnv,
         call_conv: &CallingConvention,
         params_or_returns: ParamsOrReturns,
-    ) -> (ABIOperand, u32) {
+    ) -> Result<(ABIOperand, u32)> {
+        // Check that the type is supported before assigning it a register.
+        let ty_size = <Self as ABI>::sizeof(wasm_arg)?;
         let (reg, ty) = match wasm_arg {
-            ty @ (WasmValType::I32 | WasmValType::I64) => {
+            ty @ (WasmValType::I32 | WasmValType::I64 | WasmValType::Ref(_)) => {
                 (index_env.next_gpr().map(regs::xreg), ty)
             }
 
-            ty @ (WasmValType::F32 | WasmValType::F64) => {
+            // v128 also uses a vector register (that is, an fpr).
+            ty @ (WasmValType::F32 | WasmValType::F64 | WasmValType::V128) => {
                 (index_env.next_fpr().map(regs::vreg), ty)
             }
-
-            ty => unreachable!("Unsupported argument type {:?}", ty),
         };
 
-        let ty_size = <Self as ABI>::sizeof(wasm_arg);
         let default = || {
             let arg = ABIOperand::stack_offset(stack_offset, *ty, ty_size as u32);
             let slot_size = Self::stack_slot_size();
@@ -145,9 +141,9 @@ impl Aarch64ABI {
             };
             (arg, next_stack)
         };
-        reg.map_or_else(default, |reg| {
+        Ok(reg.map_or_else(default, |reg| {
             (ABIOperand::reg(reg, *ty, ty_size as u32), stack_offset)
-        })
+        }))
     }
 }
 
@@ -161,7 +157,7 @@ mod tests {
         isa::CallingConvention,
     };
     use wasmtime_environ::{
-        WasmFuncType,
+        WasmFuncType, WasmHeapType, WasmRefType,
         WasmValType::{self, *},
     };
 
@@ -172,7 +168,7 @@ mod tests {
             [].into(),
         );
 
-        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), I32, regs::xreg(0));
@@ -193,7 +189,7 @@ mod tests {
             [].into(),
         );
 
-        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::vreg(0));
@@ -214,7 +210,7 @@ mod tests {
             [].into(),
         );
 
-        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::vreg(0));
@@ -235,7 +231,7 @@ mod tests {
             [I32, I32, I32].into(),
         );
 
-        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
         let results = sig.results;
 
@@ -258,7 +254,7 @@ mod tests {
             [I32, F32, I32, F32, I64].into(),
         );
 
-        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
         let results = sig.results;
 
@@ -275,7 +271,36 @@ mod tests {
         match_reg_arg(results.get(4).unwrap(), I64, regs::xreg(0));
     }
 
+    #[test]
+    fn ref_and_v128_abi_sig() {
+        let funcref = Ref(WasmRefType::FUNCREF);
+        let externref = Ref(WasmRefType::EXTERNREF);
+        let wasm_sig = WasmFuncType::new([funcref, V128, externref, F32].into(), [V128].into());
+
+        let sig = Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
+        let params = sig.params;
+        let results = sig.results;
+
+        match_reg_arg(params.get(0).unwrap(), funcref, regs::xreg(0));
+        match_reg_arg(params.get(1).unwrap(), V128, regs::vreg(0));
+        match_reg_arg(params.get(2).unwrap(), externref, regs::xreg(1));
+        match_reg_arg(params.get(3).unwrap(), F32, regs::vreg(1));
+
+        match_reg_arg(results.get(0).unwrap(), V128, regs::vreg(0));
+    }
+
     #[track_caller]
+    #[test]
+    fn unsupported_ref_abi_sig() {
+        let anyref = Ref(WasmRefType {
+            nullable: true,
+            heap_type: WasmHeapType::Any,
+        });
+        let wasm_sig = WasmFuncType::new([I32, anyref].into(), [].into());
+
+        assert!(Aarch64ABI::sig(&wasm_sig, &CallingConvention::Default).is_err());
+    }
+
     fn match_reg_arg(abi_arg: &ABIOperand, expected_ty: WasmValType, expected_reg: Reg) {
         match abi_arg {
             &ABIOperand::Reg { reg, ty, .. } => {
@@ -1,16 +1,19 @@
 //! Assembler library implementation for Aarch64.
 
 use super::{address::Address, regs};
-use crate::masm::{ExtendKind, FloatCmpKind, IntCmpKind, RoundingMode, ShiftKind};
+use crate::masm::{
+    DivKind, ExtendKind, FloatCmpKind, IntCmpKind, MulWideKind, RemKind, RoundingMode, ShiftKind,
+};
 use crate::{
     masm::OperandSize,
     reg::{writable, Reg, WritableReg},
 };
-use cranelift_codegen::ir::TrapCode;
+use cranelift_codegen::ir::{ExternalName, LibCall, TrapCode, UserExternalNameRef};
 use cranelift_codegen::isa::aarch64::inst::{
     BitOp, BranchTarget, Cond, CondBrKind, FPULeftShiftImm, FPUOp1, FPUOp2,
     FPUOpRI::{self, UShr32, UShr64},
-    FPUOpRIMod, FPURightShiftImm, FpuRoundMode, ImmLogic, ImmShift, ScalarSize,
+    FPUOpRIMod, FPURightShiftImm, FpuRoundMode, FpuToIntOp, ImmLogic, ImmShift, IntToFpuOp,
+    ScalarSize, UImm5, NZCV,
 };
 use cranelift_codegen::{
     ir::{MemFlags, SourceLoc},
@@ -19,8 +22,9 @@ use cranelift_codegen::{
         emit::{EmitInfo, EmitState},
         ALUOp, ALUOp3, AMode, ExtendOp, Imm12, Inst, PairAMode, VecLanesOp, VecMisc2, VectorSize,
     },
-    settings, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit, MachInstEmitState,
-    MachLabel, Writable,
+    isa::CallConv,
+    settings, CallInfo, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit,
+    MachInstEmitState, MachLabel, PatchRegion, Writable,
 };
 
 impl From<OperandSize> for inst::OperandSize {
@@ -145,9 +149,8 @@ impl Assembler {
     }
 
     /// Store a register.
-    pub fn str(&mut self, reg: Reg, addr: Address, size: OperandSize) {
+    pub fn str(&mut self, reg: Reg, addr: Address, size: OperandSize, flags: MemFlags) {
         let mem: AMode = addr.try_into().unwrap();
-        let flags = MemFlags::trusted();
 
         use OperandSize::*;
         let inst = match (reg.is_int(), size) {
@@ -192,21 +195,27 @@ impl Assembler {
     }
 
     /// Load a signed register.
-    pub fn sload(&mut self, addr: Address, rd: WritableReg, size: OperandSize) {
-        self.ldr(addr, rd, size, true);
+    pub fn sload(&mut self, addr: Address, rd: WritableReg, size: OperandSize, flags: MemFlags) {
+        self.ldr(addr, rd, size, true, flags);
     }
 
     /// Load an unsigned register.
-    pub fn uload(&mut self, addr: Address, rd: WritableReg, size: OperandSize) {
-        self.ldr(addr, rd, size, false);
+    pub fn uload(&mut self, addr: Address, rd: WritableReg, size: OperandSize, flags: MemFlags) {
+        self.ldr(addr, rd, size, false, flags);
     }
 
     /// Load a register.
-    fn ldr(&mut self, addr: Address, rd: WritableReg, size: OperandSize, signed: bool) {
+    fn ldr(
+        &mut self,
+        addr: Address,
+        rd: WritableReg,
+        size: OperandSize,
+        signed: bool,
+        flags: MemFlags,
+    ) {
         use OperandSize::*;
         let writable_reg = rd.map(Into::into);
         let mem: AMode = addr.try_into().unwrap();
-        let flags = MemFlags::trusted();
 
         let inst = match (rd.to_reg().is_int(), signed, size) {
             (_, false, S8) => Inst::ULoad8 {
@@ -300,6 +309,10 @@ impl Assembler {
                 rd: writable,
                 rn: rn.into(),
             },
+            OperandSize::S128 => Inst::FpuMove128 {
+                rd: writable,
+                rn: rn.into(),
+            },
             _ => unreachable!(),
         };
 
@@ -324,6 +337,17 @@ impl Assembler {
         });
     }
 
+    /// Move a register into a vector element.
+    pub fn mov_to_vec(&mut self, rn: Reg, rd: WritableReg, idx: u8, size: VectorSize) {
+        self.emit(Inst::MovToVec {
+            rd: rd.map(Into::into),
+            ri: rd.to_reg().into(),
+            rn: rn.into(),
+            idx,
+            size,
+        });
+    }
+
     /// Add with three registers.
     pub fn add_rrr(&mut self, rm: Reg, rn: Reg, rd: WritableReg, size: OperandSize) {
         self.emit_alu_rrr_extend(ALUOp::Add, rm, rn, rd, size);
@@ -341,6 +365,51 @@ impl Assembler {
         }
     }
 
+    /// Add with three registers, setting flags.
+    pub fn adds_rrr(&mut self, rm: Reg, rn: Reg, rd: WritableReg, size: OperandSize) {
+        self.emit_alu_rrr_extend(ALUOp::AddS, rm, rn, rd, size);
+    }
+
+    /// Add immediate and register, setting flags.
+    pub fn adds_ir(&mut self, imm: u64, rn: Reg, rd: WritableReg, size: OperandSize) {
+        let alu_op = ALUOp::AddS;
+        if let Some(imm) = Imm12::maybe_from_u64(imm) {
+            self.emit_alu_rri(alu_op, imm, rn, rd, size);
+        } else {
+            let scratch = regs::scratch();
+            self.load_constant(imm, writable!(scratch));
+            self.emit_alu_rrr_extend(alu_op, scratch, rn, rd, size);
+        }
+    }
+
+    /// 128-bit addition of two register pairs.
+    pub fn add128(
+        &mut self,
+        dst_lo: WritableReg,
+        dst_hi: WritableReg,
+        lhs_lo: Reg,
+        lhs_hi: Reg,
+        rhs_lo: Reg,
+        rhs_hi: Reg,
+    ) {
+        self.emit_alu_rrr(ALUOp::AddS, rhs_lo, lhs_lo, dst_lo, OperandSize::S64);
+        self.emit_alu_rrr(ALUOp::Adc, rhs_hi, lhs_hi, dst_hi, OperandSize::S64);
+    }
+
+    /// 128-bit subtraction of two register pairs.
+    pub fn sub128(
+        &mut self,
+        dst_lo: WritableReg,
+        dst_hi: WritableReg,
+        lhs_lo: Reg,
+        lhs_hi: Reg,
+        rhs_lo: Reg,
+        rhs_hi: Reg,
+    ) {
+        self.emit_alu_rrr(ALUOp::SubS, rhs_lo, lhs_lo, dst_lo, OperandSize::S64);
+        self.emit_alu_rrr(ALUOp::Sbc, rhs_hi, lhs_hi, dst_hi, OperandSize::S64);
+    }
+
     /// Add across Vector.
     pub fn addv(&mut self, rn: Reg, rd: WritableReg, size: VectorSize) {
         self.emit(Inst::VecLanes {
@@ -397,6 +466,99 @@ impl Assembler {
         self.emit_alu_rrrr(ALUOp3::MAdd, scratch, rn, rd, regs::zero(), size);
     }
 
+    /// Multiply two 64-bit registers, producing the low and high halves of
+    /// the 128-bit result.
+    pub fn mul_wide(
+        &mut self,
+        dst_lo: WritableReg,
+        dst_hi: WritableReg,
+        lhs: Reg,
+        rhs: Reg,
+        kind: MulWideKind,
+    ) {
+        let op = match kind {
+            MulWideKind::Signed => ALUOp::SMulH,
+            MulWideKind::Unsigned => ALUOp::UMulH,
+        };
+        // The high half is computed first since `dst_lo` may alias one of
+        // the operands.
+        self.emit_alu_rrr(op, rhs, lhs, dst_hi, OperandSize::S64);
+        self.mul_rrr(rhs, lhs, dst_lo, OperandSize::S64);
+    }
+
+    /// Division with three registers.
+    ///
+    /// Traps if the divisor is zero and, for signed division, if the
+    /// quotient overflows.
+    pub fn div_rrr(
+        &mut self,
+        divisor: Reg,
+        dividend: Reg,
+        dst: WritableReg,
+        kind: DivKind,
+        size: OperandSize,
+    ) {
+        self.trapz(divisor, size, TrapCode::INTEGER_DIVISION_BY_ZERO);
+
+        let op = match kind {
+            DivKind::Signed => {
+                // The only overflowing case is `MIN / -1`:
+                //   cmn divisor, #1
+                //   ccmp dividend, #1, #0, eq
+                //   b.vs trap
+                // `ccmp` computes `dividend - 1`, which only overflows when
+                // the dividend is `MIN`, if the divisor is `-1` and clears
+                // the flags otherwise.
+                self.emit_alu_rri(
+                    ALUOp::AddS,
+                    Imm12::maybe_from_u64(1).unwrap(),
+                    divisor,
+                    writable!(regs::zero()),
+                    size,
+                );
+                self.emit(Inst::CCmpImm {
+                    size: size.into(),
+                    rn: dividend.into(),
+                    imm: UImm5::maybe_from_u8(1).unwrap(),
+                    nzcv: NZCV::new(false, false, false, false),
+                    cond: Cond::Eq,
+                });
+                self.trapif(Cond::Vs, TrapCode::INTEGER_OVERFLOW);
+                ALUOp::SDiv
+            }
+            DivKind::Unsigned => ALUOp::UDiv,
+        };
+
+        self.emit_alu_rrr(op, divisor, dividend, dst, size);
+    }
+
+    /// Remainder with three registers.
+    ///
+    /// Traps if the divisor is zero.
+    pub fn rem_rrr(
+        &mut self,
+        divisor: Reg,
+        dividend: Reg,
+        dst: WritableReg,
+        kind: RemKind,
+        size: OperandSize,
+    ) {
+        self.trapz(divisor, size, TrapCode::INTEGER_DIVISION_BY_ZERO);
+
+        let op = match kind {
+            RemKind::Signed => ALUOp::SDiv,
+            RemKind::Unsigned => ALUOp::UDiv,
+        };
+
+        // dst = dividend - (dividend / divisor) * divisor
+        //
+        // Signed division of `MIN` by `-1` wraps to `MIN` without trapping,
+        // which yields the expected remainder of zero.
+        let scratch = regs::scratch();
+        self.emit_alu_rrr(op, divisor, dividend, writable!(scratch), size);
+        self.emit_alu_rrrr(ALUOp3::MSub, divisor, scratch, dst, dividend, size);
+    }
+
     /// And with three registers.
     pub fn and_rrr(&mut self, rm: Reg, rn: Reg, rd: WritableReg, size: OperandSize) {
         self.emit_alu_rrr(ALUOp::And, rm, rn, rd, size);
@@ -473,15 +635,19 @@ impl Assembler {
         kind: ShiftKind,
         size: OperandSize,
     ) {
-        let shift_op = self.shift_kind_to_alu_op(kind, rn, size);
-
-        if let Some(imm) = ImmShift::maybe_from_u64(imm) {
-            self.emit_alu_rri_shift(shift_op, imm, rn, rd, size);
-        } else {
-            let scratch = regs::scratch();
-            self.load_constant(imm, writable!(scratch));
-            self.emit_alu_rrr(shift_op, scratch, rn, rd, size);
-        }
+        // Shift amounts are taken modulo the operand width, and rotating
+        // left by `n` is the same as rotating right by `width - n`.
+        let bits = u64::from(size.num_bits());
+        let imm = imm & (bits - 1);
+        let (shift_op, imm) = match kind {
+            ShiftKind::Shl => (ALUOp::Lsl, imm),
+            ShiftKind::ShrS => (ALUOp::Asr, imm),
+            ShiftKind::ShrU => (ALUOp::Lsr, imm),
+            ShiftKind::Rotr => (ALUOp::RotR, imm),
+            ShiftKind::Rotl => (ALUOp::RotR, (bits - imm) & (bits - 1)),
+        };
+        let imm = ImmShift::maybe_from_u64(imm).unwrap();
+        self.emit_alu_rri_shift(shift_op, imm, rn, rd, size);
     }
 
     /// Count Leading Zeros.
@@ -611,7 +777,7 @@ impl Assembler {
         let (fpu_op, size) = match (src_size, dst_size) {
             (OperandSize::S32, OperandSize::S64) => (FPUOp1::Cvt32To64, ScalarSize::Size32),
             (OperandSize::S64, OperandSize::S32) => (FPUOp1::Cvt64To32, ScalarSize::Size64),
-            _ => unimplemented!(),
+            (s, d) => unreachable!("Invalid float conversion {s:?} -> {d:?}"),
         };
         self.emit(Inst::FpuRR {
             fpu_op,
@@ -621,6 +787,99 @@ impl Assembler {
         });
     }
 
+    /// Convert a float to a signed or unsigned integer, rounding towards
+    /// zero. NaN converts to zero and out of range values saturate.
+    pub fn cvt_float_to_int(
+        &mut self,
+        rn: Reg,
+        rd: WritableReg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        signed: bool,
+    ) {
+        use OperandSize::*;
+        let op = match (signed, src_size, dst_size) {
+            (true, S32, S32) => FpuToIntOp::F32ToI32,
+            (true, S32, S64) => FpuToIntOp::F32ToI64,
+            (true, S64, S32) => FpuToIntOp::F64ToI32,
+            (true, S64, S64) => FpuToIntOp::F64ToI64,
+            (false, S32, S32) => FpuToIntOp::F32ToU32,
+            (false, S32, S64) => FpuToIntOp::F32ToU64,
+            (false, S64, S32) => FpuToIntOp::F64ToU32,
+            (false, S64, S64) => FpuToIntOp::F64ToU64,
+            (_, s, d) => unreachable!("Invalid float to int conversion {s:?} -> {d:?}"),
+        };
+        self.emit(Inst::FpuToInt {
+            op,
+            rd: rd.map(Into::into),
+            rn: rn.into(),
+        });
+    }
+
+    /// Convert a signed or unsigned integer to a float.
+    pub fn cvt_int_to_float(
+        &mut self,
+        rn: Reg,
+        rd: WritableReg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        signed: bool,
+    ) {
+        use OperandSize::*;
+        let op = match (signed, src_size, dst_size) {
+            (true, S32, S32) => IntToFpuOp::I32ToF32,
+            (true, S32, S64) => IntToFpuOp::I32ToF64,
+            (true, S64, S32) => IntToFpuOp::I64ToF32,
+            (true, S64, S64) => IntToFpuOp::I64ToF64,
+            (false, S32, S32) => IntToFpuOp::U32ToF32,
+            (false, S32, S64) => IntToFpuOp::U32ToF64,
+            (false, S64, S32) => IntToFpuOp::U64ToF32,
+            (false, S64, S64) => IntToFpuOp::U64ToF64,
+            (_, s, d) => unreachable!("Invalid int to float conversion {s:?} -> {d:?}"),
+        };
+        self.emit(Inst::IntToFpu {
+            op,
+            rd: rd.map(Into::into),
+            rn: rn.into(),
+        });
+    }
+
+    /// Load the effective address of `addr` into a register.
+    pub fn load_addr(&mut self, addr: Address, rd: WritableReg) {
+        let mem: AMode = addr.try_into().unwrap();
+        self.emit(Inst::LoadAddr {
+            rd: rd.map(Into::into),
+            mem,
+        });
+    }
+
+    /// Emit a call to an unknown location through a register.
+    pub fn call_with_reg(&mut self, callee: Reg) {
+        self.emit(Inst::CallInd {
+            info: Box::new(CallInfo::empty(callee.into(), CallConv::SystemV)),
+        });
+    }
+
+    /// Emit a call to a locally defined function through an index.
+    pub fn call_with_name(&mut self, name: UserExternalNameRef) {
+        self.emit(Inst::Call {
+            info: Box::new(CallInfo::empty(ExternalName::user(name), CallConv::SystemV)),
+        });
+    }
+
+    /// Emit a call to a well-known libcall.
+    pub fn call_with_lib(&mut self, lib: LibCall, dst: Reg) {
+        // `use_colocated_libcalls` is never `true` from within Wasmtime, so
+        // always load the address of the libcall into a register, which
+        // supports an arbitrary 64-bit target.
+        self.emit(Inst::LoadExtName {
+            rd: Writable::from_reg(dst.into()),
+            name: Box::new(ExternalName::LibCall(lib)),
+            offset: 0,
+        });
+        self.call_with_reg(dst);
+    }
+
     /// Return instruction.
     pub fn ret(&mut self) {
         self.emit(Inst::Ret {});
@@ -675,8 +934,8 @@ impl Assembler {
         });
     }
 
-    // If the condition is true, Conditional Select writes rm to rd. If the condition is false,
-    // it writes rn to rd
+    // If the condition is true, Conditional Select writes rn to rd. If the condition is false,
+    // it writes rm to rd
     pub fn csel(&mut self, rm: Reg, rn: Reg, rd: WritableReg, cond: Cond) {
         self.emit(Inst::CSel {
             rd: rd.map(Into::into),
@@ -686,6 +945,19 @@ impl Assembler {
         });
     }
 
+    // Floating point Conditional Select, with the same operand order as
+    // `csel`.
+    pub fn fpu_csel(&mut self, rm: Reg, rn: Reg, rd: WritableReg, cond: Cond, size: OperandSize) {
+        let rd = rd.map(Into::into);
+        let (rm, rn) = (rm.into(), rn.into());
+        let inst = match size {
+            OperandSize::S32 => Inst::FpuCSel32 { rd, rn, rm, cond },
+            OperandSize::S64 => Inst::FpuCSel64 { rd, rn, rm, cond },
+            _ => unreachable!(),
+        };
+        self.emit(inst);
+    }
+
     // Population Count per byte.
     pub fn cnt(&mut self, rd: WritableReg) {
         self.emit(Inst::VecMisc {
@@ -724,6 +996,12 @@ impl Assembler {
         });
     }
 
+    /// Trap if the register is zero.
+    pub fn trapz(&mut self, rn: Reg, size: OperandSize, code: TrapCode) {
+        self.subs_ir(0, rn, size);
+        self.trapif(Cond::Eq, code);
+    }
+
     // Helpers for ALU operations.
 
     fn emit_alu_rri(&mut self, op: ALUOp, imm: Imm12, rn: Reg, rd: WritableReg, size: OperandSize) {
@@ -880,7 +1158,7 @@ impl Assembler {
             ShiftKind::Rotr => ALUOp::RotR,
             ShiftKind::Rotl => {
                 // neg(r) is sub(zero, r).
-                self.emit_alu_rrr(ALUOp::Sub, regs::zero(), r, writable!(r), size);
+                self.emit_alu_rrr(ALUOp::Sub, r, regs::zero(), writable!(r), size);
                 ALUOp::RotR
             }
         }
@@ -902,3 +1180,46 @@ impl Assembler {
         &self.buffer
     }
 }
+
+/// Captures the region in a [`MachBuffer`] where a constant is added to a
+/// register, but the constant is not yet known. The constant is materialized
+/// 16 bits at a time with `movz` and `movk`, so it is limited to 32 bits.
+pub(crate) struct PatchableAddToReg {
+    /// The region to be patched in the [`MachBuffer`]. It contains a `movz`
+    /// and a `movk` of the constant into the temporary register, followed by
+    /// the addition.
+    region: PatchRegion,
+}
+
+impl PatchableAddToReg {
+    /// Create a new [`PatchableAddToReg`] by capturing a region in the output
+    /// buffer where `tmp` is loaded with the constant and added to `reg`. The
+    /// [`MachBuffer`] will add `0` until the `::finalize` method is called.
+    pub(crate) fn new(reg: Reg, tmp: Reg, buf: &mut MachBuffer<Inst>) -> Self {
+        let reg = u32::try_from(reg.hw_enc()).unwrap();
+        let tmp = u32::try_from(tmp.hw_enc()).unwrap();
+        let open = buf.start_patchable();
+
+        // movz tmp, #0
+        buf.put4(0xd2800000 | tmp);
+        // movk tmp, #0, lsl #16
+        buf.put4(0xf2a00000 | tmp);
+        // add reg, reg, tmp
+        buf.put4(0x8b000000 | (tmp << 16) | (reg << 5) | reg);
+
+        let region = buf.end_patchable(open);
+        Self { region }
+    }
+
+    /// Patch the [`MachBuffer`] with the known constant to be added to the
+    /// register.
+    pub(crate) fn finalize(self, val: u32, buffer: &mut MachBuffer<Inst>) {
+        let slice = self.region.patch(buffer);
+        debug_assert_eq!(slice.len(), 12);
+        for (i, half) in [val & 0xffff, val >> 16].into_iter().enumerate() {
+            let word = &mut slice[i * 4..(i + 1) * 4];
+            let inst = u32::from_le_bytes(word.try_into().unwrap()) | (half << 5);
+            word.copy_from_slice(&inst.to_le_bytes());
+        }
+    }
+}
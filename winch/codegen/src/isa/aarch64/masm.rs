@@ -1,13 +1,21 @@
-use super::{abi::Aarch64ABI, address::Address, asm::Assembler, regs};
+use super::{
+    abi::Aarch64ABI,
+    address::Address,
+    asm::{Assembler, PatchableAddToReg},
+    regs,
+};
 use crate::{
-    abi::local::LocalSlot,
+    abi::{self, align_to, calculate_frame_adjustment, local::LocalSlot, vmctx},
     codegen::{ptr_type_from_ptr_size, CodeGenContext, FuncEnv},
     isa::reg::{writable, Reg, WritableReg},
     masm::{
-        CalleeKind, DivKind, ExtendKind, FloatCmpKind, Imm as I, IntCmpKind,
+        CalleeKind, DivKind, ExtendKind, ExtractLaneKind, FloatCmpKind, Imm as I, IntCmpKind,
         MacroAssembler as Masm, MulWideKind, OperandSize, RegImm, RemKind, RoundingMode, SPOffset,
-        ShiftKind, StackSlot, TrapCode, TruncKind,
+        ShiftKind, StackSlot, TrapCode, TruncKind, V128ArithKind, V128ConvertKind, V128ExtAddKind,
+        V128ExtendKind, V128MinMaxKind, V128NarrowKind, V128TruncKind, VectorShape, TRUSTED_FLAGS,
+        UNTRUSTED_FLAGS,
     },
+    stack::{TypedReg, Val},
 };
 use cranelift_codegen::{
     binemit::CodeOffset,
@@ -16,6 +24,7 @@ use cranelift_codegen::{
     settings, Final, MachBufferFinalized, MachLabel,
 };
 use regalloc2::RegClass;
+use wasmparser::Operator;
 use wasmtime_environ::PtrSize;
 
 /// Aarch64 MacroAssembler.
@@ -24,6 +33,12 @@ pub(crate) struct MacroAssembler {
     asm: Assembler,
     /// Stack pointer offset.
     sp_offset: u32,
+    /// The maximum stack size seen while compiling the function. Only valid
+    /// once all the instructions of the function have been emitted.
+    sp_max: u32,
+    /// The instructions used to add the stack max to a register, patched
+    /// with the real stack max when the masm is finalized.
+    stack_max_use_add: Option<PatchableAddToReg>,
     /// The target pointer size.
     ptr_size: OperandSize,
 }
@@ -34,6 +49,8 @@ impl MacroAssembler {
         Self {
             asm: Assembler::new(shared_flags),
             sp_offset: 0u32,
+            sp_max: 0,
+            stack_max_use_add: None,
             ptr_size: ptr_type_from_ptr_size(ptr_size.size()).into(),
         }
     }
@@ -55,8 +72,25 @@ impl Masm for MacroAssembler {
         self.move_sp_to_shadow_sp();
     }
 
-    fn check_stack(&mut self, _vmctx: Reg) {
-        // TODO: Implement when we have more complete assembler support.
+    fn check_stack(&mut self, vmctx: Reg) {
+        let ptr_size: u8 = self.ptr_size.bytes().try_into().unwrap();
+        let scratch = regs::scratch();
+
+        self.load_ptr(
+            self.address_at_reg(vmctx, ptr_size.vmcontext_runtime_limits().into()),
+            writable!(scratch),
+        );
+
+        self.load_ptr(
+            Address::offset(scratch, ptr_size.vmruntime_limits_stack_limit().into()),
+            writable!(scratch),
+        );
+
+        self.add_stack_max(scratch);
+
+        // Trap if `stack_limit + sp_max > sp`.
+        self.asm.subs_rrr(scratch, regs::sp(), self.ptr_size);
+        self.asm.trapif(Cond::Lo, TrapCode::STACK_OVERFLOW);
     }
 
     fn frame_restore(&mut self) {
@@ -123,8 +157,8 @@ impl Masm for MacroAssembler {
         Address::from_shadow_sp(offset.as_u32() as i64)
     }
 
-    fn address_at_vmctx(&self, _offset: u32) -> Self::Address {
-        todo!()
+    fn address_at_vmctx(&self, offset: u32) -> Self::Address {
+        Address::offset(vmctx!(Self), offset as i64)
     }
 
     fn store_ptr(&mut self, src: Reg, dst: Self::Address) {
@@ -134,13 +168,17 @@ impl Masm for MacroAssembler {
     fn store(&mut self, src: RegImm, dst: Address, size: OperandSize) {
         let src = match src {
             RegImm::Imm(v) => {
-                let imm = match v {
-                    I::I32(v) | I::F32(v) => v as u64,
-                    I::F64(v) | I::I64(v) => v,
-                    I::V128(_) => unreachable!(),
-                };
                 let scratch = regs::scratch();
-                self.asm.load_constant(imm, writable!(scratch));
+                match v {
+                    I::I32(v) | I::F32(v) => self.asm.load_constant(v as u64, writable!(scratch)),
+                    I::F64(v) | I::I64(v) => self.asm.load_constant(v, writable!(scratch)),
+                    I::V128(v) => {
+                        let float_scratch = regs::float_scratch();
+                        self.load_v128(v, writable!(float_scratch));
+                        self.asm.str(float_scratch, dst, size, TRUSTED_FLAGS);
+                        return;
+                    }
+                };
                 if v.is_float() {
                     let float_scratch = regs::float_scratch();
                     self.asm
@@ -153,23 +191,37 @@ impl Masm for MacroAssembler {
             RegImm::Reg(reg) => reg,
         };
 
-        self.asm.str(src, dst, size);
+        self.asm.str(src, dst, size, TRUSTED_FLAGS);
     }
 
     fn wasm_store(&mut self, src: Reg, dst: Self::Address, size: OperandSize) {
-        self.asm.str(src, dst, size);
+        self.asm.str(src, dst, size, UNTRUSTED_FLAGS);
     }
 
     fn call(
         &mut self,
-        _stack_args_size: u32,
-        _load_callee: impl FnMut(&mut Self) -> CalleeKind,
+        stack_args_size: u32,
+        mut load_callee: impl FnMut(&mut Self) -> CalleeKind,
     ) -> u32 {
-        todo!()
+        let alignment: u32 = <Self::ABI as abi::ABI>::call_stack_align().into();
+        let addend: u32 = <Self::ABI as abi::ABI>::arg_base_offset().into();
+        let delta = calculate_frame_adjustment(self.sp_offset().as_u32(), addend, alignment);
+        let aligned_args_size = align_to(stack_args_size, alignment);
+        let total_stack = delta + aligned_args_size;
+        self.reserve_stack(total_stack);
+        let callee = load_callee(self);
+        match callee {
+            CalleeKind::Indirect(reg) => self.asm.call_with_reg(reg),
+            CalleeKind::Direct(idx) => self.asm.call_with_name(idx),
+            CalleeKind::LibCall(lib) => self.asm.call_with_lib(lib, regs::scratch()),
+        };
+        // The shadow stack pointer is not preserved across calls.
+        self.move_sp_to_shadow_sp();
+        total_stack
     }
 
     fn load(&mut self, src: Address, dst: WritableReg, size: OperandSize) {
-        self.asm.uload(src, dst, size);
+        self.asm.uload(src, dst, size, TRUSTED_FLAGS);
     }
 
     fn load_ptr(&mut self, src: Self::Address, dst: WritableReg) {
@@ -185,20 +237,25 @@ impl Masm for MacroAssembler {
     ) {
         // kind is some if the value is signed
         // unlike x64, unused bits are set to zero so we don't need to extend
-        if kind.is_some() {
-            self.asm.sload(src, dst, size);
+        if let Some(kind) = kind {
+            self.asm.sload(src, dst, size, UNTRUSTED_FLAGS);
+            // Sign-extending loads always extend to 64 bits; clear the upper
+            // half when the result is an i32.
+            if kind.to_bits() == 32 {
+                self.asm.mov_rr(dst.to_reg(), dst, OperandSize::S32);
+            }
         } else {
-            self.asm.uload(src, dst, size);
+            self.asm.uload(src, dst, size, UNTRUSTED_FLAGS);
         }
     }
 
-    fn load_addr(&mut self, _src: Self::Address, _dst: WritableReg, _size: OperandSize) {
-        todo!()
+    fn load_addr(&mut self, src: Self::Address, dst: WritableReg, _size: OperandSize) {
+        self.asm.load_addr(src, dst);
     }
 
     fn pop(&mut self, dst: WritableReg, size: OperandSize) {
         let addr = self.address_from_sp(SPOffset::from_u32(self.sp_offset));
-        self.asm.uload(addr, dst, size);
+        self.asm.uload(addr, dst, size, TRUSTED_FLAGS);
         self.free_stack(size.bytes());
     }
 
@@ -206,7 +263,11 @@ impl Masm for MacroAssembler {
         SPOffset::from_u32(self.sp_offset)
     }
 
-    fn finalize(self, base: Option<SourceLoc>) -> MachBufferFinalized<Final> {
+    fn finalize(mut self, base: Option<SourceLoc>) -> MachBufferFinalized<Final> {
+        if let Some(patch) = self.stack_max_use_add {
+            patch.finalize(self.sp_max, self.asm.buffer_mut());
+        }
+
         self.asm.finalize(base)
     }
 
@@ -218,7 +279,7 @@ impl Masm for MacroAssembler {
                     I::I64(v) => v,
                     I::F32(v) => v as u64,
                     I::F64(v) => v,
-                    I::V128(_) => todo!(),
+                    I::V128(v) => return self.load_v128(v, rd),
                 };
 
                 let scratch = regs::scratch();
@@ -226,20 +287,28 @@ impl Masm for MacroAssembler {
                 match rd.to_reg().class() {
                     RegClass::Int => self.asm.mov_rr(scratch, rd, size),
                     RegClass::Float => self.asm.mov_to_fpu(scratch, rd, size),
-                    _ => todo!(),
+                    class => unreachable!("Invalid register class {class:?}"),
                 }
             }
             (RegImm::Reg(rs), rd) => match (rs.class(), rd.to_reg().class()) {
                 (RegClass::Int, RegClass::Int) => self.asm.mov_rr(rs, rd, size),
                 (RegClass::Float, RegClass::Float) => self.asm.fmov_rr(rs, rd, size),
                 (RegClass::Int, RegClass::Float) => self.asm.mov_to_fpu(rs, rd, size),
-                _ => todo!(),
+                (RegClass::Float, RegClass::Int) => self.asm.mov_from_vec(rs, rd, 0, size),
+                (src, dst) => unreachable!("Invalid move from {src:?} to {dst:?}"),
             },
         }
     }
 
-    fn cmov(&mut self, dst: WritableReg, src: Reg, cc: IntCmpKind, _size: OperandSize) {
-        self.asm.csel(src, src, dst, Cond::from(cc));
+    fn cmov(&mut self, dst: WritableReg, src: Reg, cc: IntCmpKind, size: OperandSize) {
+        match (src.class(), dst.to_reg().class()) {
+            (RegClass::Int, RegClass::Int) => self.asm.csel(dst.to_reg(), src, dst, Cond::from(cc)),
+            (RegClass::Float, RegClass::Float) => {
+                self.asm
+                    .fpu_csel(dst.to_reg(), src, dst, Cond::from(cc), size)
+            }
+            (src, dst) => unreachable!("Invalid conditional move from {src:?} to {dst:?}"),
+        }
     }
 
     fn add(&mut self, dst: WritableReg, lhs: Reg, rhs: RegImm, size: OperandSize) {
@@ -268,7 +337,17 @@ impl Masm for MacroAssembler {
         size: OperandSize,
         trap: TrapCode,
     ) {
-        self.add(dst, lhs, rhs, size);
+        match rhs {
+            RegImm::Imm(v) => {
+                let imm = match v {
+                    I::I32(v) => v as u64,
+                    I::I64(v) => v,
+                    _ => unreachable!(),
+                };
+                self.asm.adds_ir(imm, lhs, dst, size);
+            }
+            RegImm::Reg(rm) => self.asm.adds_rrr(rm, lhs, dst, size),
+        }
         self.asm.trapif(Cond::Hs, trap);
     }
 
@@ -444,12 +523,36 @@ impl Masm for MacroAssembler {
         context.stack.push(dst.into());
     }
 
-    fn div(&mut self, _context: &mut CodeGenContext, _kind: DivKind, _size: OperandSize) {
-        todo!()
+    fn div(&mut self, context: &mut CodeGenContext, kind: DivKind, size: OperandSize) {
+        let divisor = context.pop_to_reg(self, None);
+        let dividend = context.pop_to_reg(self, None);
+
+        self.asm.div_rrr(
+            divisor.into(),
+            dividend.into(),
+            writable!(dividend.into()),
+            kind,
+            size,
+        );
+
+        context.free_reg(divisor);
+        context.stack.push(dividend.into());
     }
 
-    fn rem(&mut self, _context: &mut CodeGenContext, _kind: RemKind, _size: OperandSize) {
-        todo!()
+    fn rem(&mut self, context: &mut CodeGenContext, kind: RemKind, size: OperandSize) {
+        let divisor = context.pop_to_reg(self, None);
+        let dividend = context.pop_to_reg(self, None);
+
+        self.asm.rem_rrr(
+            divisor.into(),
+            dividend.into(),
+            writable!(dividend.into()),
+            kind,
+            size,
+        );
+
+        context.free_reg(divisor);
+        context.stack.push(dividend.into());
     }
 
     fn zero(&mut self, reg: WritableReg) {
@@ -469,54 +572,64 @@ impl Masm for MacroAssembler {
 
     fn signed_truncate(
         &mut self,
-        _dst: WritableReg,
-        _src: Reg,
-        _src_size: OperandSize,
-        _dst_size: OperandSize,
-        _kind: TruncKind,
+        dst: WritableReg,
+        src: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
     ) {
-        todo!()
+        if !kind.is_checked() {
+            self.trap_on_invalid_truncation(src, src_size, dst_size, true);
+        }
+        self.asm
+            .cvt_float_to_int(src, dst, src_size, dst_size, true);
     }
 
     fn unsigned_truncate(
         &mut self,
-        _dst: WritableReg,
-        _src: Reg,
+        dst: WritableReg,
+        src: Reg,
         _tmp_fpr: Reg,
-        _src_size: OperandSize,
-        _dst_size: OperandSize,
-        _kind: TruncKind,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
     ) {
-        todo!()
+        if !kind.is_checked() {
+            self.trap_on_invalid_truncation(src, src_size, dst_size, false);
+        }
+        self.asm
+            .cvt_float_to_int(src, dst, src_size, dst_size, false);
     }
 
     fn signed_convert(
         &mut self,
-        _dst: WritableReg,
-        _src: Reg,
-        _src_size: OperandSize,
-        _dst_size: OperandSize,
+        dst: WritableReg,
+        src: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
     ) {
-        todo!()
+        self.asm
+            .cvt_int_to_float(src, dst, src_size, dst_size, true);
     }
 
     fn unsigned_convert(
         &mut self,
-        _dst: WritableReg,
-        _src: Reg,
+        dst: WritableReg,
+        src: Reg,
         _tmp_gpr: Reg,
-        _src_size: OperandSize,
-        _dst_size: OperandSize,
+        src_size: OperandSize,
+        dst_size: OperandSize,
     ) {
-        todo!()
+        self.asm
+            .cvt_int_to_float(src, dst, src_size, dst_size, false);
     }
 
-    fn reinterpret_float_as_int(&mut self, _dst: WritableReg, _src: Reg, _size: OperandSize) {
-        todo!()
+    fn reinterpret_float_as_int(&mut self, dst: WritableReg, src: Reg, size: OperandSize) {
+        self.asm.mov_from_vec(src, dst, 0, size);
     }
 
-    fn reinterpret_int_as_float(&mut self, _dst: WritableReg, _src: Reg, _size: OperandSize) {
-        todo!()
+    fn reinterpret_int_as_float(&mut self, dst: WritableReg, src: Reg, size: OperandSize) {
+        self.asm.mov_to_fpu(src, dst, size);
     }
 
     fn demote(&mut self, dst: WritableReg, src: Reg) {
@@ -532,7 +645,7 @@ impl Masm for MacroAssembler {
     fn push(&mut self, reg: Reg, size: OperandSize) -> StackSlot {
         self.reserve_stack(size.bytes());
         let address = self.address_from_sp(SPOffset::from_u32(self.sp_offset));
-        self.asm.str(reg, address, size);
+        self.asm.str(reg, address, size, TRUSTED_FLAGS);
 
         StackSlot {
             offset: SPOffset::from_u32(self.sp_offset),
@@ -573,7 +686,7 @@ impl Masm for MacroAssembler {
         kind: FloatCmpKind,
         size: OperandSize,
     ) {
-        self.asm.fcmp(src1, src2, size);
+        self.asm.fcmp(src2, src1, size);
         self.asm.cset(dst, kind.into());
     }
 
@@ -642,7 +755,7 @@ impl Masm for MacroAssembler {
         // At least one default target.
         assert!(targets.len() >= 1);
         let max = targets.len() as u64 - 1;
-        self.asm.subs_ir(max, index, OperandSize::S64);
+        self.asm.subs_ir(max, index, OperandSize::S32);
         let default_index = max as usize;
         let default = targets[default_index];
         let rest = &targets[..default_index];
@@ -654,8 +767,8 @@ impl Masm for MacroAssembler {
         self.asm.udf(code);
     }
 
-    fn trapz(&mut self, _src: Reg, _code: TrapCode) {
-        todo!()
+    fn trapz(&mut self, src: Reg, code: TrapCode) {
+        self.asm.trapz(src, self.ptr_size, code);
     }
 
     fn trapif(&mut self, cc: IntCmpKind, code: TrapCode) {
@@ -683,8 +796,8 @@ impl Masm for MacroAssembler {
         rhs_lo: Reg,
         rhs_hi: Reg,
     ) {
-        let _ = (dst_lo, dst_hi, lhs_lo, lhs_hi, rhs_lo, rhs_hi);
-        todo!()
+        self.asm
+            .add128(dst_lo, dst_hi, lhs_lo, lhs_hi, rhs_lo, rhs_hi);
     }
 
     fn sub128(
@@ -696,19 +809,264 @@ impl Masm for MacroAssembler {
         rhs_lo: Reg,
         rhs_hi: Reg,
     ) {
-        let _ = (dst_lo, dst_hi, lhs_lo, lhs_hi, rhs_lo, rhs_hi);
-        todo!()
+        self.asm
+            .sub128(dst_lo, dst_hi, lhs_lo, lhs_hi, rhs_lo, rhs_hi);
     }
 
     fn mul_wide(&mut self, context: &mut CodeGenContext, kind: MulWideKind) {
-        let _ = (context, kind);
-        todo!()
+        let rhs = context.pop_to_reg(self, None);
+        let lhs = context.pop_to_reg(self, None);
+        let hi = context.any_gpr(self);
+
+        self.asm.mul_wide(
+            writable!(lhs.into()),
+            writable!(hi),
+            lhs.into(),
+            rhs.into(),
+            kind,
+        );
+
+        context.free_reg(rhs);
+        // The low bits of the result replace `lhs`, followed by the high
+        // bits.
+        context.stack.push(lhs.into());
+        context.stack.push(Val::Reg(TypedReg::i64(hi)));
+    }
+
+    // SIMD operators are rejected through `supports_v128_op` on this target,
+    // so none of the `v128_*` methods below are reachable.
+    fn v128_splat(&mut self, _dst: WritableReg, _src: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_extract_lane(
+        &mut self,
+        _dst: WritableReg,
+        _src: Reg,
+        _lane: u8,
+        _kind: ExtractLaneKind,
+    ) {
+        unreachable!()
+    }
+
+    fn v128_replace_lane(&mut self, _dst: WritableReg, _src: Reg, _lane: u8, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_shuffle(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _lanes: [u8; 16]) {
+        unreachable!()
+    }
+
+    fn v128_swizzle(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg) {
+        unreachable!()
+    }
+
+    fn v128_not(&mut self, _dst: WritableReg) {
+        unreachable!()
+    }
+
+    fn v128_and(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg) {
+        unreachable!()
+    }
+
+    fn v128_and_not(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg) {
+        unreachable!()
+    }
+
+    fn v128_or(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg) {
+        unreachable!()
+    }
+
+    fn v128_xor(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg) {
+        unreachable!()
+    }
+
+    fn v128_bitselect(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _mask: Reg) {
+        unreachable!()
+    }
+
+    fn v128_any_true(&mut self, _dst: WritableReg, _src: Reg) {
+        unreachable!()
+    }
+
+    fn v128_all_true(&mut self, _dst: WritableReg, _src: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_bitmask(&mut self, _dst: WritableReg, _src: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_add(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _kind: V128ArithKind) {
+        unreachable!()
+    }
+
+    fn v128_sub(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _kind: V128ArithKind) {
+        unreachable!()
+    }
+
+    fn v128_mul(&mut self, _context: &mut CodeGenContext, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_div(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_min(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _kind: V128MinMaxKind) {
+        unreachable!()
+    }
+
+    fn v128_max(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _kind: V128MinMaxKind) {
+        unreachable!()
+    }
+
+    fn v128_pmin(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_pmax(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_avgr(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_neg(&mut self, _dst: WritableReg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_abs(&mut self, _dst: WritableReg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_sqrt(&mut self, _dst: WritableReg, _src: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_round(
+        &mut self,
+        _dst: WritableReg,
+        _src: Reg,
+        _mode: RoundingMode,
+        _shape: VectorShape,
+    ) {
+        unreachable!()
+    }
+
+    fn v128_popcnt(&mut self, _context: &mut CodeGenContext) {
+        unreachable!()
+    }
+
+    fn v128_shift(&mut self, _context: &mut CodeGenContext, _shape: VectorShape, _kind: ShiftKind) {
+        unreachable!()
+    }
+
+    fn v128_int_cmp(
+        &mut self,
+        _dst: WritableReg,
+        _lhs: Reg,
+        _rhs: Reg,
+        _kind: IntCmpKind,
+        _shape: VectorShape,
+    ) {
+        unreachable!()
+    }
+
+    fn v128_float_cmp(
+        &mut self,
+        _dst: WritableReg,
+        _lhs: Reg,
+        _rhs: Reg,
+        _kind: FloatCmpKind,
+        _shape: VectorShape,
+    ) {
+        unreachable!()
+    }
+
+    fn v128_narrow(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _kind: V128NarrowKind) {
+        unreachable!()
+    }
+
+    fn v128_extend(&mut self, _dst: WritableReg, _src: Reg, _kind: V128ExtendKind) {
+        unreachable!()
+    }
+
+    fn v128_extmul(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg, _kind: V128ExtendKind) {
+        unreachable!()
+    }
+
+    fn v128_extadd_pairwise(&mut self, _dst: WritableReg, _kind: V128ExtAddKind) {
+        unreachable!()
+    }
+
+    fn v128_dot(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg) {
+        unreachable!()
+    }
+
+    fn v128_q15mulr_sat_s(&mut self, _dst: WritableReg, _lhs: Reg, _rhs: Reg) {
+        unreachable!()
+    }
+
+    fn v128_convert(&mut self, _dst: WritableReg, _src: Reg, _kind: V128ConvertKind) {
+        unreachable!()
+    }
+
+    fn v128_trunc_sat(&mut self, _context: &mut CodeGenContext, _kind: V128TruncKind) {
+        unreachable!()
+    }
+
+    fn v128_demote(&mut self, _dst: WritableReg, _src: Reg) {
+        unreachable!()
+    }
+
+    fn v128_promote(&mut self, _dst: WritableReg, _src: Reg) {
+        unreachable!()
+    }
+
+    fn supports_v128_op(&self, _op: &Operator) -> bool {
+        false
+    }
+
+    fn has_native_fma(&self) -> bool {
+        true
+    }
+
+    fn v128_madd(&mut self, _dst: WritableReg, _a: Reg, _b: Reg, _c: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_nmadd(&mut self, _dst: WritableReg, _a: Reg, _b: Reg, _c: Reg, _shape: VectorShape) {
+        unreachable!()
+    }
+
+    fn v128_dot_i8x16_i7x16_s(&mut self, _context: &mut CodeGenContext, _deterministic: bool) {
+        unreachable!()
+    }
+
+    fn v128_dot_i8x16_i7x16_add_s(&mut self, _context: &mut CodeGenContext, _deterministic: bool) {
+        unreachable!()
     }
 }
 
 impl MacroAssembler {
+    /// Add the maximum stack used to a register, recording an obligation to
+    /// patch the emitted instructions with the real stack max when the masm
+    /// is finalized.
+    fn add_stack_max(&mut self, reg: Reg) {
+        assert!(self.stack_max_use_add.is_none());
+        let patch = PatchableAddToReg::new(reg, regs::ip1(), self.asm.buffer_mut());
+        self.stack_max_use_add.replace(patch);
+    }
+
     fn increment_sp(&mut self, bytes: u32) {
         self.sp_offset += bytes;
+
+        // NOTE: we use `max` here to track the largest stack allocation in
+        // `sp_max`. Once we have seen the entire function, this value will
+        // represent the maximum size for the stack frame.
+        self.sp_max = self.sp_max.max(self.sp_offset);
     }
 
     fn decrement_sp(&mut self, bytes: u32) {
@@ -728,4 +1086,59 @@ impl MacroAssembler {
         let shadow_sp = regs::shadow_sp();
         self.asm.mov_rr(sp, writable!(shadow_sp), OperandSize::S64);
     }
+
+    /// Materialize a v128 constant in a vector register, 64 bits at a time.
+    fn load_v128(&mut self, v: i128, dst: WritableReg) {
+        let scratch = regs::scratch();
+        let bits = v as u128;
+        self.asm.load_constant(bits as u64, writable!(scratch));
+        self.asm.mov_to_fpu(scratch, dst, OperandSize::S64);
+        self.asm
+            .load_constant((bits >> 64) as u64, writable!(scratch));
+        self.asm.mov_to_vec(scratch, dst, 1, VectorSize::Size64x2);
+    }
+
+    /// Emit the checks that make a float to integer truncation trap: on NaN
+    /// and on values that are out of range for the destination type.
+    fn trap_on_invalid_truncation(
+        &mut self,
+        src: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        signed: bool,
+    ) {
+        self.asm.fcmp(src, src, src_size);
+        self.asm
+            .trapif(Cond::Vs, TrapCode::BAD_CONVERSION_TO_INTEGER);
+
+        let dst_bits = dst_size.num_bits();
+        // The lower bound and whether values equal to it are out of range.
+        let (min, min_cond) = match (signed, src_size, dst_size) {
+            // `i32::MIN - 1` is representable as an f64, which lets values in
+            // `(i32::MIN - 1, i32::MIN)` truncate to `i32::MIN`.
+            (true, OperandSize::S64, OperandSize::S32) => (f64::from(i32::MIN) - 1.0, Cond::Ls),
+            (true, _, _) => (-(2f64.powi(dst_bits as i32 - 1)), Cond::Mi),
+            (false, _, _) => (-1.0, Cond::Ls),
+        };
+        let max = if signed {
+            2f64.powi(dst_bits as i32 - 1)
+        } else {
+            2f64.powi(dst_bits as i32)
+        };
+
+        for (bound, cond) in [(min, min_cond), (max, Cond::Ge)] {
+            let bits = match src_size {
+                OperandSize::S32 => u64::from((bound as f32).to_bits()),
+                OperandSize::S64 => bound.to_bits(),
+                _ => unreachable!(),
+            };
+            let scratch = regs::scratch();
+            let float_scratch = regs::float_scratch();
+            self.asm.load_constant(bits, writable!(scratch));
+            self.asm
+                .mov_to_fpu(scratch, writable!(float_scratch), src_size);
+            self.asm.fcmp(float_scratch, src, src_size);
+            self.asm.trapif(cond, TrapCode::INTEGER_OVERFLOW);
+        }
+    }
 }
@@ -12,7 +12,9 @@ use std::{
 use target_lexicon::{Architecture, Triple};
 use wasmparser::{FuncValidator, FunctionBody, ValidatorResources};
 use wasmtime_cranelift::CompiledFunction;
-use wasmtime_environ::{ModuleTranslation, ModuleTypesBuilder, Tunables, WasmFuncType};
+use wasmtime_environ::{
+    DefinedFuncIndex, ModuleTranslation, ModuleTypesBuilder, Tunables, WasmFuncType,
+};
 
 #[cfg(feature = "x64")]
 pub(crate) mod x64;
@@ -164,6 +166,7 @@ pub trait TargetIsa: Send + Sync {
         builtins: &mut BuiltinFunctions,
         validator: &mut FuncValidator<ValidatorResources>,
         tunables: &Tunables,
+        index: DefinedFuncIndex,
     ) -> Result<CompiledFunction>;
 
     /// Get the default calling convention of the underlying target triple.
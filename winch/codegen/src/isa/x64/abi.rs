@@ -1,10 +1,12 @@
 use super::regs;
 use crate::{
     abi::{align_to, ABIOperand, ABIParams, ABIResults, ABISig, ParamsOrReturns, ABI},
+    codegen::CodeGenError,
     isa::{reg::Reg, CallingConvention},
     RegIndexEnv,
 };
-use wasmtime_environ::{WasmHeapType, WasmRefType, WasmValType};
+use anyhow::Result;
+use wasmtime_environ::{WasmHeapType, WasmValType};
 
 #[derive(Default)]
 pub(crate) struct X64ABI;
@@ -39,7 +41,7 @@ impl ABI for X64ABI {
         params: &[WasmValType],
         returns: &[WasmValType],
         call_conv: &CallingConvention,
-    ) -> ABISig {
+    ) -> Result<ABISig> {
         assert!(call_conv.is_fastcall() || call_conv.is_systemv() || call_conv.is_default());
         let is_fastcall = call_conv.is_fastcall();
         // In the fastcall calling convention, the callee gets a contiguous
@@ -52,7 +54,7 @@ impl ABI for X64ABI {
             (0, RegIndexEnv::with_limits_per_class(6, 8))
         };
 
-        let results = Self::abi_results(returns, call_conv);
+        let results = Self::abi_results(returns, call_conv)?;
         let params = ABIParams::from::<_, Self>(
             params,
             params_stack_offset,
@@ -66,12 +68,12 @@ impl ABI for X64ABI {
                     ParamsOrReturns::Params,
                 )
             },
-        );
+        )?;
 
-        ABISig::new(params, results)
+        Ok(ABISig::new(params, results))
     }
 
-    fn abi_results(returns: &[WasmValType], call_conv: &CallingConvention) -> ABIResults {
+    fn abi_results(returns: &[WasmValType], call_conv: &CallingConvention) -> Result<ABIResults> {
         assert!(call_conv.is_default() || call_conv.is_fastcall() || call_conv.is_systemv());
         // Use absolute count for results given that for Winch's
         // default CallingConvention only one register is used for results
@@ -92,14 +94,8 @@ impl ABI for X64ABI {
 
     fn scratch_for(ty: &WasmValType) -> Reg {
         match ty {
-            WasmValType::I32
-            | WasmValType::I64
-            | WasmValType::Ref(WasmRefType {
-                heap_type: WasmHeapType::Func,
-                ..
-            }) => regs::scratch(),
+            WasmValType::I32 | WasmValType::I64 | WasmValType::Ref(_) => regs::scratch(),
             WasmValType::F32 | WasmValType::F64 | WasmValType::V128 => regs::scratch_xmm(),
-            _ => unimplemented!(),
         }
     }
 
@@ -117,16 +113,16 @@ impl ABI for X64ABI {
         Self::word_bytes()
     }
 
-    fn sizeof(ty: &WasmValType) -> u8 {
-        match ty {
+    fn sizeof(ty: &WasmValType) -> Result<u8> {
+        Ok(match ty {
             WasmValType::Ref(rt) => match rt.heap_type {
                 WasmHeapType::Func | WasmHeapType::Extern => Self::word_bytes(),
-                ht => unimplemented!("Support for WasmHeapType: {ht}"),
+                _ => return Err(CodeGenError::unsupported_wasm_type().into()),
             },
             WasmValType::F64 | WasmValType::I64 => Self::word_bytes(),
             WasmValType::F32 | WasmValType::I32 => Self::word_bytes() / 2,
             WasmValType::V128 => Self::word_bytes() * 2,
-        }
+        })
     }
 }
 
@@ -137,17 +133,11 @@ impl X64ABI {
         index_env: &mut RegIndexEnv,
         call_conv: &CallingConvention,
         params_or_returns: ParamsOrReturns,
-    ) -> (ABIOperand, u32) {
+    ) -> Result<(ABIOperand, u32)> {
+        // Check that the type is supported before assigning it a register.
+        let ty_size = <Self as ABI>::sizeof(wasm_arg)?;
         let (reg, ty) = match wasm_arg {
-            ty @ WasmValType::Ref(rt) => match rt.heap_type {
-                WasmHeapType::Func | WasmHeapType::Extern => (
-                    Self::int_reg_for(index_env.next_gpr(), call_conv, params_or_returns),
-                    ty,
-                ),
-                ht => unimplemented!("Support for WasmHeapType: {ht}"),
-            },
-
-            ty @ (WasmValType::I32 | WasmValType::I64) => (
+            ty @ (WasmValType::I32 | WasmValType::I64 | WasmValType::Ref(_)) => (
                 Self::int_reg_for(index_env.next_gpr(), call_conv, params_or_returns),
                 ty,
             ),
@@ -159,7 +149,6 @@ impl X64ABI {
             ),
         };
 
-        let ty_size = <Self as ABI>::sizeof(wasm_arg);
         let default = || {
             let arg = ABIOperand::stack_offset(stack_offset, *ty, ty_size as u32);
             let slot_size = Self::stack_slot_size();
@@ -187,9 +176,9 @@ impl X64ABI {
             (arg, next_stack)
         };
 
-        reg.map_or_else(default, |reg| {
+        Ok(reg.map_or_else(default, |reg| {
             (ABIOperand::reg(reg, *ty, ty_size as u32), stack_offset)
-        })
+        }))
     }
 
     fn int_reg_for(
@@ -281,7 +270,7 @@ mod tests {
         isa::{reg::Reg, x64::regs, CallingConvention},
     };
     use wasmtime_environ::{
-        WasmFuncType,
+        WasmFuncType, WasmHeapType, WasmRefType,
         WasmValType::{self, *},
     };
 
@@ -290,7 +279,7 @@ mod tests {
         let wasm_sig =
             WasmFuncType::new([I32, I64, I32, I64, I32, I32, I64, I32].into(), [].into());
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), I32, regs::rdi());
@@ -310,7 +299,7 @@ mod tests {
             [I32, I32, I32].into(),
         );
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
         let results = sig.results;
 
@@ -335,7 +324,7 @@ mod tests {
             [].into(),
         );
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::xmm0());
@@ -356,7 +345,7 @@ mod tests {
             [].into(),
         );
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), V128, regs::xmm0());
@@ -375,7 +364,7 @@ mod tests {
     fn vector_abi_sig_multi_returns() {
         let wasm_sig = WasmFuncType::new([].into(), [V128, V128, V128].into());
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let results = sig.results;
 
         match_stack_arg(results.get(0).unwrap(), V128, 16);
@@ -390,7 +379,7 @@ mod tests {
             [].into(),
         );
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::Default).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::xmm0());
@@ -411,7 +400,7 @@ mod tests {
             [].into(),
         );
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::SystemV);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::SystemV).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::xmm0());
@@ -432,7 +421,7 @@ mod tests {
             [].into(),
         );
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::WindowsFastcall);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::WindowsFastcall).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::xmm0());
@@ -450,7 +439,7 @@ mod tests {
             [I32, F32, I32, F32, I64].into(),
         );
 
-        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::WindowsFastcall);
+        let sig = X64ABI::sig(&wasm_sig, &CallingConvention::WindowsFastcall).unwrap();
         let params = sig.params;
         let results = sig.results;
 
@@ -471,6 +460,17 @@ mod tests {
     }
 
     #[track_caller]
+    #[test]
+    fn unsupported_ref_abi_sig() {
+        let anyref = Ref(WasmRefType {
+            nullable: true,
+            heap_type: WasmHeapType::Any,
+        });
+        let wasm_sig = WasmFuncType::new([].into(), [anyref].into());
+
+        assert!(X64ABI::sig(&wasm_sig, &CallingConvention::Default).is_err());
+    }
+
     fn match_reg_arg(abi_arg: &ABIOperand, expected_ty: WasmValType, expected_reg: Reg) {
         match abi_arg {
             &ABIOperand::Reg { reg, ty, .. } => {
@@ -15,10 +15,10 @@ use cranelift_codegen::{
         unwind::UnwindInst,
         x64::{
             args::{
-                self, AluRmiROpcode, Amode, CmpOpcode, DivSignedness, ExtMode, FromWritableReg,
-                Gpr, GprMem, GprMemImm, Imm8Gpr, Imm8Reg, RegMem, RegMemImm,
+                self, AluRmiROpcode, Amode, AvxOpcode, CmpOpcode, DivSignedness, ExtMode,
+                FromWritableReg, Gpr, GprMem, GprMemImm, Imm8Gpr, Imm8Reg, RegMem, RegMemImm,
                 ShiftKind as CraneliftShiftKind, SseOpcode, SyntheticAmode, WritableGpr,
-                WritableXmm, Xmm, XmmMem, XmmMemAligned, CC,
+                WritableXmm, Xmm, XmmMem, XmmMemAligned, XmmMemAlignedImm, CC,
             },
             encoding::rex::{encode_modrm, RexFlags},
             settings as x64_settings, EmitInfo, EmitState, Inst,
@@ -1269,6 +1269,123 @@ impl Assembler {
         })
     }
 
+    /// Packed SSE operation in two-argument form, computing
+    /// `dst = dst <op> src`.
+    pub fn xmm_rm_r(&mut self, op: SseOpcode, src: Reg, dst: WritableReg) {
+        self.emit(Inst::XmmRmR {
+            op,
+            src1: dst.to_reg().into(),
+            src2: XmmMemAligned::from(Xmm::from(src)),
+            dst: dst.map(Into::into),
+        });
+    }
+
+    /// Packed SSE unary operation, computing `dst = <op> src`.
+    pub fn xmm_unary_rr(&mut self, op: SseOpcode, src: Reg, dst: WritableReg) {
+        self.emit(Inst::XmmUnaryRmR {
+            op,
+            src: XmmMemAligned::from(Xmm::from(src)),
+            dst: dst.map(Into::into),
+        });
+    }
+
+    /// Packed SSE unary operation with an immediate operand, e.g. `pshufd`
+    /// or `roundps`.
+    pub fn xmm_unary_rr_imm(&mut self, op: SseOpcode, src: Reg, dst: WritableReg, imm: u8) {
+        self.emit(Inst::XmmUnaryRmRImm {
+            op,
+            src: XmmMemAligned::from(Xmm::from(src)),
+            imm,
+            dst: dst.map(Into::into),
+        });
+    }
+
+    /// Packed SSE operation in two-argument form with an immediate operand,
+    /// e.g. `cmpps`, `shufps` or `pinsrb`. For lane insertions `src` is a
+    /// general purpose register and `size` selects between the 32 and 64-bit
+    /// forms of the instruction.
+    pub fn xmm_rm_r_imm(
+        &mut self,
+        op: SseOpcode,
+        src: Reg,
+        dst: WritableReg,
+        imm: u8,
+        size: OperandSize,
+    ) {
+        self.emit(Inst::XmmRmRImm {
+            op,
+            src1: dst.to_reg().into(),
+            src2: src.into(),
+            dst: dst.map(Into::into),
+            imm,
+            size: size.into(),
+        });
+    }
+
+    /// Packed shift by an immediate amount.
+    pub fn xmm_shift_ir(&mut self, op: SseOpcode, imm: u8, dst: WritableReg) {
+        self.emit(Inst::XmmRmiReg {
+            opcode: op,
+            src1: dst.to_reg().into(),
+            src2: XmmMemAlignedImm::unwrap_new(RegMemImm::imm(imm.into())),
+            dst: dst.map(Into::into),
+        });
+    }
+
+    /// Packed shift by the amount held in the low 64 bits of the `src`
+    /// vector register.
+    pub fn xmm_shift_rr(&mut self, op: SseOpcode, src: Reg, dst: WritableReg) {
+        self.emit(Inst::XmmRmiReg {
+            opcode: op,
+            src1: dst.to_reg().into(),
+            src2: XmmMemAlignedImm::unwrap_new(src.into()),
+            dst: dst.map(Into::into),
+        });
+    }
+
+    /// Extract a vector lane into a general purpose register, e.g. `pextrb`.
+    pub fn xmm_to_gpr_imm(&mut self, op: SseOpcode, src: Reg, dst: WritableReg, imm: u8) {
+        self.emit(Inst::XmmToGprImm {
+            op,
+            src: src.into(),
+            dst: dst.map(Into::into),
+            imm,
+        });
+    }
+
+    /// Collect the most significant bit of each lane into a general purpose
+    /// register, e.g. `pmovmskb`.
+    pub fn xmm_movmsk(&mut self, op: SseOpcode, src: Reg, dst: WritableReg) {
+        self.emit(Inst::XmmToGpr {
+            op,
+            src: src.into(),
+            dst: dst.map(Into::into),
+            dst_size: args::OperandSize::Size32,
+        });
+    }
+
+    /// Logical compare of two vector registers, setting ZF if their bitwise
+    /// and is zero.
+    pub fn ptest(&mut self, src1: Reg, src2: Reg) {
+        self.emit(Inst::XmmCmpRmR {
+            op: SseOpcode::Ptest,
+            src1: src1.into(),
+            src2: Xmm::from(src2).into(),
+        });
+    }
+
+    /// Fused multiply-add using the VEX encoding, in the 213 operand order:
+    /// `dst = src1 * dst <op> src2`.
+    pub fn xmm_vex_fma(&mut self, op: AvxOpcode, src1: Reg, src2: Reg, dst: WritableReg) {
+        self.emit(Inst::XmmRmRVex3 {
+            op,
+            src1: dst.to_reg().into(),
+            src2: src1.into(),
+            src3: XmmMem::unwrap_new(src2.into()),
+            dst: dst.map(Into::into),
+        });
+    }
+
     /// Emit a call to an unknown location through a register.
     pub fn call_with_reg(&mut self, callee: Reg) {
         self.emit(Inst::CallUnknown {
@@ -6,9 +6,10 @@ use super::{
 };
 
 use crate::masm::{
-    DivKind, ExtendKind, FloatCmpKind, Imm as I, IntCmpKind, MacroAssembler as Masm, MulWideKind,
-    OperandSize, RegImm, RemKind, RoundingMode, ShiftKind, TrapCode, TruncKind, TRUSTED_FLAGS,
-    UNTRUSTED_FLAGS,
+    DivKind, ExtendKind, ExtractLaneKind, FloatCmpKind, Imm as I, IntCmpKind,
+    MacroAssembler as Masm, MulWideKind, OperandSize, RegImm, RemKind, RoundingMode, ShiftKind,
+    TrapCode, TruncKind, V128ArithKind, V128ConvertKind, V128ExtAddKind, V128ExtendKind,
+    V128MinMaxKind, V128NarrowKind, V128TruncKind, VectorShape, TRUSTED_FLAGS, UNTRUSTED_FLAGS,
 };
 use crate::{
     abi::{self, align_to, calculate_frame_adjustment, LocalSlot},
@@ -28,11 +29,12 @@ use cranelift_codegen::{
     ir::{MemFlags, RelSourceLoc, SourceLoc},
     isa::unwind::UnwindInst,
     isa::x64::{
-        args::{ExtMode, CC},
+        args::{AvxOpcode, ExtMode, SseOpcode, CC},
         settings as x64_settings,
     },
     settings, Final, MachBufferFinalized, MachLabel,
 };
+use wasmparser::Operator;
 use wasmtime_cranelift::TRAP_UNREACHABLE;
 use wasmtime_environ::{PtrSize, WasmValType};
 
@@ -1059,6 +1061,1209 @@ impl Masm for MacroAssembler {
         // The high bits of the result are in rdx, which we previously reserved.
         context.stack.push(Val::Reg(TypedReg::i64(rdx)));
     }
+
+    fn v128_splat(&mut self, dst: WritableReg, src: Reg, shape: VectorShape) {
+        match shape {
+            VectorShape::I8x16 if self.flags.has_ssse3() => {
+                let scratch = regs::scratch_xmm();
+                self.asm.gpr_to_xmm(src, dst, OperandSize::S32);
+                // Shuffling with an all-zeros mask broadcasts the first byte.
+                self.asm
+                    .xmm_rm_r(SseOpcode::Pxor, scratch, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Pshufb, scratch, dst);
+            }
+            VectorShape::I8x16 => {
+                // Without `pshufb`, widen the first byte into a word and
+                // broadcast it as in the `i16x8` case.
+                self.asm.gpr_to_xmm(src, dst, OperandSize::S32);
+                self.asm.xmm_rm_r(SseOpcode::Punpcklbw, dst.to_reg(), dst);
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshuflw, dst.to_reg(), dst, 0);
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshufd, dst.to_reg(), dst, 0);
+            }
+            VectorShape::I16x8 => {
+                self.asm.gpr_to_xmm(src, dst, OperandSize::S32);
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshuflw, dst.to_reg(), dst, 0);
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshufd, dst.to_reg(), dst, 0);
+            }
+            VectorShape::I32x4 => {
+                self.asm.gpr_to_xmm(src, dst, OperandSize::S32);
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshufd, dst.to_reg(), dst, 0);
+            }
+            VectorShape::I64x2 => {
+                self.asm.gpr_to_xmm(src, dst, OperandSize::S64);
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshufd, dst.to_reg(), dst, 0x44);
+            }
+            VectorShape::F32x4 => self.asm.xmm_unary_rr_imm(SseOpcode::Pshufd, src, dst, 0),
+            VectorShape::F64x2 => self.asm.xmm_unary_rr_imm(SseOpcode::Pshufd, src, dst, 0x44),
+        }
+    }
+
+    fn v128_extract_lane(&mut self, dst: WritableReg, src: Reg, lane: u8, kind: ExtractLaneKind) {
+        match kind {
+            // Without `pextrb`, extract the word containing the lane.
+            ExtractLaneKind::I8x16S | ExtractLaneKind::I8x16U if !self.flags.has_sse41() => {
+                self.asm
+                    .xmm_to_gpr_imm(SseOpcode::Pextrw, src, dst, lane / 2);
+                if lane % 2 == 1 {
+                    self.asm.shift_ir(8, dst, ShiftKind::ShrU, OperandSize::S32);
+                }
+                if let ExtractLaneKind::I8x16S = kind {
+                    self.asm
+                        .movsx_rr(dst.to_reg(), dst, ExtendKind::I32Extend8S);
+                } else {
+                    self.asm.and_ir(0xff, dst, OperandSize::S32);
+                }
+            }
+            // Without `pextrd` and `pextrq`, move the lane to the lowest
+            // position first.
+            ExtractLaneKind::I32x4 | ExtractLaneKind::I64x2 if !self.flags.has_sse41() => {
+                let (imm, size) = match kind {
+                    ExtractLaneKind::I32x4 => (lane, OperandSize::S32),
+                    _ => (if lane == 0 { 0x44 } else { 0xee }, OperandSize::S64),
+                };
+                let scratch = regs::scratch_xmm();
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshufd, src, writable!(scratch), imm);
+                self.asm.xmm_to_gpr(scratch, dst, size);
+            }
+            ExtractLaneKind::I8x16S => {
+                self.asm.xmm_to_gpr_imm(SseOpcode::Pextrb, src, dst, lane);
+                self.asm
+                    .movsx_rr(dst.to_reg(), dst, ExtendKind::I32Extend8S);
+            }
+            ExtractLaneKind::I8x16U => self.asm.xmm_to_gpr_imm(SseOpcode::Pextrb, src, dst, lane),
+            ExtractLaneKind::I16x8S => {
+                self.asm.xmm_to_gpr_imm(SseOpcode::Pextrw, src, dst, lane);
+                self.asm
+                    .movsx_rr(dst.to_reg(), dst, ExtendKind::I32Extend16S);
+            }
+            ExtractLaneKind::I16x8U => self.asm.xmm_to_gpr_imm(SseOpcode::Pextrw, src, dst, lane),
+            ExtractLaneKind::I32x4 => self.asm.xmm_to_gpr_imm(SseOpcode::Pextrd, src, dst, lane),
+            ExtractLaneKind::I64x2 => self.asm.xmm_to_gpr_imm(SseOpcode::Pextrq, src, dst, lane),
+            // Move the requested lane to the lowest position; the
+            // upper lanes are irrelevant for scalar floats.
+            ExtractLaneKind::F32x4 => self.asm.xmm_unary_rr_imm(SseOpcode::Pshufd, src, dst, lane),
+            ExtractLaneKind::F64x2 => {
+                let imm = if lane == 0 { 0x44 } else { 0xee };
+                self.asm.xmm_unary_rr_imm(SseOpcode::Pshufd, src, dst, imm);
+            }
+        }
+    }
+
+    fn v128_replace_lane(&mut self, dst: WritableReg, src: Reg, lane: u8, shape: VectorShape) {
+        match shape {
+            // Without `pinsrb`, merge the byte into the word containing the
+            // lane. The contents of `src` are clobbered.
+            VectorShape::I8x16 if !self.flags.has_sse41() => {
+                let scratch = regs::scratch();
+                self.asm.xmm_to_gpr_imm(
+                    SseOpcode::Pextrw,
+                    dst.to_reg(),
+                    writable!(scratch),
+                    lane / 2,
+                );
+                self.asm.and_ir(0xff, writable!(src), OperandSize::S32);
+                if lane % 2 == 1 {
+                    self.asm.and_ir(0xff, writable!(scratch), OperandSize::S32);
+                    self.asm
+                        .shift_ir(8, writable!(src), ShiftKind::Shl, OperandSize::S32);
+                } else {
+                    self.asm
+                        .and_ir(0xff00, writable!(scratch), OperandSize::S32);
+                }
+                self.asm.or_rr(src, writable!(scratch), OperandSize::S32);
+                self.asm
+                    .xmm_rm_r_imm(SseOpcode::Pinsrw, scratch, dst, lane / 2, OperandSize::S32);
+            }
+            // Without `pinsrd` and `insertps`, insert the lane as two words.
+            VectorShape::I32x4 | VectorShape::F32x4 if !self.flags.has_sse41() => {
+                let scratch = regs::scratch();
+                if src.is_int() {
+                    self.asm.mov_rr(src, writable!(scratch), OperandSize::S32);
+                } else {
+                    self.asm
+                        .xmm_to_gpr(src, writable!(scratch), OperandSize::S32);
+                }
+                self.asm
+                    .xmm_rm_r_imm(SseOpcode::Pinsrw, scratch, dst, lane * 2, OperandSize::S32);
+                self.asm
+                    .shift_ir(16, writable!(scratch), ShiftKind::ShrU, OperandSize::S32);
+                self.asm.xmm_rm_r_imm(
+                    SseOpcode::Pinsrw,
+                    scratch,
+                    dst,
+                    lane * 2 + 1,
+                    OperandSize::S32,
+                );
+            }
+            // Without `pinsrq`, insert the lane as in the `f64x2` case.
+            VectorShape::I64x2 if !self.flags.has_sse41() => {
+                let scratch = regs::scratch_xmm();
+                self.asm
+                    .gpr_to_xmm(src, writable!(scratch), OperandSize::S64);
+                let op = if lane == 0 {
+                    SseOpcode::Movsd
+                } else {
+                    SseOpcode::Movlhps
+                };
+                self.asm.xmm_rm_r(op, scratch, dst);
+            }
+            VectorShape::I8x16 => {
+                self.asm
+                    .xmm_rm_r_imm(SseOpcode::Pinsrb, src, dst, lane, OperandSize::S32)
+            }
+            VectorShape::I16x8 => {
+                self.asm
+                    .xmm_rm_r_imm(SseOpcode::Pinsrw, src, dst, lane, OperandSize::S32)
+            }
+            VectorShape::I32x4 => {
+                self.asm
+                    .xmm_rm_r_imm(SseOpcode::Pinsrd, src, dst, lane, OperandSize::S32)
+            }
+            // `pinsrq` is the 64-bit form of `pinsrd`.
+            VectorShape::I64x2 => {
+                self.asm
+                    .xmm_rm_r_imm(SseOpcode::Pinsrd, src, dst, lane, OperandSize::S64)
+            }
+            VectorShape::F32x4 => {
+                self.asm
+                    .xmm_rm_r_imm(SseOpcode::Insertps, src, dst, lane << 4, OperandSize::S32)
+            }
+            VectorShape::F64x2 => {
+                let op = if lane == 0 {
+                    SseOpcode::Movsd
+                } else {
+                    SseOpcode::Movlhps
+                };
+                self.asm.xmm_rm_r(op, src, dst);
+            }
+        }
+    }
+
+    fn v128_shuffle(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, lanes: [u8; 16]) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        // Shuffle each operand independently, zeroing the bytes selected
+        // from the other operand (0x80), and combine the results.
+        let lhs_mask = lanes.map(|l| if l < 16 { l } else { 0x80 });
+        let rhs_mask = lanes.map(|l| if l < 16 { 0x80 } else { l - 16 });
+        let scratch = regs::scratch_xmm();
+        self.load_v128_constant(u128::from_le_bytes(lhs_mask), writable!(scratch));
+        self.asm.xmm_rm_r(SseOpcode::Pshufb, scratch, dst);
+        self.load_v128_constant(u128::from_le_bytes(rhs_mask), writable!(scratch));
+        self.asm
+            .xmm_rm_r(SseOpcode::Pshufb, scratch, writable!(rhs));
+        self.asm.xmm_rm_r(SseOpcode::Por, rhs, dst);
+    }
+
+    fn v128_swizzle(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        // Saturate indices greater than 15 so that their most significant
+        // bit is set, which makes `pshufb` produce zero for them.
+        let scratch = regs::scratch_xmm();
+        self.load_v128_constant(0x70707070_70707070_70707070_70707070, writable!(scratch));
+        self.asm
+            .xmm_rm_r(SseOpcode::Paddusb, scratch, writable!(rhs));
+        self.asm.xmm_rm_r(SseOpcode::Pshufb, rhs, dst);
+    }
+
+    fn v128_not(&mut self, dst: WritableReg) {
+        let scratch = regs::scratch_xmm();
+        self.asm
+            .xmm_rm_r(SseOpcode::Pcmpeqd, scratch, writable!(scratch));
+        self.asm.xmm_rm_r(SseOpcode::Pxor, scratch, dst);
+    }
+
+    fn v128_and(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        self.asm.xmm_rm_r(SseOpcode::Pand, rhs, dst);
+    }
+
+    fn v128_and_not(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        // `pandn` negates its first operand.
+        self.asm.xmm_rm_r(SseOpcode::Pandn, lhs, writable!(rhs));
+        self.asm.xmm_mov_rr(rhs, dst, OperandSize::S128);
+    }
+
+    fn v128_or(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        self.asm.xmm_rm_r(SseOpcode::Por, rhs, dst);
+    }
+
+    fn v128_xor(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        self.asm.xmm_rm_r(SseOpcode::Pxor, rhs, dst);
+    }
+
+    fn v128_bitselect(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, mask: Reg) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        self.asm.xmm_rm_r(SseOpcode::Pand, mask, dst);
+        self.asm.xmm_rm_r(SseOpcode::Pandn, rhs, writable!(mask));
+        self.asm.xmm_rm_r(SseOpcode::Por, mask, dst);
+    }
+
+    fn v128_any_true(&mut self, dst: WritableReg, src: Reg) {
+        self.asm.ptest(src, src);
+        self.asm.setcc(IntCmpKind::Ne, dst);
+    }
+
+    fn v128_all_true(&mut self, dst: WritableReg, src: Reg, shape: VectorShape) {
+        let op = match shape {
+            VectorShape::I8x16 => SseOpcode::Pcmpeqb,
+            VectorShape::I16x8 => SseOpcode::Pcmpeqw,
+            VectorShape::I32x4 => SseOpcode::Pcmpeqd,
+            VectorShape::I64x2 => SseOpcode::Pcmpeqq,
+            VectorShape::F32x4 | VectorShape::F64x2 => unreachable!(),
+        };
+        // Compute a mask of the zero lanes and check that it's empty.
+        let scratch = regs::scratch_xmm();
+        self.asm
+            .xmm_rm_r(SseOpcode::Pxor, scratch, writable!(scratch));
+        self.asm.xmm_rm_r(op, src, writable!(scratch));
+        self.asm.ptest(scratch, scratch);
+        self.asm.setcc(IntCmpKind::Eq, dst);
+    }
+
+    fn v128_bitmask(&mut self, dst: WritableReg, src: Reg, shape: VectorShape) {
+        match shape {
+            VectorShape::I8x16 => self.asm.xmm_movmsk(SseOpcode::Pmovmskb, src, dst),
+            VectorShape::I16x8 => {
+                // Narrow the lanes to bytes, preserving their sign; the
+                // upper eight bits of the byte mask hold the result.
+                let scratch = regs::scratch_xmm();
+                self.asm
+                    .xmm_mov_rr(src, writable!(scratch), OperandSize::S128);
+                self.asm
+                    .xmm_rm_r(SseOpcode::Packsswb, scratch, writable!(scratch));
+                self.asm.xmm_movmsk(SseOpcode::Pmovmskb, scratch, dst);
+                self.asm.shift_ir(8, dst, ShiftKind::ShrU, OperandSize::S32);
+            }
+            VectorShape::I32x4 => self.asm.xmm_movmsk(SseOpcode::Movmskps, src, dst),
+            VectorShape::I64x2 => self.asm.xmm_movmsk(SseOpcode::Movmskpd, src, dst),
+            VectorShape::F32x4 | VectorShape::F64x2 => unreachable!(),
+        }
+    }
+
+    fn v128_add(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128ArithKind) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let op = match kind {
+            V128ArithKind::I8x16 => SseOpcode::Paddb,
+            V128ArithKind::I8x16SatS => SseOpcode::Paddsb,
+            V128ArithKind::I8x16SatU => SseOpcode::Paddusb,
+            V128ArithKind::I16x8 => SseOpcode::Paddw,
+            V128ArithKind::I16x8SatS => SseOpcode::Paddsw,
+            V128ArithKind::I16x8SatU => SseOpcode::Paddusw,
+            V128ArithKind::I32x4 => SseOpcode::Paddd,
+            V128ArithKind::I64x2 => SseOpcode::Paddq,
+            V128ArithKind::F32x4 => SseOpcode::Addps,
+            V128ArithKind::F64x2 => SseOpcode::Addpd,
+        };
+        self.asm.xmm_rm_r(op, rhs, dst);
+    }
+
+    fn v128_sub(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128ArithKind) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let op = match kind {
+            V128ArithKind::I8x16 => SseOpcode::Psubb,
+            V128ArithKind::I8x16SatS => SseOpcode::Psubsb,
+            V128ArithKind::I8x16SatU => SseOpcode::Psubusb,
+            V128ArithKind::I16x8 => SseOpcode::Psubw,
+            V128ArithKind::I16x8SatS => SseOpcode::Psubsw,
+            V128ArithKind::I16x8SatU => SseOpcode::Psubusw,
+            V128ArithKind::I32x4 => SseOpcode::Psubd,
+            V128ArithKind::I64x2 => SseOpcode::Psubq,
+            V128ArithKind::F32x4 => SseOpcode::Subps,
+            V128ArithKind::F64x2 => SseOpcode::Subpd,
+        };
+        self.asm.xmm_rm_r(op, rhs, dst);
+    }
+
+    fn v128_mul(&mut self, context: &mut CodeGenContext, shape: VectorShape) {
+        let op = match shape {
+            VectorShape::I8x16 => unreachable!(),
+            VectorShape::I16x8 => SseOpcode::Pmullw,
+            VectorShape::I32x4 => SseOpcode::Pmulld,
+            VectorShape::F32x4 => SseOpcode::Mulps,
+            VectorShape::F64x2 => SseOpcode::Mulpd,
+            VectorShape::I64x2 => {
+                // There's no 64-bit lane multiplication before AVX-512, so
+                // compose it out of 32-bit multiplications:
+                // lo(a) * lo(b) + ((hi(a) * lo(b) + lo(a) * hi(b)) << 32).
+                let tmp = context.any_fpr(self);
+                context.binop(self, OperandSize::S128, |masm, dst, src, _| {
+                    let scratch = regs::scratch_xmm();
+                    masm.asm
+                        .xmm_mov_rr(dst, writable!(scratch), OperandSize::S128);
+                    masm.asm
+                        .xmm_shift_ir(SseOpcode::Psrlq, 32, writable!(scratch));
+                    masm.asm
+                        .xmm_rm_r(SseOpcode::Pmuludq, src, writable!(scratch));
+                    masm.asm.xmm_mov_rr(src, writable!(tmp), OperandSize::S128);
+                    masm.asm.xmm_shift_ir(SseOpcode::Psrlq, 32, writable!(tmp));
+                    masm.asm.xmm_rm_r(SseOpcode::Pmuludq, dst, writable!(tmp));
+                    masm.asm.xmm_rm_r(SseOpcode::Paddq, tmp, writable!(scratch));
+                    masm.asm
+                        .xmm_shift_ir(SseOpcode::Psllq, 32, writable!(scratch));
+                    masm.asm.xmm_rm_r(SseOpcode::Pmuludq, src, writable!(dst));
+                    masm.asm.xmm_rm_r(SseOpcode::Paddq, scratch, writable!(dst));
+                    TypedReg::v128(dst)
+                });
+                context.free_reg(tmp);
+                return;
+            }
+        };
+        context.binop(self, OperandSize::S128, |masm, dst, src, _| {
+            masm.asm.xmm_rm_r(op, src, writable!(dst));
+            TypedReg::v128(dst)
+        });
+    }
+
+    fn v128_div(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, shape: VectorShape) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let op = match shape {
+            VectorShape::F32x4 => SseOpcode::Divps,
+            VectorShape::F64x2 => SseOpcode::Divpd,
+            _ => unreachable!(),
+        };
+        self.asm.xmm_rm_r(op, rhs, dst);
+    }
+
+    fn v128_min(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128MinMaxKind) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let (min, cmp, shift) = match kind {
+            V128MinMaxKind::I8x16S => return self.asm.xmm_rm_r(SseOpcode::Pminsb, rhs, dst),
+            V128MinMaxKind::I8x16U => return self.asm.xmm_rm_r(SseOpcode::Pminub, rhs, dst),
+            V128MinMaxKind::I16x8S => return self.asm.xmm_rm_r(SseOpcode::Pminsw, rhs, dst),
+            V128MinMaxKind::I16x8U => return self.asm.xmm_rm_r(SseOpcode::Pminuw, rhs, dst),
+            V128MinMaxKind::I32x4S => return self.asm.xmm_rm_r(SseOpcode::Pminsd, rhs, dst),
+            V128MinMaxKind::I32x4U => return self.asm.xmm_rm_r(SseOpcode::Pminud, rhs, dst),
+            V128MinMaxKind::F32x4 => (SseOpcode::Minps, SseOpcode::Cmpps, SseOpcode::Psrld),
+            V128MinMaxKind::F64x2 => (SseOpcode::Minpd, SseOpcode::Cmppd, SseOpcode::Psrlq),
+        };
+        let shift_amount = if shift == SseOpcode::Psrld { 10 } else { 13 };
+        // `minps` returns its second operand if either operand is NaN or
+        // both are zero, so compute the minimum in both directions and
+        // merge the results to propagate NaNs and -0.0; finally, canonicalize
+        // NaNs by clearing their fraction bits except the quiet bit.
+        let scratch = regs::scratch_xmm();
+        self.asm
+            .xmm_mov_rr(lhs, writable!(scratch), OperandSize::S128);
+        self.asm.xmm_rm_r(min, rhs, writable!(scratch));
+        self.asm.xmm_rm_r(min, lhs, writable!(rhs));
+        self.asm.xmm_rm_r(SseOpcode::Orps, rhs, writable!(scratch));
+        // Unordered comparison.
+        self.asm
+            .xmm_rm_r_imm(cmp, scratch, writable!(rhs), 3, OperandSize::S32);
+        self.asm.xmm_rm_r(SseOpcode::Orps, rhs, writable!(scratch));
+        self.asm.xmm_shift_ir(shift, shift_amount, writable!(rhs));
+        self.asm
+            .xmm_rm_r(SseOpcode::Andnps, scratch, writable!(rhs));
+        self.asm.xmm_mov_rr(rhs, dst, OperandSize::S128);
+    }
+
+    fn v128_max(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128MinMaxKind) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let (max, sub, cmp, shift) = match kind {
+            V128MinMaxKind::I8x16S => return self.asm.xmm_rm_r(SseOpcode::Pmaxsb, rhs, dst),
+            V128MinMaxKind::I8x16U => return self.asm.xmm_rm_r(SseOpcode::Pmaxub, rhs, dst),
+            V128MinMaxKind::I16x8S => return self.asm.xmm_rm_r(SseOpcode::Pmaxsw, rhs, dst),
+            V128MinMaxKind::I16x8U => return self.asm.xmm_rm_r(SseOpcode::Pmaxuw, rhs, dst),
+            V128MinMaxKind::I32x4S => return self.asm.xmm_rm_r(SseOpcode::Pmaxsd, rhs, dst),
+            V128MinMaxKind::I32x4U => return self.asm.xmm_rm_r(SseOpcode::Pmaxud, rhs, dst),
+            V128MinMaxKind::F32x4 => (
+                SseOpcode::Maxps,
+                SseOpcode::Subps,
+                SseOpcode::Cmpps,
+                SseOpcode::Psrld,
+            ),
+            V128MinMaxKind::F64x2 => (
+                SseOpcode::Maxpd,
+                SseOpcode::Subpd,
+                SseOpcode::Cmppd,
+                SseOpcode::Psrlq,
+            ),
+        };
+        let shift_amount = if shift == SseOpcode::Psrld { 10 } else { 13 };
+        // Similar to `v128_min`, compute the maximum in both directions; the
+        // difference between both results identifies NaNs and mismatched
+        // zeros, which are then merged into the final result.
+        let scratch = regs::scratch_xmm();
+        self.asm
+            .xmm_mov_rr(lhs, writable!(scratch), OperandSize::S128);
+        self.asm.xmm_rm_r(max, rhs, writable!(scratch));
+        self.asm.xmm_rm_r(max, lhs, writable!(rhs));
+        self.asm.xmm_rm_r(SseOpcode::Xorps, scratch, writable!(rhs));
+        self.asm.xmm_rm_r(SseOpcode::Orps, rhs, writable!(scratch));
+        self.asm.xmm_mov_rr(scratch, dst, OperandSize::S128);
+        // Unordered comparison.
+        self.asm
+            .xmm_rm_r_imm(cmp, dst.to_reg(), dst, 3, OperandSize::S32);
+        self.asm.xmm_rm_r(sub, rhs, writable!(scratch));
+        self.asm.xmm_shift_ir(shift, shift_amount, dst);
+        self.asm.xmm_rm_r(SseOpcode::Andnps, scratch, dst);
+    }
+
+    fn v128_pmin(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, shape: VectorShape) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let op = match shape {
+            VectorShape::F32x4 => SseOpcode::Minps,
+            VectorShape::F64x2 => SseOpcode::Minpd,
+            _ => unreachable!(),
+        };
+        // `minps` returns its second operand if the comparison fails, which
+        // matches `rhs < lhs ? rhs : lhs` when `rhs` is the first operand.
+        self.asm.xmm_rm_r(op, lhs, writable!(rhs));
+        self.asm.xmm_mov_rr(rhs, dst, OperandSize::S128);
+    }
+
+    fn v128_pmax(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, shape: VectorShape) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let op = match shape {
+            VectorShape::F32x4 => SseOpcode::Maxps,
+            VectorShape::F64x2 => SseOpcode::Maxpd,
+            _ => unreachable!(),
+        };
+        self.asm.xmm_rm_r(op, lhs, writable!(rhs));
+        self.asm.xmm_mov_rr(rhs, dst, OperandSize::S128);
+    }
+
+    fn v128_avgr(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, shape: VectorShape) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let op = match shape {
+            VectorShape::I8x16 => SseOpcode::Pavgb,
+            VectorShape::I16x8 => SseOpcode::Pavgw,
+            _ => unreachable!(),
+        };
+        self.asm.xmm_rm_r(op, rhs, dst);
+    }
+
+    fn v128_neg(&mut self, dst: WritableReg, shape: VectorShape) {
+        let scratch = regs::scratch_xmm();
+        let (sub, shift) = match shape {
+            VectorShape::I8x16 => (SseOpcode::Psubb, None),
+            VectorShape::I16x8 => (SseOpcode::Psubw, None),
+            VectorShape::I32x4 => (SseOpcode::Psubd, None),
+            VectorShape::I64x2 => (SseOpcode::Psubq, None),
+            VectorShape::F32x4 => (SseOpcode::Xorps, Some((SseOpcode::Pslld, 31))),
+            VectorShape::F64x2 => (SseOpcode::Xorps, Some((SseOpcode::Psllq, 63))),
+        };
+        match shift {
+            // Flip the sign bit.
+            Some((shift, amount)) => {
+                self.asm
+                    .xmm_rm_r(SseOpcode::Pcmpeqd, scratch, writable!(scratch));
+                self.asm.xmm_shift_ir(shift, amount, writable!(scratch));
+                self.asm.xmm_rm_r(sub, scratch, dst);
+            }
+            // Subtract from zero.
+            None => {
+                self.asm
+                    .xmm_rm_r(SseOpcode::Pxor, scratch, writable!(scratch));
+                self.asm.xmm_rm_r(sub, dst.to_reg(), writable!(scratch));
+                self.asm.xmm_mov_rr(scratch, dst, OperandSize::S128);
+            }
+        }
+    }
+
+    fn v128_abs(&mut self, dst: WritableReg, shape: VectorShape) {
+        let scratch = regs::scratch_xmm();
+        match shape {
+            VectorShape::I8x16 => self.asm.xmm_unary_rr(SseOpcode::Pabsb, dst.to_reg(), dst),
+            VectorShape::I16x8 => self.asm.xmm_unary_rr(SseOpcode::Pabsw, dst.to_reg(), dst),
+            VectorShape::I32x4 => self.asm.xmm_unary_rr(SseOpcode::Pabsd, dst.to_reg(), dst),
+            VectorShape::I64x2 => {
+                // Compute a mask of the sign of each lane by broadcasting
+                // the sign of the upper half, and use it to conditionally
+                // negate each lane: (x ^ mask) - mask.
+                self.asm
+                    .xmm_mov_rr(dst.to_reg(), writable!(scratch), OperandSize::S128);
+                self.asm
+                    .xmm_shift_ir(SseOpcode::Psrad, 31, writable!(scratch));
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshufd, scratch, writable!(scratch), 0xf5);
+                self.asm.xmm_rm_r(SseOpcode::Pxor, scratch, dst);
+                self.asm.xmm_rm_r(SseOpcode::Psubq, scratch, dst);
+            }
+            // Clear the sign bit.
+            VectorShape::F32x4 | VectorShape::F64x2 => {
+                let shift = if shape == VectorShape::F32x4 {
+                    SseOpcode::Psrld
+                } else {
+                    SseOpcode::Psrlq
+                };
+                self.asm
+                    .xmm_rm_r(SseOpcode::Pcmpeqd, scratch, writable!(scratch));
+                self.asm.xmm_shift_ir(shift, 1, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Andps, scratch, dst);
+            }
+        }
+    }
+
+    fn v128_sqrt(&mut self, dst: WritableReg, src: Reg, shape: VectorShape) {
+        let op = match shape {
+            VectorShape::F32x4 => SseOpcode::Sqrtps,
+            VectorShape::F64x2 => SseOpcode::Sqrtpd,
+            _ => unreachable!(),
+        };
+        self.asm.xmm_unary_rr(op, src, dst);
+    }
+
+    fn v128_round(&mut self, dst: WritableReg, src: Reg, mode: RoundingMode, shape: VectorShape) {
+        let op = match shape {
+            VectorShape::F32x4 => SseOpcode::Roundps,
+            VectorShape::F64x2 => SseOpcode::Roundpd,
+            _ => unreachable!(),
+        };
+        let imm: u8 = match mode {
+            RoundingMode::Nearest => 0x00,
+            RoundingMode::Down => 0x01,
+            RoundingMode::Up => 0x02,
+            RoundingMode::Zero => 0x03,
+        };
+        self.asm.xmm_unary_rr_imm(op, src, dst, imm);
+    }
+
+    fn v128_popcnt(&mut self, context: &mut CodeGenContext) {
+        // Count the bits of each nibble through a lookup table and add the
+        // counts of the low and high nibbles of each byte.
+        let tmp = context.any_fpr(self);
+        context.unop(self, OperandSize::S128, &mut |masm, reg, _| {
+            let scratch = regs::scratch_xmm();
+            masm.load_v128_constant(0x0f0f0f0f_0f0f0f0f_0f0f0f0f_0f0f0f0f, writable!(scratch));
+            masm.asm.xmm_mov_rr(reg, writable!(tmp), OperandSize::S128);
+            masm.asm.xmm_shift_ir(SseOpcode::Psrlw, 4, writable!(tmp));
+            masm.asm.xmm_rm_r(SseOpcode::Pand, scratch, writable!(tmp));
+            masm.asm.xmm_rm_r(SseOpcode::Pand, scratch, writable!(reg));
+            let lut = 0x04030302_03020201_03020201_02010100;
+            masm.load_v128_constant(lut, writable!(scratch));
+            masm.asm
+                .xmm_rm_r(SseOpcode::Pshufb, reg, writable!(scratch));
+            masm.load_v128_constant(lut, writable!(reg));
+            masm.asm.xmm_rm_r(SseOpcode::Pshufb, tmp, writable!(reg));
+            masm.asm.xmm_rm_r(SseOpcode::Paddb, scratch, writable!(reg));
+            TypedReg::v128(reg)
+        });
+        context.free_reg(tmp);
+    }
+
+    fn v128_shift(&mut self, context: &mut CodeGenContext, shape: VectorShape, kind: ShiftKind) {
+        let lane_bits = shape.lane_size().num_bits();
+        let needs_tmp = matches!(
+            (shape, kind),
+            (VectorShape::I8x16, _) | (VectorShape::I64x2, ShiftKind::ShrS)
+        );
+        let tmp = needs_tmp.then(|| context.any_fpr(self));
+        let amount = context.pop_to_reg(self, None);
+        let operand = context.pop_to_reg(self, None);
+        let dst = writable!(operand.reg);
+        let scratch = regs::scratch_xmm();
+
+        // The shift amount is taken modulo the lane width.
+        self.asm.and_ir(
+            i32::from(lane_bits - 1),
+            writable!(amount.reg),
+            OperandSize::S32,
+        );
+
+        match (shape, kind) {
+            (VectorShape::I8x16, ShiftKind::ShrS) => {
+                // Unpack each byte into the upper half of a 16-bit lane,
+                // shift those and pack them back.
+                let tmp = tmp.unwrap();
+                self.asm
+                    .xmm_mov_rr(operand.reg, writable!(tmp), OperandSize::S128);
+                self.asm.xmm_rm_r(SseOpcode::Punpcklbw, operand.reg, dst);
+                self.asm.xmm_rm_r(SseOpcode::Punpckhbw, tmp, writable!(tmp));
+                self.asm.add_ir(8, writable!(amount.reg), OperandSize::S32);
+                self.asm
+                    .gpr_to_xmm(amount.reg, writable!(scratch), OperandSize::S32);
+                self.asm.xmm_shift_rr(SseOpcode::Psraw, scratch, dst);
+                self.asm
+                    .xmm_shift_rr(SseOpcode::Psraw, scratch, writable!(tmp));
+                self.asm.xmm_rm_r(SseOpcode::Packsswb, tmp, dst);
+            }
+            (VectorShape::I8x16, ShiftKind::Shl | ShiftKind::ShrU) => {
+                // Shift 16-bit lanes and clear the bits that crossed over from
+                // the neighbouring byte using a mask of the bits shifted
+                // within each byte.
+                let tmp = tmp.unwrap();
+                let op = if kind == ShiftKind::Shl {
+                    SseOpcode::Psllw
+                } else {
+                    SseOpcode::Psrlw
+                };
+                self.asm
+                    .gpr_to_xmm(amount.reg, writable!(scratch), OperandSize::S32);
+                self.asm.xmm_shift_rr(op, scratch, dst);
+                self.asm.xmm_rm_r(SseOpcode::Pcmpeqd, tmp, writable!(tmp));
+                self.asm.xmm_shift_ir(SseOpcode::Psrlw, 8, writable!(tmp));
+                self.asm.xmm_shift_rr(op, scratch, writable!(tmp));
+                if kind == ShiftKind::Shl {
+                    self.asm.xmm_shift_ir(SseOpcode::Psllw, 8, writable!(tmp));
+                    self.asm.xmm_shift_ir(SseOpcode::Psrlw, 8, writable!(tmp));
+                }
+                self.asm.xmm_rm_r(SseOpcode::Packuswb, tmp, writable!(tmp));
+                self.asm.xmm_rm_r(SseOpcode::Pand, tmp, dst);
+            }
+            (VectorShape::I64x2, ShiftKind::ShrS) => {
+                // There's no arithmetic shift for 64-bit lanes before
+                // AVX-512; perform a logical shift and sign extend the
+                // result with (x ^ m) - m, where m is the shifted sign bit.
+                let tmp = tmp.unwrap();
+                self.asm
+                    .gpr_to_xmm(amount.reg, writable!(scratch), OperandSize::S32);
+                self.asm.xmm_rm_r(SseOpcode::Pcmpeqd, tmp, writable!(tmp));
+                self.asm.xmm_shift_ir(SseOpcode::Psllq, 63, writable!(tmp));
+                self.asm
+                    .xmm_shift_rr(SseOpcode::Psrlq, scratch, writable!(tmp));
+                self.asm.xmm_shift_rr(SseOpcode::Psrlq, scratch, dst);
+                self.asm.xmm_rm_r(SseOpcode::Pxor, tmp, dst);
+                self.asm.xmm_rm_r(SseOpcode::Psubq, tmp, dst);
+            }
+            (_, ShiftKind::Rotl | ShiftKind::Rotr) => unreachable!(),
+            (shape, kind) => {
+                let op = match (shape, kind) {
+                    (VectorShape::I16x8, ShiftKind::Shl) => SseOpcode::Psllw,
+                    (VectorShape::I16x8, ShiftKind::ShrS) => SseOpcode::Psraw,
+                    (VectorShape::I16x8, ShiftKind::ShrU) => SseOpcode::Psrlw,
+                    (VectorShape::I32x4, ShiftKind::Shl) => SseOpcode::Pslld,
+                    (VectorShape::I32x4, ShiftKind::ShrS) => SseOpcode::Psrad,
+                    (VectorShape::I32x4, ShiftKind::ShrU) => SseOpcode::Psrld,
+                    (VectorShape::I64x2, ShiftKind::Shl) => SseOpcode::Psllq,
+                    (VectorShape::I64x2, ShiftKind::ShrU) => SseOpcode::Psrlq,
+                    _ => unreachable!(),
+                };
+                self.asm
+                    .gpr_to_xmm(amount.reg, writable!(scratch), OperandSize::S32);
+                self.asm.xmm_shift_rr(op, scratch, dst);
+            }
+        }
+
+        context.free_reg(amount);
+        if let Some(tmp) = tmp {
+            context.free_reg(tmp);
+        }
+        context.stack.push(operand.into());
+    }
+
+    fn v128_int_cmp(
+        &mut self,
+        dst: WritableReg,
+        lhs: Reg,
+        rhs: Reg,
+        kind: IntCmpKind,
+        shape: VectorShape,
+    ) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let (eq, gt, min_s, max_s, min_u, max_u) = match shape {
+            VectorShape::I8x16 => (
+                SseOpcode::Pcmpeqb,
+                SseOpcode::Pcmpgtb,
+                SseOpcode::Pminsb,
+                SseOpcode::Pmaxsb,
+                SseOpcode::Pminub,
+                SseOpcode::Pmaxub,
+            ),
+            VectorShape::I16x8 => (
+                SseOpcode::Pcmpeqw,
+                SseOpcode::Pcmpgtw,
+                SseOpcode::Pminsw,
+                SseOpcode::Pmaxsw,
+                SseOpcode::Pminuw,
+                SseOpcode::Pmaxuw,
+            ),
+            VectorShape::I32x4 => (
+                SseOpcode::Pcmpeqd,
+                SseOpcode::Pcmpgtd,
+                SseOpcode::Pminsd,
+                SseOpcode::Pmaxsd,
+                SseOpcode::Pminud,
+                SseOpcode::Pmaxud,
+            ),
+            VectorShape::I64x2 => {
+                // There are no 64-bit lane minimum and maximum instructions,
+                // so express every comparison in terms of `pcmpeqq` and
+                // `pcmpgtq`.
+                match kind {
+                    IntCmpKind::Eq | IntCmpKind::Ne => {
+                        self.asm.xmm_rm_r(SseOpcode::Pcmpeqq, rhs, dst)
+                    }
+                    IntCmpKind::GtS | IntCmpKind::LeS => {
+                        self.asm.xmm_rm_r(SseOpcode::Pcmpgtq, rhs, dst)
+                    }
+                    IntCmpKind::LtS | IntCmpKind::GeS => {
+                        self.asm.xmm_rm_r(SseOpcode::Pcmpgtq, lhs, writable!(rhs));
+                        self.asm.xmm_mov_rr(rhs, dst, OperandSize::S128);
+                    }
+                    _ => unreachable!(),
+                }
+                if matches!(kind, IntCmpKind::Ne | IntCmpKind::LeS | IntCmpKind::GeS) {
+                    self.v128_not(dst);
+                }
+                return;
+            }
+            VectorShape::F32x4 | VectorShape::F64x2 => unreachable!(),
+        };
+
+        match kind {
+            IntCmpKind::Eq => self.asm.xmm_rm_r(eq, rhs, dst),
+            IntCmpKind::Ne => {
+                self.asm.xmm_rm_r(eq, rhs, dst);
+                self.v128_not(dst);
+            }
+            IntCmpKind::GtS => self.asm.xmm_rm_r(gt, rhs, dst),
+            IntCmpKind::LtS => {
+                self.asm.xmm_rm_r(gt, lhs, writable!(rhs));
+                self.asm.xmm_mov_rr(rhs, dst, OperandSize::S128);
+            }
+            // `lhs >= rhs` iff `max(lhs, rhs) == lhs`, and similarly for the
+            // remaining comparisons.
+            IntCmpKind::GeS | IntCmpKind::GeU | IntCmpKind::LtU => {
+                let max = if kind == IntCmpKind::GeS {
+                    max_s
+                } else {
+                    max_u
+                };
+                self.asm.xmm_rm_r(max, lhs, writable!(rhs));
+                self.asm.xmm_rm_r(eq, rhs, dst);
+                if kind == IntCmpKind::LtU {
+                    self.v128_not(dst);
+                }
+            }
+            IntCmpKind::LeS | IntCmpKind::LeU | IntCmpKind::GtU => {
+                let min = if kind == IntCmpKind::LeS {
+                    min_s
+                } else {
+                    min_u
+                };
+                self.asm.xmm_rm_r(min, lhs, writable!(rhs));
+                self.asm.xmm_rm_r(eq, rhs, dst);
+                if kind == IntCmpKind::GtU {
+                    self.v128_not(dst);
+                }
+            }
+        }
+    }
+
+    fn v128_float_cmp(
+        &mut self,
+        dst: WritableReg,
+        lhs: Reg,
+        rhs: Reg,
+        kind: FloatCmpKind,
+        shape: VectorShape,
+    ) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let op = match shape {
+            VectorShape::F32x4 => SseOpcode::Cmpps,
+            VectorShape::F64x2 => SseOpcode::Cmppd,
+            _ => unreachable!(),
+        };
+        // Immediates for the `eq`, `lt`, `le` and `neq` (unordered) predicates.
+        let (imm, swap) = match kind {
+            FloatCmpKind::Eq => (0, false),
+            FloatCmpKind::Lt => (1, false),
+            FloatCmpKind::Le => (2, false),
+            FloatCmpKind::Ne => (4, false),
+            FloatCmpKind::Gt => (1, true),
+            FloatCmpKind::Ge => (2, true),
+        };
+        if swap {
+            self.asm
+                .xmm_rm_r_imm(op, lhs, writable!(rhs), imm, OperandSize::S32);
+            self.asm.xmm_mov_rr(rhs, dst, OperandSize::S128);
+        } else {
+            self.asm.xmm_rm_r_imm(op, rhs, dst, imm, OperandSize::S32);
+        }
+    }
+
+    fn v128_narrow(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128NarrowKind) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        let op = match kind {
+            V128NarrowKind::I16x8S => SseOpcode::Packsswb,
+            V128NarrowKind::I16x8U => SseOpcode::Packuswb,
+            V128NarrowKind::I32x4S => SseOpcode::Packssdw,
+            V128NarrowKind::I32x4U => SseOpcode::Packusdw,
+        };
+        self.asm.xmm_rm_r(op, rhs, dst);
+    }
+
+    fn v128_extend(&mut self, dst: WritableReg, src: Reg, kind: V128ExtendKind) {
+        let op = match (kind.src_shape(), kind.signed()) {
+            (VectorShape::I8x16, true) => SseOpcode::Pmovsxbw,
+            (VectorShape::I8x16, false) => SseOpcode::Pmovzxbw,
+            (VectorShape::I16x8, true) => SseOpcode::Pmovsxwd,
+            (VectorShape::I16x8, false) => SseOpcode::Pmovzxwd,
+            (VectorShape::I32x4, true) => SseOpcode::Pmovsxdq,
+            (VectorShape::I32x4, false) => SseOpcode::Pmovzxdq,
+            _ => unreachable!(),
+        };
+        if kind.high() {
+            // Move the upper half to the lower half before extending.
+            self.asm.xmm_unary_rr_imm(SseOpcode::Pshufd, src, dst, 0xee);
+            self.asm.xmm_unary_rr(op, dst.to_reg(), dst);
+        } else {
+            self.asm.xmm_unary_rr(op, src, dst);
+        }
+    }
+
+    fn v128_extmul(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128ExtendKind) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        match kind.src_shape() {
+            VectorShape::I8x16 | VectorShape::I16x8 => {
+                let op = if kind.src_shape() == VectorShape::I8x16 {
+                    SseOpcode::Pmullw
+                } else {
+                    SseOpcode::Pmulld
+                };
+                self.v128_extend(dst, lhs, kind);
+                self.v128_extend(writable!(rhs), rhs, kind);
+                self.asm.xmm_rm_r(op, rhs, dst);
+            }
+            VectorShape::I32x4 => {
+                // Place the lanes to multiply in the lower half of each
+                // 64-bit lane, which is what `pmuldq` and `pmuludq` read.
+                let imm = if kind.high() { 0xfa } else { 0x50 };
+                let op = if kind.signed() {
+                    SseOpcode::Pmuldq
+                } else {
+                    SseOpcode::Pmuludq
+                };
+                self.asm.xmm_unary_rr_imm(SseOpcode::Pshufd, lhs, dst, imm);
+                self.asm
+                    .xmm_unary_rr_imm(SseOpcode::Pshufd, rhs, writable!(rhs), imm);
+                self.asm.xmm_rm_r(op, rhs, dst);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn v128_extadd_pairwise(&mut self, dst: WritableReg, kind: V128ExtAddKind) {
+        let scratch = regs::scratch_xmm();
+        match kind {
+            // `pmaddubsw` multiplies unsigned bytes from its first operand
+            // with signed bytes from its second operand and adds adjacent
+            // results, so multiplying by one yields the pairwise sum.
+            V128ExtAddKind::I8x16S => {
+                self.load_v128_constant(0x01010101_01010101_01010101_01010101, writable!(scratch));
+                self.asm
+                    .xmm_rm_r(SseOpcode::Pmaddubsw, dst.to_reg(), writable!(scratch));
+                self.asm.xmm_mov_rr(scratch, dst, OperandSize::S128);
+            }
+            V128ExtAddKind::I8x16U => {
+                self.load_v128_constant(0x01010101_01010101_01010101_01010101, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Pmaddubsw, scratch, dst);
+            }
+            V128ExtAddKind::I16x8S => {
+                self.load_v128_constant(0x00010001_00010001_00010001_00010001, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Pmaddwd, scratch, dst);
+            }
+            V128ExtAddKind::I16x8U => {
+                // `pmaddwd` is signed only: bias the lanes by -0x8000 and
+                // compensate the sum of each pair afterwards.
+                self.load_v128_constant(0x80008000_80008000_80008000_80008000, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Pxor, scratch, dst);
+                self.load_v128_constant(0x00010001_00010001_00010001_00010001, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Pmaddwd, scratch, dst);
+                self.load_v128_constant(0x00010000_00010000_00010000_00010000, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Paddd, scratch, dst);
+            }
+        }
+    }
+
+    fn v128_dot(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        self.asm.xmm_rm_r(SseOpcode::Pmaddwd, rhs, dst);
+    }
+
+    fn v128_q15mulr_sat_s(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &lhs);
+        // `pmulhrsw` only overflows for `-0x8000 * -0x8000`, producing
+        // 0x8000; flip those lanes to 0x7fff.
+        let scratch = regs::scratch_xmm();
+        self.asm.xmm_rm_r(SseOpcode::Pmulhrsw, rhs, dst);
+        self.load_v128_constant(0x80008000_80008000_80008000_80008000, writable!(scratch));
+        self.asm
+            .xmm_rm_r(SseOpcode::Pcmpeqw, dst.to_reg(), writable!(scratch));
+        self.asm.xmm_rm_r(SseOpcode::Pxor, scratch, dst);
+    }
+
+    fn v128_convert(&mut self, dst: WritableReg, src: Reg, kind: V128ConvertKind) {
+        let scratch = regs::scratch_xmm();
+        match kind {
+            V128ConvertKind::I32x4S => self.asm.xmm_unary_rr(SseOpcode::Cvtdq2ps, src, dst),
+            V128ConvertKind::I32x4LowS => self.asm.xmm_unary_rr(SseOpcode::Cvtdq2pd, src, dst),
+            V128ConvertKind::I32x4U => {
+                // Convert the low and high 16 bits of each lane separately,
+                // halving the high part so that it remains positive, and
+                // add the results.
+                self.asm
+                    .xmm_mov_rr(src, writable!(scratch), OperandSize::S128);
+                self.asm
+                    .xmm_shift_ir(SseOpcode::Pslld, 16, writable!(scratch));
+                self.asm
+                    .xmm_shift_ir(SseOpcode::Psrld, 16, writable!(scratch));
+                if dst.to_reg() != src {
+                    self.asm.xmm_mov_rr(src, dst, OperandSize::S128);
+                }
+                self.asm.xmm_rm_r(SseOpcode::Psubd, scratch, dst);
+                self.asm
+                    .xmm_unary_rr(SseOpcode::Cvtdq2ps, scratch, writable!(scratch));
+                self.asm.xmm_shift_ir(SseOpcode::Psrld, 1, dst);
+                self.asm
+                    .xmm_unary_rr(SseOpcode::Cvtdq2ps, dst.to_reg(), dst);
+                self.asm.xmm_rm_r(SseOpcode::Addps, dst.to_reg(), dst);
+                self.asm.xmm_rm_r(SseOpcode::Addps, scratch, dst);
+            }
+            V128ConvertKind::I32x4LowU => {
+                // Build the doubles 2^52 + x by using each lane as the
+                // low bits of the mantissa and subtract 2^52.
+                if dst.to_reg() != src {
+                    self.asm.xmm_mov_rr(src, dst, OperandSize::S128);
+                }
+                self.load_v128_constant(0x43300000_43300000_43300000_43300000, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Unpcklps, scratch, dst);
+                self.load_v128_constant(0x43300000_00000000_43300000_00000000, writable!(scratch));
+                self.asm.xmm_rm_r(SseOpcode::Subpd, scratch, dst);
+            }
+        }
+    }
+
+    fn v128_trunc_sat(&mut self, context: &mut CodeGenContext, kind: V128TruncKind) {
+        let tmp = context.any_fpr(self);
+        context.unop(self, OperandSize::S128, &mut |masm, reg, _| {
+            let scratch = regs::scratch_xmm();
+            let dst = writable!(reg);
+            match kind {
+                V128TruncKind::F32x4S => {
+                    // Zero NaN lanes, convert, and turn the 0x80000000
+                    // produced by positive overflow into 0x7fffffff.
+                    masm.asm
+                        .xmm_mov_rr(reg, writable!(scratch), OperandSize::S128);
+                    masm.asm.xmm_rm_r_imm(
+                        SseOpcode::Cmpps,
+                        scratch,
+                        writable!(scratch),
+                        0,
+                        OperandSize::S32,
+                    );
+                    masm.asm.xmm_rm_r(SseOpcode::Andps, scratch, dst);
+                    masm.asm.xmm_rm_r(SseOpcode::Pxor, reg, writable!(scratch));
+                    masm.asm.xmm_unary_rr(SseOpcode::Cvttps2dq, reg, dst);
+                    masm.asm.xmm_rm_r(SseOpcode::Pand, reg, writable!(scratch));
+                    masm.asm
+                        .xmm_shift_ir(SseOpcode::Psrad, 31, writable!(scratch));
+                    masm.asm.xmm_rm_r(SseOpcode::Pxor, scratch, dst);
+                }
+                V128TruncKind::F32x4U => {
+                    // Clamp negative and NaN lanes to zero, convert lanes
+                    // below 2^31 directly and add the conversion of the
+                    // remaining lanes minus 2^31, saturating those at or
+                    // above 2^32.
+                    masm.asm
+                        .xmm_rm_r(SseOpcode::Pxor, scratch, writable!(scratch));
+                    masm.asm.xmm_rm_r(SseOpcode::Maxps, scratch, dst);
+                    masm.asm
+                        .xmm_rm_r(SseOpcode::Pcmpeqd, scratch, writable!(scratch));
+                    masm.asm
+                        .xmm_shift_ir(SseOpcode::Psrld, 1, writable!(scratch));
+                    masm.asm
+                        .xmm_unary_rr(SseOpcode::Cvtdq2ps, scratch, writable!(scratch));
+                    masm.asm.xmm_mov_rr(reg, writable!(tmp), OperandSize::S128);
+                    masm.asm.xmm_unary_rr(SseOpcode::Cvttps2dq, reg, dst);
+                    masm.asm.xmm_rm_r(SseOpcode::Subps, scratch, writable!(tmp));
+                    masm.asm.xmm_rm_r_imm(
+                        SseOpcode::Cmpps,
+                        tmp,
+                        writable!(scratch),
+                        2,
+                        OperandSize::S32,
+                    );
+                    masm.asm
+                        .xmm_unary_rr(SseOpcode::Cvttps2dq, tmp, writable!(tmp));
+                    masm.asm.xmm_rm_r(SseOpcode::Pxor, scratch, writable!(tmp));
+                    masm.asm
+                        .xmm_rm_r(SseOpcode::Pxor, scratch, writable!(scratch));
+                    masm.asm
+                        .xmm_rm_r(SseOpcode::Pmaxsd, scratch, writable!(tmp));
+                    masm.asm.xmm_rm_r(SseOpcode::Paddd, tmp, dst);
+                }
+                V128TruncKind::F64x2SZero => {
+                    // Zero NaN lanes and clamp the rest to i32::MAX; the
+                    // conversion saturates negative overflow on its own.
+                    masm.asm
+                        .xmm_mov_rr(reg, writable!(scratch), OperandSize::S128);
+                    masm.asm.xmm_rm_r_imm(
+                        SseOpcode::Cmppd,
+                        scratch,
+                        writable!(scratch),
+                        0,
+                        OperandSize::S32,
+                    );
+                    masm.load_v128_constant(0x41dfffff_ffc00000_41dfffff_ffc00000, writable!(tmp));
+                    masm.asm.xmm_rm_r(SseOpcode::Andps, tmp, writable!(scratch));
+                    masm.asm.xmm_rm_r(SseOpcode::Minpd, scratch, dst);
+                    masm.asm.xmm_unary_rr(SseOpcode::Cvttpd2dq, reg, dst);
+                }
+                V128TruncKind::F64x2UZero => {
+                    // Clamp to [0, u32::MAX], truncate, and extract the
+                    // low bits of the mantissa after adding 2^52.
+                    masm.asm
+                        .xmm_rm_r(SseOpcode::Pxor, scratch, writable!(scratch));
+                    masm.asm.xmm_rm_r(SseOpcode::Maxpd, scratch, dst);
+                    masm.load_v128_constant(0x41efffff_ffe00000_41efffff_ffe00000, writable!(tmp));
+                    masm.asm.xmm_rm_r(SseOpcode::Minpd, tmp, dst);
+                    masm.asm
+                        .xmm_unary_rr_imm(SseOpcode::Roundpd, reg, dst, 0x03);
+                    masm.load_v128_constant(0x43300000_00000000_43300000_00000000, writable!(tmp));
+                    masm.asm.xmm_rm_r(SseOpcode::Addpd, tmp, dst);
+                    masm.asm
+                        .xmm_rm_r_imm(SseOpcode::Shufps, scratch, dst, 0x88, OperandSize::S32);
+                }
+            }
+            TypedReg::v128(reg)
+        });
+        context.free_reg(tmp);
+    }
+
+    fn v128_demote(&mut self, dst: WritableReg, src: Reg) {
+        self.asm.xmm_unary_rr(SseOpcode::Cvtpd2ps, src, dst);
+    }
+
+    fn v128_promote(&mut self, dst: WritableReg, src: Reg) {
+        self.asm.xmm_unary_rr(SseOpcode::Cvtps2pd, src, dst);
+    }
+
+    fn supports_v128_op(&self, op: &Operator) -> bool {
+        use Operator::*;
+        match op {
+            // These only need SSE2, or fall back to it in the absence of
+            // SSSE3 and SSE4.1.
+            V128Load { .. }
+            | V128Store { .. }
+            | V128Const { .. }
+            | V128Not
+            | V128And
+            | V128AndNot
+            | V128Or
+            | V128Xor
+            | I8x16Splat
+            | I16x8Splat
+            | I32x4Splat
+            | I64x2Splat
+            | F32x4Splat
+            | F64x2Splat
+            | I8x16ExtractLaneS { .. }
+            | I8x16ExtractLaneU { .. }
+            | I16x8ExtractLaneS { .. }
+            | I16x8ExtractLaneU { .. }
+            | I32x4ExtractLane { .. }
+            | I64x2ExtractLane { .. }
+            | F32x4ExtractLane { .. }
+            | F64x2ExtractLane { .. }
+            | I8x16ReplaceLane { .. }
+            | I16x8ReplaceLane { .. }
+            | I32x4ReplaceLane { .. }
+            | I64x2ReplaceLane { .. }
+            | F32x4ReplaceLane { .. }
+            | F64x2ReplaceLane { .. } => true,
+            // Everything else may be lowered to SSSE3, SSE4.1 or SSE4.2
+            // instructions.
+            _ => self.flags.has_ssse3() && self.flags.has_sse41() && self.flags.has_sse42(),
+        }
+    }
+
+    fn has_native_fma(&self) -> bool {
+        self.flags.use_fma()
+    }
+
+    fn v128_madd(&mut self, dst: WritableReg, a: Reg, b: Reg, c: Reg, shape: VectorShape) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &a);
+        if self.flags.use_fma() {
+            let op = match shape {
+                VectorShape::F32x4 => AvxOpcode::Vfmadd213ps,
+                VectorShape::F64x2 => AvxOpcode::Vfmadd213pd,
+                _ => unreachable!(),
+            };
+            self.asm.xmm_vex_fma(op, b, c, dst);
+            return;
+        }
+        let (mul, add) = match shape {
+            VectorShape::F32x4 => (SseOpcode::Mulps, SseOpcode::Addps),
+            VectorShape::F64x2 => (SseOpcode::Mulpd, SseOpcode::Addpd),
+            _ => unreachable!(),
+        };
+        self.asm.xmm_rm_r(mul, b, dst);
+        self.asm.xmm_rm_r(add, c, dst);
+    }
+
+    fn v128_nmadd(&mut self, dst: WritableReg, a: Reg, b: Reg, c: Reg, shape: VectorShape) {
+        Self::ensure_two_argument_form(&dst.to_reg(), &a);
+        if self.flags.use_fma() {
+            let op = match shape {
+                VectorShape::F32x4 => AvxOpcode::Vfnmadd213ps,
+                VectorShape::F64x2 => AvxOpcode::Vfnmadd213pd,
+                _ => unreachable!(),
+            };
+            self.asm.xmm_vex_fma(op, b, c, dst);
+            return;
+        }
+        let (mul, sub) = match shape {
+            VectorShape::F32x4 => (SseOpcode::Mulps, SseOpcode::Subps),
+            VectorShape::F64x2 => (SseOpcode::Mulpd, SseOpcode::Subpd),
+            _ => unreachable!(),
+        };
+        self.asm.xmm_rm_r(mul, b, dst);
+        self.asm.xmm_rm_r(sub, a, writable!(c));
+        self.asm.xmm_mov_rr(c, dst, OperandSize::S128);
+    }
+
+    fn v128_dot_i8x16_i7x16_s(&mut self, context: &mut CodeGenContext, deterministic: bool) {
+        if deterministic {
+            let tmp = context.any_fpr(self);
+            context.binop(self, OperandSize::S128, |masm, dst, src, _| {
+                masm.dot_i8x16_wrapping_s(dst, src, tmp);
+                TypedReg::v128(dst)
+            });
+            context.free_reg(tmp);
+            return;
+        }
+        context.binop(self, OperandSize::S128, |masm, dst, src, _| {
+            // The 7-bit lanes of `src` are the same when treated as unsigned,
+            // which is how `pmaddubsw` treats its first operand.
+            masm.asm.xmm_rm_r(SseOpcode::Pmaddubsw, dst, writable!(src));
+            masm.asm.xmm_mov_rr(src, writable!(dst), OperandSize::S128);
+            TypedReg::v128(dst)
+        });
+    }
+
+    fn v128_dot_i8x16_i7x16_add_s(&mut self, context: &mut CodeGenContext, deterministic: bool) {
+        let tmp = deterministic.then(|| context.any_fpr(self));
+        context.ternop(self, |masm, lhs, rhs, acc| {
+            let scratch = regs::scratch_xmm();
+            match tmp {
+                Some(tmp) => masm.dot_i8x16_wrapping_s(lhs, rhs, tmp),
+                None => {
+                    masm.asm.xmm_rm_r(SseOpcode::Pmaddubsw, lhs, writable!(rhs));
+                    masm.asm.xmm_mov_rr(rhs, writable!(lhs), OperandSize::S128);
+                }
+            }
+            masm.load_v128_constant(0x00010001_00010001_00010001_00010001, writable!(scratch));
+            masm.asm
+                .xmm_rm_r(SseOpcode::Pmaddwd, scratch, writable!(lhs));
+            masm.asm.xmm_rm_r(SseOpcode::Paddd, acc, writable!(lhs));
+            TypedReg::v128(lhs)
+        });
+        if let Some(tmp) = tmp {
+            context.free_reg(tmp);
+        }
+    }
 }
 
 impl MacroAssembler {
@@ -1117,6 +2322,37 @@ impl MacroAssembler {
         }
     }
 
+    /// Signed dot product of the i8x16 lanes in `lhs` and `rhs` into
+    /// wrapping 16-bit lanes, in `lhs`. Both `rhs` and `tmp` are clobbered.
+    fn dot_i8x16_wrapping_s(&mut self, lhs: Reg, rhs: Reg, tmp: Reg) {
+        let scratch = regs::scratch_xmm();
+        // Products of the odd (high) bytes of each 16-bit lane.
+        self.asm
+            .xmm_mov_rr(rhs, writable!(scratch), OperandSize::S128);
+        self.asm
+            .xmm_shift_ir(SseOpcode::Psraw, 8, writable!(scratch));
+        self.asm.xmm_mov_rr(lhs, writable!(tmp), OperandSize::S128);
+        self.asm.xmm_shift_ir(SseOpcode::Psraw, 8, writable!(tmp));
+        self.asm
+            .xmm_rm_r(SseOpcode::Pmullw, scratch, writable!(tmp));
+        // Products of the even (low) bytes of each 16-bit lane.
+        for reg in [lhs, rhs] {
+            self.asm.xmm_shift_ir(SseOpcode::Psllw, 8, writable!(reg));
+            self.asm.xmm_shift_ir(SseOpcode::Psraw, 8, writable!(reg));
+        }
+        self.asm.xmm_rm_r(SseOpcode::Pmullw, rhs, writable!(lhs));
+        self.asm.xmm_rm_r(SseOpcode::Paddw, tmp, writable!(lhs));
+    }
+
+    /// Loads a 128-bit constant into `dst` through the constant pool.
+    fn load_v128_constant(&mut self, bits: u128, dst: WritableReg) {
+        let addr = self.asm.add_constant(&bits.to_le_bytes());
+        // Always trusted, since we are loading the constant from the
+        // constant pool.
+        self.asm
+            .xmm_mov_mr(&addr, dst, OperandSize::S128, TRUSTED_FLAGS);
+    }
+
     /// A common implementation for zero-extend stack loads.
     fn load_impl<M>(&mut self, src: Address, dst: WritableReg, size: OperandSize, flags: MemFlags)
     where
@@ -7,6 +7,7 @@ use cranelift_codegen::{
     Final, MachBufferFinalized, MachLabel,
 };
 use std::{fmt::Debug, ops::Range};
+use wasmparser::Operator;
 use wasmtime_environ::PtrSize;
 
 pub(crate) use cranelift_codegen::ir::TrapCode;
@@ -206,6 +207,216 @@ impl ExtendKind {
     }
 }
 
+/// The shape of a 128-bit vector, in terms of the type and number of its
+/// lanes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum VectorShape {
+    /// 16 lanes of 8 bits.
+    I8x16,
+    /// 8 lanes of 16 bits.
+    I16x8,
+    /// 4 lanes of 32 bits.
+    I32x4,
+    /// 2 lanes of 64 bits.
+    I64x2,
+    /// 4 lanes of 32-bit floats.
+    F32x4,
+    /// 2 lanes of 64-bit floats.
+    F64x2,
+}
+
+impl VectorShape {
+    /// The size of each lane.
+    pub fn lane_size(&self) -> OperandSize {
+        match self {
+            Self::I8x16 => OperandSize::S8,
+            Self::I16x8 => OperandSize::S16,
+            Self::I32x4 | Self::F32x4 => OperandSize::S32,
+            Self::I64x2 | Self::F64x2 => OperandSize::S64,
+        }
+    }
+}
+
+/// Kinds of lane extraction from a 128-bit vector.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum ExtractLaneKind {
+    /// Sign extends an 8-bit lane to 32 bits.
+    I8x16S,
+    /// Zero extends an 8-bit lane to 32 bits.
+    I8x16U,
+    /// Sign extends a 16-bit lane to 32 bits.
+    I16x8S,
+    /// Zero extends a 16-bit lane to 32 bits.
+    I16x8U,
+    /// Extracts a 32-bit integer lane.
+    I32x4,
+    /// Extracts a 64-bit integer lane.
+    I64x2,
+    /// Extracts a 32-bit float lane.
+    F32x4,
+    /// Extracts a 64-bit float lane.
+    F64x2,
+}
+
+/// Kinds of lane-wise vector addition and subtraction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum V128ArithKind {
+    /// Wrapping 8-bit lanes.
+    I8x16,
+    /// Signed saturating 8-bit lanes.
+    I8x16SatS,
+    /// Unsigned saturating 8-bit lanes.
+    I8x16SatU,
+    /// Wrapping 16-bit lanes.
+    I16x8,
+    /// Signed saturating 16-bit lanes.
+    I16x8SatS,
+    /// Unsigned saturating 16-bit lanes.
+    I16x8SatU,
+    /// Wrapping 32-bit lanes.
+    I32x4,
+    /// Wrapping 64-bit lanes.
+    I64x2,
+    /// 32-bit float lanes.
+    F32x4,
+    /// 64-bit float lanes.
+    F64x2,
+}
+
+/// Kinds of lane-wise vector minimum and maximum.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum V128MinMaxKind {
+    /// Signed 8-bit lanes.
+    I8x16S,
+    /// Unsigned 8-bit lanes.
+    I8x16U,
+    /// Signed 16-bit lanes.
+    I16x8S,
+    /// Unsigned 16-bit lanes.
+    I16x8U,
+    /// Signed 32-bit lanes.
+    I32x4S,
+    /// Unsigned 32-bit lanes.
+    I32x4U,
+    /// 32-bit float lanes, with WebAssembly's NaN and signed zero semantics.
+    F32x4,
+    /// 64-bit float lanes, with WebAssembly's NaN and signed zero semantics.
+    F64x2,
+}
+
+/// Kinds of saturating vector narrowing, named after the source shape.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum V128NarrowKind {
+    /// Narrows two i16x8 into an i8x16, saturating as signed.
+    I16x8S,
+    /// Narrows two i16x8 into an i8x16, saturating as unsigned.
+    I16x8U,
+    /// Narrows two i32x4 into an i16x8, saturating as signed.
+    I32x4S,
+    /// Narrows two i32x4 into an i16x8, saturating as unsigned.
+    I32x4U,
+}
+
+/// Kinds of vector widening, named after the half of the source vector
+/// which is extended, the source shape and the signedness.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum V128ExtendKind {
+    LowI8x16S,
+    HighI8x16S,
+    LowI8x16U,
+    HighI8x16U,
+    LowI16x8S,
+    HighI16x8S,
+    LowI16x8U,
+    HighI16x8U,
+    LowI32x4S,
+    HighI32x4S,
+    LowI32x4U,
+    HighI32x4U,
+}
+
+impl V128ExtendKind {
+    /// Returns true if the high half of the source vector is extended.
+    pub fn high(&self) -> bool {
+        matches!(
+            self,
+            Self::HighI8x16S
+                | Self::HighI8x16U
+                | Self::HighI16x8S
+                | Self::HighI16x8U
+                | Self::HighI32x4S
+                | Self::HighI32x4U
+        )
+    }
+
+    /// Returns true if the extension is signed.
+    pub fn signed(&self) -> bool {
+        matches!(
+            self,
+            Self::LowI8x16S
+                | Self::HighI8x16S
+                | Self::LowI16x8S
+                | Self::HighI16x8S
+                | Self::LowI32x4S
+                | Self::HighI32x4S
+        )
+    }
+
+    /// The shape of the source vector.
+    pub fn src_shape(&self) -> VectorShape {
+        match self {
+            Self::LowI8x16S | Self::HighI8x16S | Self::LowI8x16U | Self::HighI8x16U => {
+                VectorShape::I8x16
+            }
+            Self::LowI16x8S | Self::HighI16x8S | Self::LowI16x8U | Self::HighI16x8U => {
+                VectorShape::I16x8
+            }
+            Self::LowI32x4S | Self::HighI32x4S | Self::LowI32x4U | Self::HighI32x4U => {
+                VectorShape::I32x4
+            }
+        }
+    }
+}
+
+/// Kinds of pairwise extending addition, named after the source shape.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum V128ExtAddKind {
+    /// Signed i8x16 to i16x8.
+    I8x16S,
+    /// Unsigned i8x16 to i16x8.
+    I8x16U,
+    /// Signed i16x8 to i32x4.
+    I16x8S,
+    /// Unsigned i16x8 to i32x4.
+    I16x8U,
+}
+
+/// Kinds of integer to float vector conversions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum V128ConvertKind {
+    /// Signed i32x4 to f32x4.
+    I32x4S,
+    /// Unsigned i32x4 to f32x4.
+    I32x4U,
+    /// The two low signed lanes of an i32x4 to f64x2.
+    I32x4LowS,
+    /// The two low unsigned lanes of an i32x4 to f64x2.
+    I32x4LowU,
+}
+
+/// Kinds of saturating float to integer vector truncations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum V128TruncKind {
+    /// f32x4 to signed i32x4.
+    F32x4S,
+    /// f32x4 to unsigned i32x4.
+    F32x4U,
+    /// f64x2 to the two low signed lanes of an i32x4, zeroing the rest.
+    F64x2SZero,
+    /// f64x2 to the two low unsigned lanes of an i32x4, zeroing the rest.
+    F64x2UZero,
+}
+
 /// Operand size, in bits.
 #[derive(Copy, Debug, Clone, Eq, PartialEq)]
 pub(crate) enum OperandSize {
@@ -1054,4 +1265,193 @@ pub(crate) trait MacroAssembler {
     /// Note that some platforms require special handling of registers in this
     /// instruction (e.g. x64) so full access to `CodeGenContext` is provided.
     fn mul_wide(&mut self, context: &mut CodeGenContext, kind: MulWideKind);
+
+    /// Replicates the scalar in `src` to every lane of `dst`.
+    fn v128_splat(&mut self, dst: WritableReg, src: Reg, shape: VectorShape);
+
+    /// Extracts the lane at index `lane` of the vector in `src` into `dst`.
+    fn v128_extract_lane(&mut self, dst: WritableReg, src: Reg, lane: u8, kind: ExtractLaneKind);
+
+    /// Replaces the lane at index `lane` of the vector in `dst` with the
+    /// scalar in `src`.
+    fn v128_replace_lane(&mut self, dst: WritableReg, src: Reg, lane: u8, shape: VectorShape);
+
+    /// Selects bytes from the concatenation of `lhs` and `rhs` according to
+    /// the given lane indices.
+    fn v128_shuffle(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, lanes: [u8; 16]);
+
+    /// Selects bytes from `lhs` using the indices in `rhs`; out of range
+    /// indices produce zero.
+    fn v128_swizzle(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg);
+
+    /// Bitwise not of the vector in `dst`.
+    fn v128_not(&mut self, dst: WritableReg);
+
+    /// Bitwise and of `lhs` and `rhs`.
+    fn v128_and(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg);
+
+    /// Bitwise and of `lhs` and the complement of `rhs`.
+    fn v128_and_not(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg);
+
+    /// Bitwise or of `lhs` and `rhs`.
+    fn v128_or(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg);
+
+    /// Bitwise exclusive or of `lhs` and `rhs`.
+    fn v128_xor(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg);
+
+    /// Selects bits from `lhs` where `mask` is set and from `rhs` otherwise.
+    /// The contents of `mask` may be clobbered.
+    fn v128_bitselect(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, mask: Reg);
+
+    /// Sets `dst` to 1 if any bit of `src` is set, 0 otherwise.
+    fn v128_any_true(&mut self, dst: WritableReg, src: Reg);
+
+    /// Sets `dst` to 1 if all the lanes of `src` are non-zero, 0 otherwise.
+    fn v128_all_true(&mut self, dst: WritableReg, src: Reg, shape: VectorShape);
+
+    /// Collects the most significant bit of each lane of `src` into `dst`.
+    fn v128_bitmask(&mut self, dst: WritableReg, src: Reg, shape: VectorShape);
+
+    /// Lane-wise addition.
+    fn v128_add(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128ArithKind);
+
+    /// Lane-wise subtraction.
+    fn v128_sub(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128ArithKind);
+
+    /// Lane-wise multiplication.
+    ///
+    /// Some shapes require temporary registers on some platforms (e.g. i64x2
+    /// on x64) so full access to `CodeGenContext` is provided.
+    fn v128_mul(&mut self, context: &mut CodeGenContext, shape: VectorShape);
+
+    /// Lane-wise float division.
+    fn v128_div(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, shape: VectorShape);
+
+    /// Lane-wise minimum.
+    fn v128_min(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128MinMaxKind);
+
+    /// Lane-wise maximum.
+    fn v128_max(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128MinMaxKind);
+
+    /// Lane-wise pseudo-minimum, defined as `rhs < lhs ? rhs : lhs`.
+    fn v128_pmin(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, shape: VectorShape);
+
+    /// Lane-wise pseudo-maximum, defined as `lhs < rhs ? rhs : lhs`.
+    fn v128_pmax(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, shape: VectorShape);
+
+    /// Lane-wise unsigned rounding average.
+    fn v128_avgr(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, shape: VectorShape);
+
+    /// Lane-wise negation of the vector in `dst`.
+    fn v128_neg(&mut self, dst: WritableReg, shape: VectorShape);
+
+    /// Lane-wise absolute value of the vector in `dst`.
+    fn v128_abs(&mut self, dst: WritableReg, shape: VectorShape);
+
+    /// Lane-wise float square root.
+    fn v128_sqrt(&mut self, dst: WritableReg, src: Reg, shape: VectorShape);
+
+    /// Lane-wise float rounding.
+    fn v128_round(&mut self, dst: WritableReg, src: Reg, mode: RoundingMode, shape: VectorShape);
+
+    /// Lane-wise population count of an i8x16.
+    ///
+    /// Note that some platforms require temporary registers for this
+    /// operation, so full access to `CodeGenContext` is provided.
+    fn v128_popcnt(&mut self, context: &mut CodeGenContext);
+
+    /// Lane-wise shift of a vector by a scalar amount, taken modulo the lane
+    /// width.
+    ///
+    /// Note that some platforms require temporary registers for this
+    /// operation, so full access to `CodeGenContext` is provided.
+    fn v128_shift(&mut self, context: &mut CodeGenContext, shape: VectorShape, kind: ShiftKind);
+
+    /// Lane-wise integer comparison, setting every bit of each lane of
+    /// `dst` if the comparison holds.
+    fn v128_int_cmp(
+        &mut self,
+        dst: WritableReg,
+        lhs: Reg,
+        rhs: Reg,
+        kind: IntCmpKind,
+        shape: VectorShape,
+    );
+
+    /// Lane-wise float comparison, setting every bit of each lane of `dst`
+    /// if the comparison holds.
+    fn v128_float_cmp(
+        &mut self,
+        dst: WritableReg,
+        lhs: Reg,
+        rhs: Reg,
+        kind: FloatCmpKind,
+        shape: VectorShape,
+    );
+
+    /// Saturating narrowing of `lhs` and `rhs` into the low and high halves
+    /// of `dst`, respectively.
+    fn v128_narrow(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128NarrowKind);
+
+    /// Widens half of the lanes of `src` into `dst`.
+    fn v128_extend(&mut self, dst: WritableReg, src: Reg, kind: V128ExtendKind);
+
+    /// Widening multiplication of half of the lanes of `lhs` and `rhs`.
+    /// The contents of `rhs` may be clobbered.
+    fn v128_extmul(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg, kind: V128ExtendKind);
+
+    /// Pairwise extending addition of the lanes of the vector in `dst`.
+    fn v128_extadd_pairwise(&mut self, dst: WritableReg, kind: V128ExtAddKind);
+
+    /// Signed 16-bit dot product of `lhs` and `rhs`, into 32-bit lanes.
+    fn v128_dot(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg);
+
+    /// Lane-wise saturating rounding Q15 multiplication of i16x8 vectors.
+    fn v128_q15mulr_sat_s(&mut self, dst: WritableReg, lhs: Reg, rhs: Reg);
+
+    /// Lane-wise integer to float conversion.
+    fn v128_convert(&mut self, dst: WritableReg, src: Reg, kind: V128ConvertKind);
+
+    /// Lane-wise saturating float to integer truncation of the vector at the
+    /// top of the value stack.
+    ///
+    /// Note that some platforms require temporary registers for this
+    /// operation, so full access to `CodeGenContext` is provided.
+    fn v128_trunc_sat(&mut self, context: &mut CodeGenContext, kind: V128TruncKind);
+
+    /// Demotes the two f64 lanes of `src` to the low f32 lanes of `dst`,
+    /// zeroing the high lanes.
+    fn v128_demote(&mut self, dst: WritableReg, src: Reg);
+
+    /// Promotes the two low f32 lanes of `src` to the f64 lanes of `dst`.
+    fn v128_promote(&mut self, dst: WritableReg, src: Reg);
+
+    /// Whether the operator, from the SIMD or relaxed SIMD proposals, can be
+    /// lowered with the instruction set available on this target.
+    fn supports_v128_op(&self, op: &Operator) -> bool;
+
+    /// Whether `v128_madd` and `v128_nmadd` are fused on this target.
+    fn has_native_fma(&self) -> bool;
+
+    /// Relaxed lane-wise `a * b + c`, fused if the target supports it.
+    /// The contents of `b` may be clobbered.
+    fn v128_madd(&mut self, dst: WritableReg, a: Reg, b: Reg, c: Reg, shape: VectorShape);
+
+    /// Relaxed lane-wise `-(a * b) + c`, fused if the target supports it.
+    /// The contents of `b` and `c` may be clobbered.
+    fn v128_nmadd(&mut self, dst: WritableReg, a: Reg, b: Reg, c: Reg, shape: VectorShape);
+
+    /// Relaxed dot product of the i8x16 lanes of the two operands on top of
+    /// the value stack, into 16-bit lanes.
+    ///
+    /// When `deterministic` is set both operands are treated as signed and
+    /// the pairwise sums wrap, matching the deterministic semantics of the
+    /// relaxed SIMD proposal. This may require temporary registers, so full
+    /// access to `CodeGenContext` is provided.
+    fn v128_dot_i8x16_i7x16_s(&mut self, context: &mut CodeGenContext, deterministic: bool);
+
+    /// Relaxed dot product of the i8x16 lanes of the first two of the three
+    /// operands on top of the value stack, into 32-bit lanes accumulated onto
+    /// the third. See `v128_dot_i8x16_i7x16_s` for `deterministic`.
+    fn v128_dot_i8x16_i7x16_add_s(&mut self, context: &mut CodeGenContext, deterministic: bool);
 }
@@ -7,8 +7,10 @@
 use crate::abi::RetArea;
 use crate::codegen::{control_index, Callee, CodeGen, ControlStackFrame, FnCall};
 use crate::masm::{
-    DivKind, ExtendKind, FloatCmpKind, IntCmpKind, MacroAssembler, MemMoveDirection, MulWideKind,
-    OperandSize, RegImm, RemKind, RoundingMode, SPOffset, ShiftKind, TruncKind,
+    DivKind, ExtendKind, ExtractLaneKind, FloatCmpKind, IntCmpKind, MacroAssembler,
+    MemMoveDirection, MulWideKind, OperandSize, RegImm, RemKind, RoundingMode, SPOffset, ShiftKind,
+    TruncKind, V128ArithKind, V128ConvertKind, V128ExtAddKind, V128ExtendKind, V128MinMaxKind,
+    V128NarrowKind, V128TruncKind, VectorShape,
 };
 use crate::reg::{writable, Reg};
 use crate::stack::{TypedReg, Val};
@@ -247,6 +249,259 @@ macro_rules! def_unsupported {
     (emit I64Sub128 $($rest:tt)*) => {};
     (emit I64MulWideS $($rest:tt)*) => {};
     (emit I64MulWideU $($rest:tt)*) => {};
+    (emit V128Load8x8S $($rest:tt)*) => {};
+    (emit V128Load8x8U $($rest:tt)*) => {};
+    (emit V128Load16x4S $($rest:tt)*) => {};
+    (emit V128Load16x4U $($rest:tt)*) => {};
+    (emit V128Load32x2S $($rest:tt)*) => {};
+    (emit V128Load32x2U $($rest:tt)*) => {};
+    (emit V128Load8Splat $($rest:tt)*) => {};
+    (emit V128Load16Splat $($rest:tt)*) => {};
+    (emit V128Load32Splat $($rest:tt)*) => {};
+    (emit V128Load64Splat $($rest:tt)*) => {};
+    (emit V128Load32Zero $($rest:tt)*) => {};
+    (emit V128Load64Zero $($rest:tt)*) => {};
+    (emit V128Load8Lane $($rest:tt)*) => {};
+    (emit V128Load16Lane $($rest:tt)*) => {};
+    (emit V128Load32Lane $($rest:tt)*) => {};
+    (emit V128Load64Lane $($rest:tt)*) => {};
+    (emit V128Store8Lane $($rest:tt)*) => {};
+    (emit V128Store16Lane $($rest:tt)*) => {};
+    (emit V128Store32Lane $($rest:tt)*) => {};
+    (emit V128Store64Lane $($rest:tt)*) => {};
+    (emit I8x16Shuffle $($rest:tt)*) => {};
+    (emit I8x16Swizzle $($rest:tt)*) => {};
+    (emit I8x16Splat $($rest:tt)*) => {};
+    (emit I16x8Splat $($rest:tt)*) => {};
+    (emit I32x4Splat $($rest:tt)*) => {};
+    (emit I64x2Splat $($rest:tt)*) => {};
+    (emit F32x4Splat $($rest:tt)*) => {};
+    (emit F64x2Splat $($rest:tt)*) => {};
+    (emit I8x16ExtractLaneS $($rest:tt)*) => {};
+    (emit I8x16ExtractLaneU $($rest:tt)*) => {};
+    (emit I16x8ExtractLaneS $($rest:tt)*) => {};
+    (emit I16x8ExtractLaneU $($rest:tt)*) => {};
+    (emit I32x4ExtractLane $($rest:tt)*) => {};
+    (emit I64x2ExtractLane $($rest:tt)*) => {};
+    (emit F32x4ExtractLane $($rest:tt)*) => {};
+    (emit F64x2ExtractLane $($rest:tt)*) => {};
+    (emit I8x16ReplaceLane $($rest:tt)*) => {};
+    (emit I16x8ReplaceLane $($rest:tt)*) => {};
+    (emit I32x4ReplaceLane $($rest:tt)*) => {};
+    (emit I64x2ReplaceLane $($rest:tt)*) => {};
+    (emit F32x4ReplaceLane $($rest:tt)*) => {};
+    (emit F64x2ReplaceLane $($rest:tt)*) => {};
+    (emit I8x16Eq $($rest:tt)*) => {};
+    (emit I8x16Ne $($rest:tt)*) => {};
+    (emit I8x16LtS $($rest:tt)*) => {};
+    (emit I8x16LtU $($rest:tt)*) => {};
+    (emit I8x16GtS $($rest:tt)*) => {};
+    (emit I8x16GtU $($rest:tt)*) => {};
+    (emit I8x16LeS $($rest:tt)*) => {};
+    (emit I8x16LeU $($rest:tt)*) => {};
+    (emit I8x16GeS $($rest:tt)*) => {};
+    (emit I8x16GeU $($rest:tt)*) => {};
+    (emit I16x8Eq $($rest:tt)*) => {};
+    (emit I16x8Ne $($rest:tt)*) => {};
+    (emit I16x8LtS $($rest:tt)*) => {};
+    (emit I16x8LtU $($rest:tt)*) => {};
+    (emit I16x8GtS $($rest:tt)*) => {};
+    (emit I16x8GtU $($rest:tt)*) => {};
+    (emit I16x8LeS $($rest:tt)*) => {};
+    (emit I16x8LeU $($rest:tt)*) => {};
+    (emit I16x8GeS $($rest:tt)*) => {};
+    (emit I16x8GeU $($rest:tt)*) => {};
+    (emit I32x4Eq $($rest:tt)*) => {};
+    (emit I32x4Ne $($rest:tt)*) => {};
+    (emit I32x4LtS $($rest:tt)*) => {};
+    (emit I32x4LtU $($rest:tt)*) => {};
+    (emit I32x4GtS $($rest:tt)*) => {};
+    (emit I32x4GtU $($rest:tt)*) => {};
+    (emit I32x4LeS $($rest:tt)*) => {};
+    (emit I32x4LeU $($rest:tt)*) => {};
+    (emit I32x4GeS $($rest:tt)*) => {};
+    (emit I32x4GeU $($rest:tt)*) => {};
+    (emit I64x2Eq $($rest:tt)*) => {};
+    (emit I64x2Ne $($rest:tt)*) => {};
+    (emit I64x2LtS $($rest:tt)*) => {};
+    (emit I64x2GtS $($rest:tt)*) => {};
+    (emit I64x2LeS $($rest:tt)*) => {};
+    (emit I64x2GeS $($rest:tt)*) => {};
+    (emit F32x4Eq $($rest:tt)*) => {};
+    (emit F32x4Ne $($rest:tt)*) => {};
+    (emit F32x4Lt $($rest:tt)*) => {};
+    (emit F32x4Gt $($rest:tt)*) => {};
+    (emit F32x4Le $($rest:tt)*) => {};
+    (emit F32x4Ge $($rest:tt)*) => {};
+    (emit F64x2Eq $($rest:tt)*) => {};
+    (emit F64x2Ne $($rest:tt)*) => {};
+    (emit F64x2Lt $($rest:tt)*) => {};
+    (emit F64x2Gt $($rest:tt)*) => {};
+    (emit F64x2Le $($rest:tt)*) => {};
+    (emit F64x2Ge $($rest:tt)*) => {};
+    (emit V128Not $($rest:tt)*) => {};
+    (emit V128And $($rest:tt)*) => {};
+    (emit V128AndNot $($rest:tt)*) => {};
+    (emit V128Or $($rest:tt)*) => {};
+    (emit V128Xor $($rest:tt)*) => {};
+    (emit V128Bitselect $($rest:tt)*) => {};
+    (emit V128AnyTrue $($rest:tt)*) => {};
+    (emit I8x16Abs $($rest:tt)*) => {};
+    (emit I8x16Neg $($rest:tt)*) => {};
+    (emit I8x16Popcnt $($rest:tt)*) => {};
+    (emit I8x16AllTrue $($rest:tt)*) => {};
+    (emit I8x16Bitmask $($rest:tt)*) => {};
+    (emit I8x16NarrowI16x8S $($rest:tt)*) => {};
+    (emit I8x16NarrowI16x8U $($rest:tt)*) => {};
+    (emit I8x16Shl $($rest:tt)*) => {};
+    (emit I8x16ShrS $($rest:tt)*) => {};
+    (emit I8x16ShrU $($rest:tt)*) => {};
+    (emit I8x16Add $($rest:tt)*) => {};
+    (emit I8x16AddSatS $($rest:tt)*) => {};
+    (emit I8x16AddSatU $($rest:tt)*) => {};
+    (emit I8x16Sub $($rest:tt)*) => {};
+    (emit I8x16SubSatS $($rest:tt)*) => {};
+    (emit I8x16SubSatU $($rest:tt)*) => {};
+    (emit I8x16MinS $($rest:tt)*) => {};
+    (emit I8x16MinU $($rest:tt)*) => {};
+    (emit I8x16MaxS $($rest:tt)*) => {};
+    (emit I8x16MaxU $($rest:tt)*) => {};
+    (emit I8x16AvgrU $($rest:tt)*) => {};
+    (emit I16x8Abs $($rest:tt)*) => {};
+    (emit I16x8Neg $($rest:tt)*) => {};
+    (emit I16x8Q15MulrSatS $($rest:tt)*) => {};
+    (emit I16x8AllTrue $($rest:tt)*) => {};
+    (emit I16x8Bitmask $($rest:tt)*) => {};
+    (emit I16x8NarrowI32x4S $($rest:tt)*) => {};
+    (emit I16x8NarrowI32x4U $($rest:tt)*) => {};
+    (emit I16x8ExtendLowI8x16S $($rest:tt)*) => {};
+    (emit I16x8ExtendLowI8x16U $($rest:tt)*) => {};
+    (emit I16x8ExtendHighI8x16S $($rest:tt)*) => {};
+    (emit I16x8ExtendHighI8x16U $($rest:tt)*) => {};
+    (emit I16x8Shl $($rest:tt)*) => {};
+    (emit I16x8ShrS $($rest:tt)*) => {};
+    (emit I16x8ShrU $($rest:tt)*) => {};
+    (emit I16x8Add $($rest:tt)*) => {};
+    (emit I16x8AddSatS $($rest:tt)*) => {};
+    (emit I16x8AddSatU $($rest:tt)*) => {};
+    (emit I16x8Sub $($rest:tt)*) => {};
+    (emit I16x8SubSatS $($rest:tt)*) => {};
+    (emit I16x8SubSatU $($rest:tt)*) => {};
+    (emit I16x8Mul $($rest:tt)*) => {};
+    (emit I16x8MinS $($rest:tt)*) => {};
+    (emit I16x8MinU $($rest:tt)*) => {};
+    (emit I16x8MaxS $($rest:tt)*) => {};
+    (emit I16x8MaxU $($rest:tt)*) => {};
+    (emit I16x8AvgrU $($rest:tt)*) => {};
+    (emit I16x8ExtMulLowI8x16S $($rest:tt)*) => {};
+    (emit I16x8ExtMulLowI8x16U $($rest:tt)*) => {};
+    (emit I16x8ExtMulHighI8x16S $($rest:tt)*) => {};
+    (emit I16x8ExtMulHighI8x16U $($rest:tt)*) => {};
+    (emit I16x8ExtAddPairwiseI8x16S $($rest:tt)*) => {};
+    (emit I16x8ExtAddPairwiseI8x16U $($rest:tt)*) => {};
+    (emit I32x4Abs $($rest:tt)*) => {};
+    (emit I32x4Neg $($rest:tt)*) => {};
+    (emit I32x4AllTrue $($rest:tt)*) => {};
+    (emit I32x4Bitmask $($rest:tt)*) => {};
+    (emit I32x4ExtendLowI16x8S $($rest:tt)*) => {};
+    (emit I32x4ExtendLowI16x8U $($rest:tt)*) => {};
+    (emit I32x4ExtendHighI16x8S $($rest:tt)*) => {};
+    (emit I32x4ExtendHighI16x8U $($rest:tt)*) => {};
+    (emit I32x4Shl $($rest:tt)*) => {};
+    (emit I32x4ShrS $($rest:tt)*) => {};
+    (emit I32x4ShrU $($rest:tt)*) => {};
+    (emit I32x4Add $($rest:tt)*) => {};
+    (emit I32x4Sub $($rest:tt)*) => {};
+    (emit I32x4Mul $($rest:tt)*) => {};
+    (emit I32x4MinS $($rest:tt)*) => {};
+    (emit I32x4MinU $($rest:tt)*) => {};
+    (emit I32x4MaxS $($rest:tt)*) => {};
+    (emit I32x4MaxU $($rest:tt)*) => {};
+    (emit I32x4DotI16x8S $($rest:tt)*) => {};
+    (emit I32x4ExtMulLowI16x8S $($rest:tt)*) => {};
+    (emit I32x4ExtMulLowI16x8U $($rest:tt)*) => {};
+    (emit I32x4ExtMulHighI16x8S $($rest:tt)*) => {};
+    (emit I32x4ExtMulHighI16x8U $($rest:tt)*) => {};
+    (emit I32x4ExtAddPairwiseI16x8S $($rest:tt)*) => {};
+    (emit I32x4ExtAddPairwiseI16x8U $($rest:tt)*) => {};
+    (emit I64x2Abs $($rest:tt)*) => {};
+    (emit I64x2Neg $($rest:tt)*) => {};
+    (emit I64x2AllTrue $($rest:tt)*) => {};
+    (emit I64x2Bitmask $($rest:tt)*) => {};
+    (emit I64x2ExtendLowI32x4S $($rest:tt)*) => {};
+    (emit I64x2ExtendLowI32x4U $($rest:tt)*) => {};
+    (emit I64x2ExtendHighI32x4S $($rest:tt)*) => {};
+    (emit I64x2ExtendHighI32x4U $($rest:tt)*) => {};
+    (emit I64x2Shl $($rest:tt)*) => {};
+    (emit I64x2ShrS $($rest:tt)*) => {};
+    (emit I64x2ShrU $($rest:tt)*) => {};
+    (emit I64x2Add $($rest:tt)*) => {};
+    (emit I64x2Sub $($rest:tt)*) => {};
+    (emit I64x2Mul $($rest:tt)*) => {};
+    (emit I64x2ExtMulLowI32x4S $($rest:tt)*) => {};
+    (emit I64x2ExtMulLowI32x4U $($rest:tt)*) => {};
+    (emit I64x2ExtMulHighI32x4S $($rest:tt)*) => {};
+    (emit I64x2ExtMulHighI32x4U $($rest:tt)*) => {};
+    (emit F32x4Ceil $($rest:tt)*) => {};
+    (emit F32x4Floor $($rest:tt)*) => {};
+    (emit F32x4Trunc $($rest:tt)*) => {};
+    (emit F32x4Nearest $($rest:tt)*) => {};
+    (emit F32x4Abs $($rest:tt)*) => {};
+    (emit F32x4Neg $($rest:tt)*) => {};
+    (emit F32x4Sqrt $($rest:tt)*) => {};
+    (emit F32x4Add $($rest:tt)*) => {};
+    (emit F32x4Sub $($rest:tt)*) => {};
+    (emit F32x4Mul $($rest:tt)*) => {};
+    (emit F32x4Div $($rest:tt)*) => {};
+    (emit F32x4Min $($rest:tt)*) => {};
+    (emit F32x4Max $($rest:tt)*) => {};
+    (emit F32x4PMin $($rest:tt)*) => {};
+    (emit F32x4PMax $($rest:tt)*) => {};
+    (emit F64x2Ceil $($rest:tt)*) => {};
+    (emit F64x2Floor $($rest:tt)*) => {};
+    (emit F64x2Trunc $($rest:tt)*) => {};
+    (emit F64x2Nearest $($rest:tt)*) => {};
+    (emit F64x2Abs $($rest:tt)*) => {};
+    (emit F64x2Neg $($rest:tt)*) => {};
+    (emit F64x2Sqrt $($rest:tt)*) => {};
+    (emit F64x2Add $($rest:tt)*) => {};
+    (emit F64x2Sub $($rest:tt)*) => {};
+    (emit F64x2Mul $($rest:tt)*) => {};
+    (emit F64x2Div $($rest:tt)*) => {};
+    (emit F64x2Min $($rest:tt)*) => {};
+    (emit F64x2Max $($rest:tt)*) => {};
+    (emit F64x2PMin $($rest:tt)*) => {};
+    (emit F64x2PMax $($rest:tt)*) => {};
+    (emit I32x4TruncSatF32x4S $($rest:tt)*) => {};
+    (emit I32x4TruncSatF32x4U $($rest:tt)*) => {};
+    (emit I32x4TruncSatF64x2SZero $($rest:tt)*) => {};
+    (emit I32x4TruncSatF64x2UZero $($rest:tt)*) => {};
+    (emit F32x4ConvertI32x4S $($rest:tt)*) => {};
+    (emit F32x4ConvertI32x4U $($rest:tt)*) => {};
+    (emit F64x2ConvertLowI32x4S $($rest:tt)*) => {};
+    (emit F64x2ConvertLowI32x4U $($rest:tt)*) => {};
+    (emit F32x4DemoteF64x2Zero $($rest:tt)*) => {};
+    (emit F64x2PromoteLowF32x4 $($rest:tt)*) => {};
+    (emit I8x16RelaxedSwizzle $($rest:tt)*) => {};
+    (emit I32x4RelaxedTruncF32x4S $($rest:tt)*) => {};
+    (emit I32x4RelaxedTruncF32x4U $($rest:tt)*) => {};
+    (emit I32x4RelaxedTruncF64x2SZero $($rest:tt)*) => {};
+    (emit I32x4RelaxedTruncF64x2UZero $($rest:tt)*) => {};
+    (emit F32x4RelaxedMadd $($rest:tt)*) => {};
+    (emit F32x4RelaxedNmadd $($rest:tt)*) => {};
+    (emit F64x2RelaxedMadd $($rest:tt)*) => {};
+    (emit F64x2RelaxedNmadd $($rest:tt)*) => {};
+    (emit I8x16RelaxedLaneselect $($rest:tt)*) => {};
+    (emit I16x8RelaxedLaneselect $($rest:tt)*) => {};
+    (emit I32x4RelaxedLaneselect $($rest:tt)*) => {};
+    (emit I64x2RelaxedLaneselect $($rest:tt)*) => {};
+    (emit F32x4RelaxedMin $($rest:tt)*) => {};
+    (emit F32x4RelaxedMax $($rest:tt)*) => {};
+    (emit F64x2RelaxedMin $($rest:tt)*) => {};
+    (emit F64x2RelaxedMax $($rest:tt)*) => {};
+    (emit I16x8RelaxedQ15mulrS $($rest:tt)*) => {};
+    (emit I16x8RelaxedDotI8x16I7x16S $($rest:tt)*) => {};
+    (emit I32x4RelaxedDotI8x16I7x16AddS $($rest:tt)*) => {};
 
     (emit $unsupported:tt $($rest:tt)*) => {$($rest)*};
 }
@@ -1385,10 +1640,22 @@ where
 
     fn visit_call(&mut self, index: u32) {
         let callee = self.env.callee_from_index(FuncIndex::from_u32(index));
+        let sig = self.env.callee_sig::<M::ABI>(&callee).map(|_| ());
+        if self.record_error(sig).is_none() {
+            return;
+        }
         FnCall::emit::<M>(&mut self.env, self.masm, &mut self.context, callee)
     }
 
     fn visit_call_indirect(&mut self, type_index: u32, table_index: u32) {
+        let type_index = TypeIndex::from_u32(type_index);
+        let table_index = TableIndex::from_u32(table_index);
+        let callee = self.env.funcref(type_index);
+        let sig = self.env.callee_sig::<M::ABI>(&callee).map(|_| ());
+        if self.record_error(sig).is_none() {
+            return;
+        }
+
         // Spill now because `emit_lazy_init_funcref` and the `FnCall::emit`
         // invocations will both trigger spills since they both call functions.
         // However, the machine instructions for the spill emitted by
@@ -1397,9 +1664,6 @@ where
         // unbalanced.
         self.context.spill(self.masm);
 
-        let type_index = TypeIndex::from_u32(type_index);
-        let table_index = TableIndex::from_u32(table_index);
-
         self.emit_lazy_init_funcref(table_index);
 
         // Perform the indirect call.
@@ -1410,7 +1674,6 @@ where
             .trapz(funcref_ptr.into(), TRAP_INDIRECT_CALL_TO_NULL);
         self.emit_typecheck_funcref(funcref_ptr.into(), type_index);
 
-        let callee = self.env.funcref(type_index);
         FnCall::emit::<M>(&mut self.env, self.masm, &mut self.context, callee)
     }
 
@@ -1681,11 +1944,11 @@ where
     fn visit_nop(&mut self) {}
 
     fn visit_if(&mut self, blockty: BlockType) {
-        self.control_frames.push(ControlStackFrame::r#if(
-            self.env.resolve_block_sig(blockty),
-            self.masm,
-            &mut self.context,
-        ));
+        let Some(sig) = self.record_error(self.env.resolve_block_sig::<M::ABI>(blockty)) else {
+            return;
+        };
+        self.control_frames
+            .push(ControlStackFrame::r#if(sig, self.masm, &mut self.context));
     }
 
     fn visit_else(&mut self) {
@@ -1701,24 +1964,28 @@ where
     }
 
     fn visit_block(&mut self, blockty: BlockType) {
-        self.control_frames.push(ControlStackFrame::block(
-            self.env.resolve_block_sig(blockty),
-            self.masm,
-            &mut self.context,
-        ));
+        let Some(sig) = self.record_error(self.env.resolve_block_sig::<M::ABI>(blockty)) else {
+            return;
+        };
+        self.control_frames
+            .push(ControlStackFrame::block(sig, self.masm, &mut self.context));
     }
 
     fn visit_loop(&mut self, blockty: BlockType) {
-        self.control_frames.push(ControlStackFrame::r#loop(
-            self.env.resolve_block_sig(blockty),
-            self.masm,
-            &mut self.context,
-        ));
+        let Some(sig) = self.record_error(self.env.resolve_block_sig::<M::ABI>(blockty)) else {
+            return;
+        };
+        self.control_frames
+            .push(ControlStackFrame::r#loop(sig, self.masm, &mut self.context));
 
         // Emit fuel check right after binding the loop header.
         if self.tunables.consume_fuel {
             self.emit_fuel_check();
         }
+
+        if self.tunables.tiered_compilation {
+            self.emit_tier_up_check();
+        }
     }
 
     fn visit_br(&mut self, depth: u32) {
@@ -1738,7 +2005,7 @@ where
 
         let top = {
             let top = self.context.without::<TypedReg, M, _>(
-                frame.results::<M>().regs(),
+                frame.results().regs(),
                 self.masm,
                 |ctx, masm| ctx.pop_to_reg(masm, None),
             );
@@ -1771,7 +2038,7 @@ where
         // Emit instructions to balance the machine stack if the frame has
         // a different offset.
         let current_sp_offset = self.masm.sp_offset();
-        let results_size = frame.results::<M>().size();
+        let results_size = frame.results().size();
         let state = frame.stack_state();
         let (label, cmp, needs_cleanup) = if current_sp_offset > state.target_offset {
             (self.masm.get_label(), IntCmpKind::Eq, true)
@@ -1812,7 +2079,7 @@ where
 
         let default_index = control_index(targets.default(), self.control_frames.len());
         let default_frame = &mut self.control_frames[default_index];
-        let default_result = default_frame.results::<M>();
+        let default_result = default_frame.results();
 
         let (index, tmp) = {
             let index_and_tmp = self.context.without::<(TypedReg, _), M, _>(
@@ -2219,27 +2486,1791 @@ where
         self.masm.mul_wide(&mut self.context, MulWideKind::Unsigned);
     }
 
-    wasmparser::for_each_operator!(def_unsupported);
-}
+    fn visit_v128_load8x8_s(&mut self, memarg: MemArg) {
+        self.emit_v128_load_extend(&memarg, V128ExtendKind::LowI8x16S)
+    }
 
-impl<'a, 'translation, 'data, M> CodeGen<'a, 'translation, 'data, M>
-where
-    M: MacroAssembler,
-{
-    fn cmp_i32s(&mut self, kind: IntCmpKind) {
-        self.context.i32_binop(self.masm, |masm, dst, src, size| {
-            masm.cmp_with_set(writable!(dst), src, kind, size);
-            TypedReg::i32(dst)
-        });
+    fn visit_v128_load8x8_u(&mut self, memarg: MemArg) {
+        self.emit_v128_load_extend(&memarg, V128ExtendKind::LowI8x16U)
     }
 
-    fn cmp_i64s(&mut self, kind: IntCmpKind) {
+    fn visit_v128_load16x4_s(&mut self, memarg: MemArg) {
+        self.emit_v128_load_extend(&memarg, V128ExtendKind::LowI16x8S)
+    }
+
+    fn visit_v128_load16x4_u(&mut self, memarg: MemArg) {
+        self.emit_v128_load_extend(&memarg, V128ExtendKind::LowI16x8U)
+    }
+
+    fn visit_v128_load32x2_s(&mut self, memarg: MemArg) {
+        self.emit_v128_load_extend(&memarg, V128ExtendKind::LowI32x4S)
+    }
+
+    fn visit_v128_load32x2_u(&mut self, memarg: MemArg) {
+        self.emit_v128_load_extend(&memarg, V128ExtendKind::LowI32x4U)
+    }
+
+    fn visit_v128_load8_splat(&mut self, memarg: MemArg) {
+        self.emit_v128_load_splat(&memarg, VectorShape::I8x16)
+    }
+
+    fn visit_v128_load16_splat(&mut self, memarg: MemArg) {
+        self.emit_v128_load_splat(&memarg, VectorShape::I16x8)
+    }
+
+    fn visit_v128_load32_splat(&mut self, memarg: MemArg) {
+        self.emit_v128_load_splat(&memarg, VectorShape::I32x4)
+    }
+
+    fn visit_v128_load64_splat(&mut self, memarg: MemArg) {
+        self.emit_v128_load_splat(&memarg, VectorShape::I64x2)
+    }
+
+    fn visit_v128_load32_zero(&mut self, memarg: MemArg) {
+        self.emit_wasm_load(&memarg, WasmValType::V128, OperandSize::S32, None)
+    }
+
+    fn visit_v128_load64_zero(&mut self, memarg: MemArg) {
+        self.emit_wasm_load(&memarg, WasmValType::V128, OperandSize::S64, None)
+    }
+
+    fn visit_v128_load8_lane(&mut self, memarg: MemArg, lane: u8) {
+        self.emit_v128_load_lane(&memarg, lane, VectorShape::I8x16)
+    }
+
+    fn visit_v128_load16_lane(&mut self, memarg: MemArg, lane: u8) {
+        self.emit_v128_load_lane(&memarg, lane, VectorShape::I16x8)
+    }
+
+    fn visit_v128_load32_lane(&mut self, memarg: MemArg, lane: u8) {
+        self.emit_v128_load_lane(&memarg, lane, VectorShape::I32x4)
+    }
+
+    fn visit_v128_load64_lane(&mut self, memarg: MemArg, lane: u8) {
+        self.emit_v128_load_lane(&memarg, lane, VectorShape::I64x2)
+    }
+
+    fn visit_v128_store8_lane(&mut self, memarg: MemArg, lane: u8) {
+        self.emit_v128_store_lane(&memarg, lane, ExtractLaneKind::I8x16U)
+    }
+
+    fn visit_v128_store16_lane(&mut self, memarg: MemArg, lane: u8) {
+        self.emit_v128_store_lane(&memarg, lane, ExtractLaneKind::I16x8U)
+    }
+
+    fn visit_v128_store32_lane(&mut self, memarg: MemArg, lane: u8) {
+        self.emit_v128_store_lane(&memarg, lane, ExtractLaneKind::I32x4)
+    }
+
+    fn visit_v128_store64_lane(&mut self, memarg: MemArg, lane: u8) {
+        self.emit_v128_store_lane(&memarg, lane, ExtractLaneKind::I64x2)
+    }
+
+    fn visit_i8x16_shuffle(&mut self, lanes: [u8; 16]) {
+        self.v128_binop(|masm, dst, src| masm.v128_shuffle(writable!(dst), dst, src, lanes));
+    }
+
+    fn visit_i8x16_swizzle(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_swizzle(writable!(dst), dst, src));
+    }
+
+    fn visit_i8x16_splat(&mut self) {
         self.context
-            .i64_binop(self.masm, move |masm, dst, src, size| {
-                masm.cmp_with_set(writable!(dst), src, kind, size);
-                TypedReg::i32(dst) // Return value for comparisons is an `i32`.
+            .convert_op(self.masm, WasmValType::V128, |masm, dst, src, _| {
+                masm.v128_splat(writable!(dst), src, VectorShape::I8x16);
+            });
+    }
+
+    fn visit_i16x8_splat(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::V128, |masm, dst, src, _| {
+                masm.v128_splat(writable!(dst), src, VectorShape::I16x8);
+            });
+    }
+
+    fn visit_i32x4_splat(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::V128, |masm, dst, src, _| {
+                masm.v128_splat(writable!(dst), src, VectorShape::I32x4);
+            });
+    }
+
+    fn visit_i64x2_splat(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::V128, |masm, dst, src, _| {
+                masm.v128_splat(writable!(dst), src, VectorShape::I64x2);
+            });
+    }
+
+    fn visit_f32x4_splat(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::V128, |masm, dst, src, _| {
+                masm.v128_splat(writable!(dst), src, VectorShape::F32x4);
+            });
+    }
+
+    fn visit_f64x2_splat(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::V128, |masm, dst, src, _| {
+                masm.v128_splat(writable!(dst), src, VectorShape::F64x2);
+            });
+    }
+
+    fn visit_i8x16_extract_lane_s(&mut self, lane: u8) {
+        self.emit_v128_extract_lane(lane, ExtractLaneKind::I8x16S, WasmValType::I32)
+    }
+
+    fn visit_i8x16_extract_lane_u(&mut self, lane: u8) {
+        self.emit_v128_extract_lane(lane, ExtractLaneKind::I8x16U, WasmValType::I32)
+    }
+
+    fn visit_i16x8_extract_lane_s(&mut self, lane: u8) {
+        self.emit_v128_extract_lane(lane, ExtractLaneKind::I16x8S, WasmValType::I32)
+    }
+
+    fn visit_i16x8_extract_lane_u(&mut self, lane: u8) {
+        self.emit_v128_extract_lane(lane, ExtractLaneKind::I16x8U, WasmValType::I32)
+    }
+
+    fn visit_i32x4_extract_lane(&mut self, lane: u8) {
+        self.emit_v128_extract_lane(lane, ExtractLaneKind::I32x4, WasmValType::I32)
+    }
+
+    fn visit_i64x2_extract_lane(&mut self, lane: u8) {
+        self.emit_v128_extract_lane(lane, ExtractLaneKind::I64x2, WasmValType::I64)
+    }
+
+    fn visit_f32x4_extract_lane(&mut self, lane: u8) {
+        self.emit_v128_extract_lane(lane, ExtractLaneKind::F32x4, WasmValType::F32)
+    }
+
+    fn visit_f64x2_extract_lane(&mut self, lane: u8) {
+        self.emit_v128_extract_lane(lane, ExtractLaneKind::F64x2, WasmValType::F64)
+    }
+
+    fn visit_i8x16_replace_lane(&mut self, lane: u8) {
+        self.context
+            .binop(self.masm, OperandSize::S128, |masm, dst, src, _| {
+                masm.v128_replace_lane(writable!(dst), src, lane, VectorShape::I8x16);
+                TypedReg::v128(dst)
+            });
+    }
+
+    fn visit_i16x8_replace_lane(&mut self, lane: u8) {
+        self.context
+            .binop(self.masm, OperandSize::S128, |masm, dst, src, _| {
+                masm.v128_replace_lane(writable!(dst), src, lane, VectorShape::I16x8);
+                TypedReg::v128(dst)
+            });
+    }
+
+    fn visit_i32x4_replace_lane(&mut self, lane: u8) {
+        self.context
+            .binop(self.masm, OperandSize::S128, |masm, dst, src, _| {
+                masm.v128_replace_lane(writable!(dst), src, lane, VectorShape::I32x4);
+                TypedReg::v128(dst)
+            });
+    }
+
+    fn visit_i64x2_replace_lane(&mut self, lane: u8) {
+        self.context
+            .binop(self.masm, OperandSize::S128, |masm, dst, src, _| {
+                masm.v128_replace_lane(writable!(dst), src, lane, VectorShape::I64x2);
+                TypedReg::v128(dst)
+            });
+    }
+
+    fn visit_f32x4_replace_lane(&mut self, lane: u8) {
+        self.context
+            .binop(self.masm, OperandSize::S128, |masm, dst, src, _| {
+                masm.v128_replace_lane(writable!(dst), src, lane, VectorShape::F32x4);
+                TypedReg::v128(dst)
+            });
+    }
+
+    fn visit_f64x2_replace_lane(&mut self, lane: u8) {
+        self.context
+            .binop(self.masm, OperandSize::S128, |masm, dst, src, _| {
+                masm.v128_replace_lane(writable!(dst), src, lane, VectorShape::F64x2);
+                TypedReg::v128(dst)
             });
     }
+
+    fn visit_i8x16_eq(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(writable!(dst), dst, src, IntCmpKind::Eq, VectorShape::I8x16)
+        });
+    }
+
+    fn visit_i8x16_ne(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(writable!(dst), dst, src, IntCmpKind::Ne, VectorShape::I8x16)
+        });
+    }
+
+    fn visit_i8x16_lt_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LtS,
+                VectorShape::I8x16,
+            )
+        });
+    }
+
+    fn visit_i8x16_lt_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LtU,
+                VectorShape::I8x16,
+            )
+        });
+    }
+
+    fn visit_i8x16_gt_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GtS,
+                VectorShape::I8x16,
+            )
+        });
+    }
+
+    fn visit_i8x16_gt_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GtU,
+                VectorShape::I8x16,
+            )
+        });
+    }
+
+    fn visit_i8x16_le_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LeS,
+                VectorShape::I8x16,
+            )
+        });
+    }
+
+    fn visit_i8x16_le_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LeU,
+                VectorShape::I8x16,
+            )
+        });
+    }
+
+    fn visit_i8x16_ge_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GeS,
+                VectorShape::I8x16,
+            )
+        });
+    }
+
+    fn visit_i8x16_ge_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GeU,
+                VectorShape::I8x16,
+            )
+        });
+    }
+
+    fn visit_i16x8_eq(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(writable!(dst), dst, src, IntCmpKind::Eq, VectorShape::I16x8)
+        });
+    }
+
+    fn visit_i16x8_ne(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(writable!(dst), dst, src, IntCmpKind::Ne, VectorShape::I16x8)
+        });
+    }
+
+    fn visit_i16x8_lt_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LtS,
+                VectorShape::I16x8,
+            )
+        });
+    }
+
+    fn visit_i16x8_lt_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LtU,
+                VectorShape::I16x8,
+            )
+        });
+    }
+
+    fn visit_i16x8_gt_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GtS,
+                VectorShape::I16x8,
+            )
+        });
+    }
+
+    fn visit_i16x8_gt_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GtU,
+                VectorShape::I16x8,
+            )
+        });
+    }
+
+    fn visit_i16x8_le_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LeS,
+                VectorShape::I16x8,
+            )
+        });
+    }
+
+    fn visit_i16x8_le_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LeU,
+                VectorShape::I16x8,
+            )
+        });
+    }
+
+    fn visit_i16x8_ge_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GeS,
+                VectorShape::I16x8,
+            )
+        });
+    }
+
+    fn visit_i16x8_ge_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GeU,
+                VectorShape::I16x8,
+            )
+        });
+    }
+
+    fn visit_i32x4_eq(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(writable!(dst), dst, src, IntCmpKind::Eq, VectorShape::I32x4)
+        });
+    }
+
+    fn visit_i32x4_ne(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(writable!(dst), dst, src, IntCmpKind::Ne, VectorShape::I32x4)
+        });
+    }
+
+    fn visit_i32x4_lt_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LtS,
+                VectorShape::I32x4,
+            )
+        });
+    }
+
+    fn visit_i32x4_lt_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LtU,
+                VectorShape::I32x4,
+            )
+        });
+    }
+
+    fn visit_i32x4_gt_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GtS,
+                VectorShape::I32x4,
+            )
+        });
+    }
+
+    fn visit_i32x4_gt_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GtU,
+                VectorShape::I32x4,
+            )
+        });
+    }
+
+    fn visit_i32x4_le_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LeS,
+                VectorShape::I32x4,
+            )
+        });
+    }
+
+    fn visit_i32x4_le_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LeU,
+                VectorShape::I32x4,
+            )
+        });
+    }
+
+    fn visit_i32x4_ge_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GeS,
+                VectorShape::I32x4,
+            )
+        });
+    }
+
+    fn visit_i32x4_ge_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GeU,
+                VectorShape::I32x4,
+            )
+        });
+    }
+
+    fn visit_i64x2_eq(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(writable!(dst), dst, src, IntCmpKind::Eq, VectorShape::I64x2)
+        });
+    }
+
+    fn visit_i64x2_ne(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(writable!(dst), dst, src, IntCmpKind::Ne, VectorShape::I64x2)
+        });
+    }
+
+    fn visit_i64x2_lt_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LtS,
+                VectorShape::I64x2,
+            )
+        });
+    }
+
+    fn visit_i64x2_gt_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GtS,
+                VectorShape::I64x2,
+            )
+        });
+    }
+
+    fn visit_i64x2_le_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::LeS,
+                VectorShape::I64x2,
+            )
+        });
+    }
+
+    fn visit_i64x2_ge_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_int_cmp(
+                writable!(dst),
+                dst,
+                src,
+                IntCmpKind::GeS,
+                VectorShape::I64x2,
+            )
+        });
+    }
+
+    fn visit_f32x4_eq(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Eq,
+                VectorShape::F32x4,
+            )
+        });
+    }
+
+    fn visit_f32x4_ne(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Ne,
+                VectorShape::F32x4,
+            )
+        });
+    }
+
+    fn visit_f32x4_lt(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Lt,
+                VectorShape::F32x4,
+            )
+        });
+    }
+
+    fn visit_f32x4_gt(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Gt,
+                VectorShape::F32x4,
+            )
+        });
+    }
+
+    fn visit_f32x4_le(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Le,
+                VectorShape::F32x4,
+            )
+        });
+    }
+
+    fn visit_f32x4_ge(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Ge,
+                VectorShape::F32x4,
+            )
+        });
+    }
+
+    fn visit_f64x2_eq(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Eq,
+                VectorShape::F64x2,
+            )
+        });
+    }
+
+    fn visit_f64x2_ne(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Ne,
+                VectorShape::F64x2,
+            )
+        });
+    }
+
+    fn visit_f64x2_lt(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Lt,
+                VectorShape::F64x2,
+            )
+        });
+    }
+
+    fn visit_f64x2_gt(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Gt,
+                VectorShape::F64x2,
+            )
+        });
+    }
+
+    fn visit_f64x2_le(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Le,
+                VectorShape::F64x2,
+            )
+        });
+    }
+
+    fn visit_f64x2_ge(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_float_cmp(
+                writable!(dst),
+                dst,
+                src,
+                FloatCmpKind::Ge,
+                VectorShape::F64x2,
+            )
+        });
+    }
+
+    fn visit_v128_not(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_not(writable!(reg)));
+    }
+
+    fn visit_v128_and(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_and(writable!(dst), dst, src));
+    }
+
+    fn visit_v128_andnot(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_and_not(writable!(dst), dst, src));
+    }
+
+    fn visit_v128_or(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_or(writable!(dst), dst, src));
+    }
+
+    fn visit_v128_xor(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_xor(writable!(dst), dst, src));
+    }
+
+    fn visit_v128_bitselect(&mut self) {
+        self.v128_ternop(|masm, a, b, c| masm.v128_bitselect(writable!(a), a, b, c));
+    }
+
+    fn visit_v128_any_true(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_any_true(writable!(dst), src);
+            });
+    }
+
+    fn visit_i8x16_abs(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_abs(writable!(reg), VectorShape::I8x16));
+    }
+
+    fn visit_i8x16_neg(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_neg(writable!(reg), VectorShape::I8x16));
+    }
+
+    fn visit_i8x16_popcnt(&mut self) {
+        self.masm.v128_popcnt(&mut self.context);
+    }
+
+    fn visit_i8x16_all_true(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_all_true(writable!(dst), src, VectorShape::I8x16);
+            });
+    }
+
+    fn visit_i8x16_bitmask(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_bitmask(writable!(dst), src, VectorShape::I8x16);
+            });
+    }
+
+    fn visit_i8x16_narrow_i16x8_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_narrow(writable!(dst), dst, src, V128NarrowKind::I16x8S)
+        });
+    }
+
+    fn visit_i8x16_narrow_i16x8_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_narrow(writable!(dst), dst, src, V128NarrowKind::I16x8U)
+        });
+    }
+
+    fn visit_i8x16_shl(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I8x16, ShiftKind::Shl);
+    }
+
+    fn visit_i8x16_shr_s(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I8x16, ShiftKind::ShrS);
+    }
+
+    fn visit_i8x16_shr_u(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I8x16, ShiftKind::ShrU);
+    }
+
+    fn visit_i8x16_add(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::I8x16)
+        });
+    }
+
+    fn visit_i8x16_add_sat_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::I8x16SatS)
+        });
+    }
+
+    fn visit_i8x16_add_sat_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::I8x16SatU)
+        });
+    }
+
+    fn visit_i8x16_sub(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::I8x16)
+        });
+    }
+
+    fn visit_i8x16_sub_sat_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::I8x16SatS)
+        });
+    }
+
+    fn visit_i8x16_sub_sat_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::I8x16SatU)
+        });
+    }
+
+    fn visit_i8x16_min_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::I8x16S)
+        });
+    }
+
+    fn visit_i8x16_min_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::I8x16U)
+        });
+    }
+
+    fn visit_i8x16_max_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::I8x16S)
+        });
+    }
+
+    fn visit_i8x16_max_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::I8x16U)
+        });
+    }
+
+    fn visit_i8x16_avgr_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_avgr(writable!(dst), dst, src, VectorShape::I8x16)
+        });
+    }
+
+    fn visit_i16x8_abs(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_abs(writable!(reg), VectorShape::I16x8));
+    }
+
+    fn visit_i16x8_neg(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_neg(writable!(reg), VectorShape::I16x8));
+    }
+
+    fn visit_i16x8_q15mulr_sat_s(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_q15mulr_sat_s(writable!(dst), dst, src));
+    }
+
+    fn visit_i16x8_all_true(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_all_true(writable!(dst), src, VectorShape::I16x8);
+            });
+    }
+
+    fn visit_i16x8_bitmask(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_bitmask(writable!(dst), src, VectorShape::I16x8);
+            });
+    }
+
+    fn visit_i16x8_narrow_i32x4_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_narrow(writable!(dst), dst, src, V128NarrowKind::I32x4S)
+        });
+    }
+
+    fn visit_i16x8_narrow_i32x4_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_narrow(writable!(dst), dst, src, V128NarrowKind::I32x4U)
+        });
+    }
+
+    fn visit_i16x8_extend_low_i8x16_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::LowI8x16S)
+        });
+    }
+
+    fn visit_i16x8_extend_low_i8x16_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::LowI8x16U)
+        });
+    }
+
+    fn visit_i16x8_extend_high_i8x16_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::HighI8x16S)
+        });
+    }
+
+    fn visit_i16x8_extend_high_i8x16_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::HighI8x16U)
+        });
+    }
+
+    fn visit_i16x8_shl(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I16x8, ShiftKind::Shl);
+    }
+
+    fn visit_i16x8_shr_s(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I16x8, ShiftKind::ShrS);
+    }
+
+    fn visit_i16x8_shr_u(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I16x8, ShiftKind::ShrU);
+    }
+
+    fn visit_i16x8_add(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::I16x8)
+        });
+    }
+
+    fn visit_i16x8_add_sat_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::I16x8SatS)
+        });
+    }
+
+    fn visit_i16x8_add_sat_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::I16x8SatU)
+        });
+    }
+
+    fn visit_i16x8_sub(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::I16x8)
+        });
+    }
+
+    fn visit_i16x8_sub_sat_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::I16x8SatS)
+        });
+    }
+
+    fn visit_i16x8_sub_sat_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::I16x8SatU)
+        });
+    }
+
+    fn visit_i16x8_mul(&mut self) {
+        self.masm.v128_mul(&mut self.context, VectorShape::I16x8);
+    }
+
+    fn visit_i16x8_min_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::I16x8S)
+        });
+    }
+
+    fn visit_i16x8_min_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::I16x8U)
+        });
+    }
+
+    fn visit_i16x8_max_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::I16x8S)
+        });
+    }
+
+    fn visit_i16x8_max_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::I16x8U)
+        });
+    }
+
+    fn visit_i16x8_avgr_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_avgr(writable!(dst), dst, src, VectorShape::I16x8)
+        });
+    }
+
+    fn visit_i16x8_extmul_low_i8x16_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::LowI8x16S)
+        });
+    }
+
+    fn visit_i16x8_extmul_low_i8x16_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::LowI8x16U)
+        });
+    }
+
+    fn visit_i16x8_extmul_high_i8x16_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::HighI8x16S)
+        });
+    }
+
+    fn visit_i16x8_extmul_high_i8x16_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::HighI8x16U)
+        });
+    }
+
+    fn visit_i16x8_extadd_pairwise_i8x16_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extadd_pairwise(writable!(reg), V128ExtAddKind::I8x16S)
+        });
+    }
+
+    fn visit_i16x8_extadd_pairwise_i8x16_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extadd_pairwise(writable!(reg), V128ExtAddKind::I8x16U)
+        });
+    }
+
+    fn visit_i32x4_abs(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_abs(writable!(reg), VectorShape::I32x4));
+    }
+
+    fn visit_i32x4_neg(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_neg(writable!(reg), VectorShape::I32x4));
+    }
+
+    fn visit_i32x4_all_true(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_all_true(writable!(dst), src, VectorShape::I32x4);
+            });
+    }
+
+    fn visit_i32x4_bitmask(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_bitmask(writable!(dst), src, VectorShape::I32x4);
+            });
+    }
+
+    fn visit_i32x4_extend_low_i16x8_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::LowI16x8S)
+        });
+    }
+
+    fn visit_i32x4_extend_low_i16x8_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::LowI16x8U)
+        });
+    }
+
+    fn visit_i32x4_extend_high_i16x8_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::HighI16x8S)
+        });
+    }
+
+    fn visit_i32x4_extend_high_i16x8_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::HighI16x8U)
+        });
+    }
+
+    fn visit_i32x4_shl(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I32x4, ShiftKind::Shl);
+    }
+
+    fn visit_i32x4_shr_s(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I32x4, ShiftKind::ShrS);
+    }
+
+    fn visit_i32x4_shr_u(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I32x4, ShiftKind::ShrU);
+    }
+
+    fn visit_i32x4_add(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::I32x4)
+        });
+    }
+
+    fn visit_i32x4_sub(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::I32x4)
+        });
+    }
+
+    fn visit_i32x4_mul(&mut self) {
+        self.masm.v128_mul(&mut self.context, VectorShape::I32x4);
+    }
+
+    fn visit_i32x4_min_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::I32x4S)
+        });
+    }
+
+    fn visit_i32x4_min_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::I32x4U)
+        });
+    }
+
+    fn visit_i32x4_max_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::I32x4S)
+        });
+    }
+
+    fn visit_i32x4_max_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::I32x4U)
+        });
+    }
+
+    fn visit_i32x4_dot_i16x8_s(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_dot(writable!(dst), dst, src));
+    }
+
+    fn visit_i32x4_extmul_low_i16x8_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::LowI16x8S)
+        });
+    }
+
+    fn visit_i32x4_extmul_low_i16x8_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::LowI16x8U)
+        });
+    }
+
+    fn visit_i32x4_extmul_high_i16x8_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::HighI16x8S)
+        });
+    }
+
+    fn visit_i32x4_extmul_high_i16x8_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::HighI16x8U)
+        });
+    }
+
+    fn visit_i32x4_extadd_pairwise_i16x8_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extadd_pairwise(writable!(reg), V128ExtAddKind::I16x8S)
+        });
+    }
+
+    fn visit_i32x4_extadd_pairwise_i16x8_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extadd_pairwise(writable!(reg), V128ExtAddKind::I16x8U)
+        });
+    }
+
+    fn visit_i64x2_abs(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_abs(writable!(reg), VectorShape::I64x2));
+    }
+
+    fn visit_i64x2_neg(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_neg(writable!(reg), VectorShape::I64x2));
+    }
+
+    fn visit_i64x2_all_true(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_all_true(writable!(dst), src, VectorShape::I64x2);
+            });
+    }
+
+    fn visit_i64x2_bitmask(&mut self) {
+        self.context
+            .convert_op(self.masm, WasmValType::I32, |masm, dst, src, _| {
+                masm.v128_bitmask(writable!(dst), src, VectorShape::I64x2);
+            });
+    }
+
+    fn visit_i64x2_extend_low_i32x4_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::LowI32x4S)
+        });
+    }
+
+    fn visit_i64x2_extend_low_i32x4_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::LowI32x4U)
+        });
+    }
+
+    fn visit_i64x2_extend_high_i32x4_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::HighI32x4S)
+        });
+    }
+
+    fn visit_i64x2_extend_high_i32x4_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_extend(writable!(reg), reg, V128ExtendKind::HighI32x4U)
+        });
+    }
+
+    fn visit_i64x2_shl(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I64x2, ShiftKind::Shl);
+    }
+
+    fn visit_i64x2_shr_s(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I64x2, ShiftKind::ShrS);
+    }
+
+    fn visit_i64x2_shr_u(&mut self) {
+        self.masm
+            .v128_shift(&mut self.context, VectorShape::I64x2, ShiftKind::ShrU);
+    }
+
+    fn visit_i64x2_add(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::I64x2)
+        });
+    }
+
+    fn visit_i64x2_sub(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::I64x2)
+        });
+    }
+
+    fn visit_i64x2_mul(&mut self) {
+        self.masm.v128_mul(&mut self.context, VectorShape::I64x2);
+    }
+
+    fn visit_i64x2_extmul_low_i32x4_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::LowI32x4S)
+        });
+    }
+
+    fn visit_i64x2_extmul_low_i32x4_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::LowI32x4U)
+        });
+    }
+
+    fn visit_i64x2_extmul_high_i32x4_s(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::HighI32x4S)
+        });
+    }
+
+    fn visit_i64x2_extmul_high_i32x4_u(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_extmul(writable!(dst), dst, src, V128ExtendKind::HighI32x4U)
+        });
+    }
+
+    fn visit_f32x4_ceil(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_round(writable!(reg), reg, RoundingMode::Up, VectorShape::F32x4)
+        });
+    }
+
+    fn visit_f32x4_floor(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_round(writable!(reg), reg, RoundingMode::Down, VectorShape::F32x4)
+        });
+    }
+
+    fn visit_f32x4_trunc(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_round(writable!(reg), reg, RoundingMode::Zero, VectorShape::F32x4)
+        });
+    }
+
+    fn visit_f32x4_nearest(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_round(
+                writable!(reg),
+                reg,
+                RoundingMode::Nearest,
+                VectorShape::F32x4,
+            )
+        });
+    }
+
+    fn visit_f32x4_abs(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_abs(writable!(reg), VectorShape::F32x4));
+    }
+
+    fn visit_f32x4_neg(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_neg(writable!(reg), VectorShape::F32x4));
+    }
+
+    fn visit_f32x4_sqrt(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_sqrt(writable!(reg), reg, VectorShape::F32x4));
+    }
+
+    fn visit_f32x4_add(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::F32x4)
+        });
+    }
+
+    fn visit_f32x4_sub(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::F32x4)
+        });
+    }
+
+    fn visit_f32x4_mul(&mut self) {
+        self.masm.v128_mul(&mut self.context, VectorShape::F32x4);
+    }
+
+    fn visit_f32x4_div(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_div(writable!(dst), dst, src, VectorShape::F32x4)
+        });
+    }
+
+    fn visit_f32x4_min(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::F32x4)
+        });
+    }
+
+    fn visit_f32x4_max(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::F32x4)
+        });
+    }
+
+    fn visit_f32x4_pmin(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_pmin(writable!(dst), dst, src, VectorShape::F32x4)
+        });
+    }
+
+    fn visit_f32x4_pmax(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_pmax(writable!(dst), dst, src, VectorShape::F32x4)
+        });
+    }
+
+    fn visit_f64x2_ceil(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_round(writable!(reg), reg, RoundingMode::Up, VectorShape::F64x2)
+        });
+    }
+
+    fn visit_f64x2_floor(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_round(writable!(reg), reg, RoundingMode::Down, VectorShape::F64x2)
+        });
+    }
+
+    fn visit_f64x2_trunc(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_round(writable!(reg), reg, RoundingMode::Zero, VectorShape::F64x2)
+        });
+    }
+
+    fn visit_f64x2_nearest(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_round(
+                writable!(reg),
+                reg,
+                RoundingMode::Nearest,
+                VectorShape::F64x2,
+            )
+        });
+    }
+
+    fn visit_f64x2_abs(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_abs(writable!(reg), VectorShape::F64x2));
+    }
+
+    fn visit_f64x2_neg(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_neg(writable!(reg), VectorShape::F64x2));
+    }
+
+    fn visit_f64x2_sqrt(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_sqrt(writable!(reg), reg, VectorShape::F64x2));
+    }
+
+    fn visit_f64x2_add(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_add(writable!(dst), dst, src, V128ArithKind::F64x2)
+        });
+    }
+
+    fn visit_f64x2_sub(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_sub(writable!(dst), dst, src, V128ArithKind::F64x2)
+        });
+    }
+
+    fn visit_f64x2_mul(&mut self) {
+        self.masm.v128_mul(&mut self.context, VectorShape::F64x2);
+    }
+
+    fn visit_f64x2_div(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_div(writable!(dst), dst, src, VectorShape::F64x2)
+        });
+    }
+
+    fn visit_f64x2_min(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::F64x2)
+        });
+    }
+
+    fn visit_f64x2_max(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::F64x2)
+        });
+    }
+
+    fn visit_f64x2_pmin(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_pmin(writable!(dst), dst, src, VectorShape::F64x2)
+        });
+    }
+
+    fn visit_f64x2_pmax(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_pmax(writable!(dst), dst, src, VectorShape::F64x2)
+        });
+    }
+
+    fn visit_i32x4_trunc_sat_f32x4_s(&mut self) {
+        self.masm
+            .v128_trunc_sat(&mut self.context, V128TruncKind::F32x4S);
+    }
+
+    fn visit_i32x4_trunc_sat_f32x4_u(&mut self) {
+        self.masm
+            .v128_trunc_sat(&mut self.context, V128TruncKind::F32x4U);
+    }
+
+    fn visit_i32x4_trunc_sat_f64x2_s_zero(&mut self) {
+        self.masm
+            .v128_trunc_sat(&mut self.context, V128TruncKind::F64x2SZero);
+    }
+
+    fn visit_i32x4_trunc_sat_f64x2_u_zero(&mut self) {
+        self.masm
+            .v128_trunc_sat(&mut self.context, V128TruncKind::F64x2UZero);
+    }
+
+    fn visit_f32x4_convert_i32x4_s(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_convert(writable!(reg), reg, V128ConvertKind::I32x4S));
+    }
+
+    fn visit_f32x4_convert_i32x4_u(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_convert(writable!(reg), reg, V128ConvertKind::I32x4U));
+    }
+
+    fn visit_f64x2_convert_low_i32x4_s(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_convert(writable!(reg), reg, V128ConvertKind::I32x4LowS)
+        });
+    }
+
+    fn visit_f64x2_convert_low_i32x4_u(&mut self) {
+        self.v128_unop(|masm, reg| {
+            masm.v128_convert(writable!(reg), reg, V128ConvertKind::I32x4LowU)
+        });
+    }
+
+    fn visit_f32x4_demote_f64x2_zero(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_demote(writable!(reg), reg));
+    }
+
+    fn visit_f64x2_promote_low_f32x4(&mut self) {
+        self.v128_unop(|masm, reg| masm.v128_promote(writable!(reg), reg));
+    }
+
+    fn visit_i8x16_relaxed_swizzle(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_swizzle(writable!(dst), dst, src));
+    }
+
+    fn visit_i32x4_relaxed_trunc_f32x4_s(&mut self) {
+        self.masm
+            .v128_trunc_sat(&mut self.context, V128TruncKind::F32x4S);
+    }
+
+    fn visit_i32x4_relaxed_trunc_f32x4_u(&mut self) {
+        self.masm
+            .v128_trunc_sat(&mut self.context, V128TruncKind::F32x4U);
+    }
+
+    fn visit_i32x4_relaxed_trunc_f64x2_s_zero(&mut self) {
+        self.masm
+            .v128_trunc_sat(&mut self.context, V128TruncKind::F64x2SZero);
+    }
+
+    fn visit_i32x4_relaxed_trunc_f64x2_u_zero(&mut self) {
+        self.masm
+            .v128_trunc_sat(&mut self.context, V128TruncKind::F64x2UZero);
+    }
+
+    fn visit_f32x4_relaxed_madd(&mut self) {
+        self.emit_v128_relaxed_fma(|masm, a, b, c| {
+            masm.v128_madd(writable!(a), a, b, c, VectorShape::F32x4)
+        });
+    }
+
+    fn visit_f32x4_relaxed_nmadd(&mut self) {
+        self.emit_v128_relaxed_fma(|masm, a, b, c| {
+            masm.v128_nmadd(writable!(a), a, b, c, VectorShape::F32x4)
+        });
+    }
+
+    fn visit_f64x2_relaxed_madd(&mut self) {
+        self.emit_v128_relaxed_fma(|masm, a, b, c| {
+            masm.v128_madd(writable!(a), a, b, c, VectorShape::F64x2)
+        });
+    }
+
+    fn visit_f64x2_relaxed_nmadd(&mut self) {
+        self.emit_v128_relaxed_fma(|masm, a, b, c| {
+            masm.v128_nmadd(writable!(a), a, b, c, VectorShape::F64x2)
+        });
+    }
+
+    fn visit_i8x16_relaxed_laneselect(&mut self) {
+        self.v128_ternop(|masm, a, b, c| masm.v128_bitselect(writable!(a), a, b, c));
+    }
+
+    fn visit_i16x8_relaxed_laneselect(&mut self) {
+        self.v128_ternop(|masm, a, b, c| masm.v128_bitselect(writable!(a), a, b, c));
+    }
+
+    fn visit_i32x4_relaxed_laneselect(&mut self) {
+        self.v128_ternop(|masm, a, b, c| masm.v128_bitselect(writable!(a), a, b, c));
+    }
+
+    fn visit_i64x2_relaxed_laneselect(&mut self) {
+        self.v128_ternop(|masm, a, b, c| masm.v128_bitselect(writable!(a), a, b, c));
+    }
+
+    fn visit_f32x4_relaxed_min(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::F32x4)
+        });
+    }
+
+    fn visit_f32x4_relaxed_max(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::F32x4)
+        });
+    }
+
+    fn visit_f64x2_relaxed_min(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_min(writable!(dst), dst, src, V128MinMaxKind::F64x2)
+        });
+    }
+
+    fn visit_f64x2_relaxed_max(&mut self) {
+        self.v128_binop(|masm, dst, src| {
+            masm.v128_max(writable!(dst), dst, src, V128MinMaxKind::F64x2)
+        });
+    }
+
+    fn visit_i16x8_relaxed_q15mulr_s(&mut self) {
+        self.v128_binop(|masm, dst, src| masm.v128_q15mulr_sat_s(writable!(dst), dst, src));
+    }
+
+    fn visit_i16x8_relaxed_dot_i8x16_i7x16_s(&mut self) {
+        let deterministic = self.tunables.relaxed_simd_deterministic;
+        self.masm
+            .v128_dot_i8x16_i7x16_s(&mut self.context, deterministic);
+    }
+
+    fn visit_i32x4_relaxed_dot_i8x16_i7x16_add_s(&mut self) {
+        let deterministic = self.tunables.relaxed_simd_deterministic;
+        self.masm
+            .v128_dot_i8x16_i7x16_add_s(&mut self.context, deterministic);
+    }
+
+    wasmparser::for_each_operator!(def_unsupported);
+}
+
+impl<'a, 'translation, 'data, M> CodeGen<'a, 'translation, 'data, M>
+where
+    M: MacroAssembler,
+{
+    fn cmp_i32s(&mut self, kind: IntCmpKind) {
+        self.context.i32_binop(self.masm, |masm, dst, src, size| {
+            masm.cmp_with_set(writable!(dst), src, kind, size);
+            TypedReg::i32(dst)
+        });
+    }
+
+    fn cmp_i64s(&mut self, kind: IntCmpKind) {
+        self.context
+            .i64_binop(self.masm, move |masm, dst, src, size| {
+                masm.cmp_with_set(writable!(dst), src, kind, size);
+                TypedReg::i32(dst) // Return value for comparisons is an `i32`.
+            });
+    }
+
+    /// Emits a unary operation on the v128 at the top of the value stack,
+    /// replacing it in place.
+    fn v128_unop<F>(&mut self, mut emit: F)
+    where
+        F: FnMut(&mut M, Reg),
+    {
+        self.context
+            .unop(self.masm, OperandSize::S128, &mut |masm, reg, _| {
+                emit(masm, reg);
+                TypedReg::v128(reg)
+            });
+    }
+
+    /// Emits a binary operation on the two v128 values at the top of the
+    /// value stack, leaving the result in the register of the first operand.
+    fn v128_binop<F>(&mut self, mut emit: F)
+    where
+        F: FnMut(&mut M, Reg, Reg),
+    {
+        self.context
+            .binop(self.masm, OperandSize::S128, |masm, dst, src, _| {
+                emit(masm, dst, src);
+                TypedReg::v128(dst)
+            });
+    }
+
+    /// Emits a ternary operation on the three v128 values at the top of the
+    /// value stack, leaving the result in the register of the first operand.
+    fn v128_ternop<F>(&mut self, mut emit: F)
+    where
+        F: FnMut(&mut M, Reg, Reg, Reg),
+    {
+        self.context.ternop(self.masm, |masm, a, b, c| {
+            emit(masm, a, b, c);
+            TypedReg::v128(a)
+        });
+    }
+
+    /// Emits a relaxed fused multiply-add. The deterministic semantics of the
+    /// relaxed SIMD proposal require the result to be fused, which is only
+    /// supported when the target has native FMA instructions.
+    fn emit_v128_relaxed_fma<F>(&mut self, emit: F)
+    where
+        F: FnMut(&mut M, Reg, Reg, Reg),
+    {
+        if self.tunables.relaxed_simd_deterministic && !self.masm.has_native_fma() {
+            self.found_unsupported_instruction =
+                Some("deterministic relaxed fma without native fma support");
+            return;
+        }
+        self.v128_ternop(emit);
+    }
+
+    /// Extracts a lane of the v128 at the top of the value stack into a
+    /// scalar of type `ty`.
+    fn emit_v128_extract_lane(&mut self, lane: u8, kind: ExtractLaneKind, ty: WasmValType) {
+        self.context.convert_op(self.masm, ty, |masm, dst, src, _| {
+            masm.v128_extract_lane(writable!(dst), src, lane, kind);
+        });
+    }
+
+    /// Loads 64 bits from memory and widens each of their lanes into a v128.
+    fn emit_v128_load_extend(&mut self, memarg: &MemArg, kind: V128ExtendKind) {
+        self.emit_wasm_load(memarg, WasmValType::V128, OperandSize::S64, None);
+        // The load might have been statically determined to be out of
+        // bounds, in which case the rest of the code is unreachable.
+        if self.context.reachable {
+            self.v128_unop(|masm, reg| masm.v128_extend(writable!(reg), reg, kind));
+        }
+    }
+
+    /// Loads a single lane from memory and replicates it to every lane of a
+    /// v128.
+    fn emit_v128_load_splat(&mut self, memarg: &MemArg, shape: VectorShape) {
+        let size = shape.lane_size();
+        let ty = if size == OperandSize::S64 {
+            WasmValType::I64
+        } else {
+            WasmValType::I32
+        };
+        self.emit_wasm_load(memarg, ty, size, None);
+        if self.context.reachable {
+            self.context
+                .convert_op(self.masm, WasmValType::V128, |masm, dst, src, _| {
+                    masm.v128_splat(writable!(dst), src, shape);
+                });
+        }
+    }
+
+    /// Loads a single lane from memory into a lane of the v128 at the top
+    /// of the value stack.
+    fn emit_v128_load_lane(&mut self, memarg: &MemArg, lane: u8, shape: VectorShape) {
+        let vector = self.context.pop_to_reg(self.masm, None);
+        let size = shape.lane_size();
+        let ty = if size == OperandSize::S64 {
+            WasmValType::I64
+        } else {
+            WasmValType::I32
+        };
+        self.emit_wasm_load(memarg, ty, size, None);
+        if self.context.reachable {
+            let src = self.context.pop_to_reg(self.masm, None);
+            self.masm
+                .v128_replace_lane(writable!(vector.reg), src.reg, lane, shape);
+            self.context.free_reg(src);
+            self.context.stack.push(vector.into());
+        } else {
+            self.context.free_reg(vector);
+        }
+    }
+
+    /// Stores a single lane of the v128 at the top of the value stack to
+    /// memory.
+    fn emit_v128_store_lane(&mut self, memarg: &MemArg, lane: u8, kind: ExtractLaneKind) {
+        let (ty, size) = match kind {
+            ExtractLaneKind::I8x16U => (WasmValType::I32, OperandSize::S8),
+            ExtractLaneKind::I16x8U => (WasmValType::I32, OperandSize::S16),
+            ExtractLaneKind::I32x4 => (WasmValType::I32, OperandSize::S32),
+            ExtractLaneKind::I64x2 => (WasmValType::I64, OperandSize::S64),
+            _ => unreachable!(),
+        };
+        self.emit_v128_extract_lane(lane, kind, ty);
+        self.emit_wasm_store(memarg, size);
+    }
 }
 
 impl From<WasmValType> for OperandSize {